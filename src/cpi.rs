@@ -8,39 +8,25 @@ use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
     instruction::{AccountMeta, Instruction},
-    program::invoke_signed,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     pubkey::Pubkey,
 };
 
-use crate::instruction::{FundInstruction, RecordPnLArgs};
+use crate::instruction::{
+    AddADLProfitArgs, AddLiquidationIncomeArgs, AddTradingFeeArgs, CoverShortfallArgs, FundHookPayload,
+    FundInstruction, RecordFeeRebateArgs, RecordFundExposureArgs, RecordPnLArgs, SetADLInProgressArgs,
+};
+use crate::ledger_interface;
 
 // ============================================================================
 // Ledger Program CPI Instructions (for Fund to call Ledger)
 // ============================================================================
 
-/// Ledger Program 指令枚举 (简化版，仅包含 Fund 需要调用的指令)
-#[derive(BorshSerialize)]
-enum LedgerInstruction {
-    OpenPosition {
-        user: Pubkey,
-        market_index: u8,
-        side: u8,          // 0 = Long, 1 = Short
-        size_e6: u64,
-        price_e6: u64,
-        leverage: u8,
-        batch_id: u64,
-    },
-    ClosePosition {
-        user: Pubkey,
-        market_index: u8,
-        size_e6: u64,
-        price_e6: u64,
-        batch_id: u64,
-    },
-}
+// Encoding lives in `ledger_interface`, pinned by its tests.
 
 /// CPI: 开仓 (Fund -> Ledger)
+#[allow(clippy::too_many_arguments)]
 pub fn open_position<'a>(
     ledger_program_id: &Pubkey,
     relayer: AccountInfo<'a>,
@@ -60,19 +46,17 @@ pub fn open_position<'a>(
     batch_id: u64,
     signer_seeds: &[&[&[u8]]],
 ) -> ProgramResult {
-    let instruction = Instruction {
-        program_id: *ledger_program_id,
-        accounts: vec![
-            AccountMeta::new(*relayer.key, true),
-            AccountMeta::new(*position.key, false),
-            AccountMeta::new(*user_account.key, false),
-            AccountMeta::new_readonly(*vault_config.key, false),
-            AccountMeta::new(*ledger_config.key, false),
-            AccountMeta::new(*user_stats.key, false),
-            AccountMeta::new_readonly(*vault_program.key, false),
-            AccountMeta::new_readonly(*system_program.key, false),
-        ],
-        data: LedgerInstruction::OpenPosition {
+    let instruction = ledger_interface::open_position_instruction(
+        ledger_program_id,
+        relayer.key,
+        position.key,
+        user_account.key,
+        vault_config.key,
+        ledger_config.key,
+        user_stats.key,
+        vault_program.key,
+        system_program.key,
+        ledger_interface::OpenPositionArgs {
             user,
             market_index,
             side,
@@ -80,8 +64,8 @@ pub fn open_position<'a>(
             price_e6,
             leverage,
             batch_id,
-        }.try_to_vec()?,
-    };
+        },
+    )?;
 
     invoke_signed(
         &instruction,
@@ -94,6 +78,7 @@ pub fn open_position<'a>(
 }
 
 /// CPI: 平仓 (Fund -> Ledger)
+#[allow(clippy::too_many_arguments)]
 pub fn close_position<'a>(
     ledger_program_id: &Pubkey,
     relayer: AccountInfo<'a>,
@@ -111,26 +96,24 @@ pub fn close_position<'a>(
     batch_id: u64,
     signer_seeds: &[&[&[u8]]],
 ) -> ProgramResult {
-    let instruction = Instruction {
-        program_id: *ledger_program_id,
-        accounts: vec![
-            AccountMeta::new(*relayer.key, true),
-            AccountMeta::new(*position.key, false),
-            AccountMeta::new(*user_account.key, false),
-            AccountMeta::new_readonly(*vault_config.key, false),
-            AccountMeta::new(*insurance_fund.key, false),
-            AccountMeta::new(*ledger_config.key, false),
-            AccountMeta::new(*user_stats.key, false),
-            AccountMeta::new_readonly(*vault_program.key, false),
-        ],
-        data: LedgerInstruction::ClosePosition {
+    let instruction = ledger_interface::close_position_instruction(
+        ledger_program_id,
+        relayer.key,
+        position.key,
+        user_account.key,
+        vault_config.key,
+        insurance_fund.key,
+        ledger_config.key,
+        user_stats.key,
+        vault_program.key,
+        ledger_interface::ClosePositionArgs {
             user,
             market_index,
             size_e6,
             price_e6,
             batch_id,
-        }.try_to_vec()?,
-    };
+        },
+    )?;
 
     invoke_signed(
         &instruction,
@@ -142,6 +125,173 @@ pub fn close_position<'a>(
     )
 }
 
+// ============================================================================
+// SPL Memo CPI
+// ============================================================================
+
+/// SPL Memo Program (v2) ID
+pub const SPL_MEMO_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// CPI: attach a memo signed by `signer` so it shows up in explorers
+pub fn memo<'a>(
+    memo_program: &AccountInfo<'a>,
+    signer: &AccountInfo<'a>,
+    memo: &str,
+) -> ProgramResult {
+    let instruction = Instruction {
+        program_id: SPL_MEMO_PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(*signer.key, true)],
+        data: memo.as_bytes().to_vec(),
+    };
+
+    invoke_signed(&instruction, &[signer.clone(), memo_program.clone()], &[])
+}
+
+// ============================================================================
+// Fund Hook CPI
+// ============================================================================
+
+/// CPI: report a deposit/redemption to the fund's hook program
+///
+/// `extra_accounts` keep their writability but never sign, so the hook
+/// cannot act with the investor's signature.
+pub fn fund_hook<'a>(
+    hook_program: &AccountInfo<'a>,
+    hook_authority: &AccountInfo<'a>,
+    fund: &AccountInfo<'a>,
+    investor: &AccountInfo<'a>,
+    extra_accounts: &[AccountInfo<'a>],
+    payload: &FundHookPayload,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*hook_authority.key, true),
+        AccountMeta::new_readonly(*fund.key, false),
+        AccountMeta::new_readonly(*investor.key, false),
+    ];
+    accounts.extend(extra_accounts.iter().map(|a| {
+        if a.is_writable {
+            AccountMeta::new(*a.key, false)
+        } else {
+            AccountMeta::new_readonly(*a.key, false)
+        }
+    }));
+
+    let instruction = Instruction {
+        program_id: *hook_program.key,
+        accounts,
+        data: payload.try_to_vec()?,
+    };
+
+    let mut account_infos = vec![hook_authority.clone(), fund.clone(), investor.clone()];
+    account_infos.extend(extra_accounts.iter().cloned());
+    account_infos.push(hook_program.clone());
+
+    invoke_signed(&instruction, &account_infos, signer_seeds)
+}
+
+// ============================================================================
+// Stable Swap CPI
+// ============================================================================
+
+/// CPI: run a whitelisted stable-swap instruction on the investor's behalf
+///
+/// Accounts keep their signer/writable flags as passed by the investor; the
+/// Fund PDA never signs, so the swap can only move the investor's tokens.
+pub fn stable_swap<'a>(
+    swap_program: &AccountInfo<'a>,
+    swap_accounts: &[AccountInfo<'a>],
+    data: Vec<u8>,
+) -> ProgramResult {
+    let accounts = swap_accounts
+        .iter()
+        .map(|a| {
+            if a.is_writable {
+                AccountMeta::new(*a.key, a.is_signer)
+            } else {
+                AccountMeta::new_readonly(*a.key, a.is_signer)
+            }
+        })
+        .collect();
+    
+    let instruction = Instruction {
+        program_id: *swap_program.key,
+        accounts,
+        data,
+    };
+    
+    let mut account_infos = swap_accounts.to_vec();
+    account_infos.push(swap_program.clone());
+    
+    invoke(&instruction, &account_infos)
+}
+
+// ============================================================================
+// Address Lookup Table CPI
+// ============================================================================
+
+/// CPI: create an Address Lookup Table with the Fund PDA as authority
+///
+/// The table address is derived from (authority, recent_slot); the caller
+/// checks it before recording it on the Fund.
+pub fn create_lookup_table<'a>(
+    lookup_table_program: &AccountInfo<'a>,
+    lookup_table: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    recent_slot: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let (instruction, _) = solana_program::address_lookup_table::instruction::create_lookup_table_signed(
+        *authority.key,
+        *payer.key,
+        recent_slot,
+    );
+    invoke_signed(
+        &instruction,
+        &[
+            lookup_table.clone(),
+            authority.clone(),
+            payer.clone(),
+            system_program.clone(),
+            lookup_table_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// CPI: append addresses to a lookup table owned by the Fund PDA
+#[allow(clippy::too_many_arguments)]
+pub fn extend_lookup_table<'a>(
+    lookup_table_program: &AccountInfo<'a>,
+    lookup_table: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    addresses: Vec<Pubkey>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = solana_program::address_lookup_table::instruction::extend_lookup_table(
+        *lookup_table.key,
+        *authority.key,
+        Some(*payer.key),
+        addresses,
+    );
+    invoke_signed(
+        &instruction,
+        &[
+            lookup_table.clone(),
+            authority.clone(),
+            payer.clone(),
+            system_program.clone(),
+            lookup_table_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
 // ============================================================================
 // Fund Program CPI Instructions (for others to call Fund)
 // ============================================================================
@@ -188,6 +338,73 @@ pub fn record_pnl<'a>(
     )
 }
 
+/// Credit exchange fee rebates to a fund (called by Ledger Program)
+pub fn record_fee_rebate<'a>(
+    fund_program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    fund: &AccountInfo<'a>,
+    fund_config: &AccountInfo<'a>,
+    rebate_e6: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let instruction_data = FundInstruction::RecordFeeRebate(RecordFeeRebateArgs { rebate_e6 })
+        .try_to_vec()
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let instruction = Instruction {
+        program_id: *fund_program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*caller.key, true),
+            AccountMeta::new(*fund.key, false),
+            AccountMeta::new_readonly(*fund_config.key, false),
+        ],
+        data: instruction_data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[caller.clone(), fund.clone(), fund_config.clone()],
+        signer_seeds,
+    )
+}
+
+/// Mirror a fund's open interest and margin usage (called by Ledger Program)
+#[allow(clippy::too_many_arguments)]
+pub fn record_fund_exposure<'a>(
+    fund_program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    fund: &AccountInfo<'a>,
+    fund_config: &AccountInfo<'a>,
+    open_notional_e6: u64,
+    margin_used_e6: u64,
+    free_collateral_e6: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let instruction_data = FundInstruction::RecordFundExposure(RecordFundExposureArgs {
+        open_notional_e6,
+        margin_used_e6,
+        free_collateral_e6,
+    })
+    .try_to_vec()
+    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let instruction = Instruction {
+        program_id: *fund_program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*caller.key, true),
+            AccountMeta::new(*fund.key, false),
+            AccountMeta::new_readonly(*fund_config.key, false),
+        ],
+        data: instruction_data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[caller.clone(), fund.clone(), fund_config.clone()],
+        signer_seeds,
+    )
+}
+
 /// Create instruction to record PnL
 pub fn create_record_pnl_instruction(
     fund_program_id: &Pubkey,
@@ -209,6 +426,323 @@ pub fn create_record_pnl_instruction(
     })
 }
 
+// ============================================================================
+// Insurance Fund CPI Instructions (for Ledger to call Fund)
+// ============================================================================
+
+/// Metas for optional trailing accounts, keeping their signer/writable flags
+fn trailing_account_metas(trailing_accounts: &[AccountInfo]) -> Vec<AccountMeta> {
+    trailing_accounts
+        .iter()
+        .map(|a| {
+            if a.is_writable {
+                AccountMeta::new(*a.key, a.is_signer)
+            } else {
+                AccountMeta::new_readonly(*a.key, a.is_signer)
+            }
+        })
+        .collect()
+}
+
+/// Serialize a FundInstruction for an instruction builder
+fn fund_instruction_data(instruction: FundInstruction) -> Result<Vec<u8>, ProgramError> {
+    instruction.try_to_vec().map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+/// Create AddLiquidationIncome instruction
+pub fn create_add_liquidation_income_instruction(
+    fund_program_id: &Pubkey,
+    caller: &Pubkey,
+    fund: &Pubkey,
+    insurance_config: &Pubkey,
+    fund_vault: &Pubkey,
+    source: &Pubkey,
+    amount_e6: i64,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *fund_program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(*fund, false),
+            AccountMeta::new(*insurance_config, false),
+            AccountMeta::new(*fund_vault, false),
+            AccountMeta::new_readonly(*source, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: fund_instruction_data(FundInstruction::AddLiquidationIncome(AddLiquidationIncomeArgs { amount_e6 }))?,
+    })
+}
+
+/// Create AddADLProfit instruction
+pub fn create_add_adl_profit_instruction(
+    fund_program_id: &Pubkey,
+    caller: &Pubkey,
+    fund: &Pubkey,
+    insurance_config: &Pubkey,
+    amount_e6: i64,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *fund_program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(*fund, false),
+            AccountMeta::new(*insurance_config, false),
+        ],
+        data: fund_instruction_data(FundInstruction::AddADLProfit(AddADLProfitArgs { amount_e6 }))?,
+    })
+}
+
+/// Create CoverShortfall instruction
+///
+/// `trailing` carries the optional yield venue / ADLEvent accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn create_cover_shortfall_instruction(
+    fund_program_id: &Pubkey,
+    caller: &Pubkey,
+    fund: &Pubkey,
+    insurance_config: &Pubkey,
+    fund_vault: &Pubkey,
+    destination: &Pubkey,
+    shortfall_e6: i64,
+    trailing: &[AccountMeta],
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(*fund, false),
+        AccountMeta::new(*insurance_config, false),
+        AccountMeta::new(*fund_vault, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend_from_slice(trailing);
+    
+    Ok(Instruction {
+        program_id: *fund_program_id,
+        accounts,
+        data: fund_instruction_data(FundInstruction::CoverShortfall(CoverShortfallArgs { shortfall_e6 }))?,
+    })
+}
+
+/// Create SetADLInProgress instruction
+///
+/// `trailing` carries the optional ADLEvent episode accounts.
+pub fn create_set_adl_in_progress_instruction(
+    fund_program_id: &Pubkey,
+    caller: &Pubkey,
+    insurance_config: &Pubkey,
+    in_progress: bool,
+    trailing: &[AccountMeta],
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(*insurance_config, false),
+    ];
+    accounts.extend_from_slice(trailing);
+    
+    Ok(Instruction {
+        program_id: *fund_program_id,
+        accounts,
+        data: fund_instruction_data(FundInstruction::SetADLInProgress(SetADLInProgressArgs { in_progress }))?,
+    })
+}
+
+/// Create AddTradingFee instruction
+///
+/// `trailing` carries the optional referral routing accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn create_add_trading_fee_instruction(
+    fund_program_id: &Pubkey,
+    caller: &Pubkey,
+    fund: &Pubkey,
+    insurance_config: &Pubkey,
+    vault_token_account: &Pubkey,
+    insurance_fund_vault: &Pubkey,
+    fee_e6: i64,
+    trailing: &[AccountMeta],
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(*fund, false),
+        AccountMeta::new(*insurance_config, false),
+        AccountMeta::new(*vault_token_account, false),
+        AccountMeta::new(*insurance_fund_vault, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend_from_slice(trailing);
+    
+    Ok(Instruction {
+        program_id: *fund_program_id,
+        accounts,
+        data: fund_instruction_data(FundInstruction::AddTradingFee(AddTradingFeeArgs { fee_e6 }))?,
+    })
+}
+
+/// CPI: add liquidation income to the Insurance Fund (called by Ledger Program)
+#[allow(clippy::too_many_arguments)]
+pub fn add_liquidation_income<'a>(
+    fund_program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    fund: &AccountInfo<'a>,
+    insurance_config: &AccountInfo<'a>,
+    fund_vault: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount_e6: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = create_add_liquidation_income_instruction(
+        fund_program_id,
+        caller.key,
+        fund.key,
+        insurance_config.key,
+        fund_vault.key,
+        source.key,
+        amount_e6,
+    )?;
+    
+    invoke_signed(
+        &instruction,
+        &[
+            caller.clone(),
+            fund.clone(),
+            insurance_config.clone(),
+            fund_vault.clone(),
+            source.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// CPI: add ADL profit to the Insurance Fund (called by Ledger Program)
+pub fn add_adl_profit<'a>(
+    fund_program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    fund: &AccountInfo<'a>,
+    insurance_config: &AccountInfo<'a>,
+    amount_e6: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = create_add_adl_profit_instruction(
+        fund_program_id,
+        caller.key,
+        fund.key,
+        insurance_config.key,
+        amount_e6,
+    )?;
+    
+    invoke_signed(
+        &instruction,
+        &[caller.clone(), fund.clone(), insurance_config.clone()],
+        signer_seeds,
+    )
+}
+
+/// CPI: cover a shortfall from the Insurance Fund (called by Ledger Program)
+///
+/// `trailing_accounts` (yield venue / ADLEvent) keep their signer/writable flags.
+#[allow(clippy::too_many_arguments)]
+pub fn cover_shortfall<'a>(
+    fund_program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    fund: &AccountInfo<'a>,
+    insurance_config: &AccountInfo<'a>,
+    fund_vault: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    trailing_accounts: &[AccountInfo<'a>],
+    shortfall_e6: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = create_cover_shortfall_instruction(
+        fund_program_id,
+        caller.key,
+        fund.key,
+        insurance_config.key,
+        fund_vault.key,
+        destination.key,
+        shortfall_e6,
+        &trailing_account_metas(trailing_accounts),
+    )?;
+    
+    let mut account_infos = vec![
+        caller.clone(),
+        fund.clone(),
+        insurance_config.clone(),
+        fund_vault.clone(),
+        destination.clone(),
+        token_program.clone(),
+    ];
+    account_infos.extend(trailing_accounts.iter().cloned());
+    
+    invoke_signed(&instruction, &account_infos, signer_seeds)
+}
+
+/// CPI: set the Insurance Fund's ADL-in-progress flag (called by Ledger Program)
+///
+/// `trailing_accounts` (ADLEvent episode accounts) keep their signer/writable flags.
+pub fn set_adl_in_progress<'a>(
+    fund_program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    insurance_config: &AccountInfo<'a>,
+    trailing_accounts: &[AccountInfo<'a>],
+    in_progress: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = create_set_adl_in_progress_instruction(
+        fund_program_id,
+        caller.key,
+        insurance_config.key,
+        in_progress,
+        &trailing_account_metas(trailing_accounts),
+    )?;
+    
+    let mut account_infos = vec![caller.clone(), insurance_config.clone()];
+    account_infos.extend(trailing_accounts.iter().cloned());
+    
+    invoke_signed(&instruction, &account_infos, signer_seeds)
+}
+
+/// CPI: move trading fees into the Insurance Fund (called by Ledger Program)
+///
+/// `trailing_accounts` (referral routing) keep their signer/writable flags.
+#[allow(clippy::too_many_arguments)]
+pub fn add_trading_fee<'a>(
+    fund_program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    fund: &AccountInfo<'a>,
+    insurance_config: &AccountInfo<'a>,
+    vault_token_account: &AccountInfo<'a>,
+    insurance_fund_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    trailing_accounts: &[AccountInfo<'a>],
+    fee_e6: i64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = create_add_trading_fee_instruction(
+        fund_program_id,
+        caller.key,
+        fund.key,
+        insurance_config.key,
+        vault_token_account.key,
+        insurance_fund_vault.key,
+        fee_e6,
+        &trailing_account_metas(trailing_accounts),
+    )?;
+    
+    let mut account_infos = vec![
+        caller.clone(),
+        fund.clone(),
+        insurance_config.clone(),
+        vault_token_account.clone(),
+        insurance_fund_vault.clone(),
+        token_program.clone(),
+    ];
+    account_infos.extend(trailing_accounts.iter().cloned());
+    
+    invoke_signed(&instruction, &account_infos, signer_seeds)
+}
+
 /// Helper to derive Fund PDA
 pub fn derive_fund_pda(
     program_id: &Pubkey,
@@ -280,6 +814,7 @@ pub fn derive_fund_config_pda(program_id: &Pubkey) -> (Pubkey, u8) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::{FUND_CONFIG_SEED, FUND_SEED, LP_POSITION_SEED};
 
     #[test]
     fn test_derive_fund_pda() {
@@ -289,8 +824,12 @@ mod tests {
         
         let (pda, bump) = derive_fund_pda(&program_id, &manager, fund_index);
         
-        // Verify the PDA is valid
-        assert!(bump > 0 || bump == 0);
+        // Verify the PDA is valid and re-derivable from its bump
+        let recreated = Pubkey::create_program_address(
+            &[FUND_SEED, manager.as_ref(), &fund_index.to_le_bytes(), &[bump]],
+            &program_id,
+        ).unwrap();
+        assert_eq!(recreated, pda);
         assert_ne!(pda, program_id);
         assert_ne!(pda, manager);
     }
@@ -303,7 +842,11 @@ mod tests {
         
         let (pda, bump) = derive_lp_position_pda(&program_id, &fund, &investor);
         
-        assert!(bump <= 255);
+        let recreated = Pubkey::create_program_address(
+            &[LP_POSITION_SEED, fund.as_ref(), investor.as_ref(), &[bump]],
+            &program_id,
+        ).unwrap();
+        assert_eq!(recreated, pda);
         assert_ne!(pda, fund);
         assert_ne!(pda, investor);
     }
@@ -314,7 +857,8 @@ mod tests {
         
         let (pda, bump) = derive_fund_config_pda(&program_id);
         
-        assert!(bump <= 255);
+        let recreated = Pubkey::create_program_address(&[FUND_CONFIG_SEED, &[bump]], &program_id).unwrap();
+        assert_eq!(recreated, pda);
         assert_ne!(pda, program_id);
     }
 
@@ -335,5 +879,61 @@ mod tests {
         assert_eq!(ix.accounts.len(), 2);
         assert!(!ix.data.is_empty());
     }
-}
 
+    #[test]
+    fn test_insurance_ingress_instructions() {
+        use borsh::BorshDeserialize;
+        
+        let program_id = Pubkey::new_unique();
+        let caller = Pubkey::new_unique();
+        let fund = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let adl_event = AccountMeta::new(Pubkey::new_unique(), false);
+        
+        let ix = create_add_liquidation_income_instruction(&program_id, &caller, &fund, &config, &vault, &other, 5).unwrap();
+        assert_eq!(ix.accounts.len(), 6);
+        assert!(ix.accounts[0].is_signer && !ix.accounts[0].is_writable);
+        assert!(ix.accounts[1].is_writable && ix.accounts[2].is_writable && ix.accounts[3].is_writable);
+        assert_eq!(ix.accounts[5].pubkey, spl_token::id());
+        assert!(matches!(
+            FundInstruction::try_from_slice(&ix.data).unwrap(),
+            FundInstruction::AddLiquidationIncome(AddLiquidationIncomeArgs { amount_e6: 5 })
+        ));
+        
+        let ix = create_add_adl_profit_instruction(&program_id, &caller, &fund, &config, 6).unwrap();
+        assert_eq!(ix.accounts.len(), 3);
+        assert!(matches!(
+            FundInstruction::try_from_slice(&ix.data).unwrap(),
+            FundInstruction::AddADLProfit(AddADLProfitArgs { amount_e6: 6 })
+        ));
+        
+        let ix = create_cover_shortfall_instruction(
+            &program_id, &caller, &fund, &config, &vault, &other, 7, std::slice::from_ref(&adl_event),
+        ).unwrap();
+        assert_eq!(ix.accounts.len(), 7);
+        assert!(ix.accounts[4].is_writable);
+        assert_eq!(ix.accounts[6], adl_event);
+        assert!(matches!(
+            FundInstruction::try_from_slice(&ix.data).unwrap(),
+            FundInstruction::CoverShortfall(CoverShortfallArgs { shortfall_e6: 7 })
+        ));
+        
+        let ix = create_set_adl_in_progress_instruction(&program_id, &caller, &config, false, &[adl_event]).unwrap();
+        assert_eq!(ix.accounts.len(), 3);
+        assert_eq!(ix.accounts[1].pubkey, config);
+        assert!(matches!(
+            FundInstruction::try_from_slice(&ix.data).unwrap(),
+            FundInstruction::SetADLInProgress(SetADLInProgressArgs { in_progress: false })
+        ));
+        
+        let ix = create_add_trading_fee_instruction(&program_id, &caller, &fund, &config, &other, &vault, 8, &[]).unwrap();
+        assert_eq!(ix.accounts.len(), 6);
+        assert_eq!((ix.accounts[3].pubkey, ix.accounts[4].pubkey), (other, vault));
+        assert!(matches!(
+            FundInstruction::try_from_slice(&ix.data).unwrap(),
+            FundInstruction::AddTradingFee(AddTradingFeeArgs { fee_e6: 8 })
+        ));
+    }
+}
@@ -295,6 +295,629 @@ pub enum FundError {
     /// [142] Relayer not found
     #[error("Relayer not found in authorized list")]
     RelayerNotFound,
+
+    // === 账户校验错误 (150-159) ===
+
+    /// [150] Account key mismatch
+    #[error("Account address does not match the expected key")]
+    AccountKeyMismatch,
+
+    // === LP Beneficiary 错误 (160-169) ===
+
+    /// [160] Caller is not the designated beneficiary
+    #[error("Caller is not the designated beneficiary of this LP position")]
+    NotBeneficiary,
+
+    /// [161] Inactivity period not met
+    #[error("LP position has not been inactive long enough for a beneficiary claim")]
+    BeneficiaryInactivityNotMet,
+
+    // === Fund Creation 错误 (170-179) ===
+
+    /// [170] Manager fund limit reached
+    #[error("Manager has reached the maximum number of active funds")]
+    ManagerFundLimitReached,
+
+    /// [171] Fund is not pending listing approval
+    #[error("Fund is not pending listing approval")]
+    FundNotPendingApproval,
+
+    /// [172] Fund listing not approved
+    #[error("Fund listing is pending approval or was rejected")]
+    FundListingNotApproved,
+
+    // === Fee Collection 错误 (180-189) ===
+
+    /// [180] Fee crank not authorized
+    #[error("Caller is not the manager or an authorized fee crank for this fund")]
+    FeeCrankNotAuthorized,
+
+    /// [181] Invalid fee destination
+    #[error("Fee destination account is not valid for this fund")]
+    InvalidFeeDestination,
+
+    // === Reinitialize 错误 (190-199) ===
+
+    /// [190] No reinitialization scheduled
+    #[error("No reinitialization is scheduled")]
+    ReinitializeNotScheduled,
+
+    /// [191] Timelock not elapsed
+    #[error("Timelock period has not elapsed")]
+    TimelockNotElapsed,
+
+    // === Realloc 错误 (200-209) ===
+
+    /// [200] Invalid realloc size
+    #[error("Invalid realloc size: must grow the account by at most 10 KiB")]
+    InvalidReallocSize,
+
+    // === Relayer Nonce 错误 (210-219) ===
+
+    /// [210] Relayer nonce already used
+    #[error("Relayer nonce must be greater than the last accepted nonce for this user")]
+    RelayerNonceReplay,
+
+    // === Payout Allow-list 错误 (220-229) ===
+
+    /// [220] Payout allow-list full
+    #[error("Payout allow-list is full")]
+    PayoutAllowListFull,
+
+    /// [221] Payout address not allowed
+    #[error("Redemption destination is not an active payout address for this LP position")]
+    PayoutAddressNotAllowed,
+
+    // === System Fund 错误 (230-239) ===
+
+    /// [230] System fund protected
+    #[error("Insurance/system funds cannot be closed, and only the admin can pause them")]
+    SystemFundProtected,
+
+    // === LP Index 错误 (240-249) ===
+    /// [240] LP index page missing
+    #[error("Required LPIndexPage account was not provided")]
+    LPIndexPageMissing,
+
+    // === LP Distribution 错误 (250-259) ===
+    /// [250] Distribution in progress
+    #[error("A DistributeToLPs round is in progress for this fund")]
+    DistributionInProgress,
+
+    // === Governance 错误 (260-269) ===
+    /// [260] Proposal threshold not met
+    #[error("Only the manager or an LP above the share threshold can create proposals")]
+    ProposalThresholdNotMet,
+
+    /// [261] Voting closed
+    #[error("Voting on this proposal has closed")]
+    VotingClosed,
+
+    /// [262] Voting not ended
+    #[error("Voting period has not ended yet")]
+    VotingNotEnded,
+
+    /// [263] No voting weight
+    #[error("LP position has no voting weight at the proposal snapshot")]
+    NoVotingWeight,
+
+    // === Share Lock 错误 (270-279) ===
+    /// [270] Shares locked
+    #[error("Shares are locked by a lienholder")]
+    SharesLocked,
+
+    /// [271] Share lien held by another party
+    #[error("LP position shares are pledged to a different lienholder")]
+    ShareLienHeld,
+
+    // === Invariants 错误 (280-289) ===
+    /// [280] Invariant violation (strict-invariants builds only)
+    #[error("Fund accounting invariant violated")]
+    InvariantViolation,
+
+    // === Trade Intent 错误 (290-299) ===
+    /// [290] Trade intent expired
+    #[error("Trade intent has expired")]
+    TradeIntentExpired,
+
+    /// [291] Limit price not met
+    #[error("Execution price does not satisfy the trade intent limit")]
+    TradeIntentLimitNotMet,
+
+    /// [292] Trade intent already executed
+    #[error("Trade intent has already been executed")]
+    TradeIntentAlreadyExecuted,
+
+    // === Feature Flag 错误 (300-309) ===
+    /// [300] Subsystem disabled by the admin
+    #[error("Feature is disabled")]
+    FeatureDisabled,
+
+    /// [301] Feature-gated instruction without the FundConfig account
+    #[error("FundConfig account required for feature-gated instruction")]
+    FeatureConfigMissing,
+
+    // === Redemption Fee 错误 (310-319) ===
+    /// [310] Invalid redemption fee curve
+    #[error("Invalid redemption fee curve")]
+    InvalidRedemptionFeeCurve,
+
+    // === Deposit Attribution 错误 (320-329) ===
+    /// [320] campaign_id set without the CampaignStats / DepositAttribution PDAs
+    #[error("Campaign attribution accounts missing")]
+    CampaignAccountsMissing,
+
+    // === Referral Expiry 错误 (330-339) ===
+    /// [330] Binding still within reward_validity_secs
+    #[error("Referral binding has not expired")]
+    ReferralBindingNotExpired,
+
+    // === PM Maker Reward 错误 (340-349) ===
+    /// [340] Maker reward epoch still running
+    #[error("Maker reward epoch has not ended")]
+    PMMakerEpochNotEnded,
+
+    /// [341] Maker already rewarded for the epoch
+    #[error("Maker reward already distributed for this epoch")]
+    PMMakerRewardAlreadyDistributed,
+
+    /// [342] Category index outside MAX_PM_CATEGORIES
+    #[error("Invalid prediction market category")]
+    InvalidPMCategory,
+
+    // === Treasury 错误 (350-359) ===
+    /// [350] Treasury not initialized
+    #[error("Treasury is not initialized")]
+    TreasuryNotInitialized,
+
+    /// [351] Token account is not the treasury vault
+    #[error("Account is not the treasury vault")]
+    TreasuryVaultMismatch,
+
+    /// [352] No treasury withdrawal scheduled
+    #[error("No treasury withdrawal is scheduled")]
+    TreasuryWithdrawalNotScheduled,
+
+    // === Balances Checkpoint 错误 (360-369) ===
+    /// [360] Snapshot in the future or not newer than the committed one
+    #[error("Invalid balances snapshot timestamp")]
+    InvalidSnapshotTimestamp,
+
+    /// [361] No balances root committed for the fund
+    #[error("Balances root has not been committed")]
+    BalancesRootNotCommitted,
+
+    /// [362] Inclusion proof does not match the committed root
+    #[error("Invalid balance inclusion proof")]
+    InvalidBalanceProof,
+
+    // === Memo 错误 (370-379) ===
+    /// [370] Memo empty or longer than MAX_MEMO_LEN
+    #[error("Invalid memo")]
+    InvalidMemo,
+
+    // === Pause Expiry 错误 (380-389) ===
+    /// [380] Max pause override above the admin policy bound
+    #[error("Max pause duration exceeds admin policy")]
+    PauseDurationExceedsPolicy,
+
+    // === Manager Risk 错误 (390-399) ===
+    /// [390] ManagerRiskState / ManagerRiskLimits not passed while limits are enabled
+    #[error("Manager risk state required")]
+    ManagerRiskStateRequired,
+    /// [391] Trade would push the manager's open notional above the cap
+    #[error("Manager notional cap exceeded")]
+    ManagerNotionalCapExceeded,
+    /// [392] Trade leverage above the per-manager cap
+    #[error("Manager leverage cap exceeded")]
+    ManagerLeverageCapExceeded,
+
+    // === NAV Floor 错误 (400-409) ===
+    /// [400] NAV below MIN_DEPOSIT_NAV_E6
+    #[error("NAV below deposit floor")]
+    NAVBelowFloor,
+    /// [401] Fund is in wind-down review, deposits blocked
+    #[error("Fund is in wind-down review")]
+    FundInWindDownReview,
+    /// [402] Fund is not in wind-down review
+    #[error("Fund is not in wind-down review")]
+    WindDownReviewNotActive,
+
+    // === Fund Setup 错误 (410-419) ===
+    /// [410] Fund was created via CreateFundAccount and is not finalized yet
+    #[error("Fund setup is pending")]
+    FundSetupPending,
+    /// [411] Share mint / vault not initialized when finalizing
+    #[error("Fund setup incomplete")]
+    FundSetupIncomplete,
+
+    // === Benchmark 错误 (420-429) ===
+    /// [420] Fund has a benchmark but its price feed was not passed
+    #[error("Benchmark feed required")]
+    BenchmarkFeedRequired,
+    /// [421] Benchmark feed is not a SharePriceFeed, or is the fund's own feed
+    #[error("Invalid benchmark feed")]
+    InvalidBenchmarkFeed,
+    /// [422] Benchmark price missing or older than BENCHMARK_MAX_AGE_SECS
+    #[error("Benchmark price stale")]
+    BenchmarkPriceStale,
+    /// [423] Benchmark cannot change while a performance fee is accrued
+    #[error("Performance fee accrued, collect before changing benchmark")]
+    BenchmarkChangeWithAccruedFee,
+
+    // === NAV Blackout 错误 (430-439) ===
+    /// [430] Deposits / redemptions blocked after a large RecordPnL
+    #[error("Fund is in a NAV blackout window")]
+    FundInBlackout,
+    /// [431] Blackout window / threshold outside the admin BlackoutPolicy
+    #[error("Blackout settings exceed admin policy")]
+    BlackoutOutsidePolicy,
+
+    // === Share Freeze 错误 (440-449) ===
+    /// [440] Share token account is already frozen
+    #[error("Shares already frozen")]
+    SharesAlreadyFrozen,
+    /// [441] Share token account is not frozen
+    #[error("Shares not frozen")]
+    SharesNotFrozen,
+    /// [442] Freeze applied by the admin; only the admin may thaw it
+    #[error("Admin freeze can only be lifted by the admin")]
+    AdminFreezeRequiresAdmin,
+
+    // === Insurance Yield 错误 (450-459) ===
+    /// [450] Deployment would exceed the venue's max_deploy_bps of insurance capital
+    #[error("Insurance capital deploy limit exceeded")]
+    InsuranceDeployLimitExceeded,
+    /// [451] Venue cannot change while capital is deployed
+    #[error("Insurance capital still deployed")]
+    InsuranceCapitalDeployed,
+    /// [452] Venue deposit account not held by the insurance Fund PDA in the vault's mint
+    #[error("Invalid insurance yield venue")]
+    InvalidYieldVenue,
+
+    // === ADL Event 错误 (460-469) ===
+    /// [460] ADLEvent already ended; the record is append-only
+    #[error("ADL event already ended")]
+    ADLEventClosed,
+
+    // === Trading Fee 错误 (470-479) ===
+    /// [470] Fee destination is not the insurance fund's vault
+    #[error("Fee destination does not match the insurance fund vault")]
+    FeeDestinationMismatch,
+    /// [471] Fee source / destination / reward vault hold different mints
+    #[error("Fee token mint mismatch")]
+    FeeMintMismatch,
+
+    // === Square Fund 错误 (480-489) ===
+    /// [480] Payment into the program-owned square fund vault without its SquareFundStats
+    #[error("SquareFundStats PDA required")]
+    SquareFundStatsRequired,
+    /// [481] Withdrawal larger than the accounted square fund balance
+    #[error("Square fund withdrawal exceeds accounted balance")]
+    SquareWithdrawalExceedsBalance,
+
+    // === Revenue Split 错误 (490-499) ===
+    /// [490] Split must have 1-5 distinct recipients with non-zero bps summing to 10000
+    #[error("Invalid revenue split")]
+    InvalidRevenueSplit,
+    /// [491] A split recipient's token account was not passed
+    #[error("Revenue split recipient account missing")]
+    RevenueSplitRecipientMissing,
+
+    // === Square Payer Nonce 错误 (500-509) ===
+    /// [500] SquarePayment nonce not greater than the payer's last accepted nonce
+    #[error("Square payment nonce must be greater than the payer's last nonce")]
+    SquarePaymentNonceReplay,
+    /// [501] The payer's SquarePayerNonce PDA was not passed
+    #[error("SquarePayerNonce PDA required")]
+    SquarePayerNonceRequired,
+
+    // === Square Escrow 错误 (510-519) ===
+    /// [510] Escrow is only offered for KnowledgePurchase payments
+    #[error("Escrow is only available for knowledge purchases")]
+    SquareEscrowKnowledgeOnly,
+    /// [511] Escrow is not in the state the operation needs
+    #[error("Square escrow is not held")]
+    SquareEscrowNotHeld,
+    /// [512] Only the buyer may release before the timeout
+    #[error("Square escrow can only be released by the buyer before the timeout")]
+    SquareEscrowNotReleasable,
+    /// [513] Disputes must be raised before the timeout
+    #[error("Square escrow dispute window has closed")]
+    SquareEscrowDisputeWindowClosed,
+    /// [514] Escrow / refund / payout account differs from the one recorded in escrow
+    #[error("Square escrow account mismatch")]
+    SquareEscrowAccountMismatch,
+
+    // === Square Tip Token 错误 (520-529) ===
+    /// [520] Tip token is not whitelisted / enabled
+    #[error("Square tip token is not enabled")]
+    SquareTipTokenDisabled,
+    /// [521] Tip token price missing or older than its max age
+    #[error("Square tip token price is stale")]
+    SquareTipPriceStale,
+    /// [522] Token account mint or platform vault does not match the tip token
+    #[error("Square tip token account mismatch")]
+    SquareTipTokenMismatch,
+
+    // === Fund Hook 错误 (530-539) ===
+    /// [530] Hook program must be an executable program other than this one
+    #[error("Invalid fund hook program")]
+    InvalidFundHookProgram,
+    /// [531] Fund has a hook but its program / authority PDA were not passed
+    #[error("Fund hook accounts missing")]
+    FundHookAccountsMissing,
+
+    // === Share Authority 错误 (540-549) ===
+    /// [540] The rotated ShareMintAuthority PDA was not passed
+    #[error("Share mint authority PDA required")]
+    ShareAuthorityRequired,
+    /// [541] Share mint authorities are not held by the fund's current authority
+    #[error("Share mint authority mismatch")]
+    ShareAuthorityMismatch,
+
+    // === Instruction Decoding 错误 (550-559) ===
+    /// [550] Empty instruction data or variant index beyond the last instruction
+    #[error("Unknown instruction")]
+    UnknownInstruction,
+    /// [551] Instruction decoded but bytes were left over after its arguments
+    #[error("Instruction data has trailing bytes")]
+    InstructionDataTrailingBytes,
+
+    // === Fund LP Referral 错误 (560-569) ===
+    /// [560] Fund LP referral program missing or inactive
+    #[error("Fund LP referral program inactive")]
+    FundLPReferralInactive,
+    /// [561] Referrer is the referee or unset
+    #[error("Invalid fund LP referrer")]
+    InvalidFundLPReferrer,
+    /// [562] Referee already has an LP position in this fund
+    #[error("Fund LP referral must be bound before the first deposit")]
+    FundLPReferralAfterDeposit,
+    /// [563] Bounty above MAX_FUND_LP_REFERRAL_BOUNTY_BPS
+    #[error("Fund LP referral bounty too high")]
+    FundLPReferralBountyTooHigh,
+    /// [564] Fund runs an LP referral program but its PDA was not passed
+    #[error("Fund LP referral program account missing")]
+    FundLPReferralAccountsMissing,
+    /// [565] Nothing accrued, or not yet covered by collected fees
+    #[error("No fund LP referral bounty to claim")]
+    NoFundLPReferralBounty,
+
+    // === Pause Toggle 错误 (570-579) ===
+    /// [570] Fund pause toggled less than PAUSE_TOGGLE_COOLDOWN_SECS ago
+    #[error("Fund pause toggle cooldown active")]
+    PauseToggleCooldown,
+    /// [571] MAX_PAUSE_TOGGLES_PER_DAY reached for today
+    #[error("Fund pause toggle limit reached for today")]
+    PauseToggleLimitReached,
+
+    // === Admin Intervention 错误 (580-589) ===
+    /// [580] Pause / open state was locked by an admin intervention
+    #[error("Fund state locked by admin")]
+    FundAdminLocked,
+
+    // === Insurance Migration 错误 (590-599) ===
+    /// [590] MigrateInsuranceConfigV2 already applied to this config
+    #[error("Insurance config already migrated to V2")]
+    InsuranceConfigAlreadyMigrated,
+    /// [591] Backfill components do not sum to the recorded V1 income
+    #[error("Insurance migration backfill mismatch")]
+    InsuranceMigrationMismatch,
+
+    // === Fund Vault Audit 错误 (600-609) ===
+    /// [600] Fund vault token account is not owned by the Fund PDA
+    #[error("Fund vault owner mismatch")]
+    FundVaultOwnerMismatch,
+    /// [601] Fund vault has a delegate set (AuditFundVault revokes it)
+    #[error("Fund vault has a delegate")]
+    FundVaultDelegateSet,
+    /// [602] Fund vault has a close authority set
+    #[error("Fund vault has a close authority")]
+    FundVaultCloseAuthoritySet,
+
+    // === LP Fee Statement 错误 (610-619) ===
+    /// [610] Fund has not crystallized any fees yet
+    #[error("No fee epoch to record")]
+    NoFeeEpoch,
+    /// [611] Latest fee epoch already on the statement
+    #[error("LP fee statement already up to date")]
+    LPFeeStatementUpToDate,
+    /// [612] Position changed after the crystallization; its epoch share is unknown
+    #[error("LP position changed since the fee epoch")]
+    LPFeeStatementStale,
+
+    // === Stable Swap 错误 (620-629) ===
+    /// [620] Route missing, inactive, or not for this input / fund mint
+    #[error("Invalid stable swap route")]
+    InvalidStableSwapRoute,
+    /// [621] Max slippage above MAX_STABLE_SWAP_SLIPPAGE_BPS
+    #[error("Stable swap slippage bound too high")]
+    StableSwapSlippageTooHigh,
+    /// [622] Swap returned less than the route / caller minimum
+    #[error("Stable swap slippage exceeded")]
+    StableSwapSlippageExceeded,
+    /// [623] Swap spent more than the requested input
+    #[error("Stable swap spent more than requested")]
+    StableSwapInputExceeded,
+    
+    // === Risk Enforcement 错误 (630-639) ===
+    /// [630] Breach has not outlasted RISK_BREACH_GRACE_SECS yet
+    #[error("Risk limit breach still within grace period")]
+    RiskBreachGracePeriodActive,
+    
+    // === Ledger Position 错误 (640-649) ===
+    /// [640] Position account not owned by the Ledger, or not the fund's position in this market
+    #[error("Ledger position does not belong to the fund")]
+    LedgerPositionMismatch,
+    /// [641] Close-all requested on a position with no size
+    #[error("Ledger position is empty")]
+    LedgerPositionEmpty,
+    
+    // === Share Supply 错误 (650-659) ===
+    /// [650] Share mint supply and FundStats disagree; flows blocked until reconciled
+    #[error("Fund share supply is inconsistent")]
+    ShareSupplyInconsistent,
+    
+    // === Position NFT 错误 (660-669) ===
+    /// [660] Position is already represented by a position NFT
+    #[error("LP position is wrapped in a position NFT")]
+    PositionNFTWrapped,
+    /// [661] Position is not wrapped
+    #[error("LP position is not wrapped in a position NFT")]
+    PositionNFTNotWrapped,
+    /// [662] Signer does not hold the position NFT
+    #[error("Signer does not hold the position NFT")]
+    NotPositionNFTHolder,
+    
+    // === Relayer Pause 错误 (670-679) ===
+    /// [670] Relayer* instructions paused by SetRelayerSystemPaused
+    #[error("Relayer subsystem is paused")]
+    RelayerSystemPaused,
+    
+    // === Insurance Config Update 错误 (680-689) ===
+    /// [680] No pending InsuranceFundConfig update
+    #[error("Insurance config update not scheduled")]
+    InsuranceConfigUpdateNotScheduled,
+    
+    // === LP Position Transfer 错误 (690-699) ===
+    /// [690] New owner already holds shares in this fund
+    #[error("LP position transfer target already holds shares")]
+    LPPositionTransferTargetInUse,
+    
+    // === Fund Setup Validation 错误 (700-709) ===
+    /// [700] Fund vault mint is the share mint or not the fund's denomination
+    #[error("Fund vault mint mismatch")]
+    FundVaultMintMismatch,
+    
+    /// [701] ValidateFundSetup already recorded the denomination mint
+    #[error("Fund setup already validated")]
+    FundSetupAlreadyValidated,
+    
+    // === Fund Expense 错误 (710-719) ===
+    /// [710] Expense cap above MAX_FUND_EXPENSE_BPS or missing per-payment max
+    #[error("Invalid fund expense policy")]
+    InvalidExpensePolicy,
+    
+    /// [711] Fund was created without an expense allowance
+    #[error("Fund expenses disabled")]
+    FundExpensesDisabled,
+    
+    /// [712] Payment above the per-payment maximum
+    #[error("Fund expense payment too large")]
+    FundExpenseTooLarge,
+    
+    /// [713] Payment would exceed the annual expense cap
+    #[error("Fund expense cap exceeded")]
+    FundExpenseCapExceeded,
+    
+    /// [714] Expenses go to third parties, not the manager or the fund
+    #[error("Fund expense payee not allowed")]
+    FundExpensePayeeNotAllowed,
+    
+    // === Insurance Coverage 错误 (720-729) ===
+    /// [720] The Ledger has not reported platform open interest yet
+    #[error("Platform open interest not reported")]
+    OpenInterestNotReported,
+    
+    // === Referral Claim 错误 (730-739) ===
+    /// [730] The referral link has no funded rewards to claim
+    #[error("No referral rewards to claim")]
+    NoReferralRewardsToClaim,
+    
+    // === Investor Portfolio 错误 (740-749) ===
+    /// [740] The portfolio already tracks MAX_PORTFOLIO_FUNDS funds
+    #[error("Investor portfolio full")]
+    InvestorPortfolioFull,
+    
+    // === Fund State 错误 (750-759) ===
+    /// [750] SetFundState target equals the current state, or the fund is closed
+    #[error("Invalid fund state transition")]
+    InvalidFundStateTransition,
+    
+    // === Stale Entry 错误 (760-769) ===
+    /// [760] ExpireStaleEntries was given an entry it cannot close
+    #[error("Unsupported stale entry account")]
+    UnsupportedStaleEntry,
+    
+    // === Fund Lookup Table 错误 (770-779) ===
+    /// [770] The fund has no lookup table yet (CreateFundLookupTable)
+    #[error("Fund lookup table missing")]
+    FundLookupTableMissing,
+    
+    // === Fund Coverage 错误 (780-789) ===
+    /// [780] Premium / coverage / deductible / limit out of range
+    #[error("Invalid coverage terms")]
+    InvalidCoverageTerms,
+    
+    /// [781] Policy cancelled, or its premium is overdue past the grace period
+    #[error("Fund coverage not in force")]
+    FundCoverageNotInForce,
+    
+    /// [782] No loss below the deductible backed by trading losses, or limit used up
+    #[error("No qualifying loss to claim")]
+    NoQualifyingLoss,
+    
+    // === Square Payment History 错误 (790-799) ===
+    /// [790] The payer's SquarePaymentHistory PDA was not passed
+    #[error("SquarePaymentHistory PDA required")]
+    SquarePaymentHistoryRequired,
+    
+    /// [791] The history's head page (or the next page when it is full) was not passed
+    #[error("Square payment history page missing")]
+    SquarePaymentHistoryPageMissing,
+    
+    // === LP Beneficiary Approval 错误 (800-809) ===
+    /// [800] The investor's share account no longer delegates the position's shares to the Fund PDA
+    #[error("Beneficiary share approval missing; the investor must call SetLPBeneficiary again")]
+    BeneficiaryApprovalMissing,
+    
+    // === Mark Price 错误 (810-819) ===
+    /// [810] The market's MarkPrice is missing or older than MARK_PRICE_MAX_AGE_SECS
+    #[error("Mark price stale")]
+    MarkPriceStale,
+    
+    /// [811] Keeper exit price outside RISK_ENFORCEMENT_MAX_PRICE_DEVIATION_BPS of the mark price
+    #[error("Price too far from the mark price")]
+    PriceOutsideMarkBand,
+    
+    // === Rent Reserve 错误 (820-829) ===
+    /// [820] Closing a reserve-sponsored account without the RentReserve PDA
+    #[error("Rent reserve missing")]
+    RentReserveMissing,
+    
+    // === Deposit Receipt 错误 (830-839) ===
+    /// [830] Full exit of a position holding a receipt NFT without the receipt accounts
+    #[error("Deposit receipt accounts missing")]
+    DepositReceiptMissing,
+    
+    // === Square Fund Vault 错误 (840-849) ===
+    /// [840] Platform share destination is not the square fund vault / Treasury vault, or has another mint
+    #[error("Square fund vault mismatch")]
+    SquareFundVaultMismatch,
+    
+    // === Insurance Daily Stats 错误 (850-859) ===
+    /// [850] Hourly snapshot without the day's InsuranceDailyStats once daily stats are on
+    #[error("InsuranceDailyStats PDA required")]
+    InsuranceDailyStatsRequired,
+    
+    // === Fund Exposure 错误 (860-869) ===
+    /// [860] Fund exposure not reported by the Ledger within RISK_EXPOSURE_MAX_AGE_SECS
+    #[error("Fund exposure stale")]
+    FundExposureStale,
+    
+    // === Share Checkpoints 错误 (870-879) ===
+    /// [870] Supply-changing flow without the fund's ShareCheckpoints PDA
+    #[error("ShareCheckpoints PDA required")]
+    ShareCheckpointsRequired,
+    
+    // === Referral Settlement 错误 (880-889) ===
+    /// [880] Unclaimed referral rewards below ReferralConfig.min_settlement_amount_e6
+    #[error("Referral rewards below minimum settlement amount")]
+    ReferralSettlementBelowMinimum,
+    /// [881] Payout account is not the referrer's reward-mint token account
+    #[error("Referral payout account mismatch")]
+    ReferralPayoutAccountMismatch,
 }
 
 impl From<FundError> for ProgramError {
@@ -3,9 +3,15 @@
 //! Defines all instructions for the Fund Program.
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use solana_program::{msg, program_error::ProgramError, pubkey::Pubkey};
 
-use crate::state::FeeConfig;
+use crate::error::FundError;
+
+use crate::state::{
+    ADLTriggerReason, FeeConfig, FundLifecycleState, FundStateReason, PMCategoryFees, ProposalKind, ReferralRebindPolicy, RevenueSplitRecipient,
+    ShareFreezeReason, FEATURE_PM_FEES,
+    FEATURE_REFERRALS, FEATURE_RELAYER, FEATURE_SQUARE_PAYMENTS,
+};
 
 /// All instructions supported by the Fund Program
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -34,6 +40,21 @@ pub enum FundInstruction {
     /// 6. `[]` Token Program
     /// 7. `[]` System Program
     /// 8. `[]` Rent Sysvar
+    /// 9. `[writable]` ManagerProfile PDA
+    /// 
+    /// With a creation fee set, the manager's USDC account pays it into the
+    /// Treasury vault (TreasurySource::FundCreationFee):
+    /// 10. `[writable]` Treasury PDA
+    /// 11. `[writable]` Treasury vault
+    /// 12. `[writable]` Manager's USDC account
+    /// 
+    /// With `initial_deposit_amount`, the manager seeds the fund as its first LP
+    /// so it never sits at zero TVL (10-12 must then be passed even without a
+    /// creation fee). The seed deposit is accepted while the listing awaits
+    /// approval; other LPs can only deposit once the fund is Listed:
+    /// 13. `[writable]` Manager's LP Position PDA
+    /// 14. `[signer, writable]` New share token account (created for the manager)
+    /// 15.. DepositToFund trailing accounts (LPIndexPage, ...)
     CreateFund(CreateFundArgs),
     
     // === Fund Management (10-19) ===
@@ -45,21 +66,31 @@ pub enum FundInstruction {
     /// 1. `[writable]` Fund PDA
     UpdateFund(UpdateFundArgs),
     
-    /// Open/close fund for deposits
+    /// Move a fund between Open / DepositsClosed / Paused, with a reason code
+    /// 
+    /// Replaces SetFundOpen and SetFundPaused. The target must differ from the
+    /// current state and closed funds cannot change state. Transitions into or
+    /// out of Paused follow the pause rules: insurance/system funds are admin
+    /// only, a pause of any other fund stops blocking withdrawals after its
+    /// maximum duration, and managers must wait
+    /// `Fund::PAUSE_TOGGLE_COOLDOWN_SECS` between toggles and make at most
+    /// `Fund::MAX_PAUSE_TOGGLES_PER_DAY` per UTC day. The admin may sign
+    /// instead of the manager to override the cooldown and admin locks.
+    /// Emits FUND_STATE_CHANGED.
     /// 
     /// Accounts:
-    /// 0. `[signer]` Fund manager
+    /// 0. `[signer, writable]` Fund manager or admin
     /// 1. `[writable]` Fund PDA
-    SetFundOpen(SetFundOpenArgs),
+    /// 2. `[]` FundConfig PDA
+    /// 3. `[]` System Program (only when the Fund account predates the pause toggle counters)
+    SetFundState(SetFundStateArgs),
     
-    /// Pause/unpause fund
+    /// Retired: SetFundPaused, replaced by SetFundState (always fails)
     /// 
-    /// Accounts:
-    /// 0. `[signer]` Fund manager
-    /// 1. `[writable]` Fund PDA
-    SetFundPaused(SetFundPausedArgs),
+    /// Kept so the indices of later instructions do not change.
+    RetiredSetFundPaused,
     
-    /// Close a fund (manager only)
+    /// Close a fund (manager only; insurance/system funds cannot be closed)
     /// 
     /// Accounts:
     /// 0. `[signer]` Fund manager
@@ -69,6 +100,11 @@ pub enum FundInstruction {
     /// 4. `[writable]` FundConfig PDA
     /// 5. `[writable]` Manager's USDC account
     /// 6. `[]` Token Program
+    /// 7. `[writable]` ManagerProfile PDA (frees the manager's fund slot)
+    /// 
+    /// Closing a Fund account that predates `closed_at` also needs the
+    /// manager as `[signer, writable]` and the System Program (any position
+    /// after 7) to grow it.
     CloseFund,
     
     // === LP Operations (20-29) ===
@@ -85,6 +121,34 @@ pub enum FundInstruction {
     /// 6. `[writable]` Share mint PDA
     /// 7. `[]` Token Program
     /// 8. `[]` System Program
+    /// 9. `[writable]` DepositReceipt PDA (only if fund.deposit_receipts_enabled)
+    /// 10. `[writable]` Receipt mint PDA (only if fund.deposit_receipts_enabled)
+    /// 11. `[writable]` Receipt token account PDA (only if fund.deposit_receipts_enabled)
+    /// 
+    /// Deposits into the Insurance Fund also need the InsuranceFundConfig PDA
+    /// (anywhere after 8) and are rejected while ADL is in progress.
+    /// 
+    /// When a position is opened (or reopened), the fund's last LPIndexPage
+    /// PDA, plus the next page if the last is full, must also be passed
+    /// (`[writable]`, anywhere after 8, before the RentReserve).
+    /// 
+    /// With `campaign_id` set, the CampaignStats PDA and the next
+    /// DepositAttribution PDA (seq = deposit_count) must also be passed
+    /// (`[writable]`, anywhere after 8, before the RentReserve).
+    /// 
+    /// With `memo` set, passing the SPL Memo program (anywhere after 8,
+    /// before the RentReserve) also attaches the memo on-chain.
+    /// 
+    /// After RotateShareMintAuthority, the fund's current ShareMintAuthority
+    /// PDA must also be passed (anywhere after 8).
+    /// 
+    /// An LP bound via BindFundLPReferral passes its `[writable]`
+    /// FundLPReferralBinding PDA and the fund's `[writable]`
+    /// FundLPReferralProgram PDA (anywhere after 8) to accrue the referrer's bounty.
+    /// 
+    /// Optional last account: `[writable]` RentReserve PDA (sponsors a new LP Position's rent)
+    /// 
+    /// Return data: `DepositResult`
     DepositToFund(DepositToFundArgs),
     
     /// Redeem shares from a fund
@@ -98,6 +162,17 @@ pub enum FundInstruction {
     /// 5. `[writable]` LP's share token account
     /// 6. `[writable]` Share mint PDA
     /// 7. `[]` Token Program
+    /// 8. `[writable]` DepositReceipt PDA (burned on full redemption)
+    /// 9. `[writable]` Receipt mint PDA
+    /// 10. `[writable]` Receipt token account PDA
+    /// 
+    /// Accounts 8-10 (any position after 7) are required on a full redemption
+    /// of a position holding a receipt NFT, and may be omitted otherwise.
+    /// If the position opted into a payout allow-list, its PayoutAllowList
+    /// PDA must also be passed (any position after 7). A full redemption of
+    /// an indexed position also needs its `[writable]` LPIndexPage PDA.
+    /// With `memo` set, passing the SPL Memo program (any position after 7)
+    /// also attaches the memo on-chain.
     RedeemFromFund(RedeemFromFundArgs),
     
     // === Trading Operations (30-39) ===
@@ -109,6 +184,13 @@ pub enum FundInstruction {
     /// 1. `[writable]` Fund PDA
     /// 2. `[]` Ledger Program
     /// 3. ... (Ledger Program required accounts)
+    /// 
+    /// With `simulate_only`, runs every check and returns `TradeSimulationResult`
+    /// via return data without calling the Ledger Program.
+    /// 
+    /// While manager risk limits are enabled, the manager's `[writable]`
+    /// ManagerRiskState PDA and the ManagerRiskLimits PDA must also be passed
+    /// (any position after the Ledger accounts).
     TradeFund(TradeFundArgs),
     
     /// Close a position for the fund (manager only)
@@ -118,19 +200,41 @@ pub enum FundInstruction {
     /// 1. `[writable]` Fund PDA
     /// 2. `[]` Ledger Program
     /// 3. ... (Ledger Program required accounts)
+    /// 
+    /// Passing the manager's `[writable]` ManagerRiskState PDA releases the
+    /// closed notional from it.
+    /// 
+    /// With `size_e6 = 0` the position account must be owned by the Ledger
+    /// Program and hold the fund's position in `market_index`; its full size
+    /// is closed.
     CloseFundPosition(CloseFundPositionArgs),
     
     // === Fee Operations (40-49) ===
     
-    /// Collect management and performance fees (manager only)
+    /// Collect management and performance fees (manager, or an authorized
+    /// relayer acting as fee crank when the fund has opted in)
     /// 
     /// Accounts:
-    /// 0. `[signer]` Fund manager
+    /// 0. `[signer]` Fund manager or fee crank (relayer)
     /// 1. `[writable]` Fund PDA
     /// 2. `[writable]` Fund vault PDA
-    /// 3. `[writable]` Manager's USDC account
+    /// 3. `[writable]` Fee destination USDC account (Fund.fee_destination if set,
+    ///    otherwise a manager-owned account)
     /// 4. `[]` Token Program
-    CollectFees,
+    /// 5. `[]` FundConfig PDA (required when signer is a fee crank)
+    /// 
+    /// Funds with a benchmark also need `Fund.benchmark_feed` among the accounts.
+    /// 
+    /// Funds running an LP referral program must pass its `[writable]`
+    /// FundLPReferralProgram PDA; unfunded bounties are withheld from the
+    /// payout and left in the vault for referrers to claim.
+    /// 
+    /// Funds that predate the fee epoch fields are grown on their first
+    /// crystallization: the signer must then be writable and the System
+    /// Program passed.
+    /// 
+    /// Returns `CollectFeesResult` via return data (also for previews).
+    CollectFees(CollectFeesArgs),
     
     // === Admin Operations (50-59) ===
     
@@ -155,6 +259,7 @@ pub enum FundInstruction {
     /// 
     /// Accounts:
     /// 0. `[writable]` Fund PDA
+    /// 1. `[writable]` SharePriceFeed PDA (optional, republished with the new NAV)
     UpdateNAV,
     
     /// Record realized PnL (called by Ledger Program via CPI)
@@ -210,15 +315,30 @@ pub enum FundInstruction {
     /// 3. `[writable]` Fund vault PDA
     /// 4. `[writable]` Destination token account
     /// 5. `[]` Token Program
+    /// 
+    /// When capital is deployed to a yield venue, pass the `[writable]`
+    /// InsuranceYieldVenue PDA and venue deposit account (after 5); the
+    /// vault's gap is recalled from the venue before any remainder is reported.
+    /// 
+    /// During ADL, pass the open episode's `[writable]` ADLEvent PDA (after 5)
+    /// to book the uncovered remainder as socialized; outside ADL it is held
+    /// as pending and moved into the next episode's record.
     CoverShortfall(CoverShortfallArgs),
     
     /// Update hourly snapshot (called by Relayer)
     /// 
+    /// Passing accounts 4-5 also books the income/payout since the last write
+    /// into the current day's InsuranceDailyStats. Once daily stats are on (new
+    /// configs, or legacy ones after their first such call) 4-5 are required
+    /// (InsuranceDailyStatsRequired) so each day's deltas land in that day.
+    /// 
     /// Accounts:
-    /// 0. `[signer]` Authority or Relayer
+    /// 0. `[signer]` Authority or Relayer (`[writable]` pays rent for a new day)
     /// 1. `[]` Fund PDA
     /// 2. `[writable]` InsuranceFundConfig PDA
     /// 3. `[]` Fund vault PDA
+    /// 4. `[writable]` InsuranceDailyStats PDA for the current day (optional until daily stats are on)
+    /// 5. `[]` System Program (optional until daily stats are on)
     UpdateHourlySnapshot,
     
     /// Set ADL in progress status (CPI from Ledger)
@@ -226,6 +346,15 @@ pub enum FundInstruction {
     /// Accounts:
     /// 0. `[signer]` Caller program (Ledger)
     /// 1. `[writable]` InsuranceFundConfig PDA
+    /// 
+    /// Starting an episode may also pass, to create its ADLEvent record:
+    /// 2. `[writable]` ADLEvent PDA (episode = adl_trigger_count + 1)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System Program
+    /// 5. `[]` Insurance fund vault (trigger metrics)
+    /// 6. `[]` Insurance fund PDA (NAV TWAP value for the trigger reason)
+    /// 
+    /// Ending an episode may pass 2. `[writable]` its ADLEvent PDA to close it.
     SetADLInProgress(SetADLInProgressArgs),
     
     /// Check ADL trigger conditions (view)
@@ -246,6 +375,18 @@ pub enum FundInstruction {
     /// 3. `[writable]` Vault Token Account (source of fees)
     /// 4. `[writable]` Insurance Fund Vault (destination)
     /// 5. `[]` Token Program
+    /// 
+    /// 1 must be the insurance fund of 2 and 4 its vault; 3, 4 (and 9) must
+    /// hold the same mint, otherwise nothing moves.
+    /// 
+    /// Optional referral routing (trader has a live ReferralBinding): the
+    /// referrer share of the fee goes to the referral reward vault and is
+    /// accrued on the link / binding / config in the same step; the rest goes
+    /// to the Insurance Fund.
+    /// 6. `[writable]` ReferralConfig PDA
+    /// 7. `[writable]` ReferralBinding PDA (trader)
+    /// 8. `[writable]` ReferralLink PDA
+    /// 9. `[writable]` Referral Reward Vault PDA
     AddTradingFee(AddTradingFeeArgs),
     
     /// Redeem shares from Insurance Fund (with special rules)
@@ -264,6 +405,10 @@ pub enum FundInstruction {
     /// 6. `[writable]` LP's share token account
     /// 7. `[writable]` Share mint PDA
     /// 8. `[]` Token Program
+    /// 9. `[]` PayoutAllowList PDA (required if the position opted in)
+    /// 
+    /// A full redemption of an indexed position also needs its `[writable]`
+    /// LPIndexPage PDA (any position after 8).
     RedeemFromInsuranceFund(RedeemFromInsuranceFundArgs),
     
     // === Square Platform Operations (90-99) ===
@@ -280,10 +425,28 @@ pub enum FundInstruction {
     /// 1. `[writable]` SquarePaymentRecord PDA
     /// 2. `[writable]` Payer's Vault (source)
     /// 3. `[writable]` Creator's Vault (destination for creator share)
-    /// 4. `[writable]` Square Fund vault (destination for platform share)
+    /// 4. `[writable]` Square Fund vault PDA (destination for platform share; the treasury vault when 8 is passed),
+    ///    in the same mint as 2
     /// 5. `[]` Vault Program
     /// 6. `[]` Token Program
     /// 7. `[]` System Program
+    /// 8. `[writable]` Treasury PDA (optional, routes the platform share to the Treasury)
+    /// 
+    /// Required (anywhere after 7): the payer's `[writable]` SquarePayerNonce
+    /// PDA, created on first payment. 1 is derived from (payer, args.nonce).
+    /// 
+    /// Required (anywhere after 7): the payer's `[writable]` SquarePaymentHistory
+    /// PDA and its head `[writable]` SquarePaymentHistoryPage — plus the next
+    /// page when the head is full. Created on first payment.
+    /// 
+    /// Optional (anywhere after 7): `[writable]` SquareFundStats PDA — required
+    /// when 4 is the program-owned square fund vault — and the creator's
+    /// `[writable]` SquareCreatorStats PDA (created on first payment; payer
+    /// must then be writable).
+    /// 
+    /// If the creator has an active RevenueSplit, pass its PDA and every
+    /// recipient's `[writable]` token account (anywhere after 7); the creator
+    /// share then goes to the recipients and 3 is not used.
     SquarePayment(SquarePaymentArgs),
     
     // === Referral Operations (100-119) ===
@@ -315,12 +478,19 @@ pub enum FundInstruction {
     /// 3. `[writable]` ReferralLink (update stats)
     /// 4. `[writable]` ReferralConfig (update stats)
     /// 5. `[]` System Program
+    /// 
+    /// An existing binding can be replaced only when it has expired and
+    /// ReferralConfig.rebind_policy is AfterExpiry.
+    /// 
+    /// Optional last account: `[writable]` RentReserve PDA (sponsors the binding's rent)
     BindReferral,
     
     /// Record a referral trade (CPI from Ledger)
     /// 
     /// Records the trade and calculates rewards.
     /// Actual token transfers happen in Ledger/Vault.
+    /// Expired bindings (reward_validity_secs) earn no rewards; the call
+    /// succeeds without recording so the caller's trade is not affected.
     /// 
     /// Accounts:
     /// 0. `[signer]` Caller program (Ledger)
@@ -360,30 +530,62 @@ pub enum FundInstruction {
     
     /// Relayer 版本的 DepositToFund
     /// 
+    /// 存款金额计入 FundConfig.relayer_limits (单笔 / 每日限额)，超限返回
+    /// RelayerLimitExceeded (附带 RelayerStats 时见下文)。
+    /// 
+    /// 所有 Relayer* 指令都携带按用户严格递增的 `nonce`，
+    /// 由 RelayerNonce PDA 记录，重复提交会被拒绝。
+    /// 
+    /// 五个 Relayer* 指令 (DepositToFund / RedeemFromFund /
+    /// RedeemFromInsuranceFund / SquarePayment / BindReferral) 共用同一用户的
+    /// 一个计数器 (PDA 不区分操作)：Relayer 须按提交顺序为该用户的所有操作
+    /// 分配递增 nonce，较小 nonce 的请求晚到会被拒绝。
+    /// 
+    /// 所有 Relayer* 指令都可在账户末尾附带 `[writable]` RelayerStats PDA
+    /// (relayer)，用于累计统计。附带时超出限额的请求会记为拒绝并无效果地结束，
+    /// 而不是返回 RelayerLimitExceeded。
+    /// 
     /// Accounts:
-    /// 0. `[signer]` Admin/Relayer
-    /// 1. `[writable]` Fund PDA
-    /// 2. `[writable]` Fund vault PDA
-    /// 3. `[writable]` User's Vault Account (Vault Program)
-    /// 4. `[writable]` LP Position PDA
-    /// 5. `[writable]` LP's share token account
-    /// 6. `[writable]` Share mint PDA
-    /// 7. `[]` VaultConfig
-    /// 8. `[]` Vault Program
-    /// 9. `[]` Token Program
-    /// 10. `[]` System Program
+    /// 0. `[signer, writable]` Admin/Relayer (pays rent for a new RelayerNonce)
+    /// 1. `[writable]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    /// 3. `[writable]` Fund vault PDA
+    /// 4. `[writable]` User's Vault Account (Vault Program)
+    /// 5. `[writable]` LP Position PDA
+    /// 6. `[writable]` LP's share token account
+    /// 7. `[writable]` Share mint PDA
+    /// 8. `[]` VaultConfig
+    /// 9. `[]` Vault Program
+    /// 10. `[]` Token Program
+    /// 11. `[]` System Program
+    /// 12. `[writable]` RelayerNonce PDA (user)
     RelayerDepositToFund(RelayerDepositToFundArgs),
     
     /// Relayer 版本的 RedeemFromFund
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin/Relayer
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` RelayerNonce PDA (user)
+    /// 3. `[]` System Program
+    /// 4. `[]` LP Position PDA
+    /// 5. `[]` Payout destination (user's Vault account)
+    /// 6. `[]` PayoutAllowList PDA (required if the position opted in)
     RelayerRedeemFromFund(RelayerRedeemFromFundArgs),
     
     /// Relayer 版本的 RedeemFromInsuranceFund
+    /// 
+    /// Accounts: same as RelayerRedeemFromFund
     RelayerRedeemFromInsuranceFund(RelayerRedeemFromInsuranceFundArgs),
     
     /// Relayer 版本的 SquarePayment
+    /// 
+    /// Accounts: 0-3 of RelayerRedeemFromFund (nonce keyed by payer_wallet)
     RelayerSquarePayment(RelayerSquarePaymentArgs),
     
     /// Relayer 版本的 BindReferral
+    /// 
+    /// Accounts: 0-3 of RelayerRedeemFromFund
     RelayerBindReferral(RelayerBindReferralArgs),
     
     // =========================================================================
@@ -451,16 +653,26 @@ pub enum FundInstruction {
     /// 2. `[writable]` Prediction Market Fee Vault
     /// 3. `[writable]` Source Token Account
     /// 4. `[]` Token Program
+    /// 
+    /// With `maker` set (records maker volume for the current epoch):
+    /// 5. `[writable]` PMMakerEpoch PDA (current epoch)
+    /// 6. `[writable]` PMMakerVolume PDA (maker, current epoch)
+    /// 7. `[signer, writable]` Payer (rent for new epoch / volume accounts)
+    /// 8. `[]` System Program
     CollectPredictionMarketTradingFee(CollectPredictionMarketTradingFeeArgs),
     
     /// 发放预测市场做市商奖励 (Admin or CPI)
     /// 
+    /// 奖励 = 周期奖励池 × 做市商周期做市量 / 周期做市总量, 周期结束后每个做市商发放一次
+    /// 
     /// Accounts:
     /// 0. `[signer]` Authority or Caller
     /// 1. `[writable]` PredictionMarketFeeConfig
     /// 2. `[writable]` Prediction Market Fee Vault
-    /// 3. `[writable]` Maker's Token Account
+    /// 3. `[writable]` Maker's Token Account (owned by the maker)
     /// 4. `[]` Token Program
+    /// 5. `[writable]` PMMakerEpoch PDA
+    /// 6. `[writable]` PMMakerVolume PDA
     DistributePredictionMarketMakerReward(DistributePredictionMarketMakerRewardArgs),
     
     /// 发放预测市场创建者分成 (CPI)
@@ -539,483 +751,3232 @@ pub enum FundInstruction {
     /// 0. `[signer]` Authority
     /// 1. `[writable]` SpotTradingFeeConfig
     UpdateSpotTradingFeeConfig(UpdateSpotTradingFeeConfigArgs),
-}
 
-// === Argument Structs ===
+    // =========================================================================
+    // Deposit Receipt Operations
+    // =========================================================================
 
-/// Arguments for Initialize instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct InitializeArgs {
-    /// Vault Program ID
-    pub vault_program: Pubkey,
-    /// Ledger Program ID
-    pub ledger_program: Pubkey,
-}
+    /// Enable/disable deposit receipt NFTs for a fund (manager only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    SetDepositReceipts(SetDepositReceiptsArgs),
 
-/// Arguments for CreateFund instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CreateFundArgs {
-    /// Fund name (max 32 characters)
-    pub name: String,
-    /// Management fee in basis points (e.g., 200 = 2%)
-    pub management_fee_bps: u32,
-    /// Performance fee in basis points (e.g., 2000 = 20%)
-    pub performance_fee_bps: u32,
-    /// Use High Water Mark for performance fee
-    pub use_high_water_mark: bool,
-    /// Fee collection interval in seconds (0 = default 1 day)
-    pub fee_collection_interval: i64,
-}
+    // =========================================================================
+    // LP Beneficiary Operations
+    // =========================================================================
 
-/// Arguments for UpdateFund instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct UpdateFundArgs {
-    /// New fee configuration (optional)
-    pub fee_config: Option<FeeConfig>,
-}
+    /// Designate (or clear) a transfer-on-death beneficiary for an LP position
+    /// 
+    /// Approves the Fund PDA as delegate for the position's current shares on
+    /// the LP's share token account so they can be moved on a later claim;
+    /// clearing the beneficiary revokes it. Call again after adding shares to
+    /// refresh the approval. Also counts as activity and resets the inactivity clock.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` LP investor
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` LP Position PDA
+    /// 3. `[writable]` LP's share token account
+    /// 4. `[]` Token Program
+    SetLPBeneficiary(SetLPBeneficiaryArgs),
 
-/// Arguments for SetFundOpen instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SetFundOpenArgs {
-    /// Whether the fund is open for deposits
-    pub is_open: bool,
-}
+    /// Claim an inactive LP position as its designated beneficiary
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Beneficiary
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    /// 3. `[writable]` Investor's LP Position PDA
+    /// 4. `[writable]` Beneficiary's LP Position PDA
+    /// 5. `[writable]` Investor's share token account
+    /// 6. `[writable]` Beneficiary's share token account
+    /// 7. `[]` Token Program
+    /// 8. `[]` System Program
+    /// 
+    /// 5 must be the investor's share account still delegating at least the
+    /// position's shares to the Fund PDA (else BeneficiaryApprovalMissing).
+    /// 
+    /// Also `[writable]`: the investor position's LPIndexPage PDA (if indexed)
+    /// and the fund's last LPIndexPage PDA (plus the next page if full).
+    ClaimAsBeneficiary,
 
-/// Arguments for SetFundPaused instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SetFundPausedArgs {
-    /// Whether the fund is paused
-    pub is_paused: bool,
-}
+    /// Set the inactivity period required before beneficiary claims (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[writable]` FundConfig PDA
+    SetBeneficiaryInactivityPeriod(SetBeneficiaryInactivityPeriodArgs),
 
-/// Arguments for DepositToFund instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct DepositToFundArgs {
-    /// Amount to deposit (in USDC, 6 decimals)
-    pub amount: u64,
-}
+    // =========================================================================
+    // Fund Creation Limits
+    // =========================================================================
 
-/// Arguments for RedeemFromFund instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RedeemFromFundArgs {
-    /// Number of shares to redeem
-    pub shares: u64,
-}
+    /// Update per-manager fund creation limits and fee (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[writable]` FundConfig PDA
+    UpdateFundCreationLimits(UpdateFundCreationLimitsArgs),
 
-/// Arguments for TradeFund instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct TradeFundArgs {
-    /// Market index
-    pub market_index: u8,
-    /// Side (0 = Long, 1 = Short)
-    pub side: u8,
-    /// Position size (in e6)
-    pub size_e6: u64,
-    /// Entry price (in e6)
-    pub price_e6: u64,
-    /// Leverage (1-100)
-    pub leverage: u8,
-    /// Maximum slippage in basis points
-    pub max_slippage_bps: u32,
-}
+    // =========================================================================
+    // Fund Listing Approval
+    // =========================================================================
 
-/// Arguments for CloseFundPosition instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CloseFundPositionArgs {
-    /// Market index
-    pub market_index: u8,
-    /// Close size (in e6, 0 = close all)
-    pub size_e6: u64,
-    /// Exit price (in e6)
-    pub price_e6: u64,
-}
+    /// Require (or stop requiring) admin approval for new fund listings (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[writable]` FundConfig PDA
+    SetListingApprovalRequired(SetListingApprovalRequiredArgs),
 
-/// Arguments for UpdateAuthority instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct UpdateAuthorityArgs {
-    /// New authority public key
-    pub new_authority: Pubkey,
-}
+    /// Approve or reject a pending fund listing (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    ApproveFundListing(ApproveFundListingArgs),
 
-/// Arguments for SetProgramPaused instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SetProgramPausedArgs {
-    /// Whether the program is paused
-    pub is_paused: bool,
-}
+    // =========================================================================
+    // Fee Crank Operations
+    // =========================================================================
 
-/// Arguments for RecordPnL instruction (CPI)
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RecordPnLArgs {
-    /// Realized PnL amount (can be negative)
-    pub pnl_e6: i64,
-}
+    /// Allow/disallow authorized relayers to call CollectFees for this fund (manager only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    SetFeeCrank(SetFeeCrankArgs),
 
-// === Insurance Fund Argument Structs ===
+    /// Set the token account that receives collected fees (manager only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` Fund vault PDA
+    /// 3. `[]` Fee destination USDC token account
+    SetFeeDestination,
 
-/// Arguments for InitializeInsuranceFund instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct InitializeInsuranceFundArgs {
-    /// ADL balance insufficiency trigger threshold (e6)
-    pub adl_trigger_threshold_e6: i64,
-    /// LP redemption delay in seconds
-    pub withdrawal_delay_secs: i64,
-    /// Authorized caller (Ledger Program)
-    pub authorized_caller: Pubkey,
-}
+    // =========================================================================
+    // Rent Reserve Operations
+    // =========================================================================
 
-/// Arguments for AddLiquidationIncome instruction (CPI)
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct AddLiquidationIncomeArgs {
-    /// Liquidation income amount (e6)
-    pub amount_e6: i64,
-}
+    /// Initialize the rent reserve PDA (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, pays rent)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` RentReserve PDA
+    /// 3. `[]` System Program
+    InitializeRentReserve,
 
-/// Arguments for AddADLProfit instruction (CPI)
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct AddADLProfitArgs {
-    /// ADL profit amount (e6)
-    pub amount_e6: i64,
-}
+    /// Enable/disable rent sponsoring (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` RentReserve PDA
+    SetRentReserveEnabled(SetRentReserveEnabledArgs),
 
-/// Arguments for CoverShortfall instruction (CPI)
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CoverShortfallArgs {
-    /// Shortfall amount to cover (e6)
-    pub shortfall_e6: i64,
-}
+    /// Pay sponsored rent back into the reserve
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[writable]` RentReserve PDA
+    /// 2. `[]` System Program
+    ReimburseRentReserve(ReimburseRentReserveArgs),
 
-/// Arguments for SetADLInProgress instruction (CPI)
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SetADLInProgressArgs {
-    /// Whether ADL is in progress
-    pub in_progress: bool,
-}
+    // =========================================================================
+    // Reinitialize Operations (timelocked recovery)
+    // =========================================================================
+
+    /// Schedule a FundConfig reinitialization; executable after the timelock (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, pays rent)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` PendingReinitialize PDA
+    /// 3. `[]` System Program
+    ScheduleReinitialize(ScheduleReinitializeArgs),
+
+    /// Cancel a scheduled reinitialization (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, receives rent)
+    /// 1. `[writable]` PendingReinitialize PDA
+    CancelReinitialize,
+
+    /// Execute a scheduled reinitialization, growing FundConfig if needed (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, pays any rent top-up)
+    /// 1. `[writable]` FundConfig PDA
+    /// 2. `[writable]` PendingReinitialize PDA (closed)
+    /// 3. `[]` System Program
+    Reinitialize,
+
+    // =========================================================================
+    // Account Reallocation
+    // =========================================================================
+
+    /// Grow a program-owned account (FundConfig, Fund, ...) with rent top-up (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, pays rent)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Account to grow
+    /// 3. `[]` System Program
+    ReallocAccount(ReallocAccountArgs),
+
+    // =========================================================================
+    // Payout Allow-list Operations
+    // =========================================================================
+
+    /// Register a payout address for an LP position
+    /// 
+    /// The first registration creates the PayoutAllowList and opts the
+    /// position in: from then on redemptions must pay to an active address.
+    /// New addresses activate after PayoutAllowList::ACTIVATION_DELAY_SECS.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` LP investor (pays rent)
+    /// 1. `[writable]` LP Position PDA (grown if it predates the allow-list)
+    /// 2. `[writable]` PayoutAllowList PDA
+    /// 3. `[]` System Program
+    RegisterPayoutAddress(PayoutAddressArgs),
+
+    /// Remove a payout address (effective immediately; the position stays opted in)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` LP investor
+    /// 1. `[]` LP Position PDA
+    /// 2. `[writable]` PayoutAllowList PDA
+    RemovePayoutAddress(PayoutAddressArgs),
+
+    // =========================================================================
+    // Bulk NAV Operations
+    // =========================================================================
+
+    /// Update NAV for up to MAX_NAV_MULTI_FUNDS funds in one transaction (can be called by anyone)
+    /// 
+    /// Funds that fail validation are skipped; processing stops early when
+    /// the remaining compute budget runs low. Returns `UpdateNAVMultiResult`
+    /// via return data.
+    /// 
+    /// Accounts (repeated per fund):
+    /// 0. `[writable]` Fund PDA
+    /// 1. `[]` Fund vault PDA
+    UpdateNAVMulti,
+
+    // =========================================================================
+    // System Fund Operations
+    // =========================================================================
+
+    /// Mark a fund as the Insurance Fund and/or a system fund (Admin only)
+    /// 
+    /// InitializeInsuranceFund sets both flags; this backfills funds created
+    /// before the flags existed or registers other platform-owned funds.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    SetSystemFundFlags(SetSystemFundFlagsArgs),
+
+    // =========================================================================
+    // LP Index Operations
+    // =========================================================================
+
+    /// Add an existing LP position to its fund's LPIndexPage list (permissionless)
+    /// 
+    /// Backfills positions opened before LP indexing existed. New positions
+    /// are indexed automatically on first deposit.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer (page rent / position realloc)
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` LP Position PDA
+    /// 3. `[]` System Program
+    /// 4. `[writable]` Last LPIndexPage PDA (plus the next page when the last is full)
+    IndexLPPosition,
+
+    // =========================================================================
+    // LP Distribution Operations
+    // =========================================================================
+
+    /// Pay a fixed USDC amount per share to every LP (Fund manager only)
+    /// 
+    /// The first call starts a round (budget = amount × total shares, which
+    /// the vault must hold) and blocks deposits/redemptions until it ends.
+    /// Each call then pays the LPs listed on one LPIndexPage from the
+    /// LPDistribution cursor onwards; repeat with the same amount until the
+    /// last page is done. Payouts are return of capital and lower NAV.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` Fund vault PDA
+    /// 3. `[writable]` LPDistribution PDA
+    /// 4. `[]` Token Program
+    /// 5. `[]` System Program
+    /// 6. `[]` LPIndexPage PDA at the cursor page (omit if the fund has no pages)
+    /// 7. Pairs of (`[writable]` LP Position PDA, `[writable]` LP's USDC account)
+    ///    in page order, starting at the cursor entry
+    DistributeToLPs(DistributeToLPsArgs),
+
+    // =========================================================================
+    // Governance Operations
+    // =========================================================================
+
+    /// Open a share-weighted proposal (manager, or LP above the share threshold)
+    /// 
+    /// Shares are snapshotted at creation; see `Proposal`.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Proposer (pays rent)
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` Proposal PDA
+    /// 3. `[]` System Program
+    /// 4. `[]` Proposer's LP Position PDA (required unless the proposer is the manager)
+    CreateProposal(CreateProposalArgs),
+
+    /// Vote on a proposal with an LP position's snapshot shares
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` LP investor (pays rent)
+    /// 1. `[writable]` Proposal PDA
+    /// 2. `[]` LP Position PDA
+    /// 3. `[writable]` VoteRecord PDA
+    /// 4. `[]` System Program
+    CastVote(CastVoteArgs),
+
+    /// Settle a proposal after voting ends (permissionless)
+    /// 
+    /// Passed FeeChange proposals update the fee config; passed WindDown
+    /// proposals close the fund to new deposits. ReplaceManager only records
+    /// the approval.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Caller
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` Proposal PDA
+    FinalizeProposal,
+
+    // =========================================================================
+    // Share Price Feed Operations
+    // =========================================================================
+
+    /// Create the oracle-style SharePriceFeed for a fund (permissionless)
+    /// 
+    /// Publishes the current NAV; later updates come from UpdateNAV.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` SharePriceFeed PDA
+    /// 3. `[]` System Program
+    InitializeSharePriceFeed,
+
+    // =========================================================================
+    // Share Lock Operations
+    // =========================================================================
+
+    /// Pledge part of an LP position to a lienholder (e.g. a lending protocol PDA)
+    /// 
+    /// Locked shares cannot be redeemed or claimed by a beneficiary until the
+    /// lienholder unlocks them. A position has at most one lienholder.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` LP investor
+    /// 1. `[signer]` Lienholder
+    /// 2. `[writable]` LP Position PDA (grown if it predates share locks)
+    /// 3. `[]` System Program
+    LockShares(ShareLockArgs),
+
+    /// Release pledged shares (lienholder only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Lienholder
+    /// 1. `[writable]` LP Position PDA
+    UnlockShares(ShareLockArgs),
+
+    // =========================================================================
+    // Trade Intent Operations
+    // =========================================================================
+
+    /// Create a limit-order style trade intent (Fund manager only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager (pays rent)
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` TradeIntent PDA
+    /// 3. `[]` System Program
+    CreateTradeIntent(CreateTradeIntentArgs),
+
+    /// Cancel (or clean up an executed) trade intent and reclaim its rent (Fund manager only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` TradeIntent PDA
+    CancelTradeIntent,
+
+    /// Execute a trade intent when the price satisfies its limit (whitelisted keeper)
+    /// 
+    /// Keepers are the FundConfig authorized relayers. Runs the TradeFund
+    /// risk checks, then opens the position through the Ledger Program.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Keeper
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    /// 3. `[writable]` TradeIntent PDA
+    /// 4. `[]` Ledger Program
+    /// 5. `[writable]` Position PDA
+    /// 6. `[writable]` UserAccount (Fund's Vault account)
+    /// 7. `[]` VaultConfig
+    /// 8. `[writable]` LedgerConfig
+    /// 9. `[writable]` UserStats
+    /// 10. `[]` Vault Program
+    /// 11. `[]` System Program
+    /// 
+    /// Manager risk accounts as for TradeFund.
+    ExecuteTradeIntent(ExecuteTradeIntentArgs),
+
+    // =========================================================================
+    // Feature Flags
+    // =========================================================================
+
+    /// Enable or disable subsystems (admin only)
+    /// 
+    /// `features` is a mask of FEATURE_* bits. Instructions of a disabled
+    /// subsystem are rejected by the dispatcher; feature-gated instructions
+    /// must include the FundConfig PDA among their accounts.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[writable]` FundConfig PDA
+    SetFeaturesEnabled(SetFeaturesEnabledArgs),
+
+    // =========================================================================
+    // Redemption Fee Curve
+    // =========================================================================
+
+    /// Configure the dynamic redemption fee charged while vault cash is low (manager only)
+    /// 
+    /// Grows a legacy Fund account to the current size when needed.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager (pays rent for the realloc)
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` System Program
+    SetRedemptionFeeCurve(SetRedemptionFeeCurveArgs),
+
+    // =========================================================================
+    // Referral Binding Expiry
+    // =========================================================================
+
+    /// Mark a referral binding past reward_validity_secs as expired (anyone)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Caller
+    /// 1. `[]` ReferralConfig PDA
+    /// 2. `[writable]` ReferralBinding PDA
+    ExpireReferralBinding,
+
+    // =========================================================================
+    // Prediction Market Category Fees
+    // =========================================================================
+
+    /// 设置或清除某个预测市场分类的覆盖费率 (Admin)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority
+    /// 1. `[writable]` PredictionMarketFeeConfig
+    SetPredictionMarketCategoryFees(SetPredictionMarketCategoryFeesArgs),
+
+    // =========================================================================
+    // Treasury
+    // =========================================================================
+
+    /// Create the protocol Treasury around an existing token vault (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, pays rent)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Treasury PDA
+    /// 3. `[]` Treasury vault (token account owned by the Treasury PDA)
+    /// 4. `[]` System Program
+    InitializeTreasury,
+
+    /// Schedule a treasury withdrawal; executable after the timelock (admin only)
+    /// 
+    /// Rescheduling replaces any pending withdrawal and restarts the timelock.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Treasury PDA
+    ScheduleTreasuryWithdrawal(ScheduleTreasuryWithdrawalArgs),
+
+    /// Cancel the pending treasury withdrawal (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Treasury PDA
+    CancelTreasuryWithdrawal,
+
+    /// Execute the pending treasury withdrawal after the timelock (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Treasury PDA
+    /// 3. `[writable]` Treasury vault
+    /// 4. `[writable]` Destination token account (as scheduled)
+    /// 5. `[]` Token Program
+    WithdrawTreasury,
+
+    /// 将预测市场累计协议分成划转至 Treasury (Admin)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (PM fee config admin)
+    /// 1. `[writable]` PredictionMarketFeeConfig
+    /// 2. `[writable]` PredictionMarketFeeVault
+    /// 3. `[writable]` Treasury PDA
+    /// 4. `[writable]` Treasury vault
+    /// 5. `[]` Token Program
+    SweepPredictionMarketProtocolFees,
+
+    // =========================================================================
+    // Balances Checkpoint
+    // =========================================================================
+
+    /// Post a merkle root of the fund's (investor, shares) balances at a snapshot
+    /// (fund manager or admin)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Operator (pays rent on the first commit)
+    /// 1. `[]` Fund PDA
+    /// 2. `[]` FundConfig PDA
+    /// 3. `[writable]` BalancesCheckpoint PDA
+    /// 4. `[]` System Program
+    CommitBalancesRoot(CommitBalancesRootArgs),
+
+    /// Check an (investor, shares) inclusion proof against the committed root (view)
+    /// 
+    /// Fails with InvalidBalanceProof; returns BalanceProofResult on success.
+    /// 
+    /// Accounts:
+    /// 0. `[]` Fund PDA
+    /// 1. `[]` BalancesCheckpoint PDA
+    VerifyBalanceProof(VerifyBalanceProofArgs),
+
+    // =========================================================================
+    // Pause Expiry
+    // =========================================================================
+
+    /// Set the default max pause duration and the bound for fund overrides (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[writable]` FundConfig PDA
+    SetPausePolicy(SetPausePolicyArgs),
+
+    /// Override the fund's max pause duration within the admin bound (manager only)
+    /// 
+    /// Applies from the next pause; a running pause keeps its deadline.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager (pays rent for the realloc)
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` FundConfig PDA
+    /// 3. `[]` System Program
+    SetFundMaxPause(SetFundMaxPauseArgs),
+
+    // =========================================================================
+    // Full Exit
+    // =========================================================================
+
+    /// Redeem the whole LP position, close it and return its rent to the LP
+    /// 
+    /// The last LP out receives the full remaining fund value (no NAV dust).
+    /// Rent the RentReserve sponsored goes back to the reserve instead, which
+    /// must then be passed writable among the extra accounts.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` LP investor (receives the LP Position rent)
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` Fund vault PDA
+    /// 3. `[writable]` LP's USDC account
+    /// 4. `[writable]` LP Position PDA (closed)
+    /// 5. `[writable]` LP's share token account
+    /// 6. `[writable]` Share mint PDA
+    /// 7. `[]` Token Program
+    /// 8. `[writable]` DepositReceipt PDA (burned)
+    /// 9. `[writable]` Receipt mint PDA
+    /// 10. `[writable]` Receipt token account PDA
+    /// 
+    /// Accounts 8-10 (any position after 7) are required if the position
+    /// holds a receipt NFT, and may be omitted otherwise.
+    /// 
+    /// Same extra accounts as RedeemFromFund: PayoutAllowList PDA if opted in,
+    /// `[writable]` LPIndexPage PDA if indexed, SPL Memo program for `memo`,
+    /// the fund's hook accounts if it has a hook, plus the `[writable]`
+    /// RentReserve PDA if it paid the position's rent.
+    RedeemAll(RedeemAllArgs),
+
+    // =========================================================================
+    // Insurance Health
+    // =========================================================================
+
+    /// Read-only insurance fund health summary, returned as `InsuranceHealthResult`
+    /// 
+    /// Income/outflow cover the current UTC day: the day's InsuranceDailyStats
+    /// (if passed) plus the amounts booked since its last write.
+    /// 
+    /// Accounts:
+    /// 0. `[]` InsuranceFundConfig PDA
+    /// 1. `[]` Insurance Fund PDA
+    /// 2. `[]` Insurance Fund vault
+    /// 3. `[]` InsuranceDailyStats PDA for the current day (optional)
+    GetInsuranceHealth(GetInsuranceHealthArgs),
+
+    // =========================================================================
+    // Manager Risk
+    // =========================================================================
+
+    /// Set the per-manager caps applied across all of a manager's funds (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, pays rent on first call)
+    /// 1. `[writable]` FundConfig PDA
+    /// 2. `[writable]` ManagerRiskLimits PDA
+    /// 3. `[]` System Program
+    SetManagerRiskLimits(SetManagerRiskLimitsArgs),
+
+    /// Create a manager's aggregate exposure account (anyone may pay)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[]` Fund manager wallet
+    /// 2. `[writable]` ManagerRiskState PDA
+    /// 3. `[]` System Program
+    InitializeManagerRiskState,
+
+    // =========================================================================
+    // NAV Floor
+    // =========================================================================
+
+    /// Resolve a fund's wind-down review (admin only)
+    /// 
+    /// A fund enters review when its NAV falls below MIN_DEPOSIT_NAV_E6;
+    /// deposits stay blocked until resolved. `wind_down` closes the fund to
+    /// new deposits, otherwise NAV must be back above the floor.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    ResolveWindDownReview(ResolveWindDownReviewArgs),
+
+    // =========================================================================
+    // Split Fund Creation
+    // =========================================================================
+
+    /// Step 1 of CreateFund split across transactions: book the fund in
+    /// FundConfig / ManagerProfile and create the Fund PDA in setup-pending
+    /// state (deposits blocked). Idempotent: a retry against the manager's
+    /// own pending fund is a no-op.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` FundConfig PDA
+    /// 3. `[]` System Program
+    /// 4. `[writable]` ManagerProfile PDA
+    /// 
+    /// Only if a creation fee is set:
+    /// 5. `[writable]` Treasury PDA
+    /// 6. `[writable]` Treasury vault
+    /// 7. `[writable]` Manager's USDC account (pays the fee)
+    /// 8. `[]` Token Program
+    /// 
+    /// `initial_deposit_amount` must be unset; deposit after FinalizeFund instead.
+    CreateFundAccount(CreateFundArgs),
+
+    /// Step 2: create the fund's share mint (no-op if already initialized)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` Share mint PDA
+    /// 3. `[]` Token Program
+    /// 4. `[]` System Program
+    /// 5. `[]` Rent Sysvar
+    InitShareMint,
+
+    /// Step 3: create the fund's USDC vault (no-op if already initialized)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` Fund vault PDA (token account)
+    /// 3. `[]` USDC mint
+    /// 4. `[]` Token Program
+    /// 5. `[]` System Program
+    /// 6. `[]` Rent Sysvar
+    InitFundVault,
+
+    /// Step 4: check the share mint and vault are in place and clear the
+    /// setup-pending flag
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` Fund vault PDA
+    /// 3. `[]` Share mint PDA
+    FinalizeFund,
+
+    // =========================================================================
+    // Referral Reward Vault
+    // =========================================================================
+
+    /// Create the referral reward vault funded by AddTradingFee (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (ReferralConfig authority)
+    /// 1. `[]` ReferralConfig PDA
+    /// 2. `[writable]` Referral Reward Vault PDA (token account, owner = ReferralConfig)
+    /// 3. `[]` USDC Mint
+    /// 4. `[]` Token Program
+    /// 5. `[]` System Program
+    /// 6. `[]` Rent Sysvar
+    InitializeReferralRewardVault,
+
+    // =========================================================================
+    // Fund Benchmark
+    // =========================================================================
+
+    /// Set or clear a fund's benchmark (Fund manager only)
+    /// 
+    /// The benchmark is any SharePriceFeed-layout price feed owned by this
+    /// program other than the fund's own. Its price is recorded now and at
+    /// every performance fee crystallization; with `relative_fee` the
+    /// performance fee is charged only on outperformance. Rejected while a
+    /// performance fee is accrued.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` System Program
+    /// 3. `[]` Benchmark price feed (omit to clear the benchmark)
+    SetFundBenchmark(SetFundBenchmarkArgs),
+
+    // =========================================================================
+    // NAV Blackout
+    // =========================================================================
+
+    /// Set the bounds for per-fund blackout windows (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` BlackoutPolicy PDA
+    /// 3. `[]` System Program
+    SetBlackoutPolicy(SetBlackoutPolicyArgs),
+
+    /// Configure a fund's deposit/redemption blackout (Fund manager only)
+    /// 
+    /// After a RecordPnL with |PnL| >= threshold of fund value, deposits and
+    /// redemptions are blocked for `window_secs`. `window_secs = 0` disables
+    /// new blackouts; a running one is not lifted.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` BlackoutPolicy PDA
+    /// 3. `[]` System Program
+    SetFundBlackout(SetFundBlackoutArgs),
+
+    // =========================================================================
+    // Share Freeze
+    // =========================================================================
+
+    /// Freeze a share token account for compliance (fund manager or admin)
+    /// 
+    /// CPI to spl_token FreezeAccount with the Fund PDA as freeze authority
+    /// (after a rotation, pass the ShareMintAuthority PDA after 7); the reason
+    /// code is kept in a ShareFreezeRecord PDA.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager or admin
+    /// 1. `[]` Fund PDA
+    /// 2. `[]` Share mint
+    /// 3. `[writable]` Share token account to freeze
+    /// 4. `[writable]` ShareFreezeRecord PDA
+    /// 5. `[]` FundConfig PDA
+    /// 6. `[]` Token Program
+    /// 7. `[]` System Program
+    FreezeInvestorShares(FreezeInvestorSharesArgs),
+
+    /// Thaw a frozen share token account (fund manager or admin)
+    /// 
+    /// A freeze applied by the admin can only be lifted by the admin. After a
+    /// share authority rotation, pass the ShareMintAuthority PDA after 6.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Fund manager or admin
+    /// 1. `[]` Fund PDA
+    /// 2. `[]` Share mint
+    /// 3. `[writable]` Share token account to thaw
+    /// 4. `[writable]` ShareFreezeRecord PDA
+    /// 5. `[]` FundConfig PDA
+    /// 6. `[]` Token Program
+    ThawInvestorShares,
+
+    // =========================================================================
+    // Native SOL Instructions
+    // =========================================================================
+
+    /// Deposit native SOL into a wSOL-denominated fund
+    /// 
+    /// Lamports are transferred straight into the wSOL vault and wrapped with
+    /// `sync_native`; `amount` is in lamports and is accounted in micro-SOL
+    /// (dust below 1000 lamports stays with the fund).
+    /// 
+    /// Accounts: same as DepositToFund, except 3 is unused (pass the
+    /// investor) and 0 must be writable.
+    DepositSolToFund(DepositToFundArgs),
+
+    /// Redeem shares from a wSOL-denominated fund and unwrap the proceeds
+    /// 
+    /// Pays out to the investor's wSOL account (3), then closes it so the
+    /// lamports land in the investor's wallet. Use a dedicated wSOL account,
+    /// it is closed by this instruction.
+    /// 
+    /// Accounts: same as RedeemFromFund, except 0 must be writable.
+    RedeemSolFromFund(RedeemFromFundArgs),
+
+    // =========================================================================
+    // Insurance Yield Instructions
+    // =========================================================================
+
+    /// Whitelist the yield venue for idle insurance fund capital (Admin only)
+    /// 
+    /// The venue deposit account must be a token account in the insurance
+    /// vault's mint with the insurance Fund PDA as authority, so capital can
+    /// always be recalled. The venue can only change with nothing deployed.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[]` Insurance Fund PDA
+    /// 3. `[writable]` InsuranceYieldVenue PDA
+    /// 4. `[]` Venue deposit token account
+    /// 5. `[]` Insurance fund vault
+    /// 6. `[]` System Program
+    SetInsuranceYieldVenue(SetInsuranceYieldVenueArgs),
+
+    /// Deploy idle insurance capital to the whitelisted venue (Admin only)
+    /// 
+    /// Outstanding principal may not exceed `max_deploy_bps` of vault +
+    /// deployed capital.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Insurance Fund PDA
+    /// 3. `[writable]` InsuranceYieldVenue PDA
+    /// 4. `[writable]` Insurance fund vault
+    /// 5. `[writable]` Venue deposit token account
+    /// 6. `[]` Token Program
+    /// 7. `[]` System Program (grows a legacy Fund account)
+    DeployInsuranceCapital(InsuranceCapitalArgs),
+
+    /// Recall insurance capital from the venue back to the vault (Admin only)
+    /// 
+    /// Capped at the venue balance; anything above outstanding principal is
+    /// booked as insurance fund PnL.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Insurance Fund PDA
+    /// 3. `[writable]` InsuranceYieldVenue PDA
+    /// 4. `[writable]` Insurance fund vault
+    /// 5. `[writable]` Venue deposit token account
+    /// 6. `[]` Token Program
+    RecallInsuranceCapital(InsuranceCapitalArgs),
+
+    // =========================================================================
+    // ADL Keeper Instructions
+    // =========================================================================
+
+    /// Check the ADL trigger conditions and, if met, start ADL atomically
+    /// 
+    /// Permissionless: only on-chain state is evaluated (vault balance, 1h
+    /// snapshot, threshold, pending uncovered shortfall). On trigger sets
+    /// is_adl_in_progress and opens the episode's ADLEvent, so the Ledger can
+    /// be invoked in the same transaction. Returns `TriggerADLResult`; a
+    /// no-op (not an error) when nothing triggers or ADL is already running.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Keeper (pays the ADLEvent rent)
+    /// 1. `[]` Insurance Fund PDA
+    /// 2. `[writable]` InsuranceFundConfig PDA
+    /// 3. `[]` Insurance fund vault
+    /// 4. `[writable]` ADLEvent PDA (episode = adl_trigger_count + 1)
+    /// 5. `[]` System Program
+    TriggerADLIfNeeded,
+
+    // =========================================================================
+    // Square Fund Instructions
+    // =========================================================================
+
+    /// Create the SquareFundStats ledger and its program-owned vault (Admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` SquareFundStats PDA
+    /// 3. `[writable]` Square fund vault PDA (owner = SquareFundStats)
+    /// 4. `[]` USDC Mint
+    /// 5. `[]` Token Program
+    /// 6. `[]` System Program
+    /// 7. `[]` Rent Sysvar
+    InitializeSquareFundStats,
+
+    /// Withdraw from the square fund vault against its ledger (Admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` SquareFundStats PDA
+    /// 3. `[writable]` Square fund vault
+    /// 4. `[writable]` Destination token account
+    /// 5. `[]` Token Program
+    WithdrawSquareFund(WithdrawSquareFundArgs),
+
+    // =========================================================================
+    // Revenue Split Instructions
+    // =========================================================================
+
+    /// Register or update the creator's revenue split (Creator only)
+    /// 
+    /// Up to 5 recipient token accounts with bps summing to 10000; an empty
+    /// list turns the split off.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Creator
+    /// 1. `[writable]` RevenueSplit PDA
+    /// 2. `[]` System Program
+    SetRevenueSplit(SetRevenueSplitArgs),
+
+    // =========================================================================
+    // Square Escrow Instructions
+    // =========================================================================
+
+    /// KnowledgePurchase with escrow: the full amount is held until the buyer
+    /// confirms delivery (or the 7-day timeout passes)
+    /// 
+    /// Same args and record/nonce rules as SquarePayment; 3 and 4 are stored
+    /// and must be passed again on release.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer (buyer)
+    /// 1. `[writable]` SquarePaymentRecord PDA
+    /// 2. `[writable]` Payer's token account (source, refund destination)
+    /// 3. `[]` Creator's token account
+    /// 4. `[]` Square fund vault (or Treasury vault)
+    /// 5. `[writable]` SquareEscrow PDA
+    /// 6. `[writable]` Escrow token account (owner = SquareEscrow PDA)
+    /// 7. `[]` Token Program
+    /// 8. `[]` System Program
+    /// 
+    /// Required (anywhere after 8): the payer's `[writable]` SquarePayerNonce PDA
+    /// and SquarePaymentHistory accounts as in SquarePayment.
+    EscrowSquarePayment(SquarePaymentArgs),
+
+    /// Release an escrowed payment to the creator and platform
+    /// 
+    /// The buyer may confirm at any time while held; anyone may release once
+    /// the timeout has passed without a dispute. Payout follows SquarePayment
+    /// (Treasury, SquareFundStats, SquareCreatorStats, RevenueSplit accounts
+    /// may be passed after 6).
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Buyer or keeper (writable if it creates SquareCreatorStats)
+    /// 1. `[writable]` SquareEscrow PDA
+    /// 2. `[writable]` Escrow token account
+    /// 3. `[writable]` Creator's token account
+    /// 4. `[writable]` Square fund vault (or Treasury vault)
+    /// 5. `[]` Token Program
+    /// 6. `[]` System Program
+    ConfirmDelivery,
+
+    /// Buyer disputes an escrowed payment before the timeout
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Buyer
+    /// 1. `[writable]` SquareEscrow PDA
+    RaiseDispute,
+
+    /// Settle a disputed escrow (Admin only): refund the buyer or release
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` SquareEscrow PDA
+    /// 3. `[writable]` Escrow token account
+    /// 4. `[writable]` Buyer's token account (refund)
+    /// 5. `[writable]` Creator's token account
+    /// 6. `[writable]` Square fund vault (or Treasury vault)
+    /// 7. `[]` Token Program
+    /// 8. `[]` System Program
+    ResolveDispute(ResolveDisputeArgs),
+
+    // =========================================================================
+    // Square Tip Token Instructions
+    // =========================================================================
+
+    /// Whitelist / update an SPL token for live tips (Admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` SquareTipToken PDA
+    /// 3. `[]` Token mint
+    /// 4. `[]` Platform token account (receives the platform share, same mint)
+    /// 5. `[]` System Program
+    SetSquareTipToken(SetSquareTipTokenArgs),
+
+    /// Push a tip token's USD price (price authority only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Price authority
+    /// 1. `[writable]` SquareTipToken PDA
+    UpdateSquareTipTokenPrice(UpdateSquareTipTokenPriceArgs),
+
+    /// Live tip in a whitelisted SPL token
+    /// 
+    /// Tokens are split by `creator_share_bps` between the creator and the
+    /// tip token's platform account; the SquarePaymentRecord (LiveDonation)
+    /// carries the USD e6 value at the pushed price. RevenueSplit does not
+    /// apply to token tips.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[writable]` SquarePaymentRecord PDA
+    /// 2. `[writable]` Payer's token account
+    /// 3. `[writable]` Creator's token account
+    /// 4. `[writable]` Platform token account of the tip token
+    /// 5. `[writable]` SquareTipToken PDA
+    /// 6. `[]` Token Program
+    /// 7. `[]` System Program
+    /// 
+    /// Required (anywhere after 7): the payer's `[writable]` SquarePayerNonce
+    /// PDA and SquarePaymentHistory accounts as in SquarePayment.
+    /// Optional: SquareFundStats / SquareCreatorStats as in SquarePayment.
+    SquareTokenTip(SquareTokenTipArgs),
+
+    // =========================================================================
+    // Fund Hook Instructions
+    // =========================================================================
+
+    /// Register or clear a fund's deposit/redemption hook (Manager + Admin)
+    /// 
+    /// A failing hook blocks deposits and redemptions, so the admin co-signs.
+    /// After each deposit/redemption the hook is CPI'd with a FundHookPayload,
+    /// signed by the `["fund_hook_authority", fund]` PDA; callers then pass
+    /// the hook program, that PDA and the hook's own accounts as the last
+    /// accounts.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[signer]` Admin
+    /// 2. `[]` FundConfig PDA
+    /// 3. `[writable]` Fund PDA
+    /// 4. `[]` System Program
+    /// 5. `[]` Hook program (executable; omit when clearing)
+    SetFundHook(SetFundHookArgs),
+
+    // =========================================================================
+    // Share Authority Rotation
+    // =========================================================================
+
+    /// Incident response: move the share mint and freeze authority to the
+    /// next `["share_mint_authority", fund, version]` PDA (Manager + Admin)
+    /// 
+    /// After a rotation, deposits and share freeze/thaw must also pass the
+    /// current ShareMintAuthority PDA (anywhere after their fixed accounts).
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[signer]` Admin
+    /// 2. `[]` FundConfig PDA
+    /// 3. `[writable]` Fund PDA
+    /// 4. `[writable]` Share mint
+    /// 5. `[]` Current authority (the Fund PDA before the first rotation)
+    /// 6. `[]` New ShareMintAuthority PDA (version + 1)
+    /// 7. `[]` Token Program
+    /// 8. `[]` System Program
+    RotateShareMintAuthority,
+
+    // =========================================================================
+    // Fund LP Referral Instructions
+    // =========================================================================
+
+    /// Configure the fund's LP referral bounty (Manager)
+    /// 
+    /// Creates the FundLPReferralProgram PDA on first use. Bounties accrue on
+    /// referred deposits and are funded out of the manager's fee collections.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` FundLPReferralProgram PDA
+    /// 3. `[]` System Program
+    SetFundLPReferralProgram(SetFundLPReferralProgramArgs),
+    
+    /// Bind a referrer for this fund (Referee, before their first deposit)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Referee
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` FundLPReferralProgram PDA
+    /// 3. `[writable]` FundLPReferralBinding PDA (fund, referee)
+    /// 4. `[]` Referee's LP Position PDA (must not exist)
+    /// 5. `[]` System Program
+    BindFundLPReferral(BindFundLPReferralArgs),
+    
+    /// Claim a binding's accrued bounty, up to what fee collections have funded (Referrer)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Referrer
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` FundLPReferralProgram PDA
+    /// 3. `[writable]` FundLPReferralBinding PDA
+    /// 4. `[writable]` Fund vault
+    /// 5. `[writable]` Referrer's USDC account
+    /// 6. `[]` Token Program
+    ClaimFundLPReferralBounty,
+
+    // =========================================================================
+    // Admin Intervention Instructions
+    // =========================================================================
+
+    /// Force a fund's pause state, overriding the manager (Admin)
+    /// 
+    /// Bypasses the manager's toggle cooldown; an admin pause never
+    /// auto-expires. With `lock_manager`, manager pause changes via SetFundState are rejected
+    /// until an admin call clears the lock. Emits ADMIN_FUND_INTERVENTION.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    /// 3. `[]` System Program
+    AdminSetFundPaused(AdminSetFundPausedArgs),
+    
+    /// Force a fund open/closed for deposits, overriding the manager (Admin)
+    /// 
+    /// With `lock_manager`, manager deposit open/close changes via SetFundState
+    /// are rejected until an admin call clears the lock. Emits ADMIN_FUND_INTERVENTION.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    /// 3. `[]` System Program
+    AdminSetFundOpen(AdminSetFundOpenArgs),
+
+    // =========================================================================
+    // Insurance Migration Instructions
+    // =========================================================================
+
+    /// One-time split of V1 insurance income into liquidation vs trading fees (Admin)
+    /// 
+    /// Before migration AddTradingFee keeps booking into
+    /// total_liquidation_income_e6, so the backfill values must sum to it
+    /// exactly; afterwards trading fees accrue to total_trading_fee_e6.
+    /// Combined income and daily-stats baselines are unchanged.
+    /// Emits INSURANCE_CONFIG_MIGRATED_V2.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` InsuranceFundConfig PDA
+    MigrateInsuranceConfigV2(MigrateInsuranceConfigV2Args),
+
+    // =========================================================================
+    // Fund Vault Audit Instructions
+    // =========================================================================
+
+    /// Audit a fund vault's token-account authorities (permissionless crank)
+    /// 
+    /// Requires owner == Fund PDA. A delegate is revoked and a close
+    /// authority held by the Fund PDA is cleared; a foreign owner or close
+    /// authority fails the audit. Emits FUND_VAULT_AUDIT.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Cranker
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` Fund vault
+    /// 3. `[]` Token Program
+    AuditFundVault,
+
+    // =========================================================================
+    // LP Fee Statement Instructions
+    // =========================================================================
+
+    /// Append the fund's latest fee epoch to an LP's fee statement (permissionless)
+    /// 
+    /// Attributes the latest crystallization (CollectFees) pro rata to the
+    /// position's shares. Only valid while the position is unchanged since
+    /// the crystallization, so keepers run it over the LP index right after
+    /// CollectFees. Creates the LPFeeStatement on first use.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[]` Fund PDA
+    /// 2. `[]` LPPosition PDA
+    /// 3. `[writable]` LPFeeStatement PDA
+    /// 4. `[]` System Program
+    /// 5. `[]` Investor's NotificationPrefs PDA (optional, flags the event)
+    RecordLPFeeStatement,
+
+    // =========================================================================
+    // Stable Swap Deposit Instructions
+    // =========================================================================
+
+    /// Create or update a stable-swap deposit route (Admin)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` StableSwapRoute PDA
+    /// 3. `[]` Input mint
+    /// 4. `[]` Output mint (fund vault mint)
+    /// 5. `[]` System Program
+    SetStableSwapRoute(SetStableSwapRouteArgs),
+    
+    /// Deposit an alternate stablecoin, swapped into the vault mint first
+    /// 
+    /// CPIs the route's swap program with `swap_data` (investor signature
+    /// passed through, never the Fund PDA), checks the proceeds against the
+    /// route's max slippage and `min_amount_out`, then deposits them exactly
+    /// like DepositToFund.
+    /// 
+    /// Accounts:
+    /// 0-8. As DepositToFund (3 = investor's output-mint account receiving the swap)
+    /// 9. `[writable]` StableSwapRoute PDA
+    /// 10. `[writable]` Investor's input token account
+    /// 11. `[]` Swap program
+    /// 12..12+swap_account_count. Swap program accounts
+    /// then: optional DepositToFund trailing accounts
+    DepositViaStableSwap(DepositViaStableSwapArgs),
+
+    // =========================================================================
+    // Fee Rebate Instructions
+    // =========================================================================
+
+    /// Credit exchange fee rebates earned by the fund's trading (called by Ledger Program via CPI)
+    /// 
+    /// Raises total value / NAV like realized PnL, tracked separately in
+    /// Fund.total_fee_rebates_e6.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Ledger fund caller PDA (seeds: [FUND_CALLER_SEED] under FundConfig.ledger_program)
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` FundConfig PDA
+    RecordFeeRebate(RecordFeeRebateArgs),
+
+    // =========================================================================
+    // Risk Enforcement Instructions
+    // =========================================================================
+
+    /// Reduce a fund's positions once its manager breaches the risk limits (permissionless keeper)
+    /// 
+    /// The fund's own open notional (Fund.open_notional_e6, mirrored by the
+    /// Ledger) is checked against `max_leverage` times the fund's total value
+    /// and its pro-rata share of the manager's excess over `max_notional_e6`.
+    /// That figure must have been reported by the Ledger within
+    /// RISK_EXPOSURE_MAX_AGE_SECS (FundExposureStale otherwise), and a close
+    /// never exceeds the size of the fund's Ledger position account.
+    /// The first call on a breach only records it on the fund
+    /// (RISK_LIMIT_BREACHED, notifying the manager);
+    /// after RISK_BREACH_GRACE_SECS a call closes the position via the Ledger,
+    /// clamped to the size that brings the book back to the limit, and pays
+    /// the keeper RISK_ENFORCEMENT_KEEPER_FEE_BPS of the closed notional from
+    /// the fund vault. A call with no breach clears a recorded one.
+    /// 
+    /// The exit price must lie within RISK_ENFORCEMENT_MAX_PRICE_DEVIATION_BPS
+    /// of the market's fresh MarkPrice; sizes and the keeper fee use the mark.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Keeper
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` FundConfig PDA
+    /// 3. `[]` ManagerRiskLimits PDA
+    /// 4. `[writable]` ManagerRiskState PDA (fund manager)
+    /// 5. `[writable]` Fund vault
+    /// 6. `[writable]` Keeper USDC account
+    /// 7. `[]` Token Program
+    /// 8. `[]` Ledger Program
+    /// 9. `[writable]` Ledger position
+    /// 10. `[writable]` Ledger user account
+    /// 11. `[]` Vault config
+    /// 12. `[writable]` Insurance fund
+    /// 13. `[writable]` Ledger config
+    /// 14. `[writable]` User stats
+    /// 15. `[]` Vault Program
+    /// 16. `[]` MarkPrice PDA (args.market_index)
+    EnforceRiskLimits(EnforceRiskLimitsArgs),
+
+    // =========================================================================
+    // Notification Preferences Instructions
+    // =========================================================================
+
+    /// Create or update the investor's notification preferences (Investor)
+    /// 
+    /// Stores a hash commitment to an off-chain endpoint and a NOTIFY_*
+    /// bitmask; no endpoint or contact data goes on-chain.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Investor
+    /// 1. `[writable]` NotificationPrefs PDA
+    /// 2. `[]` System Program
+    SetNotificationPrefs(SetNotificationPrefsArgs),
+    
+    /// Opt out: close the investor's NotificationPrefs, refunding rent (Investor)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Investor
+    /// 1. `[writable]` NotificationPrefs PDA
+    CloseNotificationPrefs,
+
+    // =========================================================================
+    // Fund Exposure Instructions
+    // =========================================================================
+
+    /// Mirror the fund's open interest and margin usage (called by Ledger Program via CPI)
+    /// 
+    /// Overwrites Fund.open_notional_e6 / margin_used_e6 / free_collateral_e6
+    /// so dashboards can read leverage utilization from the fund account.
+    /// Funds that predate these fields must be grown (ReallocAccount) first.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Ledger fund caller PDA (seeds: [FUND_CALLER_SEED] under FundConfig.ledger_program)
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[]` FundConfig PDA
+    RecordFundExposure(RecordFundExposureArgs),
+
+    // =========================================================================
+    // Share Supply Checkpoint Instructions
+    // =========================================================================
+
+    /// Create the fund's ShareCheckpoints history and record the current supply (Manager)
+    /// 
+    /// Sets Fund.share_checkpoints_enabled: from then on DepositToFund /
+    /// RedeemFromFund / RedeemAll must pass the `[writable]` ShareCheckpoints
+    /// PDA (anywhere after 8; ShareCheckpointsRequired otherwise), which appends
+    /// a checkpoint whenever total_shares moved more than `threshold_bps`.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` ShareCheckpoints PDA
+    /// 3. `[]` System Program
+    InitializeShareCheckpoints(InitializeShareCheckpointsArgs),
+    
+    /// Append a checkpoint if the supply moved past the threshold (permissionless crank)
+    /// 
+    /// Catches supply changes from flows that did not pass the history.
+    /// 
+    /// Accounts:
+    /// 0. `[]` Fund PDA
+    /// 1. `[writable]` ShareCheckpoints PDA
+    RecordShareCheckpoint,
+
+    // =========================================================================
+    // Share Supply Reconciliation Instructions
+    // =========================================================================
+
+    /// Compare the share mint supply with FundStats.total_shares (Admin / keeper)
+    /// 
+    /// Matching supply clears a previous inconsistent flag. On a mint still
+    /// controlled by the fund's share authority (with SHARE_MINT_DECIMALS) a
+    /// decrease of any size (burned shares), or an increase of at most
+    /// MAX_SHARE_SUPPLY_AUTO_FIX_BPS, is corrected in the stats and NAV
+    /// recomputed. Anything else flags the fund as inconsistent, blocking
+    /// deposits and redemptions until a later call finds the supply in line
+    /// or the admin calls ClearShareSupplyFlag.
+    /// 
+    /// Accounts:
+    /// 0. `[writable]` Fund PDA
+    /// 1. `[]` Share mint
+    /// 2. `[signer]` Keeper (admin or authorized relayer)
+    /// 3. `[]` FundConfig PDA
+    ReconcileShareSupply,
+
+    // =========================================================================
+    // Position NFT Instructions
+    // =========================================================================
+
+    /// Wrap a whole LP position into a transferable position NFT (LP)
+    /// 
+    /// Moves the position's shares into a Fund-owned escrow and locks them on
+    /// the LPPosition with the PositionNFT PDA as lienholder, then mints the
+    /// NFT (0 decimals, supply 1) into a new token account owned by the LP.
+    /// The position must have no other lien. Deposits into a wrapped
+    /// position are rejected.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` LP investor (pays rent)
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` LP Position PDA (grown if it predates the layout)
+    /// 3. `[writable]` PositionNFT PDA
+    /// 4. `[writable]` Position NFT mint PDA
+    /// 5. `[signer, writable]` New NFT token account (keypair, created by the program)
+    /// 6. `[writable]` LP's share token account
+    /// 7. `[writable]` Escrow share token account PDA
+    /// 8. `[]` Share mint
+    /// 9. `[]` Token Program
+    /// 10. `[]` System Program
+    WrapPositionNFT,
+    
+    /// Take over a wrapped LP position as the current NFT holder
+    /// 
+    /// Reassigns `LPPosition.investor` to the signer; the previous owner's
+    /// beneficiary and payout allow-list are cleared. The position keeps its
+    /// PDA address, so the seller cannot deposit into it again until it is
+    /// unwrapped and fully redeemed.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` NFT holder
+    /// 1. `[writable]` LP Position PDA
+    /// 2. `[writable]` PositionNFT PDA
+    /// 3. `[]` Holder's NFT token account
+    TransferPositionNFT,
+    
+    /// Burn the position NFT and release the escrowed shares (LP holding the NFT)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` LP investor (NFT holder)
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` LP Position PDA
+    /// 3. `[writable]` PositionNFT PDA
+    /// 4. `[writable]` Position NFT mint PDA
+    /// 5. `[writable]` Holder's NFT token account
+    /// 6. `[writable]` Escrow share token account PDA
+    /// 7. `[writable]` Holder's share token account
+    /// 8. `[]` Token Program
+    UnwrapPositionNFT,
+
+    // =========================================================================
+    // Relayer Subsystem Pause
+    // =========================================================================
+
+    /// Pause or resume every Relayer* instruction (admin only)
+    /// 
+    /// Independent of SetProgramPaused and the FEATURE_RELAYER bit: stops a
+    /// misbehaving relayer fleet while direct user flows keep working.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authority (admin)
+    /// 1. `[writable]` FundConfig PDA
+    SetRelayerSystemPaused(SetRelayerSystemPausedArgs),
+
+    // =========================================================================
+    // Relayer Stats
+    // =========================================================================
+
+    /// Create a relayer's RelayerStats PDA (anyone may pay the rent)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[]` Relayer (must be authorized)
+    /// 3. `[writable]` RelayerStats PDA
+    /// 4. `[]` System Program
+    InitializeRelayerStats,
+
+    // =========================================================================
+    // Insurance Config Update (timelocked)
+    // =========================================================================
+
+    /// Schedule an InsuranceFundConfig update; executable after the timelock (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, pays rent)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[]` InsuranceFundConfig PDA
+    /// 3. `[writable]` PendingInsuranceConfigUpdate PDA
+    /// 4. `[]` System Program
+    ScheduleInsuranceConfigUpdate(UpdateInsuranceConfigArgs),
+
+    /// Cancel a scheduled insurance config update (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, receives rent)
+    /// 1. `[writable]` PendingInsuranceConfigUpdate PDA
+    CancelInsuranceConfigUpdate,
+
+    /// Apply a scheduled insurance config update after the timelock (admin only)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Authority (admin, receives rent)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` InsuranceFundConfig PDA
+    /// 3. `[writable]` PendingInsuranceConfigUpdate PDA (closed)
+    UpdateInsuranceConfig,
+
+    // =========================================================================
+    // LP Position Transfer
+    // =========================================================================
+
+    /// Move an LP position and its shares to another wallet of the same user (no fee)
+    /// 
+    /// Cost basis and deposit timestamp are preserved. The new owner must not
+    /// hold shares in the fund yet.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Current owner
+    /// 1. `[signer, writable]` New owner (pays rent)
+    /// 2. `[writable]` Fund PDA
+    /// 3. `[writable]` Current owner's LP Position PDA
+    /// 4. `[writable]` New owner's LP Position PDA
+    /// 5. `[writable]` Current owner's share token account
+    /// 6. `[writable]` New owner's share token account
+    /// 7. `[]` Token Program
+    /// 8. `[]` System Program
+    /// 
+    /// Also `[writable]`: the current position's LPIndexPage PDA (if indexed)
+    /// and the fund's last LPIndexPage PDA (plus the next page if full).
+    TransferLPPosition(TransferLPPositionArgs),
+
+    // =========================================================================
+    // Fund Setup Validation
+    // =========================================================================
+
+    /// One-time check of a created fund's vault and mints (permissionless)
+    /// 
+    /// The vault must be owned by the Fund PDA with no delegate or close
+    /// authority, and hold `vault_mint`: wSOL for SOL-denominated funds,
+    /// otherwise a VAULT_MINT_DECIMALS mint other than the share mint. The
+    /// share mint must have SHARE_MINT_DECIMALS. On success the vault mint is
+    /// recorded as `Fund.denomination_mint` and every later flow rejects a
+    /// vault holding anything else.
+    /// 
+    /// Accounts:
+    /// 0. `[writable]` Fund PDA
+    /// 1. `[]` Fund vault
+    /// 2. `[]` Vault mint
+    /// 3. `[]` Share mint
+    ValidateFundSetup,
+
+    // =========================================================================
+    // Fund Expenses
+    // =========================================================================
+
+    /// Pay a third-party fund expense (auditor, data) from the vault (manager only)
+    /// 
+    /// Bounded by the fund's per-payment maximum and annual cap, both fixed
+    /// at creation. NAV drops by the amount paid; the payment and its memo
+    /// hash are disclosed in the FUND_EXPENSE_PAID event.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Fund manager
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` Fund vault
+    /// 3. `[writable]` Payee token account (not owned by the manager or the fund)
+    /// 4. `[]` Token Program
+    PayFundExpense(PayFundExpenseArgs),
+
+    // =========================================================================
+    // Insurance Coverage (Ledger handshake)
+    // =========================================================================
+
+    /// Report platform-wide open interest and republish the coverage ratio (CPI)
+    /// 
+    /// Called by the Ledger whenever open interest moves; the Ledger then
+    /// reads `coverage_ratio_bps` from InsuranceFundConfig to size margin.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Authorized caller (Ledger Program)
+    /// 1. `[writable]` InsuranceFundConfig PDA
+    /// 2. `[]` Insurance Fund PDA
+    /// 3. `[]` Insurance Fund vault
+    ReportPlatformOpenInterest(ReportPlatformOpenInterestArgs),
+
+    /// Republish the coverage ratio from the current vault balance (permissionless crank)
+    /// 
+    /// Uses the open interest last reported by the Ledger.
+    /// 
+    /// Accounts:
+    /// 0. `[writable]` InsuranceFundConfig PDA
+    /// 1. `[]` Insurance Fund PDA
+    /// 2. `[]` Insurance Fund vault
+    UpdateInsuranceCoverage,
+
+    // =========================================================================
+    // Referral Reward Claims
+    // =========================================================================
+
+    /// Claim a link's funded referral rewards as USDC or as fund shares (Referrer)
+    /// 
+    /// With `into_fund_shares` the rewards are paid to the referrer's USDC
+    /// account and deposited into the given fund in the same instruction,
+    /// exactly as a DepositToFund by the referrer.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Referrer
+    /// 1. `[writable]` ReferralConfig PDA
+    /// 2. `[writable]` ReferralLink PDA (referrer)
+    /// 3. `[writable]` Referral Reward Vault PDA
+    /// 4. `[writable]` Referrer's USDC account
+    /// 5. `[]` Token Program
+    /// 
+    /// When `into_fund_shares` (DepositToFund accounts, source = account 4):
+    /// 6. `[writable]` Fund PDA
+    /// 7. `[writable]` Fund vault
+    /// 8. `[writable]` Referrer's LP Position PDA
+    /// 9. `[writable]` Referrer's share token account
+    /// 10. `[writable]` Share mint
+    /// 11. `[]` System Program
+    /// 12.. Optional DepositToFund accounts (receipts, hooks, ...)
+    ClaimReferralRewards(ClaimReferralRewardsArgs),
+
+    // =========================================================================
+    // Investor Portfolio
+    // =========================================================================
+
+    /// Create the signer's InvestorPortfolio (Investor)
+    /// 
+    /// Once it exists, passing the `[writable]` InvestorPortfolio PDA to
+    /// DepositToFund / RedeemFromFund / RedeemAll (after any receipt accounts)
+    /// keeps the fund's entry current.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Investor
+    /// 1. `[writable]` InvestorPortfolio PDA
+    /// 2. `[]` System Program
+    InitializeInvestorPortfolio,
+    
+    /// Refresh a portfolio's entry from an LP position (permissionless crank)
+    /// 
+    /// Picks up positions that predate the portfolio, flows that did not pass
+    /// it, and NAV moves since the last deposit / redeem.
+    /// 
+    /// Accounts:
+    /// 0. `[writable]` InvestorPortfolio PDA
+    /// 1. `[]` Fund PDA
+    /// 2. `[]` LP Position PDA (fund, investor)
+    SyncInvestorPortfolio,
+
+    // =========================================================================
+    // Stale Entry Cleanup
+    // =========================================================================
+
+    /// Close stale entries of a fund and return their rent (permissionless crank)
+    /// 
+    /// A TradeIntent is stale `STALE_ENTRY_GRACE_SECS` after it expired or was
+    /// executed. Each closed entry pays the keeper `STALE_ENTRY_KEEPER_TIP_BPS`
+    /// of its rent; the rest goes back to its creator (the fund manager).
+    /// Entries that are not stale yet are skipped.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Keeper
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` Fund manager (rent refund)
+    /// 3. `[writable]` TradeIntent PDAs of the fund (one or more)
+    ExpireStaleEntries,
+
+    // =========================================================================
+    // Fund Lookup Table
+    // =========================================================================
+
+    /// Create the fund's Address Lookup Table and publish it on the Fund (Fund manager)
+    /// 
+    /// The Fund PDA is the table authority, so only this program can extend
+    /// it. Clients read `Fund.lookup_table` to build v0 transactions for
+    /// account-heavy flows such as TradeFund / CloseFundPosition.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager (pays rent)
+    /// 1. `[writable]` Fund PDA
+    /// 2. `[writable]` Lookup table (derived from the Fund PDA and `recent_slot`)
+    /// 3. `[]` Address Lookup Table Program
+    /// 4. `[]` System Program
+    CreateFundLookupTable(CreateFundLookupTableArgs),
+    
+    /// Append addresses (Ledger, vault, system accounts, ...) to the fund's lookup table (Fund manager)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager (pays rent)
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` Lookup table (Fund.lookup_table)
+    /// 3. `[]` Address Lookup Table Program
+    /// 4. `[]` System Program
+    ExtendFundLookupTable(ExtendFundLookupTableArgs),
+
+    // =========================================================================
+    // Fund Coverage
+    // =========================================================================
+
+    /// Buy drawdown coverage for the fund's LPs (Fund manager + Admin)
+    /// 
+    /// Creates the FundCoveragePolicy at the fund's current NAV and trading
+    /// PnL; the admin co-signs to accept the terms on the Insurance Fund's behalf.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Fund manager (pays rent)
+    /// 1. `[signer]` Admin (FundConfig authority)
+    /// 2. `[]` FundConfig PDA
+    /// 3. `[]` Fund PDA
+    /// 4. `[writable]` FundCoveragePolicy PDA
+    /// 5. `[]` System Program
+    RegisterFundCoverage(RegisterFundCoverageArgs),
+    
+    /// Pay the accrued premium from the fund vault into the Insurance Fund (permissionless crank)
+    /// 
+    /// Accounts:
+    /// 0. `[writable]` Fund PDA
+    /// 1. `[writable]` Fund vault
+    /// 2. `[writable]` FundCoveragePolicy PDA
+    /// 3. `[]` InsuranceFundConfig PDA
+    /// 4. `[writable]` Insurance Fund PDA
+    /// 5. `[writable]` Insurance Fund vault
+    /// 6. `[]` Token Program
+    PayCoveragePremium,
+    
+    /// Claim compensation for a qualifying loss from the Insurance Fund (LP)
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Investor (pays rent for the claim record)
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` FundCoveragePolicy PDA
+    /// 3. `[writable]` Investor's LP Position PDA (records the compensated-to NAV)
+    /// 4. `[writable]` FundCoverageClaim PDA (policy, investor)
+    /// 5. `[]` InsuranceFundConfig PDA
+    /// 6. `[writable]` Insurance Fund PDA
+    /// 7. `[writable]` Insurance Fund vault
+    /// 8. `[writable]` Investor's USDC account
+    /// 9. `[]` Token Program
+    /// 10. `[]` System Program
+    ClaimFundCoverage,
+    
+    /// Cancel the fund's coverage; premiums stop and claims close (Fund manager)
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Fund manager
+    /// 1. `[]` Fund PDA
+    /// 2. `[writable]` FundCoveragePolicy PDA
+    CancelFundCoverage,
+
+    // =========================================================================
+    // Mark Price
+    // =========================================================================
+
+    /// Publish a Ledger market's mark price (authorized relayer)
+    /// 
+    /// Reference price for keeper-driven closes such as EnforceRiskLimits.
+    /// 
+    /// Accounts:
+    /// 0. `[signer, writable]` Relayer (pays rent on first publish)
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` MarkPrice PDA
+    /// 3. `[]` System Program
+    UpdateMarkPrice(UpdateMarkPriceArgs),
+
+    // =========================================================================
+    // Share Supply Flag
+    // =========================================================================
+
+    /// Lift a fund's inconsistent share supply flag (Admin only)
+    /// 
+    /// For a flag ReconcileShareSupply cannot clear by itself once the admin
+    /// has reviewed the mint.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Admin
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` Fund PDA
+    ClearShareSupplyFlag,
+
+    // =========================================================================
+    // Referral Reward Settlement
+    // =========================================================================
+
+    /// Pay out funded referral rewards for up to MAX_REFERRAL_SETTLEMENT_BATCH referrers (authorized relayer)
+    /// 
+    /// Each referrer whose link holds at least ReferralConfig.min_settlement_amount_e6
+    /// of unclaimed rewards is paid in USDC as by ClaimReferralRewards. Entries
+    /// below the minimum or with an invalid link / USDC account are skipped;
+    /// processing stops early when the remaining compute budget runs low.
+    /// Returns `SettleReferralRewardsBatchResult` via return data.
+    /// 
+    /// Accounts:
+    /// 0. `[signer]` Relayer
+    /// 1. `[]` FundConfig PDA
+    /// 2. `[writable]` ReferralConfig PDA
+    /// 3. `[writable]` Referral Reward Vault PDA
+    /// 4. `[]` Token Program
+    /// 
+    /// Then per referrer:
+    /// 0. `[writable]` ReferralLink PDA
+    /// 1. `[writable]` Referrer's USDC account (owned by the referrer)
+    SettleReferralRewardsBatch,
+}
+
+impl FundInstruction {
+    /// Number of variants (bump when appending an instruction)
+    pub const VARIANT_COUNT: u8 = 174;
+    
+    /// Decode instruction data, logging what went wrong on failure
+    ///
+    /// Unlike `try_from_slice`, which reports every failure as
+    /// InvalidInstructionData, this separates:
+    /// - empty data / unknown variant index → UnknownInstruction
+    /// - malformed arguments for a known variant → InvalidInstructionData
+    /// - arguments followed by extra bytes → InstructionDataTrailingBytes
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let Some((&variant, args)) = data.split_first() else {
+            msg!("Empty instruction data");
+            return Err(FundError::UnknownInstruction.into());
+        };
+        if variant >= Self::VARIANT_COUNT {
+            msg!("Unknown instruction variant {} ({} arg bytes)", variant, args.len());
+            return Err(FundError::UnknownInstruction.into());
+        }
+        
+        let mut remaining = data;
+        let instruction = Self::deserialize(&mut remaining).map_err(|e| {
+            msg!("Invalid args for instruction variant {} ({} arg bytes): {}", variant, args.len(), e);
+            ProgramError::InvalidInstructionData
+        })?;
+        if !remaining.is_empty() {
+            msg!(
+                "Instruction variant {} has {} trailing bytes ({} arg bytes consumed)",
+                variant,
+                remaining.len(),
+                args.len() - remaining.len()
+            );
+            return Err(FundError::InstructionDataTrailingBytes.into());
+        }
+        Ok(instruction)
+    }
+    
+    /// FEATURE_* bits this instruction requires to be enabled (0 = ungated)
+    pub fn required_features(&self) -> u32 {
+        match self {
+            FundInstruction::SquarePayment(_)
+            | FundInstruction::EscrowSquarePayment(_)
+            | FundInstruction::SquareTokenTip(_) => FEATURE_SQUARE_PAYMENTS,
+            FundInstruction::RelayerSquarePayment(_) => FEATURE_SQUARE_PAYMENTS | FEATURE_RELAYER,
+            FundInstruction::CreateReferralLink(_)
+            | FundInstruction::BindReferral
+            | FundInstruction::RecordReferralTrade(_) => FEATURE_REFERRALS,
+            FundInstruction::RelayerBindReferral(_) => FEATURE_REFERRALS | FEATURE_RELAYER,
+            FundInstruction::CollectPredictionMarketMintingFee(_)
+            | FundInstruction::CollectPredictionMarketRedemptionFee(_)
+            | FundInstruction::CollectPredictionMarketTradingFee(_)
+            | FundInstruction::DistributePredictionMarketMakerReward(_)
+            | FundInstruction::DistributePredictionMarketCreatorReward(_) => FEATURE_PM_FEES,
+            FundInstruction::RelayerDepositToFund(_)
+            | FundInstruction::RelayerRedeemFromFund(_)
+            | FundInstruction::RelayerRedeemFromInsuranceFund(_)
+            | FundInstruction::SettleReferralRewardsBatch => FEATURE_RELAYER,
+            _ => 0,
+        }
+    }
+}
+
+/// Maximum number of funds per UpdateNAVMulti (one bit each in the success bitmap)
+pub const MAX_NAV_MULTI_FUNDS: usize = 32;
+
+/// Maximum number of referrers per SettleReferralRewardsBatch (one bit each in the settled bitmap)
+pub const MAX_REFERRAL_SETTLEMENT_BATCH: usize = 16;
+
+// === Argument Structs ===
+
+/// Arguments for Initialize instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InitializeArgs {
+    /// Vault Program ID
+    pub vault_program: Pubkey,
+    /// Ledger Program ID
+    pub ledger_program: Pubkey,
+}
+
+/// Arguments for CreateFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CreateFundArgs {
+    /// Fund name (max 32 characters)
+    pub name: String,
+    /// Management fee in basis points (e.g., 200 = 2%)
+    pub management_fee_bps: u32,
+    /// Performance fee in basis points (e.g., 2000 = 20%)
+    pub performance_fee_bps: u32,
+    /// Use High Water Mark for performance fee
+    pub use_high_water_mark: bool,
+    /// Fee collection interval in seconds (0 = default 1 day)
+    pub fee_collection_interval: i64,
+    /// Manager seed deposit made in the same step, in raw vault token units
+    /// (USDC base units, lamports for wSOL funds; CreateFund only)
+    pub initial_deposit_amount: Option<u64>,
+    /// Annual PayFundExpense allowance (bps of fund value, ≤ MAX_FUND_EXPENSE_BPS, 0 = disabled)
+    pub expense_cap_bps: u16,
+    /// Largest single PayFundExpense payment (e6)
+    pub max_expense_payment_e6: u64,
+}
+
+/// Arguments for UpdateFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdateFundArgs {
+    /// New fee configuration (optional)
+    pub fee_config: Option<FeeConfig>,
+}
+
+/// Arguments for SetFundState instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetFundStateArgs {
+    /// Target state
+    pub state: FundLifecycleState,
+    /// Reason code, logged with the change
+    pub reason: FundStateReason,
+}
+
+/// Arguments for DepositToFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DepositToFundArgs {
+    /// Amount to deposit (in vault tokens: USDC e6, or lamports for wSOL funds)
+    pub amount: u64,
+    /// Marketing campaign to attribute the deposit to
+    pub campaign_id: Option<u64>,
+    /// Payment reference (max MAX_MEMO_LEN bytes)
+    pub memo: Option<String>,
+}
+
+/// Arguments for RedeemFromFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RedeemFromFundArgs {
+    /// Number of shares to redeem
+    pub shares: u64,
+    /// Payment reference (max MAX_MEMO_LEN bytes)
+    pub memo: Option<String>,
+}
+
+/// Arguments for TradeFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct TradeFundArgs {
+    /// Market index
+    pub market_index: u8,
+    /// Side (0 = Long, 1 = Short)
+    pub side: u8,
+    /// Position size (in e6)
+    pub size_e6: u64,
+    /// Entry price (in e6)
+    pub price_e6: u64,
+    /// Leverage (1-100)
+    pub leverage: u8,
+    /// Maximum slippage in basis points
+    pub max_slippage_bps: u32,
+    /// Run all checks and return `TradeSimulationResult` without the Ledger CPI
+    pub simulate_only: bool,
+}
+
+/// Arguments for CloseFundPosition instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CloseFundPositionArgs {
+    /// Market index
+    pub market_index: u8,
+    /// Close size (in e6, 0 = close all: the full size is read from the Ledger position)
+    pub size_e6: u64,
+    /// Exit price (in e6)
+    pub price_e6: u64,
+}
+
+/// Arguments for CollectFees instruction
+///
+/// Whatever is not collected stays accrued for a later collection.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CollectFeesArgs {
+    /// Collect the management fee
+    pub include_management: bool,
+    /// Collect the performance fee (all or nothing, since it resets the HWM)
+    pub include_performance: bool,
+    /// Upper bound on the total collected (e6, 0 = no cap)
+    pub max_amount_e6: u64,
+    /// Only compute the breakdown (return data); no transfer or state change
+    pub preview_only: bool,
+}
+
+impl CollectFeesArgs {
+    /// Collect everything accrued (the pre-argument behavior)
+    pub fn all() -> Self {
+        Self {
+            include_management: true,
+            include_performance: true,
+            max_amount_e6: 0,
+            preview_only: false,
+        }
+    }
+}
+
+/// Arguments for UpdateAuthority instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdateAuthorityArgs {
+    /// New authority public key
+    pub new_authority: Pubkey,
+}
+
+/// Arguments for SetProgramPaused instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetProgramPausedArgs {
+    /// Whether the program is paused
+    pub is_paused: bool,
+}
+
+/// Arguments for RecordPnL instruction (CPI)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RecordPnLArgs {
+    /// Realized PnL amount (can be negative)
+    pub pnl_e6: i64,
+}
+
+// === Insurance Fund Argument Structs ===
+
+/// Arguments for InitializeInsuranceFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InitializeInsuranceFundArgs {
+    /// ADL balance insufficiency trigger threshold (e6)
+    pub adl_trigger_threshold_e6: i64,
+    /// LP redemption delay in seconds
+    pub withdrawal_delay_secs: i64,
+    /// Authorized caller (Ledger Program)
+    pub authorized_caller: Pubkey,
+}
+
+/// Arguments for AddLiquidationIncome instruction (CPI)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AddLiquidationIncomeArgs {
+    /// Liquidation income amount (e6)
+    pub amount_e6: i64,
+}
+
+/// Arguments for AddADLProfit instruction (CPI)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AddADLProfitArgs {
+    /// ADL profit amount (e6)
+    pub amount_e6: i64,
+}
+
+/// Arguments for CoverShortfall instruction (CPI)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CoverShortfallArgs {
+    /// Shortfall amount to cover (e6)
+    pub shortfall_e6: i64,
+}
+
+/// Arguments for SetADLInProgress instruction (CPI)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetADLInProgressArgs {
+    /// Whether ADL is in progress
+    pub in_progress: bool,
+}
+
+/// Arguments for CheckADLTrigger instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CheckADLTriggerArgs {
+    /// Shortfall to check against (e6), 0 for no bankruptcy check
+    pub shortfall_e6: i64,
+}
+
+/// Arguments for AddTradingFee instruction (CPI)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AddTradingFeeArgs {
+    /// Trading fee amount (e6)
+    pub fee_e6: i64,
+}
+
+/// Arguments for RedeemFromInsuranceFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RedeemFromInsuranceFundArgs {
+    /// Number of shares to redeem
+    pub shares: u64,
+}
+
+// === Square Platform Argument Structs ===
+
+/// Arguments for SquarePayment instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquarePaymentArgs {
+    /// Creator address (content owner)
+    pub creator: Pubkey,
+    /// Content ID (unique identifier for the content)
+    pub content_id: u64,
+    /// Payment type: 0 = KnowledgePurchase, 1 = Subscription, 2 = LiveDonation
+    pub payment_type: u8,
+    /// Total payment amount (e6)
+    pub amount_e6: i64,
+    /// Creator share in basis points (e.g., 9000 = 90%)
+    pub creator_share_bps: u16,
+    /// Subscription period (number of months, 0 for non-subscription)
+    pub subscription_period: u8,
+    /// Optional memo (max 32 bytes)
+    pub memo: Vec<u8>,
+    /// Payer nonce, must exceed the last one in SquarePayerNonce (record PDA seed)
+    pub nonce: u64,
+}
+
+// === Referral Argument Structs ===
+
+/// Arguments for InitializeReferral instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InitializeReferralArgs {
+    /// Base referrer share in basis points (e.g., 2000 = 20%)
+    pub referrer_share_bps: u16,
+    /// Base referee discount in basis points (e.g., 1000 = 10%)
+    pub referee_discount_bps: u16,
+}
+
+/// Arguments for CreateReferralLink instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CreateReferralLinkArgs {
+    /// Referral code (6-12 characters)
+    pub code: Vec<u8>,
+}
+
+/// Arguments for RecordReferralTrade instruction (CPI)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RecordReferralTradeArgs {
+    /// Trade fee amount (e6)
+    pub trade_fee_e6: i64,
+    /// Trade volume (e6)
+    pub trade_volume_e6: i64,
+    /// Referrer VIP level
+    pub referrer_vip_level: u8,
+    /// Referee VIP level
+    pub referee_vip_level: u8,
+}
+
+/// Arguments for UpdateReferralConfig instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdateReferralConfigArgs {
+    /// New referrer share in basis points (None = no change)
+    pub referrer_share_bps: Option<u16>,
+    /// New referee discount in basis points (None = no change)
+    pub referee_discount_bps: Option<u16>,
+    /// New referrer VIP bonus array (None = no change)
+    pub referrer_vip_bonus_bps: Option<[u16; 6]>,
+    /// New referee VIP bonus array (None = no change)
+    pub referee_vip_bonus_bps: Option<[u16; 6]>,
+    /// New minimum settlement amount (None = no change)
+    pub min_settlement_amount_e6: Option<i64>,
+    /// Pause/unpause (None = no change)
+    pub is_paused: Option<bool>,
+    /// New binding validity in seconds, 0 = permanent (None = no change)
+    pub reward_validity_secs: Option<i64>,
+    /// New rebinding policy (None = no change)
+    pub rebind_policy: Option<ReferralRebindPolicy>,
+}
+
+/// Arguments for SetCustomReferralRates instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetCustomReferralRatesArgs {
+    /// Custom referrer share in basis points (0 = use global)
+    pub custom_referrer_share_bps: u16,
+    /// Custom referee discount in basis points (0 = use global)
+    pub custom_referee_discount_bps: u16,
+}
+
+// === Prediction Market Fee Argument Structs ===
+
+/// Arguments for InitializePredictionMarketFeeConfig instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InitializePredictionMarketFeeConfigArgs {
+    /// Prediction market minting fee in basis points (default 10 = 0.1%)
+    pub prediction_market_minting_fee_bps: u16,
+    /// Prediction market redemption fee in basis points
+    pub prediction_market_redemption_fee_bps: u16,
+    /// Prediction market taker trading fee in basis points
+    pub prediction_market_trading_fee_taker_bps: u16,
+    /// Prediction market maker trading fee in basis points (usually 0)
+    pub prediction_market_trading_fee_maker_bps: u16,
+    /// Prediction market protocol share of fees in basis points
+    pub prediction_market_protocol_share_bps: u16,
+    /// Prediction market maker reward share in basis points
+    pub prediction_market_maker_reward_share_bps: u16,
+    /// Prediction market creator share in basis points
+    pub prediction_market_creator_share_bps: u16,
+}
+
+/// Arguments for CollectPredictionMarketMintingFee instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CollectPredictionMarketMintingFeeArgs {
+    /// Prediction market minting amount (e6) - fee calculated based on this
+    pub prediction_market_minting_amount_e6: i64,
+    /// Market category (None = default fees)
+    pub category: Option<u8>,
+}
+
+/// Arguments for CollectPredictionMarketRedemptionFee instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CollectPredictionMarketRedemptionFeeArgs {
+    /// Prediction market redemption amount (e6) - fee calculated based on this
+    pub prediction_market_redemption_amount_e6: i64,
+    /// Market category (None = default fees)
+    pub category: Option<u8>,
+}
+
+/// Arguments for CollectPredictionMarketTradingFee instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CollectPredictionMarketTradingFeeArgs {
+    /// Prediction market trade volume (e6) - fee calculated based on this
+    pub prediction_market_trade_volume_e6: i64,
+    /// Is this a taker fee?
+    pub is_taker: bool,
+    /// Market category (None = default fees)
+    pub category: Option<u8>,
+    /// Maker of the trade (records maker volume for reward distribution)
+    pub maker: Option<Pubkey>,
+}
+
+/// Arguments for DistributePredictionMarketMakerReward instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DistributePredictionMarketMakerRewardArgs {
+    /// Ended maker reward epoch to distribute for
+    pub epoch: u64,
+}
+
+/// Arguments for DistributePredictionMarketCreatorReward instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DistributePredictionMarketCreatorRewardArgs {
+    /// Prediction market creator reward amount (e6)
+    pub prediction_market_creator_reward_e6: i64,
+    /// Prediction market ID (for tracking)
+    pub prediction_market_id: u64,
+}
+
+/// Arguments for SetPredictionMarketCategoryFees instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetPredictionMarketCategoryFeesArgs {
+    /// Category index (< MAX_PM_CATEGORIES)
+    pub category: u8,
+    /// Override fees (None = clear, use defaults)
+    pub fees: Option<PMCategoryFees>,
+}
+
+/// Arguments for UpdatePredictionMarketFeeConfig instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdatePredictionMarketFeeConfigArgs {
+    /// New prediction market minting fee (None = no change)
+    pub prediction_market_minting_fee_bps: Option<u16>,
+    /// New prediction market redemption fee (None = no change)
+    pub prediction_market_redemption_fee_bps: Option<u16>,
+    /// New prediction market taker trading fee (None = no change)
+    pub prediction_market_trading_fee_taker_bps: Option<u16>,
+    /// New prediction market maker trading fee (None = no change)
+    pub prediction_market_trading_fee_maker_bps: Option<u16>,
+    /// New prediction market protocol share (None = no change)
+    pub prediction_market_protocol_share_bps: Option<u16>,
+    /// New prediction market maker reward share (None = no change)
+    pub prediction_market_maker_reward_share_bps: Option<u16>,
+    /// New prediction market creator share (None = no change)
+    pub prediction_market_creator_share_bps: Option<u16>,
+}
+
+/// Arguments for SetPredictionMarketFeePaused instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetPredictionMarketFeePausedArgs {
+    /// Prediction market fee paused state
+    pub prediction_market_fee_paused: bool,
+}
+
+// ============================================================================
+// Relayer Instructions (200-249) - Admin/Relayer 代替用户签名
+// ============================================================================
+
+/// Relayer 版本的 DepositToFund
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayerDepositToFundArgs {
+    /// 用户钱包地址
+    pub user_wallet: Pubkey,
+    /// Amount to deposit (in USDC, 6 decimals)
+    pub amount: u64,
+    /// 防重放 nonce (按用户严格递增)
+    pub nonce: u64,
+    /// Marketing campaign to attribute the deposit to
+    pub campaign_id: Option<u64>,
+    /// Payment reference (max MAX_MEMO_LEN bytes)
+    pub memo: Option<String>,
+}
+
+/// Relayer 版本的 RedeemFromFund
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayerRedeemFromFundArgs {
+    /// 用户钱包地址
+    pub user_wallet: Pubkey,
+    /// Number of shares to redeem
+    pub shares: u64,
+    /// 防重放 nonce (按用户严格递增)
+    pub nonce: u64,
+    /// Payment reference (max MAX_MEMO_LEN bytes)
+    pub memo: Option<String>,
+}
+
+/// Relayer 版本的 RedeemFromInsuranceFund
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayerRedeemFromInsuranceFundArgs {
+    /// 用户钱包地址
+    pub user_wallet: Pubkey,
+    /// Number of shares to redeem
+    pub shares: u64,
+    /// 防重放 nonce (按用户严格递增)
+    pub nonce: u64,
+}
+
+/// Relayer 版本的 SquarePayment
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayerSquarePaymentArgs {
+    /// 付款用户钱包地址
+    pub payer_wallet: Pubkey,
+    /// Creator address (content owner)
+    pub creator: Pubkey,
+    /// Content ID (unique identifier for the content)
+    pub content_id: u64,
+    /// Payment type: 0 = KnowledgePurchase, 1 = Subscription, 2 = LiveDonation
+    pub payment_type: u8,
+    /// Total payment amount (e6)
+    pub amount_e6: i64,
+    /// Creator share in basis points (e.g., 9000 = 90%)
+    pub creator_share_bps: u16,
+    /// Subscription period (number of months, 0 for non-subscription)
+    pub subscription_period: u8,
+    /// Optional memo (max 32 bytes)
+    pub memo: Vec<u8>,
+    /// 防重放 nonce (按用户严格递增)
+    pub nonce: u64,
+}
+
+/// Relayer 版本的 BindReferral
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayerBindReferralArgs {
+    /// 新用户钱包地址
+    pub user_wallet: Pubkey,
+    /// Referral link address
+    pub referral_link: Pubkey,
+    /// 防重放 nonce (按用户严格递增)
+    pub nonce: u64,
+}
+
+// ============================================================================
+// Relayer Management Instructions (250-259)
+// ============================================================================
+
+/// 添加授权 Relayer
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AddRelayerArgs {
+    /// 新 Relayer 公钥
+    pub relayer: Pubkey,
+}
+
+/// 移除 Relayer
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RemoveRelayerArgs {
+    /// 要移除的 Relayer 公钥
+    pub relayer: Pubkey,
+}
+
+/// 更新 Relayer 限额配置
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdateRelayerLimitsArgs {
+    /// 单笔交易限额 (e6), 0 = 无限制
+    pub single_tx_limit_e6: Option<i64>,
+    /// 每日限额 (e6), 0 = 无限制
+    pub daily_limit_e6: Option<i64>,
+}
+
+// ============================================================================
+// Spot Trading Fee Arguments
+// ============================================================================
 
-/// Arguments for CheckADLTrigger instruction
+/// 初始化 Spot 交易手续费配置
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CheckADLTriggerArgs {
-    /// Shortfall to check against (e6), 0 for no bankruptcy check
-    pub shortfall_e6: i64,
+pub struct InitializeSpotTradingFeeConfigArgs {
+    /// 授权调用方 (Vault Program)
+    pub authorized_caller: Pubkey,
 }
 
-/// Arguments for AddTradingFee instruction (CPI)
+/// 收取 Spot 交易手续费
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct AddTradingFeeArgs {
-    /// Trading fee amount (e6)
-    pub fee_e6: i64,
+pub struct CollectSpotTradingFeeArgs {
+    /// 交易金额 (e6)
+    pub volume_e6: i64,
+    /// 是否为 Taker
+    pub is_taker: bool,
 }
 
-/// Arguments for RedeemFromInsuranceFund instruction
+/// 分配 Spot 手续费
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RedeemFromInsuranceFundArgs {
-    /// Number of shares to redeem
+pub struct DistributeSpotFeeArgs {
+    /// 要分配的金额 (e6), 0 = 分配全部余额
+    pub amount_e6: i64,
+}
+
+/// 发放 Spot 做市商奖励
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DistributeSpotMakerRewardArgs {
+    /// 做市商地址
+    pub maker: Pubkey,
+    /// 奖励金额 (e6)
+    pub reward_e6: i64,
+}
+
+/// 更新 Spot 手续费配置
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdateSpotTradingFeeConfigArgs {
+    /// Taker 费率 (bps)
+    pub taker_fee_bps: Option<u16>,
+    /// Maker 费率 (bps)
+    pub maker_fee_bps: Option<u16>,
+    /// 协议收入占比 (bps)
+    pub protocol_share_bps: Option<u16>,
+    /// 保险基金占比 (bps)
+    pub insurance_share_bps: Option<u16>,
+    /// 返佣池占比 (bps)
+    pub referral_share_bps: Option<u16>,
+    /// 做市商激励占比 (bps)
+    pub maker_reward_share_bps: Option<u16>,
+}
+
+// === Deposit Receipt Argument Structs ===
+
+/// Arguments for SetDepositReceipts instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetDepositReceiptsArgs {
+    /// Whether deposits mint a receipt NFT
+    pub enabled: bool,
+}
+
+// === LP Beneficiary Argument Structs ===
+
+/// Arguments for SetLPBeneficiary instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetLPBeneficiaryArgs {
+    /// Beneficiary wallet (None = clear)
+    pub beneficiary: Option<Pubkey>,
+}
+
+/// Arguments for SetBeneficiaryInactivityPeriod instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetBeneficiaryInactivityPeriodArgs {
+    /// Inactivity period in seconds (0 = default 365 days)
+    pub inactivity_secs: i64,
+}
+
+// === Fund Creation Limit Argument Structs ===
+
+/// Arguments for UpdateFundCreationLimits instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdateFundCreationLimitsArgs {
+    /// Maximum active funds per manager (0 = unlimited)
+    pub max_funds_per_manager: Option<u16>,
+    /// USDC fee charged per CreateFund, routed to the Treasury (e6, 0 = free)
+    pub fund_creation_fee_e6: Option<u64>,
+}
+
+// === Fund Listing Approval Argument Structs ===
+
+/// Arguments for SetListingApprovalRequired instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetListingApprovalRequiredArgs {
+    /// Whether new funds require listing approval
+    pub required: bool,
+}
+
+/// Arguments for ApproveFundListing instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ApproveFundListingArgs {
+    /// true = list the fund, false = reject it
+    pub approved: bool,
+}
+
+// === Fee Crank Argument Structs ===
+
+/// Arguments for SetFeeCrank instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetFeeCrankArgs {
+    /// Whether authorized relayers may collect fees on schedule
+    pub enabled: bool,
+}
+
+// === Rent Reserve Argument Structs ===
+
+/// Arguments for SetRentReserveEnabled instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetRentReserveEnabledArgs {
+    /// Whether the reserve sponsors rent
+    pub enabled: bool,
+}
+
+/// Arguments for ReimburseRentReserve instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ReimburseRentReserveArgs {
+    /// Lamports to pay back
+    pub lamports: u64,
+}
+
+// === Reinitialize Argument Structs ===
+
+/// Arguments for ScheduleReinitialize instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ScheduleReinitializeArgs {
+    /// Vault Program ID
+    pub vault_program: Pubkey,
+    /// Ledger Program ID
+    pub ledger_program: Pubkey,
+    /// Fund counter to restore if the existing config cannot be decoded
+    pub total_funds: u64,
+}
+
+// === Account Reallocation Argument Structs ===
+
+/// Arguments for ReallocAccount instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ReallocAccountArgs {
+    /// New account size in bytes (must be larger than the current size)
+    pub new_size: u32,
+}
+
+// === Payout Allow-list Argument Structs ===
+
+/// Arguments for RegisterPayoutAddress / RemovePayoutAddress
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PayoutAddressArgs {
+    /// Wallet or token account allowed to receive redemptions
+    pub address: Pubkey,
+}
+
+// === System Fund Argument Structs ===
+
+/// Arguments for SetSystemFundFlags instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetSystemFundFlagsArgs {
+    /// Fund is the Insurance Fund
+    pub is_insurance_fund: bool,
+    /// Fund is a platform-owned system fund
+    pub is_system_fund: bool,
+}
+
+// === LP Distribution Argument Structs ===
+
+/// Arguments for DistributeToLPs instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DistributeToLPsArgs {
+    /// USDC (e6) per whole share; must match the active round when continuing
+    pub amount_per_share_e6: u64,
+}
+
+// === Governance Argument Structs ===
+
+/// Arguments for CreateProposal instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CreateProposalArgs {
+    /// Proposal id (unique per fund, part of the PDA seeds)
+    pub proposal_id: u64,
+    /// Proposal type
+    pub kind: ProposalKind,
+    /// FeeChange: new management fee (bps)
+    pub new_management_fee_bps: u32,
+    /// FeeChange: new performance fee (bps)
+    pub new_performance_fee_bps: u32,
+    /// ReplaceManager: proposed manager
+    pub new_manager: Pubkey,
+    /// Voting period in seconds
+    pub voting_period_secs: i64,
+}
+
+/// Arguments for CastVote instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CastVoteArgs {
+    /// true = yes, false = no
+    pub support: bool,
+}
+
+// === Share Lock Argument Structs ===
+
+/// Arguments for LockShares / UnlockShares instructions
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ShareLockArgs {
+    /// Shares to lock or unlock
     pub shares: u64,
 }
 
-// === Square Platform Argument Structs ===
+// === Trade Intent Argument Structs ===
 
-/// Arguments for SquarePayment instruction
+/// Arguments for CreateTradeIntent instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SquarePaymentArgs {
-    /// Creator address (content owner)
+pub struct CreateTradeIntentArgs {
+    /// Intent id (unique per fund, part of the PDA seeds)
+    pub intent_id: u64,
+    /// Market index
+    pub market_index: u8,
+    /// Side (0 = Long, 1 = Short)
+    pub side: u8,
+    /// Position size (in e6)
+    pub size_e6: u64,
+    /// Worst acceptable execution price (in e6)
+    pub limit_price_e6: u64,
+    /// Leverage (1-100)
+    pub leverage: u8,
+    /// Expiry timestamp
+    pub expiry_ts: i64,
+}
+
+/// Arguments for ExecuteTradeIntent instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ExecuteTradeIntentArgs {
+    /// Keeper-supplied execution price (in e6)
+    pub price_e6: u64,
+}
+
+// === Feature Flag Argument Structs ===
+
+/// Arguments for SetFeaturesEnabled instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetFeaturesEnabledArgs {
+    /// FEATURE_* bits to update
+    pub features: u32,
+    /// Enable (true) or disable (false) them
+    pub enabled: bool,
+}
+
+// === Redemption Fee Argument Structs ===
+
+/// Arguments for SetRedemptionFeeCurve instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetRedemptionFeeCurveArgs {
+    /// Cash ratio (bps of AUM) below which the fee applies, 0 = disabled
+    pub target_cash_ratio_bps: u16,
+    /// Fee at zero cash ratio (bps, max MAX_REDEMPTION_FEE_BPS)
+    pub max_fee_bps: u16,
+}
+
+// === Treasury Argument Structs ===
+
+/// Arguments for ScheduleTreasuryWithdrawal instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ScheduleTreasuryWithdrawalArgs {
+    /// Amount to withdraw (e6)
+    pub amount_e6: i64,
+    /// Destination token account
+    pub destination: Pubkey,
+}
+
+// === Balances Checkpoint Argument Structs ===
+
+/// Arguments for CommitBalancesRoot instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CommitBalancesRootArgs {
+    /// Merkle root of balance_leaf_hash(investor, shares) leaves
+    pub root: [u8; 32],
+    /// Timestamp the balances were taken at
+    pub snapshot_ts: i64,
+    /// Number of leaves
+    pub leaf_count: u32,
+    /// Sum of shares across leaves
+    pub total_shares: u64,
+}
+
+/// Arguments for VerifyBalanceProof instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VerifyBalanceProofArgs {
+    /// Investor in the leaf
+    pub investor: Pubkey,
+    /// Shares in the leaf
+    pub shares: u64,
+    /// Sibling hashes from leaf to root (max MAX_MERKLE_PROOF_DEPTH)
+    pub proof: Vec<[u8; 32]>,
+}
+
+// === Pause Expiry Argument Structs ===
+
+/// Arguments for SetPausePolicy instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetPausePolicyArgs {
+    /// Default max pause duration in seconds (0 = default 30 days)
+    pub default_max_pause_secs: u32,
+    /// Bound for per-fund overrides in seconds (0 = default 90 days)
+    pub max_pause_override_secs: u32,
+}
+
+/// Arguments for SetFundMaxPause instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetFundMaxPauseArgs {
+    /// Max pause duration in seconds (0 = config default)
+    pub max_pause_secs: u32,
+}
+
+// === Full Exit Argument Structs ===
+
+/// Arguments for RedeemAll instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RedeemAllArgs {
+    /// Payment reference (max MAX_MEMO_LEN bytes)
+    pub memo: Option<String>,
+}
+
+// === Insurance Health Argument Structs ===
+
+/// Arguments for GetInsuranceHealth instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct GetInsuranceHealthArgs {
+    /// Current open interest the fund covers (e6, supplied by the caller)
+    pub open_interest_e6: i64,
+}
+
+// === Manager Risk Argument Structs ===
+
+/// Arguments for SetManagerRiskLimits instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetManagerRiskLimitsArgs {
+    /// Max open notional per manager across their funds (e6, 0 = no cap)
+    pub max_notional_e6: u64,
+    /// Max leverage per trade (0 = no cap)
+    pub max_leverage: u8,
+    /// Require and enforce ManagerRiskState on every trade
+    pub enabled: bool,
+}
+
+// === NAV Floor Argument Structs ===
+
+/// Arguments for ResolveWindDownReview instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ResolveWindDownReviewArgs {
+    /// Close the fund to new deposits (false = reopen, NAV must be above the floor)
+    pub wind_down: bool,
+}
+
+// === Fund Benchmark Argument Structs ===
+
+/// Arguments for SetFundBenchmark instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetFundBenchmarkArgs {
+    /// Charge performance fees only on outperformance versus the benchmark
+    pub relative_fee: bool,
+}
+
+// === NAV Blackout Argument Structs ===
+
+/// Arguments for SetBlackoutPolicy instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetBlackoutPolicyArgs {
+    /// Longest blackout a manager may configure (seconds, 0 = none allowed)
+    pub max_window_secs: u32,
+    /// Smallest PnL threshold a manager may configure (bps of fund value)
+    pub min_threshold_bps: u16,
+}
+
+/// Arguments for SetFundBlackout instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetFundBlackoutArgs {
+    /// Blackout length after a large PnL (seconds, 0 = off)
+    pub window_secs: u32,
+    /// |PnL| as bps of fund value that starts a blackout
+    pub pnl_threshold_bps: u16,
+}
+
+// === Share Freeze Argument Structs ===
+
+/// Arguments for FreezeInvestorShares instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct FreezeInvestorSharesArgs {
+    /// Compliance reason code (must not be `None`)
+    pub reason: ShareFreezeReason,
+}
+
+// === Insurance Yield Argument Structs ===
+
+/// Arguments for SetInsuranceYieldVenue instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetInsuranceYieldVenueArgs {
+    /// Max share of insurance capital deployable (≤ MAX_INSURANCE_DEPLOY_BPS)
+    pub max_deploy_bps: u16,
+}
+
+/// Arguments for DeployInsuranceCapital / RecallInsuranceCapital
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InsuranceCapitalArgs {
+    /// Amount (e6); for recalls 0 = everything in the venue
+    pub amount_e6: i64,
+}
+
+// === Square Fund Argument Structs ===
+
+/// Arguments for WithdrawSquareFund instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct WithdrawSquareFundArgs {
+    /// Amount to withdraw (e6, ≤ accounted balance)
+    pub amount_e6: i64,
+}
+
+// === Revenue Split Argument Structs ===
+
+/// Arguments for SetRevenueSplit instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetRevenueSplitArgs {
+    /// Recipients (≤ MAX_SPLIT_RECIPIENTS, bps summing to 10000; empty = off)
+    pub recipients: Vec<RevenueSplitRecipient>,
+}
+
+// === Square Escrow Argument Structs ===
+
+/// Arguments for ResolveDispute instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ResolveDisputeArgs {
+    /// true = refund the buyer in full, false = release to creator/platform
+    pub refund: bool,
+}
+
+// === Square Tip Token Argument Structs ===
+
+/// Arguments for SetSquareTipToken instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetSquareTipTokenArgs {
+    /// Who may push prices
+    pub price_authority: Pubkey,
+    /// Max price age in seconds (0 = default 1 hour)
+    pub max_price_age_secs: i64,
+    /// Accept tips in this token
+    pub enabled: bool,
+}
+
+/// Arguments for UpdateSquareTipTokenPrice instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct UpdateSquareTipTokenPriceArgs {
+    /// USD price of one whole token (e6)
+    pub price_e6: i64,
+}
+
+/// Arguments for SquareTokenTip instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquareTokenTipArgs {
+    /// Creator address
     pub creator: Pubkey,
-    /// Content ID (unique identifier for the content)
+    /// Content ID (live stream)
     pub content_id: u64,
-    /// Payment type: 0 = KnowledgePurchase, 1 = Subscription, 2 = LiveDonation
-    pub payment_type: u8,
-    /// Total payment amount (e6)
-    pub amount_e6: i64,
-    /// Creator share in basis points (e.g., 9000 = 90%)
+    /// Tip amount in the token's base units
+    pub amount: u64,
+    /// Creator share in basis points
     pub creator_share_bps: u16,
-    /// Subscription period (number of months, 0 for non-subscription)
-    pub subscription_period: u8,
     /// Optional memo (max 32 bytes)
     pub memo: Vec<u8>,
+    /// Payer nonce, must exceed the last one in SquarePayerNonce (record PDA seed)
+    pub nonce: u64,
 }
 
-// === Referral Argument Structs ===
+// === Fund Hook Argument Structs ===
 
-/// Arguments for InitializeReferral instruction
+/// Arguments for SetFundHook instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct InitializeReferralArgs {
-    /// Base referrer share in basis points (e.g., 2000 = 20%)
-    pub referrer_share_bps: u16,
-    /// Base referee discount in basis points (e.g., 1000 = 10%)
-    pub referee_discount_bps: u16,
+pub struct SetFundHookArgs {
+    /// Hook program (default pubkey clears the hook)
+    pub hook_program: Pubkey,
 }
 
-/// Arguments for CreateReferralLink instruction
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CreateReferralLinkArgs {
-    /// Referral code (6-12 characters)
-    pub code: Vec<u8>,
+/// Discriminator leading every FundHookPayload
+pub const FUND_HOOK_PAYLOAD_DISCRIMINATOR: u64 = 0x464E445F484F4F4B; // "FND_HOOK"
+
+/// Fund event reported to a hook
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundHookEvent {
+    /// Shares minted for a deposit
+    Deposit = 0,
+    /// Shares burned for a redemption
+    Redeem = 1,
+}
+
+/// Instruction data of the CPI into a fund's hook program
+/// 
+/// Hook accounts:
+/// 0. `[signer]` FundHookAuthority PDA (`["fund_hook_authority", fund]` of this program)
+/// 1. `[]` Fund PDA
+/// 2. `[]` Investor
+/// 3. Hook's own accounts, in the order passed after the FundHookAuthority PDA (never signers)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FundHookPayload {
+    /// FUND_HOOK_PAYLOAD_DISCRIMINATOR
+    pub discriminator: u64,
+    /// Deposit or redemption
+    pub event: FundHookEvent,
+    /// Fund PDA
+    pub fund: Pubkey,
+    /// Investor wallet
+    pub investor: Pubkey,
+    /// Deposited amount / redemption payout (e6)
+    pub amount_e6: i64,
+    /// Shares minted / burned
+    pub shares: u64,
+    /// NAV per share used (e6)
+    pub nav_e6: i64,
+    /// Event timestamp
+    pub timestamp: i64,
+}
+
+// === Fund LP Referral Argument Structs ===
+
+/// Arguments for SetFundLPReferralProgram
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SetFundLPReferralProgramArgs {
+    /// Bounty as bps of referred deposits (≤ MAX_FUND_LP_REFERRAL_BOUNTY_BPS)
+    pub bounty_bps: u16,
+    /// Accept new bindings and accrue bounties
+    pub is_active: bool,
 }
 
-/// Arguments for RecordReferralTrade instruction (CPI)
+/// Arguments for BindFundLPReferral
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RecordReferralTradeArgs {
-    /// Trade fee amount (e6)
-    pub trade_fee_e6: i64,
-    /// Trade volume (e6)
-    pub trade_volume_e6: i64,
-    /// Referrer VIP level
-    pub referrer_vip_level: u8,
-    /// Referee VIP level
-    pub referee_vip_level: u8,
+pub struct BindFundLPReferralArgs {
+    /// Wallet credited with the referee's deposits
+    pub referrer: Pubkey,
 }
 
-/// Arguments for UpdateReferralConfig instruction
+// === Admin Intervention Argument Structs ===
+
+/// Arguments for AdminSetFundPaused
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct UpdateReferralConfigArgs {
-    /// New referrer share in basis points (None = no change)
-    pub referrer_share_bps: Option<u16>,
-    /// New referee discount in basis points (None = no change)
-    pub referee_discount_bps: Option<u16>,
-    /// New referrer VIP bonus array (None = no change)
-    pub referrer_vip_bonus_bps: Option<[u16; 6]>,
-    /// New referee VIP bonus array (None = no change)
-    pub referee_vip_bonus_bps: Option<[u16; 6]>,
-    /// New minimum settlement amount (None = no change)
-    pub min_settlement_amount_e6: Option<i64>,
-    /// Pause/unpause (None = no change)
-    pub is_paused: Option<bool>,
+pub struct AdminSetFundPausedArgs {
+    /// Target pause state
+    pub is_paused: bool,
+    /// Keep the manager from changing the pause state afterwards
+    pub lock_manager: bool,
+    /// Logged with the intervention event
+    pub reason: Option<String>,
 }
 
-/// Arguments for SetCustomReferralRates instruction
+/// Arguments for AdminSetFundOpen
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SetCustomReferralRatesArgs {
-    /// Custom referrer share in basis points (0 = use global)
-    pub custom_referrer_share_bps: u16,
-    /// Custom referee discount in basis points (0 = use global)
-    pub custom_referee_discount_bps: u16,
+pub struct AdminSetFundOpenArgs {
+    /// Target open state
+    pub is_open: bool,
+    /// Keep the manager from changing the open state afterwards
+    pub lock_manager: bool,
+    /// Logged with the intervention event
+    pub reason: Option<String>,
 }
 
-// === Prediction Market Fee Argument Structs ===
+// === Insurance Migration Argument Structs ===
 
-/// Arguments for InitializePredictionMarketFeeConfig instruction
+/// Arguments for MigrateInsuranceConfigV2
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct InitializePredictionMarketFeeConfigArgs {
-    /// Prediction market minting fee in basis points (default 10 = 0.1%)
-    pub prediction_market_minting_fee_bps: u16,
-    /// Prediction market redemption fee in basis points
-    pub prediction_market_redemption_fee_bps: u16,
-    /// Prediction market taker trading fee in basis points
-    pub prediction_market_trading_fee_taker_bps: u16,
-    /// Prediction market maker trading fee in basis points (usually 0)
-    pub prediction_market_trading_fee_maker_bps: u16,
-    /// Prediction market protocol share of fees in basis points
-    pub prediction_market_protocol_share_bps: u16,
-    /// Prediction market maker reward share in basis points
-    pub prediction_market_maker_reward_share_bps: u16,
-    /// Prediction market creator share in basis points
-    pub prediction_market_creator_share_bps: u16,
+pub struct MigrateInsuranceConfigV2Args {
+    /// Historical liquidation income (e6)
+    pub liquidation_income_e6: i64,
+    /// Historical trading-fee income (e6)
+    pub trading_fee_e6: i64,
 }
 
-/// Arguments for CollectPredictionMarketMintingFee instruction
+// === Stable Swap Argument Structs ===
+
+/// Arguments for SetStableSwapRoute
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CollectPredictionMarketMintingFeeArgs {
-    /// Prediction market minting amount (e6) - fee calculated based on this
-    pub prediction_market_minting_amount_e6: i64,
+pub struct SetStableSwapRouteArgs {
+    /// Whitelisted swap program
+    pub swap_program: Pubkey,
+    /// Max output shortfall vs par (bps, ≤ MAX_STABLE_SWAP_SLIPPAGE_BPS)
+    pub max_slippage_bps: u16,
+    /// Accept deposits through this route
+    pub is_active: bool,
 }
 
-/// Arguments for CollectPredictionMarketRedemptionFee instruction
+/// Arguments for DepositViaStableSwap
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CollectPredictionMarketRedemptionFeeArgs {
-    /// Prediction market redemption amount (e6) - fee calculated based on this
-    pub prediction_market_redemption_amount_e6: i64,
+pub struct DepositViaStableSwapArgs {
+    /// Max input tokens the swap may spend
+    pub amount_in: u64,
+    /// Caller's minimum output (the route's slippage bound also applies)
+    pub min_amount_out: u64,
+    /// Number of accounts passed to the swap program
+    pub swap_account_count: u8,
+    /// Swap program instruction data
+    pub swap_data: Vec<u8>,
+    /// Marketing campaign to attribute the deposit to
+    pub campaign_id: Option<u64>,
+    /// Payment reference (max MAX_MEMO_LEN bytes)
+    pub memo: Option<String>,
 }
 
-/// Arguments for CollectPredictionMarketTradingFee instruction
+// === Fee Rebate Argument Structs ===
+
+/// Arguments for RecordFeeRebate instruction (CPI)
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CollectPredictionMarketTradingFeeArgs {
-    /// Prediction market trade volume (e6) - fee calculated based on this
-    pub prediction_market_trade_volume_e6: i64,
-    /// Is this a taker fee?
-    pub is_taker: bool,
+pub struct RecordFeeRebateArgs {
+    /// Rebate amount (e6, > 0)
+    pub rebate_e6: i64,
 }
 
-/// Arguments for DistributePredictionMarketMakerReward instruction
+// === Risk Enforcement Argument Structs ===
+
+/// Arguments for EnforceRiskLimits instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct DistributePredictionMarketMakerRewardArgs {
-    /// Prediction market maker reward amount (e6)
-    pub prediction_market_maker_reward_e6: i64,
+pub struct EnforceRiskLimitsArgs {
+    /// Market of the position to reduce
+    pub market_index: u8,
+    /// Requested close size (e6, > 0; clamped to the excess)
+    pub size_e6: u64,
+    /// Keeper-supplied exit price (e6, within the mark price band)
+    pub price_e6: u64,
 }
 
-/// Arguments for DistributePredictionMarketCreatorReward instruction
+// === Notification Preferences Argument Structs ===
+
+/// Arguments for SetNotificationPrefs instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct DistributePredictionMarketCreatorRewardArgs {
-    /// Prediction market creator reward amount (e6)
-    pub prediction_market_creator_reward_e6: i64,
-    /// Prediction market ID (for tracking)
-    pub prediction_market_id: u64,
+pub struct SetNotificationPrefsArgs {
+    /// Hash commitment to the off-chain endpoint (non-zero)
+    pub endpoint_hash: [u8; 32],
+    /// Subscribed NOTIFY_* bits
+    pub event_mask: u32,
 }
 
-/// Arguments for UpdatePredictionMarketFeeConfig instruction
+// === Fund Exposure Argument Structs ===
+
+/// Arguments for RecordFundExposure instruction (CPI)
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct UpdatePredictionMarketFeeConfigArgs {
-    /// New prediction market minting fee (None = no change)
-    pub prediction_market_minting_fee_bps: Option<u16>,
-    /// New prediction market redemption fee (None = no change)
-    pub prediction_market_redemption_fee_bps: Option<u16>,
-    /// New prediction market taker trading fee (None = no change)
-    pub prediction_market_trading_fee_taker_bps: Option<u16>,
-    /// New prediction market maker trading fee (None = no change)
-    pub prediction_market_trading_fee_maker_bps: Option<u16>,
-    /// New prediction market protocol share (None = no change)
-    pub prediction_market_protocol_share_bps: Option<u16>,
-    /// New prediction market maker reward share (None = no change)
-    pub prediction_market_maker_reward_share_bps: Option<u16>,
-    /// New prediction market creator share (None = no change)
-    pub prediction_market_creator_share_bps: Option<u16>,
+pub struct RecordFundExposureArgs {
+    /// Total open position notional (e6)
+    pub open_notional_e6: u64,
+    /// Margin locked by open positions (e6)
+    pub margin_used_e6: u64,
+    /// Collateral free for new positions, incl. unrealized PnL (e6)
+    pub free_collateral_e6: i64,
 }
 
-/// Arguments for SetPredictionMarketFeePaused instruction
+// === Share Supply Checkpoint Argument Structs ===
+
+/// Arguments for InitializeShareCheckpoints instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SetPredictionMarketFeePausedArgs {
-    /// Prediction market fee paused state
-    pub prediction_market_fee_paused: bool,
+pub struct InitializeShareCheckpointsArgs {
+    /// Supply change that triggers a checkpoint (bps, 0 = DEFAULT_SHARE_CHECKPOINT_THRESHOLD_BPS)
+    pub threshold_bps: u16,
 }
 
-// ============================================================================
-// Relayer Instructions (200-249) - Admin/Relayer 代替用户签名
-// ============================================================================
+// === Relayer Pause Argument Structs ===
 
-/// Relayer 版本的 DepositToFund
+/// Arguments for SetRelayerSystemPaused instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RelayerDepositToFundArgs {
-    /// 用户钱包地址
-    pub user_wallet: Pubkey,
-    /// Amount to deposit (in USDC, 6 decimals)
-    pub amount: u64,
+pub struct SetRelayerSystemPausedArgs {
+    /// Pause (true) or resume (false) Relayer* instructions
+    pub is_paused: bool,
 }
 
-/// Relayer 版本的 RedeemFromFund
+// === Insurance Config Update Argument Structs ===
+
+/// Arguments for ScheduleInsuranceConfigUpdate instruction (`None` = unchanged)
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RelayerRedeemFromFundArgs {
-    /// 用户钱包地址
-    pub user_wallet: Pubkey,
-    /// Number of shares to redeem
-    pub shares: u64,
+pub struct UpdateInsuranceConfigArgs {
+    /// New ADL balance insufficiency trigger threshold (e6, >= 0)
+    pub adl_trigger_threshold_e6: Option<i64>,
+    /// New LP redemption delay in seconds (0..=MAX_INSURANCE_WITHDRAWAL_DELAY_SECS)
+    pub withdrawal_delay_secs: Option<i64>,
+    /// New authorized caller (Ledger Program)
+    pub authorized_caller: Option<Pubkey>,
 }
 
-/// Relayer 版本的 RedeemFromInsuranceFund
+// === LP Position Transfer Argument Structs ===
+
+/// Arguments for TransferLPPosition instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RelayerRedeemFromInsuranceFundArgs {
-    /// 用户钱包地址
-    pub user_wallet: Pubkey,
-    /// Number of shares to redeem
-    pub shares: u64,
+pub struct TransferLPPositionArgs {
+    /// Wallet receiving the position (must also sign)
+    pub new_owner: Pubkey,
 }
 
-/// Relayer 版本的 SquarePayment
+// === Fund Expense Argument Structs ===
+
+/// Arguments for PayFundExpense instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RelayerSquarePaymentArgs {
-    /// 付款用户钱包地址
-    pub payer_wallet: Pubkey,
-    /// Creator address (content owner)
-    pub creator: Pubkey,
-    /// Content ID (unique identifier for the content)
-    pub content_id: u64,
-    /// Payment type: 0 = KnowledgePurchase, 1 = Subscription, 2 = LiveDonation
-    pub payment_type: u8,
-    /// Total payment amount (e6)
+pub struct PayFundExpenseArgs {
+    /// Amount to pay (e6)
     pub amount_e6: i64,
-    /// Creator share in basis points (e.g., 9000 = 90%)
-    pub creator_share_bps: u16,
-    /// Subscription period (number of months, 0 for non-subscription)
-    pub subscription_period: u8,
-    /// Optional memo (max 32 bytes)
-    pub memo: Vec<u8>,
+    /// Hash of the off-chain expense memo (invoice, description)
+    pub memo_hash: [u8; 32],
 }
 
-/// Relayer 版本的 BindReferral
+// === Insurance Coverage Argument Structs ===
+
+/// Arguments for ReportPlatformOpenInterest instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RelayerBindReferralArgs {
-    /// 新用户钱包地址
-    pub user_wallet: Pubkey,
-    /// Referral link address
-    pub referral_link: Pubkey,
+pub struct ReportPlatformOpenInterestArgs {
+    /// Platform-wide open interest (e6)
+    pub open_interest_e6: i64,
 }
 
-// ============================================================================
-// Relayer Management Instructions (250-259)
-// ============================================================================
+// === Referral Claim Argument Structs ===
 
-/// 添加授权 Relayer
+/// Arguments for ClaimReferralRewards instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct AddRelayerArgs {
-    /// 新 Relayer 公钥
-    pub relayer: Pubkey,
+pub struct ClaimReferralRewardsArgs {
+    /// Deposit the rewards into a fund instead of paying out USDC
+    pub into_fund_shares: bool,
 }
 
-/// 移除 Relayer
+// === Fund Lookup Table Argument Structs ===
+
+/// Arguments for CreateFundLookupTable instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct RemoveRelayerArgs {
-    /// 要移除的 Relayer 公钥
-    pub relayer: Pubkey,
+pub struct CreateFundLookupTableArgs {
+    /// Recent slot used to derive the table address
+    pub recent_slot: u64,
 }
 
-/// 更新 Relayer 限额配置
+/// Arguments for ExtendFundLookupTable instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct UpdateRelayerLimitsArgs {
-    /// 单笔交易限额 (e6), 0 = 无限制
-    pub single_tx_limit_e6: Option<i64>,
-    /// 每日限额 (e6), 0 = 无限制
-    pub daily_limit_e6: Option<i64>,
+pub struct ExtendFundLookupTableArgs {
+    /// Addresses to append (at most MAX_LOOKUP_TABLE_EXTEND_ADDRESSES)
+    pub addresses: Vec<Pubkey>,
 }
 
-// ============================================================================
-// Spot Trading Fee Arguments
-// ============================================================================
+// === Fund Coverage Argument Structs ===
 
-/// 初始化 Spot 交易手续费配置
+/// Arguments for RegisterFundCoverage instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct InitializeSpotTradingFeeConfigArgs {
-    /// 授权调用方 (Vault Program)
-    pub authorized_caller: Pubkey,
+pub struct RegisterFundCoverageArgs {
+    /// Annual premium (bps of fund value, ≤ MAX_COVERAGE_PREMIUM_BPS)
+    pub premium_bps: u16,
+    /// Share of the qualifying loss compensated (bps, ≤ MAX_COVERAGE_BPS)
+    pub coverage_bps: u16,
+    /// Drawdown from inception NAV absorbed by LPs (bps, ≤ MAX_COVERAGE_DEDUCTIBLE_BPS)
+    pub deductible_bps: u16,
+    /// Maximum total compensation (e6)
+    pub coverage_limit_e6: i64,
 }
 
-/// 收取 Spot 交易手续费
+// === Mark Price Argument Structs ===
+
+/// Arguments for UpdateMarkPrice instruction
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct CollectSpotTradingFeeArgs {
-    /// 交易金额 (e6)
-    pub volume_e6: i64,
-    /// 是否为 Taker
-    pub is_taker: bool,
+pub struct UpdateMarkPriceArgs {
+    /// Ledger market index
+    pub market_index: u8,
+    /// Mark price (e6, > 0)
+    pub price_e6: u64,
 }
 
-/// 分配 Spot 手续费
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct DistributeSpotFeeArgs {
-    /// 要分配的金额 (e6), 0 = 分配全部余额
-    pub amount_e6: i64,
+// === Return Data ===
+// Written through `return_data::set_fund_return_data` (tag + version envelope)
+
+/// Return data of CollectFees
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CollectFeesResult {
+    /// Management fee collected (e6)
+    pub management_fee_e6: i64,
+    /// Performance fee collected (e6)
+    pub performance_fee_e6: i64,
+    /// Management fee left accrued (e6)
+    pub deferred_management_fee_e6: i64,
+    /// Performance fee left accrued (e6)
+    pub deferred_performance_fee_e6: i64,
 }
 
-/// 发放 Spot 做市商奖励
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct DistributeSpotMakerRewardArgs {
-    /// 做市商地址
-    pub maker: Pubkey,
-    /// 奖励金额 (e6)
-    pub reward_e6: i64,
+/// Return data of a simulated TradeFund
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TradeSimulationResult {
+    /// Position notional: size × price (e6)
+    pub notional_e6: u64,
+    /// Initial margin required: notional / leverage (e6)
+    pub required_margin_e6: u64,
+    /// Fund value available as margin (e6)
+    pub available_margin_e6: i64,
+    /// Leverage used
+    pub leverage: u8,
 }
 
-/// 更新 Spot 手续费配置
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct UpdateSpotTradingFeeConfigArgs {
-    /// Taker 费率 (bps)
-    pub taker_fee_bps: Option<u16>,
-    /// Maker 费率 (bps)
-    pub maker_fee_bps: Option<u16>,
-    /// 协议收入占比 (bps)
-    pub protocol_share_bps: Option<u16>,
-    /// 保险基金占比 (bps)
-    pub insurance_share_bps: Option<u16>,
-    /// 返佣池占比 (bps)
-    pub referral_share_bps: Option<u16>,
-    /// 做市商激励占比 (bps)
-    pub maker_reward_share_bps: Option<u16>,
+/// Return data of VerifyBalanceProof
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BalanceProofResult {
+    /// Snapshot the proof was checked against
+    pub snapshot_ts: i64,
+    /// Root the proof was checked against
+    pub root: [u8; 32],
+}
+
+/// Return data of GetInsuranceHealth
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InsuranceHealthResult {
+    /// Vault balance (e6)
+    pub balance_e6: i64,
+    /// Balance / open interest (bps, u64::MAX when open interest is 0)
+    pub coverage_ratio_bps: u64,
+    /// Seconds since the last hourly snapshot
+    pub secs_since_snapshot: i64,
+    /// ADL in progress (LP redemptions paused)
+    pub is_adl_in_progress: bool,
+    /// Liquidation income + ADL profit today (e6)
+    pub income_24h_e6: i64,
+    /// Shortfall payouts today (e6)
+    pub outflow_24h_e6: i64,
+}
+
+/// Return data of TriggerADLIfNeeded
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TriggerADLResult {
+    /// ADL was started by this call
+    pub triggered: bool,
+    /// Trigger condition met (None when nothing triggers)
+    pub reason: ADLTriggerReason,
+    /// Current ADL episode (adl_trigger_count)
+    pub episode: u64,
+    /// Vault balance evaluated (e6)
+    pub balance_e6: i64,
+}
+
+/// Return data of UpdateNAVMulti
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UpdateNAVMultiResult {
+    /// Number of funds attempted before stopping (≤ funds passed)
+    pub processed: u8,
+    /// Bit i set = fund i updated successfully
+    pub success_bitmap: u32,
+}
+
+/// Return data of SettleReferralRewardsBatch
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SettleReferralRewardsBatchResult {
+    /// Number of referrers attempted before stopping (≤ referrers passed)
+    pub processed: u8,
+    /// Bit i set = referrer i paid out
+    pub settled_bitmap: u32,
+    /// Total paid out in this batch (e6)
+    pub total_settled_e6: i64,
+}
+
+/// `DepositResult::remaining_capacity_e6` when the fund has no deposit cap
+pub const DEPOSIT_CAPACITY_UNLIMITED: i64 = i64::MAX;
+
+/// Return data of DepositToFund (post-deposit fund state)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DepositResult {
+    /// Shares minted to the investor
+    pub shares_minted: u64,
+    /// Amount deposited (e6)
+    pub amount_e6: i64,
+    /// NAV the shares were minted at (e6)
+    pub nav_e6: i64,
+    /// Fund total shares after the deposit
+    pub total_shares: u64,
+    /// Investor's LP position shares after the deposit
+    pub position_shares: u64,
+    /// Deposit headroom left under the fund's caps (e6, DEPOSIT_CAPACITY_UNLIMITED = uncapped)
+    pub remaining_capacity_e6: i64,
+    /// Fund still accepting deposits
+    pub accepting_deposits: bool,
 }
 
 #[cfg(test)]
@@ -1031,6 +3992,9 @@ mod tests {
             performance_fee_bps: 2000,
             use_high_water_mark: true,
             fee_collection_interval: 86400,
+            initial_deposit_amount: Some(1_000_000_000),
+            expense_cap_bps: 50,
+            max_expense_payment_e6: 10_000_000_000,
         };
         let ix = FundInstruction::CreateFund(args);
         let serialized = ix.try_to_vec().unwrap();
@@ -1042,6 +4006,8 @@ mod tests {
             FundInstruction::CreateFund(a) => {
                 assert_eq!(a.name, "Test Fund");
                 assert_eq!(a.management_fee_bps, 200);
+                assert_eq!(a.initial_deposit_amount, Some(1_000_000_000));
+                assert_eq!(a.expense_cap_bps, 50);
             }
             _ => panic!("Wrong instruction type"),
         }
@@ -1049,7 +4015,7 @@ mod tests {
 
     #[test]
     fn test_deposit_instruction() {
-        let args = DepositToFundArgs { amount: 1_000_000 };
+        let args = DepositToFundArgs { amount: 1_000_000, campaign_id: Some(7), memo: Some("INV-0042".to_string()) };
         let ix = FundInstruction::DepositToFund(args);
         let serialized = ix.try_to_vec().unwrap();
         
@@ -1057,6 +4023,8 @@ mod tests {
         match deserialized {
             FundInstruction::DepositToFund(a) => {
                 assert_eq!(a.amount, 1_000_000);
+                assert_eq!(a.campaign_id, Some(7));
+                assert_eq!(a.memo.as_deref(), Some("INV-0042"));
             }
             _ => panic!("Wrong instruction type"),
         }
@@ -1064,7 +4032,7 @@ mod tests {
 
     #[test]
     fn test_redeem_instruction() {
-        let args = RedeemFromFundArgs { shares: 500_000 };
+        let args = RedeemFromFundArgs { shares: 500_000, memo: None };
         let ix = FundInstruction::RedeemFromFund(args);
         let serialized = ix.try_to_vec().unwrap();
         
@@ -1076,5 +4044,109 @@ mod tests {
             _ => panic!("Wrong instruction type"),
         }
     }
-}
 
+    #[test]
+    fn test_update_nav_multi_result() {
+        let result = UpdateNAVMultiResult { processed: 3, success_bitmap: 0b101 };
+        let data = result.try_to_vec().unwrap();
+        assert_eq!(data.len(), 5);
+        
+        let decoded = UpdateNAVMultiResult::try_from_slice(&data).unwrap();
+        assert_eq!(decoded, result);
+        assert!(MAX_NAV_MULTI_FUNDS <= u32::BITS as usize);
+    }
+
+    #[test]
+    fn test_settle_referral_rewards_batch_result() {
+        let result = SettleReferralRewardsBatchResult { processed: 4, settled_bitmap: 0b1011, total_settled_e6: 45_000_000 };
+        let data = result.try_to_vec().unwrap();
+        assert_eq!(data.len(), 13);
+        assert_eq!(SettleReferralRewardsBatchResult::try_from_slice(&data).unwrap(), result);
+        assert!(MAX_REFERRAL_SETTLEMENT_BATCH <= u32::BITS as usize);
+    }
+
+    #[test]
+    fn test_deposit_result() {
+        let result = DepositResult {
+            shares_minted: 100_000_000,
+            amount_e6: 100_000_000,
+            nav_e6: 1_000_000,
+            total_shares: 250_000_000,
+            position_shares: 150_000_000,
+            remaining_capacity_e6: DEPOSIT_CAPACITY_UNLIMITED,
+            accepting_deposits: true,
+        };
+        let data = result.try_to_vec().unwrap();
+        assert_eq!(data.len(), 49);
+        assert_eq!(DepositResult::try_from_slice(&data).unwrap(), result);
+    }
+
+    #[test]
+    fn test_trigger_adl_result() {
+        let result = TriggerADLResult {
+            triggered: true,
+            reason: ADLTriggerReason::RapidDecline,
+            episode: 4,
+            balance_e6: 650_000_000,
+        };
+        let data = result.try_to_vec().unwrap();
+        assert_eq!(data.len(), 18);
+        assert_eq!(TriggerADLResult::try_from_slice(&data).unwrap(), result);
+    }
+
+    #[test]
+    fn test_fund_hook_payload_layout() {
+        let payload = FundHookPayload {
+            discriminator: FUND_HOOK_PAYLOAD_DISCRIMINATOR,
+            event: FundHookEvent::Redeem,
+            fund: Pubkey::new_unique(),
+            investor: Pubkey::new_unique(),
+            amount_e6: 1_050_000_000,
+            shares: 1_000_000_000,
+            nav_e6: 1_050_000,
+            timestamp: 1700000000,
+        };
+        let data = payload.try_to_vec().unwrap();
+        // Fixed layout hooks can parse without borsh
+        assert_eq!(data.len(), 8 + 1 + 32 + 32 + 8 + 8 + 8 + 8);
+        assert_eq!(&data[..8], &FUND_HOOK_PAYLOAD_DISCRIMINATOR.to_le_bytes());
+        assert_eq!(data[8], 1);
+        assert_eq!(FundHookPayload::try_from_slice(&data).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_unpack_errors() {
+        let mut data = FundInstruction::RedeemFromFund(RedeemFromFundArgs { shares: 500_000, memo: None })
+            .try_to_vec()
+            .unwrap();
+        assert!(matches!(FundInstruction::unpack(&data), Ok(FundInstruction::RedeemFromFund(a)) if a.shares == 500_000));
+        
+        // Trailing bytes are no longer silently rejected as generic invalid data
+        data.push(0);
+        assert_eq!(
+            FundInstruction::unpack(&data).unwrap_err(),
+            FundError::InstructionDataTrailingBytes.into()
+        );
+        
+        // Truncated args
+        assert_eq!(FundInstruction::unpack(&data[..4]).unwrap_err(), ProgramError::InvalidInstructionData);
+        
+        assert_eq!(FundInstruction::unpack(&[]).unwrap_err(), FundError::UnknownInstruction.into());
+        assert_eq!(
+            FundInstruction::unpack(&[FundInstruction::VARIANT_COUNT]).unwrap_err(),
+            FundError::UnknownInstruction.into()
+        );
+    }
+
+    #[test]
+    fn test_variant_count() {
+        // VARIANT_COUNT must track the enum: the index after the last variant is unknown to borsh...
+        let err = FundInstruction::try_from_slice(&[FundInstruction::VARIANT_COUNT]).unwrap_err();
+        assert!(err.to_string().contains("Unexpected variant index"), "{}", err);
+        // ...and the last index is a real variant (fails on missing args, if any, not on the index)
+        let last = [&[FundInstruction::VARIANT_COUNT - 1][..], &[0u8; 1024][..]].concat();
+        if let Err(err) = FundInstruction::try_from_slice(&last) {
+            assert!(!err.to_string().contains("Unexpected variant index"), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Fund Program Accounting Invariants
+//!
+//! Hard checks on Fund accounting that must hold after every mutation.
+//! Handlers call `enforce_fund_invariants` after writing a Fund; the checks
+//! only run when built with the `strict-invariants` feature (tests, devnet),
+//! so mainnet builds pay no compute for them.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+};
+
+use crate::{
+    error::FundError,
+    state::Fund,
+    utils::INITIAL_NAV_E6,
+};
+
+/// Checks that only need the Fund account
+///
+/// - total_shares == 0 ⇒ NAV == initial NAV (1.0)
+/// - NAV ≥ 0 (0 only once the fund is wiped out)
+/// - HWM ≥ initial NAV
+pub fn check_fund_state_invariants(fund: &Fund) -> Result<(), FundError> {
+    let stats = &fund.stats;
+
+    if stats.total_shares == 0 && stats.current_nav_e6 != INITIAL_NAV_E6 {
+        msg!("❌ INVARIANT: zero shares but NAV = {}", stats.current_nav_e6);
+        return Err(FundError::InvariantViolation);
+    }
+
+    if stats.current_nav_e6 < 0 {
+        msg!("❌ INVARIANT: negative NAV {} with {} shares", stats.current_nav_e6, stats.total_shares);
+        return Err(FundError::InvariantViolation);
+    }
+
+    if stats.high_water_mark_e6 < INITIAL_NAV_E6 {
+        msg!("❌ INVARIANT: HWM {} below initial NAV", stats.high_water_mark_e6);
+        return Err(FundError::InvariantViolation);
+    }
+
+    Ok(())
+}
+
+/// All Fund invariants, including vault solvency
+///
+/// The vault plus capital deployed to a yield venue must hold at least the
+/// value accounted to LPs (deposits − withdrawals + realized PnL − fees).
+pub fn check_fund_invariants(fund: &Fund, vault_balance: u64) -> Result<(), FundError> {
+    check_fund_state_invariants(fund)?;
+
+    let liabilities = fund.stats.total_value_e6().max(0) as u64;
+    let vault_balance = vault_balance.saturating_add(fund.deployed_capital_e6.max(0) as u64);
+    if vault_balance < liabilities {
+        msg!("❌ INVARIANT: vault {} below accounted liabilities {}", vault_balance, liabilities);
+        return Err(FundError::InvariantViolation);
+    }
+
+    Ok(())
+}
+
+/// Run the Fund invariants when built with `strict-invariants`
+///
+/// Pass the fund vault when the handler has it to include the solvency
+/// check. Violations also trip a debug assertion so native tests fail at
+/// the offending handler.
+pub fn enforce_fund_invariants(fund: &Fund, fund_vault: Option<&AccountInfo>) -> ProgramResult {
+    if !cfg!(feature = "strict-invariants") {
+        return Ok(());
+    }
+
+    let result = match fund_vault {
+        Some(vault) => {
+            if *vault.key != fund.fund_vault {
+                return Err(FundError::InvalidFundAccount.into());
+            }
+            let balance = if vault.data_len() == spl_token::state::Account::LEN {
+                let amount = spl_token::state::Account::unpack(&vault.data.borrow())?.amount;
+                fund.token_amount_to_e6(amount)? as u64
+            } else {
+                0
+            };
+            check_fund_invariants(fund, balance)
+        }
+        None => check_fund_state_invariants(fund),
+    };
+
+    debug_assert!(result.is_ok(), "fund invariant violated: {:?}", result);
+    result.map_err(ProgramError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::FeeConfig;
+    use solana_program::pubkey::Pubkey;
+
+    fn test_fund() -> Fund {
+        Fund::new(
+            Pubkey::new_unique(),
+            "Invariant Fund",
+            255,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            0,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_fund_invariants() {
+        let mut fund = test_fund();
+        assert!(check_fund_invariants(&fund, 0).is_ok());
+
+        fund.record_deposit(1_000_000_000, 1_000_000_000).unwrap();
+        assert!(check_fund_invariants(&fund, 1_000_000_000).is_ok());
+
+        // Vault short of what LPs are owed
+        assert!(matches!(check_fund_invariants(&fund, 999_999_999), Err(FundError::InvariantViolation)));
+
+        // Capital deployed to a yield venue still backs LPs
+        fund.deployed_capital_e6 = 400_000_000;
+        assert!(check_fund_invariants(&fund, 600_000_000).is_ok());
+        fund.deployed_capital_e6 = 0;
+
+        // Zero shares must mean NAV 1.0
+        let mut empty = test_fund();
+        empty.stats.current_nav_e6 = 900_000;
+        assert!(matches!(check_fund_state_invariants(&empty), Err(FundError::InvariantViolation)));
+
+        // Wiped out: NAV 0 is allowed, negative NAV is not
+        let mut wiped = test_fund();
+        wiped.record_deposit(1_000_000_000, 1_000_000_000).unwrap();
+        wiped.record_pnl(-1_500_000_000).unwrap();
+        assert_eq!(wiped.stats.current_nav_e6, 0);
+        assert!(check_fund_invariants(&wiped, 0).is_ok());
+        wiped.stats.current_nav_e6 = -1;
+        assert!(matches!(check_fund_state_invariants(&wiped), Err(FundError::InvariantViolation)));
+
+        // HWM never below initial NAV
+        fund.stats.high_water_mark_e6 = INITIAL_NAV_E6 - 1;
+        assert!(matches!(check_fund_state_invariants(&fund), Err(FundError::InvariantViolation)));
+    }
+}
@@ -0,0 +1,276 @@
+//! Ledger Program Instruction Interface
+//!
+//! Wire format of the Ledger instructions the Fund Program calls through CPI.
+//! Each instruction is a 1-byte discriminator followed by the Borsh-encoded
+//! arguments. Discriminators are explicit constants (not enum order), and the
+//! tests below pin the exact bytes, so any layout change has to bump
+//! `LEDGER_INTERFACE_VERSION` and the pinned encodings together.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Version of the Ledger instruction layout this crate encodes
+pub const LEDGER_INTERFACE_VERSION: u8 = 1;
+
+/// Seed of the Ledger PDA that signs the Fund Program's Ledger-only instructions
+///
+/// The Ledger Program cannot sign for its own id, so RecordFeeRebate and
+/// RecordFundExposure require this PDA (derived under `ledger_program`) as a
+/// signer; the Ledger passes it through `invoke_signed`.
+pub const FUND_CALLER_SEED: &[u8] = b"fund_caller";
+
+/// Ledger `OpenPosition` discriminator
+pub const OPEN_POSITION_DISCRIMINATOR: u8 = 0;
+
+/// Ledger `ClosePosition` discriminator
+pub const CLOSE_POSITION_DISCRIMINATOR: u8 = 1;
+
+/// Ledger `OpenPosition` arguments
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OpenPositionArgs {
+    /// Position owner (the fund itself for fund trades)
+    pub user: Pubkey,
+    /// Market index
+    pub market_index: u8,
+    /// 0 = Long, 1 = Short
+    pub side: u8,
+    /// Size (e6)
+    pub size_e6: u64,
+    /// Entry price (e6)
+    pub price_e6: u64,
+    /// Leverage
+    pub leverage: u8,
+    /// Batch ID
+    pub batch_id: u64,
+}
+
+/// Ledger `ClosePosition` arguments
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClosePositionArgs {
+    /// Position owner (the fund itself for fund trades)
+    pub user: Pubkey,
+    /// Market index
+    pub market_index: u8,
+    /// Size (e6, 0 = close all)
+    pub size_e6: u64,
+    /// Exit price (e6)
+    pub price_e6: u64,
+    /// Batch ID
+    pub batch_id: u64,
+}
+
+/// Ledger instructions called by the Fund Program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerInstruction {
+    /// Open a position (relayer signed)
+    OpenPosition(OpenPositionArgs),
+    /// Close a position (relayer signed)
+    ClosePosition(ClosePositionArgs),
+}
+
+impl LedgerInstruction {
+    /// Encode as discriminator + Borsh arguments
+    pub fn pack(&self) -> Result<Vec<u8>, ProgramError> {
+        let (discriminator, args) = match self {
+            Self::OpenPosition(args) => (OPEN_POSITION_DISCRIMINATOR, args.try_to_vec()?),
+            Self::ClosePosition(args) => (CLOSE_POSITION_DISCRIMINATOR, args.try_to_vec()?),
+        };
+        let mut data = Vec::with_capacity(1 + args.len());
+        data.push(discriminator);
+        data.extend_from_slice(&args);
+        Ok(data)
+    }
+
+    /// Decode instruction data produced by `pack`
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let (&discriminator, args) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+        let instruction = match discriminator {
+            OPEN_POSITION_DISCRIMINATOR => Self::OpenPosition(OpenPositionArgs::try_from_slice(args)?),
+            CLOSE_POSITION_DISCRIMINATOR => Self::ClosePosition(ClosePositionArgs::try_from_slice(args)?),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        Ok(instruction)
+    }
+}
+
+/// Leading fields of a Ledger position account
+///
+/// Only the prefix the Fund Program reads is decoded; the Ledger may append
+/// fields after `size_e6` without breaking this view.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LedgerPositionHeader {
+    /// Ledger account discriminator
+    pub discriminator: u64,
+    /// Position owner
+    pub user: Pubkey,
+    /// Market index
+    pub market_index: u8,
+    /// 0 = Long, 1 = Short
+    pub side: u8,
+    /// Open size (e6)
+    pub size_e6: u64,
+}
+
+impl LedgerPositionHeader {
+    /// Encoded prefix length in bytes
+    pub const LEN: usize = 8 + 32 + 1 + 1 + 8;
+
+    /// Decode the header from a position account's data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let header = data.get(..Self::LEN).ok_or(ProgramError::InvalidAccountData)?;
+        Ok(Self::try_from_slice(header)?)
+    }
+}
+
+/// Address of the Ledger's fund caller PDA (seeds: [FUND_CALLER_SEED])
+pub fn fund_caller_address(ledger_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FUND_CALLER_SEED], ledger_program_id)
+}
+
+/// Build a Ledger `OpenPosition` instruction
+///
+/// Accounts: relayer (signer), position, user account, vault config,
+/// ledger config, user stats, Vault Program, System Program.
+#[allow(clippy::too_many_arguments)]
+pub fn open_position_instruction(
+    ledger_program_id: &Pubkey,
+    relayer: &Pubkey,
+    position: &Pubkey,
+    user_account: &Pubkey,
+    vault_config: &Pubkey,
+    ledger_config: &Pubkey,
+    user_stats: &Pubkey,
+    vault_program: &Pubkey,
+    system_program: &Pubkey,
+    args: OpenPositionArgs,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *ledger_program_id,
+        accounts: vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new(*position, false),
+            AccountMeta::new(*user_account, false),
+            AccountMeta::new_readonly(*vault_config, false),
+            AccountMeta::new(*ledger_config, false),
+            AccountMeta::new(*user_stats, false),
+            AccountMeta::new_readonly(*vault_program, false),
+            AccountMeta::new_readonly(*system_program, false),
+        ],
+        data: LedgerInstruction::OpenPosition(args).pack()?,
+    })
+}
+
+/// Build a Ledger `ClosePosition` instruction
+///
+/// Accounts: relayer (signer), position, user account, vault config,
+/// insurance fund, ledger config, user stats, Vault Program.
+#[allow(clippy::too_many_arguments)]
+pub fn close_position_instruction(
+    ledger_program_id: &Pubkey,
+    relayer: &Pubkey,
+    position: &Pubkey,
+    user_account: &Pubkey,
+    vault_config: &Pubkey,
+    insurance_fund: &Pubkey,
+    ledger_config: &Pubkey,
+    user_stats: &Pubkey,
+    vault_program: &Pubkey,
+    args: ClosePositionArgs,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *ledger_program_id,
+        accounts: vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new(*position, false),
+            AccountMeta::new(*user_account, false),
+            AccountMeta::new_readonly(*vault_config, false),
+            AccountMeta::new(*insurance_fund, false),
+            AccountMeta::new(*ledger_config, false),
+            AccountMeta::new(*user_stats, false),
+            AccountMeta::new_readonly(*vault_program, false),
+        ],
+        data: LedgerInstruction::ClosePosition(args).pack()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_instruction_encoding_pinned() {
+        assert_eq!(LEDGER_INTERFACE_VERSION, 1);
+        let user = Pubkey::new_from_array([7u8; 32]);
+
+        let open = LedgerInstruction::OpenPosition(OpenPositionArgs {
+            user,
+            market_index: 2,
+            side: 1,
+            size_e6: 3,
+            price_e6: 4,
+            leverage: 5,
+            batch_id: 6,
+        });
+        let data = open.pack().unwrap();
+        let expected = [
+            &[OPEN_POSITION_DISCRIMINATOR][..],
+            &[7u8; 32],
+            &[2, 1],
+            &3u64.to_le_bytes(),
+            &4u64.to_le_bytes(),
+            &[5],
+            &6u64.to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(data, expected);
+        assert_eq!(data.len(), 60);
+        assert_eq!(LedgerInstruction::unpack(&data).unwrap(), open);
+
+        let close = LedgerInstruction::ClosePosition(ClosePositionArgs {
+            user,
+            market_index: 2,
+            size_e6: 3,
+            price_e6: 4,
+            batch_id: 6,
+        });
+        let data = close.pack().unwrap();
+        let expected = [
+            &[CLOSE_POSITION_DISCRIMINATOR][..],
+            &[7u8; 32],
+            &[2],
+            &3u64.to_le_bytes(),
+            &4u64.to_le_bytes(),
+            &6u64.to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(data, expected);
+        assert_eq!(data.len(), 58);
+        assert_eq!(LedgerInstruction::unpack(&data).unwrap(), close);
+
+        assert!(LedgerInstruction::unpack(&[]).is_err());
+        assert!(LedgerInstruction::unpack(&[2]).is_err());
+        // Trailing bytes are a layout mismatch, not silently ignored
+        assert!(LedgerInstruction::unpack(&[data, vec![0]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_ledger_position_header_pinned() {
+        let data = [
+            &9u64.to_le_bytes()[..],
+            &[7u8; 32],
+            &[2, 1],
+            &3_000_000u64.to_le_bytes(),
+            &[0xAA; 16],
+        ]
+        .concat();
+        let header = LedgerPositionHeader::unpack(&data).unwrap();
+        assert_eq!(header.user, Pubkey::new_from_array([7u8; 32]));
+        assert_eq!((header.market_index, header.side, header.size_e6), (2, 1, 3_000_000));
+        assert_eq!(header.try_to_vec().unwrap().len(), LedgerPositionHeader::LEN);
+        assert!(LedgerPositionHeader::unpack(&data[..LedgerPositionHeader::LEN - 1]).is_err());
+    }
+}
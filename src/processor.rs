@@ -19,8 +19,11 @@ use solana_program::{
 use crate::{
     error::FundError,
     instruction::*,
+    invariants::enforce_fund_invariants,
+    return_data::set_fund_return_data,
     state::*,
     utils::*,
+    validate_accounts,
 };
 
 /// Process a Fund Program instruction
@@ -29,8 +32,12 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = FundInstruction::try_from_slice(instruction_data)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let instruction = FundInstruction::unpack(instruction_data)?;
+
+    let required_features = instruction.required_features();
+    if required_features != 0 {
+        assert_features_enabled(program_id, accounts, required_features)?;
+    }
 
     match instruction {
         // Initialization
@@ -39,20 +46,23 @@ pub fn process_instruction(
         
         // Fund Management
         FundInstruction::UpdateFund(args) => process_update_fund(program_id, accounts, args),
-        FundInstruction::SetFundOpen(args) => process_set_fund_open(program_id, accounts, args),
-        FundInstruction::SetFundPaused(args) => process_set_fund_paused(program_id, accounts, args),
+        FundInstruction::SetFundState(args) => process_set_fund_state(program_id, accounts, args),
+        FundInstruction::RetiredSetFundPaused => {
+            msg!("SetFundPaused was replaced by SetFundState");
+            Err(FundError::UnknownInstruction.into())
+        }
         FundInstruction::CloseFund => process_close_fund(program_id, accounts),
         
         // LP Operations
-        FundInstruction::DepositToFund(args) => process_deposit_to_fund(program_id, accounts, args),
-        FundInstruction::RedeemFromFund(args) => process_redeem_from_fund(program_id, accounts, args),
+        FundInstruction::DepositToFund(args) => process_deposit_to_fund(program_id, accounts, args, false, false),
+        FundInstruction::RedeemFromFund(args) => process_redeem_from_fund(program_id, accounts, args, false),
         
         // Trading Operations
         FundInstruction::TradeFund(args) => process_trade_fund(program_id, accounts, args),
         FundInstruction::CloseFundPosition(args) => process_close_fund_position(program_id, accounts, args),
         
         // Fee Operations
-        FundInstruction::CollectFees => process_collect_fees(program_id, accounts),
+        FundInstruction::CollectFees(args) => process_collect_fees(program_id, accounts, args),
         
         // Admin Operations
         FundInstruction::UpdateAuthority(args) => process_update_authority(program_id, accounts, args),
@@ -176,6 +186,610 @@ pub fn process_instruction(
             msg!("Instruction: UpdateRelayerLimits");
             process_update_relayer_limits(program_id, accounts, args)
         }
+        
+        // Deposit Receipts
+        FundInstruction::SetDepositReceipts(args) => {
+            msg!("Instruction: SetDepositReceipts");
+            process_set_deposit_receipts(program_id, accounts, args)
+        }
+        
+        // LP Beneficiary
+        FundInstruction::SetLPBeneficiary(args) => {
+            msg!("Instruction: SetLPBeneficiary");
+            process_set_lp_beneficiary(program_id, accounts, args)
+        }
+        FundInstruction::ClaimAsBeneficiary => {
+            msg!("Instruction: ClaimAsBeneficiary");
+            process_claim_as_beneficiary(program_id, accounts)
+        }
+        FundInstruction::SetBeneficiaryInactivityPeriod(args) => {
+            msg!("Instruction: SetBeneficiaryInactivityPeriod");
+            process_set_beneficiary_inactivity_period(program_id, accounts, args)
+        }
+        
+        // Fund Creation Limits
+        FundInstruction::UpdateFundCreationLimits(args) => {
+            msg!("Instruction: UpdateFundCreationLimits");
+            process_update_fund_creation_limits(program_id, accounts, args)
+        }
+        
+        // Fund Listing Approval
+        FundInstruction::SetListingApprovalRequired(args) => {
+            msg!("Instruction: SetListingApprovalRequired");
+            process_set_listing_approval_required(program_id, accounts, args)
+        }
+        FundInstruction::ApproveFundListing(args) => {
+            msg!("Instruction: ApproveFundListing");
+            process_approve_fund_listing(program_id, accounts, args)
+        }
+        
+        // Fee Crank Operations
+        FundInstruction::SetFeeCrank(args) => {
+            msg!("Instruction: SetFeeCrank");
+            process_set_fee_crank(program_id, accounts, args)
+        }
+        FundInstruction::SetFeeDestination => {
+            msg!("Instruction: SetFeeDestination");
+            process_set_fee_destination(program_id, accounts)
+        }
+        
+        // Rent Reserve Operations
+        FundInstruction::InitializeRentReserve => {
+            msg!("Instruction: InitializeRentReserve");
+            process_initialize_rent_reserve(program_id, accounts)
+        }
+        FundInstruction::SetRentReserveEnabled(args) => {
+            msg!("Instruction: SetRentReserveEnabled");
+            process_set_rent_reserve_enabled(program_id, accounts, args)
+        }
+        FundInstruction::ReimburseRentReserve(args) => {
+            msg!("Instruction: ReimburseRentReserve");
+            process_reimburse_rent_reserve(program_id, accounts, args)
+        }
+        
+        // Reinitialize (timelocked recovery)
+        FundInstruction::ScheduleReinitialize(args) => {
+            msg!("Instruction: ScheduleReinitialize");
+            process_schedule_reinitialize(program_id, accounts, args)
+        }
+        FundInstruction::CancelReinitialize => {
+            msg!("Instruction: CancelReinitialize");
+            process_cancel_reinitialize(program_id, accounts)
+        }
+        FundInstruction::Reinitialize => {
+            msg!("Instruction: Reinitialize");
+            process_reinitialize(program_id, accounts)
+        }
+        
+        // Account Reallocation
+        FundInstruction::ReallocAccount(args) => {
+            msg!("Instruction: ReallocAccount");
+            process_realloc_account(program_id, accounts, args)
+        }
+        
+        // Payout Allow-list
+        FundInstruction::RegisterPayoutAddress(args) => {
+            msg!("Instruction: RegisterPayoutAddress");
+            process_register_payout_address(program_id, accounts, args)
+        }
+        FundInstruction::RemovePayoutAddress(args) => {
+            msg!("Instruction: RemovePayoutAddress");
+            process_remove_payout_address(program_id, accounts, args)
+        }
+        
+        // Bulk NAV Operations
+        FundInstruction::UpdateNAVMulti => {
+            msg!("Instruction: UpdateNAVMulti");
+            process_update_nav_multi(program_id, accounts)
+        }
+        
+        // System Fund Operations
+        FundInstruction::SetSystemFundFlags(args) => {
+            msg!("Instruction: SetSystemFundFlags");
+            process_set_system_fund_flags(program_id, accounts, args)
+        }
+        FundInstruction::IndexLPPosition => {
+            msg!("Instruction: IndexLPPosition");
+            process_index_lp_position(program_id, accounts)
+        }
+        FundInstruction::DistributeToLPs(args) => {
+            msg!("Instruction: DistributeToLPs");
+            process_distribute_to_lps(program_id, accounts, args)
+        }
+        FundInstruction::CreateProposal(args) => {
+            msg!("Instruction: CreateProposal");
+            process_create_proposal(program_id, accounts, args)
+        }
+        FundInstruction::CastVote(args) => {
+            msg!("Instruction: CastVote");
+            process_cast_vote(program_id, accounts, args)
+        }
+        FundInstruction::FinalizeProposal => {
+            msg!("Instruction: FinalizeProposal");
+            process_finalize_proposal(program_id, accounts)
+        }
+        FundInstruction::InitializeSharePriceFeed => {
+            msg!("Instruction: InitializeSharePriceFeed");
+            process_initialize_share_price_feed(program_id, accounts)
+        }
+        FundInstruction::LockShares(args) => {
+            msg!("Instruction: LockShares");
+            process_lock_shares(program_id, accounts, args)
+        }
+        FundInstruction::UnlockShares(args) => {
+            msg!("Instruction: UnlockShares");
+            process_unlock_shares(program_id, accounts, args)
+        }
+        FundInstruction::CreateTradeIntent(args) => {
+            msg!("Instruction: CreateTradeIntent");
+            process_create_trade_intent(program_id, accounts, args)
+        }
+        FundInstruction::CancelTradeIntent => {
+            msg!("Instruction: CancelTradeIntent");
+            process_cancel_trade_intent(program_id, accounts)
+        }
+        FundInstruction::ExecuteTradeIntent(args) => {
+            msg!("Instruction: ExecuteTradeIntent");
+            process_execute_trade_intent(program_id, accounts, args)
+        }
+        FundInstruction::SetFeaturesEnabled(args) => {
+            msg!("Instruction: SetFeaturesEnabled");
+            process_set_features_enabled(program_id, accounts, args)
+        }
+        FundInstruction::SetRedemptionFeeCurve(args) => {
+            msg!("Instruction: SetRedemptionFeeCurve");
+            process_set_redemption_fee_curve(program_id, accounts, args)
+        }
+        FundInstruction::ExpireReferralBinding => {
+            msg!("Instruction: ExpireReferralBinding");
+            process_expire_referral_binding(program_id, accounts)
+        }
+        FundInstruction::SetPredictionMarketCategoryFees(args) => {
+            msg!("Instruction: SetPredictionMarketCategoryFees");
+            process_set_pm_category_fees(program_id, accounts, args)
+        }
+        
+        // Treasury
+        FundInstruction::InitializeTreasury => {
+            msg!("Instruction: InitializeTreasury");
+            process_initialize_treasury(program_id, accounts)
+        }
+        FundInstruction::ScheduleTreasuryWithdrawal(args) => {
+            msg!("Instruction: ScheduleTreasuryWithdrawal");
+            process_schedule_treasury_withdrawal(program_id, accounts, args)
+        }
+        FundInstruction::CancelTreasuryWithdrawal => {
+            msg!("Instruction: CancelTreasuryWithdrawal");
+            process_cancel_treasury_withdrawal(program_id, accounts)
+        }
+        FundInstruction::WithdrawTreasury => {
+            msg!("Instruction: WithdrawTreasury");
+            process_withdraw_treasury(program_id, accounts)
+        }
+        FundInstruction::SweepPredictionMarketProtocolFees => {
+            msg!("Instruction: SweepPredictionMarketProtocolFees");
+            process_sweep_pm_protocol_fees(program_id, accounts)
+        }
+        
+        // Balances Checkpoint
+        FundInstruction::CommitBalancesRoot(args) => {
+            msg!("Instruction: CommitBalancesRoot");
+            process_commit_balances_root(program_id, accounts, args)
+        }
+        FundInstruction::VerifyBalanceProof(args) => {
+            msg!("Instruction: VerifyBalanceProof");
+            process_verify_balance_proof(program_id, accounts, args)
+        }
+        
+        // Pause Expiry
+        FundInstruction::SetPausePolicy(args) => {
+            msg!("Instruction: SetPausePolicy");
+            process_set_pause_policy(program_id, accounts, args)
+        }
+        FundInstruction::SetFundMaxPause(args) => {
+            msg!("Instruction: SetFundMaxPause");
+            process_set_fund_max_pause(program_id, accounts, args)
+        }
+        
+        // Full Exit
+        FundInstruction::RedeemAll(args) => {
+            msg!("Instruction: RedeemAll");
+            process_redeem_all(program_id, accounts, args)
+        }
+        
+        // Insurance Health
+        FundInstruction::GetInsuranceHealth(args) => {
+            msg!("Instruction: GetInsuranceHealth");
+            process_get_insurance_health(program_id, accounts, args)
+        }
+        
+        // Manager Risk
+        FundInstruction::SetManagerRiskLimits(args) => {
+            msg!("Instruction: SetManagerRiskLimits");
+            process_set_manager_risk_limits(program_id, accounts, args)
+        }
+        FundInstruction::InitializeManagerRiskState => {
+            msg!("Instruction: InitializeManagerRiskState");
+            process_initialize_manager_risk_state(program_id, accounts)
+        }
+        
+        // NAV Floor
+        FundInstruction::ResolveWindDownReview(args) => {
+            msg!("Instruction: ResolveWindDownReview");
+            process_resolve_wind_down_review(program_id, accounts, args)
+        }
+        
+        // Split Fund Creation
+        FundInstruction::CreateFundAccount(args) => {
+            msg!("Instruction: CreateFundAccount");
+            process_create_fund_account(program_id, accounts, args)
+        }
+        FundInstruction::InitShareMint => {
+            msg!("Instruction: InitShareMint");
+            process_init_share_mint(program_id, accounts)
+        }
+        FundInstruction::InitFundVault => {
+            msg!("Instruction: InitFundVault");
+            process_init_fund_vault(program_id, accounts)
+        }
+        FundInstruction::FinalizeFund => {
+            msg!("Instruction: FinalizeFund");
+            process_finalize_fund(program_id, accounts)
+        }
+        
+        // Referral Reward Vault
+        FundInstruction::InitializeReferralRewardVault => {
+            msg!("Instruction: InitializeReferralRewardVault");
+            process_initialize_referral_reward_vault(program_id, accounts)
+        }
+        
+        // Fund Benchmark
+        FundInstruction::SetFundBenchmark(args) => {
+            msg!("Instruction: SetFundBenchmark");
+            process_set_fund_benchmark(program_id, accounts, args)
+        }
+        
+        // NAV Blackout
+        FundInstruction::SetBlackoutPolicy(args) => {
+            msg!("Instruction: SetBlackoutPolicy");
+            process_set_blackout_policy(program_id, accounts, args)
+        }
+        FundInstruction::SetFundBlackout(args) => {
+            msg!("Instruction: SetFundBlackout");
+            process_set_fund_blackout(program_id, accounts, args)
+        }
+        
+        // Share Freeze
+        FundInstruction::FreezeInvestorShares(args) => {
+            msg!("Instruction: FreezeInvestorShares");
+            process_freeze_investor_shares(program_id, accounts, args)
+        }
+        FundInstruction::ThawInvestorShares => {
+            msg!("Instruction: ThawInvestorShares");
+            process_thaw_investor_shares(program_id, accounts)
+        }
+        
+        // Native SOL
+        FundInstruction::DepositSolToFund(args) => {
+            msg!("Instruction: DepositSolToFund");
+            process_deposit_to_fund(program_id, accounts, args, true, false)
+        }
+        FundInstruction::RedeemSolFromFund(args) => {
+            msg!("Instruction: RedeemSolFromFund");
+            process_redeem_from_fund(program_id, accounts, args, true)
+        }
+        
+        // Insurance Yield
+        FundInstruction::SetInsuranceYieldVenue(args) => {
+            msg!("Instruction: SetInsuranceYieldVenue");
+            process_set_insurance_yield_venue(program_id, accounts, args)
+        }
+        FundInstruction::DeployInsuranceCapital(args) => {
+            msg!("Instruction: DeployInsuranceCapital");
+            process_deploy_insurance_capital(program_id, accounts, args)
+        }
+        FundInstruction::RecallInsuranceCapital(args) => {
+            msg!("Instruction: RecallInsuranceCapital");
+            process_recall_insurance_capital(program_id, accounts, args)
+        }
+        
+        // ADL Keeper
+        FundInstruction::TriggerADLIfNeeded => {
+            msg!("Instruction: TriggerADLIfNeeded");
+            process_trigger_adl_if_needed(program_id, accounts)
+        }
+        
+        // Square Fund
+        FundInstruction::InitializeSquareFundStats => {
+            msg!("Instruction: InitializeSquareFundStats");
+            process_initialize_square_fund_stats(program_id, accounts)
+        }
+        FundInstruction::WithdrawSquareFund(args) => {
+            msg!("Instruction: WithdrawSquareFund");
+            process_withdraw_square_fund(program_id, accounts, args)
+        }
+        
+        // Revenue Split
+        FundInstruction::SetRevenueSplit(args) => {
+            msg!("Instruction: SetRevenueSplit");
+            process_set_revenue_split(program_id, accounts, args)
+        }
+        
+        // Square Escrow
+        FundInstruction::EscrowSquarePayment(args) => {
+            msg!("Instruction: EscrowSquarePayment");
+            process_escrow_square_payment(program_id, accounts, args)
+        }
+        FundInstruction::ConfirmDelivery => {
+            msg!("Instruction: ConfirmDelivery");
+            process_confirm_delivery(program_id, accounts)
+        }
+        FundInstruction::RaiseDispute => {
+            msg!("Instruction: RaiseDispute");
+            process_raise_dispute(program_id, accounts)
+        }
+        FundInstruction::ResolveDispute(args) => {
+            msg!("Instruction: ResolveDispute");
+            process_resolve_dispute(program_id, accounts, args)
+        }
+        
+        // Square Tip Token
+        FundInstruction::SetSquareTipToken(args) => {
+            msg!("Instruction: SetSquareTipToken");
+            process_set_square_tip_token(program_id, accounts, args)
+        }
+        FundInstruction::UpdateSquareTipTokenPrice(args) => {
+            msg!("Instruction: UpdateSquareTipTokenPrice");
+            process_update_square_tip_token_price(program_id, accounts, args)
+        }
+        FundInstruction::SquareTokenTip(args) => {
+            msg!("Instruction: SquareTokenTip");
+            process_square_token_tip(program_id, accounts, args)
+        }
+        
+        // Fund Hook
+        FundInstruction::SetFundHook(args) => {
+            msg!("Instruction: SetFundHook");
+            process_set_fund_hook(program_id, accounts, args)
+        }
+        
+        // Share Authority Rotation
+        FundInstruction::RotateShareMintAuthority => {
+            msg!("Instruction: RotateShareMintAuthority");
+            process_rotate_share_mint_authority(program_id, accounts)
+        }
+        
+        // Fund LP Referral
+        FundInstruction::SetFundLPReferralProgram(args) => {
+            msg!("Instruction: SetFundLPReferralProgram");
+            process_set_fund_lp_referral_program(program_id, accounts, args)
+        }
+        FundInstruction::BindFundLPReferral(args) => {
+            msg!("Instruction: BindFundLPReferral");
+            process_bind_fund_lp_referral(program_id, accounts, args)
+        }
+        FundInstruction::ClaimFundLPReferralBounty => {
+            msg!("Instruction: ClaimFundLPReferralBounty");
+            process_claim_fund_lp_referral_bounty(program_id, accounts)
+        }
+        
+        // Admin Intervention
+        FundInstruction::AdminSetFundPaused(args) => {
+            msg!("Instruction: AdminSetFundPaused");
+            process_admin_set_fund_paused(program_id, accounts, args)
+        }
+        FundInstruction::AdminSetFundOpen(args) => {
+            msg!("Instruction: AdminSetFundOpen");
+            process_admin_set_fund_open(program_id, accounts, args)
+        }
+        
+        // Insurance Migration
+        FundInstruction::MigrateInsuranceConfigV2(args) => {
+            msg!("Instruction: MigrateInsuranceConfigV2");
+            process_migrate_insurance_config_v2(program_id, accounts, args)
+        }
+        
+        // Fund Vault Audit
+        FundInstruction::AuditFundVault => {
+            msg!("Instruction: AuditFundVault");
+            process_audit_fund_vault(program_id, accounts)
+        }
+        
+        // LP Fee Statement
+        FundInstruction::RecordLPFeeStatement => {
+            msg!("Instruction: RecordLPFeeStatement");
+            process_record_lp_fee_statement(program_id, accounts)
+        }
+        
+        // Stable Swap Deposit
+        FundInstruction::SetStableSwapRoute(args) => {
+            msg!("Instruction: SetStableSwapRoute");
+            process_set_stable_swap_route(program_id, accounts, args)
+        }
+        FundInstruction::DepositViaStableSwap(args) => {
+            msg!("Instruction: DepositViaStableSwap");
+            process_deposit_via_stable_swap(program_id, accounts, args)
+        }
+        
+        // Fee Rebates
+        FundInstruction::RecordFeeRebate(args) => {
+            msg!("Instruction: RecordFeeRebate");
+            process_record_fee_rebate(program_id, accounts, args)
+        }
+        
+        // Risk Enforcement
+        FundInstruction::EnforceRiskLimits(args) => {
+            msg!("Instruction: EnforceRiskLimits");
+            process_enforce_risk_limits(program_id, accounts, args)
+        }
+        
+        // Notification Preferences
+        FundInstruction::SetNotificationPrefs(args) => {
+            msg!("Instruction: SetNotificationPrefs");
+            process_set_notification_prefs(program_id, accounts, args)
+        }
+        FundInstruction::CloseNotificationPrefs => {
+            msg!("Instruction: CloseNotificationPrefs");
+            process_close_notification_prefs(program_id, accounts)
+        }
+        
+        // Fund Exposure
+        FundInstruction::RecordFundExposure(args) => {
+            msg!("Instruction: RecordFundExposure");
+            process_record_fund_exposure(program_id, accounts, args)
+        }
+        
+        // Share Supply Checkpoints
+        FundInstruction::InitializeShareCheckpoints(args) => {
+            msg!("Instruction: InitializeShareCheckpoints");
+            process_initialize_share_checkpoints(program_id, accounts, args)
+        }
+        FundInstruction::RecordShareCheckpoint => {
+            msg!("Instruction: RecordShareCheckpoint");
+            process_record_share_checkpoint(program_id, accounts)
+        }
+        
+        // Share Supply Reconciliation
+        FundInstruction::ReconcileShareSupply => {
+            msg!("Instruction: ReconcileShareSupply");
+            process_reconcile_share_supply(program_id, accounts)
+        }
+        
+        // Position NFTs
+        FundInstruction::WrapPositionNFT => {
+            msg!("Instruction: WrapPositionNFT");
+            process_wrap_position_nft(program_id, accounts)
+        }
+        FundInstruction::TransferPositionNFT => {
+            msg!("Instruction: TransferPositionNFT");
+            process_transfer_position_nft(program_id, accounts)
+        }
+        FundInstruction::UnwrapPositionNFT => {
+            msg!("Instruction: UnwrapPositionNFT");
+            process_unwrap_position_nft(program_id, accounts)
+        }
+        
+        // Relayer Subsystem Pause
+        FundInstruction::SetRelayerSystemPaused(args) => {
+            msg!("Instruction: SetRelayerSystemPaused");
+            process_set_relayer_system_paused(program_id, accounts, args)
+        }
+        
+        // Relayer Stats
+        FundInstruction::InitializeRelayerStats => {
+            msg!("Instruction: InitializeRelayerStats");
+            process_initialize_relayer_stats(program_id, accounts)
+        }
+        
+        // Insurance Config Update (timelocked)
+        FundInstruction::ScheduleInsuranceConfigUpdate(args) => {
+            msg!("Instruction: ScheduleInsuranceConfigUpdate");
+            process_schedule_insurance_config_update(program_id, accounts, args)
+        }
+        FundInstruction::CancelInsuranceConfigUpdate => {
+            msg!("Instruction: CancelInsuranceConfigUpdate");
+            process_cancel_insurance_config_update(program_id, accounts)
+        }
+        FundInstruction::UpdateInsuranceConfig => {
+            msg!("Instruction: UpdateInsuranceConfig");
+            process_update_insurance_config(program_id, accounts)
+        }
+        
+        // LP Position Transfer
+        FundInstruction::TransferLPPosition(args) => {
+            msg!("Instruction: TransferLPPosition");
+            process_transfer_lp_position(program_id, accounts, args)
+        }
+        
+        // Fund Setup Validation
+        FundInstruction::ValidateFundSetup => {
+            msg!("Instruction: ValidateFundSetup");
+            process_validate_fund_setup(program_id, accounts)
+        }
+        
+        // Fund Expenses
+        FundInstruction::PayFundExpense(args) => {
+            msg!("Instruction: PayFundExpense");
+            process_pay_fund_expense(program_id, accounts, args)
+        }
+        
+        // Insurance Coverage (Ledger handshake)
+        FundInstruction::ReportPlatformOpenInterest(args) => {
+            msg!("Instruction: ReportPlatformOpenInterest");
+            process_report_platform_open_interest(program_id, accounts, args)
+        }
+        FundInstruction::UpdateInsuranceCoverage => {
+            msg!("Instruction: UpdateInsuranceCoverage");
+            process_update_insurance_coverage(program_id, accounts)
+        }
+        
+        // Referral Reward Claims
+        FundInstruction::ClaimReferralRewards(args) => {
+            msg!("Instruction: ClaimReferralRewards");
+            process_claim_referral_rewards(program_id, accounts, args)
+        }
+        
+        // Investor Portfolio
+        FundInstruction::InitializeInvestorPortfolio => {
+            msg!("Instruction: InitializeInvestorPortfolio");
+            process_initialize_investor_portfolio(program_id, accounts)
+        }
+        FundInstruction::SyncInvestorPortfolio => {
+            msg!("Instruction: SyncInvestorPortfolio");
+            process_sync_investor_portfolio(program_id, accounts)
+        }
+        
+        // Stale Entry Cleanup
+        FundInstruction::ExpireStaleEntries => {
+            msg!("Instruction: ExpireStaleEntries");
+            process_expire_stale_entries(program_id, accounts)
+        }
+        
+        // Fund Lookup Table
+        FundInstruction::CreateFundLookupTable(args) => {
+            msg!("Instruction: CreateFundLookupTable");
+            process_create_fund_lookup_table(program_id, accounts, args)
+        }
+        FundInstruction::ExtendFundLookupTable(args) => {
+            msg!("Instruction: ExtendFundLookupTable");
+            process_extend_fund_lookup_table(program_id, accounts, args)
+        }
+        
+        // Fund Coverage
+        FundInstruction::RegisterFundCoverage(args) => {
+            msg!("Instruction: RegisterFundCoverage");
+            process_register_fund_coverage(program_id, accounts, args)
+        }
+        FundInstruction::PayCoveragePremium => {
+            msg!("Instruction: PayCoveragePremium");
+            process_pay_coverage_premium(program_id, accounts)
+        }
+        FundInstruction::ClaimFundCoverage => {
+            msg!("Instruction: ClaimFundCoverage");
+            process_claim_fund_coverage(program_id, accounts)
+        }
+        FundInstruction::CancelFundCoverage => {
+            msg!("Instruction: CancelFundCoverage");
+            process_cancel_fund_coverage(program_id, accounts)
+        }
+        
+        // Mark Price
+        FundInstruction::UpdateMarkPrice(args) => {
+            msg!("Instruction: UpdateMarkPrice");
+            process_update_mark_price(program_id, accounts, args)
+        }
+        
+        // Share Supply Flag
+        FundInstruction::ClearShareSupplyFlag => {
+            msg!("Instruction: ClearShareSupplyFlag");
+            process_clear_share_supply_flag(program_id, accounts)
+        }
+        
+        // Referral Reward Settlement
+        FundInstruction::SettleReferralRewardsBatch => {
+            msg!("Instruction: SettleReferralRewardsBatch");
+            process_settle_referral_rewards_batch(program_id, accounts)
+        }
     }
 }
 
@@ -208,6 +822,11 @@ fn process_initialize(
         return Err(FundError::InvalidPDA.into());
     }
     
+    // Reject a second call cleanly instead of failing inside create_account
+    if fund_config.owner == program_id && !fund_config.data_is_empty() {
+        return Err(FundError::FundAlreadyInitialized.into());
+    }
+    
     // Create FundConfig account
     let rent = Rent::get()?;
     let space = FundConfig::SIZE;
@@ -233,7 +852,7 @@ fn process_initialize(
         config_bump,
     );
     
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
     
     msg!("Fund Program initialized");
     msg!("Authority: {}", authority.key);
@@ -244,6 +863,9 @@ fn process_initialize(
 }
 
 /// Create a new fund
+///
+/// Runs the same steps as CreateFundAccount → InitShareMint → InitFundVault →
+/// FinalizeFund in a single transaction.
 fn process_create_fund(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -257,269 +879,252 @@ fn process_create_fund(
     let share_mint = next_account_info(account_info_iter)?;
     let fund_config = next_account_info(account_info_iter)?;
     let usdc_mint = next_account_info(account_info_iter)?;
-    let _token_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     let rent_sysvar = next_account_info(account_info_iter)?;
+    let manager_profile = next_account_info(account_info_iter)?;
+    let fee_treasury = account_info_iter.next();
+    let fee_treasury_vault = account_info_iter.next();
+    let manager_usdc = account_info_iter.next();
+    let initial_deposit = args.initial_deposit_amount.filter(|amount| *amount > 0);
+    
+    let fee_accounts: Vec<&AccountInfo> = [fee_treasury, fee_treasury_vault, manager_usdc, Some(token_program)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut fund = create_fund_account(
+        program_id,
+        manager,
+        fund_account,
+        fund_config,
+        system_program,
+        manager_profile,
+        &fee_accounts,
+        &args,
+    )?;
+    init_share_mint(program_id, manager, fund_account, &fund, share_mint, system_program, rent_sysvar)?;
+    init_fund_vault(program_id, manager, fund_account, &fund, fund_vault, usdc_mint, system_program, rent_sysvar)?;
+    finalize_fund_setup(&mut fund, fund_account, fund_vault, share_mint)?;
     
-    // Verify manager is signer
-    assert_signer(manager)?;
+    msg!("Fund created: {}", args.name);
+    msg!("Fund index: {}", fund.fund_index);
+    msg!("Manager: {}", manager.key);
+    msg!("Management fee: {} bps", args.management_fee_bps);
+    msg!("Performance fee: {} bps", args.performance_fee_bps);
+    msg!("Listing status: {:?}", fund.listing_status);
     
-    // Validate fund name
-    validate_fund_name(&args.name)?;
+    if let Some(amount) = initial_deposit {
+        let manager_usdc = manager_usdc.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let lp_position = next_account_info(account_info_iter)?;
+        let manager_shares = next_account_info(account_info_iter)?;
+        
+        validate_accounts! {
+            manager_shares: signer, writable;
+            token_program: key = &spl_token::id();
+        }
+        
+        // The share mint only exists from this instruction on, so the
+        // manager's share token account is created here as well
+        invoke(
+            &system_instruction::create_account(
+                manager.key,
+                manager_shares.key,
+                Rent::get()?.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            &[manager.clone(), manager_shares.clone(), system_program.clone()],
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_account3(&spl_token::id(), manager_shares.key, share_mint.key, manager.key)?,
+            &[manager_shares.clone(), share_mint.clone(), token_program.clone()],
+        )?;
+        
+        msg!("🌱 FUND_SEED_DEPOSIT");
+        msg!("  Fund: {}", fund_account.key);
+        msg!("  Amount: {}", amount);
+        
+        let deposit_accounts: Vec<AccountInfo> = [
+            manager,
+            fund_account,
+            fund_vault,
+            manager_usdc,
+            lp_position,
+            manager_shares,
+            share_mint,
+            token_program,
+            system_program,
+        ]
+        .into_iter()
+        .chain(account_info_iter)
+        .cloned()
+        .collect();
+        process_deposit_to_fund(
+            program_id,
+            &deposit_accounts,
+            DepositToFundArgs {
+                amount,
+                campaign_id: None,
+                memo: None,
+            },
+            false,
+            true,
+        )?;
+    }
     
-    // Validate fee configuration
-    validate_fee_config(args.management_fee_bps, args.performance_fee_bps)?;
+    Ok(())
+}
+
+// =============================================================================
+// Fund Management Instructions
+// =============================================================================
+
+/// Update fund configuration
+fn process_update_fund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateFundArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
     
-    // Load and update FundConfig
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
-    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
-        return Err(FundError::FundNotInitialized.into());
-    }
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
     
-    if config.is_paused {
-        return Err(FundError::FundPaused.into());
-    }
+    assert_signer(manager)?;
+    assert_owned_by(fund_account, program_id)?;
     
-    let fund_index = config.total_funds;
-    config.total_funds = config.total_funds.saturating_add(1);
-    config.active_funds = config.active_funds.saturating_add(1);
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
     
-    // Derive Fund PDA
-    let fund_seeds = Fund::seeds(manager.key, fund_index);
-    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
-    let (fund_pda, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
     
-    if fund_account.key != &fund_pda {
-        return Err(FundError::InvalidPDA.into());
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
     }
     
-    // Derive Fund vault PDA
-    let vault_seeds = Fund::vault_seeds(&fund_pda);
-    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(&vault_seeds_refs, program_id);
-    
-    if fund_vault.key != &vault_pda {
-        return Err(FundError::InvalidPDA.into());
-    }
-    
-    // Derive Share mint PDA
-    let mint_seeds = Fund::share_mint_seeds(&fund_pda);
-    let mint_seeds_refs: Vec<&[u8]> = mint_seeds.iter().map(|s| s.as_slice()).collect();
-    let (mint_pda, mint_bump) = Pubkey::find_program_address(&mint_seeds_refs, program_id);
-    
-    if share_mint.key != &mint_pda {
-        return Err(FundError::InvalidPDA.into());
+    // Update fee config if provided
+    if let Some(new_fee_config) = args.fee_config {
+        validate_fee_config(new_fee_config.management_fee_bps, new_fee_config.performance_fee_bps)?;
+        fund.fee_config = new_fee_config;
     }
     
-    let rent = Rent::get()?;
-    let current_ts = get_current_timestamp()?;
-    
-    // Create Fund account
-    let fund_space = Fund::SIZE;
-    let fund_lamports = rent.minimum_balance(fund_space);
-    
-    invoke_signed(
-        &system_instruction::create_account(
-            manager.key,
-            fund_account.key,
-            fund_lamports,
-            fund_space as u64,
-            program_id,
-        ),
-        &[manager.clone(), fund_account.clone(), system_program.clone()],
-        &[&[FUND_SEED, manager.key.as_ref(), &fund_index.to_le_bytes(), &[fund_bump]]],
-    )?;
-    
-    // Create Share mint (SPL Token)
-    let mint_space = spl_token::state::Mint::LEN;
-    let mint_lamports = rent.minimum_balance(mint_space);
-    
-    invoke_signed(
-        &system_instruction::create_account(
-            manager.key,
-            share_mint.key,
-            mint_lamports,
-            mint_space as u64,
-            &spl_token::id(),
-        ),
-        &[manager.clone(), share_mint.clone(), system_program.clone()],
-        &[&[SHARE_MINT_SEED, fund_pda.as_ref(), &[mint_bump]]],
-    )?;
-    
-    // Initialize Share mint
-    invoke_signed(
-        &spl_token::instruction::initialize_mint(
-            &spl_token::id(),
-            share_mint.key,
-            &fund_pda, // Mint authority = Fund PDA
-            Some(&fund_pda), // Freeze authority = Fund PDA
-            6, // 6 decimals like USDC
-        )?,
-        &[share_mint.clone(), rent_sysvar.clone()],
-        &[&[SHARE_MINT_SEED, fund_pda.as_ref(), &[mint_bump]]],
-    )?;
-    
-    // Create Fund vault (token account)
-    let vault_space = spl_token::state::Account::LEN;
-    let vault_lamports = rent.minimum_balance(vault_space);
-    
-    invoke_signed(
-        &system_instruction::create_account(
-            manager.key,
-            fund_vault.key,
-            vault_lamports,
-            vault_space as u64,
-            &spl_token::id(),
-        ),
-        &[manager.clone(), fund_vault.clone(), system_program.clone()],
-        &[&[FUND_VAULT_SEED, fund_pda.as_ref(), &[vault_bump]]],
-    )?;
-    
-    // Initialize Fund vault
-    invoke_signed(
-        &spl_token::instruction::initialize_account(
-            &spl_token::id(),
-            fund_vault.key,
-            usdc_mint.key,
-            &fund_pda, // Owner = Fund PDA
-        )?,
-        &[fund_vault.clone(), usdc_mint.clone(), fund_account.clone(), rent_sysvar.clone()],
-        &[&[FUND_VAULT_SEED, fund_pda.as_ref(), &[vault_bump]]],
-    )?;
-    
-    // Create fee config
-    let fee_config = FeeConfig {
-        management_fee_bps: args.management_fee_bps,
-        performance_fee_bps: args.performance_fee_bps,
-        use_high_water_mark: args.use_high_water_mark,
-        fee_collection_interval: if args.fee_collection_interval > 0 {
-            args.fee_collection_interval
-        } else {
-            FeeConfig::DEFAULT_COLLECTION_INTERVAL
-        },
-    };
-    
-    // Initialize Fund
-    let fund = Fund::new(
-        *manager.key,
-        &args.name,
-        fund_bump,
-        *fund_vault.key,
-        *share_mint.key,
-        fee_config,
-        fund_index,
-        current_ts,
-    );
-    
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
     
-    msg!("Fund created: {}", args.name);
-    msg!("Fund index: {}", fund_index);
-    msg!("Manager: {}", manager.key);
-    msg!("Management fee: {} bps", args.management_fee_bps);
-    msg!("Performance fee: {} bps", args.performance_fee_bps);
+    msg!("Fund updated: {}", fund.name_str());
     
     Ok(())
 }
 
-// =============================================================================
-// Fund Management Instructions
-// =============================================================================
-
-/// Update fund configuration
-fn process_update_fund(
+/// Move a fund between Open / DepositsClosed / Paused
+fn process_set_fund_state(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    args: UpdateFundArgs,
+    args: SetFundStateArgs,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
-    let manager = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
     let fund_account = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
     
-    assert_signer(manager)?;
-    assert_owned_by(fund_account, program_id)?;
-    
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    validate_accounts! {
+        authority: signer;
+        fund_account: writable, owner = program_id;
+        fund_config: owner = program_id;
+    }
     
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
     if fund.discriminator != FUND_DISCRIMINATOR {
         return Err(FundError::InvalidFundAccount.into());
     }
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
     
-    if !fund.is_manager(manager.key) {
+    let is_admin = config.authority == *authority.key;
+    if !fund.is_manager(authority.key) && !is_admin {
         return Err(FundError::NotFundManager.into());
     }
     
-    // Update fee config if provided
-    if let Some(new_fee_config) = args.fee_config {
-        validate_fee_config(new_fee_config.management_fee_bps, new_fee_config.performance_fee_bps)?;
-        fund.fee_config = new_fee_config;
-    }
+    let from = fund.lifecycle_state();
+    let (open_changes, pause_changes) = fund.state_transition(args.state)?;
+    let current_ts = get_current_timestamp()?;
     
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    if open_changes && !is_admin && fund.admin_open_locked {
+        return Err(FundError::FundAdminLocked.into());
+    }
+    if pause_changes {
+        // Only the admin may pause/unpause the Insurance Fund or system funds
+        if fund.is_protected() && !is_admin {
+            return Err(FundError::SystemFundProtected.into());
+        }
+        // Admin overrides the manager's toggle cooldown and daily cap
+        if !is_admin {
+            if fund.admin_pause_locked {
+                return Err(FundError::FundAdminLocked.into());
+            }
+            fund.check_pause_toggle(current_ts)?;
+        }
+        fund.set_paused(!fund.is_paused, config.max_pause_for(fund.max_pause_secs), current_ts);
+        fund.record_pause_toggle(current_ts);
+    }
+    if open_changes {
+        fund.is_open = !fund.is_open;
+    }
+    fund.last_update_ts = current_ts;
     
-    msg!("Fund updated: {}", fund.name_str());
+    // Pause timestamps / toggle counters do not fit an older Fund account
+    if !fund.fits_in(fund_account.data_len()) {
+        let system_program = next_account_info(account_info_iter)?;
+        validate_accounts! {
+            authority: writable;
+            system_program: key = &solana_program::system_program::id();
+        }
+        realloc_with_rent(fund_account, authority, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    msg!("🔄 FUND_STATE_CHANGED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  From: {:?}", from);
+    msg!("  To: {:?}", args.state);
+    msg!("  Reason: {:?}", args.reason);
+    msg!("  By: {} ({})", authority.key, if is_admin { "admin" } else { "manager" });
+    if fund.pause_expires_at > 0 {
+        msg!("  Withdrawals reopen at: {}", fund.pause_expires_at);
+    }
     
     Ok(())
 }
 
-/// Set fund open/closed for deposits
-fn process_set_fund_open(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    args: SetFundOpenArgs,
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let manager = next_account_info(account_info_iter)?;
-    let fund_account = next_account_info(account_info_iter)?;
-    
-    assert_signer(manager)?;
-    assert_owned_by(fund_account, program_id)?;
-    
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
-    
-    if !fund.is_manager(manager.key) {
-        return Err(FundError::NotFundManager.into());
+/// Withdrawal gate shared by the redeem paths; logs when a pause has auto-expired
+fn assert_fund_withdrawable(fund: &Fund, fund_key: &Pubkey, current_ts: i64) -> ProgramResult {
+    if fund.is_share_supply_inconsistent() {
+        return Err(FundError::ShareSupplyInconsistent.into());
+    }
+    if !fund.can_withdraw(current_ts) {
+        return Err(FundError::FundPaused.into());
+    }
+    if fund.is_pause_expired(current_ts) {
+        msg!("⏰ FUND_PAUSE_AUTO_EXPIRED");
+        msg!("  Fund: {}", fund_key);
+        msg!("  Paused at: {}", fund.paused_at);
+        msg!("  Expired at: {}", fund.pause_expires_at);
     }
-    
-    fund.is_open = args.is_open;
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
-    
-    msg!("Fund {} is now {}", fund.name_str(), if args.is_open { "open" } else { "closed" });
-    
     Ok(())
 }
 
-/// Set fund paused/unpaused
-fn process_set_fund_paused(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    args: SetFundPausedArgs,
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let manager = next_account_info(account_info_iter)?;
-    let fund_account = next_account_info(account_info_iter)?;
-    
-    assert_signer(manager)?;
-    assert_owned_by(fund_account, program_id)?;
-    
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
-    
-    if !fund.is_manager(manager.key) {
-        return Err(FundError::NotFundManager.into());
+/// Reject deposits / redemptions inside a post-PnL blackout window
+fn assert_outside_blackout(fund: &Fund, current_ts: i64) -> ProgramResult {
+    if fund.is_in_blackout(current_ts) {
+        msg!("❌ Fund in NAV blackout until {}", fund.blackout_until);
+        return Err(FundError::FundInBlackout.into());
     }
-    
-    fund.is_paused = args.is_paused;
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
-    
-    msg!("Fund {} is now {}", fund.name_str(), if args.is_paused { "paused" } else { "unpaused" });
-    
     Ok(())
 }
 
@@ -537,16 +1142,39 @@ fn process_close_fund(
     let fund_config = next_account_info(account_info_iter)?;
     let manager_usdc = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
+    let manager_profile = next_account_info(account_info_iter)?;
     
     assert_signer(manager)?;
     assert_owned_by(fund_account, program_id)?;
     
-    let fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
     
     if !fund.is_manager(manager.key) {
         return Err(FundError::NotFundManager.into());
     }
     
+    let profile_seeds = ManagerProfile::seeds(manager.key);
+    let profile_seeds_refs: Vec<&[u8]> = profile_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        fund_vault: writable, key = &fund.fund_vault;
+        manager_profile: writable, pda = (&profile_seeds_refs, program_id);
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    if fund.is_protected() {
+        msg!("❌ Cannot close insurance/system fund: {}", fund.name_str());
+        return Err(FundError::SystemFundProtected.into());
+    }
+    
+    if fund.is_closed() {
+        return Err(FundError::FundClosed.into());
+    }
+    
     // Check no LP positions remain
     if fund.stats.lp_count > 0 {
         return Err(FundError::FundHasLPPositions.into());
@@ -578,12 +1206,41 @@ fn process_close_fund(
         )?;
     }
     
+    let current_ts = get_current_timestamp()?;
+    fund.is_open = false;
+    fund.closed_at = current_ts;
+    fund.last_update_ts = current_ts;
+    
+    // Fund accounts that predate closed_at need to grow first
+    if !fund.fits_in(fund_account.data_len()) {
+        let system_program = accounts
+            .iter()
+            .find(|a| *a.key == solana_program::system_program::id())
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
     // Update FundConfig
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     config.active_funds = config.active_funds.saturating_sub(1);
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+    
+    // Free up the manager's fund slot (managers whose funds all predate
+    // ManagerProfile have no profile and no slot to free)
+    if !manager_profile.data_is_empty() {
+        assert_owned_by(manager_profile, program_id)?;
+        
+        let mut profile = ManagerProfile::try_from_slice(&manager_profile.data.borrow())?;
+        if profile.discriminator != MANAGER_PROFILE_DISCRIMINATOR {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        profile.record_fund_closed(current_ts);
+        profile.serialize(&mut &mut manager_profile.data.borrow_mut()[..])?;
+    }
     
     msg!("Fund closed: {}", fund.name_str());
+    msg!("  Closed at: {}", fund.closed_at);
     
     Ok(())
 }
@@ -593,11 +1250,19 @@ fn process_close_fund(
 // =============================================================================
 
 /// Deposit USDC into a fund
+///
+/// `native_sol` deposits lamports straight from the investor wallet into a
+/// wSOL fund vault and wraps them with `sync_native`. `seed_deposit` is
+/// CreateFund's manager seed deposit, allowed while the listing still awaits
+/// approval.
 fn process_deposit_to_fund(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     args: DepositToFundArgs,
+    native_sol: bool,
+    seed_deposit: bool,
 ) -> ProgramResult {
+    log_compute_units("deposit:start");
     let account_info_iter = &mut accounts.iter();
     
     let investor = next_account_info(account_info_iter)?;
@@ -610,92 +1275,154 @@ fn process_deposit_to_fund(
     let token_program = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     
-    assert_signer(investor)?;
-    assert_owned_by(fund_account, program_id)?;
+    validate_accounts! {
+        investor: signer;
+        fund_account: writable, owner = program_id;
+        fund_vault: writable;
+        investor_usdc: writable;
+        lp_position: writable;
+        investor_shares: writable;
+        share_mint: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
     
     if args.amount == 0 {
         return Err(FundError::InvalidAmount.into());
     }
+    validate_memo(args.memo.as_deref())?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    if native_sol {
+        if !fund.sol_denominated {
+            return Err(FundError::InvalidMint.into());
+        }
+        assert_writable(investor)?;
+    }
     
-    let amount_e6 = args.amount as i64;
-    if amount_e6 < MIN_DEPOSIT_AMOUNT_E6 {
+    // wSOL funds take lamports and account in micro-SOL
+    let amount_e6 = fund.token_amount_to_e6(args.amount)?;
+    if amount_e6 < fund.min_deposit_e6() {
         return Err(FundError::DepositTooSmall.into());
     }
     
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    if fund.distribution_in_progress {
+        return Err(FundError::DistributionInProgress.into());
+    }
     
-    if fund.discriminator != FUND_DISCRIMINATOR {
-        return Err(FundError::InvalidFundAccount.into());
+    if fund.is_insurance_fund {
+        assert_insurance_deposits_allowed(program_id, accounts)?;
+    }
+    
+    if fund.listing_status != FundListingStatus::Listed
+        && !(seed_deposit && fund.listing_status == FundListingStatus::PendingApproval)
+    {
+        return Err(FundError::FundListingNotApproved.into());
+    }
+    
+    if fund.is_in_wind_down_review() || fund.is_below_nav_floor() {
+        return Err(FundError::FundInWindDownReview.into());
     }
     
-    if !fund.can_deposit() {
+    if fund.setup_pending {
+        return Err(FundError::FundSetupPending.into());
+    }
+    
+    if fund.is_share_supply_inconsistent() {
+        return Err(FundError::ShareSupplyInconsistent.into());
+    }
+    
+    let can_deposit = if seed_deposit { fund.can_seed_deposit() } else { fund.can_deposit() };
+    if !can_deposit {
         return Err(FundError::FundClosed.into());
     }
     
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+        share_mint: key = &fund.share_mint;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    assert_fund_vault_authorities(
+        &spl_token::state::Account::unpack(&fund_vault.data.borrow())?,
+        fund_account.key,
+    )?;
+    
     let current_ts = get_current_timestamp()?;
+    assert_outside_blackout(&fund, current_ts)?;
     
     // Calculate shares to mint
     let shares = calculate_shares_to_mint(amount_e6, fund.stats.current_nav_e6)?;
     
-    // Transfer USDC to fund vault
-    invoke(
-        &spl_token::instruction::transfer(
-            &spl_token::id(),
-            investor_usdc.key,
-            fund_vault.key,
-            investor.key,
-            &[],
-            args.amount,
-        )?,
-        &[investor_usdc.clone(), fund_vault.clone(), investor.clone(), token_program.clone()],
-    )?;
-    
-    // Mint share tokens to investor
-    let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
-    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
-    let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+    log_compute_units("deposit:validated");
     
-    invoke_signed(
-        &spl_token::instruction::mint_to(
+    // Transfer USDC to fund vault
+    if native_sol {
+        invoke(
+            &system_instruction::transfer(investor.key, fund_vault.key, args.amount),
+            &[investor.clone(), fund_vault.clone(), system_program.clone()],
+        )?;
+        invoke(
+            &spl_token::instruction::sync_native(&spl_token::id(), fund_vault.key)?,
+            &[fund_vault.clone(), token_program.clone()],
+        )?;
+    } else {
+        invoke(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                investor_usdc.key,
+                fund_vault.key,
+                investor.key,
+                &[],
+                args.amount,
+            )?,
+            &[investor_usdc.clone(), fund_vault.clone(), investor.clone(), token_program.clone()],
+        )?;
+    }
+    
+    // Mint share tokens to investor
+    let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
+    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
+    let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+    let (mint_authority, mint_authority_seeds) = share_authority(program_id, accounts, fund_account, &fund)?;
+    let mint_authority_refs: Vec<&[u8]> = mint_authority_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    invoke_signed(
+        &spl_token::instruction::mint_to(
             &spl_token::id(),
             share_mint.key,
             investor_shares.key,
-            fund_account.key,
+            mint_authority.key,
             &[],
             shares,
         )?,
-        &[share_mint.clone(), investor_shares.clone(), fund_account.clone(), token_program.clone()],
-        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]]],
+        &[share_mint.clone(), investor_shares.clone(), mint_authority.clone(), token_program.clone()],
+        &[&mint_authority_refs],
     )?;
     
     // Update or create LP position
     let lp_seeds = LPPosition::seeds(fund_account.key, investor.key);
     let lp_seeds_refs: Vec<&[u8]> = lp_seeds.iter().map(|s| s.as_slice()).collect();
-    let (lp_pda, lp_bump) = Pubkey::find_program_address(&lp_seeds_refs, program_id);
-    
-    if lp_position.key != &lp_pda {
-        return Err(FundError::InvalidPDA.into());
-    }
+    let lp_bump = assert_pda(lp_position, &lp_seeds_refs, program_id)?;
     
-    if lp_position.data_is_empty() {
-        // Create new LP position
-        let rent = Rent::get()?;
-        let lp_space = LPPosition::SIZE;
-        let lp_lamports = rent.minimum_balance(lp_space);
-        
-        invoke_signed(
-            &system_instruction::create_account(
-                investor.key,
-                lp_position.key,
-                lp_lamports,
-                lp_space as u64,
-                program_id,
-            ),
-            &[investor.clone(), lp_position.clone(), system_program.clone()],
-            &[&[LP_POSITION_SEED, fund_account.key.as_ref(), investor.key.as_ref(), &[lp_bump]]],
+    let position_shares = if lp_position.data_is_empty() {
+        // Create new LP position (rent sponsored by the reserve when available)
+        let rent_sponsored = create_user_flow_pda(
+            program_id,
+            accounts,
+            investor,
+            lp_position,
+            system_program,
+            LPPosition::SIZE,
+            &[LP_POSITION_SEED, fund_account.key.as_ref(), investor.key.as_ref(), &[lp_bump]],
+            current_ts,
         )?;
         
-        let position = LPPosition::new(
+        let mut position = LPPosition::new(
             *fund_account.key,
             *investor.key,
             shares,
@@ -704,35 +1431,182 @@ fn process_deposit_to_fund(
             current_ts,
             lp_bump,
         );
-        position.serialize(&mut *lp_position.data.borrow_mut())?;
+        position.rent_sponsored = rent_sponsored;
+        position.has_receipt = fund.deposit_receipts_enabled;
+        lp_index_add(program_id, accounts, investor, system_program, fund_account.key, &mut fund, lp_position, &mut position, current_ts)?;
+        position.pack_versioned(&mut lp_position.data.borrow_mut())?;
         
         // Increment LP count
         fund.stats.lp_count = fund.stats.lp_count.saturating_add(1);
+        position.shares
     } else {
         // Update existing LP position
-        let mut position = LPPosition::try_from_slice(&lp_position.data.borrow())?;
+        let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+        if position.nft_wrapped {
+            return Err(FundError::PositionNFTWrapped.into());
+        }
+        if position.investor != *investor.key {
+            // Handed to a position NFT buyer; the seller gets it back once emptied
+            if !position.is_empty() {
+                return Err(FundError::LPPositionNotFound.into());
+            }
+            position.reassign(*investor.key, current_ts);
+        }
+        let reactivated = position.is_empty();
         position.add_shares(shares, amount_e6, fund.stats.current_nav_e6, current_ts)?;
-        position.serialize(&mut *lp_position.data.borrow_mut())?;
+        if reactivated {
+            // A previously emptied position counts (and is listed) again
+            fund.stats.lp_count = fund.stats.lp_count.saturating_add(1);
+            lp_index_add(program_id, accounts, investor, system_program, fund_account.key, &mut fund, lp_position, &mut position, current_ts)?;
+        }
+        position.has_receipt |= fund.deposit_receipts_enabled;
+        position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+        position.shares
+    };
+    
+    // Issue or refresh the deposit receipt NFT
+    if fund.deposit_receipts_enabled {
+        let deposit_receipt = next_account_info(account_info_iter)?;
+        let receipt_mint = next_account_info(account_info_iter)?;
+        let receipt_token = next_account_info(account_info_iter)?;
+        
+        issue_deposit_receipt(
+            program_id,
+            investor,
+            fund_account,
+            lp_position,
+            deposit_receipt,
+            receipt_mint,
+            receipt_token,
+            token_program,
+            system_program,
+            &[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]],
+            amount_e6,
+            fund.stats.current_nav_e6,
+            current_ts,
+        )?;
     }
     
     // Update fund stats
+    fund.accrue_aum(current_ts);
     fund.record_deposit(amount_e6, shares)?;
     fund.last_update_ts = current_ts;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    
+    if let Some(campaign_id) = args.campaign_id {
+        record_deposit_attribution(
+            program_id,
+            accounts,
+            investor,
+            system_program,
+            campaign_id,
+            fund_account.key,
+            amount_e6,
+            shares,
+            fund.stats.current_nav_e6,
+            current_ts,
+        )?;
+    }
+    
+    accrue_fund_lp_referral(program_id, accounts, fund_account.key, &fund, investor.key, amount_e6)?;
+    record_share_checkpoint(program_id, accounts, fund_account.key, &fund, current_ts)?;
+    update_investor_portfolio(program_id, accounts, lp_position, fund.stats.current_nav_e6, current_ts)?;
+    
+    invoke_fund_hook(
+        program_id,
+        accounts,
+        fund_account,
+        &fund,
+        investor,
+        FundHookEvent::Deposit,
+        amount_e6,
+        shares,
+        current_ts,
+    )?;
     
     msg!("Deposit to fund: {} USDC", args.amount);
     msg!("Shares minted: {}", shares);
     msg!("Current NAV: {}", fund.stats.current_nav_e6);
+    if let Some(memo) = args.memo.as_deref() {
+        attach_memo(accounts, investor, memo)?;
+    }
+    
+    // Set after every CPI so a hook or memo cannot overwrite it
+    set_fund_return_data(&DepositResult {
+        shares_minted: shares,
+        amount_e6,
+        nav_e6: fund.stats.current_nav_e6,
+        total_shares: fund.stats.total_shares,
+        position_shares,
+        remaining_capacity_e6: DEPOSIT_CAPACITY_UNLIMITED,
+        accepting_deposits: fund.can_deposit() && !fund.is_share_supply_inconsistent(),
+    })?;
+    log_compute_units("deposit:end");
     
     Ok(())
 }
 
 /// Redeem shares from a fund
+///
+/// `unwrap_sol` closes the investor's wSOL account after the payout so the
+/// proceeds land in their wallet as native SOL.
 fn process_redeem_from_fund(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     args: RedeemFromFundArgs,
+    unwrap_sol: bool,
 ) -> ProgramResult {
+    log_compute_units("redeem:start");
+    if args.shares == 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    validate_memo(args.memo.as_deref())?;
+    
+    let redemption = redeem_lp_position(program_id, accounts, RedeemAmount::Shares(args.shares), unwrap_sol)?;
+    
+    msg!("Redeem from fund: {} shares", redemption.shares);
+    msg!("USDC received: {}", redemption.payout_e6);
+    if redemption.fee_e6 > 0 {
+        msg!("Redemption fee retained: {}", redemption.fee_e6);
+    }
+    msg!("Current NAV: {}", redemption.nav_e6);
+    if let Some(memo) = args.memo.as_deref() {
+        attach_memo(accounts, &accounts[0], memo)?;
+    }
+    log_compute_units("redeem:end");
+    
+    Ok(())
+}
+
+/// How much of the LP position a redemption takes
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RedeemAmount {
+    /// A number of shares; the position stays open when emptied
+    Shares(u64),
+    /// Every share, closing the position (RedeemAll)
+    All,
+}
+
+/// What a redemption paid out
+struct Redemption {
+    shares: u64,
+    payout_e6: i64,
+    fee_e6: i64,
+    nav_e6: i64,
+}
+
+/// Redemption core shared by RedeemFromFund, RedeemFromFundSol and RedeemAll
+///
+/// Accounts 0-7 as in RedeemFromFund (investor must be writable for
+/// RedeemAll and SOL unwrapping); everything else is found among the
+/// trailing accounts.
+fn redeem_lp_position(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: RedeemAmount,
+    unwrap_sol: bool,
+) -> Result<Redemption, ProgramError> {
     let account_info_iter = &mut accounts.iter();
     
     let investor = next_account_info(account_info_iter)?;
@@ -744,42 +1618,94 @@ fn process_redeem_from_fund(
     let share_mint = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     
-    assert_signer(investor)?;
-    assert_owned_by(fund_account, program_id)?;
+    validate_accounts! {
+        investor: signer;
+        fund_account: writable, owner = program_id;
+        fund_vault: writable;
+        investor_usdc: writable;
+        lp_position: writable, owner = program_id;
+        investor_shares: writable;
+        share_mint: writable;
+        token_program: key = &spl_token::id();
+    }
+    let close_position = amount == RedeemAmount::All;
+    if close_position || unwrap_sol {
+        assert_writable(investor)?;
+    }
     
-    if args.shares == 0 {
-        return Err(FundError::InvalidAmount.into());
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
     }
     
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    if fund.distribution_in_progress {
+        return Err(FundError::DistributionInProgress.into());
+    }
     
-    if !fund.can_withdraw() {
-        return Err(FundError::FundPaused.into());
+    if unwrap_sol && !fund.sol_denominated {
+        return Err(FundError::InvalidMint.into());
     }
     
     let current_ts = get_current_timestamp()?;
+    assert_fund_withdrawable(&fund, fund_account.key, current_ts)?;
+    assert_outside_blackout(&fund, current_ts)?;
     
-    // Calculate redemption value
-    let redemption_value = calculate_redemption_value(args.shares, fund.stats.current_nav_e6)?;
-    
-    // Check fund has enough balance
-    let vault_account = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
-    if vault_account.amount < redemption_value as u64 {
-        return Err(FundError::InsufficientBalance.into());
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+        share_mint: key = &fund.share_mint;
     }
+    assert_fund_vault_mint(&fund, fund_vault)?;
     
-    // Update LP position
-    let mut position = LPPosition::try_from_slice(&lp_position.data.borrow())?;
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
     
-    if position.fund != *fund_account.key || position.investor != *investor.key {
+    if position.discriminator != LP_POSITION_DISCRIMINATOR
+        || position.fund != *fund_account.key
+        || position.investor != *investor.key
+    {
         return Err(FundError::LPPositionNotFound.into());
     }
     
-    if position.shares < args.shares {
-        return Err(FundError::InsufficientShares.into());
+    let (shares, redemption_value) = match amount {
+        RedeemAmount::Shares(shares) => {
+            if position.shares < shares {
+                return Err(FundError::InsufficientShares.into());
+            }
+            (shares, calculate_redemption_value(shares, fund.stats.current_nav_e6)?)
+        }
+        RedeemAmount::All => {
+            if position.shares == 0 {
+                return Err(FundError::InsufficientShares.into());
+            }
+            // The last LP out takes the full remaining value (no NAV dust)
+            (position.shares, fund.full_exit_value_e6(position.shares)?)
+        }
+    };
+    
+    if position.payout_allowlist_enabled {
+        assert_payout_allowed(program_id, lp_position, investor_usdc, accounts, current_ts)?;
     }
     
-    position.remove_shares(args.shares, redemption_value, current_ts)?;
+    // Dynamic fee on the cash ratio left behind, retained for the remaining LPs
+    let vault_account = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    assert_fund_vault_authorities(&vault_account, fund_account.key)?;
+    let vault_balance_e6 = fund.token_amount_to_e6(vault_account.amount)?;
+    let redemption_fee = fund.redemption_fee_curve.fee_for(
+        redemption_value,
+        (vault_balance_e6 as u64).saturating_sub(redemption_value as u64),
+        fund.stats.total_value_e6().saturating_sub(redemption_value),
+    );
+    let payout = redemption_value - redemption_fee;
+    let payout_amount = fund.e6_to_token_amount(payout)?;
+    
+    // Check fund has enough balance
+    if vault_account.amount < payout_amount {
+        return Err(FundError::InsufficientBalance.into());
+    }
+    
+    // Fails with SharesLocked while any shares are pledged
+    position.remove_shares(shares, payout, current_ts)?;
+    log_compute_units("redeem:validated");
     
     // Burn share tokens
     invoke(
@@ -789,45 +1715,95 @@ fn process_redeem_from_fund(
             share_mint.key,
             investor.key,
             &[],
-            args.shares,
+            shares,
         )?,
         &[investor_shares.clone(), share_mint.clone(), investor.clone(), token_program.clone()],
     )?;
     
     // Transfer USDC to investor
-    let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
-    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
-    let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+    if payout_amount > 0 {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                fund_vault.key,
+                investor_usdc.key,
+                fund_account.key,
+                &[],
+                payout_amount,
+            )?,
+            &[fund_vault.clone(), investor_usdc.clone(), fund_account.clone(), token_program.clone()],
+            &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund.bump]]],
+        )?;
+    }
     
-    invoke_signed(
-        &spl_token::instruction::transfer(
-            &spl_token::id(),
-            fund_vault.key,
-            investor_usdc.key,
-            fund_account.key,
-            &[],
-            redemption_value as u64,
-        )?,
-        &[fund_vault.clone(), investor_usdc.clone(), fund_account.clone(), token_program.clone()],
-        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]]],
-    )?;
+    // Unwrap: closing the native account releases its lamports to the investor
+    if unwrap_sol {
+        invoke(
+            &spl_token::instruction::close_account(
+                &spl_token::id(),
+                investor_usdc.key,
+                investor.key,
+                investor.key,
+                &[],
+            )?,
+            &[investor_usdc.clone(), investor.clone(), investor.clone(), token_program.clone()],
+        )?;
+    }
     
-    // Check if position is empty
     if position.is_empty() {
         fund.stats.lp_count = fund.stats.lp_count.saturating_sub(1);
+        lp_index_remove(program_id, accounts, fund_account.key, lp_position, &mut position)?;
+        close_out_deposit_receipt(program_id, accounts, investor, lp_position, &mut position, token_program)?;
     }
     
-    position.serialize(&mut *lp_position.data.borrow_mut())?;
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
     
     // Update fund stats
-    fund.record_withdrawal(redemption_value, args.shares)?;
+    fund.accrue_aum(current_ts);
+    fund.record_withdrawal(payout, shares)?;
+    fund.total_redemption_fees_e6 = safe_add_i64(fund.total_redemption_fees_e6, redemption_fee)?;
     fund.last_update_ts = current_ts;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    record_share_checkpoint(program_id, accounts, fund_account.key, &fund, current_ts)?;
+    update_investor_portfolio(program_id, accounts, lp_position, fund.stats.current_nav_e6, current_ts)?;
     
-    msg!("Redeem from fund: {} shares", args.shares);
-    msg!("USDC received: {}", redemption_value);
-    msg!("Current NAV: {}", fund.stats.current_nav_e6);
+    // Before the close: the rent refund would leave the hook CPI unbalanced
+    invoke_fund_hook(
+        program_id,
+        accounts,
+        fund_account,
+        &fund,
+        investor,
+        FundHookEvent::Redeem,
+        payout,
+        shares,
+        current_ts,
+    )?;
+    
+    // Closed only after the portfolio has read the emptied position
+    if close_position {
+        close_user_flow_pda(program_id, accounts, lp_position, investor, position.rent_sponsored, current_ts)?;
+    }
     
+    Ok(Redemption {
+        shares,
+        payout_e6: payout,
+        fee_e6: redemption_fee,
+        nav_e6: fund.stats.current_nav_e6,
+    })
+}
+
+/// Log a truncated memo and, if the SPL Memo program was passed, attach it on-chain
+fn attach_memo<'a>(
+    accounts: &[AccountInfo<'a>],
+    signer: &AccountInfo<'a>,
+    memo: &str,
+) -> ProgramResult {
+    msg!("Memo: {}", truncate_memo(memo, MEMO_EVENT_LEN));
+    if let Some(memo_program) = accounts.iter().find(|a| *a.key == crate::cpi::SPL_MEMO_PROGRAM_ID) {
+        crate::cpi::memo(memo_program, signer, memo)?;
+    }
     Ok(())
 }
 
@@ -835,12 +1811,33 @@ fn process_redeem_from_fund(
 // Trading Operations
 // =============================================================================
 
+/// Risk checks shared by every fund trade path: order parameters and margin buffer
+/// Returns (notional, required margin, available margin)
+fn check_trade_risk(
+    fund: &Fund,
+    side: u8,
+    size_e6: u64,
+    price_e6: u64,
+    leverage: u8,
+) -> Result<(u64, u64, i64), ProgramError> {
+    if side > 1 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (notional_e6, required_margin_e6) = calculate_trade_margin(size_e6, price_e6, leverage)?;
+    let available_margin_e6 = fund.stats.total_value_e6();
+    if (required_margin_e6 as i128) > available_margin_e6 as i128 {
+        return Err(FundError::InsufficientBalance.into());
+    }
+    Ok((notional_e6, required_margin_e6, available_margin_e6))
+}
+
 /// Trade using fund assets
 fn process_trade_fund(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     args: TradeFundArgs,
 ) -> ProgramResult {
+    log_compute_units("trade:start");
     let account_info_iter = &mut accounts.iter();
     
     let manager = next_account_info(account_info_iter)?;
@@ -855,10 +1852,18 @@ fn process_trade_fund(
     let vault_program = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     
-    assert_signer(manager)?;
-    assert_owned_by(fund_account, program_id)?;
+    validate_accounts! {
+        manager: signer;
+        fund_account: owner = program_id;
+        fund_config: owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
     
-    let fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
     
     if !fund.is_manager(manager.key) {
         return Err(FundError::NotFundManager.into());
@@ -869,10 +1874,42 @@ fn process_trade_fund(
     }
     
     // Verify Ledger Program
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
     if ledger_program.key != &config.ledger_program {
         return Err(FundError::InvalidAccountOwner.into());
     }
+    validate_accounts! {
+        vault_program: key = &config.vault_program;
+    }
+    
+    let (notional_e6, required_margin_e6, available_margin_e6) =
+        check_trade_risk(&fund, args.side, args.size_e6, args.price_e6, args.leverage)?;
+    apply_manager_risk(
+        program_id,
+        accounts,
+        &config,
+        manager.key,
+        notional_e6,
+        args.leverage,
+        !args.simulate_only,
+    )?;
+    
+    if args.simulate_only {
+        let result = TradeSimulationResult {
+            notional_e6,
+            required_margin_e6,
+            available_margin_e6,
+            leverage: args.leverage,
+        };
+        set_fund_return_data(&result)?;
+        
+        msg!("Trade simulation: market={}, side={}, notional={}, margin={}, available={}",
+            args.market_index, args.side, notional_e6, required_margin_e6, available_margin_e6);
+        return Ok(());
+    }
     
     // CPI call to Ledger Program to open position
     let fund_seeds = Fund::seeds(manager.key, fund.fund_index);
@@ -881,6 +1918,7 @@ fn process_trade_fund(
     
     // Generate batch ID from timestamp
     let batch_id = get_current_timestamp()? as u64;
+    log_compute_units("trade:validated");
     
     crate::cpi::open_position(
         ledger_program.key,
@@ -904,6 +1942,7 @@ fn process_trade_fund(
     
     msg!("Trade fund: market={}, side={}, size={}, leverage={}, batch_id={}",
         args.market_index, args.side, args.size_e6, args.leverage, batch_id);
+    log_compute_units("trade:end");
     
     Ok(())
 }
@@ -928,20 +1967,41 @@ fn process_close_fund_position(
     let user_stats = next_account_info(account_info_iter)?;
     let vault_program = next_account_info(account_info_iter)?;
     
-    assert_signer(manager)?;
-    assert_owned_by(fund_account, program_id)?;
+    validate_accounts! {
+        manager: signer;
+        fund_account: owner = program_id;
+        fund_config: owner = program_id;
+    }
     
-    let fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
     
     if !fund.is_manager(manager.key) {
         return Err(FundError::NotFundManager.into());
     }
     
     // Verify Ledger Program
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
     if ledger_program.key != &config.ledger_program {
         return Err(FundError::InvalidAccountOwner.into());
     }
+    validate_accounts! {
+        vault_program: key = &config.vault_program;
+    }
+    
+    let size_e6 = if args.size_e6 == 0 {
+        ledger_position_size(position, ledger_program.key, fund_account.key, args.market_index)?
+    } else {
+        args.size_e6
+    };
+    
+    release_manager_risk(program_id, accounts, manager.key, size_e6, args.price_e6)?;
     
     // CPI call to Ledger Program to close position
     let fund_seeds = Fund::seeds(manager.key, fund.fund_index);
@@ -963,18 +2023,38 @@ fn process_close_fund_position(
         vault_program.clone(),
         *fund_account.key,  // User is the fund itself
         args.market_index,
-        args.size_e6,
+        size_e6,
         args.price_e6,
         batch_id,
         &[&[FUND_SEED, manager.key.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]]],
     )?;
     
     msg!("Close fund position: market={}, size={}, price={}, batch_id={}",
-        args.market_index, args.size_e6, args.price_e6, batch_id);
+        args.market_index, size_e6, args.price_e6, batch_id);
     
     Ok(())
 }
 
+/// Full size of the fund's Ledger position in `market_index` (for close-all)
+fn ledger_position_size(
+    position: &AccountInfo,
+    ledger_program: &Pubkey,
+    fund: &Pubkey,
+    market_index: u8,
+) -> Result<u64, ProgramError> {
+    if position.owner != ledger_program {
+        return Err(FundError::LedgerPositionMismatch.into());
+    }
+    let header = crate::ledger_interface::LedgerPositionHeader::unpack(&position.data.borrow())?;
+    if header.user != *fund || header.market_index != market_index {
+        return Err(FundError::LedgerPositionMismatch.into());
+    }
+    if header.size_e6 == 0 {
+        return Err(FundError::LedgerPositionEmpty.into());
+    }
+    Ok(header.size_e6)
+}
+
 // =============================================================================
 // Fee Operations
 // =============================================================================
@@ -983,66 +2063,157 @@ fn process_close_fund_position(
 fn process_collect_fees(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    args: CollectFeesArgs,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
-    let manager = next_account_info(account_info_iter)?;
+    let caller = next_account_info(account_info_iter)?;
     let fund_account = next_account_info(account_info_iter)?;
     let fund_vault = next_account_info(account_info_iter)?;
     let manager_usdc = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     
-    assert_signer(manager)?;
-    assert_owned_by(fund_account, program_id)?;
+    validate_accounts! {
+        caller: signer;
+        fund_account: writable, owner = program_id;
+        fund_vault: writable;
+        manager_usdc: writable;
+        token_program: key = &spl_token::id();
+    }
     
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
     
-    if !fund.is_manager(manager.key) {
-        return Err(FundError::NotFundManager.into());
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    // Fee crank path: an authorized relayer collects on the manager's behalf
+    if !fund.is_manager(caller.key) {
+        let fund_config = next_account_info(account_info_iter)
+            .map_err(|_| ProgramError::from(FundError::NotFundManager))?;
+        assert_owned_by(fund_config, program_id)?;
+        
+        let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+        if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+            return Err(FundError::FundNotInitialized.into());
+        }
+        
+        if !fund.can_collect_fees(caller.key, Some(&config)) {
+            return Err(FundError::FeeCrankNotAuthorized.into());
+        }
+    }
+    
+    // Proceeds must land in the fund's configured fee destination
+    let destination = spl_token::state::Account::unpack(&manager_usdc.data.borrow())?;
+    if !fund.is_valid_fee_destination(manager_usdc.key, &destination.owner) {
+        return Err(FundError::InvalidFeeDestination.into());
     }
     
     let current_ts = get_current_timestamp()?;
     
+    // Benchmark price for a relative performance fee / crystallization record
+    let benchmark_price_e6 = if fund.has_benchmark() {
+        Some(read_benchmark_price(program_id, accounts, &fund.benchmark_feed, current_ts)?)
+    } else {
+        None
+    };
+    
+    // Calculate fees (management fee from the time-weighted AUM integral)
+    fund.accrue_aum(current_ts);
+    let (mgmt_accrued, perf_accrued) = fund.calculate_fees_with_benchmark(current_ts, benchmark_price_e6)?;
+    let (mgmt_fee, perf_fee) = Fund::plan_fee_collection(
+        mgmt_accrued,
+        perf_accrued,
+        args.include_management,
+        args.include_performance,
+        args.max_amount_e6,
+    );
+    let total_fee = safe_add_i64(mgmt_fee, perf_fee)?;
+    
+    let result = CollectFeesResult {
+        management_fee_e6: mgmt_fee,
+        performance_fee_e6: perf_fee,
+        deferred_management_fee_e6: mgmt_accrued.saturating_sub(mgmt_fee),
+        deferred_performance_fee_e6: perf_accrued.saturating_sub(perf_fee),
+    };
+    set_fund_return_data(&result)?;
+    
+    if args.preview_only {
+        msg!("Fee preview:");
+        msg!("  Management fee: {} (deferred {})", mgmt_fee, result.deferred_management_fee_e6);
+        msg!("  Performance fee: {} (deferred {})", perf_fee, result.deferred_performance_fee_e6);
+        return Ok(());
+    }
+    
     // Check fee collection interval
     if !can_collect_fees(fund.stats.last_fee_collection_ts, fund.fee_config.fee_collection_interval)? {
         return Err(FundError::FeeCollectionTooEarly.into());
     }
     
-    // Calculate fees
-    let (mgmt_fee, perf_fee) = fund.calculate_fees(current_ts)?;
-    let total_fee = safe_add_i64(mgmt_fee, perf_fee)?;
-    
     if total_fee <= 0 {
         return Err(FundError::NoFeesToCollect.into());
     }
     
+    // LP referral bounties are funded first and stay in the vault until claimed
+    let referral_withheld = withhold_fund_lp_referral_bounty(program_id, accounts, fund_account.key, &fund, total_fee)?;
+    let payout = total_fee - referral_withheld;
+    
     // Transfer fees to manager
-    let fund_seeds = Fund::seeds(manager.key, fund.fund_index);
+    let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
     let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
     let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
     
-    invoke_signed(
-        &spl_token::instruction::transfer(
-            &spl_token::id(),
-            fund_vault.key,
-            manager_usdc.key,
-            fund_account.key,
-            &[],
-            total_fee as u64,
-        )?,
-        &[fund_vault.clone(), manager_usdc.clone(), fund_account.clone(), token_program.clone()],
-        &[&[FUND_SEED, manager.key.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]]],
-    )?;
-    
-    // Update fund state
-    fund.collect_fees(mgmt_fee, perf_fee, current_ts)?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
-    
-    msg!("Fees collected:");
-    msg!("  Management fee: {}", mgmt_fee);
-    msg!("  Performance fee: {}", perf_fee);
-    msg!("  Total: {}", total_fee);
-    
+    if payout > 0 {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                fund_vault.key,
+                manager_usdc.key,
+                fund_account.key,
+                &[],
+                fund.e6_to_token_amount(payout)?,
+            )?,
+            &[fund_vault.clone(), manager_usdc.clone(), fund_account.clone(), token_program.clone()],
+            &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]]],
+        )?;
+    }
+    
+    // Update fund state
+    let accrued_aum_seconds_e6 = fund.aum_seconds_e6;
+    let perf_collected = args.include_performance && perf_fee == perf_accrued;
+    fund.collect_fees_partial(mgmt_fee, mgmt_accrued, perf_collected, perf_fee, current_ts)?;
+    if let (true, Some(price)) = (perf_collected, benchmark_price_e6) {
+        fund.benchmark_base_price_e6 = price;
+        msg!("  Benchmark crystallized at: {}", price);
+    }
+    // Funds that predate the fee epoch fields grow on their first crystallization
+    if !fund.fits_in(fund_account.data_len()) {
+        let system_program = accounts
+            .iter()
+            .find(|a| *a.key == solana_program::system_program::id())
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        realloc_with_rent(fund_account, caller, system_program, Fund::SIZE)?;
+        fund.start_nav_twap(current_ts);
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    
+    msg!("Fees collected:");
+    msg!("  Collected by: {}", caller.key);
+    msg!("  Destination: {}", manager_usdc.key);
+    msg!("  AUM-seconds accrued: {}", accrued_aum_seconds_e6);
+    msg!("  Management fee: {} (deferred {})", mgmt_fee, result.deferred_management_fee_e6);
+    msg!("  Performance fee: {} (deferred {})", perf_fee, result.deferred_performance_fee_e6);
+    msg!("  Total: {}", total_fee);
+    if referral_withheld > 0 {
+        msg!("  LP referral bounty withheld: {}", referral_withheld);
+    }
+    
     Ok(())
 }
 
@@ -1064,14 +2235,14 @@ fn process_update_authority(
     assert_signer(authority)?;
     assert_owned_by(fund_config, program_id)?;
     
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     
     if config.authority != *authority.key {
         return Err(FundError::AdminRequired.into());
     }
     
     config.authority = args.new_authority;
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
     
     msg!("Authority updated to: {}", args.new_authority);
     
@@ -1092,14 +2263,14 @@ fn process_set_program_paused(
     assert_signer(authority)?;
     assert_owned_by(fund_config, program_id)?;
     
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     
     if config.authority != *authority.key {
         return Err(FundError::AdminRequired.into());
     }
     
     config.is_paused = args.is_paused;
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
     
     msg!("Program is now {}", if args.is_paused { "paused" } else { "unpaused" });
     
@@ -1121,11 +2292,19 @@ fn process_update_nav(
     
     assert_owned_by(fund_account, program_id)?;
     
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
     
+    let current_ts = get_current_timestamp()?;
+    fund.accrue_aum(current_ts);
     fund.stats.update_nav()?;
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    check_nav_floor(&mut fund, fund_account, current_ts);
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    if let Some(price_feed) = account_info_iter.next() {
+        publish_share_price(program_id, price_feed, fund_account.key, &fund, current_ts)?;
+    }
     
     msg!("NAV updated: {}", fund.stats.current_nav_e6);
     
@@ -1145,7 +2324,7 @@ fn process_record_pnl(
     let fund_config = next_account_info(account_info_iter)?;
     
     // Verify caller is Ledger Program
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::FundNotInitialized.into());
     }
@@ -1158,11 +2337,22 @@ fn process_record_pnl(
     
     assert_owned_by(fund_account, program_id)?;
     
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    
+    let current_ts = get_current_timestamp()?;
     
+    fund.accrue_aum(current_ts);
+    let value_before_e6 = fund.stats.total_value_e6();
     fund.record_pnl(args.pnl_e6)?;
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    check_nav_floor(&mut fund, fund_account, current_ts);
+    if fund.check_pnl_blackout(args.pnl_e6, value_before_e6, current_ts) {
+        msg!("⏸️ NAV_BLACKOUT_STARTED");
+        msg!("  Fund: {}", fund_account.key);
+        msg!("  Until: {}", fund.blackout_until);
+    }
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
     
     msg!("PnL recorded: {}", args.pnl_e6);
     msg!("New NAV: {}", fund.stats.current_nav_e6);
@@ -1170,6 +2360,16 @@ fn process_record_pnl(
     Ok(())
 }
 
+/// Verify a Ledger-only instruction was signed by the Ledger's fund caller PDA
+fn assert_ledger_caller(config: &FundConfig, caller: &AccountInfo) -> ProgramResult {
+    let (expected, _) = crate::ledger_interface::fund_caller_address(&config.ledger_program);
+    if caller.key != &expected {
+        msg!("Unauthorized caller: expected {}, got {}", expected, caller.key);
+        return Err(FundError::UnauthorizedCaller.into());
+    }
+    assert_signer(caller)
+}
+
 // =============================================================================
 // Insurance Fund Operations
 // =============================================================================
@@ -1200,7 +2400,7 @@ fn process_initialize_insurance_fund(
     assert_signer(authority)?;
     
     // Load FundConfig and verify authority
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::FundNotInitialized.into());
     }
@@ -1295,7 +2495,7 @@ fn process_initialize_insurance_fund(
             share_mint.key,
             &fund_pda,
             Some(&fund_pda),
-            6,
+            SHARE_MINT_DECIMALS,
         )?,
         &[share_mint.clone(), rent_sysvar.clone()],
         &[&[SHARE_MINT_SEED, fund_pda.as_ref(), &[mint_bump]]],
@@ -1353,7 +2553,7 @@ fn process_initialize_insurance_fund(
         fee_collection_interval: 0,
     };
     
-    let fund = Fund::new(
+    let mut fund = Fund::new(
         *authority.key,
         "1024 Insurance Fund",
         fund_bump,
@@ -1363,8 +2563,11 @@ fn process_initialize_insurance_fund(
         fund_index,
         current_ts,
     );
+    fund.is_insurance_fund = true;
+    fund.is_system_fund = true;
     
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
     
     // Initialize InsuranceFundConfig
     let insurance_fund_config = InsuranceFundConfig::new(
@@ -1376,18 +2579,19 @@ fn process_initialize_insurance_fund(
         current_ts,
     );
     
-    insurance_fund_config.serialize(&mut *insurance_config.data.borrow_mut())?;
+    insurance_fund_config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
     
     // Update FundConfig
     config.total_funds = config.total_funds.saturating_add(1);
     config.active_funds = config.active_funds.saturating_add(1);
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
     
     msg!("Insurance Fund initialized");
     msg!("Fund: {}", fund_account.key);
     msg!("Config: {}", insurance_config.key);
     msg!("ADL threshold: {}", args.adl_trigger_threshold_e6);
     msg!("Withdrawal delay: {} seconds", args.withdrawal_delay_secs);
+    msg!("Created at: {}", current_ts);
     
     Ok(())
 }
@@ -1408,7 +2612,7 @@ fn process_add_liquidation_income(
     assert_owned_by(insurance_config, program_id)?;
     
     // Load and verify InsuranceFundConfig
-    let mut config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1422,13 +2626,16 @@ fn process_add_liquidation_income(
     // Update stats
     config.add_liquidation_income(args.amount_e6);
     config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *insurance_config.data.borrow_mut())?;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
     
     // Update Fund's realized PnL (income is positive PnL for the fund)
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    let current_ts = get_current_timestamp()?;
+    fund.accrue_aum(current_ts);
     fund.record_pnl(args.amount_e6)?;
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
     
     msg!("Liquidation income added: {}", args.amount_e6);
     msg!("Total liquidation income: {}", config.total_liquidation_income_e6);
@@ -1452,7 +2659,7 @@ fn process_add_adl_profit(
     assert_owned_by(insurance_config, program_id)?;
     
     // Load and verify InsuranceFundConfig
-    let mut config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1466,13 +2673,16 @@ fn process_add_adl_profit(
     // Update stats
     config.add_adl_profit(args.amount_e6);
     config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *insurance_config.data.borrow_mut())?;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
     
     // Update Fund's realized PnL
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    let current_ts = get_current_timestamp()?;
+    fund.accrue_aum(current_ts);
     fund.record_pnl(args.amount_e6)?;
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
     
     msg!("ADL profit added: {}", args.amount_e6);
     msg!("Total ADL profit: {}", config.total_adl_profit_e6);
@@ -1499,7 +2709,7 @@ fn process_cover_shortfall(
     assert_owned_by(insurance_config, program_id)?;
     
     // Load and verify InsuranceFundConfig
-    let mut config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1510,16 +2720,37 @@ fn process_cover_shortfall(
         return Err(FundError::UnauthorizedCaller.into());
     }
     
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    validate_accounts! {
+        fund_vault: writable, key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
     // Get current balance
     let vault_account = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
-    let current_balance = vault_account.amount as i64;
+    let mut current_balance = vault_account.amount as i64;
+    
+    // Pull deployed capital back before reporting an uncovered remainder
+    if args.shortfall_e6 > current_balance {
+        current_balance += recall_for_shortfall(
+            program_id,
+            accounts,
+            fund_account,
+            fund_vault,
+            token_program,
+            args.shortfall_e6 - current_balance,
+        )?;
+    }
     
     // Calculate coverage
     let (covered, remaining) = config.cover_shortfall(args.shortfall_e6, current_balance);
     
     if covered > 0 {
         // Transfer covered amount from insurance fund
-        let fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+        let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
         let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
         let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
         let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
@@ -1538,20 +2769,28 @@ fn process_cover_shortfall(
         )?;
         
         // Update Fund stats (shortfall is negative PnL)
-        let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+        let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+        let current_ts = get_current_timestamp()?;
+        fund.accrue_aum(current_ts);
         fund.record_pnl(-covered)?;
-        fund.last_update_ts = get_current_timestamp()?;
-        fund.serialize(&mut *fund_account.data.borrow_mut())?;
+        fund.last_update_ts = current_ts;
+        fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+        enforce_fund_invariants(&fund, Some(fund_vault))?;
     }
     
     config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *insurance_config.data.borrow_mut())?;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
     
     msg!("Shortfall coverage:");
     msg!("  Requested: {}", args.shortfall_e6);
     msg!("  Covered: {}", covered);
     msg!("  Remaining (needs ADL): {}", remaining);
     
+    // The uncovered remainder is socialized by the current (or next) ADL episode
+    if remaining > 0 {
+        record_adl_shortfall(program_id, accounts, insurance_config, remaining)?;
+    }
+    
     if remaining > 0 {
         msg!("⚠️ Insurance Fund insufficient, ADL required for: {}", remaining);
     }
@@ -1560,22 +2799,28 @@ fn process_cover_shortfall(
 }
 
 /// Update hourly snapshot (for 30% decline trigger condition)
+///
+/// When the InsuranceDailyStats PDA for the current day is passed, the
+/// income/payout since the last daily write is also booked to it. Once daily
+/// stats are on it is required, so deltas never pile up into a later day.
 fn process_update_hourly_snapshot(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
-    let _caller = next_account_info(account_info_iter)?;
+    let caller = next_account_info(account_info_iter)?;
     let fund_account = next_account_info(account_info_iter)?;
     let insurance_config = next_account_info(account_info_iter)?;
     let fund_vault = next_account_info(account_info_iter)?;
+    let daily_stats = next_account_info(account_info_iter).ok();
+    let system_program = next_account_info(account_info_iter).ok();
     
     assert_owned_by(fund_account, program_id)?;
     assert_owned_by(insurance_config, program_id)?;
     
     // Load InsuranceFundConfig
-    let mut config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1593,9 +2838,26 @@ fn process_update_hourly_snapshot(
     let vault_account = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
     let current_balance = vault_account.amount as i64;
     
+    // Book the day's income/payout before the snapshot overwrites the opening balance
+    if daily_stats.is_none() && config.daily_baseline_set {
+        return Err(FundError::InsuranceDailyStatsRequired.into());
+    }
+    if let Some(daily_stats) = daily_stats {
+        let system_program = system_program.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        record_insurance_daily_stats(
+            program_id,
+            caller,
+            daily_stats,
+            system_program,
+            &mut config,
+            current_balance,
+            current_ts,
+        )?;
+    }
+    
     // Update snapshot
     config.update_hourly_snapshot(current_balance, current_ts);
-    config.serialize(&mut *insurance_config.data.borrow_mut())?;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
     
     msg!("Hourly snapshot updated");
     msg!("  Balance: {}", current_balance);
@@ -1604,6 +2866,70 @@ fn process_update_hourly_snapshot(
     Ok(())
 }
 
+/// Write the current day's InsuranceDailyStats, creating it on the first
+/// snapshot of the day (rent paid by the caller)
+fn record_insurance_daily_stats<'a>(
+    program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    daily_stats: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    config: &mut InsuranceFundConfig,
+    current_balance: i64,
+    current_ts: i64,
+) -> ProgramResult {
+    validate_accounts! {
+        daily_stats: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let day = InsuranceDailyStats::day_index(current_ts);
+    let stats_seeds = InsuranceDailyStats::seeds(day);
+    let stats_seeds_refs: Vec<&[u8]> = stats_seeds.iter().map(|s| s.as_slice()).collect();
+    let stats_bump = assert_pda(daily_stats, &stats_seeds_refs, program_id)?;
+    
+    let mut stats = if daily_stats.data_is_empty() {
+        validate_accounts! {
+            caller: signer, writable;
+        }
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                caller.key,
+                daily_stats.key,
+                Rent::get()?.minimum_balance(InsuranceDailyStats::SIZE),
+                InsuranceDailyStats::SIZE as u64,
+                program_id,
+            ),
+            &[caller.clone(), daily_stats.clone(), system_program.clone()],
+            &[&[INSURANCE_DAILY_STATS_SEED, &day.to_le_bytes(), &[stats_bump]]],
+        )?;
+        
+        // Previous snapshot balance is the best available opening balance
+        InsuranceDailyStats::new(day, config.balance_1h_ago_e6, stats_bump)
+    } else {
+        assert_owned_by(daily_stats, program_id)?;
+        let stats = InsuranceDailyStats::try_from_slice(&daily_stats.data.borrow())?;
+        if stats.discriminator != INSURANCE_DAILY_STATS_DISCRIMINATOR {
+            return Err(FundError::InvalidInsuranceFundConfig.into());
+        }
+        stats
+    };
+    
+    let (liquidation_income, adl_profit, shortfall_payout) = config.take_daily_deltas();
+    stats.record_snapshot(liquidation_income, adl_profit, shortfall_payout, current_balance, current_ts);
+    stats.serialize(&mut &mut daily_stats.data.borrow_mut()[..])?;
+    
+    msg!("📊 INSURANCE_DAILY_STATS_UPDATED");
+    msg!("  Day: {}", day);
+    msg!("  Liquidation income: {}", stats.liquidation_income_e6);
+    msg!("  ADL profit: {}", stats.adl_profit_e6);
+    msg!("  Shortfall payout: {}", stats.shortfall_payout_e6);
+    msg!("  Net: {}", stats.net_e6);
+    msg!("  Ending balance: {}", stats.ending_balance_e6);
+    
+    Ok(())
+}
+
 /// Set ADL in progress status (CPI from Ledger)
 fn process_set_adl_in_progress(
     program_id: &Pubkey,
@@ -1618,7 +2944,7 @@ fn process_set_adl_in_progress(
     assert_owned_by(insurance_config, program_id)?;
     
     // Load and verify InsuranceFundConfig
-    let mut config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1629,9 +2955,48 @@ fn process_set_adl_in_progress(
         return Err(FundError::UnauthorizedCaller.into());
     }
     
-    config.set_adl_in_progress(args.in_progress);
-    config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *insurance_config.data.borrow_mut())?;
+    let was_in_progress = config.is_adl_in_progress;
+    let started = config.set_adl_in_progress(args.in_progress);
+    let current_ts = get_current_timestamp()?;
+    
+    // Append to the ADL event ledger when the episode accounts are passed
+    if started {
+        if let (Ok(adl_event), Ok(payer), Ok(system_program), Ok(fund_vault), Ok(fund_account)) = (
+            next_account_info(account_info_iter),
+            next_account_info(account_info_iter),
+            next_account_info(account_info_iter),
+            next_account_info(account_info_iter),
+            next_account_info(account_info_iter),
+        ) {
+            assert_owned_by(fund_account, program_id)?;
+            let twap_balance_e6 = insurance_twap_value(fund_account, &config)?;
+            open_adl_event(
+                program_id,
+                &config,
+                adl_event,
+                payer,
+                system_program,
+                fund_vault,
+                Some(twap_balance_e6),
+                current_ts,
+            )?;
+        }
+        config.pending_adl_shortfall_e6 = 0;
+    } else if was_in_progress && !args.in_progress {
+        if let Ok(adl_event) = next_account_info(account_info_iter) {
+            let mut event = load_adl_event(program_id, adl_event, config.adl_trigger_count)?;
+            event.close(current_ts)?;
+            event.serialize(&mut &mut adl_event.data.borrow_mut()[..])?;
+            
+            msg!("✅ ADL_EVENT_CLOSED");
+            msg!("  Episode: {}", event.episode);
+            msg!("  Socialized: {}", event.amount_socialized_e6);
+            msg!("  Duration: {} secs", event.ended_at - event.started_at);
+        }
+    }
+    
+    config.last_update_ts = current_ts;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
     
     msg!("ADL in progress: {}", args.in_progress);
     if args.in_progress {
@@ -1659,7 +3024,7 @@ fn process_check_adl_trigger(
     assert_owned_by(insurance_config, program_id)?;
     
     // Load InsuranceFundConfig
-    let config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1668,11 +3033,15 @@ fn process_check_adl_trigger(
     let vault_account = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
     let current_balance = vault_account.amount as i64;
     
+    // Rapid decline must also show in the NAV TWAP value
+    let twap_balance = insurance_twap_value(fund_account, &config)?;
+    
     // Check trigger conditions
-    let trigger_reason = config.should_trigger_adl(current_balance, args.shortfall_e6);
+    let trigger_reason = config.should_trigger_adl(current_balance, args.shortfall_e6, Some(twap_balance));
     
     msg!("ADL Trigger Check:");
     msg!("  Current balance: {}", current_balance);
+    msg!("  TWAP value: {}", twap_balance);
     msg!("  1h ago balance: {}", config.balance_1h_ago_e6);
     msg!("  ADL threshold: {}", config.adl_trigger_threshold_e6);
     msg!("  Shortfall: {}", args.shortfall_e6);
@@ -1706,6 +3075,9 @@ fn process_check_adl_trigger(
 /// 3. `[writable]` Vault Token Account (source of fees)
 /// 4. `[writable]` Insurance Fund Vault (destination)
 /// 5. `[]` Token Program
+/// 
+/// Optional referral routing: ReferralConfig, ReferralBinding, ReferralLink,
+/// Referral Reward Vault (accounts 6-9)
 fn process_add_trading_fee(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -1720,11 +3092,22 @@ fn process_add_trading_fee(
     let insurance_fund_vault = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
     
+    // Optional referral routing accounts
+    let referral_accounts = match account_info_iter.next() {
+        Some(referral_config) => Some((
+            referral_config,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        )),
+        None => None,
+    };
+    
     assert_owned_by(fund_account, program_id)?;
     assert_owned_by(insurance_config, program_id)?;
     
     // Load and verify InsuranceFundConfig
-    let mut config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1741,6 +3124,48 @@ fn process_add_trading_fee(
         return Err(FundError::InvalidAmount.into());
     }
     
+    // Fees may only land in this insurance fund's vault, in the vault's mint
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR || *fund_account.key != config.fund {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    validate_accounts! {
+        token_program: key = &spl_token::id();
+        vault_token_account: writable, owner = &spl_token::id();
+        insurance_fund_vault: writable, owner = &spl_token::id();
+    }
+    if *insurance_fund_vault.key != fund.fund_vault {
+        msg!("Fee destination {} is not the insurance vault {}", insurance_fund_vault.key, fund.fund_vault);
+        return Err(FundError::FeeDestinationMismatch.into());
+    }
+    let source_state = spl_token::state::Account::unpack(&vault_token_account.data.borrow())?;
+    let destination_state = spl_token::state::Account::unpack(&insurance_fund_vault.data.borrow())?;
+    if source_state.mint != destination_state.mint {
+        msg!("Fee source mint {} != insurance vault mint {}", source_state.mint, destination_state.mint);
+        return Err(FundError::FeeMintMismatch.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    
+    // Carve out the referrer share first; the remainder is insurance income
+    let referrer_reward_e6 = match referral_accounts {
+        Some((referral_config, referral_binding, referral_link, reward_vault)) => route_referral_reward(
+            program_id,
+            caller,
+            vault_token_account,
+            &source_state.mint,
+            token_program,
+            referral_config,
+            referral_binding,
+            referral_link,
+            reward_vault,
+            args.fee_e6,
+            current_ts,
+        )?,
+        None => 0,
+    };
+    let insurance_fee_e6 = args.fee_e6.saturating_sub(referrer_reward_e6);
+    
     // Transfer tokens from Vault to Insurance Fund
     let transfer_ix = spl_token::instruction::transfer(
         token_program.key,
@@ -1748,7 +3173,7 @@ fn process_add_trading_fee(
         insurance_fund_vault.key,
         caller.key,  // Ledger program is the authority
         &[],
-        args.fee_e6 as u64,
+        insurance_fee_e6 as u64,
     )?;
     
     invoke(
@@ -1762,22 +3187,154 @@ fn process_add_trading_fee(
     )?;
     
     // Update stats
-    config.add_trading_fee(args.fee_e6);
-    config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *insurance_config.data.borrow_mut())?;
+    config.add_trading_fee(insurance_fee_e6);
+    config.last_update_ts = current_ts;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
     
     // Update Fund's realized PnL (fee income is positive PnL for the fund)
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
-    fund.record_pnl(args.fee_e6)?;
-    fund.last_update_ts = get_current_timestamp()?;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    fund.accrue_aum(current_ts);
+    fund.record_pnl(insurance_fee_e6)?;
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
     
     msg!("TRADING_FEE_COLLECTED: fee_e6={}", args.fee_e6);
+    if referrer_reward_e6 > 0 {
+        msg!("Referrer share: {}", referrer_reward_e6);
+    }
     msg!("Total income now: {}", config.total_income_e6());
     
     Ok(())
 }
 
+/// Move the referrer share of a collected trading fee into the referral
+/// reward vault
+/// 
+/// The reward is accrued on the ReferralLink / ReferralBinding / ReferralConfig
+/// at the same time, so accrued and funded rewards never drift apart. Returns
+/// the amount routed; 0 when referrals are paused or the binding has lapsed.
+#[allow(clippy::too_many_arguments)]
+fn route_referral_reward<'a>(
+    program_id: &Pubkey,
+    caller: &AccountInfo<'a>,
+    fee_source: &AccountInfo<'a>,
+    fee_mint: &Pubkey,
+    token_program: &AccountInfo<'a>,
+    referral_config: &AccountInfo<'a>,
+    referral_binding: &AccountInfo<'a>,
+    referral_link: &AccountInfo<'a>,
+    reward_vault: &AccountInfo<'a>,
+    fee_e6: i64,
+    current_ts: i64,
+) -> Result<i64, ProgramError> {
+    assert_owned_by(referral_config, program_id)?;
+    assert_owned_by(referral_binding, program_id)?;
+    assert_owned_by(referral_link, program_id)?;
+    
+    let config_seeds = ReferralConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(referral_config, &config_seeds_refs, program_id)?;
+    
+    let vault_seeds = ReferralConfig::reward_vault_seeds();
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(reward_vault, &vault_seeds_refs, program_id)?;
+    let reward_vault_state = spl_token::state::Account::unpack(&reward_vault.data.borrow())?;
+    if reward_vault_state.mint != *fee_mint {
+        return Err(FundError::FeeMintMismatch.into());
+    }
+    
+    let mut config = ReferralConfig::try_from_slice(&referral_config.data.borrow())?;
+    if config.discriminator != REFERRAL_CONFIG_DISCRIMINATOR {
+        return Err(FundError::ReferralNotInitialized.into());
+    }
+    
+    let mut binding = ReferralBinding::try_from_slice(&referral_binding.data.borrow())?;
+    if binding.discriminator != REFERRAL_BINDING_DISCRIMINATOR {
+        return Err(FundError::NoReferralBinding.into());
+    }
+    
+    let mut link = ReferralLink::try_from_slice(&referral_link.data.borrow())?;
+    if link.discriminator != REFERRAL_LINK_DISCRIMINATOR || binding.referral_link != *referral_link.key {
+        return Err(FundError::ReferralLinkNotFound.into());
+    }
+    
+    // Same rule as RecordReferralTrade: no rewards, but don't fail the fee
+    if config.is_paused || binding.is_expired(config.reward_validity_secs, current_ts) {
+        msg!("⏳ Referral inactive, no referrer share routed");
+        return Ok(0);
+    }
+    
+    let reward_e6 = config.referrer_share_of_fee(fee_e6, link.custom_referrer_share_bps);
+    if reward_e6 <= 0 {
+        return Ok(0);
+    }
+    
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            fee_source.key,
+            reward_vault.key,
+            caller.key,
+            &[],
+            reward_e6 as u64,
+        )?,
+        &[
+            fee_source.clone(),
+            reward_vault.clone(),
+            caller.clone(),
+            token_program.clone(),
+        ],
+    )?;
+    
+    binding.record_funded_reward(reward_e6, current_ts);
+    binding.serialize(&mut &mut referral_binding.data.borrow_mut()[..])?;
+    
+    link.record_funded_reward(reward_e6, current_ts);
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
+    
+    config.record_funded_reward(reward_e6, current_ts);
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
+    
+    msg!("✅ REFERRAL_REWARD_FUNDED");
+    msg!("  Referrer: {}", binding.referrer);
+    msg!("  Referee: {}", binding.referee);
+    msg!("  Reward: {}", reward_e6);
+    msg!("  Total funded: {}", config.total_rewards_funded_e6);
+    
+    Ok(reward_e6)
+}
+
+/// Block Insurance Fund deposits while ADL is in progress
+/// 
+/// New LPs would otherwise buy in at a NAV about to absorb ADL losses. The
+/// InsuranceFundConfig PDA is looked up by address among `accounts`.
+fn assert_insurance_deposits_allowed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let config_seeds = InsuranceFundConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    let (config_key, _) = Pubkey::find_program_address(&config_seeds_refs, program_id);
+    
+    let config_account = accounts
+        .iter()
+        .find(|a| a.key == &config_key)
+        .ok_or(FundError::InvalidInsuranceFundConfig)?;
+    assert_owned_by(config_account, program_id)?;
+    
+    let config = InsuranceFundConfig::unpack_versioned(&config_account.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+    
+    if !config.can_deposit() {
+        msg!("❌ Insurance Fund deposits paused: ADL in progress");
+        return Err(FundError::ADLInProgress.into());
+    }
+    
+    Ok(())
+}
+
 /// Redeem shares from Insurance Fund (with special rules)
 /// 
 /// Special rules:
@@ -1809,7 +3366,7 @@ fn process_redeem_from_insurance_fund(
     }
     
     // Load InsuranceFundConfig
-    let config = InsuranceFundConfig::try_from_slice(&insurance_config.data.borrow())?;
+    let config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
     if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::InsuranceFundNotInitialized.into());
     }
@@ -1821,21 +3378,24 @@ fn process_redeem_from_insurance_fund(
     }
     
     // Load Fund
-    let mut fund = Fund::try_from_slice(&fund_account.data.borrow())?;
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
     
     // Verify this is the Insurance Fund
     if fund.fund_vault != *fund_vault.key || config.fund != *fund_account.key {
         return Err(FundError::InvalidFundAccount.into());
     }
+    assert_fund_vault_mint(&fund, fund_vault)?;
     
-    if !fund.can_withdraw() {
-        return Err(FundError::FundPaused.into());
+    if fund.distribution_in_progress {
+        return Err(FundError::DistributionInProgress.into());
     }
     
     let current_ts = get_current_timestamp()?;
+    assert_fund_withdrawable(&fund, fund_account.key, current_ts)?;
+    assert_outside_blackout(&fund, current_ts)?;
     
     // Load LP position
-    let mut position = LPPosition::try_from_slice(&lp_position.data.borrow())?;
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
     
     if position.fund != *fund_account.key || position.investor != *investor.key {
         return Err(FundError::LPPositionNotFound.into());
@@ -1869,6 +3429,10 @@ fn process_redeem_from_insurance_fund(
         return Err(FundError::InsufficientBalance.into());
     }
     
+    if position.payout_allowlist_enabled {
+        assert_payout_allowed(program_id, lp_position, investor_usdc, accounts, current_ts)?;
+    }
+    
     // Update LP position
     position.remove_shares(args.shares, redemption_value, current_ts)?;
     
@@ -1906,14 +3470,17 @@ fn process_redeem_from_insurance_fund(
     // Check if position is empty
     if position.is_empty() {
         fund.stats.lp_count = fund.stats.lp_count.saturating_sub(1);
+        lp_index_remove(program_id, accounts, fund_account.key, lp_position, &mut position)?;
     }
     
-    position.serialize(&mut *lp_position.data.borrow_mut())?;
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
     
     // Update fund stats
+    fund.accrue_aum(current_ts);
     fund.record_withdrawal(redemption_value, args.shares)?;
     fund.last_update_ts = current_ts;
-    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
     
     msg!(
         "✅ Insurance Fund redemption: {} shares = {} lamports",
@@ -1951,6 +3518,56 @@ fn process_square_payment(
     // Verify payer is signer
     assert_signer(payer)?;
     
+    let current_ts = get_current_timestamp()?;
+    let record = create_square_payment_record(program_id, accounts, payer, payment_record, system_program, &args, current_ts)?;
+    
+    let (revenue_split, to_treasury) = settle_square_payment(
+        program_id,
+        accounts,
+        payer,
+        payer_vault,
+        &[],
+        creator_vault,
+        square_fund_vault,
+        token_program,
+        system_program,
+        payer,
+        &args.creator,
+        record.payment_type,
+        record.creator_amount_e6,
+        record.platform_amount_e6,
+        current_ts,
+    )?;
+    
+    msg!("📝 SQUARE_PAYMENT_RECORD:");
+    msg!("  payer: {}", payer.key);
+    msg!("  creator: {}", args.creator);
+    msg!("  content_id: {}", args.content_id);
+    msg!("  payment_type: {:?}", record.payment_type);
+    msg!("  total_amount_e6: {}", args.amount_e6);
+    msg!("  creator_amount_e6: {}", record.creator_amount_e6);
+    msg!("  platform_amount_e6: {}", record.platform_amount_e6);
+    msg!("  platform_to_treasury: {}", to_treasury);
+    msg!("  creator_share_bps: {}", args.creator_share_bps);
+    if let Some(split) = revenue_split.as_ref() {
+        msg!("  revenue_split_recipients: {}", split.recipient_count);
+    }
+    msg!("  timestamp: {}", current_ts);
+    msg!("  record: {}", payment_record.key);
+    
+    Ok(())
+}
+
+/// Validate SquarePayment args, advance the payer nonce and create the record
+fn create_square_payment_record<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    payer: &AccountInfo<'a>,
+    payment_record: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    args: &SquarePaymentArgs,
+    current_ts: i64,
+) -> Result<SquarePaymentRecord, ProgramError> {
     if args.amount_e6 <= 0 {
         return Err(FundError::InvalidAmount.into());
     }
@@ -1959,9 +3576,6 @@ fn process_square_payment(
         return Err(FundError::InvalidFeeConfiguration.into());
     }
     
-    let current_ts = get_current_timestamp()?;
-    let rent = Rent::get()?;
-    
     // Convert payment type
     let payment_type = match args.payment_type {
         0 => SquarePaymentType::KnowledgePurchase,
@@ -1970,8 +3584,12 @@ fn process_square_payment(
         _ => return Err(FundError::InvalidPaymentType.into()),
     };
     
+    // Payer nonce must advance; it keys the record PDA
+    consume_square_payer_nonce(program_id, accounts, payer, system_program, args.nonce)?;
+    append_square_payment_history(program_id, accounts, payer, system_program, args.nonce, current_ts)?;
+    
     // Derive SquarePaymentRecord PDA
-    let record_seeds = SquarePaymentRecord::seeds(payer.key, args.content_id, current_ts);
+    let record_seeds = SquarePaymentRecord::seeds(payer.key, args.nonce);
     let record_seeds_refs: Vec<&[u8]> = record_seeds.iter().map(|s| s.as_slice()).collect();
     let (record_pda, record_bump) = Pubkey::find_program_address(&record_seeds_refs, program_id);
     
@@ -1984,13 +3602,9 @@ fn process_square_payment(
         return Err(FundError::PaymentRecordAlreadyExists.into());
     }
     
-    // Calculate amounts
-    let creator_amount_e6 = (args.amount_e6 as i128 * args.creator_share_bps as i128 / 10000) as i64;
-    let platform_amount_e6 = args.amount_e6.saturating_sub(creator_amount_e6);
-    
     // Create payment record account
     let record_space = SquarePaymentRecord::SIZE;
-    let record_lamports = rent.minimum_balance(record_space);
+    let record_lamports = Rent::get()?.minimum_balance(record_space);
     
     invoke_signed(
         &system_instruction::create_account(
@@ -2004,8 +3618,7 @@ fn process_square_payment(
         &[&[
             SQUARE_PAYMENT_RECORD_SEED,
             payer.key.as_ref(),
-            &args.content_id.to_le_bytes(),
-            &current_ts.to_le_bytes(),
+            &args.nonce.to_le_bytes(),
             &[record_bump],
         ]],
     )?;
@@ -2021,76 +3634,164 @@ fn process_square_payment(
         current_ts,
         args.subscription_period,
         &args.memo,
+        args.nonce,
         record_bump,
     );
     
-    record.serialize(&mut *payment_record.data.borrow_mut())?;
+    record.serialize(&mut &mut payment_record.data.borrow_mut()[..])?;
     
-    // Transfer creator share from payer vault to creator vault
-    if creator_amount_e6 > 0 {
-        invoke(
-            &spl_token::instruction::transfer(
-                &spl_token::id(),
-                payer_vault.key,
-                creator_vault.key,
-                payer.key,
-                &[],
-                creator_amount_e6 as u64,
-            )?,
-            &[
-                payer_vault.clone(),
-                creator_vault.clone(),
-                payer.clone(),
-                token_program.clone(),
-            ],
-        )?;
+    Ok(record)
+}
+
+/// The platform share may only go to the program's square fund vault, or to
+/// the Treasury vault when the Treasury is passed, in the source's mint
+fn assert_square_platform_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    square_fund_vault: &AccountInfo,
+    source_vault: &AccountInfo,
+) -> ProgramResult {
+    match find_treasury(program_id, accounts) {
+        Some(treasury) => {
+            load_treasury(program_id, treasury, square_fund_vault)?;
+        }
+        None => {
+            let vault_seeds = SquareFundStats::vault_seeds();
+            let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+            let (vault_key, _) = Pubkey::find_program_address(&vault_seeds_refs, program_id);
+            if *square_fund_vault.key != vault_key {
+                msg!("Square fund vault {} is not the program vault {}", square_fund_vault.key, vault_key);
+                return Err(FundError::SquareFundVaultMismatch.into());
+            }
+        }
     }
     
-    // Transfer platform share from payer vault to square fund vault
-    if platform_amount_e6 > 0 {
-        invoke(
-            &spl_token::instruction::transfer(
-                &spl_token::id(),
-                payer_vault.key,
-                square_fund_vault.key,
-                payer.key,
-                &[],
-                platform_amount_e6 as u64,
-            )?,
-            &[
-                payer_vault.clone(),
-                square_fund_vault.clone(),
-                payer.clone(),
-                token_program.clone(),
-            ],
-        )?;
+    assert_owned_by(square_fund_vault, &spl_token::id())?;
+    let vault = spl_token::state::Account::unpack(&square_fund_vault.data.borrow())?;
+    let source = spl_token::state::Account::unpack(&source_vault.data.borrow())?;
+    if vault.mint != source.mint {
+        msg!("Square fund vault mint {} does not match the payment mint {}", vault.mint, source.mint);
+        return Err(FundError::SquareFundVaultMismatch.into());
     }
-    
-    msg!("📝 SQUARE_PAYMENT_RECORD:");
-    msg!("  payer: {}", payer.key);
-    msg!("  creator: {}", args.creator);
-    msg!("  content_id: {}", args.content_id);
-    msg!("  payment_type: {:?}", payment_type);
-    msg!("  total_amount_e6: {}", args.amount_e6);
-    msg!("  creator_amount_e6: {}", creator_amount_e6);
-    msg!("  platform_amount_e6: {}", platform_amount_e6);
-    msg!("  creator_share_bps: {}", args.creator_share_bps);
-    msg!("  timestamp: {}", current_ts);
-    msg!("  record: {}", payment_record.key);
-    
     Ok(())
 }
 
-// =============================================================================
-// Referral Operations
-// =============================================================================
-
-/// Initialize the Referral system
-/// 
-/// Creates the global ReferralConfig PDA.
-fn process_initialize_referral(
+/// Pay a square payment's creator and platform shares out of `source_vault`
+///
+/// Used by SquarePayment (payer signs, no seeds) and by escrow release
+/// (SquareEscrow PDA signs). Returns the applied RevenueSplit and whether the
+/// platform share went to the Treasury.
+#[allow(clippy::too_many_arguments)]
+fn settle_square_payment<'a>(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
+    accounts: &[AccountInfo<'a>],
+    source_authority: &AccountInfo<'a>,
+    source_vault: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+    creator_vault: &AccountInfo<'a>,
+    square_fund_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    funder: &AccountInfo<'a>,
+    creator: &Pubkey,
+    payment_type: SquarePaymentType,
+    creator_amount_e6: i64,
+    platform_amount_e6: i64,
+    current_ts: i64,
+) -> Result<(Option<RevenueSplit>, bool), ProgramError> {
+    assert_square_platform_vault(program_id, accounts, square_fund_vault, source_vault)?;
+    
+    // Transfer creator share from source vault to creator vault (or its split recipients)
+    let revenue_split = load_active_revenue_split(program_id, accounts, creator)?;
+    if creator_amount_e6 > 0 {
+        if let Some(split) = revenue_split.as_ref() {
+            pay_revenue_split(accounts, split, source_authority, source_vault, token_program, signer_seeds, creator_amount_e6)?;
+        } else {
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    &spl_token::id(),
+                    source_vault.key,
+                    creator_vault.key,
+                    source_authority.key,
+                    &[],
+                    creator_amount_e6 as u64,
+                )?,
+                &[
+                    source_vault.clone(),
+                    creator_vault.clone(),
+                    source_authority.clone(),
+                    token_program.clone(),
+                ],
+                signer_seeds,
+            )?;
+        }
+    }
+    
+    // Platform share goes to the Treasury when it is passed (square_fund_vault = treasury vault),
+    // otherwise to the square fund vault
+    let treasury = find_treasury(program_id, accounts);
+    if platform_amount_e6 > 0 {
+        if let Some(treasury) = treasury {
+            route_to_treasury(
+                program_id,
+                treasury,
+                square_fund_vault,
+                source_vault,
+                source_authority,
+                token_program,
+                signer_seeds,
+                TreasurySource::SquarePlatform,
+                platform_amount_e6,
+            )?;
+        } else {
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    &spl_token::id(),
+                    source_vault.key,
+                    square_fund_vault.key,
+                    source_authority.key,
+                    &[],
+                    platform_amount_e6 as u64,
+                )?,
+                &[
+                    source_vault.clone(),
+                    square_fund_vault.clone(),
+                    source_authority.clone(),
+                    token_program.clone(),
+                ],
+                signer_seeds,
+            )?;
+        }
+    }
+    
+    record_square_stats(
+        program_id,
+        accounts,
+        funder,
+        square_fund_vault,
+        system_program,
+        creator,
+        payment_type,
+        creator_amount_e6.saturating_add(platform_amount_e6),
+        creator_amount_e6,
+        platform_amount_e6,
+        treasury.is_none(),
+        current_ts,
+    )?;
+    
+    Ok((revenue_split, treasury.is_some()))
+}
+
+// =============================================================================
+// Referral Operations
+// =============================================================================
+
+/// Initialize the Referral system
+/// 
+/// Creates the global ReferralConfig PDA.
+fn process_initialize_referral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
     args: InitializeReferralArgs,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -2154,10 +3855,11 @@ fn process_initialize_referral(
         current_ts,
     );
     
-    config.serialize(&mut *referral_config.data.borrow_mut())?;
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
     
     msg!("🎁 Referral system initialized");
     msg!("  Authority: {}", authority.key);
+    msg!("  Created at: {}", config.created_at);
     msg!("  Referrer share: {} bps ({}%)", args.referrer_share_bps, args.referrer_share_bps as f64 / 100.0);
     msg!("  Referee discount: {} bps ({}%)", args.referee_discount_bps, args.referee_discount_bps as f64 / 100.0);
     
@@ -2243,16 +3945,17 @@ fn process_create_referral_link(
         current_ts,
     );
     
-    link.serialize(&mut *referral_link.data.borrow_mut())?;
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
     
     // Update config stats
     config.total_referral_links = config.total_referral_links.saturating_add(1);
     config.last_update_ts = current_ts;
-    config.serialize(&mut *referral_config.data.borrow_mut())?;
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
     
     msg!("🔗 Referral link created");
     msg!("  Referrer: {}", referrer.key);
     msg!("  Code: {}", link.code_str());
+    msg!("  Created at: {}", link.created_at);
     
     Ok(())
 }
@@ -2309,28 +4012,32 @@ fn process_bind_referral(
         return Err(FundError::InvalidPDA.into());
     }
     
-    // Check if already bound
-    if !referral_binding.data_is_empty() {
-        return Err(FundError::AlreadyBoundToReferrer.into());
-    }
-    
-    // Create ReferralBinding account
-    let rent = Rent::get()?;
-    let space = ReferralBinding::SIZE;
-    let lamports = rent.minimum_balance(space);
     let current_ts = get_current_timestamp()?;
     
-    invoke_signed(
-        &system_instruction::create_account(
-            referee.key,
-            referral_binding.key,
-            lamports,
-            space as u64,
+    // Check if already bound (expired bindings may be replaced under the rebind policy)
+    let rebinding = !referral_binding.data_is_empty();
+    if rebinding {
+        assert_owned_by(referral_binding, program_id)?;
+        let existing = ReferralBinding::try_from_slice(&referral_binding.data.borrow())?;
+        if existing.discriminator != REFERRAL_BINDING_DISCRIMINATOR
+            || !config.can_rebind(&existing, current_ts)
+        {
+            return Err(FundError::AlreadyBoundToReferrer.into());
+        }
+        msg!("  Previous referrer: {}", existing.referrer);
+    } else {
+        // Create ReferralBinding account (rent sponsored by the reserve when available)
+        create_user_flow_pda(
             program_id,
-        ),
-        &[referee.clone(), referral_binding.clone(), system_program.clone()],
-        &[&[REFERRAL_BINDING_SEED, referee.key.as_ref(), &[binding_bump]]],
-    )?;
+            accounts,
+            referee,
+            referral_binding,
+            system_program,
+            ReferralBinding::SIZE,
+            &[REFERRAL_BINDING_SEED, referee.key.as_ref(), &[binding_bump]],
+            current_ts,
+        )?;
+    }
     
     // Initialize ReferralBinding
     let binding = ReferralBinding::new(
@@ -2341,21 +4048,24 @@ fn process_bind_referral(
         current_ts,
     );
     
-    binding.serialize(&mut *referral_binding.data.borrow_mut())?;
+    binding.serialize(&mut &mut referral_binding.data.borrow_mut()[..])?;
     
     // Update link stats
-    link.record_referral();
-    link.serialize(&mut *referral_link.data.borrow_mut())?;
+    link.record_referral(current_ts);
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
     
     // Update config stats
-    config.total_referred_users = config.total_referred_users.saturating_add(1);
+    if !rebinding {
+        config.total_referred_users = config.total_referred_users.saturating_add(1);
+    }
     config.last_update_ts = current_ts;
-    config.serialize(&mut *referral_config.data.borrow_mut())?;
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
     
-    msg!("🤝 Referral binding created");
+    msg!("🤝 Referral binding {}", if rebinding { "renewed" } else { "created" });
     msg!("  Referee: {}", referee.key);
     msg!("  Referrer: {}", link.referrer);
     msg!("  Link code: {}", link.code_str());
+    msg!("  Bound at: {}", binding.bound_at);
     
     Ok(())
 }
@@ -2401,6 +4111,13 @@ fn process_record_referral_trade(
     
     let current_ts = get_current_timestamp()?;
     
+    // Attribution has lapsed: no rewards, but don't fail the caller's trade
+    if binding.is_expired(config.reward_validity_secs, current_ts) {
+        msg!("⏳ Referral binding expired, no rewards recorded");
+        msg!("  Referee: {}", binding.referee);
+        return Ok(());
+    }
+    
     // Calculate rewards
     let (referrer_reward, referee_discount, _platform_income) = config.calculate_rewards(
         args.trade_fee_e6,
@@ -2415,21 +4132,22 @@ fn process_record_referral_trade(
         referee_discount,
         current_ts,
     );
-    binding.serialize(&mut *referral_binding.data.borrow_mut())?;
+    binding.serialize(&mut &mut referral_binding.data.borrow_mut()[..])?;
     
     // Update link stats
-    link.record_reward(referrer_reward, referee_discount, args.trade_volume_e6);
-    link.serialize(&mut *referral_link.data.borrow_mut())?;
+    link.record_reward(referrer_reward, referee_discount, args.trade_volume_e6, current_ts);
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
     
     // Update config stats
     config.record_reward(referrer_reward, referee_discount, args.trade_volume_e6, current_ts);
-    config.serialize(&mut *referral_config.data.borrow_mut())?;
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
     
     msg!("📊 REFERRAL_TRADE_RECORDED:");
     msg!("  Fee: {}", args.trade_fee_e6);
     msg!("  Volume: {}", args.trade_volume_e6);
     msg!("  Referrer reward: {}", referrer_reward);
     msg!("  Referee discount: {}", referee_discount);
+    msg!("  Timestamp: {}", current_ts);
     
     Ok(())
 }
@@ -2490,8 +4208,19 @@ fn process_update_referral_config(
         config.is_paused = is_paused;
     }
     
+    if let Some(reward_validity_secs) = args.reward_validity_secs {
+        if reward_validity_secs < 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        config.reward_validity_secs = reward_validity_secs;
+    }
+    
+    if let Some(rebind_policy) = args.rebind_policy {
+        config.rebind_policy = rebind_policy;
+    }
+    
     config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *referral_config.data.borrow_mut())?;
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
     
     msg!("⚙️ Referral config updated");
     msg!("  Referrer share: {} bps", config.referrer_share_bps);
@@ -2526,12 +4255,13 @@ fn process_deactivate_referral_link(
     }
     
     // Deactivate
-    link.is_active = false;
-    link.serialize(&mut *referral_link.data.borrow_mut())?;
+    link.deactivate(get_current_timestamp()?);
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
     
     msg!("🔒 Referral link deactivated");
     msg!("  Referrer: {}", referrer.key);
     msg!("  Code: {}", link.code_str());
+    msg!("  Deactivated at: {}", link.deactivated_at);
     
     Ok(())
 }
@@ -2578,7 +4308,8 @@ fn process_set_custom_referral_rates(
     
     link.custom_referrer_share_bps = args.custom_referrer_share_bps;
     link.custom_referee_discount_bps = args.custom_referee_discount_bps;
-    link.serialize(&mut *referral_link.data.borrow_mut())?;
+    link.last_update_ts = get_current_timestamp()?;
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
     
     msg!("⚙️ Custom referral rates set");
     msg!("  Link: {}", referral_link.key);
@@ -2712,7 +4443,7 @@ fn process_initialize_pm_fee_config(
     config_mut.prediction_market_maker_reward_share_bps = args.prediction_market_maker_reward_share_bps;
     config_mut.prediction_market_creator_share_bps = args.prediction_market_creator_share_bps;
     
-    config_mut.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config_mut.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_FEE_CONFIG_INITIALIZED");
     msg!("  Config: {}", pm_fee_config.key);
@@ -2764,7 +4495,8 @@ fn process_collect_pm_minting_fee(
     }
     
     // Calculate fee
-    let fee_e6 = config.calculate_prediction_market_minting_fee(args.prediction_market_minting_amount_e6);
+    let fees = config.prediction_market_category_fees(args.category)?;
+    let fee_e6 = (args.prediction_market_minting_amount_e6 as i128 * fees.minting_fee_bps as i128 / 10000) as i64;
     
     if fee_e6 <= 0 {
         msg!("No minting fee to collect for amount: {}", args.prediction_market_minting_amount_e6);
@@ -2792,7 +4524,7 @@ fn process_collect_pm_minting_fee(
     // Update stats
     let current_ts = get_current_timestamp()?;
     config.record_prediction_market_minting_fee(fee_e6, current_ts);
-    config.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_MINTING_FEE_COLLECTED");
     msg!("  Amount: {}", args.prediction_market_minting_amount_e6);
@@ -2835,7 +4567,8 @@ fn process_collect_pm_redemption_fee(
     }
     
     // Calculate fee
-    let fee_e6 = config.calculate_prediction_market_redemption_fee(args.prediction_market_redemption_amount_e6);
+    let fees = config.prediction_market_category_fees(args.category)?;
+    let fee_e6 = (args.prediction_market_redemption_amount_e6 as i128 * fees.redemption_fee_bps as i128 / 10000) as i64;
     
     if fee_e6 <= 0 {
         msg!("No redemption fee to collect for amount: {}", args.prediction_market_redemption_amount_e6);
@@ -2863,7 +4596,7 @@ fn process_collect_pm_redemption_fee(
     // Update stats
     let current_ts = get_current_timestamp()?;
     config.record_prediction_market_redemption_fee(fee_e6, current_ts);
-    config.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_REDEMPTION_FEE_COLLECTED");
     msg!("  Amount: {}", args.prediction_market_redemption_amount_e6);
@@ -2905,12 +4638,36 @@ fn process_collect_pm_trading_fee(
     }
     
     // Calculate fee based on taker/maker
+    let fees = config.prediction_market_category_fees(args.category)?;
     let fee_e6 = if args.is_taker {
-        config.calculate_prediction_market_taker_fee(args.prediction_market_trade_volume_e6)
+        (args.prediction_market_trade_volume_e6 as i128 * fees.trading_fee_taker_bps as i128 / 10000) as i64
     } else {
         config.calculate_prediction_market_maker_fee(args.prediction_market_trade_volume_e6)
     };
     
+    let current_ts = get_current_timestamp()?;
+    
+    // Record maker volume (even for zero-fee maker fills) and the epoch reward pool
+    if let Some(maker) = args.maker {
+        let maker_epoch = next_account_info(account_info_iter)?;
+        let maker_volume = next_account_info(account_info_iter)?;
+        let payer = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+        
+        let (_, maker_reward_e6, _) = config.distribute_prediction_market_fee(fee_e6.max(0));
+        record_pm_maker_volume(
+            program_id,
+            maker_epoch,
+            maker_volume,
+            payer,
+            system_program,
+            &maker,
+            args.prediction_market_trade_volume_e6,
+            maker_reward_e6,
+            current_ts,
+        )?;
+    }
+    
     if fee_e6 <= 0 {
         msg!("No trading fee to collect for volume: {}", args.prediction_market_trade_volume_e6);
         return Ok(());
@@ -2935,9 +4692,8 @@ fn process_collect_pm_trading_fee(
     )?;
     
     // Update stats
-    let current_ts = get_current_timestamp()?;
     config.record_prediction_market_trading_fee(fee_e6, current_ts);
-    config.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_TRADING_FEE_COLLECTED");
     msg!("  Volume: {}", args.prediction_market_trade_volume_e6);
@@ -2955,6 +4711,8 @@ fn process_collect_pm_trading_fee(
 /// 2. `[writable]` Prediction Market Fee Vault
 /// 3. `[writable]` Maker's Token Account
 /// 4. `[]` Token Program
+/// 5. `[writable]` PMMakerEpoch PDA
+/// 6. `[writable]` PMMakerVolume PDA
 fn process_distribute_pm_maker_reward(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -2967,10 +4725,17 @@ fn process_distribute_pm_maker_reward(
     let pm_fee_vault = next_account_info(account_info_iter)?;
     let maker_token_account = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
+    let maker_epoch = next_account_info(account_info_iter)?;
+    let maker_volume = next_account_info(account_info_iter)?;
     
     assert_signer(caller)?;
     assert_owned_by(pm_fee_config, program_id)?;
     
+    validate_accounts! {
+        maker_epoch: writable, owner = program_id;
+        maker_volume: writable, owner = program_id;
+    }
+    
     // Load and verify config
     let mut config = PredictionMarketFeeConfig::try_from_slice(&pm_fee_config.data.borrow())?;
     if config.discriminator != PREDICTION_MARKET_FEE_CONFIG_DISCRIMINATOR {
@@ -2987,9 +4752,34 @@ fn process_distribute_pm_maker_reward(
         return Err(FundError::PMFeePaused.into());
     }
     
-    let reward_e6 = args.prediction_market_maker_reward_e6;
+    // Reward is the maker's pro-rata share of the ended epoch's pool
+    let mut epoch = PMMakerEpoch::try_from_slice(&maker_epoch.data.borrow())?;
+    if epoch.discriminator != PM_MAKER_EPOCH_DISCRIMINATOR || epoch.epoch != args.epoch {
+        return Err(FundError::InvalidPDA.into());
+    }
+    
+    let mut volume = PMMakerVolume::try_from_slice(&maker_volume.data.borrow())?;
+    if volume.discriminator != PM_MAKER_VOLUME_DISCRIMINATOR || volume.epoch != args.epoch {
+        return Err(FundError::InvalidPDA.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    if !epoch.is_ended(current_ts) {
+        return Err(FundError::PMMakerEpochNotEnded.into());
+    }
+    
+    if volume.is_rewarded {
+        return Err(FundError::PMMakerRewardAlreadyDistributed.into());
+    }
+    
+    let maker_token = spl_token::state::Account::unpack(&maker_token_account.data.borrow())?;
+    if maker_token.owner != volume.maker {
+        return Err(FundError::InvalidAccountOwner.into());
+    }
+    
+    let reward_e6 = epoch.reward_for(volume.volume_e6);
     if reward_e6 <= 0 {
-        msg!("Invalid reward amount: {}", reward_e6);
+        msg!("No maker reward for volume: {}", volume.volume_e6);
         return Err(FundError::InvalidAmount.into());
     }
     
@@ -3025,12 +4815,20 @@ fn process_distribute_pm_maker_reward(
     )?;
     
     // Update stats
-    let current_ts = get_current_timestamp()?;
+    volume.is_rewarded = true;
+    volume.reward_paid_e6 = reward_e6;
+    volume.serialize(&mut &mut maker_volume.data.borrow_mut()[..])?;
+    
+    epoch.distributed_e6 = epoch.distributed_e6.saturating_add(reward_e6);
+    epoch.serialize(&mut &mut maker_epoch.data.borrow_mut()[..])?;
+    
     config.record_prediction_market_maker_reward(reward_e6, current_ts);
-    config.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_MAKER_REWARD_DISTRIBUTED");
-    msg!("  Maker: {}", maker_token_account.key);
+    msg!("  Maker: {}", volume.maker);
+    msg!("  Epoch: {}", args.epoch);
+    msg!("  Volume: {} / {}", volume.volume_e6, epoch.total_maker_volume_e6);
     msg!("  Reward: {}", reward_e6);
     msg!("  Total maker rewards: {}", config.prediction_market_total_maker_rewards_e6);
     
@@ -3119,7 +4917,7 @@ fn process_distribute_pm_creator_reward(
     // Update stats
     let current_ts = get_current_timestamp()?;
     config.record_prediction_market_creator_reward(reward_e6, current_ts);
-    config.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_CREATOR_REWARD_DISTRIBUTED");
     msg!("  Market ID: {}", args.prediction_market_id);
@@ -3179,7 +4977,7 @@ fn process_update_pm_fee_config(
     }
     
     config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_FEE_CONFIG_UPDATED");
     msg!("  Minting fee: {} bps", config.prediction_market_minting_fee_bps);
@@ -3216,7 +5014,7 @@ fn process_set_pm_fee_paused(
     
     config.is_paused = args.prediction_market_fee_paused;
     config.last_update_ts = get_current_timestamp()?;
-    config.serialize(&mut *pm_fee_config.data.borrow_mut())?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
     
     msg!("✅ PM_FEE_PAUSED_STATE: {}", args.prediction_market_fee_paused);
     
@@ -3239,12 +5037,18 @@ fn verify_fund_relayer(config: &FundConfig, relayer: &Pubkey) -> Result<(), Prog
 }
 
 /// 验证 Relayer 并检查限额
+///
+/// Returns false when the request is over the limits and was counted in the
+/// relayer's RelayerStats; the caller then ends without effect so the count
+/// persists. Without RelayerStats it fails with RelayerLimitExceeded.
 fn verify_and_check_relayer_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
     config: &mut FundConfig,
     relayer: &Pubkey,
     amount_e6: i64,
     current_ts: i64,
-) -> Result<(), ProgramError> {
+) -> Result<bool, ProgramError> {
     // First verify the relayer is authorized
     verify_fund_relayer(config, relayer)?;
     
@@ -3255,9 +5059,122 @@ fn verify_and_check_relayer_limits(
         msg!("  Single tx limit: {}", config.relayer_limits.single_tx_limit_e6);
         msg!("  Daily limit: {}", config.relayer_limits.daily_limit_e6);
         msg!("  Daily used: {}", config.relayer_limits.daily_used_e6);
-        return Err(FundError::RelayerLimitExceeded.into());
+        
+        let Some(stats_account) = find_relayer_stats(program_id, accounts, relayer) else {
+            return Err(FundError::RelayerLimitExceeded.into());
+        };
+        let mut stats = load_relayer_stats(program_id, stats_account)?;
+        stats.record_rejection(current_ts);
+        stats.serialize(&mut &mut stats_account.data.borrow_mut()[..])?;
+        
+        msg!("🚫 RELAYER_LIMIT_REJECTED");
+        msg!("  Relayer: {}", relayer);
+        msg!("  Rejected total: {}", stats.rejected_over_limit_count);
+        return Ok(false);
+    }
+    
+    Ok(true)
+}
+
+/// Relayer's RelayerStats PDA, if passed among `accounts`
+fn find_relayer_stats<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+    relayer: &Pubkey,
+) -> Option<&'b AccountInfo<'a>> {
+    let seeds = RelayerStats::seeds(relayer);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    accounts.iter().find(|a| *a.key == expected)
+}
+
+/// Load a RelayerStats for update
+fn load_relayer_stats(
+    program_id: &Pubkey,
+    stats_account: &AccountInfo,
+) -> Result<RelayerStats, ProgramError> {
+    validate_accounts! {
+        stats_account: writable, owner = program_id;
+    }
+    let stats = RelayerStats::try_from_slice(&stats_account.data.borrow())?;
+    if stats.discriminator != RELAYER_STATS_DISCRIMINATOR {
+        return Err(FundError::PDAMismatch.into());
+    }
+    Ok(stats)
+}
+
+/// Count an accepted Relayer* operation in the relayer's RelayerStats (if passed)
+fn record_relayer_operation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    relayer: &Pubkey,
+    volume_e6: u64,
+    current_ts: i64,
+) -> ProgramResult {
+    if let Some(stats_account) = find_relayer_stats(program_id, accounts, relayer) {
+        let mut stats = load_relayer_stats(program_id, stats_account)?;
+        stats.record_operation(volume_e6, current_ts);
+        stats.serialize(&mut &mut stats_account.data.borrow_mut()[..])?;
+    }
+    Ok(())
+}
+
+/// 校验并消费用户的 Relayer nonce (首次使用时创建 RelayerNonce，由 Relayer 支付租金)
+fn consume_relayer_nonce<'a>(
+    program_id: &Pubkey,
+    relayer: &AccountInfo<'a>,
+    relayer_nonce: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    user: &Pubkey,
+    nonce: u64,
+    current_ts: i64,
+) -> ProgramResult {
+    validate_accounts! {
+        relayer_nonce: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let nonce_seeds = RelayerNonce::seeds(user);
+    let nonce_seeds_refs: Vec<&[u8]> = nonce_seeds.iter().map(|s| s.as_slice()).collect();
+    let nonce_bump = assert_pda(relayer_nonce, &nonce_seeds_refs, program_id)?;
+    
+    let mut state = if relayer_nonce.data_is_empty() {
+        validate_accounts! {
+            relayer: writable;
+        }
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                relayer.key,
+                relayer_nonce.key,
+                Rent::get()?.minimum_balance(RelayerNonce::SIZE),
+                RelayerNonce::SIZE as u64,
+                program_id,
+            ),
+            &[relayer.clone(), relayer_nonce.clone(), system_program.clone()],
+            &[&[RELAYER_NONCE_SEED, user.as_ref(), &[nonce_bump]]],
+        )?;
+        
+        RelayerNonce::new(*user, nonce_bump)
+    } else {
+        assert_owned_by(relayer_nonce, program_id)?;
+        let state = RelayerNonce::try_from_slice(&relayer_nonce.data.borrow())?;
+        if state.discriminator != RELAYER_NONCE_DISCRIMINATOR || state.user != *user {
+            return Err(FundError::PDAMismatch.into());
+        }
+        state
+    };
+    
+    if !state.consume(nonce, current_ts) {
+        msg!("❌ Relayer nonce replay");
+        msg!("  User: {}", user);
+        msg!("  Nonce: {}", nonce);
+        msg!("  Last accepted: {}", state.last_nonce);
+        return Err(FundError::RelayerNonceReplay.into());
     }
     
+    state.serialize(&mut &mut relayer_nonce.data.borrow_mut()[..])?;
+    
     Ok(())
 }
 
@@ -3271,6 +5188,7 @@ fn process_relayer_deposit_to_fund(
     
     let relayer = next_account_info(account_info_iter)?;
     assert_signer(relayer)?;
+    validate_memo(args.memo.as_deref())?;
     
     let fund_config = next_account_info(account_info_iter)?;
     let fund = next_account_info(account_info_iter)?;
@@ -3282,20 +5200,36 @@ fn process_relayer_deposit_to_fund(
     let _vault_config = next_account_info(account_info_iter)?;
     let _vault_program = next_account_info(account_info_iter)?;
     let _token_program = next_account_info(account_info_iter)?;
-    let _system_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let relayer_nonce = next_account_info(account_info_iter)?;
+    
+    assert_owned_by(fund_config, program_id)?;
+    assert_owned_by(fund, program_id)?;
     
     // Load and validate FundConfig
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
-    verify_fund_relayer(&config, relayer.key)?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    let current_ts = get_current_timestamp()?;
+    if !verify_and_check_relayer_limits(program_id, accounts, &mut config, relayer.key, args.amount as i64, current_ts)? {
+        return Ok(());
+    }
+    consume_relayer_nonce(program_id, relayer, relayer_nonce, system_program, &args.user_wallet, args.nonce, current_ts)?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+    record_relayer_operation(program_id, accounts, relayer.key, args.amount, current_ts)?;
     
     // Load Fund
-    let fund_data = Fund::try_from_slice(&fund.data.borrow())?;
+    let fund_data = Fund::unpack_versioned(&fund.data.borrow())?;
     
     // TODO: Implement actual deposit logic via Vault CPI
     msg!("✅ RelayerDepositToFund");
     msg!("  User: {}", args.user_wallet);
     msg!("  Fund: {}", fund_data.name_str());
     msg!("  Amount: {}", args.amount);
+    if let Some(campaign_id) = args.campaign_id {
+        msg!("  Campaign: {}", campaign_id);
+    }
+    if let Some(memo) = args.memo.as_deref() {
+        msg!("  Memo: {}", truncate_memo(memo, MEMO_EVENT_LEN));
+    }
     
     Ok(())
 }
@@ -3310,16 +5244,37 @@ fn process_relayer_redeem_from_fund(
     
     let relayer = next_account_info(account_info_iter)?;
     assert_signer(relayer)?;
+    validate_memo(args.memo.as_deref())?;
     
     let fund_config = next_account_info(account_info_iter)?;
+    let relayer_nonce = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let payout_destination = next_account_info(account_info_iter)?;
+    assert_owned_by(fund_config, program_id)?;
+    assert_owned_by(lp_position, program_id)?;
     
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     verify_fund_relayer(&config, relayer.key)?;
+    let current_ts = get_current_timestamp()?;
+    consume_relayer_nonce(program_id, relayer, relayer_nonce, system_program, &args.user_wallet, args.nonce, current_ts)?;
+    record_relayer_operation(program_id, accounts, relayer.key, 0, current_ts)?;
+    
+    let position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.investor != args.user_wallet {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    if position.payout_allowlist_enabled {
+        assert_payout_allowed(program_id, lp_position, payout_destination, accounts, current_ts)?;
+    }
     
     // TODO: Implement actual redemption logic
     msg!("✅ RelayerRedeemFromFund");
     msg!("  User: {}", args.user_wallet);
     msg!("  Shares: {}", args.shares);
+    if let Some(memo) = args.memo.as_deref() {
+        msg!("  Memo: {}", truncate_memo(memo, MEMO_EVENT_LEN));
+    }
     
     Ok(())
 }
@@ -3336,9 +5291,26 @@ fn process_relayer_redeem_from_insurance_fund(
     assert_signer(relayer)?;
     
     let fund_config = next_account_info(account_info_iter)?;
+    let relayer_nonce = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let payout_destination = next_account_info(account_info_iter)?;
+    assert_owned_by(fund_config, program_id)?;
+    assert_owned_by(lp_position, program_id)?;
     
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     verify_fund_relayer(&config, relayer.key)?;
+    let current_ts = get_current_timestamp()?;
+    consume_relayer_nonce(program_id, relayer, relayer_nonce, system_program, &args.user_wallet, args.nonce, current_ts)?;
+    record_relayer_operation(program_id, accounts, relayer.key, 0, current_ts)?;
+    
+    let position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.investor != args.user_wallet {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    if position.payout_allowlist_enabled {
+        assert_payout_allowed(program_id, lp_position, payout_destination, accounts, current_ts)?;
+    }
     
     // TODO: Implement with special rules for Insurance Fund
     msg!("✅ RelayerRedeemFromInsuranceFund");
@@ -3360,9 +5332,15 @@ fn process_relayer_square_payment(
     assert_signer(relayer)?;
     
     let fund_config = next_account_info(account_info_iter)?;
+    let relayer_nonce = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    assert_owned_by(fund_config, program_id)?;
     
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     verify_fund_relayer(&config, relayer.key)?;
+    let current_ts = get_current_timestamp()?;
+    consume_relayer_nonce(program_id, relayer, relayer_nonce, system_program, &args.payer_wallet, args.nonce, current_ts)?;
+    record_relayer_operation(program_id, accounts, relayer.key, args.amount_e6.max(0) as u64, current_ts)?;
     
     // TODO: Implement actual payment processing
     msg!("✅ RelayerSquarePayment");
@@ -3386,9 +5364,15 @@ fn process_relayer_bind_referral(
     assert_signer(relayer)?;
     
     let fund_config = next_account_info(account_info_iter)?;
+    let relayer_nonce = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    assert_owned_by(fund_config, program_id)?;
     
-    let config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     verify_fund_relayer(&config, relayer.key)?;
+    let current_ts = get_current_timestamp()?;
+    consume_relayer_nonce(program_id, relayer, relayer_nonce, system_program, &args.user_wallet, args.nonce, current_ts)?;
+    record_relayer_operation(program_id, accounts, relayer.key, 0, current_ts)?;
     
     // TODO: Implement actual referral binding
     msg!("✅ RelayerBindReferral");
@@ -3416,7 +5400,7 @@ fn process_add_relayer(
     assert_signer(authority)?;
     assert_owned_by(fund_config, program_id)?;
     
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     
     if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::FundNotInitialized.into());
@@ -3428,11 +5412,9 @@ fn process_add_relayer(
     }
     
     // Add relayer
-    if config.add_relayer(args.relayer).is_err() {
-        return Err(FundError::MaxRelayersReached.into());
-    }
+    config.add_relayer(args.relayer)?;
     
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
     
     msg!("✅ RELAYER_ADDED");
     msg!("  Relayer: {}", args.relayer);
@@ -3455,7 +5437,7 @@ fn process_remove_relayer(
     assert_signer(authority)?;
     assert_owned_by(fund_config, program_id)?;
     
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     
     if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::FundNotInitialized.into());
@@ -3471,7 +5453,7 @@ fn process_remove_relayer(
         return Err(FundError::RelayerNotFound.into());
     }
     
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
     
     msg!("✅ RELAYER_REMOVED");
     msg!("  Relayer: {}", args.relayer);
@@ -3494,7 +5476,7 @@ fn process_update_relayer_limits(
     assert_signer(authority)?;
     assert_owned_by(fund_config, program_id)?;
     
-    let mut config = FundConfig::try_from_slice(&fund_config.data.borrow())?;
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
     
     if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
         return Err(FundError::FundNotInitialized.into());
@@ -3513,7 +5495,7 @@ fn process_update_relayer_limits(
         config.relayer_limits.daily_limit_e6 = daily_limit;
     }
     
-    config.serialize(&mut *fund_config.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
     
     msg!("✅ RELAYER_LIMITS_UPDATED");
     msg!("  Single tx limit: {} e6", config.relayer_limits.single_tx_limit_e6);
@@ -3635,11 +5617,12 @@ fn process_initialize_spot_fee_config(
         current_ts,
     );
     
-    spot_fee_config.serialize(&mut *spot_fee_config_info.data.borrow_mut())?;
+    spot_fee_config.serialize(&mut &mut spot_fee_config_info.data.borrow_mut()[..])?;
     
     msg!("✅ SpotTradingFeeConfig initialized");
     msg!("  Vault: {}", spot_fee_vault_info.key);
     msg!("  Authorized Caller: {}", args.authorized_caller);
+    msg!("  Created at: {}", spot_fee_config.created_at);
     
     Ok(())
 }
@@ -3691,7 +5674,7 @@ fn process_collect_spot_trading_fee(
         config.record_maker_fee(fee_e6, current_ts);
     }
     
-    config.serialize(&mut *spot_fee_config_info.data.borrow_mut())?;
+    config.serialize(&mut &mut spot_fee_config_info.data.borrow_mut()[..])?;
     
     msg!("✅ SpotTradingFee collected: volume={}, fee={}, is_taker={}", 
          args.volume_e6, fee_e6, args.is_taker);
@@ -3762,7 +5745,7 @@ fn process_distribute_spot_maker_reward(
     
     let current_ts = Clock::get()?.unix_timestamp;
     config.record_maker_reward(args.reward_e6, current_ts);
-    config.serialize(&mut *spot_fee_config_info.data.borrow_mut())?;
+    config.serialize(&mut &mut spot_fee_config_info.data.borrow_mut()[..])?;
     
     msg!("✅ SpotMakerReward distributed: maker={}, amount={}", args.maker, args.reward_e6);
     
@@ -3803,7 +5786,7 @@ fn process_update_spot_fee_config(
     if let Some(v) = args.maker_reward_share_bps { config.maker_reward_share_bps = v; }
     
     config.last_update_ts = Clock::get()?.unix_timestamp;
-    config.serialize(&mut *spot_fee_config_info.data.borrow_mut())?;
+    config.serialize(&mut &mut spot_fee_config_info.data.borrow_mut()[..])?;
     
     msg!("✅ SpotTradingFeeConfig updated");
     msg!("  Taker fee: {} bps", config.taker_fee_bps);
@@ -3811,3 +5794,10764 @@ fn process_update_spot_fee_config(
     
     Ok(())
 }
+
+// =============================================================================
+// Deposit Receipt Instructions
+// =============================================================================
+
+/// Enable/disable deposit receipt NFTs for a fund (manager only)
+fn process_set_deposit_receipts(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetDepositReceiptsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        manager: signer;
+        fund_account: writable, owner = program_id;
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+
+    fund.deposit_receipts_enabled = args.enabled;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+
+    msg!("✅ DEPOSIT_RECEIPTS_UPDATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Enabled: {}", args.enabled);
+
+    Ok(())
+}
+
+/// Mint (or refresh) the receipt NFT for an LP position
+///
+/// The receipt mint and token account are created on first use and paid by
+/// the investor. A new NFT is only minted when the token account is empty,
+/// so repeated deposits refresh the DepositReceipt record in place.
+#[allow(clippy::too_many_arguments)]
+fn issue_deposit_receipt<'a>(
+    program_id: &Pubkey,
+    investor: &AccountInfo<'a>,
+    fund_account: &AccountInfo<'a>,
+    lp_position: &AccountInfo<'a>,
+    deposit_receipt: &AccountInfo<'a>,
+    receipt_mint: &AccountInfo<'a>,
+    receipt_token: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    fund_signer_seeds: &[&[u8]],
+    amount_e6: i64,
+    nav_e6: i64,
+    current_ts: i64,
+) -> ProgramResult {
+    let receipt_seeds = DepositReceipt::seeds(lp_position.key);
+    let receipt_seeds_refs: Vec<&[u8]> = receipt_seeds.iter().map(|s| s.as_slice()).collect();
+    let receipt_bump = assert_pda(deposit_receipt, &receipt_seeds_refs, program_id)?;
+
+    let mint_seeds = DepositReceipt::mint_seeds(lp_position.key);
+    let mint_seeds_refs: Vec<&[u8]> = mint_seeds.iter().map(|s| s.as_slice()).collect();
+    let mint_bump = assert_pda(receipt_mint, &mint_seeds_refs, program_id)?;
+
+    let token_seeds = DepositReceipt::token_seeds(lp_position.key);
+    let token_seeds_refs: Vec<&[u8]> = token_seeds.iter().map(|s| s.as_slice()).collect();
+    let token_bump = assert_pda(receipt_token, &token_seeds_refs, program_id)?;
+
+    let rent = Rent::get()?;
+
+    // Create receipt mint (0 decimals, Fund PDA as authority)
+    if receipt_mint.data_is_empty() {
+        let mint_space = spl_token::state::Mint::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                receipt_mint.key,
+                rent.minimum_balance(mint_space),
+                mint_space as u64,
+                &spl_token::id(),
+            ),
+            &[investor.clone(), receipt_mint.clone(), system_program.clone()],
+            &[&[RECEIPT_MINT_SEED, lp_position.key.as_ref(), &[mint_bump]]],
+        )?;
+
+        invoke(
+            &spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                receipt_mint.key,
+                fund_account.key,
+                Some(fund_account.key),
+                0,
+            )?,
+            &[receipt_mint.clone(), token_program.clone()],
+        )?;
+    }
+
+    // Create receipt token account (owned by the investor)
+    if receipt_token.data_is_empty() {
+        let token_space = spl_token::state::Account::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                receipt_token.key,
+                rent.minimum_balance(token_space),
+                token_space as u64,
+                &spl_token::id(),
+            ),
+            &[investor.clone(), receipt_token.clone(), system_program.clone()],
+            &[&[RECEIPT_TOKEN_SEED, lp_position.key.as_ref(), &[token_bump]]],
+        )?;
+
+        invoke(
+            &spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                receipt_token.key,
+                receipt_mint.key,
+                investor.key,
+            )?,
+            &[receipt_token.clone(), receipt_mint.clone(), token_program.clone()],
+        )?;
+    }
+
+    // Mint the NFT if the investor doesn't currently hold it
+    let token_state = spl_token::state::Account::unpack(&receipt_token.data.borrow())?;
+    if token_state.amount == 0 {
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                &spl_token::id(),
+                receipt_mint.key,
+                receipt_token.key,
+                fund_account.key,
+                &[],
+                1,
+            )?,
+            &[receipt_mint.clone(), receipt_token.clone(), fund_account.clone(), token_program.clone()],
+            &[fund_signer_seeds],
+        )?;
+    }
+
+    // Create or update the receipt record
+    if deposit_receipt.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                deposit_receipt.key,
+                rent.minimum_balance(DepositReceipt::SIZE),
+                DepositReceipt::SIZE as u64,
+                program_id,
+            ),
+            &[investor.clone(), deposit_receipt.clone(), system_program.clone()],
+            &[&[DEPOSIT_RECEIPT_SEED, lp_position.key.as_ref(), &[receipt_bump]]],
+        )?;
+
+        let receipt = DepositReceipt::new(
+            *fund_account.key,
+            *investor.key,
+            *receipt_mint.key,
+            amount_e6,
+            nav_e6,
+            current_ts,
+            receipt_bump,
+        );
+        receipt.serialize(&mut &mut deposit_receipt.data.borrow_mut()[..])?;
+    } else {
+        let mut receipt = DepositReceipt::try_from_slice(&deposit_receipt.data.borrow())?;
+        if receipt.discriminator != DEPOSIT_RECEIPT_DISCRIMINATOR {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        receipt.record_deposit(amount_e6, nav_e6, current_ts);
+        receipt.serialize(&mut &mut deposit_receipt.data.borrow_mut()[..])?;
+    }
+
+    msg!("🧾 DEPOSIT_RECEIPT_ISSUED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Investor: {}", investor.key);
+    msg!("  Mint: {}", receipt_mint.key);
+    msg!("  Amount: {} NAV: {}", amount_e6, nav_e6);
+
+    Ok(())
+}
+
+/// Burn an emptied position's receipt NFT. Required when the position holds
+/// one; positions from before `has_receipt` burn it if the accounts are passed.
+fn close_out_deposit_receipt<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    investor: &AccountInfo<'a>,
+    lp_position: &AccountInfo<'a>,
+    position: &mut LPPosition,
+    token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let find = |seeds: Vec<Vec<u8>>| {
+        let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+        let (expected, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+        accounts.iter().find(|a| *a.key == expected)
+    };
+    let receipt_accounts = (
+        find(DepositReceipt::seeds(lp_position.key)),
+        find(DepositReceipt::mint_seeds(lp_position.key)),
+        find(DepositReceipt::token_seeds(lp_position.key)),
+    );
+
+    match receipt_accounts {
+        (Some(deposit_receipt), Some(receipt_mint), Some(receipt_token)) => {
+            burn_deposit_receipt(
+                program_id,
+                investor,
+                lp_position,
+                deposit_receipt,
+                receipt_mint,
+                receipt_token,
+                token_program,
+            )?;
+        }
+        _ if position.has_receipt => return Err(FundError::DepositReceiptMissing.into()),
+        _ => {}
+    }
+
+    position.has_receipt = false;
+    Ok(())
+}
+
+/// Burn the receipt NFT and close its record after a full redemption
+fn burn_deposit_receipt<'a>(
+    program_id: &Pubkey,
+    investor: &AccountInfo<'a>,
+    lp_position: &AccountInfo<'a>,
+    deposit_receipt: &AccountInfo<'a>,
+    receipt_mint: &AccountInfo<'a>,
+    receipt_token: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let receipt_seeds = DepositReceipt::seeds(lp_position.key);
+    let receipt_seeds_refs: Vec<&[u8]> = receipt_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(deposit_receipt, &receipt_seeds_refs, program_id)?;
+
+    let mint_seeds = DepositReceipt::mint_seeds(lp_position.key);
+    let mint_seeds_refs: Vec<&[u8]> = mint_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(receipt_mint, &mint_seeds_refs, program_id)?;
+
+    let token_seeds = DepositReceipt::token_seeds(lp_position.key);
+    let token_seeds_refs: Vec<&[u8]> = token_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(receipt_token, &token_seeds_refs, program_id)?;
+
+    if !receipt_token.data_is_empty() {
+        let token_state = spl_token::state::Account::unpack(&receipt_token.data.borrow())?;
+        if token_state.amount > 0 {
+            invoke(
+                &spl_token::instruction::burn(
+                    &spl_token::id(),
+                    receipt_token.key,
+                    receipt_mint.key,
+                    investor.key,
+                    &[],
+                    token_state.amount,
+                )?,
+                &[receipt_token.clone(), receipt_mint.clone(), investor.clone(), token_program.clone()],
+            )?;
+        }
+
+        // Reclaim the token account rent
+        invoke(
+            &spl_token::instruction::close_account(
+                &spl_token::id(),
+                receipt_token.key,
+                investor.key,
+                investor.key,
+                &[],
+            )?,
+            &[receipt_token.clone(), investor.clone(), token_program.clone()],
+        )?;
+    }
+
+    if !deposit_receipt.data_is_empty() {
+        assert_owned_by(deposit_receipt, program_id)?;
+        close_program_account(deposit_receipt, investor)?;
+    }
+
+    msg!("🔥 DEPOSIT_RECEIPT_BURNED");
+    msg!("  Investor: {}", investor.key);
+    msg!("  Mint: {}", receipt_mint.key);
+
+    Ok(())
+}
+
+// =============================================================================
+// LP Beneficiary Instructions
+// =============================================================================
+
+/// Designate or clear a transfer-on-death beneficiary for an LP position
+fn process_set_lp_beneficiary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetLPBeneficiaryArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let investor = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let investor_shares = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        investor: signer;
+        fund_account: owner = program_id;
+        lp_position: writable, owner = program_id;
+        investor_shares: writable;
+        token_program: key = &spl_token::id();
+    }
+
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+
+    if position.discriminator != LP_POSITION_DISCRIMINATOR
+        || position.fund != *fund_account.key
+        || position.investor != *investor.key
+    {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+
+    if args.beneficiary == Some(*investor.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    let shares_account = spl_token::state::Account::unpack(&investor_shares.data.borrow())?;
+    if shares_account.owner != *investor.key {
+        return Err(FundError::InvalidAccountOwner.into());
+    }
+    if shares_account.mint != fund.share_mint {
+        return Err(FundError::InvalidMint.into());
+    }
+
+    match args.beneficiary {
+        Some(beneficiary) => {
+            // Let the Fund PDA move exactly the position's shares on a later claim
+            // (call again after adding shares to refresh the approval)
+            invoke(
+                &spl_token::instruction::approve(
+                    &spl_token::id(),
+                    investor_shares.key,
+                    fund_account.key,
+                    investor.key,
+                    &[],
+                    position.shares,
+                )?,
+                &[investor_shares.clone(), fund_account.clone(), investor.clone(), token_program.clone()],
+            )?;
+            position.beneficiary = beneficiary;
+        }
+        None => {
+            invoke(
+                &spl_token::instruction::revoke(
+                    &spl_token::id(),
+                    investor_shares.key,
+                    investor.key,
+                    &[],
+                )?,
+                &[investor_shares.clone(), investor.clone(), token_program.clone()],
+            )?;
+            position.beneficiary = Pubkey::default();
+        }
+    }
+
+    position.last_update_ts = get_current_timestamp()?;
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+
+    msg!("✅ LP_BENEFICIARY_UPDATED");
+    msg!("  Investor: {}", investor.key);
+    msg!("  Beneficiary: {}", position.beneficiary);
+
+    Ok(())
+}
+
+/// Claim an inactive LP position as its designated beneficiary
+fn process_claim_as_beneficiary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let beneficiary = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let investor_position = next_account_info(account_info_iter)?;
+    let beneficiary_position = next_account_info(account_info_iter)?;
+    let investor_shares = next_account_info(account_info_iter)?;
+    let beneficiary_shares = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        beneficiary: signer;
+        fund_config: owner = program_id;
+        fund_account: writable, owner = program_id;
+        investor_position: writable, owner = program_id;
+        beneficiary_position: writable;
+        investor_shares: writable;
+        beneficiary_shares: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if fund.distribution_in_progress {
+        return Err(FundError::DistributionInProgress.into());
+    }
+
+    let mut position = LPPosition::unpack_versioned(&investor_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.fund != *fund_account.key {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+
+    if position.beneficiary != *beneficiary.key {
+        return Err(FundError::NotBeneficiary.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+    if !position.can_beneficiary_claim(config.beneficiary_inactivity_period(), current_ts) {
+        return Err(FundError::BeneficiaryInactivityNotMet.into());
+    }
+
+    if position.is_empty() {
+        return Err(FundError::InsufficientShares.into());
+    }
+
+    if position.locked_shares > 0 {
+        return Err(FundError::SharesLocked.into());
+    }
+
+    let investor = position.investor;
+    let deposit_nav_e6 = position.deposit_nav_e6;
+    let (shares, net_invested_e6) = position.transfer_out(current_ts);
+
+    // The source must be the investor's own share account, still delegated to the Fund PDA
+    let source = spl_token::state::Account::unpack(&investor_shares.data.borrow())?;
+    if source.owner != investor {
+        return Err(FundError::InvalidAccountOwner.into());
+    }
+    if source.mint != fund.share_mint {
+        return Err(FundError::InvalidMint.into());
+    }
+    if source.delegate != COption::Some(*fund_account.key) || source.delegated_amount < shares {
+        return Err(FundError::BeneficiaryApprovalMissing.into());
+    }
+    let destination = spl_token::state::Account::unpack(&beneficiary_shares.data.borrow())?;
+    if destination.owner != *beneficiary.key {
+        return Err(FundError::InvalidAccountOwner.into());
+    }
+    if destination.mint != fund.share_mint {
+        return Err(FundError::InvalidMint.into());
+    }
+
+    // Move share tokens using the Fund PDA's delegate approval
+    let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
+    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
+    let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            investor_shares.key,
+            beneficiary_shares.key,
+            fund_account.key,
+            &[],
+            shares,
+        )?,
+        &[investor_shares.clone(), beneficiary_shares.clone(), fund_account.clone(), token_program.clone()],
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]]],
+    )?;
+
+    // Create or top up the beneficiary's LP position
+    let lp_seeds = LPPosition::seeds(fund_account.key, beneficiary.key);
+    let lp_seeds_refs: Vec<&[u8]> = lp_seeds.iter().map(|s| s.as_slice()).collect();
+    let lp_bump = assert_pda(beneficiary_position, &lp_seeds_refs, program_id)?;
+
+    if beneficiary_position.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                beneficiary.key,
+                beneficiary_position.key,
+                rent.minimum_balance(LPPosition::SIZE),
+                LPPosition::SIZE as u64,
+                program_id,
+            ),
+            &[beneficiary.clone(), beneficiary_position.clone(), system_program.clone()],
+            &[&[LP_POSITION_SEED, fund_account.key.as_ref(), beneficiary.key.as_ref(), &[lp_bump]]],
+        )?;
+
+        let mut new_position = LPPosition::new(
+            *fund_account.key,
+            *beneficiary.key,
+            shares,
+            deposit_nav_e6,
+            net_invested_e6,
+            current_ts,
+            lp_bump,
+        );
+        lp_index_add(program_id, accounts, beneficiary, system_program, fund_account.key, &mut fund, beneficiary_position, &mut new_position, current_ts)?;
+        new_position.pack_versioned(&mut beneficiary_position.data.borrow_mut())?;
+    } else {
+        let mut existing = LPPosition::unpack_versioned(&beneficiary_position.data.borrow())?;
+        if existing.discriminator != LP_POSITION_DISCRIMINATOR || existing.investor != *beneficiary.key {
+            return Err(FundError::LPPositionNotFound.into());
+        }
+        if existing.nft_wrapped {
+            return Err(FundError::PositionNFTWrapped.into());
+        }
+        let reactivated = existing.is_empty();
+        if reactivated {
+            fund.stats.lp_count = fund.stats.lp_count.saturating_add(1);
+        }
+        existing.add_shares(shares, net_invested_e6, fund.stats.current_nav_e6, current_ts)?;
+        if reactivated {
+            lp_index_add(program_id, accounts, beneficiary, system_program, fund_account.key, &mut fund, beneficiary_position, &mut existing, current_ts)?;
+        }
+        existing.pack_versioned(&mut beneficiary_position.data.borrow_mut())?;
+
+        // Two positions merged into one
+        fund.stats.lp_count = fund.stats.lp_count.saturating_sub(1);
+    }
+
+    lp_index_remove(program_id, accounts, fund_account.key, investor_position, &mut position)?;
+    position.pack_versioned(&mut investor_position.data.borrow_mut())?;
+
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+
+    msg!("✅ LP_POSITION_CLAIMED_BY_BENEFICIARY");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Investor: {}", investor);
+    msg!("  Beneficiary: {}", beneficiary.key);
+    msg!("  Shares: {}", shares);
+
+    Ok(())
+}
+
+/// Set the beneficiary inactivity period (Admin only)
+fn process_set_beneficiary_inactivity_period(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetBeneficiaryInactivityPeriodArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        fund_config: writable, owner = program_id;
+    }
+
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    if args.inactivity_secs < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config.beneficiary_inactivity_secs = args.inactivity_secs;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+
+    msg!("✅ BENEFICIARY_INACTIVITY_PERIOD_UPDATED");
+    msg!("  Inactivity period: {} secs", config.beneficiary_inactivity_period());
+
+    Ok(())
+}
+
+// =============================================================================
+// Fund Creation Limits
+// =============================================================================
+
+/// Update per-manager fund creation limits (Admin only)
+fn process_update_fund_creation_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateFundCreationLimitsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        fund_config: writable, owner = program_id;
+    }
+
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    if let Some(max_funds) = args.max_funds_per_manager {
+        config.max_funds_per_manager = max_funds;
+    }
+    if let Some(fee) = args.fund_creation_fee_e6 {
+        config.fund_creation_fee_e6 = fee;
+    }
+
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+
+    msg!("✅ FUND_CREATION_LIMITS_UPDATED");
+    msg!("  Max funds per manager: {}", config.max_funds_per_manager);
+    msg!("  Creation fee: {} USDC e6", config.fund_creation_fee_e6);
+
+    Ok(())
+}
+
+// =============================================================================
+// Fund Listing Approval
+// =============================================================================
+
+/// Toggle the listing-approval requirement for new funds (Admin only)
+fn process_set_listing_approval_required(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetListingApprovalRequiredArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        fund_config: writable, owner = program_id;
+    }
+
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    config.requires_listing_approval = args.required;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+
+    msg!("✅ LISTING_APPROVAL_REQUIRED_UPDATED");
+    msg!("  Required: {}", args.required);
+
+    Ok(())
+}
+
+/// Approve or reject a pending fund listing (Admin only)
+fn process_approve_fund_listing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ApproveFundListingArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        fund_config: owner = program_id;
+        fund_account: writable, owner = program_id;
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if fund.listing_status != FundListingStatus::PendingApproval {
+        return Err(FundError::FundNotPendingApproval.into());
+    }
+
+    fund.listing_status = if args.approved {
+        FundListingStatus::Listed
+    } else {
+        FundListingStatus::Rejected
+    };
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+
+    msg!("✅ FUND_LISTING_REVIEWED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Status: {:?}", fund.listing_status);
+
+    Ok(())
+}
+
+// =============================================================================
+// Fee Crank Operations
+// =============================================================================
+
+/// Opt the fund in/out of relayer-driven fee collection (manager only)
+fn process_set_fee_crank(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFeeCrankArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        manager: signer;
+        fund_account: writable, owner = program_id;
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+
+    fund.fee_crank_enabled = args.enabled;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+
+    msg!("✅ FEE_CRANK_UPDATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Enabled: {}", args.enabled);
+
+    Ok(())
+}
+
+/// Set the fee destination token account (manager only)
+fn process_set_fee_destination(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let fee_destination = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        manager: signer;
+        fund_account: writable, owner = program_id;
+        fee_destination: owner = &spl_token::id();
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+
+    // Destination must hold the same token as the fund vault
+    let vault_state = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    let destination_state = spl_token::state::Account::unpack(&fee_destination.data.borrow())?;
+    if destination_state.mint != vault_state.mint {
+        return Err(FundError::InvalidMint.into());
+    }
+
+    fund.fee_destination = *fee_destination.key;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+
+    msg!("✅ FEE_DESTINATION_UPDATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Destination: {}", fee_destination.key);
+
+    Ok(())
+}
+
+// =============================================================================
+// Rent Reserve Operations
+// =============================================================================
+
+/// Who funds a PDA created during a user flow
+enum RentPayer<'a, 'b> {
+    /// The signing user pays (default)
+    Signer(&'b AccountInfo<'a>),
+    /// The program's RentReserve pays
+    Reserve(&'b AccountInfo<'a>),
+}
+
+/// Pick the RentReserve if it was passed as the last account, is enabled and
+/// can cover `lamports`; otherwise fall back to the signing user
+fn resolve_rent_payer<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+    user: &'b AccountInfo<'a>,
+    lamports: u64,
+) -> Result<RentPayer<'a, 'b>, ProgramError> {
+    let candidate = match accounts.last() {
+        Some(acc) if acc.owner == program_id && acc.data_len() == RentReserve::SIZE => acc,
+        _ => return Ok(RentPayer::Signer(user)),
+    };
+
+    let reserve = RentReserve::try_from_slice(&candidate.data.borrow())?;
+    if reserve.discriminator != RENT_RESERVE_DISCRIMINATOR {
+        return Ok(RentPayer::Signer(user));
+    }
+
+    let seeds = RentReserve::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(candidate, &seeds_refs, program_id)?;
+
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(RentReserve::SIZE);
+    if !reserve.is_enabled
+        || RentReserve::available_lamports(candidate.lamports(), rent_exempt_minimum) < lamports
+    {
+        return Ok(RentPayer::Signer(user));
+    }
+
+    Ok(RentPayer::Reserve(candidate))
+}
+
+/// Create a program-owned PDA for a user flow, sponsoring rent from the
+/// RentReserve when possible. Returns true if the reserve paid.
+#[allow(clippy::too_many_arguments)]
+fn create_user_flow_pda<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    user: &AccountInfo<'a>,
+    new_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+    current_ts: i64,
+) -> Result<bool, ProgramError> {
+    let lamports = Rent::get()?.minimum_balance(space);
+
+    let sponsored = match resolve_rent_payer(program_id, accounts, user, lamports)? {
+        RentPayer::Signer(payer) => {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    new_account.key,
+                    lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[payer.clone(), new_account.clone(), system_program.clone()],
+                &[signer_seeds],
+            )?;
+            false
+        }
+        RentPayer::Reserve(reserve_account) => {
+            // The reserve is program-owned, so it cannot fund create_account;
+            // move the lamports directly, then allocate + assign as the new PDA
+            let top_up = lamports.saturating_sub(new_account.lamports());
+            **reserve_account.try_borrow_mut_lamports()? = reserve_account
+                .lamports()
+                .checked_sub(top_up)
+                .ok_or(FundError::InsufficientBalance)?;
+            **new_account.try_borrow_mut_lamports()? = new_account
+                .lamports()
+                .checked_add(top_up)
+                .ok_or(FundError::Overflow)?;
+
+            invoke_signed(
+                &system_instruction::allocate(new_account.key, space as u64),
+                &[new_account.clone(), system_program.clone()],
+                &[signer_seeds],
+            )?;
+            invoke_signed(
+                &system_instruction::assign(new_account.key, program_id),
+                &[new_account.clone(), system_program.clone()],
+                &[signer_seeds],
+            )?;
+
+            let mut reserve = RentReserve::try_from_slice(&reserve_account.data.borrow())?;
+            reserve.record_sponsorship(top_up, current_ts);
+            reserve.serialize(&mut &mut reserve_account.data.borrow_mut()[..])?;
+
+            msg!("🏦 RENT_SPONSORED");
+            msg!("  Account: {}", new_account.key);
+            msg!("  Lamports: {}", top_up);
+            true
+        }
+    };
+
+    Ok(sponsored)
+}
+
+/// Close a user flow PDA, returning its lamports to the RentReserve when the
+/// reserve sponsored it (the reserve must then be passed) or else to `user`
+fn close_user_flow_pda<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    account: &AccountInfo<'a>,
+    user: &AccountInfo<'a>,
+    sponsored: bool,
+    current_ts: i64,
+) -> ProgramResult {
+    if !sponsored {
+        return close_program_account(account, user);
+    }
+
+    let seeds = RentReserve::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let reserve_account = accounts
+        .iter()
+        .find(|a| *a.key == expected)
+        .ok_or(FundError::RentReserveMissing)?;
+    assert_owned_by(reserve_account, program_id)?;
+    assert_writable(reserve_account)?;
+
+    let mut reserve = RentReserve::try_from_slice(&reserve_account.data.borrow())?;
+    if reserve.discriminator != RENT_RESERVE_DISCRIMINATOR {
+        return Err(FundError::RentReserveMissing.into());
+    }
+
+    let lamports = account.lamports();
+    close_program_account(account, reserve_account)?;
+    reserve.record_reimbursement(lamports, current_ts);
+    reserve.serialize(&mut &mut reserve_account.data.borrow_mut()[..])?;
+
+    msg!("🏦 RENT_RETURNED");
+    msg!("  Account: {}", account.key);
+    msg!("  Lamports: {}", lamports);
+
+    Ok(())
+}
+
+/// Initialize the rent reserve PDA (Admin only)
+fn process_initialize_rent_reserve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let rent_reserve = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let seeds = RentReserve::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+
+    validate_accounts! {
+        authority: signer, writable;
+        fund_config: owner = program_id;
+        rent_reserve: writable, pda = (&seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    if !rent_reserve.data_is_empty() {
+        return Err(FundError::FundAlreadyInitialized.into());
+    }
+
+    let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let rent = Rent::get()?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            rent_reserve.key,
+            rent.minimum_balance(RentReserve::SIZE),
+            RentReserve::SIZE as u64,
+            program_id,
+        ),
+        &[authority.clone(), rent_reserve.clone(), system_program.clone()],
+        &[&[RENT_RESERVE_SEED, &[bump]]],
+    )?;
+
+    let reserve = RentReserve::new(bump, get_current_timestamp()?);
+    reserve.serialize(&mut &mut rent_reserve.data.borrow_mut()[..])?;
+
+    msg!("✅ RENT_RESERVE_INITIALIZED");
+    msg!("  Reserve: {}", rent_reserve.key);
+
+    Ok(())
+}
+
+/// Enable/disable rent sponsoring (Admin only)
+fn process_set_rent_reserve_enabled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetRentReserveEnabledArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let rent_reserve = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        fund_config: owner = program_id;
+        rent_reserve: writable, owner = program_id;
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    let mut reserve = RentReserve::try_from_slice(&rent_reserve.data.borrow())?;
+
+    if reserve.discriminator != RENT_RESERVE_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    reserve.is_enabled = args.enabled;
+    reserve.last_update_ts = get_current_timestamp()?;
+    reserve.serialize(&mut &mut rent_reserve.data.borrow_mut()[..])?;
+
+    msg!("✅ RENT_RESERVE_UPDATED");
+    msg!("  Enabled: {}", args.enabled);
+
+    Ok(())
+}
+
+/// Pay sponsored rent back into the reserve
+fn process_reimburse_rent_reserve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ReimburseRentReserveArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let rent_reserve = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        payer: signer, writable;
+        rent_reserve: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    if args.lamports == 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+
+    let mut reserve = RentReserve::try_from_slice(&rent_reserve.data.borrow())?;
+
+    if reserve.discriminator != RENT_RESERVE_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    invoke(
+        &system_instruction::transfer(payer.key, rent_reserve.key, args.lamports),
+        &[payer.clone(), rent_reserve.clone(), system_program.clone()],
+    )?;
+
+    reserve.record_reimbursement(args.lamports, get_current_timestamp()?);
+    reserve.serialize(&mut &mut rent_reserve.data.borrow_mut()[..])?;
+
+    msg!("✅ RENT_RESERVE_REIMBURSED");
+    msg!("  Payer: {}", payer.key);
+    msg!("  Lamports: {}", args.lamports);
+    msg!("  Outstanding: {}", reserve.outstanding_lamports());
+
+    Ok(())
+}
+
+// =============================================================================
+// Reinitialize (timelocked recovery)
+// =============================================================================
+
+/// Resize a program-owned account, topping up rent from `payer` when it grows
+fn realloc_with_rent<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_size: usize,
+) -> ProgramResult {
+    let required = Rent::get()?.minimum_balance(new_size);
+    let top_up = required.saturating_sub(account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, top_up),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+
+    account.realloc(new_size, false)?;
+    Ok(())
+}
+
+/// Schedule a FundConfig reinitialization (Admin only)
+fn process_schedule_reinitialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ScheduleReinitializeArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let pending_reinit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let seeds = PendingReinitialize::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+
+    validate_accounts! {
+        authority: signer, writable;
+        fund_config: owner = program_id;
+        pending_reinit: writable, pda = (&seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    // Read the authority from the fixed header so a damaged config can still be recovered
+    let config_authority = FundConfig::authority_from_header(&fund_config.data.borrow())
+        .ok_or(FundError::FundNotInitialized)?;
+    if config_authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let current_ts = get_current_timestamp()?;
+
+    if pending_reinit.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                pending_reinit.key,
+                rent.minimum_balance(PendingReinitialize::SIZE),
+                PendingReinitialize::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), pending_reinit.clone(), system_program.clone()],
+            &[&[PENDING_REINIT_SEED, &[bump]]],
+        )?;
+    } else {
+        assert_owned_by(pending_reinit, program_id)?;
+    }
+
+    // (Re)scheduling always restarts the timelock
+    let pending = PendingReinitialize::new(
+        *authority.key,
+        args.vault_program,
+        args.ledger_program,
+        args.total_funds,
+        bump,
+        current_ts,
+    );
+    pending.serialize(&mut &mut pending_reinit.data.borrow_mut()[..])?;
+
+    msg!("⏳ REINITIALIZE_SCHEDULED");
+    msg!("  Vault Program: {}", args.vault_program);
+    msg!("  Ledger Program: {}", args.ledger_program);
+    msg!("  Executable at: {}", pending.executable_at);
+
+    Ok(())
+}
+
+/// Cancel a scheduled reinitialization (Admin only)
+fn process_cancel_reinitialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let pending_reinit = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer, writable;
+        pending_reinit: writable, owner = program_id;
+    }
+
+    let pending = PendingReinitialize::try_from_slice(&pending_reinit.data.borrow())?;
+
+    if pending.discriminator != PENDING_REINIT_DISCRIMINATOR {
+        return Err(FundError::ReinitializeNotScheduled.into());
+    }
+
+    if pending.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    close_program_account(pending_reinit, authority)?;
+
+    msg!("✅ REINITIALIZE_CANCELLED");
+
+    Ok(())
+}
+
+/// Rewrite FundConfig after the timelock has elapsed (Admin only)
+///
+/// Counters and relayer settings are kept when the existing config can still be
+/// decoded; otherwise a fresh config is written using the scheduled
+/// `total_funds`. The account grows to FundConfig::SIZE if smaller; a larger
+/// account is never shrunk, only its bytes past the layout are zeroed.
+fn process_reinitialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let pending_reinit = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let (config_pda, config_bump) = Pubkey::find_program_address(&[FUND_CONFIG_SEED], program_id);
+
+    validate_accounts! {
+        authority: signer, writable;
+        fund_config: writable, owner = program_id, key = &config_pda;
+        pending_reinit: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    let pending = PendingReinitialize::try_from_slice(&pending_reinit.data.borrow())?;
+
+    if pending.discriminator != PENDING_REINIT_DISCRIMINATOR {
+        return Err(FundError::ReinitializeNotScheduled.into());
+    }
+
+    if pending.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+    if !pending.is_executable(current_ts) {
+        return Err(FundError::TimelockNotElapsed.into());
+    }
+
+    // Keep whatever can still be decoded from the old layout
+    let existing = FundConfig::unpack_versioned(&fund_config.data.borrow())
+        .ok()
+        .filter(|c| c.discriminator == FUND_CONFIG_DISCRIMINATOR);
+    let preserved = existing.is_some();
+    let mut config = match existing {
+        Some(mut config) => {
+            config.vault_program = pending.vault_program;
+            config.ledger_program = pending.ledger_program;
+            config
+        }
+        None => {
+            let mut config = FundConfig::new(
+                *authority.key,
+                pending.vault_program,
+                pending.ledger_program,
+                config_bump,
+            );
+            config.total_funds = pending.total_funds;
+            config
+        }
+    };
+    config.bump = config_bump;
+
+    if fund_config.data_len() < FundConfig::SIZE {
+        realloc_with_rent(fund_config, authority, system_program, FundConfig::SIZE)?;
+    }
+    {
+        let mut data = fund_config.data.borrow_mut();
+        config.serialize(&mut &mut data[..])?;
+        data[FundConfig::SIZE..].fill(0);
+    }
+
+    close_program_account(pending_reinit, authority)?;
+
+    msg!("✅ FUND_CONFIG_REINITIALIZED");
+    msg!("  State preserved: {}", preserved);
+    msg!("  Vault Program: {}", config.vault_program);
+    msg!("  Ledger Program: {}", config.ledger_program);
+    msg!("  Total funds: {}", config.total_funds);
+
+    Ok(())
+}
+
+// =============================================================================
+// Account Reallocation
+// =============================================================================
+
+/// Grow a program-owned account so its layout can be extended (Admin only)
+fn process_realloc_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ReallocAccountArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let target = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer, writable;
+        fund_config: owner = program_id;
+        target: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    let old_size = target.data_len();
+    let new_size = args.new_size as usize;
+
+    // Grow only, within the per-instruction realloc limit
+    if new_size <= old_size
+        || new_size - old_size > solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE
+    {
+        return Err(FundError::InvalidReallocSize.into());
+    }
+
+    realloc_with_rent(target, authority, system_program, new_size)?;
+
+    // A Fund grown to the current layout can now hold its NAV TWAP accumulator
+    if new_size >= Fund::SIZE {
+        let fund = Fund::unpack_versioned(&target.data.borrow()).ok();
+        if let Some(mut fund) = fund.filter(|f| f.discriminator == FUND_DISCRIMINATOR && f.nav_cumulative_ts == 0) {
+            fund.start_nav_twap(get_current_timestamp()?);
+            fund.pack_versioned(&mut target.data.borrow_mut())?;
+            msg!("  NAV TWAP started");
+        }
+    }
+
+    msg!("✅ ACCOUNT_REALLOCATED");
+    msg!("  Account: {}", target.key);
+    msg!("  Size: {} -> {}", old_size, new_size);
+
+    Ok(())
+}
+
+// =============================================================================
+// Payout Allow-list Instructions
+// =============================================================================
+
+/// Check a redemption destination against the position's PayoutAllowList
+///
+/// The allow-list PDA is looked up by address among `accounts`, so it can be
+/// appended after any optional accounts. A token account destination matches
+/// either by its own address or by its owner wallet.
+fn assert_payout_allowed(
+    program_id: &Pubkey,
+    lp_position: &AccountInfo,
+    destination: &AccountInfo,
+    accounts: &[AccountInfo],
+    current_ts: i64,
+) -> ProgramResult {
+    let list_seeds = PayoutAllowList::seeds(lp_position.key);
+    let list_seeds_refs: Vec<&[u8]> = list_seeds.iter().map(|s| s.as_slice()).collect();
+    let (list_key, _) = Pubkey::find_program_address(&list_seeds_refs, program_id);
+    
+    let list_account = accounts
+        .iter()
+        .find(|a| a.key == &list_key)
+        .ok_or(FundError::PayoutAddressNotAllowed)?;
+    assert_owned_by(list_account, program_id)?;
+    
+    let list = PayoutAllowList::try_from_slice(&list_account.data.borrow())?;
+    if list.discriminator != PAYOUT_ALLOWLIST_DISCRIMINATOR {
+        return Err(FundError::PayoutAddressNotAllowed.into());
+    }
+    
+    if list.is_allowed(destination.key, current_ts) {
+        return Ok(());
+    }
+    
+    if destination.owner == &spl_token::id() {
+        if let Ok(token_account) = spl_token::state::Account::unpack(&destination.data.borrow()) {
+            if list.is_allowed(&token_account.owner, current_ts) {
+                return Ok(());
+            }
+        }
+    }
+    
+    msg!("❌ Payout destination not allowed: {}", destination.key);
+    Err(FundError::PayoutAddressNotAllowed.into())
+}
+
+/// Register a payout address, opting the LP position into the allow-list
+fn process_register_payout_address(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: PayoutAddressArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let allowlist = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        investor: signer, writable;
+        lp_position: writable, owner = program_id;
+        allowlist: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.investor != *investor.key {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    let list_seeds = PayoutAllowList::seeds(lp_position.key);
+    let list_seeds_refs: Vec<&[u8]> = list_seeds.iter().map(|s| s.as_slice()).collect();
+    let list_bump = assert_pda(allowlist, &list_seeds_refs, program_id)?;
+    
+    let current_ts = get_current_timestamp()?;
+    
+    let mut list = if allowlist.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                allowlist.key,
+                Rent::get()?.minimum_balance(PayoutAllowList::SIZE),
+                PayoutAllowList::SIZE as u64,
+                program_id,
+            ),
+            &[investor.clone(), allowlist.clone(), system_program.clone()],
+            &[&[PAYOUT_ALLOWLIST_SEED, lp_position.key.as_ref(), &[list_bump]]],
+        )?;
+        PayoutAllowList::new(*lp_position.key, *investor.key, list_bump, current_ts)
+    } else {
+        assert_owned_by(allowlist, program_id)?;
+        let list = PayoutAllowList::try_from_slice(&allowlist.data.borrow())?;
+        if list.discriminator != PAYOUT_ALLOWLIST_DISCRIMINATOR {
+            return Err(FundError::PDAMismatch.into());
+        }
+        list
+    };
+    
+    let active_after = list
+        .register(args.address, current_ts)
+        .ok_or(FundError::PayoutAllowListFull)?;
+    list.serialize(&mut &mut allowlist.data.borrow_mut()[..])?;
+    
+    if !position.payout_allowlist_enabled {
+        // Positions created before the allow-list need room for the flag
+        if lp_position.data_len() < LPPosition::SIZE {
+            realloc_with_rent(lp_position, investor, system_program, LPPosition::SIZE)?;
+        }
+        position.payout_allowlist_enabled = true;
+        position.last_update_ts = current_ts;
+        position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    }
+    
+    msg!("✅ PAYOUT_ADDRESS_REGISTERED");
+    msg!("  LP Position: {}", lp_position.key);
+    msg!("  Address: {}", args.address);
+    msg!("  Active after: {}", active_after);
+    
+    Ok(())
+}
+
+/// Remove a payout address from an LP position's allow-list
+fn process_remove_payout_address(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: PayoutAddressArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let allowlist = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        investor: signer;
+        lp_position: owner = program_id;
+        allowlist: writable, owner = program_id;
+    }
+    
+    let mut list = PayoutAllowList::try_from_slice(&allowlist.data.borrow())?;
+    if list.discriminator != PAYOUT_ALLOWLIST_DISCRIMINATOR
+        || list.lp_position != *lp_position.key
+        || list.investor != *investor.key
+    {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    if !list.remove(&args.address, get_current_timestamp()?) {
+        return Err(FundError::PayoutAddressNotAllowed.into());
+    }
+    list.serialize(&mut &mut allowlist.data.borrow_mut()[..])?;
+    
+    msg!("✅ PAYOUT_ADDRESS_REMOVED");
+    msg!("  LP Position: {}", lp_position.key);
+    msg!("  Address: {}", args.address);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Bulk NAV Operations
+// =============================================================================
+
+/// Compute units kept in reserve per remaining fund in UpdateNAVMulti
+const NAV_MULTI_CU_PER_FUND: u64 = 8_000;
+
+/// Update NAV for several funds, skipping (not failing on) bad entries
+fn process_update_nav_multi(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if accounts.is_empty() || !accounts.len().is_multiple_of(2) {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    if accounts.len() / 2 > MAX_NAV_MULTI_FUNDS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    let mut result = UpdateNAVMultiResult { processed: 0, success_bitmap: 0 };
+    
+    for (i, pair) in accounts.chunks(2).enumerate() {
+        if remaining_compute_units() < NAV_MULTI_CU_PER_FUND {
+            msg!("Compute budget low, stopping after {} funds", i);
+            break;
+        }
+        
+        match update_fund_nav(program_id, &pair[0], &pair[1], current_ts) {
+            Ok(nav_e6) => {
+                result.success_bitmap |= 1 << i;
+                msg!("  [{}] {} NAV: {}", i, pair[0].key, nav_e6);
+            }
+            Err(e) => msg!("  [{}] {} skipped: {}", i, pair[0].key, e),
+        }
+        result.processed += 1;
+    }
+    
+    set_fund_return_data(&result)?;
+    
+    msg!("✅ NAV_MULTI_UPDATED");
+    msg!("  Processed: {}", result.processed);
+    msg!("  Success bitmap: {:#034b}", result.success_bitmap);
+    
+    Ok(())
+}
+
+/// Validate one (fund, vault) pair and refresh its NAV, returning the new NAV
+fn update_fund_nav(
+    program_id: &Pubkey,
+    fund_account: &AccountInfo,
+    fund_vault: &AccountInfo,
+    current_ts: i64,
+) -> Result<i64, ProgramError> {
+    validate_accounts! {
+        fund_account: writable, owner = program_id;
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    fund.accrue_aum(current_ts);
+    fund.stats.update_nav()?;
+    check_nav_floor(&mut fund, fund_account, current_ts);
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    
+    Ok(fund.stats.current_nav_e6)
+}
+
+// =============================================================================
+// System Fund Operations
+// =============================================================================
+
+/// Set the insurance/system fund flags on a fund (Admin only)
+fn process_set_system_fund_flags(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetSystemFundFlagsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        authority: signer;
+        fund_config: owner = program_id;
+        fund_account: writable, owner = program_id;
+    }
+    
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    fund.is_insurance_fund = args.is_insurance_fund;
+    fund.is_system_fund = args.is_system_fund;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    msg!("✅ SYSTEM_FUND_FLAGS_UPDATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Insurance fund: {}", args.is_insurance_fund);
+    msg!("  System fund: {}", args.is_system_fund);
+    
+    Ok(())
+}
+
+// =============================================================================
+// LP Index
+// =============================================================================
+
+/// Find the LPIndexPage PDA for `page` among the passed accounts
+fn find_lp_index_page<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+    fund_key: &Pubkey,
+    page: u16,
+) -> Option<(&'b AccountInfo<'a>, u8)> {
+    let seeds = LPIndexPage::seeds(fund_key, page);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    accounts.iter().find(|a| *a.key == expected).map(|a| (a, bump))
+}
+
+/// Load an LPIndexPage and check it belongs to `fund_key`
+fn load_lp_index_page(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    fund_key: &Pubkey,
+) -> Result<LPIndexPage, ProgramError> {
+    assert_owned_by(account, program_id)?;
+    let index_page = LPIndexPage::try_from_slice(&account.data.borrow())?;
+    if index_page.discriminator != LP_INDEX_PAGE_DISCRIMINATOR || index_page.fund != *fund_key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(index_page)
+}
+
+/// Append an LP position to the fund's last LPIndexPage
+///
+/// Opens a new page (paid by `payer`, or the RentReserve) when the last
+/// one is full. A legacy-size position is grown so the index fields fit.
+#[allow(clippy::too_many_arguments)]
+fn lp_index_add<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    fund_key: &Pubkey,
+    fund: &mut Fund,
+    lp_position: &AccountInfo<'a>,
+    position: &mut LPPosition,
+    current_ts: i64,
+) -> ProgramResult {
+    if position.is_indexed {
+        return Ok(());
+    }
+
+    if lp_position.data_len() < LPPosition::SIZE {
+        realloc_with_rent(lp_position, payer, system_program, LPPosition::SIZE)?;
+    }
+
+    // Try the current last page first
+    if fund.lp_index_page_count > 0 {
+        let last = fund.lp_index_page_count - 1;
+        let (page_account, _) = find_lp_index_page(program_id, accounts, fund_key, last)
+            .ok_or(FundError::LPIndexPageMissing)?;
+        let mut index_page = load_lp_index_page(program_id, page_account, fund_key)?;
+        if index_page.push(*lp_position.key) {
+            assert_writable(page_account)?;
+            index_page.serialize(&mut &mut page_account.data.borrow_mut()[..])?;
+            position.is_indexed = true;
+            position.lp_index_page = last;
+            return Ok(());
+        }
+    }
+
+    // Open the next page
+    let next = fund.lp_index_page_count;
+    let (page_account, bump) = find_lp_index_page(program_id, accounts, fund_key, next)
+        .ok_or(FundError::LPIndexPageMissing)?;
+    assert_writable(page_account)?;
+
+    create_user_flow_pda(
+        program_id,
+        accounts,
+        payer,
+        page_account,
+        system_program,
+        LPIndexPage::SIZE,
+        &[LP_INDEX_PAGE_SEED, fund_key.as_ref(), &next.to_le_bytes(), &[bump]],
+        current_ts,
+    )?;
+
+    let mut index_page = LPIndexPage::new(*fund_key, next, bump);
+    index_page.push(*lp_position.key);
+    index_page.serialize(&mut &mut page_account.data.borrow_mut()[..])?;
+
+    fund.lp_index_page_count = next.checked_add(1).ok_or(FundError::Overflow)?;
+    position.is_indexed = true;
+    position.lp_index_page = next;
+
+    msg!("📇 LP_INDEX_PAGE_OPENED");
+    msg!("  Fund: {}", fund_key);
+    msg!("  Page: {}", next);
+
+    Ok(())
+}
+
+/// Prune a closed LP position from its LPIndexPage
+fn lp_index_remove(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fund_key: &Pubkey,
+    lp_position: &AccountInfo,
+    position: &mut LPPosition,
+) -> ProgramResult {
+    if !position.is_indexed {
+        return Ok(());
+    }
+
+    let (page_account, _) = find_lp_index_page(program_id, accounts, fund_key, position.lp_index_page)
+        .ok_or(FundError::LPIndexPageMissing)?;
+    assert_writable(page_account)?;
+
+    let mut index_page = load_lp_index_page(program_id, page_account, fund_key)?;
+    index_page.remove(lp_position.key);
+    index_page.serialize(&mut &mut page_account.data.borrow_mut()[..])?;
+
+    position.is_indexed = false;
+    position.lp_index_page = 0;
+
+    Ok(())
+}
+
+/// Index an LP position opened before LP indexing existed (permissionless)
+fn process_index_lp_position(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        payer: signer, writable;
+        fund_account: writable, owner = program_id;
+        lp_position: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.fund != *fund_account.key {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    if position.is_indexed || position.is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    lp_index_add(program_id, accounts, payer, system_program, fund_account.key, &mut fund, lp_position, &mut position, current_ts)?;
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    msg!("✅ LP_POSITION_INDEXED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Position: {}", lp_position.key);
+    msg!("  Page: {}", position.lp_index_page);
+    
+    Ok(())
+}
+
+// =============================================================================
+// LP Distribution
+// =============================================================================
+
+/// Pay a fixed USDC amount per share to LPs, one LPIndexPage per call
+fn process_distribute_to_lps(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: DistributeToLPsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let distribution_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        fund_vault: writable;
+        distribution_account: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let dist_seeds = LPDistribution::seeds(fund_account.key);
+    let dist_seeds_refs: Vec<&[u8]> = dist_seeds.iter().map(|s| s.as_slice()).collect();
+    let dist_bump = assert_pda(distribution_account, &dist_seeds_refs, program_id)?;
+    
+    if distribution_account.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                manager.key,
+                distribution_account.key,
+                rent.minimum_balance(LPDistribution::SIZE),
+                LPDistribution::SIZE as u64,
+                program_id,
+            ),
+            &[manager.clone(), distribution_account.clone(), system_program.clone()],
+            &[&[LP_DISTRIBUTION_SEED, fund_account.key.as_ref(), &[dist_bump]]],
+        )?;
+        LPDistribution::new(*fund_account.key, dist_bump)
+            .serialize(&mut &mut distribution_account.data.borrow_mut()[..])?;
+    }
+    
+    assert_owned_by(distribution_account, program_id)?;
+    let mut distribution = LPDistribution::try_from_slice(&distribution_account.data.borrow())?;
+    if distribution.discriminator != LP_DISTRIBUTION_DISCRIMINATOR || distribution.fund != *fund_account.key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    let vault_state = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    
+    if !distribution.is_active {
+        if args.amount_per_share_e6 == 0 || fund.stats.total_shares == 0 {
+            return Err(FundError::InvalidAmount.into());
+        }
+        let budget = distribution.start(args.amount_per_share_e6, fund.stats.total_shares, current_ts)?;
+        if (fund.token_amount_to_e6(vault_state.amount)? as u64) < budget {
+            return Err(FundError::InsufficientBalance.into());
+        }
+        fund.distribution_in_progress = true;
+        
+        msg!("✅ LP_DISTRIBUTION_STARTED");
+        msg!("  Fund: {}", fund.name_str());
+        msg!("  Round: {}", distribution.round);
+        msg!("  Per share: {}", args.amount_per_share_e6);
+        msg!("  Budget: {}", budget);
+    } else if args.amount_per_share_e6 != distribution.amount_per_share_e6 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let mut paid_this_call: u64 = 0;
+    
+    if distribution.next_page < fund.lp_index_page_count {
+        let page_account = next_account_info(account_info_iter)?;
+        let seeds = LPIndexPage::seeds(fund_account.key, distribution.next_page);
+        let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+        assert_pda(page_account, &seeds_refs, program_id)?;
+        let index_page = load_lp_index_page(program_id, page_account, fund_account.key)?;
+        
+        let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
+        let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
+        let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+        let fund_signer_seeds: &[&[u8]] =
+            &[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]];
+        
+        let mut index = distribution.next_index as usize;
+        while index < index_page.positions().len() {
+            let (Some(lp_position), Some(lp_usdc)) = (account_info_iter.next(), account_info_iter.next()) else {
+                break;
+            };
+            
+            if *lp_position.key != index_page.positions()[index] {
+                return Err(FundError::LPPositionNotFound.into());
+            }
+            assert_owned_by(lp_position, program_id)?;
+            assert_writable(lp_position)?;
+            assert_writable(lp_usdc)?;
+            
+            let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+            if position.discriminator != LP_POSITION_DISCRIMINATOR || position.fund != *fund_account.key {
+                return Err(FundError::LPPositionNotFound.into());
+            }
+            
+            // Pay only into the investor's own USDC account
+            let destination = spl_token::state::Account::unpack(&lp_usdc.data.borrow())?;
+            if destination.owner != position.investor || destination.mint != vault_state.mint {
+                return Err(FundError::InvalidMint.into());
+            }
+            
+            let payout = LPDistribution::payout_for(distribution.amount_per_share_e6, position.shares)?;
+            if payout > 0 {
+                invoke_signed(
+                    &spl_token::instruction::transfer(
+                        &spl_token::id(),
+                        fund_vault.key,
+                        lp_usdc.key,
+                        fund_account.key,
+                        &[],
+                        fund.e6_to_token_amount(payout as i64)?,
+                    )?,
+                    &[fund_vault.clone(), lp_usdc.clone(), fund_account.clone(), token_program.clone()],
+                    &[fund_signer_seeds],
+                )?;
+                
+                position.total_withdrawn_e6 = safe_add_i64(position.total_withdrawn_e6, payout as i64)?;
+                position.last_update_ts = current_ts;
+                position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+            }
+            
+            distribution.record_payout(payout)?;
+            paid_this_call = paid_this_call.saturating_add(payout);
+            index += 1;
+        }
+        
+        if index >= index_page.positions().len() {
+            distribution.next_page += 1;
+            distribution.next_index = 0;
+        } else {
+            distribution.next_index = index as u16;
+        }
+    }
+    
+    // Return of capital: shares stay, NAV drops
+    fund.accrue_aum(current_ts);
+    fund.record_withdrawal(paid_this_call as i64, 0)?;
+    
+    if distribution.next_page >= fund.lp_index_page_count {
+        distribution.finish(current_ts);
+        fund.distribution_in_progress = false;
+        
+        msg!("✅ LP_DISTRIBUTION_COMPLETED");
+        msg!("  Round: {}", distribution.round);
+        msg!("  Paid: {} to {} LPs", distribution.paid_e6, distribution.paid_count);
+    } else {
+        msg!("✅ LP_DISTRIBUTION_PROGRESS");
+        msg!("  Paid this call: {}", paid_this_call);
+        msg!("  Cursor: page {} entry {}", distribution.next_page, distribution.next_index);
+    }
+    
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    distribution.serialize(&mut &mut distribution_account.data.borrow_mut()[..])?;
+    
+    Ok(())
+}
+
+// =============================================================================
+// Governance
+// =============================================================================
+
+/// Open a share-weighted governance proposal
+fn process_create_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CreateProposalArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let proposer = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        proposer: signer, writable;
+        fund_account: owner = program_id;
+        proposal_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    // Manager, or an LP holding at least PROPOSAL_LP_THRESHOLD_BPS of shares
+    if !fund.is_manager(proposer.key) {
+        let lp_position = next_account_info(account_info_iter)?;
+        assert_owned_by(lp_position, program_id)?;
+        let position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+        if position.discriminator != LP_POSITION_DISCRIMINATOR
+            || position.fund != *fund_account.key
+            || position.investor != *proposer.key
+        {
+            return Err(FundError::LPPositionNotFound.into());
+        }
+        if !Proposal::meets_lp_threshold(position.shares, fund.stats.total_shares) {
+            return Err(FundError::ProposalThresholdNotMet.into());
+        }
+    }
+    
+    if !(MIN_VOTING_PERIOD_SECS..=MAX_VOTING_PERIOD_SECS).contains(&args.voting_period_secs) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    match args.kind {
+        ProposalKind::FeeChange => {
+            validate_fee_config(args.new_management_fee_bps, args.new_performance_fee_bps)?;
+        }
+        ProposalKind::ReplaceManager => {
+            if args.new_manager == Pubkey::default() || args.new_manager == fund.manager {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        ProposalKind::WindDown => {}
+    }
+    
+    let proposal_seeds = Proposal::seeds(fund_account.key, args.proposal_id);
+    let proposal_seeds_refs: Vec<&[u8]> = proposal_seeds.iter().map(|s| s.as_slice()).collect();
+    let proposal_bump = assert_pda(proposal_account, &proposal_seeds_refs, program_id)?;
+    
+    if !proposal_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            proposer.key,
+            proposal_account.key,
+            rent.minimum_balance(Proposal::SIZE),
+            Proposal::SIZE as u64,
+            program_id,
+        ),
+        &[proposer.clone(), proposal_account.clone(), system_program.clone()],
+        &[&[PROPOSAL_SEED, fund_account.key.as_ref(), &args.proposal_id.to_le_bytes(), &[proposal_bump]]],
+    )?;
+    
+    let current_ts = get_current_timestamp()?;
+    let mut proposal = Proposal::new(
+        *fund_account.key,
+        args.proposal_id,
+        *proposer.key,
+        args.kind,
+        fund.stats.total_shares,
+        current_ts,
+        args.voting_period_secs,
+        proposal_bump,
+    );
+    proposal.new_management_fee_bps = args.new_management_fee_bps;
+    proposal.new_performance_fee_bps = args.new_performance_fee_bps;
+    proposal.new_manager = args.new_manager;
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ PROPOSAL_CREATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Proposal: {} ({:?})", args.proposal_id, args.kind);
+    msg!("  Proposer: {}", proposer.key);
+    msg!("  Voting ends: {}", proposal.voting_ends_ts);
+    
+    Ok(())
+}
+
+/// Vote on a proposal with an LP position's snapshot shares
+fn process_cast_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CastVoteArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let vote_record = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        investor: signer, writable;
+        proposal_account: writable, owner = program_id;
+        lp_position: owner = program_id;
+        vote_record: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut proposal = Proposal::try_from_slice(&proposal_account.data.borrow())?;
+    if proposal.discriminator != PROPOSAL_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    if !proposal.is_voting_open(current_ts) {
+        return Err(FundError::VotingClosed.into());
+    }
+    
+    let position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.investor != *investor.key {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    let weight = proposal.vote_weight(&position);
+    if weight == 0 {
+        return Err(FundError::NoVotingWeight.into());
+    }
+    
+    // One vote per position: creating the record fails if it already exists
+    let record_seeds = VoteRecord::seeds(proposal_account.key, lp_position.key);
+    let record_seeds_refs: Vec<&[u8]> = record_seeds.iter().map(|s| s.as_slice()).collect();
+    let record_bump = assert_pda(vote_record, &record_seeds_refs, program_id)?;
+    
+    if !vote_record.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            investor.key,
+            vote_record.key,
+            rent.minimum_balance(VoteRecord::SIZE),
+            VoteRecord::SIZE as u64,
+            program_id,
+        ),
+        &[investor.clone(), vote_record.clone(), system_program.clone()],
+        &[&[VOTE_RECORD_SEED, proposal_account.key.as_ref(), lp_position.key.as_ref(), &[record_bump]]],
+    )?;
+    
+    let record = VoteRecord::new(*proposal_account.key, *lp_position.key, args.support, weight, current_ts, record_bump);
+    record.serialize(&mut &mut vote_record.data.borrow_mut()[..])?;
+    
+    proposal.record_vote(args.support, weight);
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ VOTE_CAST");
+    msg!("  Proposal: {}", proposal.proposal_id);
+    msg!("  Voter: {}", investor.key);
+    msg!("  Support: {} Weight: {}", args.support, weight);
+    
+    Ok(())
+}
+
+/// Settle a proposal after its voting period (permissionless)
+fn process_finalize_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let caller = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        caller: signer;
+        fund_account: writable, owner = program_id;
+        proposal_account: writable, owner = program_id;
+    }
+    
+    let mut proposal = Proposal::try_from_slice(&proposal_account.data.borrow())?;
+    if proposal.discriminator != PROPOSAL_DISCRIMINATOR || proposal.fund != *fund_account.key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    if proposal.status != ProposalStatus::Active {
+        return Err(FundError::VotingClosed.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    if current_ts < proposal.voting_ends_ts {
+        return Err(FundError::VotingNotEnded.into());
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let status = proposal.finalize(current_ts);
+    if status == ProposalStatus::Passed {
+        match proposal.kind {
+            ProposalKind::FeeChange => {
+                fund.fee_config.management_fee_bps = proposal.new_management_fee_bps;
+                fund.fee_config.performance_fee_bps = proposal.new_performance_fee_bps;
+            }
+            ProposalKind::WindDown => {
+                fund.is_open = false;
+            }
+            ProposalKind::ReplaceManager => {
+                msg!("  Approved manager: {}", proposal.new_manager);
+            }
+        }
+        fund.last_update_ts = current_ts;
+        fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+        enforce_fund_invariants(&fund, None)?;
+    }
+    
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ PROPOSAL_FINALIZED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Proposal: {} ({:?})", proposal.proposal_id, proposal.kind);
+    msg!("  Status: {:?}", status);
+    msg!("  Yes: {} No: {} of {}", proposal.yes_weight, proposal.no_weight, proposal.total_shares_snapshot);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Share Price Feed
+// =============================================================================
+
+/// Create a fund's SharePriceFeed and publish the current NAV
+fn process_initialize_share_price_feed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let price_feed = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        payer: signer, writable;
+        fund_account: owner = program_id;
+        price_feed: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let feed_seeds = SharePriceFeed::seeds(fund_account.key);
+    let feed_seeds_refs: Vec<&[u8]> = feed_seeds.iter().map(|s| s.as_slice()).collect();
+    let feed_bump = assert_pda(price_feed, &feed_seeds_refs, program_id)?;
+    
+    if !price_feed.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            price_feed.key,
+            rent.minimum_balance(SharePriceFeed::SIZE),
+            SharePriceFeed::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), price_feed.clone(), system_program.clone()],
+        &[&[SHARE_PRICE_FEED_SEED, fund_account.key.as_ref(), &[feed_bump]]],
+    )?;
+    
+    SharePriceFeed::new(*fund_account.key, fund.share_mint, feed_bump)
+        .serialize(&mut &mut price_feed.data.borrow_mut()[..])?;
+    
+    publish_share_price(program_id, price_feed, fund_account.key, &fund, get_current_timestamp()?)?;
+    
+    msg!("✅ SHARE_PRICE_FEED_INITIALIZED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Feed: {}", price_feed.key);
+    
+    Ok(())
+}
+
+/// Write the fund's current NAV into its SharePriceFeed
+fn publish_share_price(
+    program_id: &Pubkey,
+    price_feed: &AccountInfo,
+    fund_key: &Pubkey,
+    fund: &Fund,
+    current_ts: i64,
+) -> ProgramResult {
+    assert_owned_by(price_feed, program_id)?;
+    assert_writable(price_feed)?;
+    
+    let mut feed = SharePriceFeed::try_from_slice(&price_feed.data.borrow())?;
+    if feed.discriminator != SHARE_PRICE_FEED_DISCRIMINATOR || feed.fund != *fund_key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    feed.publish(fund.stats.current_nav_e6, fund.stats.total_value_e6(), fund.stats.total_shares, current_ts);
+    feed.serialize(&mut &mut price_feed.data.borrow_mut()[..])?;
+    
+    msg!("📈 SHARE_PRICE_PUBLISHED");
+    msg!("  Price: {} (expo {}) Conf: {}", feed.price, feed.expo, feed.conf);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Share Lock Instructions
+// =============================================================================
+
+/// Pledge LP shares to a lienholder
+fn process_lock_shares(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ShareLockArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let lienholder = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        investor: signer, writable;
+        lienholder: signer;
+        lp_position: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.investor != *investor.key {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    position.lock_shares(*lienholder.key, args.shares)?;
+    
+    // Positions created before share locks need room for the lock fields
+    if lp_position.data_len() < LPPosition::SIZE {
+        realloc_with_rent(lp_position, investor, system_program, LPPosition::SIZE)?;
+    }
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    
+    msg!("🔒 SHARES_LOCKED");
+    msg!("  LP Position: {}", lp_position.key);
+    msg!("  Lienholder: {}", lienholder.key);
+    msg!("  Shares: {} (total locked {})", args.shares, position.locked_shares);
+    
+    Ok(())
+}
+
+/// Release pledged LP shares (lienholder only)
+fn process_unlock_shares(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ShareLockArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let lienholder = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        lienholder: signer;
+        lp_position: writable, owner = program_id;
+    }
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    position.unlock_shares(lienholder.key, args.shares)?;
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    
+    msg!("🔓 SHARES_UNLOCKED");
+    msg!("  LP Position: {}", lp_position.key);
+    msg!("  Lienholder: {}", lienholder.key);
+    msg!("  Shares: {} (still locked {})", args.shares, position.locked_shares);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Trade Intent Instructions
+// =============================================================================
+
+/// Create a limit-order style trade intent (manager only)
+fn process_create_trade_intent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CreateTradeIntentArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let intent_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: owner = program_id;
+        intent_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    
+    // Reject intents that could never pass the risk checks
+    if args.side > 1 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    calculate_trade_margin(args.size_e6, args.limit_price_e6, args.leverage)?;
+    
+    let current_ts = get_current_timestamp()?;
+    if args.expiry_ts <= current_ts {
+        return Err(FundError::TradeIntentExpired.into());
+    }
+    
+    let intent_seeds = TradeIntent::seeds(fund_account.key, args.intent_id);
+    let intent_seeds_refs: Vec<&[u8]> = intent_seeds.iter().map(|s| s.as_slice()).collect();
+    let intent_bump = assert_pda(intent_account, &intent_seeds_refs, program_id)?;
+    
+    if !intent_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            manager.key,
+            intent_account.key,
+            rent.minimum_balance(TradeIntent::SIZE),
+            TradeIntent::SIZE as u64,
+            program_id,
+        ),
+        &[manager.clone(), intent_account.clone(), system_program.clone()],
+        &[&[TRADE_INTENT_SEED, fund_account.key.as_ref(), &args.intent_id.to_le_bytes(), &[intent_bump]]],
+    )?;
+    
+    let intent = TradeIntent::new(
+        *fund_account.key,
+        args.intent_id,
+        args.market_index,
+        args.side,
+        args.size_e6,
+        args.limit_price_e6,
+        args.leverage,
+        args.expiry_ts,
+        current_ts,
+        intent_bump,
+    );
+    intent.serialize(&mut &mut intent_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ TRADE_INTENT_CREATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Intent: {}", args.intent_id);
+    msg!("  Market: {} Side: {} Size: {}", args.market_index, args.side, args.size_e6);
+    msg!("  Limit: {} Expiry: {}", args.limit_price_e6, args.expiry_ts);
+    
+    Ok(())
+}
+
+/// Close a trade intent and return its rent to the manager
+fn process_cancel_trade_intent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let intent_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: owner = program_id;
+        intent_account: writable, owner = program_id;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    
+    let intent = TradeIntent::try_from_slice(&intent_account.data.borrow())?;
+    if intent.discriminator != TRADE_INTENT_DISCRIMINATOR || intent.fund != *fund_account.key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    close_program_account(intent_account, manager)?;
+    
+    msg!("✅ TRADE_INTENT_CANCELLED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Intent: {}", intent.intent_id);
+    msg!("  Executed: {}", intent.is_executed);
+    
+    Ok(())
+}
+
+/// Execute a trade intent through the Ledger Program (whitelisted keeper)
+fn process_execute_trade_intent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ExecuteTradeIntentArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let keeper = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let intent_account = next_account_info(account_info_iter)?;
+    let ledger_program = next_account_info(account_info_iter)?;
+    let position = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let vault_config = next_account_info(account_info_iter)?;
+    let ledger_config = next_account_info(account_info_iter)?;
+    let user_stats = next_account_info(account_info_iter)?;
+    let vault_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        keeper: signer;
+        fund_config: owner = program_id;
+        fund_account: writable, owner = program_id;
+        intent_account: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    if !config.is_authorized_relayer(keeper.key) {
+        return Err(FundError::UnauthorizedCaller.into());
+    }
+    validate_accounts! {
+        ledger_program: key = &config.ledger_program;
+        vault_program: key = &config.vault_program;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if fund.is_paused {
+        return Err(FundError::FundPaused.into());
+    }
+    
+    let mut intent = TradeIntent::try_from_slice(&intent_account.data.borrow())?;
+    if intent.discriminator != TRADE_INTENT_DISCRIMINATOR || intent.fund != *fund_account.key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if intent.is_executed {
+        return Err(FundError::TradeIntentAlreadyExecuted.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    if intent.is_expired(current_ts) {
+        return Err(FundError::TradeIntentExpired.into());
+    }
+    if !intent.price_satisfies_limit(args.price_e6) {
+        return Err(FundError::TradeIntentLimitNotMet.into());
+    }
+    
+    let (notional_e6, _, _) =
+        check_trade_risk(&fund, intent.side, intent.size_e6, args.price_e6, intent.leverage)?;
+    apply_manager_risk(program_id, accounts, &config, &fund.manager, notional_e6, intent.leverage, true)?;
+    
+    // Mark executed before the CPI so the intent cannot be replayed
+    intent.mark_executed(*keeper.key, args.price_e6, current_ts);
+    intent.serialize(&mut &mut intent_account.data.borrow_mut()[..])?;
+    
+    let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
+    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
+    let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+    
+    let batch_id = current_ts as u64;
+    
+    crate::cpi::open_position(
+        ledger_program.key,
+        fund_account.clone(),  // Fund acts as relayer
+        position.clone(),
+        user_account.clone(),
+        vault_config.clone(),
+        ledger_config.clone(),
+        user_stats.clone(),
+        vault_program.clone(),
+        system_program.clone(),
+        *fund_account.key,  // User is the fund itself
+        intent.market_index,
+        intent.side,
+        intent.size_e6,
+        args.price_e6,
+        intent.leverage,
+        batch_id,
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]]],
+    )?;
+    
+    msg!("✅ TRADE_INTENT_EXECUTED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Intent: {}", intent.intent_id);
+    msg!("  Keeper: {}", keeper.key);
+    msg!("  Price: {} (limit {})", args.price_e6, intent.limit_price_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Feature Flags
+// =============================================================================
+
+/// Reject the instruction if any of `features` is disabled in FundConfig
+///
+/// Looks the FundConfig PDA up by key among the instruction accounts and
+/// fails closed when it is missing.
+fn assert_features_enabled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    features: u32,
+) -> ProgramResult {
+    let config_seeds = FundConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    let (config_key, _) = Pubkey::find_program_address(&config_seeds_refs, program_id);
+
+    let config_account = accounts
+        .iter()
+        .find(|a| a.key == &config_key)
+        .ok_or(FundError::FeatureConfigMissing)?;
+    assert_owned_by(config_account, program_id)?;
+
+    let config = FundConfig::unpack_versioned(&config_account.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if !config.is_feature_enabled(features) {
+        msg!("❌ Feature disabled: {:#x}", features & config.disabled_features);
+        return Err(FundError::FeatureDisabled.into());
+    }
+
+    if features & FEATURE_RELAYER != 0 && config.relayer_paused {
+        msg!("❌ Relayer subsystem paused");
+        return Err(FundError::RelayerSystemPaused.into());
+    }
+
+    Ok(())
+}
+
+/// Enable or disable subsystems (Admin only)
+fn process_set_features_enabled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFeaturesEnabledArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        fund_config: writable, owner = program_id;
+    }
+
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    if args.features == 0 || args.features & !ALL_FEATURES != 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config.set_features_enabled(args.features, args.enabled);
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+
+    msg!("✅ FEATURES_UPDATED");
+    msg!("  Features: {:#x}", args.features);
+    msg!("  Enabled: {}", args.enabled);
+    msg!("  Disabled mask: {:#x}", config.disabled_features);
+
+    Ok(())
+}
+
+// =============================================================================
+// Redemption Fee Curve
+// =============================================================================
+
+/// Configure the dynamic redemption fee curve (manager only)
+fn process_set_redemption_fee_curve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetRedemptionFeeCurveArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+
+    if args.max_fee_bps > MAX_REDEMPTION_FEE_BPS
+        || args.target_cash_ratio_bps as u64 > BPS_DENOMINATOR
+        || (args.max_fee_bps > 0 && args.target_cash_ratio_bps == 0)
+    {
+        return Err(FundError::InvalidRedemptionFeeCurve.into());
+    }
+
+    // Legacy funds have no room for the curve yet
+    if fund_account.data_len() < Fund::SIZE {
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+
+    fund.redemption_fee_curve = RedemptionFeeCurve {
+        target_cash_ratio_bps: args.target_cash_ratio_bps,
+        max_fee_bps: args.max_fee_bps,
+    };
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+
+    msg!("✅ REDEMPTION_FEE_CURVE_UPDATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Target cash ratio: {} bps", args.target_cash_ratio_bps);
+    msg!("  Max fee: {} bps", args.max_fee_bps);
+
+    Ok(())
+}
+
+// =============================================================================
+// Deposit Attribution
+// =============================================================================
+
+/// Record a deposit against a marketing campaign
+///
+/// Creates the CampaignStats PDA on the campaign's first deposit and appends
+/// a DepositAttribution PDA at the next sequence, both paid by the investor.
+/// The PDAs are looked up by key among the instruction accounts.
+#[allow(clippy::too_many_arguments)]
+fn record_deposit_attribution<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    investor: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    campaign_id: u64,
+    fund_key: &Pubkey,
+    amount_e6: i64,
+    shares: u64,
+    nav_e6: i64,
+    current_ts: i64,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    
+    let stats_seeds = CampaignStats::seeds(campaign_id);
+    let stats_seeds_refs: Vec<&[u8]> = stats_seeds.iter().map(|s| s.as_slice()).collect();
+    let (stats_key, stats_bump) = Pubkey::find_program_address(&stats_seeds_refs, program_id);
+    let campaign_stats = accounts
+        .iter()
+        .find(|a| *a.key == stats_key)
+        .ok_or(FundError::CampaignAccountsMissing)?;
+    
+    let mut stats = if campaign_stats.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                campaign_stats.key,
+                rent.minimum_balance(CampaignStats::SIZE),
+                CampaignStats::SIZE as u64,
+                program_id,
+            ),
+            &[investor.clone(), campaign_stats.clone(), system_program.clone()],
+            &[&[CAMPAIGN_STATS_SEED, &campaign_id.to_le_bytes(), &[stats_bump]]],
+        )?;
+        CampaignStats::new(campaign_id, stats_bump)
+    } else {
+        assert_owned_by(campaign_stats, program_id)?;
+        let stats = CampaignStats::try_from_slice(&campaign_stats.data.borrow())?;
+        if stats.discriminator != CAMPAIGN_STATS_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        stats
+    };
+    
+    let seq = stats.record_deposit(amount_e6, shares, current_ts)?;
+    
+    let attribution_seeds = DepositAttribution::seeds(campaign_id, seq);
+    let attribution_seeds_refs: Vec<&[u8]> = attribution_seeds.iter().map(|s| s.as_slice()).collect();
+    let (attribution_key, attribution_bump) = Pubkey::find_program_address(&attribution_seeds_refs, program_id);
+    let deposit_attribution = accounts
+        .iter()
+        .find(|a| *a.key == attribution_key)
+        .ok_or(FundError::CampaignAccountsMissing)?;
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            investor.key,
+            deposit_attribution.key,
+            rent.minimum_balance(DepositAttribution::SIZE),
+            DepositAttribution::SIZE as u64,
+            program_id,
+        ),
+        &[investor.clone(), deposit_attribution.clone(), system_program.clone()],
+        &[&[DEPOSIT_ATTRIBUTION_SEED, &campaign_id.to_le_bytes(), &seq.to_le_bytes(), &[attribution_bump]]],
+    )?;
+    
+    let attribution = DepositAttribution {
+        discriminator: DEPOSIT_ATTRIBUTION_DISCRIMINATOR,
+        campaign_id,
+        seq,
+        fund: *fund_key,
+        investor: *investor.key,
+        amount_e6,
+        shares,
+        nav_e6,
+        deposited_at: current_ts,
+        bump: attribution_bump,
+    };
+    attribution.serialize(&mut &mut deposit_attribution.data.borrow_mut()[..])?;
+    stats.serialize(&mut &mut campaign_stats.data.borrow_mut()[..])?;
+    
+    msg!("✅ DEPOSIT_ATTRIBUTED");
+    msg!("  Campaign: {}", campaign_id);
+    msg!("  Seq: {}", seq);
+    msg!("  Campaign deposits: {}", stats.total_deposits_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Referral Binding Expiry
+// =============================================================================
+
+/// Mark a referral binding past its validity window as expired (permissionless)
+fn process_expire_referral_binding(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?;
+    let referral_config = next_account_info(account_info_iter)?;
+    let referral_binding = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        caller: signer;
+        referral_config: owner = program_id;
+        referral_binding: writable, owner = program_id;
+    }
+
+    let config = ReferralConfig::try_from_slice(&referral_config.data.borrow())?;
+    if config.discriminator != REFERRAL_CONFIG_DISCRIMINATOR {
+        return Err(FundError::ReferralNotInitialized.into());
+    }
+
+    let mut binding = ReferralBinding::try_from_slice(&referral_binding.data.borrow())?;
+    if binding.discriminator != REFERRAL_BINDING_DISCRIMINATOR {
+        return Err(FundError::NoReferralBinding.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+    if !binding.is_expired(config.reward_validity_secs, current_ts) {
+        return Err(FundError::ReferralBindingNotExpired.into());
+    }
+
+    binding.mark_expired(current_ts);
+    binding.serialize(&mut &mut referral_binding.data.borrow_mut()[..])?;
+
+    msg!("✅ REFERRAL_BINDING_EXPIRED");
+    msg!("  Referee: {}", binding.referee);
+    msg!("  Referrer: {}", binding.referrer);
+    msg!("  Bound at: {}", binding.bound_at);
+    msg!("  Expired at: {}", binding.expired_at);
+
+    Ok(())
+}
+
+// =============================================================================
+// Prediction Market Maker Volume
+// =============================================================================
+
+/// Add a maker fill to the current epoch's PMMakerEpoch / PMMakerVolume
+///
+/// Creates either account on first use, paid by `payer`.
+#[allow(clippy::too_many_arguments)]
+fn record_pm_maker_volume<'a>(
+    program_id: &Pubkey,
+    maker_epoch: &AccountInfo<'a>,
+    maker_volume: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    maker: &Pubkey,
+    volume_e6: i64,
+    maker_reward_e6: i64,
+    current_ts: i64,
+) -> ProgramResult {
+    validate_accounts! {
+        maker_epoch: writable;
+        maker_volume: writable;
+        payer: signer, writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let rent = Rent::get()?;
+    let epoch_index = PMMakerEpoch::epoch_at(current_ts);
+    
+    let epoch_seeds = PMMakerEpoch::seeds(epoch_index);
+    let epoch_seeds_refs: Vec<&[u8]> = epoch_seeds.iter().map(|s| s.as_slice()).collect();
+    let epoch_bump = assert_pda(maker_epoch, &epoch_seeds_refs, program_id)?;
+    
+    let mut epoch = if maker_epoch.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                maker_epoch.key,
+                rent.minimum_balance(PMMakerEpoch::SIZE),
+                PMMakerEpoch::SIZE as u64,
+                program_id,
+            ),
+            &[payer.clone(), maker_epoch.clone(), system_program.clone()],
+            &[&[PM_MAKER_EPOCH_SEED, &epoch_index.to_le_bytes(), &[epoch_bump]]],
+        )?;
+        PMMakerEpoch::new(epoch_index, epoch_bump)
+    } else {
+        assert_owned_by(maker_epoch, program_id)?;
+        PMMakerEpoch::try_from_slice(&maker_epoch.data.borrow())?
+    };
+    
+    let volume_seeds = PMMakerVolume::seeds(maker, epoch_index);
+    let volume_seeds_refs: Vec<&[u8]> = volume_seeds.iter().map(|s| s.as_slice()).collect();
+    let volume_bump = assert_pda(maker_volume, &volume_seeds_refs, program_id)?;
+    
+    let mut volume = if maker_volume.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                maker_volume.key,
+                rent.minimum_balance(PMMakerVolume::SIZE),
+                PMMakerVolume::SIZE as u64,
+                program_id,
+            ),
+            &[payer.clone(), maker_volume.clone(), system_program.clone()],
+            &[&[PM_MAKER_VOLUME_SEED, maker.as_ref(), &epoch_index.to_le_bytes(), &[volume_bump]]],
+        )?;
+        epoch.maker_count = epoch.maker_count.saturating_add(1);
+        PMMakerVolume::new(*maker, epoch_index, volume_bump)
+    } else {
+        assert_owned_by(maker_volume, program_id)?;
+        PMMakerVolume::try_from_slice(&maker_volume.data.borrow())?
+    };
+    
+    volume.volume_e6 = safe_add_i64(volume.volume_e6, volume_e6)?;
+    epoch.total_maker_volume_e6 = safe_add_i64(epoch.total_maker_volume_e6, volume_e6)?;
+    epoch.reward_pool_e6 = safe_add_i64(epoch.reward_pool_e6, maker_reward_e6)?;
+    
+    volume.serialize(&mut &mut maker_volume.data.borrow_mut()[..])?;
+    epoch.serialize(&mut &mut maker_epoch.data.borrow_mut()[..])?;
+    
+    msg!("📊 PM_MAKER_VOLUME_RECORDED");
+    msg!("  Maker: {}", maker);
+    msg!("  Epoch: {}", epoch_index);
+    msg!("  Volume: {}", volume.volume_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Prediction Market Category Fees
+// =============================================================================
+
+/// Set or clear a prediction market category's fee override (Admin only)
+fn process_set_pm_category_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetPredictionMarketCategoryFeesArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let pm_fee_config = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        pm_fee_config: writable, owner = program_id;
+    }
+
+    let mut config = PredictionMarketFeeConfig::try_from_slice(&pm_fee_config.data.borrow())?;
+    if config.discriminator != PREDICTION_MARKET_FEE_CONFIG_DISCRIMINATOR {
+        return Err(FundError::PMFeeConfigNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    if let Some(fees) = args.fees {
+        let max_bps = BPS_DENOMINATOR as u16;
+        if fees.minting_fee_bps > max_bps || fees.redemption_fee_bps > max_bps || fees.trading_fee_taker_bps > max_bps {
+            return Err(FundError::InvalidFeeConfig.into());
+        }
+    }
+
+    config.set_category_fees(args.category, args.fees)?;
+    config.last_update_ts = get_current_timestamp()?;
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
+
+    let effective = config.prediction_market_category_fees(Some(args.category))?;
+    msg!("✅ PM_CATEGORY_FEES_UPDATED");
+    msg!("  Category: {}", args.category);
+    msg!("  Override: {}", args.fees.is_some());
+    msg!("  Minting fee: {} bps", effective.minting_fee_bps);
+    msg!("  Redemption fee: {} bps", effective.redemption_fee_bps);
+    msg!("  Trading fee (taker): {} bps", effective.trading_fee_taker_bps);
+
+    Ok(())
+}
+
+// =============================================================================
+// Treasury
+// =============================================================================
+
+/// Find the Treasury PDA among the passed accounts
+fn find_treasury<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+) -> Option<&'b AccountInfo<'a>> {
+    let seeds = Treasury::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    accounts.iter().find(|a| *a.key == expected)
+}
+
+/// Load the Treasury and check `treasury_vault` is its vault
+fn load_treasury(
+    program_id: &Pubkey,
+    treasury: &AccountInfo,
+    treasury_vault: &AccountInfo,
+) -> Result<Treasury, ProgramError> {
+    assert_owned_by(treasury, program_id)?;
+    
+    let state = Treasury::try_from_slice(&treasury.data.borrow())?;
+    if state.discriminator != TREASURY_DISCRIMINATOR {
+        return Err(FundError::TreasuryNotInitialized.into());
+    }
+    
+    if state.vault != *treasury_vault.key {
+        return Err(FundError::TreasuryVaultMismatch.into());
+    }
+    
+    Ok(state)
+}
+
+/// Check the caller is the FundConfig admin
+fn assert_config_admin(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    fund_config: &AccountInfo,
+) -> ProgramResult {
+    assert_signer(authority)?;
+    assert_owned_by(fund_config, program_id)?;
+    
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+    
+    Ok(())
+}
+
+/// Route protocol revenue into the Treasury vault and book it by source
+///
+/// `signer_seeds` is empty when `source_authority` signed the transaction and
+/// holds the PDA seeds when the source token account is program-owned.
+#[allow(clippy::too_many_arguments)]
+fn route_to_treasury<'a>(
+    program_id: &Pubkey,
+    treasury: &AccountInfo<'a>,
+    treasury_vault: &AccountInfo<'a>,
+    source_token: &AccountInfo<'a>,
+    source_authority: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+    source: TreasurySource,
+    amount_e6: i64,
+) -> ProgramResult {
+    if amount_e6 <= 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    
+    let mut state = load_treasury(program_id, treasury, treasury_vault)?;
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            source_token.key,
+            treasury_vault.key,
+            source_authority.key,
+            &[],
+            amount_e6 as u64,
+        )?,
+        &[
+            source_token.clone(),
+            treasury_vault.clone(),
+            source_authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    
+    state.record_inflow(source, amount_e6);
+    state.serialize(&mut &mut treasury.data.borrow_mut()[..])?;
+    
+    msg!("🏦 TREASURY_INFLOW");
+    msg!("  Source: {:?}", source);
+    msg!("  Amount: {}", amount_e6);
+    msg!("  Source total: {}", state.total_routed_e6[source as usize]);
+    
+    Ok(())
+}
+
+/// Create the Treasury PDA (Admin only)
+fn process_initialize_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let treasury_vault = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let seeds = Treasury::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+
+    validate_accounts! {
+        authority: signer, writable;
+        treasury: writable, pda = (&seeds_refs, program_id);
+        treasury_vault: owner = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    assert_config_admin(program_id, authority, fund_config)?;
+
+    if !treasury.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Only the Treasury PDA may move vault funds
+    let vault_state = spl_token::state::Account::unpack(&treasury_vault.data.borrow())?;
+    if vault_state.owner != *treasury.key {
+        return Err(FundError::TreasuryVaultMismatch.into());
+    }
+
+    let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let current_ts = get_current_timestamp()?;
+    let rent = Rent::get()?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            treasury.key,
+            rent.minimum_balance(Treasury::SIZE),
+            Treasury::SIZE as u64,
+            program_id,
+        ),
+        &[authority.clone(), treasury.clone(), system_program.clone()],
+        &[&[TREASURY_SEED, &[bump]]],
+    )?;
+
+    let state = Treasury::new(*treasury_vault.key, bump, current_ts);
+    state.serialize(&mut &mut treasury.data.borrow_mut()[..])?;
+
+    msg!("✅ TREASURY_INITIALIZED");
+    msg!("  Treasury: {}", treasury.key);
+    msg!("  Vault: {}", treasury_vault.key);
+
+    Ok(())
+}
+
+/// Schedule a timelocked treasury withdrawal (Admin only)
+fn process_schedule_treasury_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ScheduleTreasuryWithdrawalArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        treasury: writable, owner = program_id;
+    }
+
+    assert_config_admin(program_id, authority, fund_config)?;
+
+    if args.amount_e6 <= 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+
+    let mut state = Treasury::try_from_slice(&treasury.data.borrow())?;
+    if state.discriminator != TREASURY_DISCRIMINATOR {
+        return Err(FundError::TreasuryNotInitialized.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+    state.schedule_withdrawal(args.amount_e6, args.destination, current_ts);
+    state.serialize(&mut &mut treasury.data.borrow_mut()[..])?;
+
+    msg!("⏳ TREASURY_WITHDRAWAL_SCHEDULED");
+    msg!("  Amount: {}", args.amount_e6);
+    msg!("  Destination: {}", args.destination);
+    msg!("  Executable at: {}", state.withdrawal_executable_at);
+
+    Ok(())
+}
+
+/// Cancel the pending treasury withdrawal (Admin only)
+fn process_cancel_treasury_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        treasury: writable, owner = program_id;
+    }
+
+    assert_config_admin(program_id, authority, fund_config)?;
+
+    let mut state = Treasury::try_from_slice(&treasury.data.borrow())?;
+    if state.discriminator != TREASURY_DISCRIMINATOR {
+        return Err(FundError::TreasuryNotInitialized.into());
+    }
+
+    if !state.has_pending_withdrawal() {
+        return Err(FundError::TreasuryWithdrawalNotScheduled.into());
+    }
+
+    state.clear_pending_withdrawal();
+    state.serialize(&mut &mut treasury.data.borrow_mut()[..])?;
+
+    msg!("✅ TREASURY_WITHDRAWAL_CANCELLED");
+
+    Ok(())
+}
+
+/// Execute the pending treasury withdrawal after the timelock (Admin only)
+fn process_withdraw_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let treasury_vault = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        treasury: writable;
+        treasury_vault: writable;
+        destination: writable;
+        token_program: key = &spl_token::id();
+    }
+
+    assert_config_admin(program_id, authority, fund_config)?;
+
+    let mut state = load_treasury(program_id, treasury, treasury_vault)?;
+
+    if !state.has_pending_withdrawal() {
+        return Err(FundError::TreasuryWithdrawalNotScheduled.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+    if !state.is_withdrawal_executable(current_ts) {
+        return Err(FundError::TimelockNotElapsed.into());
+    }
+
+    if state.pending_destination != *destination.key {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+
+    let vault_state = spl_token::state::Account::unpack(&treasury_vault.data.borrow())?;
+    if vault_state.amount < state.pending_withdrawal_e6 as u64 {
+        return Err(FundError::InsufficientBalance.into());
+    }
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            treasury_vault.key,
+            destination.key,
+            treasury.key,
+            &[],
+            state.pending_withdrawal_e6 as u64,
+        )?,
+        &[
+            treasury_vault.clone(),
+            destination.clone(),
+            treasury.clone(),
+            token_program.clone(),
+        ],
+        &[&[TREASURY_SEED, &[state.bump]]],
+    )?;
+
+    let amount = state.complete_withdrawal();
+    state.serialize(&mut &mut treasury.data.borrow_mut()[..])?;
+
+    msg!("✅ TREASURY_WITHDRAWN");
+    msg!("  Amount: {}", amount);
+    msg!("  Destination: {}", destination.key);
+    msg!("  Total withdrawn: {}", state.total_withdrawn_e6);
+
+    Ok(())
+}
+
+/// Sweep accrued prediction market protocol share into the Treasury (Admin only)
+fn process_sweep_pm_protocol_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let pm_fee_config = next_account_info(account_info_iter)?;
+    let pm_fee_vault = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+    let treasury_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        pm_fee_config: writable, owner = program_id;
+        pm_fee_vault: writable;
+        treasury: writable;
+        treasury_vault: writable;
+        token_program: key = &spl_token::id();
+    }
+
+    let mut config = PredictionMarketFeeConfig::try_from_slice(&pm_fee_config.data.borrow())?;
+    if config.discriminator != PREDICTION_MARKET_FEE_CONFIG_DISCRIMINATOR {
+        return Err(FundError::PMFeeConfigNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    if config.prediction_market_fee_vault != *pm_fee_vault.key {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+
+    // Never sweep more than the vault holds; the remainder carries over
+    let vault_state = spl_token::state::Account::unpack(&pm_fee_vault.data.borrow())?;
+    let amount_e6 = config
+        .prediction_market_unswept_protocol_income_e6()
+        .min(vault_state.amount.min(i64::MAX as u64) as i64);
+    if amount_e6 <= 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+
+    route_to_treasury(
+        program_id,
+        treasury,
+        treasury_vault,
+        pm_fee_vault,
+        pm_fee_config,
+        token_program,
+        &[&[PREDICTION_MARKET_FEE_CONFIG_SEED, &[config.bump]]],
+        TreasurySource::PredictionMarketProtocol,
+        amount_e6,
+    )?;
+
+    let current_ts = get_current_timestamp()?;
+    config.record_prediction_market_protocol_swept(amount_e6, current_ts);
+    config.serialize(&mut &mut pm_fee_config.data.borrow_mut()[..])?;
+
+    msg!("✅ PM_PROTOCOL_FEES_SWEPT");
+    msg!("  Amount: {}", amount_e6);
+    msg!("  Total swept: {}", config.prediction_market_total_protocol_swept_e6);
+
+    Ok(())
+}
+
+// =============================================================================
+// Balances Checkpoint
+// =============================================================================
+
+/// Commit a merkle root of LP balances at a snapshot (fund manager or admin)
+fn process_commit_balances_root(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CommitBalancesRootArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let operator = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let checkpoint_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let seeds = BalancesCheckpoint::seeds(fund_account.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+
+    validate_accounts! {
+        operator: signer, writable;
+        fund_account: owner = program_id;
+        fund_config: owner = program_id;
+        checkpoint_account: writable, pda = (&seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if *operator.key != fund.manager && *operator.key != config.authority {
+        return Err(FundError::Unauthorized.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+
+    let mut checkpoint = if checkpoint_account.data_is_empty() {
+        let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                operator.key,
+                checkpoint_account.key,
+                rent.minimum_balance(BalancesCheckpoint::SIZE),
+                BalancesCheckpoint::SIZE as u64,
+                program_id,
+            ),
+            &[operator.clone(), checkpoint_account.clone(), system_program.clone()],
+            &[&[BALANCES_CHECKPOINT_SEED, fund_account.key.as_ref(), &[bump]]],
+        )?;
+        BalancesCheckpoint::new(*fund_account.key, bump)
+    } else {
+        assert_owned_by(checkpoint_account, program_id)?;
+        BalancesCheckpoint::try_from_slice(&checkpoint_account.data.borrow())?
+    };
+
+    checkpoint.commit(
+        args.root,
+        args.snapshot_ts,
+        args.leaf_count,
+        args.total_shares,
+        *operator.key,
+        current_ts,
+    )?;
+    checkpoint.serialize(&mut &mut checkpoint_account.data.borrow_mut()[..])?;
+
+    msg!("✅ BALANCES_ROOT_COMMITTED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Root: {}", solana_program::hash::Hash::new_from_array(args.root));
+    msg!("  Snapshot: {}", args.snapshot_ts);
+    msg!("  Leaves: {}", args.leaf_count);
+    msg!("  Total shares: {}", args.total_shares);
+
+    Ok(())
+}
+
+/// Verify an (investor, shares) inclusion proof against the committed root (view)
+fn process_verify_balance_proof(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: VerifyBalanceProofArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let fund_account = next_account_info(account_info_iter)?;
+    let checkpoint_account = next_account_info(account_info_iter)?;
+
+    let seeds = BalancesCheckpoint::seeds(fund_account.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+
+    validate_accounts! {
+        checkpoint_account: owner = program_id, pda = (&seeds_refs, program_id);
+    }
+
+    let checkpoint = BalancesCheckpoint::try_from_slice(&checkpoint_account.data.borrow())?;
+    if checkpoint.discriminator != BALANCES_CHECKPOINT_DISCRIMINATOR || !checkpoint.is_committed() {
+        return Err(FundError::BalancesRootNotCommitted.into());
+    }
+
+    if !checkpoint.verify(&args.investor, args.shares, &args.proof) {
+        return Err(FundError::InvalidBalanceProof.into());
+    }
+
+    let result = BalanceProofResult {
+        snapshot_ts: checkpoint.snapshot_ts,
+        root: checkpoint.root,
+    };
+    set_fund_return_data(&result)?;
+
+    msg!("✅ BALANCE_PROOF_VALID");
+    msg!("  Investor: {}", args.investor);
+    msg!("  Shares: {}", args.shares);
+    msg!("  Snapshot: {}", checkpoint.snapshot_ts);
+
+    Ok(())
+}
+
+// =============================================================================
+// Pause Expiry
+// =============================================================================
+
+/// Set the max pause policy (Admin only)
+fn process_set_pause_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetPausePolicyArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer;
+        fund_config: writable, owner = program_id;
+    }
+
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    config.default_max_pause_secs = args.default_max_pause_secs;
+    config.max_pause_override_secs = args.max_pause_override_secs;
+
+    // The default must itself be a valid override
+    if config.default_max_pause() > config.max_pause_override() {
+        return Err(FundError::PauseDurationExceedsPolicy.into());
+    }
+
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+
+    msg!("✅ PAUSE_POLICY_UPDATED");
+    msg!("  Default max pause: {} secs", config.default_max_pause());
+    msg!("  Max override: {} secs", config.max_pause_override());
+
+    Ok(())
+}
+
+/// Override a fund's max pause duration within policy (Manager only)
+fn process_set_fund_max_pause(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFundMaxPauseArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        fund_config: owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+
+    if fund.is_protected() {
+        return Err(FundError::SystemFundProtected.into());
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+
+    if args.max_pause_secs > config.max_pause_override() {
+        return Err(FundError::PauseDurationExceedsPolicy.into());
+    }
+
+    fund.max_pause_secs = args.max_pause_secs;
+    fund.last_update_ts = get_current_timestamp()?;
+
+    if fund_account.data_len() < Fund::SIZE {
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+
+    msg!("✅ FUND_MAX_PAUSE_UPDATED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Max pause: {} secs", config.max_pause_for(fund.max_pause_secs));
+
+    Ok(())
+}
+
+// =============================================================================
+// Full Exit
+// =============================================================================
+
+/// Redeem an entire LP position and close it
+fn process_redeem_all(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RedeemAllArgs,
+) -> ProgramResult {
+    log_compute_units("redeem_all:start");
+    validate_memo(args.memo.as_deref())?;
+    
+    let redemption = redeem_lp_position(program_id, accounts, RedeemAmount::All, false)?;
+    
+    msg!("✅ LP_POSITION_REDEEMED_ALL");
+    msg!("  Fund: {}", accounts[1].key);
+    msg!("  Shares burned: {}", redemption.shares);
+    msg!("  USDC received: {}", redemption.payout_e6);
+    if redemption.fee_e6 > 0 {
+        msg!("  Redemption fee retained: {}", redemption.fee_e6);
+    }
+    msg!("  Current NAV: {}", redemption.nav_e6);
+    if let Some(memo) = args.memo.as_deref() {
+        attach_memo(accounts, &accounts[0], memo)?;
+    }
+    log_compute_units("redeem_all:end");
+    
+    Ok(())
+}
+
+// =============================================================================
+// Insurance Health
+// =============================================================================
+
+/// Report insurance fund health via return data (read-only)
+fn process_get_insurance_health(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: GetInsuranceHealthArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let insurance_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let daily_stats = next_account_info(account_info_iter).ok();
+    
+    let config_seeds = InsuranceFundConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        insurance_config: owner = program_id, pda = (&config_seeds_refs, program_id);
+        fund_account: owner = program_id;
+    }
+    
+    let config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+    if config.fund != *fund_account.key {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.fund_vault != *fund_vault.key {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let vault_account = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    let balance_e6 = vault_account.amount as i64;
+    let current_ts = get_current_timestamp()?;
+    
+    // Today's booked totals (if the day's stats account exists) + unbooked deltas
+    let (mut income_e6, mut outflow_e6) = (0i64, 0i64);
+    if let Some(daily_stats) = daily_stats {
+        let day = InsuranceDailyStats::day_index(current_ts);
+        let stats_seeds = InsuranceDailyStats::seeds(day);
+        let stats_seeds_refs: Vec<&[u8]> = stats_seeds.iter().map(|s| s.as_slice()).collect();
+        assert_pda(daily_stats, &stats_seeds_refs, program_id)?;
+        
+        if !daily_stats.data_is_empty() {
+            assert_owned_by(daily_stats, program_id)?;
+            let stats = InsuranceDailyStats::try_from_slice(&daily_stats.data.borrow())?;
+            if stats.discriminator != INSURANCE_DAILY_STATS_DISCRIMINATOR {
+                return Err(FundError::InvalidInsuranceFundConfig.into());
+            }
+            income_e6 = stats.liquidation_income_e6.saturating_add(stats.adl_profit_e6);
+            outflow_e6 = stats.shortfall_payout_e6;
+        }
+    }
+    let (liquidation_income, adl_profit, shortfall_payout) = config.pending_daily_deltas();
+    income_e6 = income_e6
+        .saturating_add(liquidation_income)
+        .saturating_add(adl_profit);
+    outflow_e6 = outflow_e6.saturating_add(shortfall_payout);
+    
+    let result = InsuranceHealthResult {
+        balance_e6,
+        coverage_ratio_bps: InsuranceFundConfig::coverage_ratio_bps(balance_e6, args.open_interest_e6),
+        secs_since_snapshot: current_ts.saturating_sub(config.last_snapshot_ts),
+        is_adl_in_progress: config.is_adl_in_progress,
+        income_24h_e6: income_e6,
+        outflow_24h_e6: outflow_e6,
+    };
+    set_fund_return_data(&result)?;
+    
+    msg!("Insurance Health:");
+    msg!("  Balance: {}", result.balance_e6);
+    msg!("  Open interest: {}", args.open_interest_e6);
+    msg!("  Coverage ratio (bps): {}", result.coverage_ratio_bps);
+    msg!("  Since snapshot (s): {}", result.secs_since_snapshot);
+    msg!("  ADL in progress: {}", result.is_adl_in_progress);
+    msg!("  Income today: {}", result.income_24h_e6);
+    msg!("  Outflow today: {}", result.outflow_24h_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Manager Risk
+// =============================================================================
+
+/// Set the global per-manager risk caps (Admin only)
+fn process_set_manager_risk_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetManagerRiskLimitsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let limits_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = ManagerRiskLimits::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        authority: signer, writable;
+        fund_config: writable, owner = program_id;
+        limits_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(limits_account, &seeds_refs, program_id)?;
+    
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+    if args.max_leverage > MAX_TRADE_LEVERAGE {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let mut limits = if limits_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                limits_account.key,
+                Rent::get()?.minimum_balance(ManagerRiskLimits::SIZE),
+                ManagerRiskLimits::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), limits_account.clone(), system_program.clone()],
+            &[&[MANAGER_RISK_LIMITS_SEED, &[bump]]],
+        )?;
+        ManagerRiskLimits::new(bump)
+    } else {
+        assert_owned_by(limits_account, program_id)?;
+        let limits = ManagerRiskLimits::try_from_slice(&limits_account.data.borrow())?;
+        if limits.discriminator != MANAGER_RISK_LIMITS_DISCRIMINATOR {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        limits
+    };
+    
+    limits.max_notional_e6 = args.max_notional_e6;
+    limits.max_leverage = args.max_leverage;
+    limits.updated_at = get_current_timestamp()?;
+    limits.serialize(&mut &mut limits_account.data.borrow_mut()[..])?;
+    
+    config.manager_risk_limits_enabled = args.enabled;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+    
+    msg!("✅ MANAGER_RISK_LIMITS_UPDATED");
+    msg!("  Enabled: {}", args.enabled);
+    msg!("  Max notional: {}", limits.max_notional_e6);
+    msg!("  Max leverage: {}", limits.max_leverage);
+    
+    Ok(())
+}
+
+/// Create a manager's ManagerRiskState (anyone may pay the rent)
+fn process_initialize_manager_risk_state(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let manager = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = ManagerRiskState::seeds(manager.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        payer: signer, writable;
+        state_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(state_account, &seeds_refs, program_id)?;
+    
+    if !state_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            state_account.key,
+            Rent::get()?.minimum_balance(ManagerRiskState::SIZE),
+            ManagerRiskState::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), state_account.clone(), system_program.clone()],
+        &[&[MANAGER_RISK_STATE_SEED, manager.key.as_ref(), &[bump]]],
+    )?;
+    
+    let state = ManagerRiskState::new(*manager.key, bump, get_current_timestamp()?);
+    state.serialize(&mut &mut state_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ MANAGER_RISK_STATE_CREATED");
+    msg!("  Manager: {}", manager.key);
+    
+    Ok(())
+}
+
+/// Look up a manager's ManagerRiskState by key among `accounts`
+fn find_manager_risk_state<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+    manager: &Pubkey,
+) -> Option<&'b AccountInfo<'a>> {
+    let seeds = ManagerRiskState::seeds(manager);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    accounts.iter().find(|a| *a.key == expected)
+}
+
+/// Load a ManagerRiskState for update
+fn load_manager_risk_state(
+    program_id: &Pubkey,
+    state_account: &AccountInfo,
+) -> Result<ManagerRiskState, ProgramError> {
+    validate_accounts! {
+        state_account: writable, owner = program_id;
+    }
+    let state = ManagerRiskState::try_from_slice(&state_account.data.borrow())?;
+    if state.discriminator != MANAGER_RISK_STATE_DISCRIMINATOR {
+        return Err(FundError::ManagerRiskStateRequired.into());
+    }
+    Ok(state)
+}
+
+/// Check an opening trade against the per-manager caps and book its notional
+///
+/// While limits are enabled the ManagerRiskState and ManagerRiskLimits PDAs
+/// must be passed (any position); otherwise a passed ManagerRiskState is
+/// still kept up to date. With `record = false` (simulation) nothing is written.
+fn apply_manager_risk(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &FundConfig,
+    manager: &Pubkey,
+    notional_e6: u64,
+    leverage: u8,
+    record: bool,
+) -> ProgramResult {
+    let state_account = find_manager_risk_state(program_id, accounts, manager);
+    if state_account.is_none() && !config.manager_risk_limits_enabled {
+        return Ok(());
+    }
+    let state_account = state_account.ok_or(FundError::ManagerRiskStateRequired)?;
+    let mut state = load_manager_risk_state(program_id, state_account)?;
+    
+    if config.manager_risk_limits_enabled {
+        let seeds = ManagerRiskLimits::seeds();
+        let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+        let (limits_key, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+        let limits_account = accounts
+            .iter()
+            .find(|a| *a.key == limits_key)
+            .ok_or(FundError::ManagerRiskStateRequired)?;
+        assert_owned_by(limits_account, program_id)?;
+        
+        let limits = ManagerRiskLimits::try_from_slice(&limits_account.data.borrow())?;
+        if limits.discriminator != MANAGER_RISK_LIMITS_DISCRIMINATOR {
+            return Err(FundError::ManagerRiskStateRequired.into());
+        }
+        limits.check(state.open_notional_e6, notional_e6, leverage)?;
+    }
+    
+    if record {
+        state.record_open(notional_e6, get_current_timestamp()?);
+        state.serialize(&mut &mut state_account.data.borrow_mut()[..])?;
+        
+        msg!("  Manager open notional: {}", state.open_notional_e6);
+    }
+    
+    Ok(())
+}
+
+/// Release a closed position's notional from the manager's ManagerRiskState, if passed
+fn release_manager_risk(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    manager: &Pubkey,
+    size_e6: u64,
+    price_e6: u64,
+) -> ProgramResult {
+    let state_account = match find_manager_risk_state(program_id, accounts, manager) {
+        Some(state_account) => state_account,
+        None => return Ok(()),
+    };
+    let mut state = load_manager_risk_state(program_id, state_account)?;
+    
+    let notional_e6 = ((size_e6 as u128) * (price_e6 as u128) / 1_000_000).min(u64::MAX as u128) as u64;
+    state.record_close(notional_e6, get_current_timestamp()?);
+    state.serialize(&mut &mut state_account.data.borrow_mut()[..])?;
+    
+    msg!("  Manager open notional: {}", state.open_notional_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// NAV Floor
+// =============================================================================
+
+/// Put the fund into wind-down review once its NAV falls below the deposit floor
+///
+/// Legacy-sized Fund accounts cannot hold the review flag; their deposits
+/// are still refused by the NAV floor check itself.
+fn check_nav_floor(fund: &mut Fund, fund_account: &AccountInfo, current_ts: i64) {
+    if !fund.check_nav_floor(current_ts) {
+        return;
+    }
+    
+    if !fund.fits_in(fund_account.data_len()) {
+        fund.wind_down_review_since = 0;
+        msg!("⚠️ FUND_NAV_BELOW_FLOOR");
+        msg!("  Fund: {}", fund_account.key);
+        msg!("  NAV: {}", fund.stats.current_nav_e6);
+    } else {
+        msg!("⚠️ FUND_WIND_DOWN_REVIEW");
+        msg!("  Fund: {}", fund_account.key);
+        msg!("  NAV: {} (floor {})", fund.stats.current_nav_e6, MIN_DEPOSIT_NAV_E6);
+    }
+}
+
+/// Resolve a fund's wind-down review (Admin only)
+///
+/// Either winds the fund down (closed to new deposits) or, once NAV is back
+/// above the floor, reopens deposits.
+fn process_resolve_wind_down_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ResolveWindDownReviewArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        fund_account: writable, owner = program_id;
+    }
+    
+    assert_config_admin(program_id, authority, fund_config)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_in_wind_down_review() {
+        return Err(FundError::WindDownReviewNotActive.into());
+    }
+    
+    if args.wind_down {
+        fund.is_open = false;
+    } else if fund.is_below_nav_floor() {
+        return Err(FundError::NAVBelowFloor.into());
+    }
+    
+    let review_since = fund.wind_down_review_since;
+    fund.wind_down_review_since = 0;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("✅ WIND_DOWN_REVIEW_RESOLVED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Wind down: {}", args.wind_down);
+    msg!("  In review since: {}", review_since);
+    msg!("  NAV: {}", fund.stats.current_nav_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Split Fund Creation
+// =============================================================================
+
+/// Book a new fund in FundConfig / ManagerProfile and create its Fund PDA
+///
+/// `fee_accounts` (Treasury, Treasury vault, manager's USDC account, Token
+/// Program) pay the creation fee, if one is set.
+///
+/// The fund is written with `setup_pending` set. If `fund_account` already
+/// holds this manager's pending fund (a retried transaction) it is returned
+/// unchanged.
+#[allow(clippy::too_many_arguments)]
+fn create_fund_account<'a>(
+    program_id: &Pubkey,
+    manager: &AccountInfo<'a>,
+    fund_account: &AccountInfo<'a>,
+    fund_config: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    manager_profile: &AccountInfo<'a>,
+    fee_accounts: &[&AccountInfo<'a>],
+    args: &CreateFundArgs,
+) -> Result<Fund, ProgramError> {
+    // Verify manager is signer
+    assert_signer(manager)?;
+    
+    if !fund_account.data_is_empty() {
+        let fund = load_pending_fund(program_id, manager, fund_account)?;
+        if !fund.setup_pending {
+            return Err(FundError::FundAlreadyInitialized.into());
+        }
+        msg!("Fund account already created, skipping");
+        return Ok(fund);
+    }
+    
+    // Validate fund name
+    validate_fund_name(&args.name)?;
+    
+    // Validate fee configuration
+    validate_fee_config(args.management_fee_bps, args.performance_fee_bps)?;
+    validate_expense_policy(args.expense_cap_bps, args.max_expense_payment_e6)?;
+    
+    // Load and update FundConfig
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    
+    if config.is_paused {
+        return Err(FundError::FundPaused.into());
+    }
+    
+    let rent = Rent::get()?;
+    let current_ts = get_current_timestamp()?;
+    
+    // Load or create the manager profile and enforce the per-manager limit
+    let profile_seeds = ManagerProfile::seeds(manager.key);
+    let profile_seeds_refs: Vec<&[u8]> = profile_seeds.iter().map(|s| s.as_slice()).collect();
+    let profile_bump = assert_pda(manager_profile, &profile_seeds_refs, program_id)?;
+    
+    let mut profile = if manager_profile.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                manager.key,
+                manager_profile.key,
+                rent.minimum_balance(ManagerProfile::SIZE),
+                ManagerProfile::SIZE as u64,
+                program_id,
+            ),
+            &[manager.clone(), manager_profile.clone(), system_program.clone()],
+            &[&[MANAGER_PROFILE_SEED, manager.key.as_ref(), &[profile_bump]]],
+        )?;
+        ManagerProfile::new(*manager.key, profile_bump, current_ts)
+    } else {
+        let profile = ManagerProfile::try_from_slice(&manager_profile.data.borrow())?;
+        if profile.discriminator != MANAGER_PROFILE_DISCRIMINATOR {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        profile
+    };
+    
+    if !profile.can_create_fund(config.max_funds_per_manager) {
+        msg!("❌ Manager fund limit reached: {} active", profile.active_funds);
+        return Err(FundError::ManagerFundLimitReached.into());
+    }
+    
+    // Charge the creation fee into the protocol Treasury
+    if config.fund_creation_fee_e6 > 0 {
+        let [treasury, treasury_vault, manager_usdc, token_program] = fee_accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        validate_accounts! {
+            token_program: key = &spl_token::id();
+        }
+        route_to_treasury(
+            program_id,
+            treasury,
+            treasury_vault,
+            manager_usdc,
+            manager,
+            token_program,
+            &[],
+            TreasurySource::FundCreationFee,
+            config.fund_creation_fee_e6 as i64,
+        )?;
+    }
+    
+    profile.record_fund_created(config.fund_creation_fee_e6, current_ts);
+    profile.serialize(&mut &mut manager_profile.data.borrow_mut()[..])?;
+    
+    let fund_index = config.total_funds;
+    config.total_funds = config.total_funds.saturating_add(1);
+    config.active_funds = config.active_funds.saturating_add(1);
+    
+    // Derive Fund PDA
+    let fund_seeds = Fund::seeds(manager.key, fund_index);
+    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
+    let (fund_pda, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+    
+    if fund_account.key != &fund_pda {
+        return Err(FundError::InvalidPDA.into());
+    }
+    
+    // Vault / share mint addresses are fixed now, the accounts come later
+    let vault_seeds = Fund::vault_seeds(&fund_pda);
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    let (vault_pda, _) = Pubkey::find_program_address(&vault_seeds_refs, program_id);
+    
+    let mint_seeds = Fund::share_mint_seeds(&fund_pda);
+    let mint_seeds_refs: Vec<&[u8]> = mint_seeds.iter().map(|s| s.as_slice()).collect();
+    let (mint_pda, _) = Pubkey::find_program_address(&mint_seeds_refs, program_id);
+    
+    // Create Fund account
+    let fund_space = Fund::SIZE;
+    let fund_lamports = rent.minimum_balance(fund_space);
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            manager.key,
+            fund_account.key,
+            fund_lamports,
+            fund_space as u64,
+            program_id,
+        ),
+        &[manager.clone(), fund_account.clone(), system_program.clone()],
+        &[&[FUND_SEED, manager.key.as_ref(), &fund_index.to_le_bytes(), &[fund_bump]]],
+    )?;
+    
+    // Create fee config
+    let fee_config = FeeConfig {
+        management_fee_bps: args.management_fee_bps,
+        performance_fee_bps: args.performance_fee_bps,
+        use_high_water_mark: args.use_high_water_mark,
+        fee_collection_interval: if args.fee_collection_interval > 0 {
+            args.fee_collection_interval
+        } else {
+            FeeConfig::DEFAULT_COLLECTION_INTERVAL
+        },
+    };
+    
+    // Initialize Fund
+    let mut fund = Fund::new(
+        *manager.key,
+        &args.name,
+        fund_bump,
+        vault_pda,
+        mint_pda,
+        fee_config,
+        fund_index,
+        current_ts,
+    );
+    fund.setup_pending = true;
+    fund.expense_cap_bps = args.expense_cap_bps;
+    fund.max_expense_payment_e6 = args.max_expense_payment_e6;
+    
+    if config.requires_listing_approval {
+        fund.listing_status = FundListingStatus::PendingApproval;
+    }
+    
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+    
+    Ok(fund)
+}
+
+/// Load a Fund owned by this program and managed by `manager` (signer)
+fn load_pending_fund(
+    program_id: &Pubkey,
+    manager: &AccountInfo,
+    fund_account: &AccountInfo,
+) -> Result<Fund, ProgramError> {
+    validate_accounts! {
+        manager: signer;
+        fund_account: owner = program_id;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if fund.manager != *manager.key {
+        return Err(FundError::NotFundManager.into());
+    }
+    
+    let fund_seeds = Fund::seeds(manager.key, fund.fund_index);
+    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(fund_account, &fund_seeds_refs, program_id)?;
+    
+    Ok(fund)
+}
+
+/// Share mint exists with the fund PDA as mint authority?
+fn is_share_mint_ready(share_mint: &AccountInfo, fund_pda: &Pubkey) -> bool {
+    share_mint.owner == &spl_token::id()
+        && spl_token::state::Mint::unpack(&share_mint.data.borrow())
+            .map(|mint| mint.mint_authority == Some(*fund_pda).into())
+            .unwrap_or(false)
+}
+
+/// Fund vault exists as a token account owned by the fund PDA?
+fn is_fund_vault_ready(fund_vault: &AccountInfo, fund_pda: &Pubkey) -> bool {
+    fund_vault.owner == &spl_token::id()
+        && spl_token::state::Account::unpack(&fund_vault.data.borrow())
+            .map(|vault| vault.owner == *fund_pda)
+            .unwrap_or(false)
+}
+
+/// Create and initialize the fund's share mint; no-op if already done
+fn init_share_mint<'a>(
+    program_id: &Pubkey,
+    manager: &AccountInfo<'a>,
+    fund_account: &AccountInfo<'a>,
+    fund: &Fund,
+    share_mint: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent_sysvar: &AccountInfo<'a>,
+) -> ProgramResult {
+    assert_key(share_mint, &fund.share_mint)?;
+    
+    if is_share_mint_ready(share_mint, fund_account.key) {
+        msg!("Share mint already initialized, skipping");
+        return Ok(());
+    }
+    
+    let mint_seeds = Fund::share_mint_seeds(fund_account.key);
+    let mint_seeds_refs: Vec<&[u8]> = mint_seeds.iter().map(|s| s.as_slice()).collect();
+    let mint_bump = assert_pda(share_mint, &mint_seeds_refs, program_id)?;
+    
+    // Create Share mint (SPL Token)
+    let mint_space = spl_token::state::Mint::LEN;
+    let mint_lamports = Rent::get()?.minimum_balance(mint_space);
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            manager.key,
+            share_mint.key,
+            mint_lamports,
+            mint_space as u64,
+            &spl_token::id(),
+        ),
+        &[manager.clone(), share_mint.clone(), system_program.clone()],
+        &[&[SHARE_MINT_SEED, fund_account.key.as_ref(), &[mint_bump]]],
+    )?;
+    
+    // Initialize Share mint
+    invoke_signed(
+        &spl_token::instruction::initialize_mint(
+            &spl_token::id(),
+            share_mint.key,
+            fund_account.key, // Mint authority = Fund PDA
+            Some(fund_account.key), // Freeze authority = Fund PDA
+            SHARE_MINT_DECIMALS, // 6 decimals like USDC
+        )?,
+        &[share_mint.clone(), rent_sysvar.clone()],
+        &[&[SHARE_MINT_SEED, fund_account.key.as_ref(), &[mint_bump]]],
+    )?;
+    
+    Ok(())
+}
+
+/// Create and initialize the fund's USDC vault; no-op if already done
+#[allow(clippy::too_many_arguments)]
+fn init_fund_vault<'a>(
+    program_id: &Pubkey,
+    manager: &AccountInfo<'a>,
+    fund_account: &AccountInfo<'a>,
+    fund: &Fund,
+    fund_vault: &AccountInfo<'a>,
+    usdc_mint: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent_sysvar: &AccountInfo<'a>,
+) -> ProgramResult {
+    assert_key(fund_vault, &fund.fund_vault)?;
+    
+    if is_fund_vault_ready(fund_vault, fund_account.key) {
+        msg!("Fund vault already initialized, skipping");
+        return Ok(());
+    }
+    
+    let vault_seeds = Fund::vault_seeds(fund_account.key);
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    let vault_bump = assert_pda(fund_vault, &vault_seeds_refs, program_id)?;
+    
+    // Create Fund vault (token account)
+    let vault_space = spl_token::state::Account::LEN;
+    let vault_lamports = Rent::get()?.minimum_balance(vault_space);
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            manager.key,
+            fund_vault.key,
+            vault_lamports,
+            vault_space as u64,
+            &spl_token::id(),
+        ),
+        &[manager.clone(), fund_vault.clone(), system_program.clone()],
+        &[&[FUND_VAULT_SEED, fund_account.key.as_ref(), &[vault_bump]]],
+    )?;
+    
+    // Initialize Fund vault
+    invoke_signed(
+        &spl_token::instruction::initialize_account(
+            &spl_token::id(),
+            fund_vault.key,
+            usdc_mint.key,
+            fund_account.key, // Owner = Fund PDA
+        )?,
+        &[fund_vault.clone(), usdc_mint.clone(), fund_account.clone(), rent_sysvar.clone()],
+        &[&[FUND_VAULT_SEED, fund_account.key.as_ref(), &[vault_bump]]],
+    )?;
+    
+    Ok(())
+}
+
+/// Clear `setup_pending` once the share mint and vault are in place
+///
+/// Already-finalized funds are left untouched.
+fn finalize_fund_setup(
+    fund: &mut Fund,
+    fund_account: &AccountInfo,
+    fund_vault: &AccountInfo,
+    share_mint: &AccountInfo,
+) -> ProgramResult {
+    assert_key(fund_vault, &fund.fund_vault)?;
+    assert_key(share_mint, &fund.share_mint)?;
+    
+    if !fund.setup_pending {
+        msg!("Fund setup already finalized, skipping");
+        return Ok(());
+    }
+    
+    if !is_share_mint_ready(share_mint, fund_account.key)
+        || !is_fund_vault_ready(fund_vault, fund_account.key)
+    {
+        return Err(FundError::FundSetupIncomplete.into());
+    }
+    
+    // A wrapped-SOL vault makes this a SOL-denominated fund
+    let vault_state = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    fund.sol_denominated = vault_state.mint == spl_token::native_mint::id();
+    assert_fund_vault_mint(fund, fund_vault)?;
+    fund.setup_pending = false;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(fund, Some(fund_vault))
+}
+
+/// Create the Fund PDA in setup-pending state (CreateFund step 1)
+fn process_create_fund_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CreateFundArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let manager_profile = next_account_info(account_info_iter)?;
+    let fee_accounts: Vec<&AccountInfo> = account_info_iter.take(4).collect();
+    
+    // The split flow has no vault yet; seed the fund with DepositToFund after FinalizeFund
+    if args.initial_deposit_amount.is_some_and(|amount| amount > 0) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let fund = create_fund_account(
+        program_id,
+        manager,
+        fund_account,
+        fund_config,
+        system_program,
+        manager_profile,
+        &fee_accounts,
+        &args,
+    )?;
+    
+    msg!("✅ FUND_ACCOUNT_CREATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Fund index: {}", fund.fund_index);
+    msg!("  Manager: {}", manager.key);
+    msg!("  Vault: {}", fund.fund_vault);
+    msg!("  Share mint: {}", fund.share_mint);
+    
+    Ok(())
+}
+
+/// Create the fund's share mint (CreateFund step 2)
+fn process_init_share_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+    let _token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    
+    let fund = load_pending_fund(program_id, manager, fund_account)?;
+    init_share_mint(program_id, manager, fund_account, &fund, share_mint, system_program, rent_sysvar)?;
+    
+    msg!("✅ SHARE_MINT_READY");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Share mint: {}", share_mint.key);
+    
+    Ok(())
+}
+
+/// Create the fund's USDC vault (CreateFund step 3)
+fn process_init_fund_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let usdc_mint = next_account_info(account_info_iter)?;
+    let _token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    
+    let fund = load_pending_fund(program_id, manager, fund_account)?;
+    init_fund_vault(program_id, manager, fund_account, &fund, fund_vault, usdc_mint, system_program, rent_sysvar)?;
+    
+    msg!("✅ FUND_VAULT_READY");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Vault: {}", fund_vault.key);
+    
+    Ok(())
+}
+
+/// Clear the setup-pending flag (CreateFund step 4)
+fn process_finalize_fund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        fund_account: writable;
+    }
+    
+    let mut fund = load_pending_fund(program_id, manager, fund_account)?;
+    finalize_fund_setup(&mut fund, fund_account, fund_vault, share_mint)?;
+    
+    msg!("✅ FUND_SETUP_FINALIZED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Fund index: {}", fund.fund_index);
+    msg!("  Listing status: {:?}", fund.listing_status);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Referral Reward Vault
+// =============================================================================
+
+/// Create the referral reward vault (ReferralConfig authority only)
+///
+/// A USDC token account owned by the ReferralConfig PDA; AddTradingFee routes
+/// the referrer share of collected fees into it.
+fn process_initialize_referral_reward_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let referral_config = next_account_info(account_info_iter)?;
+    let reward_vault = next_account_info(account_info_iter)?;
+    let usdc_mint = next_account_info(account_info_iter)?;
+    let _token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    
+    let config_seeds = ReferralConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    let vault_seeds = ReferralConfig::reward_vault_seeds();
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        authority: signer, writable;
+        referral_config: owner = program_id, pda = (&config_seeds_refs, program_id);
+        reward_vault: writable, pda = (&vault_seeds_refs, program_id);
+    }
+    
+    let config = ReferralConfig::try_from_slice(&referral_config.data.borrow())?;
+    if config.discriminator != REFERRAL_CONFIG_DISCRIMINATOR {
+        return Err(FundError::ReferralNotInitialized.into());
+    }
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+    
+    if !reward_vault.data_is_empty() {
+        return Err(FundError::ReferralAlreadyInitialized.into());
+    }
+    
+    let (_, vault_bump) = Pubkey::find_program_address(&vault_seeds_refs, program_id);
+    let vault_space = spl_token::state::Account::LEN;
+    let vault_lamports = Rent::get()?.minimum_balance(vault_space);
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            reward_vault.key,
+            vault_lamports,
+            vault_space as u64,
+            &spl_token::id(),
+        ),
+        &[authority.clone(), reward_vault.clone(), system_program.clone()],
+        &[&[REFERRAL_REWARD_VAULT_SEED, &[vault_bump]]],
+    )?;
+    
+    invoke_signed(
+        &spl_token::instruction::initialize_account(
+            &spl_token::id(),
+            reward_vault.key,
+            usdc_mint.key,
+            referral_config.key, // Owner = ReferralConfig PDA
+        )?,
+        &[reward_vault.clone(), usdc_mint.clone(), referral_config.clone(), rent_sysvar.clone()],
+        &[&[REFERRAL_REWARD_VAULT_SEED, &[vault_bump]]],
+    )?;
+    
+    msg!("✅ REFERRAL_REWARD_VAULT_INITIALIZED");
+    msg!("  Vault: {}", reward_vault.key);
+    msg!("  Mint: {}", usdc_mint.key);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Fund Benchmark
+// =============================================================================
+
+/// Load a benchmark SharePriceFeed and return its price if fresh
+fn load_benchmark_price(
+    program_id: &Pubkey,
+    feed_account: &AccountInfo,
+    current_ts: i64,
+) -> Result<(SharePriceFeed, i64), ProgramError> {
+    if feed_account.owner != program_id {
+        return Err(FundError::InvalidBenchmarkFeed.into());
+    }
+    let feed = SharePriceFeed::try_from_slice(&feed_account.data.borrow())
+        .map_err(|_| ProgramError::from(FundError::InvalidBenchmarkFeed))?;
+    if feed.discriminator != SHARE_PRICE_FEED_DISCRIMINATOR {
+        return Err(FundError::InvalidBenchmarkFeed.into());
+    }
+    
+    match feed.get_price_no_older_than(current_ts, BENCHMARK_MAX_AGE_SECS) {
+        Some((price, _)) if price > 0 => Ok((feed, price)),
+        _ => Err(FundError::BenchmarkPriceStale.into()),
+    }
+}
+
+/// Read a fund's benchmark price; the feed is looked up by address among `accounts`
+fn read_benchmark_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    benchmark_feed: &Pubkey,
+    current_ts: i64,
+) -> Result<i64, ProgramError> {
+    let feed_account = accounts
+        .iter()
+        .find(|a| a.key == benchmark_feed)
+        .ok_or(FundError::BenchmarkFeedRequired)?;
+    let (_, price) = load_benchmark_price(program_id, feed_account, current_ts)?;
+    Ok(price)
+}
+
+/// Set or clear a fund's benchmark (Fund manager only)
+fn process_set_fund_benchmark(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFundBenchmarkArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let benchmark_feed = account_info_iter.next();
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    
+    // Switching benchmarks mid-period would move the hurdle of an accrued fee
+    fund.accrue_aum(current_ts);
+    let (_, perf_accrued) = fund.calculate_fees(current_ts)?;
+    if perf_accrued > 0 {
+        return Err(FundError::BenchmarkChangeWithAccruedFee.into());
+    }
+    
+    match benchmark_feed {
+        Some(feed_account) => {
+            let (feed, price) = load_benchmark_price(program_id, feed_account, current_ts)?;
+            if feed.fund == *fund_account.key {
+                return Err(FundError::InvalidBenchmarkFeed.into());
+            }
+            fund.benchmark_feed = *feed_account.key;
+            fund.benchmark_base_price_e6 = price;
+            fund.benchmark_relative_fee = args.relative_fee;
+        }
+        None => {
+            if args.relative_fee {
+                return Err(FundError::BenchmarkFeedRequired.into());
+            }
+            fund.benchmark_feed = Pubkey::default();
+            fund.benchmark_base_price_e6 = 0;
+            fund.benchmark_relative_fee = false;
+        }
+    }
+    
+    fund.last_update_ts = current_ts;
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("✅ FUND_BENCHMARK_SET");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Benchmark feed: {}", fund.benchmark_feed);
+    msg!("  Base price: {}", fund.benchmark_base_price_e6);
+    msg!("  Relative fee: {}", fund.benchmark_relative_fee);
+    msg!("  Timestamp: {}", current_ts);
+    
+    Ok(())
+}
+
+// =============================================================================
+// NAV Blackout
+// =============================================================================
+
+/// Set the bounds for per-fund blackout windows (Admin only)
+fn process_set_blackout_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetBlackoutPolicyArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let policy_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = BlackoutPolicy::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        authority: signer, writable;
+        policy_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(policy_account, &seeds_refs, program_id)?;
+    
+    assert_config_admin(program_id, authority, fund_config)?;
+    
+    if args.min_threshold_bps as u64 > BPS_DENOMINATOR {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let mut policy = if policy_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                policy_account.key,
+                Rent::get()?.minimum_balance(BlackoutPolicy::SIZE),
+                BlackoutPolicy::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), policy_account.clone(), system_program.clone()],
+            &[&[BLACKOUT_POLICY_SEED, &[bump]]],
+        )?;
+        BlackoutPolicy::new(bump)
+    } else {
+        assert_owned_by(policy_account, program_id)?;
+        let policy = BlackoutPolicy::try_from_slice(&policy_account.data.borrow())?;
+        if policy.discriminator != BLACKOUT_POLICY_DISCRIMINATOR {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        policy
+    };
+    
+    policy.max_window_secs = args.max_window_secs;
+    policy.min_threshold_bps = args.min_threshold_bps;
+    policy.updated_at = get_current_timestamp()?;
+    policy.serialize(&mut &mut policy_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ BLACKOUT_POLICY_UPDATED");
+    msg!("  Max window: {} secs", policy.max_window_secs);
+    msg!("  Min threshold: {} bps", policy.min_threshold_bps);
+    
+    Ok(())
+}
+
+/// Configure a fund's deposit/redemption blackout within policy (Manager only)
+fn process_set_fund_blackout(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFundBlackoutArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let policy_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = BlackoutPolicy::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        policy_account: owner = program_id, pda = (&seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    
+    if args.window_secs > 0 {
+        let policy = BlackoutPolicy::try_from_slice(&policy_account.data.borrow())?;
+        if policy.discriminator != BLACKOUT_POLICY_DISCRIMINATOR {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        if !policy.allows(args.window_secs, args.pnl_threshold_bps) {
+            return Err(FundError::BlackoutOutsidePolicy.into());
+        }
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    fund.blackout_secs = args.window_secs;
+    fund.blackout_pnl_threshold_bps = args.pnl_threshold_bps;
+    fund.last_update_ts = current_ts;
+    
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("✅ FUND_BLACKOUT_UPDATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Window: {} secs", fund.blackout_secs);
+    msg!("  PnL threshold: {} bps", fund.blackout_pnl_threshold_bps);
+    msg!("  Timestamp: {}", current_ts);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Share Freeze
+// =============================================================================
+
+/// Load the fund behind a freeze/thaw and authorize the signer
+///
+/// Returns the fund and whether the signer acted as admin (not manager).
+fn load_fund_for_freeze(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    fund_account: &AccountInfo,
+    share_mint: &AccountInfo,
+    fund_config: &AccountInfo,
+) -> Result<(Fund, bool), ProgramError> {
+    validate_accounts! {
+        authority: signer;
+        fund_account: owner = program_id;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    validate_accounts! {
+        share_mint: key = &fund.share_mint;
+    }
+    
+    if fund.is_manager(authority.key) {
+        return Ok((fund, false));
+    }
+    assert_config_admin(program_id, authority, fund_config)?;
+    Ok((fund, true))
+}
+
+/// Freeze a share token account with a compliance reason code
+fn process_freeze_investor_shares(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: FreezeInvestorSharesArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+    let investor_shares = next_account_info(account_info_iter)?;
+    let freeze_record = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let record_seeds = ShareFreezeRecord::seeds(investor_shares.key);
+    let record_seeds_refs: Vec<&[u8]> = record_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        authority: writable;
+        investor_shares: writable;
+        freeze_record: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    let record_bump = assert_pda(freeze_record, &record_seeds_refs, program_id)?;
+    
+    if args.reason == ShareFreezeReason::None {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let (fund, by_admin) = load_fund_for_freeze(program_id, authority, fund_account, share_mint, fund_config)?;
+    
+    let token_account = spl_token::state::Account::unpack(&investor_shares.data.borrow())?;
+    if token_account.mint != fund.share_mint {
+        return Err(FundError::InvalidMint.into());
+    }
+    if token_account.is_frozen() {
+        return Err(FundError::SharesAlreadyFrozen.into());
+    }
+    
+    let (freeze_authority, freeze_authority_seeds) = share_authority(program_id, accounts, fund_account, &fund)?;
+    let freeze_authority_refs: Vec<&[u8]> = freeze_authority_seeds.iter().map(|s| s.as_slice()).collect();
+    invoke_signed(
+        &spl_token::instruction::freeze_account(
+            &spl_token::id(),
+            investor_shares.key,
+            share_mint.key,
+            freeze_authority.key,
+            &[],
+        )?,
+        &[investor_shares.clone(), share_mint.clone(), freeze_authority.clone(), token_program.clone()],
+        &[&freeze_authority_refs],
+    )?;
+    
+    let mut record = if freeze_record.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                freeze_record.key,
+                Rent::get()?.minimum_balance(ShareFreezeRecord::SIZE),
+                ShareFreezeRecord::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), freeze_record.clone(), system_program.clone()],
+            &[&[SHARE_FREEZE_RECORD_SEED, investor_shares.key.as_ref(), &[record_bump]]],
+        )?;
+        ShareFreezeRecord::new(*fund_account.key, *investor_shares.key, token_account.owner, record_bump)
+    } else {
+        assert_owned_by(freeze_record, program_id)?;
+        let record = ShareFreezeRecord::try_from_slice(&freeze_record.data.borrow())?;
+        if record.discriminator != SHARE_FREEZE_RECORD_DISCRIMINATOR {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        record
+    };
+    
+    let current_ts = get_current_timestamp()?;
+    record.owner = token_account.owner;
+    record.record_freeze(args.reason, *authority.key, current_ts);
+    record.serialize(&mut &mut freeze_record.data.borrow_mut()[..])?;
+    
+    msg!("🧊 INVESTOR_SHARES_FROZEN");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Token account: {}", investor_shares.key);
+    msg!("  Owner: {}", token_account.owner);
+    msg!("  Reason: {:?}", args.reason);
+    msg!("  By admin: {}", by_admin);
+    msg!("  Frozen at: {}", current_ts);
+    
+    Ok(())
+}
+
+/// Thaw a frozen share token account
+fn process_thaw_investor_shares(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+    let investor_shares = next_account_info(account_info_iter)?;
+    let freeze_record = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    let record_seeds = ShareFreezeRecord::seeds(investor_shares.key);
+    let record_seeds_refs: Vec<&[u8]> = record_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        investor_shares: writable;
+        freeze_record: writable, owner = program_id, pda = (&record_seeds_refs, program_id);
+        token_program: key = &spl_token::id();
+    }
+    
+    let (fund, by_admin) = load_fund_for_freeze(program_id, authority, fund_account, share_mint, fund_config)?;
+    
+    let mut record = ShareFreezeRecord::try_from_slice(&freeze_record.data.borrow())?;
+    if record.discriminator != SHARE_FREEZE_RECORD_DISCRIMINATOR || record.fund != *fund_account.key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !record.is_frozen {
+        return Err(FundError::SharesNotFrozen.into());
+    }
+    
+    // A manager can't lift a freeze the admin applied
+    if !by_admin && record.frozen_by != fund.manager {
+        return Err(FundError::AdminFreezeRequiresAdmin.into());
+    }
+    
+    let (freeze_authority, freeze_authority_seeds) = share_authority(program_id, accounts, fund_account, &fund)?;
+    let freeze_authority_refs: Vec<&[u8]> = freeze_authority_seeds.iter().map(|s| s.as_slice()).collect();
+    invoke_signed(
+        &spl_token::instruction::thaw_account(
+            &spl_token::id(),
+            investor_shares.key,
+            share_mint.key,
+            freeze_authority.key,
+            &[],
+        )?,
+        &[investor_shares.clone(), share_mint.clone(), freeze_authority.clone(), token_program.clone()],
+        &[&freeze_authority_refs],
+    )?;
+    
+    let current_ts = get_current_timestamp()?;
+    record.record_thaw(*authority.key, current_ts);
+    record.serialize(&mut &mut freeze_record.data.borrow_mut()[..])?;
+    
+    msg!("🔥 INVESTOR_SHARES_THAWED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Token account: {}", investor_shares.key);
+    msg!("  Last reason: {:?}", record.reason);
+    msg!("  By admin: {}", by_admin);
+    msg!("  Thawed at: {}", current_ts);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Insurance Yield
+// =============================================================================
+
+/// Load the insurance Fund behind an admin yield-venue instruction
+fn load_insurance_fund_for_yield(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    fund_config: &AccountInfo,
+    fund_account: &AccountInfo,
+) -> Result<Fund, ProgramError> {
+    assert_config_admin(program_id, authority, fund_config)?;
+    assert_owned_by(fund_account, program_id)?;
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR || !fund.is_insurance_fund {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(fund)
+}
+
+/// Load the InsuranceYieldVenue PDA of an insurance fund
+fn load_insurance_yield_venue(
+    program_id: &Pubkey,
+    venue_account: &AccountInfo,
+    fund_key: &Pubkey,
+) -> Result<InsuranceYieldVenue, ProgramError> {
+    assert_owned_by(venue_account, program_id)?;
+    let venue = InsuranceYieldVenue::try_from_slice(&venue_account.data.borrow())?;
+    if venue.discriminator != INSURANCE_YIELD_VENUE_DISCRIMINATOR || venue.fund != *fund_key {
+        return Err(FundError::InvalidYieldVenue.into());
+    }
+    Ok(venue)
+}
+
+/// Move up to `amount_e6` from the venue back into the insurance vault
+///
+/// Returns the amount recalled (capped at the venue balance). Principal is
+/// repaid first; the excess is booked as fund PnL. Caller packs both accounts.
+#[allow(clippy::too_many_arguments)]
+fn recall_insurance_capital<'a>(
+    fund: &mut Fund,
+    fund_account: &AccountInfo<'a>,
+    fund_vault: &AccountInfo<'a>,
+    venue: &mut InsuranceYieldVenue,
+    venue_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount_e6: i64,
+    current_ts: i64,
+) -> Result<i64, ProgramError> {
+    assert_key(venue_vault, &venue.venue_vault)?;
+    assert_key(fund_vault, &fund.fund_vault)?;
+    assert_fund_vault_mint(fund, fund_vault)?;
+    
+    let venue_state = spl_token::state::Account::unpack(&venue_vault.data.borrow())?;
+    let recalled = amount_e6.min(fund.token_amount_to_e6(venue_state.amount)?);
+    if recalled <= 0 {
+        return Ok(0);
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            venue_vault.key,
+            fund_vault.key,
+            fund_account.key,
+            &[],
+            fund.e6_to_token_amount(recalled)?,
+        )?,
+        &[venue_vault.clone(), fund_vault.clone(), fund_account.clone(), token_program.clone()],
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund.bump]]],
+    )?;
+    
+    let (deployed, yield_e6) = venue.record_recall(recalled, fund.deployed_capital_e6)?;
+    fund.deployed_capital_e6 = deployed;
+    if yield_e6 > 0 {
+        fund.accrue_aum(current_ts);
+        fund.record_pnl(yield_e6)?;
+    }
+    fund.last_update_ts = current_ts;
+    venue.last_update_ts = current_ts;
+    
+    msg!("✅ INSURANCE_CAPITAL_RECALLED");
+    msg!("  Amount: {}", recalled);
+    msg!("  Yield: {}", yield_e6);
+    msg!("  Still deployed: {}", deployed);
+    
+    Ok(recalled)
+}
+
+/// CoverShortfall hook: recall deployed capital if the venue accounts were passed
+fn recall_for_shortfall<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    fund_account: &AccountInfo<'a>,
+    fund_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    needed_e6: i64,
+) -> Result<i64, ProgramError> {
+    let seeds = InsuranceYieldVenue::seeds(fund_account.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (venue_key, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let Some(venue_account) = accounts.iter().find(|a| *a.key == venue_key) else {
+        return Ok(0);
+    };
+    
+    let mut venue = load_insurance_yield_venue(program_id, venue_account, fund_account.key)?;
+    let venue_vault = accounts
+        .iter()
+        .find(|a| *a.key == venue.venue_vault)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    assert_writable(venue_account)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    let recalled = recall_insurance_capital(
+        &mut fund,
+        fund_account,
+        fund_vault,
+        &mut venue,
+        venue_vault,
+        token_program,
+        needed_e6,
+        get_current_timestamp()?,
+    )?;
+    if recalled > 0 {
+        fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+        venue.serialize(&mut &mut venue_account.data.borrow_mut()[..])?;
+    }
+    Ok(recalled)
+}
+
+/// Whitelist the insurance fund's yield venue (Admin only)
+fn process_set_insurance_yield_venue(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetInsuranceYieldVenueArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let venue_account = next_account_info(account_info_iter)?;
+    let venue_vault = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        authority: signer, writable;
+        venue_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = load_insurance_fund_for_yield(program_id, authority, fund_config, fund_account)?;
+    
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    if args.max_deploy_bps > MAX_INSURANCE_DEPLOY_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Venue deposits must stay recallable by the Fund PDA
+    let vault_state = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    let venue_valid = venue_vault.owner == &spl_token::id()
+        && venue_vault.key != fund_vault.key
+        && spl_token::state::Account::unpack(&venue_vault.data.borrow())
+            .map(|v| v.owner == *fund_account.key && v.mint == vault_state.mint && v.delegate.is_none())
+            .unwrap_or(false);
+    if !venue_valid {
+        return Err(FundError::InvalidYieldVenue.into());
+    }
+    
+    let seeds = InsuranceYieldVenue::seeds(fund_account.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let bump = assert_pda(venue_account, &seeds_refs, program_id)?;
+    
+    let mut venue = if venue_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                venue_account.key,
+                Rent::get()?.minimum_balance(InsuranceYieldVenue::SIZE),
+                InsuranceYieldVenue::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), venue_account.clone(), system_program.clone()],
+            &[&[INSURANCE_YIELD_VENUE_SEED, fund_account.key.as_ref(), &[bump]]],
+        )?;
+        InsuranceYieldVenue::new(*fund_account.key, *venue_vault.key, args.max_deploy_bps, bump)
+    } else {
+        let venue = load_insurance_yield_venue(program_id, venue_account, fund_account.key)?;
+        if venue.venue_vault != *venue_vault.key && fund.deployed_capital_e6 > 0 {
+            return Err(FundError::InsuranceCapitalDeployed.into());
+        }
+        venue
+    };
+    
+    venue.venue_vault = *venue_vault.key;
+    venue.max_deploy_bps = args.max_deploy_bps;
+    venue.last_update_ts = get_current_timestamp()?;
+    venue.serialize(&mut &mut venue_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ INSURANCE_YIELD_VENUE_SET");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Venue account: {}", venue_vault.key);
+    msg!("  Max deploy: {} bps", venue.max_deploy_bps);
+    
+    Ok(())
+}
+
+/// Deploy idle insurance capital to the whitelisted venue (Admin only)
+fn process_deploy_insurance_capital(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: InsuranceCapitalArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let venue_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let venue_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        authority: signer, writable;
+        fund_account: writable;
+        venue_account: writable;
+        fund_vault: writable;
+        venue_vault: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    if args.amount_e6 <= 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    
+    let mut fund = load_insurance_fund_for_yield(program_id, authority, fund_config, fund_account)?;
+    let mut venue = load_insurance_yield_venue(program_id, venue_account, fund_account.key)?;
+    
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+        venue_vault: key = &venue.venue_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let vault_state = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    let vault_balance_e6 = fund.token_amount_to_e6(vault_state.amount)?;
+    let capacity = venue.deploy_capacity_e6(vault_balance_e6, fund.deployed_capital_e6);
+    if args.amount_e6 > capacity {
+        msg!("Deploy {} exceeds capacity {}", args.amount_e6, capacity);
+        return Err(FundError::InsuranceDeployLimitExceeded.into());
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            fund_vault.key,
+            venue_vault.key,
+            fund_account.key,
+            &[],
+            fund.e6_to_token_amount(args.amount_e6)?,
+        )?,
+        &[fund_vault.clone(), venue_vault.clone(), fund_account.clone(), token_program.clone()],
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund.bump]]],
+    )?;
+    
+    let current_ts = get_current_timestamp()?;
+    fund.deployed_capital_e6 = venue.record_deploy(args.amount_e6, fund.deployed_capital_e6)?;
+    fund.last_update_ts = current_ts;
+    venue.last_update_ts = current_ts;
+    
+    // Fund accounts that predate deployed_capital_e6 need to grow first
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, authority, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    venue.serialize(&mut &mut venue_account.data.borrow_mut()[..])?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    
+    msg!("✅ INSURANCE_CAPITAL_DEPLOYED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Amount: {}", args.amount_e6);
+    msg!("  Deployed: {}", fund.deployed_capital_e6);
+    
+    Ok(())
+}
+
+/// Recall insurance capital from the venue (Admin only)
+fn process_recall_insurance_capital(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: InsuranceCapitalArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let venue_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let venue_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        authority: signer;
+        fund_account: writable;
+        venue_account: writable;
+        fund_vault: writable;
+        venue_vault: writable;
+        token_program: key = &spl_token::id();
+    }
+    
+    if args.amount_e6 < 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    
+    let mut fund = load_insurance_fund_for_yield(program_id, authority, fund_config, fund_account)?;
+    let mut venue = load_insurance_yield_venue(program_id, venue_account, fund_account.key)?;
+    
+    let amount_e6 = if args.amount_e6 == 0 { i64::MAX } else { args.amount_e6 };
+    let recalled = recall_insurance_capital(
+        &mut fund,
+        fund_account,
+        fund_vault,
+        &mut venue,
+        venue_vault,
+        token_program,
+        amount_e6,
+        get_current_timestamp()?,
+    )?;
+    if recalled == 0 {
+        return Err(FundError::InsufficientBalance.into());
+    }
+    
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    venue.serialize(&mut &mut venue_account.data.borrow_mut()[..])?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    
+    Ok(())
+}
+
+// =============================================================================
+// ADL Event Ledger
+// =============================================================================
+
+/// Load the ADLEvent PDA of an episode
+fn load_adl_event(
+    program_id: &Pubkey,
+    adl_event: &AccountInfo,
+    episode: u64,
+) -> Result<ADLEvent, ProgramError> {
+    let seeds = ADLEvent::seeds(episode);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    validate_accounts! {
+        adl_event: writable, owner = program_id, pda = (&seeds_refs, program_id);
+    }
+    
+    let event = ADLEvent::try_from_slice(&adl_event.data.borrow())?;
+    if event.discriminator != ADL_EVENT_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(event)
+}
+
+/// Insurance fund value at its NAV TWAP
+///
+/// The Fund account must be the insurance fund.
+fn insurance_twap_value(fund_account: &AccountInfo, config: &InsuranceFundConfig) -> Result<i64, ProgramError> {
+    if fund_account.key != &config.fund {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    Ok(fund.twap_value_e6(get_current_timestamp()?))
+}
+
+/// Create the ADLEvent for a newly started episode with its trigger metrics
+///
+/// `twap_balance_e6` is the insurance fund's TWAP value that confirmed the
+/// trigger, so the recorded reason matches the one that started the episode.
+#[allow(clippy::too_many_arguments)]
+fn open_adl_event<'a>(
+    program_id: &Pubkey,
+    config: &InsuranceFundConfig,
+    adl_event: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    fund_vault: &AccountInfo<'a>,
+    twap_balance_e6: Option<i64>,
+    current_ts: i64,
+) -> ProgramResult {
+    let seeds = ADLEvent::seeds(config.adl_trigger_count);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        adl_event: writable;
+        payer: signer, writable;
+        system_program: key = &solana_program::system_program::id();
+        fund_vault: owner = &spl_token::id();
+    }
+    let bump = assert_pda(adl_event, &seeds_refs, program_id)?;
+    
+    // Metrics come from the insurance fund's own vault
+    let vault_state = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    if vault_state.owner != config.fund {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            adl_event.key,
+            Rent::get()?.minimum_balance(ADLEvent::SIZE),
+            ADLEvent::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), adl_event.clone(), system_program.clone()],
+        &[&[ADL_EVENT_SEED, &config.adl_trigger_count.to_le_bytes(), &[bump]]],
+    )?;
+    
+    let event = ADLEvent::open(config, vault_state.amount as i64, twap_balance_e6, current_ts, bump);
+    event.serialize(&mut &mut adl_event.data.borrow_mut()[..])?;
+    
+    msg!("⚠️ ADL_EVENT_OPENED");
+    msg!("  Episode: {}", event.episode);
+    msg!("  Reason: {:?}", event.trigger_reason);
+    msg!("  Balance: {}", event.balance_at_trigger_e6);
+    msg!("  1h ago balance: {}", event.balance_1h_ago_e6);
+    msg!("  Threshold: {}", event.adl_threshold_e6);
+    msg!("  Pending shortfall: {}", event.amount_socialized_e6);
+    
+    Ok(())
+}
+
+/// Book an uncovered shortfall: to the open episode's ADLEvent if passed,
+/// otherwise as pending for the next episode
+fn record_adl_shortfall(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    insurance_config: &AccountInfo,
+    remaining_e6: i64,
+) -> ProgramResult {
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    
+    if config.is_adl_in_progress {
+        let seeds = ADLEvent::seeds(config.adl_trigger_count);
+        let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+        let (event_key, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+        if let Some(adl_event) = accounts.iter().find(|a| *a.key == event_key) {
+            let mut event = load_adl_event(program_id, adl_event, config.adl_trigger_count)?;
+            event.record_socialized(remaining_e6)?;
+            event.serialize(&mut &mut adl_event.data.borrow_mut()[..])?;
+            msg!("  ADL episode {} socialized: {}", event.episode, event.amount_socialized_e6);
+        }
+        return Ok(());
+    }
+    
+    config.pending_adl_shortfall_e6 = safe_add_i64(config.pending_adl_shortfall_e6, remaining_e6)?;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Evaluate the ADL trigger and start ADL in the same step (keeper)
+fn process_trigger_adl_if_needed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let keeper = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let insurance_config = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let adl_event = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        keeper: signer, writable;
+        fund_account: owner = program_id;
+        insurance_config: writable, owner = program_id;
+    }
+    
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    validate_accounts! {
+        fund_account: key = &config.fund;
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let vault_state = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    let balance_e6 = vault_state.amount as i64;
+    let twap_balance_e6 = fund.twap_value_e6(get_current_timestamp()?);
+    let reason = config.adl_start_reason(balance_e6, Some(twap_balance_e6));
+    
+    let triggered = reason != ADLTriggerReason::None && !config.is_adl_in_progress;
+    if triggered {
+        let current_ts = get_current_timestamp()?;
+        config.set_adl_in_progress(true);
+        open_adl_event(
+            program_id,
+            &config,
+            adl_event,
+            keeper,
+            system_program,
+            fund_vault,
+            Some(twap_balance_e6),
+            current_ts,
+        )?;
+        config.pending_adl_shortfall_e6 = 0;
+        config.last_update_ts = current_ts;
+        config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
+        
+        msg!("⚠️ ADL_TRIGGERED");
+        msg!("  Episode: {}", config.adl_trigger_count);
+        msg!("  Reason: {:?}", reason);
+        msg!("  Triggered by: {}", keeper.key);
+        msg!("  LP deposits and redemptions are now paused");
+    } else if config.is_adl_in_progress {
+        msg!("ADL already in progress (episode {})", config.adl_trigger_count);
+    } else {
+        msg!("No ADL required, balance: {}", balance_e6);
+    }
+    
+    let result = TriggerADLResult {
+        triggered,
+        reason,
+        episode: config.adl_trigger_count,
+        balance_e6,
+    };
+    set_fund_return_data(&result)?;
+    
+    Ok(())
+}
+
+// =============================================================================
+// Square Fund Stats
+// =============================================================================
+
+/// Book a SquarePayment in the SquareFundStats / SquareCreatorStats PDAs
+///
+/// Both are optional, except that a platform share landing in the
+/// program-owned square fund vault must always be booked.
+#[allow(clippy::too_many_arguments)]
+fn record_square_stats<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    payer: &AccountInfo<'a>,
+    square_fund_vault: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    creator: &Pubkey,
+    payment_type: SquarePaymentType,
+    amount_e6: i64,
+    creator_amount_e6: i64,
+    platform_amount_e6: i64,
+    to_square_vault: bool,
+    current_ts: i64,
+) -> ProgramResult {
+    let stats_seeds = SquareFundStats::seeds();
+    let stats_seeds_refs: Vec<&[u8]> = stats_seeds.iter().map(|s| s.as_slice()).collect();
+    let (stats_key, _) = Pubkey::find_program_address(&stats_seeds_refs, program_id);
+    let vault_seeds = SquareFundStats::vault_seeds();
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    let (vault_key, _) = Pubkey::find_program_address(&vault_seeds_refs, program_id);
+    
+    match accounts.iter().find(|a| *a.key == stats_key) {
+        Some(stats_account) => {
+            let mut stats = load_square_fund_stats(program_id, stats_account)?;
+            let into_vault = to_square_vault && *square_fund_vault.key == stats.square_fund_vault;
+            stats.record_payment(payment_type, amount_e6, creator_amount_e6, platform_amount_e6, into_vault, current_ts)?;
+            stats.serialize(&mut &mut stats_account.data.borrow_mut()[..])?;
+        }
+        None if to_square_vault && *square_fund_vault.key == vault_key => {
+            return Err(FundError::SquareFundStatsRequired.into());
+        }
+        None => {}
+    }
+    
+    let creator_seeds = SquareCreatorStats::seeds(creator);
+    let creator_seeds_refs: Vec<&[u8]> = creator_seeds.iter().map(|s| s.as_slice()).collect();
+    let (creator_key, creator_bump) = Pubkey::find_program_address(&creator_seeds_refs, program_id);
+    if let Some(creator_stats) = accounts.iter().find(|a| *a.key == creator_key) {
+        assert_writable(creator_stats)?;
+        let mut stats = if creator_stats.data_is_empty() {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    creator_stats.key,
+                    Rent::get()?.minimum_balance(SquareCreatorStats::SIZE),
+                    SquareCreatorStats::SIZE as u64,
+                    program_id,
+                ),
+                &[payer.clone(), creator_stats.clone(), system_program.clone()],
+                &[&[SQUARE_CREATOR_STATS_SEED, creator.as_ref(), &[creator_bump]]],
+            )?;
+            SquareCreatorStats::new(*creator, creator_bump, current_ts)
+        } else {
+            assert_owned_by(creator_stats, program_id)?;
+            let stats = SquareCreatorStats::try_from_slice(&creator_stats.data.borrow())?;
+            if stats.discriminator != SQUARE_CREATOR_STATS_DISCRIMINATOR {
+                return Err(FundError::InvalidFundAccount.into());
+            }
+            stats
+        };
+        stats.record_payment(amount_e6, creator_amount_e6, current_ts)?;
+        stats.serialize(&mut &mut creator_stats.data.borrow_mut()[..])?;
+    }
+    
+    Ok(())
+}
+
+/// Load the SquareFundStats PDA
+fn load_square_fund_stats(
+    program_id: &Pubkey,
+    stats_account: &AccountInfo,
+) -> Result<SquareFundStats, ProgramError> {
+    validate_accounts! {
+        stats_account: writable, owner = program_id;
+    }
+    let stats = SquareFundStats::try_from_slice(&stats_account.data.borrow())?;
+    if stats.discriminator != SQUARE_FUND_STATS_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(stats)
+}
+
+/// Create the SquareFundStats ledger and its vault (Admin only)
+fn process_initialize_square_fund_stats(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let stats_account = next_account_info(account_info_iter)?;
+    let square_fund_vault = next_account_info(account_info_iter)?;
+    let usdc_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    
+    let stats_seeds = SquareFundStats::seeds();
+    let stats_seeds_refs: Vec<&[u8]> = stats_seeds.iter().map(|s| s.as_slice()).collect();
+    let vault_seeds = SquareFundStats::vault_seeds();
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        authority: signer, writable;
+        stats_account: writable;
+        square_fund_vault: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    let stats_bump = assert_pda(stats_account, &stats_seeds_refs, program_id)?;
+    let vault_bump = assert_pda(square_fund_vault, &vault_seeds_refs, program_id)?;
+    
+    assert_config_admin(program_id, authority, fund_config)?;
+    
+    if !stats_account.data_is_empty() || !square_fund_vault.data_is_empty() {
+        return Err(FundError::FundAlreadyInitialized.into());
+    }
+    
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            stats_account.key,
+            rent.minimum_balance(SquareFundStats::SIZE),
+            SquareFundStats::SIZE as u64,
+            program_id,
+        ),
+        &[authority.clone(), stats_account.clone(), system_program.clone()],
+        &[&[SQUARE_FUND_STATS_SEED, &[stats_bump]]],
+    )?;
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            square_fund_vault.key,
+            rent.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[authority.clone(), square_fund_vault.clone(), system_program.clone()],
+        &[&[SQUARE_FUND_VAULT_SEED, &[vault_bump]]],
+    )?;
+    
+    invoke(
+        &spl_token::instruction::initialize_account(
+            &spl_token::id(),
+            square_fund_vault.key,
+            usdc_mint.key,
+            stats_account.key, // Owner = SquareFundStats PDA
+        )?,
+        &[square_fund_vault.clone(), usdc_mint.clone(), stats_account.clone(), rent_sysvar.clone()],
+    )?;
+    
+    let current_ts = get_current_timestamp()?;
+    let stats = SquareFundStats::new(*square_fund_vault.key, stats_bump, current_ts);
+    stats.serialize(&mut &mut stats_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ SQUARE_FUND_STATS_INITIALIZED");
+    msg!("  Stats: {}", stats_account.key);
+    msg!("  Vault: {}", square_fund_vault.key);
+    msg!("  Mint: {}", usdc_mint.key);
+    
+    Ok(())
+}
+
+/// Withdraw from the square fund vault against its ledger (Admin only)
+fn process_withdraw_square_fund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: WithdrawSquareFundArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let stats_account = next_account_info(account_info_iter)?;
+    let square_fund_vault = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        square_fund_vault: writable;
+        destination: writable;
+        token_program: key = &spl_token::id();
+    }
+    
+    assert_config_admin(program_id, authority, fund_config)?;
+    
+    let stats_seeds = SquareFundStats::seeds();
+    let stats_seeds_refs: Vec<&[u8]> = stats_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(stats_account, &stats_seeds_refs, program_id)?;
+    let mut stats = load_square_fund_stats(program_id, stats_account)?;
+    
+    validate_accounts! {
+        square_fund_vault: key = &stats.square_fund_vault;
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    stats.record_withdrawal(args.amount_e6, current_ts)?;
+    
+    let vault_state = spl_token::state::Account::unpack(&square_fund_vault.data.borrow())?;
+    if vault_state.amount < args.amount_e6 as u64 {
+        return Err(FundError::InsufficientBalance.into());
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            square_fund_vault.key,
+            destination.key,
+            stats_account.key,
+            &[],
+            args.amount_e6 as u64,
+        )?,
+        &[square_fund_vault.clone(), destination.clone(), stats_account.clone(), token_program.clone()],
+        &[&[SQUARE_FUND_STATS_SEED, &[stats.bump]]],
+    )?;
+    
+    stats.serialize(&mut &mut stats_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ SQUARE_FUND_WITHDRAWN");
+    msg!("  Amount: {}", args.amount_e6);
+    msg!("  Destination: {}", destination.key);
+    msg!("  Accounted balance: {}", stats.accounted_balance_e6());
+    msg!("  Withdrawn by: {}", authority.key);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Revenue Split
+// =============================================================================
+
+/// Find the creator's RevenueSplit among the accounts; None if absent or off
+fn load_active_revenue_split(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    creator: &Pubkey,
+) -> Result<Option<RevenueSplit>, ProgramError> {
+    let seeds = RevenueSplit::seeds(creator);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (split_key, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let Some(split_account) = accounts.iter().find(|a| *a.key == split_key) else {
+        return Ok(None);
+    };
+    if split_account.data_is_empty() {
+        return Ok(None);
+    }
+    
+    assert_owned_by(split_account, program_id)?;
+    let split = RevenueSplit::try_from_slice(&split_account.data.borrow())?;
+    if split.discriminator != REVENUE_SPLIT_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(split.is_active().then_some(split))
+}
+
+/// Pay the creator share out to the split recipients
+fn pay_revenue_split<'a>(
+    accounts: &[AccountInfo<'a>],
+    split: &RevenueSplit,
+    payer: &AccountInfo<'a>,
+    payer_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+    creator_amount_e6: i64,
+) -> ProgramResult {
+    for (recipient_vault, share_e6) in split.allocate(creator_amount_e6) {
+        if share_e6 <= 0 {
+            continue;
+        }
+        let recipient = accounts
+            .iter()
+            .find(|a| *a.key == recipient_vault)
+            .ok_or(FundError::RevenueSplitRecipientMissing)?;
+        assert_writable(recipient)?;
+        
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                payer_vault.key,
+                recipient.key,
+                payer.key,
+                &[],
+                share_e6 as u64,
+            )?,
+            &[payer_vault.clone(), recipient.clone(), payer.clone(), token_program.clone()],
+            signer_seeds,
+        )?;
+        msg!("  split_payout: {} -> {}", share_e6, recipient.key);
+    }
+    Ok(())
+}
+
+/// Register or update a creator's revenue split (Creator only)
+fn process_set_revenue_split(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetRevenueSplitArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let creator = next_account_info(account_info_iter)?;
+    let split_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = RevenueSplit::seeds(creator.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        creator: signer, writable;
+        split_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(split_account, &seeds_refs, program_id)?;
+    
+    let mut split = if split_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                creator.key,
+                split_account.key,
+                Rent::get()?.minimum_balance(RevenueSplit::SIZE),
+                RevenueSplit::SIZE as u64,
+                program_id,
+            ),
+            &[creator.clone(), split_account.clone(), system_program.clone()],
+            &[&[REVENUE_SPLIT_SEED, creator.key.as_ref(), &[bump]]],
+        )?;
+        RevenueSplit::new(*creator.key, bump)
+    } else {
+        assert_owned_by(split_account, program_id)?;
+        let split = RevenueSplit::try_from_slice(&split_account.data.borrow())?;
+        if split.discriminator != REVENUE_SPLIT_DISCRIMINATOR || split.creator != *creator.key {
+            return Err(FundError::InvalidFundAccount.into());
+        }
+        split
+    };
+    
+    split.set_recipients(&args.recipients, get_current_timestamp()?)?;
+    split.serialize(&mut &mut split_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ REVENUE_SPLIT_SET");
+    msg!("  Creator: {}", creator.key);
+    for recipient in split.active_recipients() {
+        msg!("  Recipient: {} ({} bps)", recipient.recipient_vault, recipient.share_bps);
+    }
+    if !split.is_active() {
+        msg!("  Split disabled");
+    }
+    
+    Ok(())
+}
+
+// =============================================================================
+// Square Payer Nonce
+// =============================================================================
+
+/// Advance the payer's SquarePayerNonce to `nonce` (created on first payment)
+fn consume_square_payer_nonce<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    nonce: u64,
+) -> ProgramResult {
+    let seeds = SquarePayerNonce::seeds(payer.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (nonce_key, nonce_bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let nonce_account = accounts
+        .iter()
+        .find(|a| *a.key == nonce_key)
+        .ok_or(FundError::SquarePayerNonceRequired)?;
+    assert_writable(nonce_account)?;
+    
+    let mut state = if nonce_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                nonce_account.key,
+                Rent::get()?.minimum_balance(SquarePayerNonce::SIZE),
+                SquarePayerNonce::SIZE as u64,
+                program_id,
+            ),
+            &[payer.clone(), nonce_account.clone(), system_program.clone()],
+            &[&[SQUARE_PAYER_NONCE_SEED, payer.key.as_ref(), &[nonce_bump]]],
+        )?;
+        SquarePayerNonce::new(*payer.key, nonce_bump)
+    } else {
+        assert_owned_by(nonce_account, program_id)?;
+        let state = SquarePayerNonce::try_from_slice(&nonce_account.data.borrow())?;
+        if state.discriminator != SQUARE_PAYER_NONCE_DISCRIMINATOR || state.payer != *payer.key {
+            return Err(FundError::PDAMismatch.into());
+        }
+        state
+    };
+    
+    if !state.consume(nonce) {
+        msg!("❌ Square payment nonce replay");
+        msg!("  Payer: {}", payer.key);
+        msg!("  Nonce: {}", nonce);
+        msg!("  Last accepted: {}", state.last_nonce);
+        return Err(FundError::SquarePaymentNonceReplay.into());
+    }
+    
+    state.serialize(&mut &mut nonce_account.data.borrow_mut()[..])?;
+    
+    Ok(())
+}
+
+// =============================================================================
+// Square Payment History
+// =============================================================================
+
+/// Find the payer's SquarePaymentHistoryPage PDA for `page` among the passed accounts
+fn find_square_payment_history_page<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+    payer: &Pubkey,
+    page: u32,
+) -> Option<(&'b AccountInfo<'a>, u8)> {
+    let seeds = SquarePaymentHistoryPage::seeds(payer, page);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    accounts.iter().find(|a| *a.key == expected).map(|a| (a, bump))
+}
+
+/// Append a payment nonce to the payer's SquarePaymentHistory
+///
+/// The history and its first page are created on first use; a new page is
+/// opened (paid by `payer`) when the head page is full.
+fn append_square_payment_history<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    nonce: u64,
+    current_ts: i64,
+) -> ProgramResult {
+    let seeds = SquarePaymentHistory::seeds(payer.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (history_key, history_bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let history_account = accounts
+        .iter()
+        .find(|a| *a.key == history_key)
+        .ok_or(FundError::SquarePaymentHistoryRequired)?;
+    assert_writable(history_account)?;
+    
+    let mut history = if history_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                history_account.key,
+                Rent::get()?.minimum_balance(SquarePaymentHistory::SIZE),
+                SquarePaymentHistory::SIZE as u64,
+                program_id,
+            ),
+            &[payer.clone(), history_account.clone(), system_program.clone()],
+            &[&[SQUARE_PAYMENT_HISTORY_SEED, payer.key.as_ref(), &[history_bump]]],
+        )?;
+        SquarePaymentHistory::new(*payer.key, history_bump, current_ts)
+    } else {
+        assert_owned_by(history_account, program_id)?;
+        let history = SquarePaymentHistory::try_from_slice(&history_account.data.borrow())?;
+        if history.discriminator != SQUARE_PAYMENT_HISTORY_DISCRIMINATOR || history.payer != *payer.key {
+            return Err(FundError::PDAMismatch.into());
+        }
+        history
+    };
+    
+    // Try the head page first
+    let mut appended = false;
+    if history.page_count() > 0 {
+        let (page_account, _) = find_square_payment_history_page(program_id, accounts, payer.key, history.head_page)
+            .ok_or(FundError::SquarePaymentHistoryPageMissing)?;
+        assert_owned_by(page_account, program_id)?;
+        let mut page = SquarePaymentHistoryPage::try_from_slice(&page_account.data.borrow())?;
+        if page.discriminator != SQUARE_PAYMENT_HISTORY_PAGE_DISCRIMINATOR || page.payer != *payer.key {
+            return Err(FundError::PDAMismatch.into());
+        }
+        if page.push(nonce) {
+            assert_writable(page_account)?;
+            page.serialize(&mut &mut page_account.data.borrow_mut()[..])?;
+            appended = true;
+        } else {
+            history.head_page = history.head_page.checked_add(1).ok_or(FundError::Overflow)?;
+        }
+    }
+    
+    // Open the next page
+    if !appended {
+        let next = history.head_page;
+        let (page_account, page_bump) = find_square_payment_history_page(program_id, accounts, payer.key, next)
+            .ok_or(FundError::SquarePaymentHistoryPageMissing)?;
+        assert_writable(page_account)?;
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                page_account.key,
+                Rent::get()?.minimum_balance(SquarePaymentHistoryPage::SIZE),
+                SquarePaymentHistoryPage::SIZE as u64,
+                program_id,
+            ),
+            &[payer.clone(), page_account.clone(), system_program.clone()],
+            &[&[SQUARE_PAYMENT_HISTORY_PAGE_SEED, payer.key.as_ref(), &next.to_le_bytes(), &[page_bump]]],
+        )?;
+        
+        let mut page = SquarePaymentHistoryPage::new(*payer.key, next, page_bump);
+        page.push(nonce);
+        page.serialize(&mut &mut page_account.data.borrow_mut()[..])?;
+        
+        msg!("📇 SQUARE_PAYMENT_HISTORY_PAGE_OPENED");
+        msg!("  Payer: {}", payer.key);
+        msg!("  Page: {}", next);
+    }
+    
+    history.payment_count = history.payment_count.saturating_add(1);
+    history.serialize(&mut &mut history_account.data.borrow_mut()[..])?;
+    
+    Ok(())
+}
+
+// =============================================================================
+// Square Escrow
+// =============================================================================
+
+/// KnowledgePurchase with the full amount held in escrow
+fn process_escrow_square_payment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SquarePaymentArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let payment_record = next_account_info(account_info_iter)?;
+    let payer_vault = next_account_info(account_info_iter)?;
+    let creator_vault = next_account_info(account_info_iter)?;
+    let square_fund_vault = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let escrow_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        payer: signer, writable;
+        payer_vault: writable, owner = &spl_token::id();
+        escrow_account: writable;
+        escrow_vault: writable, owner = &spl_token::id();
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    if args.payment_type != SquarePaymentType::KnowledgePurchase as u8 {
+        return Err(FundError::SquareEscrowKnowledgeOnly.into());
+    }
+    // Stored for release, so it must already be a valid platform destination
+    assert_square_platform_vault(program_id, accounts, square_fund_vault, payer_vault)?;
+    
+    let current_ts = get_current_timestamp()?;
+    let record = create_square_payment_record(program_id, accounts, payer, payment_record, system_program, &args, current_ts)?;
+    
+    let escrow_seeds = SquareEscrow::seeds(payment_record.key);
+    let escrow_seeds_refs: Vec<&[u8]> = escrow_seeds.iter().map(|s| s.as_slice()).collect();
+    let escrow_bump = assert_pda(escrow_account, &escrow_seeds_refs, program_id)?;
+    if !escrow_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    // Only the SquareEscrow PDA may move the held funds
+    let source_state = spl_token::state::Account::unpack(&payer_vault.data.borrow())?;
+    let vault_state = spl_token::state::Account::unpack(&escrow_vault.data.borrow())?;
+    if vault_state.owner != *escrow_account.key || vault_state.mint != source_state.mint {
+        return Err(FundError::SquareEscrowAccountMismatch.into());
+    }
+    
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            payer_vault.key,
+            escrow_vault.key,
+            payer.key,
+            &[],
+            record.total_amount_e6 as u64,
+        )?,
+        &[payer_vault.clone(), escrow_vault.clone(), payer.clone(), token_program.clone()],
+    )?;
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            escrow_account.key,
+            Rent::get()?.minimum_balance(SquareEscrow::SIZE),
+            SquareEscrow::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), escrow_account.clone(), system_program.clone()],
+        &[&[SQUARE_ESCROW_SEED, payment_record.key.as_ref(), &[escrow_bump]]],
+    )?;
+    
+    let escrow = SquareEscrow::new(
+        *payment_record.key,
+        &record,
+        *escrow_vault.key,
+        *payer_vault.key,
+        *creator_vault.key,
+        *square_fund_vault.key,
+        current_ts,
+        escrow_bump,
+    );
+    escrow.serialize(&mut &mut escrow_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ SQUARE_ESCROW_OPENED");
+    msg!("  Payer: {}", payer.key);
+    msg!("  Creator: {}", record.creator);
+    msg!("  Content ID: {}", record.content_id);
+    msg!("  Amount: {}", record.total_amount_e6);
+    msg!("  Release after: {}", escrow.release_after);
+    msg!("  Record: {}", payment_record.key);
+    
+    Ok(())
+}
+
+/// Release an escrowed payment (buyer any time, anyone after the timeout)
+fn process_confirm_delivery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let caller = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let escrow_vault = next_account_info(account_info_iter)?;
+    let creator_vault = next_account_info(account_info_iter)?;
+    let square_fund_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        caller: signer;
+    }
+    
+    let mut escrow = load_square_escrow(program_id, escrow_account)?;
+    let current_ts = get_current_timestamp()?;
+    if !escrow.can_release(caller.key, current_ts) {
+        if escrow.status != SquareEscrowStatus::Held {
+            return Err(FundError::SquareEscrowNotHeld.into());
+        }
+        return Err(FundError::SquareEscrowNotReleasable.into());
+    }
+    
+    release_square_escrow(
+        program_id,
+        accounts,
+        caller,
+        escrow_account,
+        &escrow,
+        escrow_vault,
+        creator_vault,
+        square_fund_vault,
+        token_program,
+        system_program,
+        current_ts,
+    )?;
+    
+    escrow.settle(false, current_ts);
+    escrow.serialize(&mut &mut escrow_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ SQUARE_ESCROW_RELEASED");
+    msg!("  Escrow: {}", escrow_account.key);
+    msg!("  By buyer: {}", *caller.key == escrow.payer);
+    msg!("  Creator amount: {}", escrow.creator_amount_e6);
+    msg!("  Platform amount: {}", escrow.platform_amount_e6);
+    
+    Ok(())
+}
+
+/// Buyer disputes an escrowed payment before the timeout
+fn process_raise_dispute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        payer: signer;
+    }
+    
+    let mut escrow = load_square_escrow(program_id, escrow_account)?;
+    if escrow.payer != *payer.key {
+        return Err(FundError::Unauthorized.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    escrow.raise_dispute(current_ts)?;
+    escrow.serialize(&mut &mut escrow_account.data.borrow_mut()[..])?;
+    
+    msg!("⚠️ SQUARE_ESCROW_DISPUTED");
+    msg!("  Escrow: {}", escrow_account.key);
+    msg!("  Payer: {}", payer.key);
+    msg!("  Creator: {}", escrow.creator);
+    msg!("  Amount: {}", escrow.amount_e6);
+    
+    Ok(())
+}
+
+/// Settle a disputed escrow (Admin only)
+fn process_resolve_dispute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ResolveDisputeArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let escrow_vault = next_account_info(account_info_iter)?;
+    let payer_vault = next_account_info(account_info_iter)?;
+    let creator_vault = next_account_info(account_info_iter)?;
+    let square_fund_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    assert_config_admin(program_id, authority, fund_config)?;
+    
+    let mut escrow = load_square_escrow(program_id, escrow_account)?;
+    if escrow.status != SquareEscrowStatus::Disputed {
+        return Err(FundError::SquareEscrowNotHeld.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    if args.refund {
+        validate_accounts! {
+            escrow_vault: writable, key = &escrow.escrow_vault;
+            payer_vault: writable, key = &escrow.payer_vault;
+            token_program: key = &spl_token::id();
+        }
+        
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                escrow_vault.key,
+                payer_vault.key,
+                escrow_account.key,
+                &[],
+                escrow.amount_e6 as u64,
+            )?,
+            &[escrow_vault.clone(), payer_vault.clone(), escrow_account.clone(), token_program.clone()],
+            &[&[SQUARE_ESCROW_SEED, escrow.payment_record.as_ref(), &[escrow.bump]]],
+        )?;
+    } else {
+        release_square_escrow(
+            program_id,
+            accounts,
+            authority,
+            escrow_account,
+            &escrow,
+            escrow_vault,
+            creator_vault,
+            square_fund_vault,
+            token_program,
+            system_program,
+            current_ts,
+        )?;
+    }
+    
+    escrow.settle(args.refund, current_ts);
+    escrow.serialize(&mut &mut escrow_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ SQUARE_ESCROW_RESOLVED");
+    msg!("  Escrow: {}", escrow_account.key);
+    msg!("  Refunded: {}", args.refund);
+    msg!("  Amount: {}", escrow.amount_e6);
+    
+    Ok(())
+}
+
+/// Load a SquareEscrow for update
+fn load_square_escrow(
+    program_id: &Pubkey,
+    escrow_account: &AccountInfo,
+) -> Result<SquareEscrow, ProgramError> {
+    validate_accounts! {
+        escrow_account: writable, owner = program_id;
+    }
+    
+    let escrow = SquareEscrow::try_from_slice(&escrow_account.data.borrow())?;
+    if escrow.discriminator != SQUARE_ESCROW_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(escrow)
+}
+
+/// Pay the held amount out like a direct SquarePayment, signed by the escrow PDA
+#[allow(clippy::too_many_arguments)]
+fn release_square_escrow<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    funder: &AccountInfo<'a>,
+    escrow_account: &AccountInfo<'a>,
+    escrow: &SquareEscrow,
+    escrow_vault: &AccountInfo<'a>,
+    creator_vault: &AccountInfo<'a>,
+    square_fund_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    current_ts: i64,
+) -> ProgramResult {
+    validate_accounts! {
+        escrow_vault: writable, key = &escrow.escrow_vault;
+        creator_vault: key = &escrow.creator_vault;
+        square_fund_vault: writable, key = &escrow.platform_vault;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    settle_square_payment(
+        program_id,
+        accounts,
+        escrow_account,
+        escrow_vault,
+        &[&[SQUARE_ESCROW_SEED, escrow.payment_record.as_ref(), &[escrow.bump]]],
+        creator_vault,
+        square_fund_vault,
+        token_program,
+        system_program,
+        funder,
+        &escrow.creator,
+        SquarePaymentType::KnowledgePurchase,
+        escrow.creator_amount_e6,
+        escrow.platform_amount_e6,
+        current_ts,
+    )?;
+    
+    Ok(())
+}
+
+// =============================================================================
+// Square Tip Token
+// =============================================================================
+
+/// Whitelist or update an SPL tip token (Admin only)
+fn process_set_square_tip_token(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetSquareTipTokenArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let tip_token_account = next_account_info(account_info_iter)?;
+    let mint = next_account_info(account_info_iter)?;
+    let platform_vault = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = SquareTipToken::seeds(mint.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        authority: signer, writable;
+        tip_token_account: writable, pda = (&seeds_refs, program_id);
+        mint: owner = &spl_token::id();
+        platform_vault: owner = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    assert_config_admin(program_id, authority, fund_config)?;
+    
+    if args.max_price_age_secs < 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    
+    let vault_state = spl_token::state::Account::unpack(&platform_vault.data.borrow())?;
+    if vault_state.mint != *mint.key {
+        return Err(FundError::SquareTipTokenMismatch.into());
+    }
+    
+    let mut tip_token = if tip_token_account.data_is_empty() {
+        let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                tip_token_account.key,
+                Rent::get()?.minimum_balance(SquareTipToken::SIZE),
+                SquareTipToken::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), tip_token_account.clone(), system_program.clone()],
+            &[&[SQUARE_TIP_TOKEN_SEED, mint.key.as_ref(), &[bump]]],
+        )?;
+        let mint_state = spl_token::state::Mint::unpack(&mint.data.borrow())?;
+        SquareTipToken::new(*mint.key, mint_state.decimals, bump)
+    } else {
+        load_square_tip_token(program_id, tip_token_account)?
+    };
+    
+    tip_token.platform_vault = *platform_vault.key;
+    tip_token.price_authority = args.price_authority;
+    tip_token.max_price_age_secs = if args.max_price_age_secs == 0 {
+        DEFAULT_TIP_PRICE_MAX_AGE_SECS
+    } else {
+        args.max_price_age_secs
+    };
+    tip_token.enabled = args.enabled;
+    tip_token.serialize(&mut &mut tip_token_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ SQUARE_TIP_TOKEN_SET");
+    msg!("  Mint: {}", mint.key);
+    msg!("  Decimals: {}", tip_token.decimals);
+    msg!("  Platform vault: {}", platform_vault.key);
+    msg!("  Price authority: {}", args.price_authority);
+    msg!("  Max price age: {}", tip_token.max_price_age_secs);
+    msg!("  Enabled: {}", args.enabled);
+    
+    Ok(())
+}
+
+/// Push a tip token's USD price (price authority only)
+fn process_update_square_tip_token_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateSquareTipTokenPriceArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let price_authority = next_account_info(account_info_iter)?;
+    let tip_token_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        price_authority: signer;
+    }
+    
+    let mut tip_token = load_square_tip_token(program_id, tip_token_account)?;
+    if tip_token.price_authority != *price_authority.key {
+        return Err(FundError::Unauthorized.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    tip_token.set_price(args.price_e6, current_ts)?;
+    tip_token.serialize(&mut &mut tip_token_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ SQUARE_TIP_PRICE_UPDATED");
+    msg!("  Mint: {}", tip_token.mint);
+    msg!("  Price: {}", args.price_e6);
+    
+    Ok(())
+}
+
+/// Live tip in a whitelisted SPL token
+fn process_square_token_tip(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SquareTokenTipArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let payment_record = next_account_info(account_info_iter)?;
+    let payer_token = next_account_info(account_info_iter)?;
+    let creator_token = next_account_info(account_info_iter)?;
+    let platform_vault = next_account_info(account_info_iter)?;
+    let tip_token_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        payer: signer, writable;
+        payer_token: writable, owner = &spl_token::id();
+        creator_token: writable, owner = &spl_token::id();
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    if args.amount == 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    if args.creator_share_bps > 10000 {
+        return Err(FundError::InvalidFeeConfiguration.into());
+    }
+    
+    let mut tip_token = load_square_tip_token(program_id, tip_token_account)?;
+    if !tip_token.enabled {
+        return Err(FundError::SquareTipTokenDisabled.into());
+    }
+    validate_accounts! {
+        platform_vault: writable, key = &tip_token.platform_vault;
+    }
+    let source_state = spl_token::state::Account::unpack(&payer_token.data.borrow())?;
+    let creator_state = spl_token::state::Account::unpack(&creator_token.data.borrow())?;
+    if source_state.mint != tip_token.mint || creator_state.mint != tip_token.mint {
+        return Err(FundError::SquareTipTokenMismatch.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    let value_e6 = tip_token.value_e6(args.amount, current_ts)?;
+    if value_e6 <= 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    
+    // Record carries the USD value; tokens are split on their own amounts
+    let record = create_square_payment_record(
+        program_id,
+        accounts,
+        payer,
+        payment_record,
+        system_program,
+        &SquarePaymentArgs {
+            creator: args.creator,
+            content_id: args.content_id,
+            payment_type: SquarePaymentType::LiveDonation as u8,
+            amount_e6: value_e6,
+            creator_share_bps: args.creator_share_bps,
+            subscription_period: 0,
+            memo: args.memo,
+            nonce: args.nonce,
+        },
+        current_ts,
+    )?;
+    
+    let (creator_tokens, platform_tokens) = SquareTipToken::split_amount(args.amount, args.creator_share_bps);
+    for (destination, amount) in [(creator_token, creator_tokens), (platform_vault, platform_tokens)] {
+        if amount == 0 {
+            continue;
+        }
+        invoke(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                payer_token.key,
+                destination.key,
+                payer.key,
+                &[],
+                amount,
+            )?,
+            &[payer_token.clone(), destination.clone(), payer.clone(), token_program.clone()],
+        )?;
+    }
+    
+    tip_token.record_tip(args.amount, value_e6)?;
+    tip_token.serialize(&mut &mut tip_token_account.data.borrow_mut()[..])?;
+    
+    record_square_stats(
+        program_id,
+        accounts,
+        payer,
+        platform_vault,
+        system_program,
+        &args.creator,
+        SquarePaymentType::LiveDonation,
+        value_e6,
+        record.creator_amount_e6,
+        record.platform_amount_e6,
+        false,
+        current_ts,
+    )?;
+    
+    msg!("📝 SQUARE_TOKEN_TIP:");
+    msg!("  payer: {}", payer.key);
+    msg!("  creator: {}", args.creator);
+    msg!("  content_id: {}", args.content_id);
+    msg!("  mint: {}", tip_token.mint);
+    msg!("  token_amount: {}", args.amount);
+    msg!("  creator_tokens: {}", creator_tokens);
+    msg!("  platform_tokens: {}", platform_tokens);
+    msg!("  value_e6: {}", value_e6);
+    msg!("  price_e6: {}", tip_token.price_e6);
+    msg!("  record: {}", payment_record.key);
+    
+    Ok(())
+}
+
+/// Load a SquareTipToken for update
+fn load_square_tip_token(
+    program_id: &Pubkey,
+    tip_token_account: &AccountInfo,
+) -> Result<SquareTipToken, ProgramError> {
+    validate_accounts! {
+        tip_token_account: writable, owner = program_id;
+    }
+    
+    let tip_token = SquareTipToken::try_from_slice(&tip_token_account.data.borrow())?;
+    if tip_token.discriminator != SQUARE_TIP_TOKEN_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(tip_token)
+}
+
+// =============================================================================
+// Fund Hook
+// =============================================================================
+
+/// Register or clear a fund's deposit/redemption hook (Manager + Admin)
+fn process_set_fund_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFundHookArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let hook_program = account_info_iter.next();
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    assert_config_admin(program_id, admin, fund_config)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    
+    if args.hook_program != Pubkey::default() {
+        let Some(hook_program) = hook_program else {
+            return Err(FundError::InvalidFundHookProgram.into());
+        };
+        if *hook_program.key != args.hook_program || !hook_program.executable || hook_program.key == program_id {
+            return Err(FundError::InvalidFundHookProgram.into());
+        }
+    }
+    
+    fund.hook_program = args.hook_program;
+    fund.last_update_ts = get_current_timestamp()?;
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    let hook_seeds = Fund::hook_authority_seeds(fund_account.key);
+    let hook_seeds_refs: Vec<&[u8]> = hook_seeds.iter().map(|s| s.as_slice()).collect();
+    let (hook_authority, _) = Pubkey::find_program_address(&hook_seeds_refs, program_id);
+    
+    msg!("✅ FUND_HOOK_SET");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Hook program: {}", fund.hook_program);
+    msg!("  Hook authority: {}", hook_authority);
+    
+    Ok(())
+}
+
+/// CPI the fund's hook program (if any) after a deposit/redemption
+///
+/// The hook program must be passed, followed by the FundHookAuthority PDA
+/// and then the hook's own accounts.
+#[allow(clippy::too_many_arguments)]
+fn invoke_fund_hook<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    fund_account: &AccountInfo<'a>,
+    fund: &Fund,
+    investor: &AccountInfo<'a>,
+    event: FundHookEvent,
+    amount_e6: i64,
+    shares: u64,
+    current_ts: i64,
+) -> ProgramResult {
+    if !fund.has_hook() {
+        return Ok(());
+    }
+    
+    let hook_index = accounts
+        .iter()
+        .position(|a| *a.key == fund.hook_program)
+        .ok_or(FundError::FundHookAccountsMissing)?;
+    let hook_program = &accounts[hook_index];
+    let hook_authority = accounts
+        .get(hook_index + 1)
+        .ok_or(FundError::FundHookAccountsMissing)?;
+    
+    let hook_seeds = Fund::hook_authority_seeds(fund_account.key);
+    let hook_seeds_refs: Vec<&[u8]> = hook_seeds.iter().map(|s| s.as_slice()).collect();
+    let hook_bump = assert_pda(hook_authority, &hook_seeds_refs, program_id)?;
+    
+    let payload = FundHookPayload {
+        discriminator: FUND_HOOK_PAYLOAD_DISCRIMINATOR,
+        event,
+        fund: *fund_account.key,
+        investor: *investor.key,
+        amount_e6,
+        shares,
+        nav_e6: fund.stats.current_nav_e6,
+        timestamp: current_ts,
+    };
+    
+    crate::cpi::fund_hook(
+        hook_program,
+        hook_authority,
+        fund_account,
+        investor,
+        &accounts[hook_index + 2..],
+        &payload,
+        &[&[FUND_HOOK_AUTHORITY_SEED, fund_account.key.as_ref(), &[hook_bump]]],
+    )?;
+    
+    msg!("  Hook: {:?} -> {}", event, fund.hook_program);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Share Authority Rotation
+// =============================================================================
+
+/// Current share mint/freeze authority and its signer seeds (bump included)
+///
+/// Version 0 is the Fund PDA itself; a rotated authority PDA is looked up by
+/// address among `accounts`.
+fn share_authority<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+    fund_account: &'b AccountInfo<'a>,
+    fund: &Fund,
+) -> Result<(&'b AccountInfo<'a>, Vec<Vec<u8>>), ProgramError> {
+    if fund.share_authority_version == 0 {
+        let mut seeds = Fund::seeds(&fund.manager, fund.fund_index);
+        seeds.push(vec![fund.bump]);
+        return Ok((fund_account, seeds));
+    }
+    
+    let mut seeds = Fund::share_authority_seeds(fund_account.key, fund.share_authority_version);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (authority_key, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let authority = accounts
+        .iter()
+        .find(|a| *a.key == authority_key)
+        .ok_or(FundError::ShareAuthorityRequired)?;
+    seeds.push(vec![bump]);
+    Ok((authority, seeds))
+}
+
+/// Move the share mint/freeze authority to the next PDA version (Manager + Admin)
+fn process_rotate_share_mint_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+    let _current_authority = next_account_info(account_info_iter)?;
+    let new_authority = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        share_mint: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    assert_config_admin(program_id, admin, fund_config)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    validate_accounts! {
+        share_mint: key = &fund.share_mint;
+    }
+    
+    let new_version = fund
+        .share_authority_version
+        .checked_add(1)
+        .ok_or(FundError::Overflow)?;
+    let new_seeds = Fund::share_authority_seeds(fund_account.key, new_version);
+    let new_seeds_refs: Vec<&[u8]> = new_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(new_authority, &new_seeds_refs, program_id)?;
+    
+    let (current, current_seeds) = share_authority(program_id, accounts, fund_account, &fund)?;
+    let current_refs: Vec<&[u8]> = current_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    let mint_state = spl_token::state::Mint::unpack(&share_mint.data.borrow())?;
+    if mint_state.mint_authority != solana_program::program_option::COption::Some(*current.key)
+        || mint_state.freeze_authority != solana_program::program_option::COption::Some(*current.key)
+    {
+        return Err(FundError::ShareAuthorityMismatch.into());
+    }
+    
+    for authority_type in [
+        spl_token::instruction::AuthorityType::MintTokens,
+        spl_token::instruction::AuthorityType::FreezeAccount,
+    ] {
+        invoke_signed(
+            &spl_token::instruction::set_authority(
+                &spl_token::id(),
+                share_mint.key,
+                Some(new_authority.key),
+                authority_type,
+                current.key,
+                &[],
+            )?,
+            &[share_mint.clone(), current.clone(), token_program.clone()],
+            &[&current_refs],
+        )?;
+    }
+    
+    let previous_version = fund.share_authority_version;
+    let current_ts = get_current_timestamp()?;
+    fund.share_authority_version = new_version;
+    fund.share_authority_rotated_at = current_ts;
+    fund.last_update_ts = current_ts;
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("🔐 SHARE_MINT_AUTHORITY_ROTATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Share mint: {}", share_mint.key);
+    msg!("  Version: {} -> {}", previous_version, new_version);
+    msg!("  Old authority: {}", current.key);
+    msg!("  New authority: {}", new_authority.key);
+    msg!("  Timestamp: {}", current_ts);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Fund LP Referral
+// =============================================================================
+
+/// Load a FundLPReferralProgram and check it belongs to `fund_key`
+fn load_fund_lp_referral(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    fund_key: &Pubkey,
+) -> Result<FundLPReferralProgram, ProgramError> {
+    assert_owned_by(account, program_id)?;
+    let program = FundLPReferralProgram::try_from_slice(&account.data.borrow())?;
+    if program.discriminator != FUND_LP_REFERRAL_DISCRIMINATOR || program.fund != *fund_key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(program)
+}
+
+/// Load a FundLPReferralBinding and check it belongs to `fund_key`
+fn load_fund_lp_referral_binding(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    fund_key: &Pubkey,
+) -> Result<FundLPReferralBinding, ProgramError> {
+    assert_owned_by(account, program_id)?;
+    let binding = FundLPReferralBinding::try_from_slice(&account.data.borrow())?;
+    if binding.discriminator != FUND_LP_REFERRAL_BINDING_DISCRIMINATOR || binding.fund != *fund_key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(binding)
+}
+
+/// Find the fund's FundLPReferralProgram PDA among the passed accounts
+fn find_fund_lp_referral<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+    fund_key: &Pubkey,
+) -> Result<(&'b AccountInfo<'a>, FundLPReferralProgram), ProgramError> {
+    let seeds = FundLPReferralProgram::seeds(fund_key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let account = accounts
+        .iter()
+        .find(|a| *a.key == expected)
+        .ok_or(FundError::FundLPReferralAccountsMissing)?;
+    assert_writable(account)?;
+    let program = load_fund_lp_referral(program_id, account, fund_key)?;
+    Ok((account, program))
+}
+
+/// Accrue the referrer's bounty for a deposit by a bound LP
+///
+/// The binding is optional (found by PDA); once passed, the fund's
+/// FundLPReferralProgram must be passed too.
+fn accrue_fund_lp_referral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fund_key: &Pubkey,
+    fund: &Fund,
+    investor: &Pubkey,
+    deposit_e6: i64,
+) -> ProgramResult {
+    if !fund.lp_referral_enabled {
+        return Ok(());
+    }
+    
+    let seeds = FundLPReferralBinding::seeds(fund_key, investor);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (expected, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let Some(binding_account) = accounts.iter().find(|a| *a.key == expected) else {
+        return Ok(());
+    };
+    if binding_account.data_is_empty() {
+        return Ok(());
+    }
+    assert_writable(binding_account)?;
+    let mut binding = load_fund_lp_referral_binding(program_id, binding_account, fund_key)?;
+    
+    let (program_account, mut program) = find_fund_lp_referral(program_id, accounts, fund_key)?;
+    if !program.is_active {
+        return Ok(());
+    }
+    
+    let bounty = program.record_deposit(deposit_e6)?;
+    binding.record_deposit(deposit_e6, bounty)?;
+    program.serialize(&mut &mut program_account.data.borrow_mut()[..])?;
+    binding.serialize(&mut &mut binding_account.data.borrow_mut()[..])?;
+    
+    msg!("🤝 FUND_LP_REFERRAL_ACCRUED");
+    msg!("  Referrer: {}", binding.referrer);
+    msg!("  Deposit: {}", deposit_e6);
+    msg!("  Bounty: {}", bounty);
+    
+    Ok(())
+}
+
+/// Withhold unfunded LP referral bounties from a fee payout, returning the amount withheld
+fn withhold_fund_lp_referral_bounty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fund_key: &Pubkey,
+    fund: &Fund,
+    fee_e6: i64,
+) -> Result<i64, ProgramError> {
+    if !fund.lp_referral_enabled {
+        return Ok(0);
+    }
+    
+    let (program_account, mut program) = find_fund_lp_referral(program_id, accounts, fund_key)?;
+    let withheld = program.fund_from_fees(fee_e6)?;
+    if withheld > 0 {
+        program.serialize(&mut &mut program_account.data.borrow_mut()[..])?;
+    }
+    Ok(withheld)
+}
+
+/// Configure the fund's LP referral bounty
+fn process_set_fund_lp_referral_program(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFundLPReferralProgramArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let program_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = FundLPReferralProgram::seeds(fund_account.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        program_account: writable, pda = (&seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    if args.bounty_bps > MAX_FUND_LP_REFERRAL_BOUNTY_BPS {
+        return Err(FundError::FundLPReferralBountyTooHigh.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    
+    let mut program = if program_account.data_is_empty() {
+        let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+        invoke_signed(
+            &system_instruction::create_account(
+                manager.key,
+                program_account.key,
+                Rent::get()?.minimum_balance(FundLPReferralProgram::SIZE),
+                FundLPReferralProgram::SIZE as u64,
+                program_id,
+            ),
+            &[manager.clone(), program_account.clone(), system_program.clone()],
+            &[&[FUND_LP_REFERRAL_SEED, fund_account.key.as_ref(), &[bump]]],
+        )?;
+        FundLPReferralProgram::new(*fund_account.key, bump, current_ts)
+    } else {
+        load_fund_lp_referral(program_id, program_account, fund_account.key)?
+    };
+    
+    program.bounty_bps = args.bounty_bps;
+    program.is_active = args.is_active;
+    program.updated_at = current_ts;
+    program.serialize(&mut &mut program_account.data.borrow_mut()[..])?;
+    
+    // From now on CollectFees must pass the program so accrued bounties get funded
+    if !fund.lp_referral_enabled {
+        fund.lp_referral_enabled = true;
+        fund.last_update_ts = current_ts;
+        if !fund.fits_in(fund_account.data_len()) {
+            realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+        }
+        fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    }
+    
+    msg!("✅ FUND_LP_REFERRAL_SET");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Bounty: {} bps", args.bounty_bps);
+    msg!("  Active: {}", args.is_active);
+    
+    Ok(())
+}
+
+/// Bind a referrer for the signer's LP inflows into a fund
+fn process_bind_fund_lp_referral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: BindFundLPReferralArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let referee = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let program_account = next_account_info(account_info_iter)?;
+    let binding_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let binding_seeds = FundLPReferralBinding::seeds(fund_account.key, referee.key);
+    let binding_seeds_refs: Vec<&[u8]> = binding_seeds.iter().map(|s| s.as_slice()).collect();
+    let lp_seeds = LPPosition::seeds(fund_account.key, referee.key);
+    let lp_seeds_refs: Vec<&[u8]> = lp_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        referee: signer, writable;
+        fund_account: owner = program_id;
+        program_account: writable;
+        binding_account: writable, pda = (&binding_seeds_refs, program_id);
+        lp_position: pda = (&lp_seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let mut program = load_fund_lp_referral(program_id, program_account, fund_account.key)?;
+    if !program.is_active {
+        return Err(FundError::FundLPReferralInactive.into());
+    }
+    if args.referrer == *referee.key || args.referrer == Pubkey::default() {
+        return Err(FundError::InvalidFundLPReferrer.into());
+    }
+    if !binding_account.data_is_empty() {
+        return Err(FundError::AlreadyBoundToReferrer.into());
+    }
+    // Only new capital counts as introduced
+    if !lp_position.data_is_empty() {
+        return Err(FundError::FundLPReferralAfterDeposit.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    let (_, bump) = Pubkey::find_program_address(&binding_seeds_refs, program_id);
+    invoke_signed(
+        &system_instruction::create_account(
+            referee.key,
+            binding_account.key,
+            Rent::get()?.minimum_balance(FundLPReferralBinding::SIZE),
+            FundLPReferralBinding::SIZE as u64,
+            program_id,
+        ),
+        &[referee.clone(), binding_account.clone(), system_program.clone()],
+        &[&[FUND_LP_REFERRAL_BINDING_SEED, fund_account.key.as_ref(), referee.key.as_ref(), &[bump]]],
+    )?;
+    
+    let binding = FundLPReferralBinding::new(*fund_account.key, *referee.key, args.referrer, bump, current_ts);
+    binding.serialize(&mut &mut binding_account.data.borrow_mut()[..])?;
+    
+    program.referee_count = program.referee_count.saturating_add(1);
+    program.serialize(&mut &mut program_account.data.borrow_mut()[..])?;
+    
+    msg!("🔗 FUND_LP_REFERRAL_BOUND");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Referee: {}", referee.key);
+    msg!("  Referrer: {}", args.referrer);
+    
+    Ok(())
+}
+
+/// Pay out a binding's bounty from the funded pool
+fn process_claim_fund_lp_referral_bounty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let referrer = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let program_account = next_account_info(account_info_iter)?;
+    let binding_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let referrer_usdc = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        referrer: signer;
+        fund_account: owner = program_id;
+        program_account: writable;
+        binding_account: writable;
+        fund_vault: writable;
+        referrer_usdc: writable;
+        token_program: key = &spl_token::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let mut program = load_fund_lp_referral(program_id, program_account, fund_account.key)?;
+    let mut binding = load_fund_lp_referral_binding(program_id, binding_account, fund_account.key)?;
+    if binding.referrer != *referrer.key {
+        return Err(FundError::Unauthorized.into());
+    }
+    
+    let amount_e6 = binding.claim(&mut program)?;
+    if amount_e6 <= 0 {
+        return Err(FundError::NoFundLPReferralBounty.into());
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            fund_vault.key,
+            referrer_usdc.key,
+            fund_account.key,
+            &[],
+            fund.e6_to_token_amount(amount_e6)?,
+        )?,
+        &[fund_vault.clone(), referrer_usdc.clone(), fund_account.clone(), token_program.clone()],
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund.bump]]],
+    )?;
+    
+    program.serialize(&mut &mut program_account.data.borrow_mut()[..])?;
+    binding.serialize(&mut &mut binding_account.data.borrow_mut()[..])?;
+    
+    msg!("💸 FUND_LP_REFERRAL_CLAIMED");
+    msg!("  Referrer: {}", referrer.key);
+    msg!("  Referee: {}", binding.referee);
+    msg!("  Amount: {}", amount_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Admin Intervention
+// =============================================================================
+
+/// Load a fund for an admin intervention (admin signer, FundConfig authority)
+fn load_fund_for_intervention(
+    program_id: &Pubkey,
+    admin: &AccountInfo,
+    fund_config: &AccountInfo,
+    fund_account: &AccountInfo,
+    system_program: &AccountInfo,
+) -> Result<Fund, ProgramError> {
+    validate_accounts! {
+        admin: signer, writable;
+        fund_account: writable, owner = program_id;
+        system_program: key = &solana_program::system_program::id();
+    }
+    assert_config_admin(program_id, admin, fund_config)?;
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(fund)
+}
+
+/// Emit ADMIN_FUND_INTERVENTION
+#[allow(clippy::too_many_arguments)]
+fn log_fund_intervention(
+    fund_key: &Pubkey,
+    admin: &Pubkey,
+    field: &str,
+    before: bool,
+    after: bool,
+    lock_manager: bool,
+    reason: Option<&str>,
+    current_ts: i64,
+) {
+    msg!("🛡️ ADMIN_FUND_INTERVENTION");
+    msg!("  Fund: {}", fund_key);
+    msg!("  Admin: {}", admin);
+    msg!("  {}: {} -> {}", field, before, after);
+    msg!("  Manager locked: {}", lock_manager);
+    if let Some(reason) = reason {
+        msg!("  Reason: {}", reason);
+    }
+    msg!("  Timestamp: {}", current_ts);
+}
+
+/// Force a fund's pause state
+fn process_admin_set_fund_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: AdminSetFundPausedArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let admin = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let mut fund = load_fund_for_intervention(program_id, admin, fund_config, fund_account, system_program)?;
+    validate_memo(args.reason.as_deref())?;
+    
+    let current_ts = get_current_timestamp()?;
+    let was_paused = fund.is_paused;
+    // No max duration: an admin pause stays until an admin lifts it
+    fund.set_paused(args.is_paused, 0, current_ts);
+    fund.record_pause_toggle(current_ts);
+    fund.admin_pause_locked = args.lock_manager;
+    fund.last_update_ts = current_ts;
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, admin, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    log_fund_intervention(
+        fund_account.key,
+        admin.key,
+        "Paused",
+        was_paused,
+        args.is_paused,
+        args.lock_manager,
+        args.reason.as_deref(),
+        current_ts,
+    );
+    
+    Ok(())
+}
+
+/// Force a fund open/closed for deposits
+fn process_admin_set_fund_open(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: AdminSetFundOpenArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let admin = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let mut fund = load_fund_for_intervention(program_id, admin, fund_config, fund_account, system_program)?;
+    validate_memo(args.reason.as_deref())?;
+    
+    let current_ts = get_current_timestamp()?;
+    let was_open = fund.is_open;
+    fund.is_open = args.is_open;
+    fund.admin_open_locked = args.lock_manager;
+    fund.last_update_ts = current_ts;
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, admin, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    log_fund_intervention(
+        fund_account.key,
+        admin.key,
+        "Open",
+        was_open,
+        args.is_open,
+        args.lock_manager,
+        args.reason.as_deref(),
+        current_ts,
+    );
+    
+    Ok(())
+}
+
+// =============================================================================
+// Insurance Migration
+// =============================================================================
+
+/// Split historical insurance income into liquidation vs trading fees
+fn process_migrate_insurance_config_v2(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: MigrateInsuranceConfigV2Args,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let admin = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let insurance_config = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        insurance_config: writable, owner = program_id;
+    }
+    assert_config_admin(program_id, admin, fund_config)?;
+    
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+    
+    let v1_income_e6 = config.total_liquidation_income_e6;
+    let current_ts = get_current_timestamp()?;
+    if let Err(e) = config.migrate_v2(args.liquidation_income_e6, args.trading_fee_e6, current_ts) {
+        msg!(
+            "Backfill {} + {} vs V1 income {} (migrated at {})",
+            args.liquidation_income_e6,
+            args.trading_fee_e6,
+            v1_income_e6,
+            config.v2_migrated_at
+        );
+        return Err(e);
+    }
+    config.last_update_ts = current_ts;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
+    
+    msg!("🗂️ INSURANCE_CONFIG_MIGRATED_V2");
+    msg!("  Insurance config: {}", insurance_config.key);
+    msg!("  Admin: {}", admin.key);
+    msg!("  V1 income: {}", v1_income_e6);
+    msg!("  Liquidation income: {}", config.total_liquidation_income_e6);
+    msg!("  Trading fees: {}", config.total_trading_fee_e6);
+    msg!("  Timestamp: {}", current_ts);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Fund Vault Audit
+// =============================================================================
+
+use solana_program::program_option::COption;
+
+/// Fund vault must be owned by the Fund PDA with no delegate or close authority
+fn assert_fund_vault_authorities(vault: &spl_token::state::Account, fund_key: &Pubkey) -> ProgramResult {
+    if vault.owner != *fund_key {
+        msg!("Fund vault owner {} is not the fund {}", vault.owner, fund_key);
+        return Err(FundError::FundVaultOwnerMismatch.into());
+    }
+    if let COption::Some(delegate) = vault.delegate {
+        msg!("Fund vault delegate set: {} ({})", delegate, vault.delegated_amount);
+        return Err(FundError::FundVaultDelegateSet.into());
+    }
+    if let COption::Some(close_authority) = vault.close_authority {
+        msg!("Fund vault close authority set: {}", close_authority);
+        return Err(FundError::FundVaultCloseAuthoritySet.into());
+    }
+    Ok(())
+}
+
+/// Check the fund vault's mint (see `Fund::check_vault_mint`)
+fn assert_fund_vault_mint(fund: &Fund, fund_vault: &AccountInfo) -> ProgramResult {
+    let vault = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    if let Err(err) = fund.check_vault_mint(&vault.mint) {
+        msg!("Fund vault mint {} does not match the fund's denomination", vault.mint);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Audit a fund vault, revoking authorities the Fund PDA can clear
+fn process_audit_fund_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let cranker = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        cranker: signer;
+        fund_account: owner = program_id;
+        fund_vault: writable, owner = &spl_token::id();
+        token_program: key = &spl_token::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let vault = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    if vault.owner != *fund_account.key {
+        msg!("Fund vault owner {} is not the fund {}", vault.owner, fund_account.key);
+        return Err(FundError::FundVaultOwnerMismatch.into());
+    }
+    // Only the current close authority can change it
+    if let COption::Some(close_authority) = vault.close_authority {
+        if close_authority != *fund_account.key {
+            msg!("Fund vault close authority set: {}", close_authority);
+            return Err(FundError::FundVaultCloseAuthoritySet.into());
+        }
+    }
+    
+    let fund_seeds = Fund::seeds(&fund.manager, fund.fund_index);
+    let fund_seeds_refs: Vec<&[u8]> = fund_seeds.iter().map(|s| s.as_slice()).collect();
+    let (_, fund_bump) = Pubkey::find_program_address(&fund_seeds_refs, program_id);
+    let signer_seeds: &[&[u8]] = &[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund_bump]];
+    
+    let revoked_delegate = vault.delegate;
+    if revoked_delegate.is_some() {
+        invoke_signed(
+            &spl_token::instruction::revoke(&spl_token::id(), fund_vault.key, fund_account.key, &[])?,
+            &[fund_vault.clone(), fund_account.clone(), token_program.clone()],
+            &[signer_seeds],
+        )?;
+    }
+    let cleared_close_authority = vault.close_authority.is_some();
+    if cleared_close_authority {
+        invoke_signed(
+            &spl_token::instruction::set_authority(
+                &spl_token::id(),
+                fund_vault.key,
+                None,
+                spl_token::instruction::AuthorityType::CloseAccount,
+                fund_account.key,
+                &[],
+            )?,
+            &[fund_vault.clone(), fund_account.clone(), token_program.clone()],
+            &[signer_seeds],
+        )?;
+    }
+    
+    msg!("🔍 FUND_VAULT_AUDIT");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Vault: {}", fund_vault.key);
+    match revoked_delegate {
+        COption::Some(delegate) => msg!("  Delegate revoked: {} ({})", delegate, vault.delegated_amount),
+        COption::None => msg!("  Delegate: none"),
+    }
+    msg!("  Close authority cleared: {}", cleared_close_authority);
+    msg!("  Clean: {}", revoked_delegate.is_none() && !cleared_close_authority);
+    
+    Ok(())
+}
+
+// =============================================================================
+// LP Fee Statement
+// =============================================================================
+
+/// Attribute the latest fee epoch to an LP's statement
+fn process_record_lp_fee_statement(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let statement_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let statement_seeds = LPFeeStatement::seeds(lp_position.key);
+    let statement_seeds_refs: Vec<&[u8]> = statement_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        payer: signer, writable;
+        fund_account: owner = program_id;
+        lp_position: owner = program_id;
+        statement_account: writable, pda = (&statement_seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    let position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR || position.fund != *fund_account.key {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    if fund.fee_epoch == 0 {
+        return Err(FundError::NoFeeEpoch.into());
+    }
+    // Shares moved at or after the crystallization: the epoch's split is unknown
+    if position.last_update_ts >= fund.fee_epoch_at {
+        msg!("Position updated at {}, fee epoch {} at {}", position.last_update_ts, fund.fee_epoch, fund.fee_epoch_at);
+        return Err(FundError::LPFeeStatementStale.into());
+    }
+    
+    let mut statement = if statement_account.data_is_empty() {
+        let (_, bump) = Pubkey::find_program_address(&statement_seeds_refs, program_id);
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                statement_account.key,
+                Rent::get()?.minimum_balance(LPFeeStatement::SIZE),
+                LPFeeStatement::SIZE as u64,
+                program_id,
+            ),
+            &[payer.clone(), statement_account.clone(), system_program.clone()],
+            &[&[LP_FEE_STATEMENT_SEED, lp_position.key.as_ref(), &[bump]]],
+        )?;
+        LPFeeStatement::new(*fund_account.key, *lp_position.key, position.investor, bump)
+    } else {
+        let statement = LPFeeStatement::try_from_slice(&statement_account.data.borrow())?;
+        if statement.discriminator != LP_FEE_STATEMENT_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        statement
+    };
+    
+    let skipped = fund.fee_epoch.saturating_sub(statement.last_epoch).saturating_sub(1);
+    let entry = LPFeeStatement::entry_for(&fund, position.shares);
+    statement.append(entry)?;
+    statement.serialize(&mut &mut statement_account.data.borrow_mut()[..])?;
+    
+    msg!("🧾 LP_FEE_STATEMENT_RECORDED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Investor: {}", position.investor);
+    msg!("  Epoch: {} (skipped {})", entry.epoch, skipped);
+    msg!("  Shares: {} / {}", entry.shares, fund.fee_epoch_shares);
+    msg!("  Management fee: {}", entry.mgmt_fee_e6);
+    msg!("  Performance fee: {}", entry.perf_fee_e6);
+    log_notification_flag(program_id, accounts, &position.investor, NOTIFY_FEE_COLLECTED);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Stable Swap Deposit
+// =============================================================================
+
+/// Create or update a stable-swap deposit route (Admin only)
+fn process_set_stable_swap_route(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetStableSwapRouteArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let route_account = next_account_info(account_info_iter)?;
+    let input_mint = next_account_info(account_info_iter)?;
+    let output_mint = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = StableSwapRoute::seeds(input_mint.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        authority: signer, writable;
+        route_account: writable, pda = (&seeds_refs, program_id);
+        input_mint: owner = &spl_token::id();
+        output_mint: owner = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    assert_config_admin(program_id, authority, fund_config)?;
+    
+    if args.max_slippage_bps > MAX_STABLE_SWAP_SLIPPAGE_BPS {
+        return Err(FundError::StableSwapSlippageTooHigh.into());
+    }
+    if input_mint.key == output_mint.key || args.swap_program == Pubkey::default() {
+        return Err(FundError::InvalidStableSwapRoute.into());
+    }
+    let input_decimals = spl_token::state::Mint::unpack(&input_mint.data.borrow())?.decimals;
+    let output_decimals = spl_token::state::Mint::unpack(&output_mint.data.borrow())?.decimals;
+    
+    let current_ts = get_current_timestamp()?;
+    let mut route = if route_account.data_is_empty() {
+        let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                route_account.key,
+                Rent::get()?.minimum_balance(StableSwapRoute::SIZE),
+                StableSwapRoute::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), route_account.clone(), system_program.clone()],
+            &[&[STABLE_SWAP_ROUTE_SEED, input_mint.key.as_ref(), &[bump]]],
+        )?;
+        StableSwapRoute::new(
+            *input_mint.key,
+            *output_mint.key,
+            input_decimals,
+            output_decimals,
+            args.swap_program,
+            args.max_slippage_bps,
+            bump,
+            current_ts,
+        )
+    } else {
+        load_stable_swap_route(program_id, route_account)?
+    };
+    
+    route.output_mint = *output_mint.key;
+    route.input_decimals = input_decimals;
+    route.output_decimals = output_decimals;
+    route.swap_program = args.swap_program;
+    route.max_slippage_bps = args.max_slippage_bps;
+    route.is_active = args.is_active;
+    route.last_update_ts = current_ts;
+    route.serialize(&mut &mut route_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ STABLE_SWAP_ROUTE_SET");
+    msg!("  Input mint: {}", route.input_mint);
+    msg!("  Output mint: {}", route.output_mint);
+    msg!("  Swap program: {}", route.swap_program);
+    msg!("  Max slippage: {} bps", route.max_slippage_bps);
+    msg!("  Active: {}", route.is_active);
+    
+    Ok(())
+}
+
+/// Load a StableSwapRoute owned by this program
+fn load_stable_swap_route(program_id: &Pubkey, route_account: &AccountInfo) -> Result<StableSwapRoute, ProgramError> {
+    assert_owned_by(route_account, program_id)?;
+    let route = StableSwapRoute::try_from_slice(&route_account.data.borrow())?;
+    if route.discriminator != STABLE_SWAP_ROUTE_DISCRIMINATOR {
+        return Err(FundError::InvalidStableSwapRoute.into());
+    }
+    Ok(route)
+}
+
+/// Swap an alternate stablecoin into the vault mint, then deposit the proceeds
+fn process_deposit_via_stable_swap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: DepositViaStableSwapArgs,
+) -> ProgramResult {
+    const FIXED_ACCOUNTS: usize = 12;
+    let swap_end = FIXED_ACCOUNTS + args.swap_account_count as usize;
+    if accounts.len() < swap_end {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    
+    let investor = &accounts[0];
+    let fund_account = &accounts[1];
+    let fund_vault = &accounts[2];
+    let investor_output = &accounts[3];
+    let route_account = &accounts[9];
+    let investor_input = &accounts[10];
+    let swap_program = &accounts[11];
+    let swap_accounts = &accounts[FIXED_ACCOUNTS..swap_end];
+    
+    validate_accounts! {
+        investor: signer;
+        fund_account: owner = program_id;
+        investor_output: writable, owner = &spl_token::id();
+        route_account: writable;
+        investor_input: writable, owner = &spl_token::id();
+    }
+    if args.amount_in == 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    
+    let mut route = load_stable_swap_route(program_id, route_account)?;
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+        swap_program: key = &route.swap_program;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    let vault_mint = spl_token::state::Account::unpack(&fund_vault.data.borrow())?.mint;
+    let input_before = spl_token::state::Account::unpack(&investor_input.data.borrow())?;
+    let output_before = spl_token::state::Account::unpack(&investor_output.data.borrow())?;
+    if !route.is_active
+        || fund.sol_denominated
+        || route.output_mint != vault_mint
+        || input_before.mint != route.input_mint
+        || output_before.mint != route.output_mint
+        || input_before.owner != *investor.key
+        || output_before.owner != *investor.key
+    {
+        return Err(FundError::InvalidStableSwapRoute.into());
+    }
+    // The swap only ever sees the investor's side
+    if swap_accounts.iter().any(|a| a.key == fund_account.key || a.key == fund_vault.key) {
+        return Err(FundError::InvalidStableSwapRoute.into());
+    }
+    
+    crate::cpi::stable_swap(swap_program, swap_accounts, args.swap_data)?;
+    
+    let input_after = spl_token::state::Account::unpack(&investor_input.data.borrow())?;
+    let output_after = spl_token::state::Account::unpack(&investor_output.data.borrow())?;
+    let spent = input_before.amount.saturating_sub(input_after.amount);
+    let received = output_after.amount.saturating_sub(output_before.amount);
+    if spent > args.amount_in {
+        msg!("Swap spent {} > amount_in {}", spent, args.amount_in);
+        return Err(FundError::StableSwapInputExceeded.into());
+    }
+    let min_out = route.min_output(spent).max(args.min_amount_out);
+    if received == 0 || received < min_out {
+        msg!("Swap returned {} < minimum {} for {} in", received, min_out, spent);
+        return Err(FundError::StableSwapSlippageExceeded.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    route.record_swap(spent, received, current_ts);
+    route.serialize(&mut &mut route_account.data.borrow_mut()[..])?;
+    
+    msg!("🔁 STABLE_SWAP_DEPOSIT");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Investor: {}", investor.key);
+    msg!("  Input: {} {}", spent, route.input_mint);
+    msg!("  Output: {} {}", received, route.output_mint);
+    
+    // Regular deposit of the proceeds, with the DepositToFund accounts only
+    let deposit_accounts: Vec<AccountInfo> = accounts[..9].iter().chain(accounts[swap_end..].iter()).cloned().collect();
+    process_deposit_to_fund(
+        program_id,
+        &deposit_accounts,
+        DepositToFundArgs {
+            amount: received,
+            campaign_id: args.campaign_id,
+            memo: args.memo,
+        },
+        false,
+        false,
+    )
+}
+
+// =============================================================================
+// Fee Rebates
+// =============================================================================
+
+/// Credit exchange fee rebates to a fund (CPI from Ledger)
+fn process_record_fee_rebate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RecordFeeRebateArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let caller = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        fund_account: writable, owner = program_id;
+        fund_config: owner = program_id;
+    }
+    
+    // Credits NAV, so only the Ledger may call it: its fund caller PDA must sign
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    assert_ledger_caller(&config, caller)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    fund.accrue_aum(current_ts);
+    fund.record_fee_rebate(args.rebate_e6)?;
+    fund.last_update_ts = current_ts;
+    // Funds that predate total_fee_rebates_e6 must be grown (ReallocAccount) first
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    msg!("💸 FUND_FEE_REBATE_RECORDED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Rebate: {}", args.rebate_e6);
+    msg!("  Total rebates: {}", fund.total_fee_rebates_e6);
+    msg!("  New NAV: {}", fund.stats.current_nav_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Risk Enforcement
+// =============================================================================
+
+/// Reduce a fund's positions after a persisting risk limit breach (permissionless keeper)
+fn process_enforce_risk_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: EnforceRiskLimitsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let keeper = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let limits_account = next_account_info(account_info_iter)?;
+    let state_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let keeper_usdc = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let ledger_program = next_account_info(account_info_iter)?;
+    let position = next_account_info(account_info_iter)?;
+    let user_account = next_account_info(account_info_iter)?;
+    let vault_config = next_account_info(account_info_iter)?;
+    let insurance_fund = next_account_info(account_info_iter)?;
+    let ledger_config = next_account_info(account_info_iter)?;
+    let user_stats = next_account_info(account_info_iter)?;
+    let vault_program = next_account_info(account_info_iter)?;
+    let mark_price_account = next_account_info(account_info_iter)?;
+    
+    let limits_seeds = ManagerRiskLimits::seeds();
+    let limits_seeds_refs: Vec<&[u8]> = limits_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        keeper: signer;
+        fund_account: writable, owner = program_id;
+        fund_config: owner = program_id;
+        limits_account: owner = program_id, pda = (&limits_seeds_refs, program_id);
+        fund_vault: writable;
+        keeper_usdc: writable;
+        token_program: key = &spl_token::id();
+    }
+    
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    validate_accounts! {
+        ledger_program: key = &config.ledger_program;
+        vault_program: key = &config.vault_program;
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let limits = ManagerRiskLimits::try_from_slice(&limits_account.data.borrow())?;
+    if limits.discriminator != MANAGER_RISK_LIMITS_DISCRIMINATOR {
+        return Err(FundError::ManagerRiskStateRequired.into());
+    }
+    let mut state = load_manager_risk_state(program_id, state_account)?;
+    if state.manager != fund.manager {
+        return Err(FundError::ManagerRiskStateRequired.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    // Judged on this fund's own book and equity. The book is only written by the
+    // Ledger (RecordFundExposure, signed by its fund caller PDA) and must be recent
+    if !fund.has_fresh_exposure(current_ts) {
+        msg!("Exposure last reported at {}", fund.exposure_updated_at);
+        return Err(FundError::FundExposureStale.into());
+    }
+    let equity_e6 = fund.stats.total_value_e6();
+    let excess_e6 = limits.fund_excess_notional_e6(state.open_notional_e6, fund.open_notional_e6, equity_e6);
+    
+    if excess_e6 == 0 {
+        let was_breached = fund.risk_breach_since != 0;
+        fund.observe_risk_breach(0, current_ts);
+        fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+        if was_breached {
+            msg!("✅ RISK_LIMIT_BREACH_CLEARED");
+            msg!("  Manager: {}", fund.manager);
+            msg!("  Fund: {}", fund_account.key);
+        }
+        return Ok(());
+    }
+    
+    if fund.observe_risk_breach(excess_e6, current_ts) {
+        // Funds that predate risk_breach_since must be grown (ReallocAccount) first
+        fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+        msg!("⚠️ RISK_LIMIT_BREACHED");
+        msg!("  Manager: {}", fund.manager);
+        msg!("  Fund: {}", fund_account.key);
+        msg!("  Open notional: {} (equity {})", fund.open_notional_e6, equity_e6);
+        msg!("  Manager open notional: {}", state.open_notional_e6);
+        msg!("  Excess: {}", excess_e6);
+        msg!("  Enforceable at: {}", current_ts.saturating_add(RISK_BREACH_GRACE_SECS));
+        return Ok(());
+    }
+    if !fund.is_risk_breach_enforceable(current_ts) {
+        return Err(FundError::RiskBreachGracePeriodActive.into());
+    }
+    
+    if args.size_e6 == 0 || args.price_e6 == 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    // The keeper only picks the fill inside a band around the mark price
+    let mark_e6 = load_mark_price(program_id, mark_price_account, args.market_index, current_ts)?;
+    if !MarkPrice::within_band(mark_e6, args.price_e6, RISK_ENFORCEMENT_MAX_PRICE_DEVIATION_BPS) {
+        msg!("Exit price {} outside the band around mark {}", args.price_e6, mark_e6);
+        return Err(FundError::PriceOutsideMarkBand.into());
+    }
+    // Reduce no further than back to the limit (size rounded up), valued at the mark
+    let max_size_e6 = (excess_e6 as u128 * 1_000_000).div_ceil(mark_e6 as u128).min(u64::MAX as u128) as u64;
+    // Never more than the fund actually holds in the Ledger position
+    let position_size_e6 = ledger_position_size(position, ledger_program.key, fund_account.key, args.market_index)?;
+    let size_e6 = args.size_e6.min(max_size_e6).min(position_size_e6);
+    let notional_e6 = ((size_e6 as u128) * (mark_e6 as u128) / 1_000_000).min(u64::MAX as u128) as u64;
+    
+    let (manager, fund_index_bytes, fund_bump) = (fund.manager, fund.fund_index.to_le_bytes(), [fund.bump]);
+    let fund_signer_seeds: &[&[u8]] = &[FUND_SEED, manager.as_ref(), &fund_index_bytes, &fund_bump];
+    
+    crate::cpi::close_position(
+        ledger_program.key,
+        fund_account.clone(),  // Fund acts as relayer
+        position.clone(),
+        user_account.clone(),
+        vault_config.clone(),
+        insurance_fund.clone(),
+        ledger_config.clone(),
+        user_stats.clone(),
+        vault_program.clone(),
+        *fund_account.key,  // User is the fund itself
+        args.market_index,
+        size_e6,
+        args.price_e6,
+        current_ts as u64,
+        &[fund_signer_seeds],
+    )?;
+    
+    // Book only what this fund could have had open
+    let closed_e6 = fund.record_enforced_close(notional_e6);
+    state.record_close(closed_e6, current_ts);
+    state.serialize(&mut &mut state_account.data.borrow_mut()[..])?;
+    let remaining_excess_e6 = limits.fund_excess_notional_e6(state.open_notional_e6, fund.open_notional_e6, equity_e6);
+    fund.observe_risk_breach(remaining_excess_e6, current_ts);
+    
+    // Keeper incentive is charged to the fund as a realized loss
+    let keeper_fee_e6 = risk_enforcement_keeper_fee_e6(closed_e6).min(equity_e6.max(0) as u64) as i64;
+    if keeper_fee_e6 > 0 {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                fund_vault.key,
+                keeper_usdc.key,
+                fund_account.key,
+                &[],
+                fund.e6_to_token_amount(keeper_fee_e6)?,
+            )?,
+            &[fund_vault.clone(), keeper_usdc.clone(), fund_account.clone(), token_program.clone()],
+            &[fund_signer_seeds],
+        )?;
+        fund.record_pnl(-keeper_fee_e6)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("🛡️ RISK_LIMIT_ENFORCED");
+    msg!("  Manager: {}", fund.manager);
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Market: {}", args.market_index);
+    msg!("  Closed: size={}, price={} (mark {}), notional={}", size_e6, args.price_e6, mark_e6, closed_e6);
+    msg!("  Remaining excess: {}", remaining_excess_e6);
+    msg!("  Keeper: {} (fee {})", keeper.key, keeper_fee_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Notification Preferences
+// =============================================================================
+
+/// Create or update an investor's NotificationPrefs
+fn process_set_notification_prefs(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetNotificationPrefsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let prefs_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = NotificationPrefs::seeds(investor.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        investor: signer, writable;
+        prefs_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(prefs_account, &seeds_refs, program_id)?;
+    
+    let current_ts = get_current_timestamp()?;
+    let mut prefs = if prefs_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                prefs_account.key,
+                Rent::get()?.minimum_balance(NotificationPrefs::SIZE),
+                NotificationPrefs::SIZE as u64,
+                program_id,
+            ),
+            &[investor.clone(), prefs_account.clone(), system_program.clone()],
+            &[&[NOTIFICATION_PREFS_SEED, investor.key.as_ref(), &[bump]]],
+        )?;
+        NotificationPrefs::new(*investor.key, bump, current_ts)
+    } else {
+        load_notification_prefs(program_id, prefs_account)?
+    };
+    
+    prefs.update(args.endpoint_hash, args.event_mask, current_ts)?;
+    prefs.serialize(&mut &mut prefs_account.data.borrow_mut()[..])?;
+    
+    msg!("🔔 NOTIFICATION_PREFS_UPDATED");
+    msg!("  Investor: {}", investor.key);
+    msg!("  Event mask: {:#x}", prefs.event_mask);
+    
+    Ok(())
+}
+
+/// Close an investor's NotificationPrefs (opt out)
+fn process_close_notification_prefs(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let prefs_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        investor: signer, writable;
+        prefs_account: writable;
+    }
+    
+    let prefs = load_notification_prefs(program_id, prefs_account)?;
+    if prefs.investor != *investor.key {
+        return Err(FundError::Unauthorized.into());
+    }
+    close_program_account(prefs_account, investor)?;
+    
+    msg!("🔕 NOTIFICATION_PREFS_CLOSED");
+    msg!("  Investor: {}", investor.key);
+    
+    Ok(())
+}
+
+/// Load a NotificationPrefs account
+fn load_notification_prefs(
+    program_id: &Pubkey,
+    prefs_account: &AccountInfo,
+) -> Result<NotificationPrefs, ProgramError> {
+    assert_owned_by(prefs_account, program_id)?;
+    let prefs = NotificationPrefs::try_from_slice(&prefs_account.data.borrow())?;
+    if prefs.discriminator != NOTIFICATION_PREFS_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    Ok(prefs)
+}
+
+/// Log the investor's notification flag for `event` if their NotificationPrefs is passed
+/// 
+/// Optional and best effort: a missing or foreign account never fails the
+/// surrounding instruction.
+fn log_notification_flag(program_id: &Pubkey, accounts: &[AccountInfo], investor: &Pubkey, event: u32) {
+    let seeds = NotificationPrefs::seeds(investor);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (prefs_key, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    
+    let prefs = accounts
+        .iter()
+        .find(|a| *a.key == prefs_key)
+        .and_then(|a| load_notification_prefs(program_id, a).ok());
+    if let Some(prefs) = prefs.filter(|p| p.wants(event)) {
+        msg!("  Notify: {:#x}", prefs.event_mask);
+    }
+}
+
+// =============================================================================
+// Fund Exposure
+// =============================================================================
+
+/// Mirror the Ledger's open interest and margin usage into the Fund (CPI from Ledger)
+fn process_record_fund_exposure(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RecordFundExposureArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let caller = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        fund_account: writable, owner = program_id;
+        fund_config: owner = program_id;
+    }
+    
+    // EnforceRiskLimits acts on this figure: only the Ledger's fund caller PDA may write it
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    assert_ledger_caller(&config, caller)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    fund.record_exposure(
+        args.open_notional_e6,
+        args.margin_used_e6,
+        args.free_collateral_e6,
+        current_ts,
+    );
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    msg!("📊 FUND_EXPOSURE_UPDATED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Open notional: {}", fund.open_notional_e6);
+    msg!("  Margin used: {} (free {})", fund.margin_used_e6, fund.free_collateral_e6);
+    msg!("  Utilization: {} bps", fund.margin_utilization_bps());
+    
+    Ok(())
+}
+
+// =============================================================================
+// Share Supply Checkpoints
+// =============================================================================
+
+/// Create a fund's ShareCheckpoints history (Manager only)
+fn process_initialize_share_checkpoints(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: InitializeShareCheckpointsArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let history_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = ShareCheckpoints::seeds(fund_account.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        history_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(history_account, &seeds_refs, program_id)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    if !history_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let threshold_bps = match args.threshold_bps {
+        0 => DEFAULT_SHARE_CHECKPOINT_THRESHOLD_BPS,
+        bps if bps as u64 > BPS_DENOMINATOR => return Err(ProgramError::InvalidArgument),
+        bps => bps,
+    };
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            manager.key,
+            history_account.key,
+            Rent::get()?.minimum_balance(ShareCheckpoints::SIZE),
+            ShareCheckpoints::SIZE as u64,
+            program_id,
+        ),
+        &[manager.clone(), history_account.clone(), system_program.clone()],
+        &[&[SHARE_CHECKPOINTS_SEED, fund_account.key.as_ref(), &[bump]]],
+    )?;
+    
+    let mut history = ShareCheckpoints::new(*fund_account.key, threshold_bps, bump);
+    history.maybe_record(get_current_timestamp()?, fund.stats.total_shares, fund.stats.current_nav_e6);
+    history.serialize(&mut &mut history_account.data.borrow_mut()[..])?;
+    
+    // From now on DepositToFund / RedeemFromFund / RedeemAll require the PDA;
+    // funds that predate share_checkpoints_enabled must be grown (ReallocAccount) first
+    fund.share_checkpoints_enabled = true;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("✅ SHARE_CHECKPOINTS_CREATED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Threshold: {} bps", threshold_bps);
+    msg!("  Total shares: {}", fund.stats.total_shares);
+    
+    Ok(())
+}
+
+/// Append a share supply checkpoint if due (permissionless)
+fn process_record_share_checkpoint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let fund_account = next_account_info(account_info_iter)?;
+    let _history_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        fund_account: owner = program_id;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    record_share_checkpoint(program_id, accounts, fund_account.key, &fund, get_current_timestamp()?)
+}
+
+/// Append to the fund's ShareCheckpoints when the supply moved past its threshold
+///
+/// The PDA is required once InitializeShareCheckpoints enabled it on the fund.
+fn record_share_checkpoint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fund_key: &Pubkey,
+    fund: &Fund,
+    current_ts: i64,
+) -> ProgramResult {
+    let seeds = ShareCheckpoints::seeds(fund_key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (history_key, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let history_account = match accounts.iter().find(|a| *a.key == history_key) {
+        Some(history_account) => history_account,
+        None if fund.share_checkpoints_enabled => {
+            msg!("ShareCheckpoints PDA {} required", history_key);
+            return Err(FundError::ShareCheckpointsRequired.into());
+        }
+        None => return Ok(()),
+    };
+    
+    validate_accounts! {
+        history_account: writable, owner = program_id;
+    }
+    let mut history = ShareCheckpoints::try_from_slice(&history_account.data.borrow())?;
+    if history.discriminator != SHARE_CHECKPOINTS_DISCRIMINATOR || history.fund != *fund_key {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    if history.maybe_record(current_ts, fund.stats.total_shares, fund.stats.current_nav_e6) {
+        history.serialize(&mut &mut history_account.data.borrow_mut()[..])?;
+        
+        msg!("📍 SHARE_CHECKPOINT_RECORDED");
+        msg!("  Fund: {}", fund_key);
+        msg!("  Total shares: {}", fund.stats.total_shares);
+        msg!("  NAV: {}", fund.stats.current_nav_e6);
+        msg!("  Checkpoint: {}", history.checkpoint_count);
+    }
+    
+    Ok(())
+}
+
+// =============================================================================
+// Share Supply Reconciliation
+// =============================================================================
+
+/// Compare the share mint with FundStats; correct drift or flag the fund (Admin / keeper)
+fn process_reconcile_share_supply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let fund_account = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+    let keeper = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        fund_account: writable, owner = program_id;
+        share_mint: owner = &spl_token::id();
+        keeper: signer;
+        fund_config: owner = program_id;
+    }
+    
+    // Flagging blocks every LP's flows, so only trusted cranks may trigger it
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    if !config.is_authorized_relayer(keeper.key) {
+        return Err(FundError::UnauthorizedCaller.into());
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if fund.setup_pending {
+        return Err(FundError::FundSetupPending.into());
+    }
+    validate_accounts! {
+        share_mint: key = &fund.share_mint;
+    }
+    
+    let mint = spl_token::state::Mint::unpack(&share_mint.data.borrow())?;
+    let authority_key = if fund.share_authority_version == 0 {
+        *fund_account.key
+    } else {
+        let seeds = Fund::share_authority_seeds(fund_account.key, fund.share_authority_version);
+        let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+        Pubkey::find_program_address(&seeds_refs, program_id).0
+    };
+    let mint_controlled = mint.mint_authority == COption::Some(authority_key)
+        && mint.decimals == SHARE_MINT_DECIMALS;
+    
+    let current_ts = get_current_timestamp()?;
+    let recorded_shares = fund.stats.total_shares;
+    let was_inconsistent = fund.is_share_supply_inconsistent();
+    let outcome = fund.reconcile_share_supply(mint.supply, mint_controlled, current_ts)?;
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+    
+    match outcome {
+        ShareSupplyReconciliation::Consistent => {
+            msg!("✅ SHARE_SUPPLY_CONSISTENT");
+            msg!("  Fund: {}", fund_account.key);
+            msg!("  Total shares: {}", mint.supply);
+            if was_inconsistent {
+                msg!("  Inconsistent flag cleared");
+            }
+        }
+        ShareSupplyReconciliation::Fixed { previous_shares } => {
+            msg!("⚖️ SHARE_SUPPLY_RECONCILED");
+            msg!("  Fund: {}", fund_account.key);
+            msg!("  Recorded shares: {}", previous_shares);
+            msg!("  Mint supply: {}", mint.supply);
+            msg!("  NAV: {}", fund.stats.current_nav_e6);
+        }
+        ShareSupplyReconciliation::Inconsistent => {
+            msg!("🚨 SHARE_SUPPLY_INCONSISTENT");
+            msg!("  Fund: {}", fund_account.key);
+            msg!("  Recorded shares: {}", recorded_shares);
+            msg!("  Mint supply: {}", mint.supply);
+            msg!("  Mint controlled: {}", mint_controlled);
+            msg!("  Since: {}", fund.share_supply_inconsistent_since);
+        }
+    }
+    
+    Ok(())
+}
+
+// =============================================================================
+// Position NFTs
+// =============================================================================
+
+/// Wrap a whole LP position into a transferable position NFT (LP)
+fn process_wrap_position_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let position_nft = next_account_info(account_info_iter)?;
+    let nft_mint = next_account_info(account_info_iter)?;
+    let nft_token = next_account_info(account_info_iter)?;
+    let investor_shares = next_account_info(account_info_iter)?;
+    let escrow = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        investor: signer, writable;
+        fund_account: owner = program_id;
+        lp_position: writable, owner = program_id;
+        position_nft: writable;
+        nft_mint: writable;
+        nft_token: signer, writable;
+        investor_shares: writable;
+        escrow: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if fund.distribution_in_progress {
+        return Err(FundError::DistributionInProgress.into());
+    }
+    validate_accounts! {
+        share_mint: key = &fund.share_mint;
+    }
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR
+        || position.fund != *fund_account.key
+        || position.investor != *investor.key
+    {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    if position.is_empty() {
+        return Err(FundError::InsufficientShares.into());
+    }
+    
+    let record_seeds = PositionNFT::seeds(lp_position.key);
+    let record_seeds_refs: Vec<&[u8]> = record_seeds.iter().map(|s| s.as_slice()).collect();
+    let record_bump = assert_pda(position_nft, &record_seeds_refs, program_id)?;
+    
+    let mint_seeds = PositionNFT::mint_seeds(lp_position.key);
+    let mint_seeds_refs: Vec<&[u8]> = mint_seeds.iter().map(|s| s.as_slice()).collect();
+    let mint_bump = assert_pda(nft_mint, &mint_seeds_refs, program_id)?;
+    
+    let escrow_seeds = PositionNFT::escrow_seeds(lp_position.key);
+    let escrow_seeds_refs: Vec<&[u8]> = escrow_seeds.iter().map(|s| s.as_slice()).collect();
+    let escrow_bump = assert_pda(escrow, &escrow_seeds_refs, program_id)?;
+    
+    let rent = Rent::get()?;
+    let fund_index_bytes = fund.fund_index.to_le_bytes();
+    let fund_signer_seeds: &[&[u8]] = &[FUND_SEED, fund.manager.as_ref(), &fund_index_bytes, &[fund.bump]];
+    
+    // Position NFT mint (0 decimals, Fund PDA as authority), reused on re-wrap
+    if nft_mint.data_is_empty() {
+        let mint_space = spl_token::state::Mint::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                nft_mint.key,
+                rent.minimum_balance(mint_space),
+                mint_space as u64,
+                &spl_token::id(),
+            ),
+            &[investor.clone(), nft_mint.clone(), system_program.clone()],
+            &[&[POSITION_NFT_MINT_SEED, lp_position.key.as_ref(), &[mint_bump]]],
+        )?;
+        
+        invoke(
+            &spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                nft_mint.key,
+                fund_account.key,
+                Some(fund_account.key),
+                0,
+            )?,
+            &[nft_mint.clone(), token_program.clone()],
+        )?;
+    }
+    
+    // Share escrow (owned by the Fund PDA)
+    if escrow.data_is_empty() {
+        let token_space = spl_token::state::Account::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                escrow.key,
+                rent.minimum_balance(token_space),
+                token_space as u64,
+                &spl_token::id(),
+            ),
+            &[investor.clone(), escrow.clone(), system_program.clone()],
+            &[&[POSITION_NFT_ESCROW_SEED, lp_position.key.as_ref(), &[escrow_bump]]],
+        )?;
+        
+        invoke(
+            &spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                escrow.key,
+                share_mint.key,
+                fund_account.key,
+            )?,
+            &[escrow.clone(), share_mint.clone(), token_program.clone()],
+        )?;
+    }
+    
+    // Fresh token account for the NFT (owned by the LP)
+    let token_space = spl_token::state::Account::LEN;
+    invoke(
+        &system_instruction::create_account(
+            investor.key,
+            nft_token.key,
+            rent.minimum_balance(token_space),
+            token_space as u64,
+            &spl_token::id(),
+        ),
+        &[investor.clone(), nft_token.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account3(
+            &spl_token::id(),
+            nft_token.key,
+            nft_mint.key,
+            investor.key,
+        )?,
+        &[nft_token.clone(), nft_mint.clone(), token_program.clone()],
+    )?;
+    
+    let mut record = if position_nft.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                investor.key,
+                position_nft.key,
+                rent.minimum_balance(PositionNFT::SIZE),
+                PositionNFT::SIZE as u64,
+                program_id,
+            ),
+            &[investor.clone(), position_nft.clone(), system_program.clone()],
+            &[&[POSITION_NFT_SEED, lp_position.key.as_ref(), &[record_bump]]],
+        )?;
+        PositionNFT::new(*fund_account.key, *lp_position.key, *nft_mint.key, record_bump)
+    } else {
+        let record = PositionNFT::try_from_slice(&position_nft.data.borrow())?;
+        if record.discriminator != POSITION_NFT_DISCRIMINATOR || record.lp_position != *lp_position.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        record
+    };
+    
+    let current_ts = get_current_timestamp()?;
+    record.wrap(&mut position, *position_nft.key, current_ts)?;
+    
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            investor_shares.key,
+            escrow.key,
+            investor.key,
+            &[],
+            record.shares,
+        )?,
+        &[investor_shares.clone(), escrow.clone(), investor.clone(), token_program.clone()],
+    )?;
+    
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            &spl_token::id(),
+            nft_mint.key,
+            nft_token.key,
+            fund_account.key,
+            &[],
+            1,
+        )?,
+        &[nft_mint.clone(), nft_token.clone(), fund_account.clone(), token_program.clone()],
+        &[fund_signer_seeds],
+    )?;
+    
+    // Positions created before the lock fields need the room
+    if lp_position.data_len() < LPPosition::SIZE {
+        realloc_with_rent(lp_position, investor, system_program, LPPosition::SIZE)?;
+    }
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    record.serialize(&mut &mut position_nft.data.borrow_mut()[..])?;
+    
+    msg!("🎟️ POSITION_NFT_WRAPPED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  LP Position: {}", lp_position.key);
+    msg!("  NFT mint: {}", nft_mint.key);
+    msg!("  Holder: {}", investor.key);
+    msg!("  Shares: {}", record.shares);
+    
+    Ok(())
+}
+
+/// Check that `holder` owns the position NFT in `token_account`
+fn assert_position_nft_holder(record: &PositionNFT, token_account: &AccountInfo, holder: &Pubkey) -> ProgramResult {
+    validate_accounts! {
+        token_account: owner = &spl_token::id();
+    }
+    let token = spl_token::state::Account::unpack(&token_account.data.borrow())?;
+    if token.mint != record.nft_mint || token.owner != *holder || token.amount != 1 {
+        return Err(FundError::NotPositionNFTHolder.into());
+    }
+    Ok(())
+}
+
+/// Take over a wrapped LP position as the NFT holder
+fn process_transfer_position_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let holder = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let position_nft = next_account_info(account_info_iter)?;
+    let nft_token = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        holder: signer;
+        lp_position: writable, owner = program_id;
+        position_nft: writable, owner = program_id;
+    }
+    
+    let mut record = PositionNFT::try_from_slice(&position_nft.data.borrow())?;
+    if record.discriminator != POSITION_NFT_DISCRIMINATOR || record.lp_position != *lp_position.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    assert_position_nft_holder(&record, nft_token, holder.key)?;
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    let previous = position.investor;
+    record.transfer(&mut position, *holder.key, get_current_timestamp()?)?;
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    record.serialize(&mut &mut position_nft.data.borrow_mut()[..])?;
+    
+    msg!("🔁 POSITION_NFT_TRANSFERRED");
+    msg!("  Fund: {}", record.fund);
+    msg!("  LP Position: {}", lp_position.key);
+    msg!("  From: {}", previous);
+    msg!("  To: {}", holder.key);
+    msg!("  Shares: {}", record.shares);
+    
+    Ok(())
+}
+
+/// Burn the position NFT and release the escrowed shares
+fn process_unwrap_position_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let holder = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let position_nft = next_account_info(account_info_iter)?;
+    let nft_mint = next_account_info(account_info_iter)?;
+    let nft_token = next_account_info(account_info_iter)?;
+    let escrow = next_account_info(account_info_iter)?;
+    let holder_shares = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        holder: signer;
+        fund_account: owner = program_id;
+        lp_position: writable, owner = program_id;
+        position_nft: writable, owner = program_id;
+        nft_mint: writable;
+        nft_token: writable;
+        escrow: writable;
+        holder_shares: writable;
+        token_program: key = &spl_token::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let mut record = PositionNFT::try_from_slice(&position_nft.data.borrow())?;
+    if record.discriminator != POSITION_NFT_DISCRIMINATOR
+        || record.lp_position != *lp_position.key
+        || record.fund != *fund_account.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    validate_accounts! {
+        nft_mint: key = &record.nft_mint;
+    }
+    let escrow_seeds = PositionNFT::escrow_seeds(lp_position.key);
+    let escrow_seeds_refs: Vec<&[u8]> = escrow_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(escrow, &escrow_seeds_refs, program_id)?;
+    assert_position_nft_holder(&record, nft_token, holder.key)?;
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    // A new holder takes over the position first
+    if position.investor != *holder.key {
+        return Err(FundError::NotPositionNFTHolder.into());
+    }
+    
+    let shares = record.unwrap(&mut position, position_nft.key)?;
+    
+    invoke(
+        &spl_token::instruction::burn(
+            &spl_token::id(),
+            nft_token.key,
+            nft_mint.key,
+            holder.key,
+            &[],
+            1,
+        )?,
+        &[nft_token.clone(), nft_mint.clone(), holder.clone(), token_program.clone()],
+    )?;
+    
+    let fund_index_bytes = fund.fund_index.to_le_bytes();
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            escrow.key,
+            holder_shares.key,
+            fund_account.key,
+            &[],
+            shares,
+        )?,
+        &[escrow.clone(), holder_shares.clone(), fund_account.clone(), token_program.clone()],
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund_index_bytes, &[fund.bump]]],
+    )?;
+    
+    position.last_update_ts = get_current_timestamp()?;
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    record.serialize(&mut &mut position_nft.data.borrow_mut()[..])?;
+    
+    msg!("🎟️ POSITION_NFT_UNWRAPPED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  LP Position: {}", lp_position.key);
+    msg!("  Holder: {}", holder.key);
+    msg!("  Shares: {}", shares);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Relayer Subsystem Pause
+// =============================================================================
+
+/// Pause or resume all Relayer* instructions (Admin only)
+fn process_set_relayer_system_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetRelayerSystemPausedArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        authority: signer;
+        fund_config: writable, owner = program_id;
+    }
+    
+    let mut config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+    
+    config.relayer_paused = args.is_paused;
+    config.serialize(&mut &mut fund_config.data.borrow_mut()[..])?;
+    
+    if args.is_paused {
+        msg!("⏸️ RELAYER_SYSTEM_PAUSED");
+    } else {
+        msg!("▶️ RELAYER_SYSTEM_RESUMED");
+    }
+    msg!("  Authority: {}", authority.key);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Relayer Stats
+// =============================================================================
+
+/// Create a relayer's RelayerStats PDA (anyone may pay)
+fn process_initialize_relayer_stats(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let payer = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let relayer = next_account_info(account_info_iter)?;
+    let stats_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = RelayerStats::seeds(relayer.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        payer: signer, writable;
+        fund_config: owner = program_id;
+        stats_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(stats_account, &seeds_refs, program_id)?;
+    
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    verify_fund_relayer(&config, relayer.key)?;
+    if !stats_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            stats_account.key,
+            Rent::get()?.minimum_balance(RelayerStats::SIZE),
+            RelayerStats::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), stats_account.clone(), system_program.clone()],
+        &[&[RELAYER_STATS_SEED, relayer.key.as_ref(), &[bump]]],
+    )?;
+    
+    let stats = RelayerStats::new(*relayer.key, bump);
+    stats.serialize(&mut &mut stats_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ RELAYER_STATS_CREATED");
+    msg!("  Relayer: {}", relayer.key);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Insurance Config Update (timelocked)
+// =============================================================================
+
+/// Load the FundConfig authority and require it to be `authority`
+fn assert_fund_config_admin(
+    program_id: &Pubkey,
+    fund_config: &AccountInfo,
+    authority: &AccountInfo,
+) -> ProgramResult {
+    let (config_pda, _) = Pubkey::find_program_address(&[FUND_CONFIG_SEED], program_id);
+
+    validate_accounts! {
+        fund_config: owner = program_id, key = &config_pda;
+    }
+
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+    Ok(())
+}
+
+/// Schedule an InsuranceFundConfig update (Admin only)
+fn process_schedule_insurance_config_update(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateInsuranceConfigArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let insurance_config = next_account_info(account_info_iter)?;
+    let pending_update = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let seeds = PendingInsuranceConfigUpdate::seeds();
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+
+    validate_accounts! {
+        authority: signer, writable;
+        insurance_config: owner = program_id;
+        pending_update: writable, pda = (&seeds_refs, program_id);
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    assert_fund_config_admin(program_id, fund_config, authority)?;
+
+    let config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+
+    if args.adl_trigger_threshold_e6.is_none()
+        && args.withdrawal_delay_secs.is_none()
+        && args.authorized_caller.is_none()
+    {
+        return Err(FundError::InvalidInsuranceFundConfig.into());
+    }
+    if args.adl_trigger_threshold_e6.is_some_and(|t| t < 0) {
+        return Err(FundError::InvalidInsuranceFundConfig.into());
+    }
+    if args
+        .withdrawal_delay_secs
+        .is_some_and(|d| !(0..=MAX_INSURANCE_WITHDRAWAL_DELAY_SECS).contains(&d))
+    {
+        return Err(FundError::InvalidInsuranceFundConfig.into());
+    }
+    if args.authorized_caller == Some(Pubkey::default()) {
+        return Err(FundError::InvalidInsuranceFundConfig.into());
+    }
+
+    let (_, bump) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let current_ts = get_current_timestamp()?;
+
+    if pending_update.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                pending_update.key,
+                rent.minimum_balance(PendingInsuranceConfigUpdate::SIZE),
+                PendingInsuranceConfigUpdate::SIZE as u64,
+                program_id,
+            ),
+            &[authority.clone(), pending_update.clone(), system_program.clone()],
+            &[&[PENDING_INSURANCE_UPDATE_SEED, &[bump]]],
+        )?;
+    } else {
+        assert_owned_by(pending_update, program_id)?;
+    }
+
+    // (Re)scheduling always restarts the timelock
+    let pending = PendingInsuranceConfigUpdate::new(
+        *authority.key,
+        args.adl_trigger_threshold_e6,
+        args.withdrawal_delay_secs,
+        args.authorized_caller,
+        bump,
+        current_ts,
+    );
+    pending.serialize(&mut &mut pending_update.data.borrow_mut()[..])?;
+
+    msg!("⏳ INSURANCE_CONFIG_UPDATE_SCHEDULED");
+    msg!("  ADL threshold: {:?}", args.adl_trigger_threshold_e6);
+    msg!("  Withdrawal delay: {:?}", args.withdrawal_delay_secs);
+    msg!("  Authorized caller: {:?}", args.authorized_caller);
+    msg!("  Executable at: {}", pending.executable_at);
+
+    Ok(())
+}
+
+/// Cancel a scheduled insurance config update (Admin only)
+fn process_cancel_insurance_config_update(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let pending_update = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        authority: signer, writable;
+        pending_update: writable, owner = program_id;
+    }
+
+    let pending = PendingInsuranceConfigUpdate::try_from_slice(&pending_update.data.borrow())?;
+
+    if pending.discriminator != PENDING_INSURANCE_UPDATE_DISCRIMINATOR {
+        return Err(FundError::InsuranceConfigUpdateNotScheduled.into());
+    }
+
+    if pending.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    close_program_account(pending_update, authority)?;
+
+    msg!("✅ INSURANCE_CONFIG_UPDATE_CANCELLED");
+
+    Ok(())
+}
+
+/// Apply a scheduled insurance config update after the timelock (Admin only)
+///
+/// The admin is re-checked against FundConfig, so a handed-off authority
+/// cannot execute an update scheduled by its predecessor.
+fn process_update_insurance_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let insurance_config = next_account_info(account_info_iter)?;
+    let pending_update = next_account_info(account_info_iter)?;
+
+    let (insurance_pda, _) = Pubkey::find_program_address(&[INSURANCE_FUND_CONFIG_SEED], program_id);
+
+    validate_accounts! {
+        authority: signer, writable;
+        insurance_config: writable, owner = program_id, key = &insurance_pda;
+        pending_update: writable, owner = program_id;
+    }
+
+    assert_fund_config_admin(program_id, fund_config, authority)?;
+
+    let pending = PendingInsuranceConfigUpdate::try_from_slice(&pending_update.data.borrow())?;
+
+    if pending.discriminator != PENDING_INSURANCE_UPDATE_DISCRIMINATOR {
+        return Err(FundError::InsuranceConfigUpdateNotScheduled.into());
+    }
+
+    if pending.authority != *authority.key {
+        return Err(FundError::AdminRequired.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+    if !pending.is_executable(current_ts) {
+        return Err(FundError::TimelockNotElapsed.into());
+    }
+
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+
+    pending.apply(&mut config);
+    config.last_update_ts = current_ts;
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
+
+    close_program_account(pending_update, authority)?;
+
+    msg!("✅ INSURANCE_CONFIG_UPDATED");
+    msg!("  ADL threshold: {}", config.adl_trigger_threshold_e6);
+    msg!("  Withdrawal delay: {}", config.withdrawal_delay_secs);
+    msg!("  Authorized caller: {}", config.authorized_caller);
+
+    Ok(())
+}
+
+// =============================================================================
+// LP Position Transfer
+// =============================================================================
+
+/// Move an LP position to another wallet of the same user, fee-free
+///
+/// Unlike redeem + deposit this keeps the cost basis and `deposited_at`.
+/// Records keyed by the old position (deposit receipt, payout allow-list, fee
+/// statement) stay behind.
+fn process_transfer_lp_position(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: TransferLPPositionArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner = next_account_info(account_info_iter)?;
+    let new_owner = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let owner_position = next_account_info(account_info_iter)?;
+    let new_owner_position = next_account_info(account_info_iter)?;
+    let owner_shares = next_account_info(account_info_iter)?;
+    let new_owner_shares = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        owner: signer;
+        new_owner: signer, writable, key = &args.new_owner;
+        fund_account: writable, owner = program_id;
+        owner_position: writable, owner = program_id;
+        new_owner_position: writable;
+        owner_shares: writable;
+        new_owner_shares: writable, owner = &spl_token::id();
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+
+    if owner.key == new_owner.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+
+    if fund.distribution_in_progress {
+        return Err(FundError::DistributionInProgress.into());
+    }
+
+    let mut position = LPPosition::unpack_versioned(&owner_position.data.borrow())?;
+    if position.discriminator != LP_POSITION_DISCRIMINATOR
+        || position.fund != *fund_account.key
+        || position.investor != *owner.key
+    {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+
+    if position.nft_wrapped {
+        return Err(FundError::PositionNFTWrapped.into());
+    }
+
+    if position.is_empty() {
+        return Err(FundError::InsufficientShares.into());
+    }
+
+    if position.locked_shares > 0 {
+        return Err(FundError::SharesLocked.into());
+    }
+
+    let share_account = spl_token::state::Account::unpack(&new_owner_shares.data.borrow())?;
+    if share_account.mint != fund.share_mint {
+        return Err(FundError::InvalidMint.into());
+    }
+    if share_account.owner != *new_owner.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let lp_seeds = LPPosition::seeds(fund_account.key, new_owner.key);
+    let lp_seeds_refs: Vec<&[u8]> = lp_seeds.iter().map(|s| s.as_slice()).collect();
+    let lp_bump = assert_pda(new_owner_position, &lp_seeds_refs, program_id)?;
+
+    if new_owner_position.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                new_owner.key,
+                new_owner_position.key,
+                rent.minimum_balance(LPPosition::SIZE),
+                LPPosition::SIZE as u64,
+                program_id,
+            ),
+            &[new_owner.clone(), new_owner_position.clone(), system_program.clone()],
+            &[&[LP_POSITION_SEED, fund_account.key.as_ref(), new_owner.key.as_ref(), &[lp_bump]]],
+        )?;
+    } else {
+        // An emptied position can be taken over; merging would blend cost bases
+        assert_owned_by(new_owner_position, program_id)?;
+        let existing = LPPosition::unpack_versioned(&new_owner_position.data.borrow())?;
+        if existing.discriminator != LP_POSITION_DISCRIMINATOR || existing.investor != *new_owner.key {
+            return Err(FundError::LPPositionNotFound.into());
+        }
+        if !existing.is_empty() || existing.nft_wrapped {
+            return Err(FundError::LPPositionTransferTargetInUse.into());
+        }
+        if new_owner_position.data_len() < LPPosition::SIZE {
+            realloc_with_rent(new_owner_position, new_owner, system_program, LPPosition::SIZE)?;
+        }
+    }
+
+    let current_ts = get_current_timestamp()?;
+    let mut moved = position.move_to(*new_owner.key, lp_bump, current_ts);
+
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            owner_shares.key,
+            new_owner_shares.key,
+            owner.key,
+            &[],
+            moved.shares,
+        )?,
+        &[owner_shares.clone(), new_owner_shares.clone(), owner.clone(), token_program.clone()],
+    )?;
+
+    lp_index_remove(program_id, accounts, fund_account.key, owner_position, &mut position)?;
+    position.pack_versioned(&mut owner_position.data.borrow_mut())?;
+
+    lp_index_add(program_id, accounts, new_owner, system_program, fund_account.key, &mut fund, new_owner_position, &mut moved, current_ts)?;
+    moved.pack_versioned(&mut new_owner_position.data.borrow_mut())?;
+
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, None)?;
+
+    msg!("🔁 LP_POSITION_TRANSFERRED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  From: {}", owner.key);
+    msg!("  To: {}", new_owner.key);
+    msg!("  Shares: {}", moved.shares);
+    msg!("  Deposited at: {}", moved.deposited_at);
+
+    Ok(())
+}
+
+// =============================================================================
+// Fund Setup Validation
+// =============================================================================
+
+/// Validate a fund's vault and mints once and record its denomination mint
+fn process_validate_fund_setup(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let vault_mint = next_account_info(account_info_iter)?;
+    let share_mint = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        fund_account: writable, owner = program_id;
+        fund_vault: owner = &spl_token::id();
+        vault_mint: owner = &spl_token::id();
+        share_mint: owner = &spl_token::id();
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if fund.setup_pending {
+        return Err(FundError::FundSetupPending.into());
+    }
+    if fund.is_setup_validated() {
+        return Err(FundError::FundSetupAlreadyValidated.into());
+    }
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+        share_mint: key = &fund.share_mint;
+    }
+
+    let vault = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    assert_fund_vault_authorities(&vault, fund_account.key)?;
+    if vault.mint != *vault_mint.key {
+        return Err(FundError::FundVaultMintMismatch.into());
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+
+    let expected_decimals = if fund.sol_denominated {
+        spl_token::native_mint::DECIMALS
+    } else {
+        VAULT_MINT_DECIMALS
+    };
+    let mint = spl_token::state::Mint::unpack(&vault_mint.data.borrow())?;
+    if mint.decimals != expected_decimals {
+        msg!("Vault mint decimals {} != {}", mint.decimals, expected_decimals);
+        return Err(FundError::FundVaultMintMismatch.into());
+    }
+    let shares = spl_token::state::Mint::unpack(&share_mint.data.borrow())?;
+    if shares.decimals != SHARE_MINT_DECIMALS {
+        msg!("Share mint decimals {} != {}", shares.decimals, SHARE_MINT_DECIMALS);
+        return Err(FundError::InvalidMint.into());
+    }
+
+    fund.denomination_mint = vault.mint;
+    fund.last_update_ts = get_current_timestamp()?;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+
+    msg!("✅ FUND_SETUP_VALIDATED");
+    msg!("  Fund: {}", fund.name_str());
+    msg!("  Vault: {}", fund.fund_vault);
+    msg!("  Denomination mint: {}", fund.denomination_mint);
+
+    Ok(())
+}
+
+// =============================================================================
+// Fund Expenses
+// =============================================================================
+
+/// Pay a bounded third-party expense from the fund vault (Manager only)
+fn process_pay_fund_expense(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: PayFundExpenseArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let payee = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    validate_accounts! {
+        manager: signer;
+        fund_account: writable, owner = program_id;
+        fund_vault: writable;
+        payee: writable, owner = &spl_token::id();
+        token_program: key = &spl_token::id();
+    }
+
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    if fund.distribution_in_progress {
+        return Err(FundError::DistributionInProgress.into());
+    }
+
+    let current_ts = get_current_timestamp()?;
+    assert_fund_withdrawable(&fund, fund_account.key, current_ts)?;
+
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+
+    let payee_state = spl_token::state::Account::unpack(&payee.data.borrow())?;
+    if payee_state.owner == fund.manager || payee_state.owner == *fund_account.key {
+        return Err(FundError::FundExpensePayeeNotAllowed.into());
+    }
+
+    fund.accrue_aum(current_ts);
+    fund.record_expense(args.amount_e6, current_ts)?;
+
+    let fund_index_bytes = fund.fund_index.to_le_bytes();
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            fund_vault.key,
+            payee.key,
+            fund_account.key,
+            &[],
+            fund.e6_to_token_amount(args.amount_e6)?,
+        )?,
+        &[fund_vault.clone(), payee.clone(), fund_account.clone(), token_program.clone()],
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund_index_bytes, &[fund.bump]]],
+    )?;
+
+    fund.last_update_ts = current_ts;
+    // Funds that predate the expense fields were created with expenses disabled
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+
+    msg!("🧾 FUND_EXPENSE_PAID");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Payee: {} (owner {})", payee.key, payee_state.owner);
+    msg!("  Amount: {}", args.amount_e6);
+    msg!("  Memo hash: {}", solana_program::hash::Hash::new_from_array(args.memo_hash));
+    msg!("  Paid this period: {} / {}", fund.expense_period_paid_e6, fund.expense_cap_e6());
+    msg!("  Total expenses: {}", fund.total_expenses_e6);
+    msg!("  New NAV: {}", fund.stats.current_nav_e6);
+
+    Ok(())
+}
+
+// =============================================================================
+// Insurance Coverage (Ledger handshake)
+// =============================================================================
+
+/// Recompute the coverage ratio from the insurance vault and store it
+///
+/// `report` carries the authorized caller and its open interest figure; without
+/// it the last reported figure is used.
+fn publish_insurance_coverage(
+    program_id: &Pubkey,
+    insurance_config: &AccountInfo,
+    fund_account: &AccountInfo,
+    fund_vault: &AccountInfo,
+    report: Option<(&AccountInfo, i64)>,
+) -> ProgramResult {
+    let config_seeds = InsuranceFundConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+
+    validate_accounts! {
+        insurance_config: writable, owner = program_id, pda = (&config_seeds_refs, program_id);
+        fund_account: owner = program_id;
+    }
+
+    let mut config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+    if config.fund != *fund_account.key {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.fund_vault != *fund_vault.key {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+
+    let current_ts = get_current_timestamp()?;
+    if let Some((caller, open_interest_e6)) = report {
+        assert_signer(caller)?;
+        if !config.is_authorized_caller(caller.key) {
+            msg!("Unauthorized caller: {}", caller.key);
+            return Err(FundError::UnauthorizedCaller.into());
+        }
+        config.record_open_interest(open_interest_e6, current_ts)?;
+    }
+
+    // Vault only: deployed capital is recallable but not immediately available
+    let vault = spl_token::state::Account::unpack(&fund_vault.data.borrow())?;
+    let balance_e6 = fund.token_amount_to_e6(vault.amount)?;
+    let coverage_bps = config.update_coverage(balance_e6, current_ts)?;
+    // Configs that predate the coverage fields must be grown (ReallocAccount) first
+    config.pack_versioned(&mut insurance_config.data.borrow_mut())?;
+
+    msg!("🛡️ INSURANCE_COVERAGE_PUBLISHED");
+    msg!("  Balance: {}", balance_e6);
+    msg!("  Open interest: {}", config.platform_open_interest_e6);
+    msg!("  Coverage ratio (bps): {}", coverage_bps);
+
+    Ok(())
+}
+
+/// Store the Ledger's platform open interest and republish coverage (CPI)
+fn process_report_platform_open_interest(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ReportPlatformOpenInterestArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?;
+    let insurance_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+
+    publish_insurance_coverage(
+        program_id,
+        insurance_config,
+        fund_account,
+        fund_vault,
+        Some((caller, args.open_interest_e6)),
+    )
+}
+
+/// Republish coverage from the current vault balance (permissionless crank)
+fn process_update_insurance_coverage(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let insurance_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+
+    publish_insurance_coverage(program_id, insurance_config, fund_account, fund_vault, None)
+}
+
+// =============================================================================
+// Referral Reward Claims
+// =============================================================================
+
+/// Pay out a link's funded rewards, optionally straight into fund shares
+fn process_claim_referral_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ClaimReferralRewardsArgs,
+) -> ProgramResult {
+    const CLAIM_ACCOUNTS: usize = 6;
+    if accounts.len() < CLAIM_ACCOUNTS {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    
+    let referrer = &accounts[0];
+    let referral_config = &accounts[1];
+    let referral_link = &accounts[2];
+    let reward_vault = &accounts[3];
+    let referrer_usdc = &accounts[4];
+    let token_program = &accounts[5];
+    
+    let config_seeds = ReferralConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    let link_seeds = ReferralLink::seeds(referrer.key);
+    let link_seeds_refs: Vec<&[u8]> = link_seeds.iter().map(|s| s.as_slice()).collect();
+    let vault_seeds = ReferralConfig::reward_vault_seeds();
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        referrer: signer;
+        referral_config: writable, owner = program_id, pda = (&config_seeds_refs, program_id);
+        referral_link: writable, owner = program_id, pda = (&link_seeds_refs, program_id);
+        reward_vault: writable, pda = (&vault_seeds_refs, program_id);
+        referrer_usdc: writable, owner = &spl_token::id();
+        token_program: key = &spl_token::id();
+    }
+    
+    let mut config = ReferralConfig::try_from_slice(&referral_config.data.borrow())?;
+    if config.discriminator != REFERRAL_CONFIG_DISCRIMINATOR {
+        return Err(FundError::ReferralNotInitialized.into());
+    }
+    let mut link = ReferralLink::try_from_slice(&referral_link.data.borrow())?;
+    if link.discriminator != REFERRAL_LINK_DISCRIMINATOR {
+        return Err(FundError::ReferralLinkNotFound.into());
+    }
+    
+    // Already funded, so claimable even while referrals are paused or the link is inactive
+    let current_ts = get_current_timestamp()?;
+    let amount_e6 = link.claim_rewards(current_ts);
+    if amount_e6 <= 0 {
+        return Err(FundError::NoReferralRewardsToClaim.into());
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            reward_vault.key,
+            referrer_usdc.key,
+            referral_config.key,
+            &[],
+            amount_e6 as u64,
+        )?,
+        &[reward_vault.clone(), referrer_usdc.clone(), referral_config.clone(), token_program.clone()],
+        &[&[REFERRAL_CONFIG_SEED, &[config.bump]]],
+    )?;
+    
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
+    config.record_claimed_reward(amount_e6, current_ts);
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
+    
+    msg!("💸 REFERRAL_REWARDS_CLAIMED");
+    msg!("  Referrer: {}", referrer.key);
+    msg!("  Amount: {}", amount_e6);
+    msg!("  Payout: {}", if args.into_fund_shares { "fund shares" } else { "USDC" });
+    
+    if !args.into_fund_shares {
+        return Ok(());
+    }
+    
+    // Regular deposit of the claimed USDC, with the DepositToFund accounts only
+    const DEPOSIT_ACCOUNTS: usize = CLAIM_ACCOUNTS + 6;
+    if accounts.len() < DEPOSIT_ACCOUNTS {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let deposit_accounts: Vec<AccountInfo> = [
+        referrer,
+        &accounts[6],  // Fund
+        &accounts[7],  // Fund vault
+        referrer_usdc,
+        &accounts[8],  // LP Position
+        &accounts[9],  // Share token account
+        &accounts[10], // Share mint
+        token_program,
+        &accounts[11], // System Program
+    ]
+    .into_iter()
+    .chain(accounts[DEPOSIT_ACCOUNTS..].iter())
+    .cloned()
+    .collect();
+    process_deposit_to_fund(
+        program_id,
+        &deposit_accounts,
+        DepositToFundArgs {
+            amount: amount_e6 as u64,
+            campaign_id: None,
+            memo: None,
+        },
+        false,
+        false,
+    )
+}
+
+// =============================================================================
+// Referral Reward Settlement
+// =============================================================================
+
+/// Compute units kept in reserve per remaining referrer in SettleReferralRewardsBatch
+const REFERRAL_SETTLEMENT_CU_PER_ITEM: u64 = 12_000;
+
+/// Pay out several referrers' funded rewards, skipping (not failing on) bad entries
+fn process_settle_referral_rewards_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    const HEADER_ACCOUNTS: usize = 5;
+    if accounts.len() <= HEADER_ACCOUNTS || !(accounts.len() - HEADER_ACCOUNTS).is_multiple_of(2) {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    if (accounts.len() - HEADER_ACCOUNTS) / 2 > MAX_REFERRAL_SETTLEMENT_BATCH {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let relayer = &accounts[0];
+    let fund_config = &accounts[1];
+    let referral_config = &accounts[2];
+    let reward_vault = &accounts[3];
+    let token_program = &accounts[4];
+    
+    let fund_config_seeds = FundConfig::seeds();
+    let fund_config_seeds_refs: Vec<&[u8]> = fund_config_seeds.iter().map(|s| s.as_slice()).collect();
+    let config_seeds = ReferralConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    let vault_seeds = ReferralConfig::reward_vault_seeds();
+    let vault_seeds_refs: Vec<&[u8]> = vault_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        relayer: signer;
+        fund_config: owner = program_id, pda = (&fund_config_seeds_refs, program_id);
+        referral_config: writable, owner = program_id, pda = (&config_seeds_refs, program_id);
+        reward_vault: writable, pda = (&vault_seeds_refs, program_id);
+        token_program: key = &spl_token::id();
+    }
+    
+    let fund_config_state = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    verify_fund_relayer(&fund_config_state, relayer.key)?;
+    
+    let mut config = ReferralConfig::try_from_slice(&referral_config.data.borrow())?;
+    if config.discriminator != REFERRAL_CONFIG_DISCRIMINATOR {
+        return Err(FundError::ReferralNotInitialized.into());
+    }
+    let reward_mint = spl_token::state::Account::unpack(&reward_vault.data.borrow())?.mint;
+    
+    let current_ts = get_current_timestamp()?;
+    let mut result = SettleReferralRewardsBatchResult { processed: 0, settled_bitmap: 0, total_settled_e6: 0 };
+    
+    for (i, pair) in accounts[HEADER_ACCOUNTS..].chunks(2).enumerate() {
+        if remaining_compute_units() < REFERRAL_SETTLEMENT_CU_PER_ITEM {
+            msg!("Compute budget low, stopping after {} referrers", i);
+            break;
+        }
+        
+        match settle_referral_link(
+            program_id,
+            &config,
+            &pair[0],
+            &pair[1],
+            referral_config,
+            reward_vault,
+            token_program,
+            &reward_mint,
+            current_ts,
+        ) {
+            Ok(amount_e6) => {
+                config.record_claimed_reward(amount_e6, current_ts);
+                result.settled_bitmap |= 1 << i;
+                result.total_settled_e6 = result.total_settled_e6.saturating_add(amount_e6);
+                msg!("  [{}] {} settled: {}", i, pair[0].key, amount_e6);
+            }
+            Err(e) => msg!("  [{}] {} skipped: {}", i, pair[0].key, e),
+        }
+        result.processed += 1;
+    }
+    
+    config.serialize(&mut &mut referral_config.data.borrow_mut()[..])?;
+    set_fund_return_data(&result)?;
+    
+    msg!("💸 REFERRAL_REWARDS_SETTLED");
+    msg!("  Relayer: {}", relayer.key);
+    msg!("  Processed: {}", result.processed);
+    msg!("  Settled bitmap: {:#018b}", result.settled_bitmap);
+    msg!("  Total: {}", result.total_settled_e6);
+    
+    Ok(())
+}
+
+/// Validate one (link, USDC account) pair and pay out its unclaimed rewards, returning the amount
+///
+/// Every check runs before the transfer, so a skipped entry changes nothing.
+#[allow(clippy::too_many_arguments)]
+fn settle_referral_link<'a>(
+    program_id: &Pubkey,
+    config: &ReferralConfig,
+    referral_link: &AccountInfo<'a>,
+    referrer_usdc: &AccountInfo<'a>,
+    referral_config: &AccountInfo<'a>,
+    reward_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    reward_mint: &Pubkey,
+    current_ts: i64,
+) -> Result<i64, ProgramError> {
+    validate_accounts! {
+        referral_link: writable, owner = program_id;
+        referrer_usdc: writable, owner = &spl_token::id();
+    }
+    
+    let mut link = ReferralLink::try_from_slice(&referral_link.data.borrow())?;
+    if link.discriminator != REFERRAL_LINK_DISCRIMINATOR {
+        return Err(FundError::ReferralLinkNotFound.into());
+    }
+    let link_seeds = ReferralLink::seeds(&link.referrer);
+    let link_seeds_refs: Vec<&[u8]> = link_seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(referral_link, &link_seeds_refs, program_id)?;
+    
+    // Only the referrer's own, usable reward-mint account can receive the payout
+    let payout = spl_token::state::Account::unpack(&referrer_usdc.data.borrow())?;
+    if payout.owner != link.referrer || payout.mint != *reward_mint || payout.is_frozen() {
+        return Err(FundError::ReferralPayoutAccountMismatch.into());
+    }
+    
+    if link.unclaimed_rewards_e6 <= 0 || link.unclaimed_rewards_e6 < config.min_settlement_amount_e6 {
+        return Err(FundError::ReferralSettlementBelowMinimum.into());
+    }
+    let amount_e6 = link.claim_rewards(current_ts);
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            reward_vault.key,
+            referrer_usdc.key,
+            referral_config.key,
+            &[],
+            amount_e6 as u64,
+        )?,
+        &[reward_vault.clone(), referrer_usdc.clone(), referral_config.clone(), token_program.clone()],
+        &[&[REFERRAL_CONFIG_SEED, &[config.bump]]],
+    )?;
+    
+    link.serialize(&mut &mut referral_link.data.borrow_mut()[..])?;
+    Ok(amount_e6)
+}
+
+// =============================================================================
+// Investor Portfolio
+// =============================================================================
+
+/// Create an empty InvestorPortfolio for the signer
+fn process_initialize_investor_portfolio(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let portfolio_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = InvestorPortfolio::seeds(investor.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        investor: signer, writable;
+        portfolio_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(portfolio_account, &seeds_refs, program_id)?;
+    if !portfolio_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            investor.key,
+            portfolio_account.key,
+            Rent::get()?.minimum_balance(InvestorPortfolio::SIZE),
+            InvestorPortfolio::SIZE as u64,
+            program_id,
+        ),
+        &[investor.clone(), portfolio_account.clone(), system_program.clone()],
+        &[&[INVESTOR_PORTFOLIO_SEED, investor.key.as_ref(), &[bump]]],
+    )?;
+    
+    let portfolio = InvestorPortfolio::new(*investor.key, bump, get_current_timestamp()?);
+    portfolio.serialize(&mut &mut portfolio_account.data.borrow_mut()[..])?;
+    
+    msg!("✅ INVESTOR_PORTFOLIO_CREATED");
+    msg!("  Investor: {}", investor.key);
+    
+    Ok(())
+}
+
+/// Refresh a portfolio entry from an LP position at the fund's current NAV
+fn process_sync_investor_portfolio(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let portfolio_account = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        portfolio_account: writable, owner = program_id;
+        fund_account: owner = program_id;
+        lp_position: owner = program_id;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    let position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.fund != *fund_account.key {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    let seeds = InvestorPortfolio::seeds(&position.investor);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    assert_pda(portfolio_account, &seeds_refs, program_id)?;
+    
+    update_investor_portfolio(program_id, accounts, lp_position, fund.stats.current_nav_e6, get_current_timestamp()?)
+}
+
+/// Mirror an LP position into its investor's InvestorPortfolio, if passed
+fn update_investor_portfolio(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lp_position: &AccountInfo,
+    nav_e6: i64,
+    current_ts: i64,
+) -> ProgramResult {
+    let position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    let seeds = InvestorPortfolio::seeds(&position.investor);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    let (portfolio_key, _) = Pubkey::find_program_address(&seeds_refs, program_id);
+    let Some(portfolio_account) = accounts.iter().find(|a| *a.key == portfolio_key) else {
+        return Ok(());
+    };
+    
+    validate_accounts! {
+        portfolio_account: writable, owner = program_id;
+    }
+    let mut portfolio = InvestorPortfolio::try_from_slice(&portfolio_account.data.borrow())?;
+    if portfolio.discriminator != INVESTOR_PORTFOLIO_DISCRIMINATOR || portfolio.investor != position.investor {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    portfolio.sync_position(&position, nav_e6, current_ts)?;
+    portfolio.serialize(&mut &mut portfolio_account.data.borrow_mut()[..])?;
+    
+    msg!("📒 INVESTOR_PORTFOLIO_UPDATED");
+    msg!("  Investor: {}", portfolio.investor);
+    msg!("  Fund: {}", position.fund);
+    msg!("  Total invested: {}", portfolio.total_invested_e6);
+    msg!("  Total value: {}", portfolio.total_value_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Stale Entry Cleanup
+// =============================================================================
+
+/// Close a fund's stale TradeIntents, tipping the keeper out of the rent
+fn process_expire_stale_entries(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let keeper = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let manager = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        keeper: signer, writable;
+        fund_account: owner = program_id;
+        manager: writable;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    validate_accounts! {
+        manager: key = &fund.manager;
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    let mut closed: u32 = 0;
+    let mut tips: u64 = 0;
+    
+    for entry in account_info_iter {
+        validate_accounts! {
+            entry: writable, owner = program_id;
+        }
+        // Already closed earlier in this batch (or a duplicate)
+        if entry.data_is_empty() || entry.lamports() == 0 {
+            continue;
+        }
+        
+        let intent = TradeIntent::try_from_slice(&entry.data.borrow())
+            .ok()
+            .filter(|i| i.discriminator == TRADE_INTENT_DISCRIMINATOR && i.fund == *fund_account.key)
+            .ok_or(FundError::UnsupportedStaleEntry)?;
+        if !intent.is_stale(current_ts) {
+            continue;
+        }
+        
+        let tip = stale_entry_keeper_tip(entry.lamports());
+        close_program_account(entry, manager)?;
+        **manager.try_borrow_mut_lamports()? -= tip;
+        **keeper.try_borrow_mut_lamports()? = keeper.lamports().checked_add(tip).ok_or(FundError::Overflow)?;
+        
+        closed += 1;
+        tips = tips.saturating_add(tip);
+        msg!("  Closed intent {} ({})", intent.intent_id, if intent.is_executed { "executed" } else { "expired" });
+    }
+    
+    msg!("🧹 STALE_ENTRIES_EXPIRED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Closed: {}", closed);
+    msg!("  Keeper tips: {} lamports", tips);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Fund Lookup Table
+// =============================================================================
+
+/// Create an Address Lookup Table owned by the Fund PDA and record it on the Fund
+fn process_create_fund_lookup_table(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CreateFundLookupTableArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lookup_table = next_account_info(account_info_iter)?;
+    let lookup_table_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let (expected_table, _) = solana_program::address_lookup_table::instruction::derive_lookup_table_address(
+        fund_account.key,
+        args.recent_slot,
+    );
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: writable, owner = program_id;
+        lookup_table: writable, key = &expected_table;
+        lookup_table_program: key = &solana_program::address_lookup_table::program::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    if fund.has_lookup_table() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    crate::cpi::create_lookup_table(
+        lookup_table_program,
+        lookup_table,
+        fund_account,
+        manager,
+        system_program,
+        args.recent_slot,
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund.bump]]],
+    )?;
+    
+    fund.lookup_table = *lookup_table.key;
+    fund.last_update_ts = get_current_timestamp()?;
+    if !fund.fits_in(fund_account.data_len()) {
+        realloc_with_rent(fund_account, manager, system_program, Fund::SIZE)?;
+    }
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("✅ FUND_LOOKUP_TABLE_CREATED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Lookup table: {}", lookup_table.key);
+    
+    Ok(())
+}
+
+/// Append addresses to the fund's lookup table
+fn process_extend_fund_lookup_table(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ExtendFundLookupTableArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let lookup_table = next_account_info(account_info_iter)?;
+    let lookup_table_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        manager: signer, writable;
+        fund_account: owner = program_id;
+        lookup_table: writable;
+        lookup_table_program: key = &solana_program::address_lookup_table::program::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    if !fund.has_lookup_table() {
+        return Err(FundError::FundLookupTableMissing.into());
+    }
+    validate_accounts! {
+        lookup_table: key = &fund.lookup_table;
+    }
+    if args.addresses.is_empty() || args.addresses.len() > MAX_LOOKUP_TABLE_EXTEND_ADDRESSES {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let added = args.addresses.len();
+    
+    crate::cpi::extend_lookup_table(
+        lookup_table_program,
+        lookup_table,
+        fund_account,
+        manager,
+        system_program,
+        args.addresses,
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund.bump]]],
+    )?;
+    
+    msg!("📇 FUND_LOOKUP_TABLE_EXTENDED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Lookup table: {}", lookup_table.key);
+    msg!("  Added: {}", added);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Fund Coverage
+// =============================================================================
+
+/// Load a FundCoveragePolicy and check it is the PDA of `fund_key`
+fn load_fund_coverage_policy(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    fund_key: &Pubkey,
+) -> Result<FundCoveragePolicy, ProgramError> {
+    let seeds = FundCoveragePolicy::seeds(fund_key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    validate_accounts! {
+        account: owner = program_id, pda = (&seeds_refs, program_id);
+    }
+    let policy = FundCoveragePolicy::try_from_slice(&account.data.borrow())?;
+    if policy.discriminator != FUND_COVERAGE_POLICY_DISCRIMINATOR || policy.fund != *fund_key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(policy)
+}
+
+/// Load the Insurance Fund behind InsuranceFundConfig and check its vault
+fn load_insurance_fund_for_coverage(
+    program_id: &Pubkey,
+    insurance_config: &AccountInfo,
+    insurance_fund: &AccountInfo,
+    insurance_vault: &AccountInfo,
+) -> Result<(InsuranceFundConfig, Fund), ProgramError> {
+    let config_seeds = InsuranceFundConfig::seeds();
+    let config_seeds_refs: Vec<&[u8]> = config_seeds.iter().map(|s| s.as_slice()).collect();
+    validate_accounts! {
+        insurance_config: owner = program_id, pda = (&config_seeds_refs, program_id);
+        insurance_fund: writable, owner = program_id;
+        insurance_vault: writable;
+    }
+    
+    let config = InsuranceFundConfig::unpack_versioned(&insurance_config.data.borrow())?;
+    if config.discriminator != INSURANCE_FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::InsuranceFundNotInitialized.into());
+    }
+    if config.fund != *insurance_fund.key {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+    let fund = Fund::unpack_versioned(&insurance_fund.data.borrow())?;
+    validate_accounts! {
+        insurance_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, insurance_vault)?;
+    Ok((config, fund))
+}
+
+/// Register a drawdown coverage policy for a fund
+fn process_register_fund_coverage(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RegisterFundCoverageArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let policy_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = FundCoveragePolicy::seeds(fund_account.key);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        manager: signer, writable;
+        admin: signer;
+        fund_account: owner = program_id;
+        policy_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    assert_fund_config_admin(program_id, fund_config, admin)?;
+    let bump = assert_pda(policy_account, &seeds_refs, program_id)?;
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    // The Insurance Fund cannot insure itself
+    if fund.is_protected() {
+        return Err(FundError::SystemFundProtected.into());
+    }
+    if !policy_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    FundCoveragePolicy::validate_terms(
+        args.premium_bps,
+        args.coverage_bps,
+        args.deductible_bps,
+        args.coverage_limit_e6,
+    )?;
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            manager.key,
+            policy_account.key,
+            Rent::get()?.minimum_balance(FundCoveragePolicy::SIZE),
+            FundCoveragePolicy::SIZE as u64,
+            program_id,
+        ),
+        &[manager.clone(), policy_account.clone(), system_program.clone()],
+        &[&[FUND_COVERAGE_POLICY_SEED, fund_account.key.as_ref(), &[bump]]],
+    )?;
+    
+    let policy = FundCoveragePolicy::new(
+        *fund_account.key,
+        args.premium_bps,
+        args.coverage_bps,
+        args.deductible_bps,
+        args.coverage_limit_e6,
+        fund.stats.current_nav_e6,
+        fund.trading_pnl_e6(),
+        bump,
+        get_current_timestamp()?,
+    );
+    policy.serialize(&mut &mut policy_account.data.borrow_mut()[..])?;
+    
+    msg!("🛡️ FUND_COVERAGE_REGISTERED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Premium: {} bps/yr", args.premium_bps);
+    msg!("  Coverage: {} bps beyond a {} bps deductible", args.coverage_bps, args.deductible_bps);
+    msg!("  Limit: {}", args.coverage_limit_e6);
+    msg!("  Inception NAV: {}", policy.inception_nav_e6);
+    
+    Ok(())
+}
+
+/// Move the accrued coverage premium from the fund vault to the Insurance Fund
+fn process_pay_coverage_premium(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let fund_account = next_account_info(account_info_iter)?;
+    let fund_vault = next_account_info(account_info_iter)?;
+    let policy_account = next_account_info(account_info_iter)?;
+    let insurance_config = next_account_info(account_info_iter)?;
+    let insurance_fund = next_account_info(account_info_iter)?;
+    let insurance_vault = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        fund_account: writable, owner = program_id;
+        fund_vault: writable;
+        policy_account: writable;
+        token_program: key = &spl_token::id();
+    }
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    validate_accounts! {
+        fund_vault: key = &fund.fund_vault;
+    }
+    assert_fund_vault_mint(&fund, fund_vault)?;
+    
+    let mut policy = load_fund_coverage_policy(program_id, policy_account, fund_account.key)?;
+    if !policy.is_active {
+        return Err(FundError::FundCoverageNotInForce.into());
+    }
+    let (_, mut insurance) = load_insurance_fund_for_coverage(program_id, insurance_config, insurance_fund, insurance_vault)?;
+    if insurance.fund_vault == fund.fund_vault {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+    let vault_mint = spl_token::state::Account::unpack(&fund_vault.data.borrow())?.mint;
+    let insurance_mint = spl_token::state::Account::unpack(&insurance_vault.data.borrow())?.mint;
+    if vault_mint != insurance_mint {
+        return Err(FundError::FeeMintMismatch.into());
+    }
+    
+    let current_ts = get_current_timestamp()?;
+    let premium_e6 = policy.premium_due_e6(fund.stats.total_value_e6(), current_ts);
+    if premium_e6 <= 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            fund_vault.key,
+            insurance_vault.key,
+            fund_account.key,
+            &[],
+            fund.e6_to_token_amount(premium_e6)?,
+        )?,
+        &[fund_vault.clone(), insurance_vault.clone(), fund_account.clone(), token_program.clone()],
+        &[&[FUND_SEED, fund.manager.as_ref(), &fund.fund_index.to_le_bytes(), &[fund.bump]]],
+    )?;
+    
+    fund.accrue_aum(current_ts);
+    fund.record_coverage_premium(premium_e6)?;
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    enforce_fund_invariants(&fund, Some(fund_vault))?;
+    
+    // Premium income for the Insurance Fund
+    insurance.accrue_aum(current_ts);
+    insurance.record_pnl(premium_e6)?;
+    insurance.last_update_ts = current_ts;
+    insurance.pack_versioned(&mut insurance_fund.data.borrow_mut())?;
+    
+    policy.record_premium(premium_e6, current_ts);
+    policy.serialize(&mut &mut policy_account.data.borrow_mut()[..])?;
+    
+    msg!("💰 FUND_COVERAGE_PREMIUM_PAID");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Premium: {}", premium_e6);
+    msg!("  Total premiums: {}", policy.total_premiums_e6);
+    
+    Ok(())
+}
+
+/// Pay an LP's compensation for a qualifying loss from the Insurance Fund
+fn process_claim_fund_coverage(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let investor = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let policy_account = next_account_info(account_info_iter)?;
+    let lp_position = next_account_info(account_info_iter)?;
+    let claim_account = next_account_info(account_info_iter)?;
+    let insurance_config = next_account_info(account_info_iter)?;
+    let insurance_fund = next_account_info(account_info_iter)?;
+    let insurance_vault = next_account_info(account_info_iter)?;
+    let investor_usdc = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let lp_seeds = LPPosition::seeds(fund_account.key, investor.key);
+    let lp_seeds_refs: Vec<&[u8]> = lp_seeds.iter().map(|s| s.as_slice()).collect();
+    let claim_seeds = FundCoverageClaim::seeds(policy_account.key, investor.key);
+    let claim_seeds_refs: Vec<&[u8]> = claim_seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        investor: signer, writable;
+        fund_account: owner = program_id;
+        policy_account: writable;
+        lp_position: writable, owner = program_id, pda = (&lp_seeds_refs, program_id);
+        claim_account: writable;
+        investor_usdc: writable;
+        token_program: key = &spl_token::id();
+        system_program: key = &solana_program::system_program::id();
+    }
+    let claim_bump = assert_pda(claim_account, &claim_seeds_refs, program_id)?;
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    let mut policy = load_fund_coverage_policy(program_id, policy_account, fund_account.key)?;
+    let current_ts = get_current_timestamp()?;
+    if !policy.is_in_force(current_ts) {
+        return Err(FundError::FundCoverageNotInForce.into());
+    }
+    
+    let (config, mut insurance) = load_insurance_fund_for_coverage(program_id, insurance_config, insurance_fund, insurance_vault)?;
+    // Same rule as Insurance Fund redemptions
+    if config.is_adl_in_progress {
+        return Err(FundError::ADLInProgress.into());
+    }
+    
+    let mut position = LPPosition::unpack_versioned(&lp_position.data.borrow())?;
+    if position.investor != *investor.key || position.is_empty() {
+        return Err(FundError::LPPositionNotFound.into());
+    }
+    
+    let mut claim = if claim_account.data_is_empty() {
+        create_user_flow_pda(
+            program_id,
+            accounts,
+            investor,
+            claim_account,
+            system_program,
+            FundCoverageClaim::SIZE,
+            &[FUND_COVERAGE_CLAIM_SEED, policy_account.key.as_ref(), investor.key.as_ref(), &[claim_bump]],
+            current_ts,
+        )?;
+        FundCoverageClaim::new(*policy_account.key, *investor.key, claim_bump)
+    } else {
+        assert_owned_by(claim_account, program_id)?;
+        let claim = FundCoverageClaim::try_from_slice(&claim_account.data.borrow())?;
+        if claim.discriminator != FUND_COVERAGE_CLAIM_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        claim
+    };
+    
+    let nav_e6 = fund.stats.current_nav_e6;
+    let amount_e6 = policy.compensation_e6(
+        position.shares,
+        // The position's own mark covers claims made before it moved wallets
+        claim.reference_nav_e6(position.coverage_reference_nav_e6()),
+        nav_e6,
+        fund.trading_pnl_e6(),
+    );
+    if amount_e6 <= 0 {
+        return Err(FundError::NoQualifyingLoss.into());
+    }
+    
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            insurance_vault.key,
+            investor_usdc.key,
+            insurance_fund.key,
+            &[],
+            insurance.e6_to_token_amount(amount_e6)?,
+        )?,
+        &[insurance_vault.clone(), investor_usdc.clone(), insurance_fund.clone(), token_program.clone()],
+        &[&[FUND_SEED, insurance.manager.as_ref(), &insurance.fund_index.to_le_bytes(), &[insurance.bump]]],
+    )?;
+    
+    insurance.accrue_aum(current_ts);
+    insurance.record_pnl(-amount_e6)?;
+    insurance.last_update_ts = current_ts;
+    insurance.pack_versioned(&mut insurance_fund.data.borrow_mut())?;
+    enforce_fund_invariants(&insurance, Some(insurance_vault))?;
+    
+    policy.record_claim(amount_e6);
+    policy.serialize(&mut &mut policy_account.data.borrow_mut()[..])?;
+    claim.record_claim(amount_e6, nav_e6, current_ts);
+    claim.serialize(&mut &mut claim_account.data.borrow_mut()[..])?;
+    position.record_coverage_claim(nav_e6, current_ts);
+    // Positions that predate coverage_nav_e6 must be grown (ReallocAccount) first
+    position.pack_versioned(&mut lp_position.data.borrow_mut())?;
+    
+    msg!("🩹 FUND_COVERAGE_CLAIMED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Investor: {}", investor.key);
+    msg!("  Shares: {}", position.shares);
+    msg!("  NAV: {} (trigger {})", nav_e6, policy.trigger_nav_e6());
+    msg!("  Amount: {}", amount_e6);
+    msg!("  Total claims: {} / {}", policy.total_claims_e6, policy.coverage_limit_e6);
+    
+    Ok(())
+}
+
+/// Cancel a fund's coverage policy
+fn process_cancel_fund_coverage(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let manager = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    let policy_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        manager: signer;
+        fund_account: owner = program_id;
+        policy_account: writable;
+    }
+    
+    let fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    if !fund.is_manager(manager.key) {
+        return Err(FundError::NotFundManager.into());
+    }
+    let mut policy = load_fund_coverage_policy(program_id, policy_account, fund_account.key)?;
+    if !policy.is_active {
+        return Err(FundError::FundCoverageNotInForce.into());
+    }
+    
+    policy.cancel(get_current_timestamp()?);
+    policy.serialize(&mut &mut policy_account.data.borrow_mut()[..])?;
+    
+    msg!("🛑 FUND_COVERAGE_CANCELLED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Premiums paid: {}", policy.total_premiums_e6);
+    msg!("  Claims paid: {}", policy.total_claims_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Mark Price
+// =============================================================================
+
+/// Read a market's fresh mark price from its MarkPrice PDA
+fn load_mark_price(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    market_index: u8,
+    current_ts: i64,
+) -> Result<u64, ProgramError> {
+    let seeds = MarkPrice::seeds(market_index);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    validate_accounts! {
+        account: pda = (&seeds_refs, program_id);
+    }
+    if account.data_is_empty() {
+        return Err(FundError::MarkPriceStale.into());
+    }
+    assert_owned_by(account, program_id)?;
+    let mark = MarkPrice::try_from_slice(&account.data.borrow())?;
+    if mark.discriminator != MARK_PRICE_DISCRIMINATOR {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    mark.fresh_price_e6(current_ts).ok_or_else(|| FundError::MarkPriceStale.into())
+}
+
+/// Publish a market's mark price (authorized relayer)
+fn process_update_mark_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateMarkPriceArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let relayer = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let mark_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    let seeds = MarkPrice::seeds(args.market_index);
+    let seeds_refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    
+    validate_accounts! {
+        relayer: signer, writable;
+        fund_config: owner = program_id;
+        mark_account: writable;
+        system_program: key = &solana_program::system_program::id();
+    }
+    let bump = assert_pda(mark_account, &seeds_refs, program_id)?;
+    
+    let config = FundConfig::unpack_versioned(&fund_config.data.borrow())?;
+    if config.discriminator != FUND_CONFIG_DISCRIMINATOR {
+        return Err(FundError::FundNotInitialized.into());
+    }
+    verify_fund_relayer(&config, relayer.key)?;
+    
+    let mut mark = if mark_account.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                relayer.key,
+                mark_account.key,
+                Rent::get()?.minimum_balance(MarkPrice::SIZE),
+                MarkPrice::SIZE as u64,
+                program_id,
+            ),
+            &[relayer.clone(), mark_account.clone(), system_program.clone()],
+            &[&[MARK_PRICE_SEED, &[args.market_index], &[bump]]],
+        )?;
+        MarkPrice::new(args.market_index, bump)
+    } else {
+        assert_owned_by(mark_account, program_id)?;
+        let mark = MarkPrice::try_from_slice(&mark_account.data.borrow())?;
+        if mark.discriminator != MARK_PRICE_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        mark
+    };
+    
+    mark.update(args.price_e6, get_current_timestamp()?)?;
+    mark.serialize(&mut &mut mark_account.data.borrow_mut()[..])?;
+    
+    msg!("📈 MARK_PRICE_UPDATED");
+    msg!("  Market: {}", args.market_index);
+    msg!("  Price: {}", args.price_e6);
+    
+    Ok(())
+}
+
+// =============================================================================
+// Share Supply Flag
+// =============================================================================
+
+/// Lift a fund's inconsistent share supply flag (Admin only)
+fn process_clear_share_supply_flag(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let admin = next_account_info(account_info_iter)?;
+    let fund_config = next_account_info(account_info_iter)?;
+    let fund_account = next_account_info(account_info_iter)?;
+    
+    validate_accounts! {
+        admin: signer;
+        fund_account: writable, owner = program_id;
+    }
+    assert_fund_config_admin(program_id, fund_config, admin)?;
+    
+    let mut fund = Fund::unpack_versioned(&fund_account.data.borrow())?;
+    if fund.discriminator != FUND_DISCRIMINATOR {
+        return Err(FundError::InvalidFundAccount.into());
+    }
+    
+    let since = fund.clear_share_supply_flag()?;
+    let current_ts = get_current_timestamp()?;
+    fund.last_update_ts = current_ts;
+    fund.pack_versioned(&mut fund_account.data.borrow_mut())?;
+    
+    msg!("✅ SHARE_SUPPLY_FLAG_CLEARED");
+    msg!("  Fund: {}", fund_account.key);
+    msg!("  Flagged since: {}", since);
+    msg!("  Recorded shares: {}", fund.stats.total_shares);
+    msg!("  Admin: {}", admin.key);
+    
+    Ok(())
+}
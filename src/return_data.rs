@@ -0,0 +1,191 @@
+//! Fund Program Return Data Envelope
+//!
+//! Every view / result payload the program hands back through `set_return_data`
+//! is wrapped as `[tag u8][version u8][borsh payload]`. The tag identifies the
+//! result struct, the version its layout. Payloads only ever grow by appending
+//! fields, so a client decoding an older layout than the program wrote reads
+//! the known prefix and ignores the rest. The tests below pin the tags and the
+//! envelope bytes.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, program_error::ProgramError};
+
+use crate::instruction::{
+    BalanceProofResult, CollectFeesResult, DepositResult, InsuranceHealthResult,
+    SettleReferralRewardsBatchResult, TradeSimulationResult, TriggerADLResult, UpdateNAVMultiResult,
+};
+
+/// Envelope header length (tag + version)
+pub const RETURN_DATA_HEADER_LEN: usize = 2;
+
+/// `CollectFeesResult` tag
+pub const COLLECT_FEES_RESULT_TAG: u8 = 1;
+
+/// `TradeSimulationResult` tag
+pub const TRADE_SIMULATION_RESULT_TAG: u8 = 2;
+
+/// `BalanceProofResult` tag
+pub const BALANCE_PROOF_RESULT_TAG: u8 = 3;
+
+/// `InsuranceHealthResult` tag
+pub const INSURANCE_HEALTH_RESULT_TAG: u8 = 4;
+
+/// `TriggerADLResult` tag
+pub const TRIGGER_ADL_RESULT_TAG: u8 = 5;
+
+/// `UpdateNAVMultiResult` tag
+pub const UPDATE_NAV_MULTI_RESULT_TAG: u8 = 6;
+
+/// `DepositResult` tag
+pub const DEPOSIT_RESULT_TAG: u8 = 7;
+
+/// `SettleReferralRewardsBatchResult` tag
+pub const SETTLE_REFERRAL_REWARDS_BATCH_RESULT_TAG: u8 = 8;
+
+/// A struct returned through the envelope
+///
+/// `VERSION` must be bumped whenever fields are appended; fields are never
+/// removed or reordered.
+pub trait ReturnData: BorshSerialize + BorshDeserialize {
+    /// Struct tag (unique per result type)
+    const TAG: u8;
+    /// Payload layout version
+    const VERSION: u8;
+}
+
+impl ReturnData for CollectFeesResult {
+    const TAG: u8 = COLLECT_FEES_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+impl ReturnData for TradeSimulationResult {
+    const TAG: u8 = TRADE_SIMULATION_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+impl ReturnData for BalanceProofResult {
+    const TAG: u8 = BALANCE_PROOF_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+impl ReturnData for InsuranceHealthResult {
+    const TAG: u8 = INSURANCE_HEALTH_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+impl ReturnData for TriggerADLResult {
+    const TAG: u8 = TRIGGER_ADL_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+impl ReturnData for UpdateNAVMultiResult {
+    const TAG: u8 = UPDATE_NAV_MULTI_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+impl ReturnData for DepositResult {
+    const TAG: u8 = DEPOSIT_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+impl ReturnData for SettleReferralRewardsBatchResult {
+    const TAG: u8 = SETTLE_REFERRAL_REWARDS_BATCH_RESULT_TAG;
+    const VERSION: u8 = 1;
+}
+
+/// Undecoded envelope: header plus raw payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReturnDataEnvelope<'a> {
+    /// Struct tag
+    pub tag: u8,
+    /// Payload layout version
+    pub version: u8,
+    /// Borsh payload
+    pub payload: &'a [u8],
+}
+
+impl<'a> ReturnDataEnvelope<'a> {
+    /// Split return data into header and payload
+    pub fn parse(data: &'a [u8]) -> Result<Self, ProgramError> {
+        match data {
+            [tag, version, payload @ ..] => Ok(Self { tag: *tag, version: *version, payload }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// Encode a result as tag + version + borsh payload
+pub fn encode_return_data<T: ReturnData>(result: &T) -> Result<Vec<u8>, ProgramError> {
+    let payload = result.try_to_vec()?;
+    let mut data = Vec::with_capacity(RETURN_DATA_HEADER_LEN + payload.len());
+    data.push(T::TAG);
+    data.push(T::VERSION);
+    data.extend_from_slice(&payload);
+    Ok(data)
+}
+
+/// Decode return data written by `encode_return_data`
+///
+/// Rejects a different tag, or an older version than `T` knows (its payload
+/// lacks fields). A newer version decodes its leading fields and ignores the
+/// appended ones.
+pub fn decode_return_data<T: ReturnData>(data: &[u8]) -> Result<T, ProgramError> {
+    let envelope = ReturnDataEnvelope::parse(data)?;
+    if envelope.tag != T::TAG || envelope.version < T::VERSION {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let mut payload = envelope.payload;
+    let result = T::deserialize(&mut payload)?;
+    if envelope.version == T::VERSION && !payload.is_empty() {
+        // Same version must match exactly
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(result)
+}
+
+/// Set the transaction return data to the enveloped result
+pub fn set_fund_return_data<T: ReturnData>(result: &T) -> ProgramResult {
+    solana_program::program::set_return_data(&encode_return_data(result)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_return_data_envelope_pinned() {
+        let result = UpdateNAVMultiResult { processed: 3, success_bitmap: 0b101 };
+        let data = encode_return_data(&result).unwrap();
+        assert_eq!(data, [&[6u8, 1, 3][..], &0b101u32.to_le_bytes()].concat());
+        assert_eq!(decode_return_data::<UpdateNAVMultiResult>(&data).unwrap(), result);
+
+        let envelope = ReturnDataEnvelope::parse(&data).unwrap();
+        assert_eq!((envelope.tag, envelope.version, envelope.payload.len()), (6, 1, 5));
+        assert!(ReturnDataEnvelope::parse(&[6]).is_err());
+
+        // Wrong tag
+        assert!(decode_return_data::<CollectFeesResult>(&data).is_err());
+        // Older layout than the client knows
+        assert!(decode_return_data::<UpdateNAVMultiResult>(&[&[6u8, 0][..], &data[2..]].concat()).is_err());
+        // Trailing bytes at the same version are a layout mismatch
+        assert!(decode_return_data::<UpdateNAVMultiResult>(&[&data[..], &[0]].concat()).is_err());
+        // A newer version with appended fields still decodes the known prefix
+        let newer = [&[6u8, 2][..], &data[2..], &42u64.to_le_bytes()].concat();
+        assert_eq!(decode_return_data::<UpdateNAVMultiResult>(&newer).unwrap(), result);
+
+        // Tags are unique
+        let mut tags = [
+            CollectFeesResult::TAG,
+            TradeSimulationResult::TAG,
+            BalanceProofResult::TAG,
+            InsuranceHealthResult::TAG,
+            TriggerADLResult::TAG,
+            UpdateNAVMultiResult::TAG,
+            DepositResult::TAG,
+            SettleReferralRewardsBatchResult::TAG,
+        ];
+        tags.sort_unstable();
+        assert_eq!(tags, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
@@ -6,8 +6,10 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
 use crate::utils::{
-    calculate_management_fee, calculate_nav_e6, calculate_performance_fee,
-    safe_add_i64, INITIAL_NAV_E6, MAX_FUND_NAME_LEN,
+    balance_leaf_hash, calculate_management_fee_from_integral, calculate_nav_e6,
+    calculate_performance_fee, calculate_redemption_value, safe_add_i64, verify_merkle_proof,
+    BPS_DENOMINATOR, INITIAL_NAV_E6, LAMPORTS_PER_SOL_E6, MAX_FUND_NAME_LEN, MIN_DEPOSIT_AMOUNT_E6,
+    MAX_SHARE_SUPPLY_AUTO_FIX_BPS, MIN_DEPOSIT_NAV_E6, MIN_SOL_DEPOSIT_E6, SECONDS_PER_YEAR,
 };
 use solana_program::program_error::ProgramError;
 
@@ -65,6 +67,12 @@ pub const FUND_VAULT_SEED: &[u8] = b"fund_vault";
 /// Seed prefix for Share mint PDA
 pub const SHARE_MINT_SEED: &[u8] = b"share_mint";
 
+/// Seed prefix for a rotated share mint/freeze authority PDA
+pub const SHARE_MINT_AUTHORITY_SEED: &[u8] = b"share_mint_authority";
+
+/// Seed prefix for the PDA that signs fund hook CPIs (holds no funds)
+pub const FUND_HOOK_AUTHORITY_SEED: &[u8] = b"fund_hook_authority";
+
 /// Seed prefix for LP position PDA
 pub const LP_POSITION_SEED: &[u8] = b"lp_position";
 
@@ -83,6 +91,9 @@ pub const REFERRAL_LINK_SEED: &[u8] = b"referral_link";
 /// Seed prefix for ReferralBinding PDA
 pub const REFERRAL_BINDING_SEED: &[u8] = b"referral_binding";
 
+/// Seed prefix for Referral Reward Vault PDA (token account owned by ReferralConfig)
+pub const REFERRAL_REWARD_VAULT_SEED: &[u8] = b"referral_reward_vault";
+
 /// Seed prefix for PredictionMarketFeeConfig PDA
 pub const PREDICTION_MARKET_FEE_CONFIG_SEED: &[u8] = b"prediction_market_fee_config";
 
@@ -165,6 +176,95 @@ impl RelayerLimits {
     }
 }
 
+// === Versioned Accounts ===
+
+/// Accounts whose layout can grow over time
+///
+/// Once `reserved` is exhausted, new fields are appended after it and live
+/// accounts are grown with `ReallocAccount`. Decoding tolerates both sides of
+/// the upgrade: a longer (reallocated) buffer ignores the unknown tail, and a
+/// shorter (not yet reallocated) buffer reads the missing fields as zero.
+pub trait VersionedAccount: BorshDeserialize + BorshSerialize {
+    /// Serialized size of the current layout
+    const LEN: usize;
+    
+    /// Decode account data written by this or an adjacent layout version
+    fn unpack_versioned(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() >= Self::LEN {
+            Ok(Self::deserialize(&mut &data[..])?)
+        } else {
+            let mut padded = data.to_vec();
+            padded.resize(Self::LEN, 0);
+            Ok(Self::try_from_slice(&padded)?)
+        }
+    }
+    
+    /// Encode into account data written by this or an older layout version
+    ///
+    /// A shorter (not yet reallocated) buffer is accepted as long as the
+    /// fields it cannot hold are still zero; otherwise the account has to be
+    /// grown first.
+    fn pack_versioned(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let bytes = self.try_to_vec()?;
+        if data.len() >= bytes.len() {
+            data[..bytes.len()].copy_from_slice(&bytes);
+        } else if bytes[data.len()..].iter().all(|b| *b == 0) {
+            let len = data.len();
+            data.copy_from_slice(&bytes[..len]);
+        } else {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        Ok(())
+    }
+    
+    /// Can `pack_versioned` write this value into a buffer of `len` bytes?
+    fn fits_in(&self, len: usize) -> bool {
+        self.try_to_vec()
+            .map(|bytes| len >= bytes.len() || bytes[len..].iter().all(|b| *b == 0))
+            .unwrap_or(false)
+    }
+}
+
+impl VersionedAccount for FundConfig {
+    const LEN: usize = FundConfig::SIZE;
+}
+
+impl VersionedAccount for Fund {
+    const LEN: usize = Fund::SIZE;
+}
+
+impl VersionedAccount for LPPosition {
+    const LEN: usize = LPPosition::SIZE;
+}
+
+impl VersionedAccount for InsuranceFundConfig {
+    const LEN: usize = InsuranceFundConfig::SIZE;
+}
+
+// === Feature Flags ===
+
+/// Square payments (SquarePayment, RelayerSquarePayment)
+pub const FEATURE_SQUARE_PAYMENTS: u32 = 1 << 0;
+
+/// Referral user flows (link creation, binding, trade recording)
+pub const FEATURE_REFERRALS: u32 = 1 << 1;
+
+/// Prediction market fee collection and reward distribution
+pub const FEATURE_PM_FEES: u32 = 1 << 2;
+
+/// Relayer-submitted user operations
+pub const FEATURE_RELAYER: u32 = 1 << 3;
+
+/// Copy trading (reserved for the copy-trading instructions)
+pub const FEATURE_COPY_TRADING: u32 = 1 << 4;
+
+/// All defined feature bits
+pub const ALL_FEATURES: u32 = FEATURE_SQUARE_PAYMENTS
+    | FEATURE_REFERRALS
+    | FEATURE_PM_FEES
+    | FEATURE_RELAYER
+    | FEATURE_COPY_TRADING;
+
 // === Fund Config ===
 
 /// Global configuration for the Fund Program
@@ -211,8 +311,32 @@ pub struct FundConfig {
     /// Relayer operation limits
     pub relayer_limits: RelayerLimits,
     
-    /// Reserved for future use
-    pub reserved: [u8; 32],
+    /// Inactivity required before a beneficiary may claim an LP position (0 = default)
+    pub beneficiary_inactivity_secs: i64,
+    
+    /// Maximum active funds per manager wallet (0 = unlimited)
+    pub max_funds_per_manager: u16,
+    
+    /// USDC fee charged on CreateFund, routed into the Treasury vault (e6, 0 = free)
+    pub fund_creation_fee_e6: u64,
+    
+    /// New funds start in PendingApproval until an admin approves the listing?
+    pub requires_listing_approval: bool,
+    
+    /// Subsystems switched off by the admin (FEATURE_* bits; 0 = everything enabled)
+    pub disabled_features: u32,
+    
+    /// Pause length after which LPs may withdraw anyway (0 = default 30 days)
+    pub default_max_pause_secs: u32,
+    
+    /// Upper bound for a fund's own max pause override (0 = default 90 days)
+    pub max_pause_override_secs: u32,
+    
+    /// TradeFund must update the manager's ManagerRiskState and respect ManagerRiskLimits?
+    pub manager_risk_limits_enabled: bool,
+    
+    /// Relayer* instructions rejected (set by SetRelayerSystemPaused, independent of is_paused)
+    pub relayer_paused: bool,
 }
 
 impl FundConfig {
@@ -230,7 +354,24 @@ impl FundConfig {
         + MAX_RELAYERS  // relayer_active
         + 1   // active_relayer_count
         + RelayerLimits::SIZE  // relayer_limits
-        + 32; // reserved
+        + 8   // beneficiary_inactivity_secs
+        + 2   // max_funds_per_manager
+        + 8   // fund_creation_fee_e6
+        + 1   // requires_listing_approval
+        + 4   // disabled_features
+        + 4   // default_max_pause_secs
+        + 4   // max_pause_override_secs
+        + 1   // manager_risk_limits_enabled
+        + 1;  // relayer_paused
+    
+    /// Default beneficiary inactivity period (365 days)
+    pub const DEFAULT_BENEFICIARY_INACTIVITY_SECS: i64 = 365 * 24 * 60 * 60;
+    
+    /// Default maximum pause duration (30 days)
+    pub const DEFAULT_MAX_PAUSE_SECS: u32 = 30 * 24 * 60 * 60;
+    
+    /// Default bound for per-fund max pause overrides (90 days)
+    pub const DEFAULT_MAX_PAUSE_OVERRIDE_SECS: u32 = 90 * 24 * 60 * 60;
     
     /// Create a new FundConfig
     pub fn new(authority: Pubkey, vault_program: Pubkey, ledger_program: Pubkey, bump: u8) -> Self {
@@ -248,7 +389,51 @@ impl FundConfig {
             relayer_active: [false; MAX_RELAYERS],
             active_relayer_count: 0,
             relayer_limits: RelayerLimits::new(),
-            reserved: [0u8; 32],
+            beneficiary_inactivity_secs: 0,
+            max_funds_per_manager: 0,
+            fund_creation_fee_e6: 0,
+            requires_listing_approval: false,
+            disabled_features: 0,
+            default_max_pause_secs: 0,
+            max_pause_override_secs: 0,
+            manager_risk_limits_enabled: false,
+            relayer_paused: false,
+        }
+    }
+    
+    /// Effective beneficiary inactivity period
+    pub fn beneficiary_inactivity_period(&self) -> i64 {
+        if self.beneficiary_inactivity_secs > 0 {
+            self.beneficiary_inactivity_secs
+        } else {
+            Self::DEFAULT_BENEFICIARY_INACTIVITY_SECS
+        }
+    }
+    
+    /// Effective default maximum pause duration
+    pub fn default_max_pause(&self) -> u32 {
+        if self.default_max_pause_secs > 0 {
+            self.default_max_pause_secs
+        } else {
+            Self::DEFAULT_MAX_PAUSE_SECS
+        }
+    }
+    
+    /// Effective bound for per-fund max pause overrides
+    pub fn max_pause_override(&self) -> u32 {
+        if self.max_pause_override_secs > 0 {
+            self.max_pause_override_secs
+        } else {
+            Self::DEFAULT_MAX_PAUSE_OVERRIDE_SECS
+        }
+    }
+    
+    /// Maximum pause duration for a fund, honouring its override within policy
+    pub fn max_pause_for(&self, fund_override_secs: u32) -> u32 {
+        if fund_override_secs > 0 {
+            fund_override_secs.min(self.max_pause_override())
+        } else {
+            self.default_max_pause()
         }
     }
     
@@ -257,6 +442,29 @@ impl FundConfig {
         vec![FUND_CONFIG_SEED.to_vec()]
     }
     
+    /// Check that every FEATURE_* bit in `features` is enabled
+    pub fn is_feature_enabled(&self, features: u32) -> bool {
+        self.disabled_features & features == 0
+    }
+    
+    /// Enable or disable the FEATURE_* bits in `features`
+    pub fn set_features_enabled(&mut self, features: u32, enabled: bool) {
+        if enabled {
+            self.disabled_features &= !features;
+        } else {
+            self.disabled_features |= features;
+        }
+    }
+    
+    /// Read the authority from the fixed header (discriminator + authority),
+    /// without decoding the rest of the account
+    pub fn authority_from_header(data: &[u8]) -> Option<Pubkey> {
+        if data.len() < 40 || data[..8] != FUND_CONFIG_DISCRIMINATOR.to_le_bytes() {
+            return None;
+        }
+        Pubkey::try_from(&data[8..40]).ok()
+    }
+    
     /// Check if a pubkey is an authorized relayer
     pub fn is_authorized_relayer(&self, relayer: &Pubkey) -> bool {
         // Admin is always authorized
@@ -275,7 +483,7 @@ impl FundConfig {
     }
     
     /// Add a new authorized relayer
-    pub fn add_relayer(&mut self, relayer: Pubkey) -> Result<(), ()> {
+    pub fn add_relayer(&mut self, relayer: Pubkey) -> Result<(), ProgramError> {
         // Check if already exists
         for i in 0..MAX_RELAYERS {
             if self.authorized_relayers[i] == relayer {
@@ -299,7 +507,7 @@ impl FundConfig {
         }
         
         // No space
-        Err(())
+        Err(crate::error::FundError::MaxRelayersReached.into())
     }
     
     /// Remove a relayer
@@ -364,6 +572,49 @@ impl FeeConfig {
     }
 }
 
+// === Redemption Fee Curve ===
+
+/// Dynamic redemption fee charged while the fund vault is short on cash
+///
+/// Utilization-style curve on the post-redemption cash ratio (vault cash / AUM):
+/// no fee at or above `target_cash_ratio_bps`, rising linearly to `max_fee_bps`
+/// as the ratio approaches zero. The fee stays in the fund for the remaining LPs.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedemptionFeeCurve {
+    /// Cash ratio below which the fee applies (bps of AUM, 0 = disabled)
+    pub target_cash_ratio_bps: u16,
+    
+    /// Fee at zero cash ratio (bps of redemption value)
+    pub max_fee_bps: u16,
+}
+
+impl RedemptionFeeCurve {
+    /// Size in bytes
+    pub const SIZE: usize = 2  // target_cash_ratio_bps
+        + 2; // max_fee_bps
+    
+    /// Is the curve configured?
+    pub fn is_enabled(&self) -> bool {
+        self.target_cash_ratio_bps > 0 && self.max_fee_bps > 0
+    }
+    
+    /// Fee rate in bps given the vault cash and AUM left after the redemption
+    pub fn fee_bps(&self, cash_after_e6: u64, aum_after_e6: i64) -> u16 {
+        if !self.is_enabled() || aum_after_e6 <= 0 {
+            return 0;
+        }
+        let target = self.target_cash_ratio_bps as u128;
+        let cash_ratio_bps = (cash_after_e6 as u128 * BPS_DENOMINATOR as u128 / aum_after_e6 as u128).min(target);
+        (self.max_fee_bps as u128 * (target - cash_ratio_bps) / target) as u16
+    }
+    
+    /// Fee (e6) on a redemption of `value_e6`
+    pub fn fee_for(&self, value_e6: i64, cash_after_e6: u64, aum_after_e6: i64) -> i64 {
+        let fee_bps = self.fee_bps(cash_after_e6, aum_after_e6);
+        ((value_e6.max(0) as i128) * fee_bps as i128 / BPS_DENOMINATOR as i128) as i64
+    }
+}
+
 // === Fund Stats ===
 
 /// Statistics for a fund
@@ -463,6 +714,69 @@ impl FundStats {
     }
 }
 
+// === Share Supply Reconciliation ===
+
+/// Outcome of `Fund::reconcile_share_supply`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareSupplyReconciliation {
+    /// Mint supply matches stats
+    Consistent,
+    /// stats.total_shares set to the mint supply
+    Fixed {
+        /// total_shares before the correction
+        previous_shares: u64,
+    },
+    /// Not correctable; the fund is flagged
+    Inconsistent,
+}
+
+// === Fund Listing Status ===
+
+/// Marketplace listing status of a fund
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FundListingStatus {
+    /// Listed and accepting deposits (subject to is_open / is_paused)
+    #[default]
+    Listed = 0,
+    /// Awaiting admin approval, deposits disabled
+    PendingApproval = 1,
+    /// Listing rejected by admin, deposits disabled
+    Rejected = 2,
+}
+
+// === Fund Lifecycle State ===
+
+/// Manager-controlled operating state of a fund (SetFundState)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundLifecycleState {
+    /// Accepting deposits; redemptions and trading allowed
+    Open = 0,
+    /// Deposits closed; redemptions and trading continue
+    DepositsClosed = 1,
+    /// Deposits, redemptions and trading paused (redemptions reopen when the pause expires)
+    Paused = 2,
+}
+
+/// Why a fund's state was changed, logged with FUND_STATE_CHANGED
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FundStateReason {
+    /// No reason given
+    #[default]
+    Unspecified = 0,
+    /// Planned maintenance or upgrade
+    Maintenance = 1,
+    /// Market or risk event
+    RiskEvent = 2,
+    /// Strategy capacity reached
+    CapacityReached = 3,
+    /// Strategy change or rebalancing
+    StrategyChange = 4,
+    /// Compliance or legal requirement
+    Compliance = 5,
+    /// Any other reason
+    Other = 6,
+}
+
 // === Fund ===
 
 /// A single fund managed by a fund manager
@@ -507,8 +821,193 @@ pub struct Fund {
     /// Fund index (unique identifier)
     pub fund_index: u64,
     
-    /// Reserved for future use
-    pub reserved: [u8; 64],
+    /// Mint a DepositReceipt NFT to LPs on deposit?
+    pub deposit_receipts_enabled: bool,
+    
+    /// Marketplace listing status
+    pub listing_status: FundListingStatus,
+    
+    /// Time-weighted AUM integral since last fee collection (e6 * seconds)
+    pub aum_seconds_e6: i128,
+    
+    /// Timestamp the AUM integral was last advanced to (0 = use last_fee_collection_ts)
+    pub last_aum_accrual_ts: i64,
+    
+    /// Authorized relayers may call CollectFees on the manager's behalf?
+    pub fee_crank_enabled: bool,
+    
+    /// USDC token account receiving collected fees (default = any manager-owned account)
+    pub fee_destination: Pubkey,
+    
+    /// This fund is the Insurance Fund (referenced by InsuranceFundConfig)
+    pub is_insurance_fund: bool,
+    
+    /// Platform-owned fund: cannot be closed, pause/unpause is admin only
+    pub is_system_fund: bool,
+    
+    /// Number of LPIndexPage accounts created for this fund
+    pub lp_index_page_count: u16,
+    
+    /// A DistributeToLPs round is running (deposits/redemptions are blocked)
+    pub distribution_in_progress: bool,
+    
+    /// Dynamic redemption fee while vault cash is low
+    pub redemption_fee_curve: RedemptionFeeCurve,
+    
+    /// Total redemption fees retained for remaining LPs (e6)
+    pub total_redemption_fees_e6: i64,
+    
+    /// When the current pause started (0 = not paused)
+    pub paused_at: i64,
+    
+    /// Withdrawals reopen at this time even if still paused (0 = never)
+    pub pause_expires_at: i64,
+    
+    /// Manager's max pause duration override (0 = config default)
+    pub max_pause_secs: u32,
+    
+    /// NAV fell below MIN_DEPOSIT_NAV_E6 at this time; deposits blocked until
+    /// an admin resolves the review (0 = not in review)
+    pub wind_down_review_since: i64,
+    
+    /// CloseFund timestamp (0 = not closed)
+    pub closed_at: i64,
+    
+    /// Created via CreateFundAccount and not yet finalized; share mint /
+    /// vault may still be missing, so deposits are blocked
+    pub setup_pending: bool,
+    
+    /// Benchmark price feed (SharePriceFeed layout; default = no benchmark)
+    pub benchmark_feed: Pubkey,
+    
+    /// Benchmark price at the last performance fee crystallization (e6)
+    pub benchmark_base_price_e6: i64,
+    
+    /// Charge performance fees only on outperformance versus the benchmark
+    pub benchmark_relative_fee: bool,
+    
+    /// Deposit/redemption blackout after a large RecordPnL (seconds, 0 = off)
+    pub blackout_secs: u32,
+    
+    /// |PnL| as bps of fund value that starts a blackout
+    pub blackout_pnl_threshold_bps: u16,
+    
+    /// Deposits and redemptions blocked until this time (0 = none)
+    pub blackout_until: i64,
+    
+    /// Vault holds wrapped SOL; accounting is in micro-SOL (e6) while
+    /// token transfers move lamports (9 decimals)
+    pub sol_denominated: bool,
+    
+    /// Capital moved out of the vault to a yield venue (e6); still backs LPs
+    pub deployed_capital_e6: i64,
+    
+    /// Program CPI'd after every deposit/redemption (default = no hook)
+    pub hook_program: Pubkey,
+    
+    /// Share mint/freeze authority derivation (0 = Fund PDA, n = ["share_mint_authority", fund, n])
+    pub share_authority_version: u8,
+    
+    /// Last share authority rotation
+    pub share_authority_rotated_at: i64,
+    
+    /// A FundLPReferralProgram exists; deposits with a binding and
+    /// CollectFees must then pass it
+    pub lp_referral_enabled: bool,
+    
+    /// Last pause toggle via SetFundState (0 = never)
+    pub last_pause_toggle_at: i64,
+    
+    /// UTC day (unix days) counted by `pause_toggles_today`
+    pub pause_toggle_day: u32,
+    
+    /// Pause toggles on `pause_toggle_day`
+    pub pause_toggles_today: u8,
+    
+    /// Pause state set by AdminSetFundPaused; the manager cannot change it
+    pub admin_pause_locked: bool,
+    
+    /// Open state set by AdminSetFundOpen; the manager cannot change it
+    pub admin_open_locked: bool,
+    
+    /// NAV TWAP accumulator: Σ current_nav_e6 × seconds
+    pub nav_cumulative_e6: i128,
+    
+    /// Accumulator last advanced at (0 = not started)
+    pub nav_cumulative_ts: i64,
+    
+    /// Accumulator checkpoint preceding `twap_last_*`
+    pub twap_prev_cumulative_e6: i128,
+    
+    /// Time of `twap_prev_cumulative_e6` (0 = none yet)
+    pub twap_prev_ts: i64,
+    
+    /// Latest accumulator checkpoint (taken at most once per NAV_TWAP_WINDOW_SECS)
+    pub twap_last_cumulative_e6: i128,
+    
+    /// Time of `twap_last_cumulative_e6`
+    pub twap_last_ts: i64,
+    
+    /// Fee crystallizations so far (fee epoch of the latest one)
+    pub fee_epoch: u32,
+    
+    /// Latest crystallization timestamp
+    pub fee_epoch_at: i64,
+    
+    /// Total shares the latest crystallization was charged across
+    pub fee_epoch_shares: u64,
+    
+    /// Management fee crystallized in the latest epoch (e6)
+    pub fee_epoch_mgmt_fee_e6: i64,
+    
+    /// Performance fee crystallized in the latest epoch (e6)
+    pub fee_epoch_perf_fee_e6: i64,
+    
+    /// Exchange fee rebates credited to the fund (e6), also part of total_realized_pnl_e6
+    pub total_fee_rebates_e6: i64,
+    
+    /// Open position notional on the Ledger, as last reported (e6)
+    pub open_notional_e6: u64,
+    
+    /// Margin locked by open positions, as last reported (e6)
+    pub margin_used_e6: u64,
+    
+    /// Collateral free for new positions incl. unrealized PnL, as last reported (e6)
+    pub free_collateral_e6: i64,
+    
+    /// When the Ledger last reported the exposure (0 = never)
+    pub exposure_updated_at: i64,
+    
+    /// ReconcileShareSupply found a share mint supply it could not correct
+    /// at this time; deposits and redemptions blocked (0 = consistent)
+    pub share_supply_inconsistent_since: i64,
+    
+    /// Vault mint recorded by ValidateFundSetup (Pubkey::default() = not yet validated)
+    pub denomination_mint: Pubkey,
+    
+    /// Annual expense allowance (bps of fund value, fixed at creation, 0 = disabled)
+    pub expense_cap_bps: u16,
+    
+    /// Largest single PayFundExpense payment (e6)
+    pub max_expense_payment_e6: u64,
+    
+    /// Start of the current expense period (0 = no expense paid yet)
+    pub expense_period_start: i64,
+    
+    /// Expenses paid in the current period (e6)
+    pub expense_period_paid_e6: i64,
+    
+    /// Lifetime expenses paid from the vault (e6)
+    pub total_expenses_e6: i64,
+    
+    /// Address Lookup Table owned by the Fund PDA (default = none)
+    pub lookup_table: Pubkey,
+    
+    /// When this fund's current risk limit breach was first observed (0 = none)
+    pub risk_breach_since: i64,
+    
+    /// ShareCheckpoints PDA exists; supply-changing flows must pass it
+    pub share_checkpoints_enabled: bool,
 }
 
 impl Fund {
@@ -526,9 +1025,82 @@ impl Fund {
         + 8   // created_at
         + 8   // last_update_ts
         + 8   // fund_index
-        + 64; // reserved
+        + 1   // deposit_receipts_enabled
+        + 1   // listing_status
+        + 16  // aum_seconds_e6
+        + 8   // last_aum_accrual_ts
+        + 1   // fee_crank_enabled
+        + 32  // fee_destination
+        + 1   // is_insurance_fund
+        + 1   // is_system_fund
+        + 2   // lp_index_page_count
+        + 1   // distribution_in_progress
+        + RedemptionFeeCurve::SIZE  // redemption_fee_curve
+        + 8   // total_redemption_fees_e6
+        + 8   // paused_at
+        + 8   // pause_expires_at
+        + 4   // max_pause_secs
+        + 8   // wind_down_review_since
+        + 8   // closed_at
+        + 1   // setup_pending
+        + 32  // benchmark_feed
+        + 8   // benchmark_base_price_e6
+        + 1   // benchmark_relative_fee
+        + 4   // blackout_secs
+        + 2   // blackout_pnl_threshold_bps
+        + 8   // blackout_until
+        + 1   // sol_denominated
+        + 8   // deployed_capital_e6
+        + 32  // hook_program
+        + 1   // share_authority_version
+        + 8   // share_authority_rotated_at
+        + 1   // lp_referral_enabled
+        + 8   // last_pause_toggle_at
+        + 4   // pause_toggle_day
+        + 1   // pause_toggles_today
+        + 1   // admin_pause_locked
+        + 1   // admin_open_locked
+        + 16  // nav_cumulative_e6
+        + 8   // nav_cumulative_ts
+        + 16  // twap_prev_cumulative_e6
+        + 8   // twap_prev_ts
+        + 16  // twap_last_cumulative_e6
+        + 8   // twap_last_ts
+        + 4   // fee_epoch
+        + 8   // fee_epoch_at
+        + 8   // fee_epoch_shares
+        + 8   // fee_epoch_mgmt_fee_e6
+        + 8   // fee_epoch_perf_fee_e6
+        + 8   // total_fee_rebates_e6
+        + 8   // open_notional_e6
+        + 8   // margin_used_e6
+        + 8   // free_collateral_e6
+        + 8   // exposure_updated_at
+        + 8   // share_supply_inconsistent_since
+        + 32  // denomination_mint
+        + 2   // expense_cap_bps
+        + 8   // max_expense_payment_e6
+        + 8   // expense_period_start
+        + 8   // expense_period_paid_e6
+        + 8   // total_expenses_e6
+        + 32  // lookup_table
+        + 8   // risk_breach_since
+        + 1;  // share_checkpoints_enabled
+    
+    /// Fund expense period length (365 days)
+    pub const EXPENSE_PERIOD_SECS: i64 = 365 * 24 * 60 * 60;
+    
+    /// Minimum NAV TWAP window used for fees and ADL checks (1 hour)
+    pub const NAV_TWAP_WINDOW_SECS: i64 = 3600;
+    
+    /// Minimum time between manager pause toggles (1 hour)
+    pub const PAUSE_TOGGLE_COOLDOWN_SECS: i64 = 3600;
+    
+    /// Maximum manager pause toggles per UTC day
+    pub const MAX_PAUSE_TOGGLES_PER_DAY: u8 = 4;
     
     /// Create a new Fund
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         manager: Pubkey,
         name: &str,
@@ -557,7 +1129,67 @@ impl Fund {
             created_at,
             last_update_ts: created_at,
             fund_index,
-            reserved: [0u8; 64],
+            deposit_receipts_enabled: false,
+            listing_status: FundListingStatus::Listed,
+            aum_seconds_e6: 0,
+            last_aum_accrual_ts: created_at,
+            fee_crank_enabled: false,
+            fee_destination: Pubkey::default(),
+            is_insurance_fund: false,
+            is_system_fund: false,
+            lp_index_page_count: 0,
+            distribution_in_progress: false,
+            redemption_fee_curve: RedemptionFeeCurve::default(),
+            total_redemption_fees_e6: 0,
+            paused_at: 0,
+            pause_expires_at: 0,
+            max_pause_secs: 0,
+            wind_down_review_since: 0,
+            closed_at: 0,
+            setup_pending: false,
+            benchmark_feed: Pubkey::default(),
+            benchmark_base_price_e6: 0,
+            benchmark_relative_fee: false,
+            blackout_secs: 0,
+            blackout_pnl_threshold_bps: 0,
+            blackout_until: 0,
+            sol_denominated: false,
+            deployed_capital_e6: 0,
+            hook_program: Pubkey::default(),
+            share_authority_version: 0,
+            share_authority_rotated_at: 0,
+            lp_referral_enabled: false,
+            last_pause_toggle_at: 0,
+            pause_toggle_day: 0,
+            pause_toggles_today: 0,
+            admin_pause_locked: false,
+            admin_open_locked: false,
+            nav_cumulative_e6: 0,
+            nav_cumulative_ts: created_at,
+            twap_prev_cumulative_e6: 0,
+            twap_prev_ts: 0,
+            twap_last_cumulative_e6: 0,
+            twap_last_ts: created_at,
+            fee_epoch: 0,
+            fee_epoch_at: 0,
+            fee_epoch_shares: 0,
+            fee_epoch_mgmt_fee_e6: 0,
+            fee_epoch_perf_fee_e6: 0,
+            total_fee_rebates_e6: 0,
+            open_notional_e6: 0,
+            margin_used_e6: 0,
+            free_collateral_e6: 0,
+            exposure_updated_at: 0,
+            share_supply_inconsistent_since: 0,
+            denomination_mint: Pubkey::default(),
+            expense_cap_bps: 0,
+            max_expense_payment_e6: 0,
+            expense_period_start: 0,
+            expense_period_paid_e6: 0,
+            total_expenses_e6: 0,
+            lookup_table: Pubkey::default(),
+            risk_breach_since: 0,
+            share_checkpoints_enabled: false,
         }
     }
     
@@ -592,1437 +1224,2671 @@ impl Fund {
         ]
     }
     
+    /// PDA seeds for the signer of hook CPIs
+    pub fn hook_authority_seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![
+            FUND_HOOK_AUTHORITY_SEED.to_vec(),
+            fund.to_bytes().to_vec(),
+        ]
+    }
+    
+    /// PDA seeds of a rotated share mint/freeze authority (version ≥ 1)
+    pub fn share_authority_seeds(fund: &Pubkey, version: u8) -> Vec<Vec<u8>> {
+        vec![
+            SHARE_MINT_AUTHORITY_SEED.to_vec(),
+            fund.to_bytes().to_vec(),
+            vec![version],
+        ]
+    }
+    
+    /// Whether a deposit/redemption hook is registered
+    pub fn has_hook(&self) -> bool {
+        self.hook_program != Pubkey::default()
+    }
+    
     /// Check if this fund is the correct manager
     pub fn is_manager(&self, signer: &Pubkey) -> bool {
         self.manager == *signer
     }
     
+    /// Check if a signer may trigger CollectFees
+    pub fn can_collect_fees(&self, caller: &Pubkey, config: Option<&FundConfig>) -> bool {
+        if self.is_manager(caller) {
+            return true;
+        }
+        self.fee_crank_enabled && config.is_some_and(|c| c.is_authorized_relayer(caller))
+    }
+    
+    /// Check if a token account may receive this fund's fees
+    pub fn is_valid_fee_destination(&self, destination: &Pubkey, destination_owner: &Pubkey) -> bool {
+        if self.fee_destination == Pubkey::default() {
+            // Not configured: fall back to any manager-owned account
+            *destination_owner == self.manager
+        } else {
+            *destination == self.fee_destination
+        }
+    }
+    
+    /// Insurance or system fund: protected from manager-driven close/pause
+    pub fn is_protected(&self) -> bool {
+        self.is_insurance_fund || self.is_system_fund
+    }
+    
     /// Check if deposits are allowed
     pub fn can_deposit(&self) -> bool {
-        self.is_open && !self.is_paused
+        self.listing_status == FundListingStatus::Listed && self.can_seed_deposit()
     }
     
-    /// Check if withdrawals are allowed
-    pub fn can_withdraw(&self) -> bool {
-        !self.is_paused
+    /// Check if the manager's CreateFund seed deposit is allowed (also while pending approval)
+    pub fn can_seed_deposit(&self) -> bool {
+        self.is_open
+            && !self.is_paused
+            && self.listing_status != FundListingStatus::Rejected
+            && !self.is_in_wind_down_review()
+            && !self.setup_pending
+            && !self.is_share_supply_inconsistent()
     }
     
-    /// Record a deposit
-    pub fn record_deposit(&mut self, amount_e6: i64, shares: u64) -> Result<(), ProgramError> {
-        self.stats.total_deposits_e6 = safe_add_i64(self.stats.total_deposits_e6, amount_e6)?;
-        self.stats.total_shares = self.stats.total_shares.saturating_add(shares);
-        self.stats.update_nav()?;
-        Ok(())
+    /// Closed by the manager via CloseFund?
+    pub fn is_closed(&self) -> bool {
+        self.closed_at != 0
     }
     
-    /// Record a withdrawal
-    pub fn record_withdrawal(&mut self, amount_e6: i64, shares: u64) -> Result<(), ProgramError> {
-        self.stats.total_withdrawals_e6 = safe_add_i64(self.stats.total_withdrawals_e6, amount_e6)?;
-        self.stats.total_shares = self.stats.total_shares.saturating_sub(shares);
-        self.stats.update_nav()?;
-        Ok(())
+    /// Has an Address Lookup Table been created for the fund?
+    pub fn has_lookup_table(&self) -> bool {
+        self.lookup_table != Pubkey::default()
     }
     
-    /// Record realized PnL
-    pub fn record_pnl(&mut self, pnl_e6: i64) -> Result<(), ProgramError> {
-        self.stats.total_realized_pnl_e6 = safe_add_i64(self.stats.total_realized_pnl_e6, pnl_e6)?;
-        self.stats.update_nav()?;
-        self.stats.update_hwm();
-        Ok(())
+    /// Current SetFundState state (a pause takes precedence over is_open)
+    pub fn lifecycle_state(&self) -> FundLifecycleState {
+        if self.is_paused {
+            FundLifecycleState::Paused
+        } else if self.is_open {
+            FundLifecycleState::Open
+        } else {
+            FundLifecycleState::DepositsClosed
+        }
     }
     
-    /// Calculate and record fees
-    pub fn calculate_fees(
-        &self,
-        current_ts: i64,
-    ) -> Result<(i64, i64), ProgramError> {
-        let time_elapsed = current_ts - self.stats.last_fee_collection_ts;
-        if time_elapsed <= 0 {
-            return Ok((0, 0));
+    /// Flags to change for a move to `target`: (changes is_open, changes is_paused)
+    ///
+    /// Leaving Paused sets is_open from the target; entering Paused keeps it,
+    /// so unpausing later restores the chosen deposit state explicitly.
+    pub fn state_transition(&self, target: FundLifecycleState) -> Result<(bool, bool), ProgramError> {
+        if self.is_closed() || target == self.lifecycle_state() {
+            return Err(crate::error::FundError::InvalidFundStateTransition.into());
         }
-        
-        let total_value = self.stats.total_value_e6();
-        
-        // Calculate management fee
-        let mgmt_fee = calculate_management_fee(
-            total_value,
-            self.fee_config.management_fee_bps,
-            time_elapsed,
-        )?;
-        
-        // Calculate performance fee
-        let perf_fee = if self.fee_config.use_high_water_mark {
-            calculate_performance_fee(
-                self.stats.current_nav_e6,
-                self.stats.high_water_mark_e6,
-                total_value,
-                self.fee_config.performance_fee_bps,
-            )?
-        } else {
-            0
-        };
-        
-        Ok((mgmt_fee, perf_fee))
+        Ok(match target {
+            FundLifecycleState::Paused => (false, true),
+            FundLifecycleState::Open => (!self.is_open, self.is_paused),
+            FundLifecycleState::DepositsClosed => (self.is_open, self.is_paused),
+        })
     }
     
-    /// Collect fees (update state)
-    pub fn collect_fees(&mut self, mgmt_fee: i64, perf_fee: i64, current_ts: i64) -> Result<(), ProgramError> {
-        self.stats.total_management_fee_e6 = safe_add_i64(self.stats.total_management_fee_e6, mgmt_fee)?;
-        self.stats.total_performance_fee_e6 = safe_add_i64(self.stats.total_performance_fee_e6, perf_fee)?;
-        self.stats.last_fee_collection_ts = current_ts;
-        
-        // Update NAV after fee deduction
-        self.stats.update_nav()?;
-        
-        // Update HWM after performance fee
-        self.stats.update_hwm();
-        
-        Ok(())
+    /// Deposits blocked pending an admin wind-down review?
+    pub fn is_in_wind_down_review(&self) -> bool {
+        self.wind_down_review_since != 0
     }
-}
-
-// === LP Position ===
-
-/// An LP investor's position in a fund
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct LPPosition {
-    /// Discriminator for account type
-    pub discriminator: u64,
     
-    /// Fund this position belongs to
-    pub fund: Pubkey,
+    /// NAV of a fund with shares outstanding has fallen below the deposit floor?
+    pub fn is_below_nav_floor(&self) -> bool {
+        self.stats.total_shares > 0 && self.stats.current_nav_e6 < MIN_DEPOSIT_NAV_E6
+    }
     
-    /// Investor wallet
-    pub investor: Pubkey,
+    /// Enter wind-down review if NAV is below the floor; returns true when newly entered
+    pub fn check_nav_floor(&mut self, current_ts: i64) -> bool {
+        if self.is_in_wind_down_review() || !self.is_below_nav_floor() {
+            return false;
+        }
+        self.wind_down_review_since = current_ts;
+        true
+    }
     
-    /// Number of shares held
-    pub shares: u64,
-    
-    /// NAV at time of deposit (for tracking returns)
-    pub deposit_nav_e6: i64,
-    
-    /// Total amount deposited (e6)
-    pub total_deposited_e6: i64,
+    /// Inside a post-PnL deposit/redemption blackout?
+    pub fn is_in_blackout(&self, current_ts: i64) -> bool {
+        current_ts < self.blackout_until
+    }
     
-    /// Total amount withdrawn (e6)
-    pub total_withdrawn_e6: i64,
+    /// Start (or extend) a blackout if `pnl_e6` is large relative to the
+    /// fund value before it was recorded; returns true when one was started
+    pub fn check_pnl_blackout(&mut self, pnl_e6: i64, value_before_e6: i64, current_ts: i64) -> bool {
+        if self.blackout_secs == 0 || pnl_e6 == 0 {
+            return false;
+        }
+        let is_large = value_before_e6 <= 0
+            || (pnl_e6.unsigned_abs() as u128) * (BPS_DENOMINATOR as u128)
+                >= (self.blackout_pnl_threshold_bps as u128) * (value_before_e6 as u128);
+        if !is_large {
+            return false;
+        }
+        self.blackout_until = self.blackout_until.max(current_ts.saturating_add(self.blackout_secs as i64));
+        true
+    }
     
-    /// Timestamp of first deposit
-    pub deposited_at: i64,
+    /// Convert a vault token amount into the fund's e6 accounting units
+    ///
+    /// wSOL funds floor lamports to micro-SOL; the sub-micro dust stays in
+    /// the vault for the remaining LPs.
+    pub fn token_amount_to_e6(&self, amount: u64) -> Result<i64, ProgramError> {
+        let amount_e6 = if self.sol_denominated {
+            amount / LAMPORTS_PER_SOL_E6
+        } else {
+            amount
+        };
+        i64::try_from(amount_e6).map_err(|_| crate::error::FundError::Overflow.into())
+    }
     
-    /// Last update timestamp
-    pub last_update_ts: i64,
+    /// Convert e6 accounting units into the vault token amount to transfer
+    pub fn e6_to_token_amount(&self, value_e6: i64) -> Result<u64, ProgramError> {
+        let value = u64::try_from(value_e6).map_err(|_| crate::error::FundError::InvalidAmount)?;
+        if self.sol_denominated {
+            value.checked_mul(LAMPORTS_PER_SOL_E6).ok_or_else(|| crate::error::FundError::Overflow.into())
+        } else {
+            Ok(value)
+        }
+    }
     
-    /// PDA bump
-    pub bump: u8,
+    /// Minimum deposit in the fund's e6 accounting units
+    pub fn min_deposit_e6(&self) -> i64 {
+        if self.sol_denominated {
+            MIN_SOL_DEPOSIT_E6
+        } else {
+            MIN_DEPOSIT_AMOUNT_E6
+        }
+    }
     
-    /// Reserved for future use
-    pub reserved: [u8; 32],
-}
-
-impl LPPosition {
-    /// Account size in bytes
-    pub const SIZE: usize = 8  // discriminator
-        + 32  // fund
-        + 32  // investor
-        + 8   // shares
-        + 8   // deposit_nav_e6
-        + 8   // total_deposited_e6
-        + 8   // total_withdrawn_e6
-        + 8   // deposited_at
-        + 8   // last_update_ts
-        + 1   // bump
-        + 32; // reserved
+    /// Check if withdrawals are allowed
+    ///
+    /// A pause stops blocking withdrawals once it outlives its maximum duration.
+    pub fn can_withdraw(&self, current_ts: i64) -> bool {
+        !self.is_paused || self.is_pause_expired(current_ts)
+    }
     
-    /// Create a new LP position
-    pub fn new(
-        fund: Pubkey,
-        investor: Pubkey,
-        shares: u64,
-        deposit_nav_e6: i64,
-        deposited_amount_e6: i64,
-        deposited_at: i64,
-        bump: u8,
-    ) -> Self {
-        Self {
-            discriminator: LP_POSITION_DISCRIMINATOR,
-            fund,
-            investor,
-            shares,
-            deposit_nav_e6,
-            total_deposited_e6: deposited_amount_e6,
-            total_withdrawn_e6: 0,
-            deposited_at,
-            last_update_ts: deposited_at,
-            bump,
-            reserved: [0u8; 32],
+    /// Manager may toggle pause now? (cooldown since the last toggle and daily cap)
+    ///
+    /// Stops a manager from flickering the pause state to trap in-flight
+    /// redemptions; admin toggles bypass this check.
+    pub fn check_pause_toggle(&self, current_ts: i64) -> Result<(), ProgramError> {
+        if self.last_pause_toggle_at > 0
+            && current_ts.saturating_sub(self.last_pause_toggle_at) < Self::PAUSE_TOGGLE_COOLDOWN_SECS
+        {
+            return Err(crate::error::FundError::PauseToggleCooldown.into());
+        }
+        if self.pause_toggle_day == (current_ts / 86400) as u32
+            && self.pause_toggles_today >= Self::MAX_PAUSE_TOGGLES_PER_DAY
+        {
+            return Err(crate::error::FundError::PauseToggleLimitReached.into());
         }
+        Ok(())
     }
     
-    /// PDA seeds for LP position
-    pub fn seeds(fund: &Pubkey, investor: &Pubkey) -> Vec<Vec<u8>> {
-        vec![
-            LP_POSITION_SEED.to_vec(),
-            fund.to_bytes().to_vec(),
-            investor.to_bytes().to_vec(),
-        ]
+    /// Count a pause toggle towards the cooldown and today's cap
+    pub fn record_pause_toggle(&mut self, current_ts: i64) {
+        let day = (current_ts / 86400) as u32;
+        if day != self.pause_toggle_day {
+            self.pause_toggle_day = day;
+            self.pause_toggles_today = 0;
+        }
+        self.pause_toggles_today = self.pause_toggles_today.saturating_add(1);
+        self.last_pause_toggle_at = current_ts;
     }
     
-    /// Calculate current value of position
-    pub fn current_value(&self, current_nav_e6: i64) -> i64 {
-        // value = shares * nav / 1e6
-        ((self.shares as i128) * (current_nav_e6 as i128) / 1_000_000) as i64
+    /// Paused for longer than the maximum pause duration?
+    pub fn is_pause_expired(&self, current_ts: i64) -> bool {
+        self.is_paused && self.pause_expires_at > 0 && current_ts >= self.pause_expires_at
     }
     
-    /// Calculate unrealized PnL
-    pub fn unrealized_pnl(&self, current_nav_e6: i64) -> i64 {
-        let current_value = self.current_value(current_nav_e6);
-        let net_invested = self.total_deposited_e6.saturating_sub(self.total_withdrawn_e6);
-        current_value.saturating_sub(net_invested)
+    /// Pause or unpause; protected funds never auto-expire (pause is admin only)
+    pub fn set_paused(&mut self, is_paused: bool, max_pause_secs: u32, current_ts: i64) {
+        self.is_paused = is_paused;
+        if is_paused {
+            self.paused_at = current_ts;
+            self.refresh_pause_expiry(max_pause_secs);
+        } else {
+            self.paused_at = 0;
+            self.pause_expires_at = 0;
+        }
     }
     
-    /// Add shares (deposit)
-    pub fn add_shares(
-        &mut self,
-        shares: u64,
-        amount_e6: i64,
-        current_nav_e6: i64,
-        current_ts: i64,
-    ) -> Result<(), ProgramError> {
-        self.shares = self.shares.saturating_add(shares);
-        self.total_deposited_e6 = safe_add_i64(self.total_deposited_e6, amount_e6)?;
-        
-        // Update weighted average deposit NAV
-        // new_avg_nav = (old_shares * old_nav + new_shares * new_nav) / total_shares
-        // Simplified: just update to current NAV for now
-        self.deposit_nav_e6 = current_nav_e6;
-        self.last_update_ts = current_ts;
-        
-        Ok(())
+    /// Recompute the auto-expiry deadline of a running pause
+    pub fn refresh_pause_expiry(&mut self, max_pause_secs: u32) {
+        self.pause_expires_at = if self.is_paused && !self.is_protected() && max_pause_secs > 0 {
+            self.paused_at.saturating_add(max_pause_secs as i64)
+        } else {
+            0
+        };
     }
     
-    /// Remove shares (redeem)
-    pub fn remove_shares(
-        &mut self,
-        shares: u64,
-        amount_e6: i64,
-        current_ts: i64,
-    ) -> Result<(), ProgramError> {
-        if shares > self.shares {
-            return Err(crate::error::FundError::InsufficientShares.into());
+    /// AUM integral including the not-yet-accrued period up to `current_ts`
+    pub fn pending_aum_seconds_e6(&self, current_ts: i64) -> i128 {
+        // Accounts created before the accumulator existed start from the last collection
+        let since = if self.last_aum_accrual_ts == 0 {
+            self.stats.last_fee_collection_ts
+        } else {
+            self.last_aum_accrual_ts
+        };
+        let elapsed = current_ts.saturating_sub(since);
+        if elapsed <= 0 {
+            return self.aum_seconds_e6;
         }
         
-        self.shares = self.shares.saturating_sub(shares);
-        self.total_withdrawn_e6 = safe_add_i64(self.total_withdrawn_e6, amount_e6)?;
-        self.last_update_ts = current_ts;
-        
-        Ok(())
+        let aum = self.stats.total_value_e6().max(0);
+        self.aum_seconds_e6
+            .saturating_add((aum as i128).saturating_mul(elapsed as i128))
     }
     
-    /// Check if position is empty
-    pub fn is_empty(&self) -> bool {
-        self.shares == 0
+    /// Advance the AUM integral (and the NAV TWAP accumulator) to `current_ts`
+    /// 
+    /// Must be called before every NAV-changing operation so each interval is
+    /// weighted by the AUM that was actually held during it.
+    pub fn accrue_aum(&mut self, current_ts: i64) {
+        self.aum_seconds_e6 = self.pending_aum_seconds_e6(current_ts);
+        if current_ts > self.last_aum_accrual_ts {
+            self.last_aum_accrual_ts = current_ts;
+        }
+        self.accrue_nav_twap(current_ts);
     }
-}
-
-// =============================================================================
-// Insurance Fund Config
-// =============================================================================
-
-/// ADL 触发原因
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ADLTriggerReason {
-    /// 不需要触发 ADL
-    None = 0,
-    /// 穿仓触发 (保险基金无法覆盖)
-    Bankruptcy = 1,
-    /// 余额不足触发 (低于阈值)
-    InsufficientBalance = 2,
-    /// 1小时内快速下降触发 (下降超过30%)
-    RapidDecline = 3,
-}
-
-impl Default for ADLTriggerReason {
-    fn default() -> Self {
-        ADLTriggerReason::None
+    
+    /// NAV accumulator including the not-yet-accrued period up to `current_ts`
+    fn pending_nav_cumulative_e6(&self, current_ts: i64) -> i128 {
+        let elapsed = current_ts.saturating_sub(self.nav_cumulative_ts);
+        if self.nav_cumulative_ts == 0 || elapsed <= 0 {
+            return self.nav_cumulative_e6;
+        }
+        self.nav_cumulative_e6
+            .saturating_add((self.stats.current_nav_e6 as i128).saturating_mul(elapsed as i128))
     }
-}
-
-/// Insurance Fund 专用配置账户
-/// 
-/// 这是 Insurance Fund 在 Fund Program 中的扩展配置，
-/// 与基础 Fund 账户配合使用。
-/// 
-/// PDA Seeds: ["insurance_fund_config"]
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct InsuranceFundConfig {
-    /// 账户类型标识符
-    pub discriminator: u64,
     
-    /// 关联的 Fund 账户地址
-    pub fund: Pubkey,
+    /// Start the NAV accumulator (new funds, or legacy accounts once grown)
+    pub fn start_nav_twap(&mut self, current_ts: i64) {
+        if self.nav_cumulative_ts == 0 {
+            self.nav_cumulative_ts = current_ts;
+            self.twap_last_ts = current_ts;
+        }
+    }
     
-    /// PDA bump
-    pub bump: u8,
+    /// Advance the NAV accumulator, rolling checkpoints once per TWAP window
+    /// 
+    /// A no-op until started: accounts that predate the accumulator may not
+    /// have room for it, and then fall back to spot NAV.
+    pub fn accrue_nav_twap(&mut self, current_ts: i64) {
+        if self.nav_cumulative_ts == 0 || current_ts <= self.nav_cumulative_ts {
+            return;
+        }
+        self.nav_cumulative_e6 = self.pending_nav_cumulative_e6(current_ts);
+        self.nav_cumulative_ts = current_ts;
+        if current_ts.saturating_sub(self.twap_last_ts) >= Self::NAV_TWAP_WINDOW_SECS {
+            self.twap_prev_cumulative_e6 = self.twap_last_cumulative_e6;
+            self.twap_prev_ts = self.twap_last_ts;
+            self.twap_last_cumulative_e6 = self.nav_cumulative_e6;
+            self.twap_last_ts = current_ts;
+        }
+    }
     
-    // === 收入统计 ===
+    /// Time-weighted average NAV over at least the last TWAP window
+    /// 
+    /// Measured from the newest checkpoint that is a full window old (falling
+    /// back to the oldest one available); spot NAV while no time has elapsed.
+    pub fn nav_twap_e6(&self, current_ts: i64) -> i64 {
+        let (since_cumulative, since_ts) = if current_ts.saturating_sub(self.twap_last_ts) < Self::NAV_TWAP_WINDOW_SECS
+            && self.twap_prev_ts > 0
+        {
+            (self.twap_prev_cumulative_e6, self.twap_prev_ts)
+        } else {
+            (self.twap_last_cumulative_e6, self.twap_last_ts)
+        };
+        let elapsed = current_ts.saturating_sub(since_ts);
+        if self.nav_cumulative_ts == 0 || since_ts == 0 || elapsed <= 0 {
+            return self.stats.current_nav_e6;
+        }
+        let twap = (self.pending_nav_cumulative_e6(current_ts) - since_cumulative) / elapsed as i128;
+        i64::try_from(twap).unwrap_or(self.stats.current_nav_e6)
+    }
     
-    /// 累计清算收入 (e6) - 来自强平罚金
-    pub total_liquidation_income_e6: i64,
+    /// NAV for performance fees: spot or TWAP, whichever is lower
+    /// 
+    /// A NAV spike just before a collection cannot crystallize a fee.
+    pub fn fee_nav_e6(&self, current_ts: i64) -> i64 {
+        self.stats.current_nav_e6.min(self.nav_twap_e6(current_ts))
+    }
     
-    /// 累计 ADL 盈余收入 (e6) - 来自 ADL 执行
-    pub total_adl_profit_e6: i64,
+    /// Fund value at the TWAP NAV (current shares)
+    pub fn twap_value_e6(&self, current_ts: i64) -> i64 {
+        calculate_redemption_value(self.stats.total_shares, self.nav_twap_e6(current_ts)).unwrap_or(0)
+    }
     
-    // === 支出统计 ===
+    /// Record a deposit
+    pub fn record_deposit(&mut self, amount_e6: i64, shares: u64) -> Result<(), ProgramError> {
+        self.stats.total_deposits_e6 = safe_add_i64(self.stats.total_deposits_e6, amount_e6)?;
+        self.stats.total_shares = self.stats.total_shares.saturating_add(shares);
+        self.stats.update_nav()?;
+        Ok(())
+    }
     
-    /// 累计穿仓支出 (e6) - 用于覆盖穿仓
-    pub total_shortfall_payout_e6: i64,
+    /// Value of a full exit of `shares`
+    ///
+    /// The last LP out receives the whole remaining fund value instead of
+    /// `shares × NAV`, so NAV rounding leaves no dust behind.
+    pub fn full_exit_value_e6(&self, shares: u64) -> Result<i64, ProgramError> {
+        if shares > 0 && shares == self.stats.total_shares {
+            Ok(self.stats.total_value_e6().max(0))
+        } else {
+            calculate_redemption_value(shares, self.stats.current_nav_e6)
+        }
+    }
     
-    // === ADL 配置 ===
+    /// Record a withdrawal
+    pub fn record_withdrawal(&mut self, amount_e6: i64, shares: u64) -> Result<(), ProgramError> {
+        self.stats.total_withdrawals_e6 = safe_add_i64(self.stats.total_withdrawals_e6, amount_e6)?;
+        self.stats.total_shares = self.stats.total_shares.saturating_sub(shares);
+        self.stats.update_nav()?;
+        Ok(())
+    }
     
-    /// ADL 余额不足触发阈值 (e6)
-    pub adl_trigger_threshold_e6: i64,
+    /// Record realized PnL
+    pub fn record_pnl(&mut self, pnl_e6: i64) -> Result<(), ProgramError> {
+        self.stats.total_realized_pnl_e6 = safe_add_i64(self.stats.total_realized_pnl_e6, pnl_e6)?;
+        self.stats.update_nav()?;
+        self.stats.update_hwm();
+        Ok(())
+    }
     
-    /// ADL 触发次数统计
-    pub adl_trigger_count: u64,
+    /// Credit an exchange fee rebate (maker rebate) to the fund
+    /// 
+    /// Booked as realized income so it flows into total_value_e6 / NAV, and
+    /// tracked in `total_fee_rebates_e6` to separate it from trading PnL.
+    pub fn record_fee_rebate(&mut self, rebate_e6: i64) -> Result<(), ProgramError> {
+        if rebate_e6 <= 0 {
+            return Err(crate::error::FundError::InvalidAmount.into());
+        }
+        self.total_fee_rebates_e6 = safe_add_i64(self.total_fee_rebates_e6, rebate_e6)?;
+        self.record_pnl(rebate_e6)
+    }
     
-    // === 1小时快照 (用于30%下降触发条件) ===
+    /// Realized trading PnL excluding fee rebates and fund expenses (e6)
+    pub fn trading_pnl_e6(&self) -> i64 {
+        self.stats.total_realized_pnl_e6
+            .saturating_sub(self.total_fee_rebates_e6)
+            .saturating_add(self.total_expenses_e6)
+    }
     
-    /// 1小时前的余额 (e6)
-    pub balance_1h_ago_e6: i64,
+    /// Annual expense allowance set at creation?
+    pub fn expenses_enabled(&self) -> bool {
+        self.expense_cap_bps > 0
+    }
     
-    /// 上次快照时间戳
-    pub last_snapshot_ts: i64,
+    /// Expenses paid in the period containing `current_ts` (e6)
+    pub fn expenses_paid_in_period_e6(&self, current_ts: i64) -> i64 {
+        if self.expense_period_start == 0
+            || current_ts >= self.expense_period_start.saturating_add(Self::EXPENSE_PERIOD_SECS)
+        {
+            0
+        } else {
+            self.expense_period_paid_e6
+        }
+    }
     
-    // === LP 赎回控制 ===
+    /// Annual expense budget at the current fund value (e6)
+    pub fn expense_cap_e6(&self) -> i64 {
+        let value = self.stats.total_value_e6().max(0) as u128;
+        (value * self.expense_cap_bps as u128 / BPS_DENOMINATOR as u128) as i64
+    }
     
-    /// 赎回延迟 (秒) - 提交赎回后需等待的时间
-    pub withdrawal_delay_secs: i64,
+    /// Pay a fund expense out of fund value
+    ///
+    /// Bounded by the per-payment maximum and, within each
+    /// EXPENSE_PERIOD_SECS period (starting at its first payment), by
+    /// `expense_cap_bps` of the fund value at payment time. Booked as a
+    /// realized loss so NAV drops by the amount paid.
+    pub fn record_expense(&mut self, amount_e6: i64, current_ts: i64) -> Result<(), ProgramError> {
+        if !self.expenses_enabled() {
+            return Err(crate::error::FundError::FundExpensesDisabled.into());
+        }
+        if amount_e6 <= 0 {
+            return Err(crate::error::FundError::InvalidAmount.into());
+        }
+        if amount_e6 as u64 > self.max_expense_payment_e6 {
+            return Err(crate::error::FundError::FundExpenseTooLarge.into());
+        }
+        
+        let paid = self.expenses_paid_in_period_e6(current_ts);
+        if safe_add_i64(paid, amount_e6)? > self.expense_cap_e6() {
+            return Err(crate::error::FundError::FundExpenseCapExceeded.into());
+        }
+        
+        if paid == 0 {
+            self.expense_period_start = current_ts;
+        }
+        self.expense_period_paid_e6 = paid + amount_e6;
+        self.total_expenses_e6 = safe_add_i64(self.total_expenses_e6, amount_e6)?;
+        self.record_pnl(-amount_e6)
+    }
     
-    /// ADL 进行中标志 - ADL 期间暂停 LP 赎回
-    pub is_adl_in_progress: bool,
+    /// Pay a coverage premium out of fund value
+    ///
+    /// Booked like a fund expense (outside the PayFundExpense allowance) so
+    /// it lowers NAV without counting as a trading loss.
+    pub fn record_coverage_premium(&mut self, premium_e6: i64) -> Result<(), ProgramError> {
+        if premium_e6 <= 0 {
+            return Err(crate::error::FundError::InvalidAmount.into());
+        }
+        self.total_expenses_e6 = safe_add_i64(self.total_expenses_e6, premium_e6)?;
+        self.record_pnl(-premium_e6)
+    }
     
-    // === 授权调用方 ===
+    /// Mirror the Ledger's view of the fund's open interest and margin
+    pub fn record_exposure(
+        &mut self,
+        open_notional_e6: u64,
+        margin_used_e6: u64,
+        free_collateral_e6: i64,
+        current_ts: i64,
+    ) {
+        self.open_notional_e6 = open_notional_e6;
+        self.margin_used_e6 = margin_used_e6;
+        self.free_collateral_e6 = free_collateral_e6;
+        self.exposure_updated_at = current_ts;
+    }
     
-    /// 授权调用 AddLiquidationIncome/AddADLProfit/CoverShortfall 的程序
-    pub authorized_caller: Pubkey,
+    /// Track a risk limit breach observation; returns true if it is newly opened
+    /// 
+    /// `excess_notional_e6 == 0` clears a previously observed breach.
+    pub fn observe_risk_breach(&mut self, excess_notional_e6: u64, current_ts: i64) -> bool {
+        if excess_notional_e6 == 0 {
+            self.risk_breach_since = 0;
+            return false;
+        }
+        if self.risk_breach_since == 0 {
+            self.risk_breach_since = current_ts;
+            return true;
+        }
+        false
+    }
     
-    /// 最后更新时间戳
-    pub last_update_ts: i64,
+    /// Has the breach outlasted RISK_BREACH_GRACE_SECS?
+    pub fn is_risk_breach_enforceable(&self, current_ts: i64) -> bool {
+        self.risk_breach_since > 0 && current_ts.saturating_sub(self.risk_breach_since) >= RISK_BREACH_GRACE_SECS
+    }
     
-    /// 预留字段 (扩展用)
-    pub reserved: [u8; 64],
-}
-
-impl InsuranceFundConfig {
-    /// 账户大小 (bytes)
-    pub const SIZE: usize = 8   // discriminator
-        + 32  // fund
-        + 1   // bump
-        + 8   // total_liquidation_income_e6
-        + 8   // total_adl_profit_e6
-        + 8   // total_shortfall_payout_e6
-        + 8   // adl_trigger_threshold_e6
-        + 8   // adl_trigger_count
-        + 8   // balance_1h_ago_e6
-        + 8   // last_snapshot_ts
-        + 8   // withdrawal_delay_secs
-        + 1   // is_adl_in_progress
-        + 32  // authorized_caller
-        + 8   // last_update_ts
-        + 64; // reserved
+    /// Was the mirrored exposure reported by the Ledger within RISK_EXPOSURE_MAX_AGE_SECS?
+    pub fn has_fresh_exposure(&self, current_ts: i64) -> bool {
+        self.exposure_updated_at > 0
+            && current_ts.saturating_sub(self.exposure_updated_at) <= RISK_EXPOSURE_MAX_AGE_SECS
+    }
     
-    /// 创建新的 InsuranceFundConfig
-    pub fn new(
-        fund: Pubkey,
-        bump: u8,
-        adl_trigger_threshold_e6: i64,
-        withdrawal_delay_secs: i64,
-        authorized_caller: Pubkey,
-        created_at: i64,
-    ) -> Self {
-        Self {
-            discriminator: INSURANCE_FUND_CONFIG_DISCRIMINATOR,
-            fund,
-            bump,
-            total_liquidation_income_e6: 0,
-            total_adl_profit_e6: 0,
-            total_shortfall_payout_e6: 0,
-            adl_trigger_threshold_e6,
-            adl_trigger_count: 0,
-            balance_1h_ago_e6: 0,
-            last_snapshot_ts: created_at,
-            withdrawal_delay_secs,
-            is_adl_in_progress: false,
-            authorized_caller,
-            last_update_ts: created_at,
-            reserved: [0u8; 64],
+    /// Book an enforced close against the mirrored exposure
+    /// 
+    /// Returns the notional removed, which never exceeds what the fund had
+    /// open; the Ledger's next RecordFundExposure overwrites the estimate.
+    pub fn record_enforced_close(&mut self, notional_e6: u64) -> u64 {
+        let closed = notional_e6.min(self.open_notional_e6);
+        self.open_notional_e6 -= closed;
+        closed
+    }
+    
+    /// Share of collateral locked as margin (bps, 10_000 = fully used)
+    pub fn margin_utilization_bps(&self) -> u64 {
+        let collateral = self.margin_used_e6 as u128 + self.free_collateral_e6.max(0) as u128;
+        if collateral == 0 {
+            return 0;
         }
+        (self.margin_used_e6 as u128 * BPS_DENOMINATOR as u128 / collateral) as u64
     }
     
-    /// PDA seeds for InsuranceFundConfig
-    pub fn seeds() -> Vec<Vec<u8>> {
-        vec![INSURANCE_FUND_CONFIG_SEED.to_vec()]
+    /// Flows blocked by an irreconcilable share supply?
+    pub fn is_share_supply_inconsistent(&self) -> bool {
+        self.share_supply_inconsistent_since != 0
     }
     
-    /// 检查是否需要触发 ADL
-    /// 
-    /// 三重触发条件:
-    /// 1. 穿仓触发: 保险基金余额 < 需要覆盖的穿仓金额
-    /// 2. 余额不足触发: 保险基金余额 < 最低阈值
-    /// 3. 1小时下降30%触发: 当前余额 < 1小时前余额 * 70%
-    pub fn should_trigger_adl(&self, current_balance_e6: i64, shortfall_e6: i64) -> ADLTriggerReason {
-        // 条件1: 穿仓触发
-        if shortfall_e6 > 0 && current_balance_e6 < shortfall_e6 {
-            return ADLTriggerReason::Bankruptcy;
-        }
-        
-        // 条件2: 余额不足触发
-        if current_balance_e6 < self.adl_trigger_threshold_e6 {
-            return ADLTriggerReason::InsufficientBalance;
+    /// ValidateFundSetup has recorded the denomination mint?
+    pub fn is_setup_validated(&self) -> bool {
+        self.denomination_mint != Pubkey::default()
+    }
+    
+    /// Check the fund vault's mint against the fund's denomination
+    ///
+    /// The vault may not hold the fund's own shares, must be wSOL exactly when
+    /// the fund is SOL-denominated, and must match `denomination_mint` once
+    /// recorded.
+    pub fn check_vault_mint(&self, vault_mint: &Pubkey) -> Result<(), ProgramError> {
+        let is_native = *vault_mint == spl_token::native_mint::id();
+        if *vault_mint == self.share_mint
+            || is_native != self.sol_denominated
+            || (self.is_setup_validated() && *vault_mint != self.denomination_mint)
+        {
+            return Err(crate::error::FundError::FundVaultMintMismatch.into());
         }
+        Ok(())
+    }
+    
+    /// Reconcile stats.total_shares with the share mint
+    ///
+    /// `mint_controlled` = the mint's authority is the fund's share authority
+    /// and its decimals are SHARE_MINT_DECIMALS. A matching supply clears the
+    /// flag. On a controlled mint the stats follow the mint (it is what LPs
+    /// hold) and NAV is recomputed for any decrease, since burned shares only
+    /// raise NAV for the remaining holders, and for an increase of at most
+    /// MAX_SHARE_SUPPLY_AUTO_FIX_BPS; anything else flags the fund.
+    pub fn reconcile_share_supply(
+        &mut self,
+        mint_supply: u64,
+        mint_controlled: bool,
+        current_ts: i64,
+    ) -> Result<ShareSupplyReconciliation, ProgramError> {
+        let recorded = self.stats.total_shares;
+        let drift = mint_supply.abs_diff(recorded);
+        let fixable = mint_controlled
+            && (mint_supply <= recorded
+                || drift as u128 * BPS_DENOMINATOR as u128
+                    <= recorded.max(mint_supply) as u128 * MAX_SHARE_SUPPLY_AUTO_FIX_BPS as u128);
         
-        // 条件3: 1小时下降30%触发
-        // 只有在有历史数据时才检查
-        if self.balance_1h_ago_e6 > 0 {
-            let threshold_70_percent = self.balance_1h_ago_e6 * 70 / 100;
-            if current_balance_e6 < threshold_70_percent {
-                return ADLTriggerReason::RapidDecline;
+        if !fixable {
+            if !self.is_share_supply_inconsistent() {
+                self.share_supply_inconsistent_since = current_ts;
             }
+            return Ok(ShareSupplyReconciliation::Inconsistent);
         }
         
-        ADLTriggerReason::None
-    }
-    
-    /// 覆盖穿仓损失
-    /// 
-    /// 返回: (实际覆盖金额, 剩余穿仓金额)
-    /// 如果剩余穿仓金额 > 0，需要触发 ADL
-    pub fn cover_shortfall(&mut self, shortfall_e6: i64, current_balance_e6: i64) -> (i64, i64) {
-        if shortfall_e6 <= current_balance_e6 {
-            // 保险基金可以完全覆盖
-            self.total_shortfall_payout_e6 = self.total_shortfall_payout_e6.saturating_add(shortfall_e6);
-            (shortfall_e6, 0)
+        self.share_supply_inconsistent_since = 0;
+        if drift == 0 {
+            return Ok(ShareSupplyReconciliation::Consistent);
+        }
+        self.stats.total_shares = mint_supply;
+        if mint_supply == 0 {
+            self.stats.current_nav_e6 = INITIAL_NAV_E6;
         } else {
-            // 保险基金不足，返回剩余穿仓金额
-            let covered = current_balance_e6;
-            let remaining = shortfall_e6.saturating_sub(covered);
-            self.total_shortfall_payout_e6 = self.total_shortfall_payout_e6.saturating_add(covered);
-            (covered, remaining)
+            self.stats.update_nav()?;
         }
+        Ok(ShareSupplyReconciliation::Fixed { previous_shares: recorded })
     }
     
-    /// 添加清算收入
-    pub fn add_liquidation_income(&mut self, amount_e6: i64) {
-        self.total_liquidation_income_e6 = self.total_liquidation_income_e6.saturating_add(amount_e6);
-    }
-    
-    /// 添加 ADL 盈余
-    pub fn add_adl_profit(&mut self, amount_e6: i64) {
-        self.total_adl_profit_e6 = self.total_adl_profit_e6.saturating_add(amount_e6);
+    /// Lift an inconsistent share supply flag (admin decision)
+    pub fn clear_share_supply_flag(&mut self) -> Result<i64, ProgramError> {
+        if !self.is_share_supply_inconsistent() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(std::mem::take(&mut self.share_supply_inconsistent_since))
     }
     
-    /// 添加交易手续费收入 (V1 简化方案: 记入 liquidation_income)
-    /// 
-    /// V1: 手续费直接计入 total_liquidation_income_e6 统一管理
-    /// V2: 可扩展为单独的 total_trading_fee_e6 字段 (使用 reserved bytes)
-    pub fn add_trading_fee(&mut self, fee_e6: i64) {
-        // V1: 简化方案 - 手续费与清算收入一起记账
-        self.total_liquidation_income_e6 = self.total_liquidation_income_e6.saturating_add(fee_e6);
+    /// Calculate and record fees
+    pub fn calculate_fees(
+        &self,
+        current_ts: i64,
+    ) -> Result<(i64, i64), ProgramError> {
+        self.calculate_fees_with_benchmark(current_ts, None)
     }
     
-    /// 更新1小时快照
-    pub fn update_hourly_snapshot(&mut self, current_balance_e6: i64, current_ts: i64) {
-        self.balance_1h_ago_e6 = current_balance_e6;
-        self.last_snapshot_ts = current_ts;
+    /// Benchmark designated?
+    pub fn has_benchmark(&self) -> bool {
+        self.benchmark_feed != Pubkey::default()
     }
     
-    /// 设置 ADL 进行中状态
-    pub fn set_adl_in_progress(&mut self, in_progress: bool) {
-        self.is_adl_in_progress = in_progress;
-        if in_progress {
-            self.adl_trigger_count = self.adl_trigger_count.saturating_add(1);
+    /// Performance fee hurdle for a relative fee
+    ///
+    /// The HWM scaled by the benchmark's return since the last crystallization,
+    /// never below the HWM itself: only profit that beats both is charged.
+    pub fn benchmark_hurdle_nav_e6(&self, benchmark_price_e6: i64) -> i64 {
+        let hwm = self.stats.high_water_mark_e6;
+        if self.benchmark_base_price_e6 <= 0 || benchmark_price_e6 <= self.benchmark_base_price_e6 {
+            return hwm;
         }
+        let hurdle = (hwm as i128) * (benchmark_price_e6 as i128) / (self.benchmark_base_price_e6 as i128);
+        hurdle.min(i64::MAX as i128) as i64
     }
     
-    /// 检查是否允许 LP 赎回
-    pub fn can_withdraw(&self) -> bool {
-        !self.is_adl_in_progress
+    /// Calculate fees, measuring the performance fee against the benchmark
+    /// when a relative fee is configured and its price is given
+    pub fn calculate_fees_with_benchmark(
+        &self,
+        current_ts: i64,
+        benchmark_price_e6: Option<i64>,
+    ) -> Result<(i64, i64), ProgramError> {
+        let time_elapsed = current_ts - self.stats.last_fee_collection_ts;
+        if time_elapsed <= 0 {
+            return Ok((0, 0));
+        }
+        
+        let total_value = self.stats.total_value_e6();
+        
+        // Calculate management fee from the time-weighted AUM integral
+        let mgmt_fee = calculate_management_fee_from_integral(
+            self.pending_aum_seconds_e6(current_ts),
+            self.fee_config.management_fee_bps,
+        )?;
+        
+        // Calculate performance fee
+        let hurdle_nav_e6 = match benchmark_price_e6 {
+            Some(price) if self.benchmark_relative_fee => self.benchmark_hurdle_nav_e6(price),
+            _ => self.stats.high_water_mark_e6,
+        };
+        let perf_fee = if self.fee_config.use_high_water_mark {
+            calculate_performance_fee(
+                self.fee_nav_e6(current_ts),
+                hurdle_nav_e6,
+                total_value,
+                self.fee_config.performance_fee_bps,
+            )?
+        } else {
+            0
+        };
+        
+        Ok((mgmt_fee, perf_fee))
     }
     
-    /// 验证调用方是否授权
-    pub fn is_authorized_caller(&self, caller: &Pubkey) -> bool {
-        caller == &self.authorized_caller
+    /// Collect fees (update state)
+    pub fn collect_fees(&mut self, mgmt_fee: i64, perf_fee: i64, current_ts: i64) -> Result<(), ProgramError> {
+        self.collect_fees_partial(mgmt_fee, mgmt_fee, true, perf_fee, current_ts)
     }
     
-    /// 获取总收入
-    pub fn total_income_e6(&self) -> i64 {
-        self.total_liquidation_income_e6.saturating_add(self.total_adl_profit_e6)
+    /// Split accrued fees into (collected, deferred) for a partial collection
+    /// 
+    /// Management fee is capped first; the performance fee is taken only
+    /// whole, because collecting it moves the high water mark.
+    /// `max_amount_e6 == 0` means no cap.
+    pub fn plan_fee_collection(
+        mgmt_accrued: i64,
+        perf_accrued: i64,
+        include_management: bool,
+        include_performance: bool,
+        max_amount_e6: u64,
+    ) -> (i64, i64) {
+        let mut cap = if max_amount_e6 == 0 { i64::MAX } else { max_amount_e6.min(i64::MAX as u64) as i64 };
+        
+        let mgmt = if include_management { mgmt_accrued.max(0).min(cap) } else { 0 };
+        cap -= mgmt;
+        
+        let perf = if include_performance && perf_accrued > 0 && perf_accrued <= cap { perf_accrued } else { 0 };
+        
+        (mgmt, perf)
     }
     
-    /// 获取净收入 (收入 - 支出)
-    pub fn net_income_e6(&self) -> i64 {
-        self.total_income_e6().saturating_sub(self.total_shortfall_payout_e6)
+    /// Collect part of the accrued fees (update state)
+    /// 
+    /// The uncollected share of the management fee stays in the AUM integral.
+    /// The HWM only moves when the performance fee was collected.
+    pub fn collect_fees_partial(
+        &mut self,
+        mgmt_fee: i64,
+        mgmt_accrued: i64,
+        perf_collected: bool,
+        perf_fee: i64,
+        current_ts: i64,
+    ) -> Result<(), ProgramError> {
+        self.stats.total_management_fee_e6 = safe_add_i64(self.stats.total_management_fee_e6, mgmt_fee)?;
+        self.stats.total_performance_fee_e6 = safe_add_i64(self.stats.total_performance_fee_e6, perf_fee)?;
+        self.stats.last_fee_collection_ts = current_ts;
+        
+        // Open a fee epoch for per-LP statements (fees are borne pro rata to shares)
+        if mgmt_fee > 0 || perf_fee > 0 {
+            self.fee_epoch = self.fee_epoch.saturating_add(1);
+            self.fee_epoch_at = current_ts;
+            self.fee_epoch_shares = self.stats.total_shares;
+            self.fee_epoch_mgmt_fee_e6 = mgmt_fee;
+            self.fee_epoch_perf_fee_e6 = perf_fee;
+        }
+        
+        // Restart the AUM integral for the next period, keeping any deferred part
+        if mgmt_fee >= mgmt_accrued || mgmt_accrued <= 0 {
+            self.aum_seconds_e6 = 0;
+        } else {
+            let deferred = (mgmt_accrued - mgmt_fee) as i128;
+            self.aum_seconds_e6 = self.aum_seconds_e6.saturating_mul(deferred) / mgmt_accrued as i128;
+        }
+        self.last_aum_accrual_ts = current_ts;
+        
+        // Update NAV after fee deduction
+        self.stats.update_nav()?;
+        
+        // Update HWM after performance fee, never above the NAV the fee was charged on
+        if perf_collected {
+            let fee_nav_e6 = self.fee_nav_e6(current_ts);
+            if fee_nav_e6 > self.stats.high_water_mark_e6 {
+                self.stats.high_water_mark_e6 = fee_nav_e6;
+            }
+        }
+        
+        Ok(())
     }
 }
 
-// =============================================================================
-// Square Payment Record
-// =============================================================================
-
-/// Square 支付类型
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SquarePaymentType {
-    /// 知识付费买断
-    KnowledgePurchase = 0,
-    /// 月度订阅
-    Subscription = 1,
-    /// 直播打赏
-    LiveDonation = 2,
-}
-
-impl Default for SquarePaymentType {
-    fn default() -> Self {
-        SquarePaymentType::KnowledgePurchase
-    }
-}
+// === LP Position ===
 
-/// Square 平台支付记录
-/// 
-/// 记录 Square 平台上的所有支付交易，包括：
-/// - 知识付费买断
-/// - 月度订阅
-/// - 直播打赏
-/// 
-/// 资金分成: 一部分进入创作者 Vault，一部分进入平台 Square Fund
-/// 
-/// PDA Seeds: ["square_payment", payer, content_id, timestamp]
+/// An LP investor's position in a fund
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SquarePaymentRecord {
-    /// 账户类型标识符
+pub struct LPPosition {
+    /// Discriminator for account type
     pub discriminator: u64,
     
-    /// 支付者地址 (用户)
-    pub payer: Pubkey,
+    /// Fund this position belongs to
+    pub fund: Pubkey,
     
-    /// 创作者地址 (收款人)
-    pub creator: Pubkey,
+    /// Investor wallet
+    pub investor: Pubkey,
     
-    /// 内容 ID (唯一标识内容)
-    pub content_id: u64,
+    /// Number of shares held
+    pub shares: u64,
     
-    /// 支付类型
-    pub payment_type: SquarePaymentType,
+    /// NAV at time of deposit (for tracking returns)
+    pub deposit_nav_e6: i64,
     
-    /// 总支付金额 (e6)
-    pub total_amount_e6: i64,
+    /// Total amount deposited (e6)
+    pub total_deposited_e6: i64,
     
-    /// 创作者分成金额 (e6) - 进入创作者 Vault
-    pub creator_amount_e6: i64,
+    /// Total amount withdrawn (e6)
+    pub total_withdrawn_e6: i64,
     
-    /// 平台分成金额 (e6) - 进入 Square Fund
-    pub platform_amount_e6: i64,
+    /// Timestamp of first deposit
+    pub deposited_at: i64,
     
-    /// 创作者分成比例 (基点, 10000 = 100%)
-    pub creator_share_bps: u16,
+    /// Last update timestamp
+    pub last_update_ts: i64,
     
-    /// 支付时间戳
-    pub payment_ts: i64,
+    /// PDA bump
+    pub bump: u8,
     
-    /// 订阅周期数 (仅用于订阅类型)
-    pub subscription_period: u8,
+    /// Transfer-on-death beneficiary (Pubkey::default() = none)
+    pub beneficiary: Pubkey,
     
-    /// 交易备注 (最多32字节)
-    pub memo: [u8; 32],
+    // === Appended after the original layout (see VersionedAccount) ===
     
-    /// PDA bump
-    pub bump: u8,
+    /// Redemptions only payable to the PayoutAllowList of this position
+    pub payout_allowlist_enabled: bool,
     
-    /// 保留字段
-    pub reserved: [u8; 16],
+    /// Listed in the fund's LP index?
+    pub is_indexed: bool,
+    
+    /// LPIndexPage holding this position (valid when is_indexed)
+    pub lp_index_page: u16,
+    
+    /// Shares pledged to `lienholder` (cannot be redeemed)
+    pub locked_shares: u64,
+    
+    /// External party holding the lock (Pubkey::default() = none)
+    pub lienholder: Pubkey,
+    
+    /// Represented by a PositionNFT; shares escrowed and locked to it
+    pub nft_wrapped: bool,
+    
+    /// Rent was paid by the RentReserve (refunded to it on close)
+    pub rent_sponsored: bool,
+    
+    /// A deposit receipt NFT was issued (burned on full exit)
+    pub has_receipt: bool,
+    
+    /// NAV this position's loss has been compensated down to by ClaimFundCoverage (e6, 0 = never)
+    pub coverage_nav_e6: i64,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 9],
 }
 
-impl SquarePaymentRecord {
+impl LPPosition {
     /// Account size in bytes
-    pub const SIZE: usize = 8    // discriminator
-        + 32  // payer
-        + 32  // creator
-        + 8   // content_id
-        + 1   // payment_type
-        + 8   // total_amount_e6
-        + 8   // creator_amount_e6
-        + 8   // platform_amount_e6
-        + 2   // creator_share_bps
-        + 8   // payment_ts
-        + 1   // subscription_period
-        + 32  // memo
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // investor
+        + 8   // shares
+        + 8   // deposit_nav_e6
+        + 8   // total_deposited_e6
+        + 8   // total_withdrawn_e6
+        + 8   // deposited_at
+        + 8   // last_update_ts
         + 1   // bump
-        + 16; // reserved
+        + 32  // beneficiary (formerly reserved)
+        + 1   // payout_allowlist_enabled
+        + 1   // is_indexed
+        + 2   // lp_index_page
+        + 8   // locked_shares
+        + 32  // lienholder
+        + 1   // nft_wrapped
+        + 1   // rent_sponsored
+        + 1   // has_receipt
+        + 8   // coverage_nav_e6
+        + 9;  // reserved
+    
+    /// Size of positions created before the layout was extended
+    pub const LEGACY_SIZE: usize = 153;
     
-    /// 创建新的支付记录
+    /// Create a new LP position
     pub fn new(
-        payer: Pubkey,
-        creator: Pubkey,
-        content_id: u64,
-        payment_type: SquarePaymentType,
-        total_amount_e6: i64,
-        creator_share_bps: u16,
-        payment_ts: i64,
-        subscription_period: u8,
-        memo: &[u8],
+        fund: Pubkey,
+        investor: Pubkey,
+        shares: u64,
+        deposit_nav_e6: i64,
+        deposited_amount_e6: i64,
+        deposited_at: i64,
         bump: u8,
     ) -> Self {
-        // 计算分成金额
-        let creator_amount_e6 = (total_amount_e6 as i128 * creator_share_bps as i128 / 10000) as i64;
-        let platform_amount_e6 = total_amount_e6.saturating_sub(creator_amount_e6);
-        
-        let mut memo_array = [0u8; 32];
-        let copy_len = memo.len().min(32);
-        memo_array[..copy_len].copy_from_slice(&memo[..copy_len]);
-        
         Self {
-            discriminator: SQUARE_PAYMENT_RECORD_DISCRIMINATOR,
-            payer,
-            creator,
-            content_id,
-            payment_type,
-            total_amount_e6,
-            creator_amount_e6,
-            platform_amount_e6,
-            creator_share_bps,
-            payment_ts,
-            subscription_period,
-            memo: memo_array,
+            discriminator: LP_POSITION_DISCRIMINATOR,
+            fund,
+            investor,
+            shares,
+            deposit_nav_e6,
+            total_deposited_e6: deposited_amount_e6,
+            total_withdrawn_e6: 0,
+            deposited_at,
+            last_update_ts: deposited_at,
             bump,
-            reserved: [0u8; 16],
+            beneficiary: Pubkey::default(),
+            payout_allowlist_enabled: false,
+            is_indexed: false,
+            lp_index_page: 0,
+            locked_shares: 0,
+            lienholder: Pubkey::default(),
+            nft_wrapped: false,
+            rent_sponsored: false,
+            has_receipt: false,
+            coverage_nav_e6: 0,
+            reserved: [0u8; 9],
         }
     }
     
-    /// PDA seeds for SquarePaymentRecord
-    pub fn seeds(payer: &Pubkey, content_id: u64, timestamp: i64) -> Vec<Vec<u8>> {
+    /// PDA seeds for LP position
+    pub fn seeds(fund: &Pubkey, investor: &Pubkey) -> Vec<Vec<u8>> {
         vec![
-            SQUARE_PAYMENT_RECORD_SEED.to_vec(),
-            payer.to_bytes().to_vec(),
-            content_id.to_le_bytes().to_vec(),
-            timestamp.to_le_bytes().to_vec(),
+            LP_POSITION_SEED.to_vec(),
+            fund.to_bytes().to_vec(),
+            investor.to_bytes().to_vec(),
         ]
     }
     
-    /// 获取创作者分成金额
-    pub fn get_creator_amount(&self) -> i64 {
-        self.creator_amount_e6
+    /// Calculate current value of position
+    pub fn current_value(&self, current_nav_e6: i64) -> i64 {
+        // value = shares * nav / 1e6
+        ((self.shares as i128) * (current_nav_e6 as i128) / 1_000_000) as i64
     }
     
-    /// 获取平台分成金额
-    pub fn get_platform_amount(&self) -> i64 {
-        self.platform_amount_e6
+    /// Calculate unrealized PnL
+    pub fn unrealized_pnl(&self, current_nav_e6: i64) -> i64 {
+        let current_value = self.current_value(current_nav_e6);
+        let net_invested = self.total_deposited_e6.saturating_sub(self.total_withdrawn_e6);
+        current_value.saturating_sub(net_invested)
     }
     
-    /// 检查是否为订阅类型
-    pub fn is_subscription(&self) -> bool {
-        self.payment_type == SquarePaymentType::Subscription
+    /// Add shares (deposit)
+    pub fn add_shares(
+        &mut self,
+        shares: u64,
+        amount_e6: i64,
+        current_nav_e6: i64,
+        current_ts: i64,
+    ) -> Result<(), ProgramError> {
+        self.shares = self.shares.saturating_add(shares);
+        self.total_deposited_e6 = safe_add_i64(self.total_deposited_e6, amount_e6)?;
+        
+        // Update weighted average deposit NAV
+        // new_avg_nav = (old_shares * old_nav + new_shares * new_nav) / total_shares
+        // Simplified: just update to current NAV for now
+        self.deposit_nav_e6 = current_nav_e6;
+        self.last_update_ts = current_ts;
+        
+        Ok(())
     }
     
-    /// 获取 memo 字符串
-    pub fn memo_str(&self) -> &str {
-        let end = self.memo.iter().position(|&b| b == 0).unwrap_or(32);
-        std::str::from_utf8(&self.memo[..end]).unwrap_or("")
+    /// Remove shares (redeem)
+    pub fn remove_shares(
+        &mut self,
+        shares: u64,
+        amount_e6: i64,
+        current_ts: i64,
+    ) -> Result<(), ProgramError> {
+        if shares > self.shares {
+            return Err(crate::error::FundError::InsufficientShares.into());
+        }
+        if shares > self.available_shares() {
+            return Err(crate::error::FundError::SharesLocked.into());
+        }
+        
+        self.shares = self.shares.saturating_sub(shares);
+        self.total_withdrawn_e6 = safe_add_i64(self.total_withdrawn_e6, amount_e6)?;
+        self.last_update_ts = current_ts;
+        
+        Ok(())
+    }
+    
+    /// Check if position is empty
+    pub fn is_empty(&self) -> bool {
+        self.shares == 0
+    }
+    
+    /// Shares not pledged to a lienholder
+    pub fn available_shares(&self) -> u64 {
+        self.shares.saturating_sub(self.locked_shares)
+    }
+    
+    /// Pledge shares to `lienholder` (one lienholder at a time)
+    pub fn lock_shares(&mut self, lienholder: Pubkey, shares: u64) -> Result<(), ProgramError> {
+        if self.locked_shares > 0 && self.lienholder != lienholder {
+            return Err(crate::error::FundError::ShareLienHeld.into());
+        }
+        if shares == 0 || shares > self.available_shares() {
+            return Err(crate::error::FundError::InsufficientShares.into());
+        }
+        self.locked_shares += shares;
+        self.lienholder = lienholder;
+        Ok(())
+    }
+    
+    /// Release pledged shares; clears the lienholder once nothing is locked
+    pub fn unlock_shares(&mut self, lienholder: &Pubkey, shares: u64) -> Result<(), ProgramError> {
+        if self.locked_shares == 0 || self.lienholder != *lienholder {
+            return Err(crate::error::FundError::ShareLienHeld.into());
+        }
+        if shares == 0 || shares > self.locked_shares {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.locked_shares -= shares;
+        if self.locked_shares == 0 {
+            self.lienholder = Pubkey::default();
+        }
+        Ok(())
+    }
+    
+    /// Check if a beneficiary has been designated
+    pub fn has_beneficiary(&self) -> bool {
+        self.beneficiary != Pubkey::default()
+    }
+    
+    /// Check if the beneficiary may claim (position inactive long enough)
+    pub fn can_beneficiary_claim(&self, inactivity_secs: i64, current_ts: i64) -> bool {
+        self.has_beneficiary() && current_ts.saturating_sub(self.last_update_ts) >= inactivity_secs
+    }
+    
+    /// Move all shares out of this position (beneficiary claim)
+    /// Returns (shares, net invested e6) carried over to the new position
+    pub fn transfer_out(&mut self, current_ts: i64) -> (u64, i64) {
+        let shares = self.shares;
+        let net_invested = self.total_deposited_e6.saturating_sub(self.total_withdrawn_e6).max(0);
+        self.shares = 0;
+        self.beneficiary = Pubkey::default();
+        self.last_update_ts = current_ts;
+        (shares, net_invested)
+    }
+    
+    /// Hand the position to a new investor (position NFT transfer)
+    ///
+    /// The previous owner's beneficiary and payout allow-list no longer apply.
+    pub fn reassign(&mut self, investor: Pubkey, current_ts: i64) {
+        self.investor = investor;
+        self.beneficiary = Pubkey::default();
+        self.payout_allowlist_enabled = false;
+        self.last_update_ts = current_ts;
+    }
+    
+    /// NAV a ClaimFundCoverage on this position is measured from
+    ///
+    /// The entry NAV, or where an earlier claim on these shares stopped. The
+    /// mark stays with the shares (move_to, NFT reassignment), so a loss is
+    /// compensated once whichever wallet claims it.
+    pub fn coverage_reference_nav_e6(&self) -> i64 {
+        if self.coverage_nav_e6 == 0 {
+            self.deposit_nav_e6
+        } else {
+            self.coverage_nav_e6.min(self.deposit_nav_e6)
+        }
+    }
+    
+    /// Record that the position's loss was compensated down to `nav_e6`
+    pub fn record_coverage_claim(&mut self, nav_e6: i64, current_ts: i64) {
+        self.coverage_nav_e6 = nav_e6;
+        self.last_update_ts = current_ts;
+    }
+    
+    /// Move the whole position to `investor`'s LP position PDA (wallet rotation)
+    ///
+    /// Cost basis, `deposited_at` and the coverage mark carry over unchanged;
+    /// this position is left empty, like `transfer_out`.
+    pub fn move_to(&mut self, investor: Pubkey, bump: u8, current_ts: i64) -> LPPosition {
+        let mut moved = LPPosition::new(
+            self.fund,
+            investor,
+            self.shares,
+            self.deposit_nav_e6,
+            self.total_deposited_e6,
+            self.deposited_at,
+            bump,
+        );
+        moved.total_withdrawn_e6 = self.total_withdrawn_e6;
+        moved.coverage_nav_e6 = self.coverage_nav_e6;
+        moved.last_update_ts = current_ts;
+        
+        self.shares = 0;
+        self.beneficiary = Pubkey::default();
+        self.payout_allowlist_enabled = false;
+        self.last_update_ts = current_ts;
+        moved
     }
 }
 
 // =============================================================================
-// Referral System
+// Insurance Fund Config
 // =============================================================================
 
-/// 最大邀请码长度
-pub const MAX_REFERRAL_CODE_LEN: usize = 12;
-
-/// VIP 等级数量
-pub const VIP_LEVELS: usize = 6;
-
-/// 默认邀请人分成 (2000 = 20%)
-pub const DEFAULT_REFERRER_SHARE_BPS: u16 = 2000;
-
-/// 默认被邀请人折扣 (1000 = 10%)
-pub const DEFAULT_REFEREE_DISCOUNT_BPS: u16 = 1000;
+/// ADL 触发原因
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ADLTriggerReason {
+    /// 不需要触发 ADL
+    #[default]
+    None = 0,
+    /// 穿仓触发 (保险基金无法覆盖)
+    Bankruptcy = 1,
+    /// 余额不足触发 (低于阈值)
+    InsufficientBalance = 2,
+    /// 1小时内快速下降触发 (下降超过30%)
+    RapidDecline = 3,
+}
 
-/// 全局返佣配置
+/// Insurance Fund 专用配置账户
 /// 
-/// PDA Seeds: ["referral_config"]
+/// 这是 Insurance Fund 在 Fund Program 中的扩展配置，
+/// 与基础 Fund 账户配合使用。
+/// 
+/// PDA Seeds: ["insurance_fund_config"]
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct ReferralConfig {
-    /// 账户类型标识
+pub struct InsuranceFundConfig {
+    /// 账户类型标识符
     pub discriminator: u64,
     
-    /// 管理员
-    pub authority: Pubkey,
+    /// 关联的 Fund 账户地址
+    pub fund: Pubkey,
     
-    /// Vault Program ID (用于 CPI 转账)
-    pub vault_program: Pubkey,
+    /// PDA bump
+    pub bump: u8,
     
-    // === 基础分成比例 (basis points, 10000 = 100%) ===
+    // === 收入统计 ===
     
-    /// 邀请人获得手续费的比例 (默认 2000 = 20%)
-    pub referrer_share_bps: u16,
+    /// 累计清算收入 (e6) - 来自强平罚金
+    pub total_liquidation_income_e6: i64,
     
-    /// 被邀请人手续费折扣 (默认 1000 = 10%)
-    pub referee_discount_bps: u16,
+    /// 累计 ADL 盈余收入 (e6) - 来自 ADL 执行
+    pub total_adl_profit_e6: i64,
     
-    // === VIP 等级加成 ===
+    // === 支出统计 ===
     
-    /// 邀请人 VIP 等级加成 [VIP0, VIP1, ..., VIP5] bps
-    pub referrer_vip_bonus_bps: [u16; VIP_LEVELS],
+    /// 累计穿仓支出 (e6) - 用于覆盖穿仓
+    pub total_shortfall_payout_e6: i64,
     
-    /// 被邀请人 VIP 等级折扣加成 [VIP0, VIP1, ..., VIP5] bps
-    pub referee_vip_bonus_bps: [u16; VIP_LEVELS],
+    // === ADL 配置 ===
     
-    // === 限制 ===
+    /// ADL 余额不足触发阈值 (e6)
+    pub adl_trigger_threshold_e6: i64,
     
-    /// 最低结算金额 (e6) - 低于此金额累计
-    pub min_settlement_amount_e6: i64,
+    /// ADL 触发次数统计
+    pub adl_trigger_count: u64,
     
-    /// 返佣有效期 (秒) - 0 = 永久
-    pub reward_validity_secs: i64,
+    // === 1小时快照 (用于30%下降触发条件) ===
     
-    // === 统计 ===
+    /// 1小时前的余额 (e6)
+    pub balance_1h_ago_e6: i64,
     
-    /// 总发放返佣金额 (e6)
-    pub total_rewards_paid_e6: i64,
+    /// 上次快照时间戳
+    pub last_snapshot_ts: i64,
     
-    /// 总发放折扣金额 (e6)
-    pub total_discounts_given_e6: i64,
+    // === LP 赎回控制 ===
     
-    /// 总注册邀请链接数
-    pub total_referral_links: u64,
+    /// 赎回延迟 (秒) - 提交赎回后需等待的时间
+    pub withdrawal_delay_secs: i64,
     
-    /// 总邀请用户数
-    pub total_referred_users: u64,
+    /// ADL 进行中标志 - ADL 期间暂停 LP 赎回
+    pub is_adl_in_progress: bool,
     
-    /// 总产生交易量 (e6)
-    pub total_referred_volume_e6: i64,
+    // === 授权调用方 ===
     
-    // === 状态 ===
+    /// 授权调用 AddLiquidationIncome/AddADLProfit/CoverShortfall 的程序
+    pub authorized_caller: Pubkey,
     
-    /// 是否暂停
-    pub is_paused: bool,
+    /// 最后更新时间戳
+    pub last_update_ts: i64,
     
-    /// PDA bump
-    pub bump: u8,
+    // === 每日统计基线 (上次写入 InsuranceDailyStats 时的累计值) ===
     
-    /// 最后更新时间
-    pub last_update_ts: i64,
+    /// 基线: 累计清算收入 + 交易手续费 (e6)
+    pub daily_baseline_liquidation_income_e6: i64,
     
-    /// 预留字段
-    pub reserved: [u8; 64],
+    /// 基线: 累计 ADL 盈余 (e6)
+    pub daily_baseline_adl_profit_e6: i64,
+    
+    /// 基线: 累计穿仓支出 (e6)
+    pub daily_baseline_shortfall_payout_e6: i64,
+    
+    /// 基线是否已建立 (旧账户为 false，首次写入每日统计时建立)
+    pub daily_baseline_set: bool,
+    
+    /// 创建时间 (旧账户为 0)
+    pub created_at: i64,
+    
+    /// 未被覆盖、等待 ADL 的穿仓金额 (e6)，下一次 ADL 开始时计入 ADLEvent
+    pub pending_adl_shortfall_e6: i64,
+    
+    /// 累计交易手续费收入 (e6) - V2 起单独记账 (迁移前为 0，手续费计入清算收入)
+    pub total_trading_fee_e6: i64,
+    
+    /// MigrateInsuranceConfigV2 执行时间 (0 = 未迁移，仍按 V1 记账)
+    pub v2_migrated_at: i64,
+    
+    /// 预留字段 (扩展用)
+    pub reserved: [u8; 7],
+    
+    // === 覆盖率 (reserved 用尽后追加，旧账户需先 ReallocAccount) ===
+    
+    /// Ledger 上报的全平台未平仓量 (e6)
+    pub platform_open_interest_e6: i64,
+    
+    /// 未平仓量上报时间 (0 = 从未上报)
+    pub open_interest_reported_at: i64,
+    
+    /// 发布的覆盖率 (bps): 保险基金金库余额 / 全平台未平仓量
+    pub coverage_ratio_bps: u64,
+    
+    /// 覆盖率计算时间 (0 = 从未计算)
+    pub coverage_updated_at: i64,
 }
 
-impl ReferralConfig {
-    /// 账户大小
+impl InsuranceFundConfig {
+    /// 账户大小 (bytes)
     pub const SIZE: usize = 8   // discriminator
-        + 32  // authority
-        + 32  // vault_program
-        + 2   // referrer_share_bps
-        + 2   // referee_discount_bps
-        + 12  // referrer_vip_bonus_bps (6 * 2)
-        + 12  // referee_vip_bonus_bps (6 * 2)
-        + 8   // min_settlement_amount_e6
-        + 8   // reward_validity_secs
-        + 8   // total_rewards_paid_e6
-        + 8   // total_discounts_given_e6
-        + 8   // total_referral_links
-        + 8   // total_referred_users
-        + 8   // total_referred_volume_e6
-        + 1   // is_paused
+        + 32  // fund
         + 1   // bump
+        + 8   // total_liquidation_income_e6
+        + 8   // total_adl_profit_e6
+        + 8   // total_shortfall_payout_e6
+        + 8   // adl_trigger_threshold_e6
+        + 8   // adl_trigger_count
+        + 8   // balance_1h_ago_e6
+        + 8   // last_snapshot_ts
+        + 8   // withdrawal_delay_secs
+        + 1   // is_adl_in_progress
+        + 32  // authorized_caller
         + 8   // last_update_ts
-        + 64; // reserved
+        + 8   // daily_baseline_liquidation_income_e6
+        + 8   // daily_baseline_adl_profit_e6
+        + 8   // daily_baseline_shortfall_payout_e6
+        + 1   // daily_baseline_set
+        + 8   // created_at
+        + 8   // pending_adl_shortfall_e6
+        + 8   // total_trading_fee_e6
+        + 8   // v2_migrated_at
+        + 7   // reserved
+        + 8   // platform_open_interest_e6
+        + 8   // open_interest_reported_at
+        + 8   // coverage_ratio_bps
+        + 8;  // coverage_updated_at
     
-    /// 创建新的 ReferralConfig
+    /// 覆盖率字段在账户数据中的偏移 (供非 Rust 的 Ledger 直接读取)
+    pub const COVERAGE_OFFSET: usize = Self::SIZE - 32;
+    
+    /// 覆盖率发布后的有效期 (秒)，超过后 Ledger 应视为过期
+    pub const COVERAGE_MAX_AGE_SECS: i64 = 3600;
+    
+    /// 创建新的 InsuranceFundConfig
     pub fn new(
-        authority: Pubkey,
-        vault_program: Pubkey,
-        referrer_share_bps: u16,
-        referee_discount_bps: u16,
+        fund: Pubkey,
         bump: u8,
+        adl_trigger_threshold_e6: i64,
+        withdrawal_delay_secs: i64,
+        authorized_caller: Pubkey,
         created_at: i64,
     ) -> Self {
         Self {
-            discriminator: REFERRAL_CONFIG_DISCRIMINATOR,
-            authority,
-            vault_program,
-            referrer_share_bps,
-            referee_discount_bps,
-            // 默认 VIP 加成: [0%, 2%, 5%, 10%, 15%, 20%]
-            referrer_vip_bonus_bps: [0, 200, 500, 1000, 1500, 2000],
-            referee_vip_bonus_bps: [0, 200, 500, 1000, 1500, 2000],
-            min_settlement_amount_e6: 10_000_000, // $10 最低结算
-            reward_validity_secs: 0, // 永久有效
-            total_rewards_paid_e6: 0,
-            total_discounts_given_e6: 0,
-            total_referral_links: 0,
-            total_referred_users: 0,
-            total_referred_volume_e6: 0,
-            is_paused: false,
+            discriminator: INSURANCE_FUND_CONFIG_DISCRIMINATOR,
+            fund,
             bump,
+            total_liquidation_income_e6: 0,
+            total_adl_profit_e6: 0,
+            total_shortfall_payout_e6: 0,
+            adl_trigger_threshold_e6,
+            adl_trigger_count: 0,
+            balance_1h_ago_e6: 0,
+            last_snapshot_ts: created_at,
+            withdrawal_delay_secs,
+            is_adl_in_progress: false,
+            authorized_caller,
             last_update_ts: created_at,
-            reserved: [0u8; 64],
+            daily_baseline_liquidation_income_e6: 0,
+            daily_baseline_adl_profit_e6: 0,
+            daily_baseline_shortfall_payout_e6: 0,
+            daily_baseline_set: true,
+            created_at,
+            pending_adl_shortfall_e6: 0,
+            total_trading_fee_e6: 0,
+            // New configs start on V2 accounting
+            v2_migrated_at: created_at,
+            reserved: [0u8; 7],
+            platform_open_interest_e6: 0,
+            open_interest_reported_at: 0,
+            coverage_ratio_bps: 0,
+            coverage_updated_at: 0,
         }
     }
     
-    /// PDA seeds
+    /// PDA seeds for InsuranceFundConfig
     pub fn seeds() -> Vec<Vec<u8>> {
-        vec![REFERRAL_CONFIG_SEED.to_vec()]
-    }
-    
-    /// 获取邀请人总分成比例 (基础 + VIP 加成)
-    pub fn get_referrer_share(&self, vip_level: u8) -> u16 {
-        let level = (vip_level as usize).min(VIP_LEVELS - 1);
-        self.referrer_share_bps.saturating_add(self.referrer_vip_bonus_bps[level])
-    }
-    
-    /// 获取被邀请人总折扣比例 (基础 + VIP 加成)
-    pub fn get_referee_discount(&self, vip_level: u8) -> u16 {
-        let level = (vip_level as usize).min(VIP_LEVELS - 1);
-        self.referee_discount_bps.saturating_add(self.referee_vip_bonus_bps[level])
+        vec![INSURANCE_FUND_CONFIG_SEED.to_vec()]
     }
     
-    /// 计算返佣金额
+    /// 检查是否需要触发 ADL
     /// 
-    /// 返回: (referrer_reward, referee_discount, platform_income)
-    pub fn calculate_rewards(
+    /// 三重触发条件:
+    /// 1. 穿仓触发: 保险基金余额 < 需要覆盖的穿仓金额
+    /// 2. 余额不足触发: 保险基金余额 < 最低阈值
+    /// 3. 1小时下降30%触发: 当前余额 < 1小时前余额 * 70%
+    ///    (传入 `twap_balance_e6` 时，TWAP 价值也须低于 70%，避免瞬时 NAV 波动误触发)
+    pub fn should_trigger_adl(
         &self,
-        trade_fee_e6: i64,
-        referrer_vip: u8,
-        referee_vip: u8,
-    ) -> (i64, i64, i64) {
-        // 取较高的 VIP 等级
-        let effective_vip = referrer_vip.max(referee_vip);
-        
-        // 计算被邀请人折扣
-        let discount_bps = self.get_referee_discount(effective_vip);
-        let referee_discount = (trade_fee_e6 as i128 * discount_bps as i128 / 10000) as i64;
-        
-        // 实际收取的手续费
-        let actual_fee = trade_fee_e6.saturating_sub(referee_discount);
+        current_balance_e6: i64,
+        shortfall_e6: i64,
+        twap_balance_e6: Option<i64>,
+    ) -> ADLTriggerReason {
+        // 条件1: 穿仓触发
+        if shortfall_e6 > 0 && current_balance_e6 < shortfall_e6 {
+            return ADLTriggerReason::Bankruptcy;
+        }
         
-        // 计算邀请人返佣 (基于实际收取的手续费)
-        let referrer_share_bps = self.get_referrer_share(effective_vip);
-        let referrer_reward = (actual_fee as i128 * referrer_share_bps as i128 / 10000) as i64;
+        // 条件2: 余额不足触发
+        if current_balance_e6 < self.adl_trigger_threshold_e6 {
+            return ADLTriggerReason::InsufficientBalance;
+        }
         
-        // 平台收入
-        let platform_income = actual_fee.saturating_sub(referrer_reward);
+        // 条件3: 1小时下降30%触发
+        // 只有在有历史数据时才检查
+        if self.balance_1h_ago_e6 > 0 {
+            let threshold_70_percent = self.balance_1h_ago_e6 * 70 / 100;
+            let twap_confirms = twap_balance_e6.is_none_or(|twap| twap < threshold_70_percent);
+            if current_balance_e6 < threshold_70_percent && twap_confirms {
+                return ADLTriggerReason::RapidDecline;
+            }
+        }
         
-        (referrer_reward, referee_discount, platform_income)
+        ADLTriggerReason::None
     }
     
-    /// 更新统计
-    pub fn record_reward(
-        &mut self,
-        referrer_reward_e6: i64,
-        referee_discount_e6: i64,
-        volume_e6: i64,
-        current_ts: i64,
-    ) {
-        self.total_rewards_paid_e6 = self.total_rewards_paid_e6.saturating_add(referrer_reward_e6);
-        self.total_discounts_given_e6 = self.total_discounts_given_e6.saturating_add(referee_discount_e6);
-        self.total_referred_volume_e6 = self.total_referred_volume_e6.saturating_add(volume_e6);
-        self.last_update_ts = current_ts;
+    /// 覆盖穿仓损失
+    /// 
+    /// 返回: (实际覆盖金额, 剩余穿仓金额)
+    /// 如果剩余穿仓金额 > 0，需要触发 ADL
+    pub fn cover_shortfall(&mut self, shortfall_e6: i64, current_balance_e6: i64) -> (i64, i64) {
+        if shortfall_e6 <= current_balance_e6 {
+            // 保险基金可以完全覆盖
+            self.total_shortfall_payout_e6 = self.total_shortfall_payout_e6.saturating_add(shortfall_e6);
+            (shortfall_e6, 0)
+        } else {
+            // 保险基金不足，返回剩余穿仓金额
+            let covered = current_balance_e6;
+            let remaining = shortfall_e6.saturating_sub(covered);
+            self.total_shortfall_payout_e6 = self.total_shortfall_payout_e6.saturating_add(covered);
+            (covered, remaining)
+        }
     }
-}
-
-/// 邀请链接
-/// 
-/// PDA Seeds: ["referral_link", referrer]
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct ReferralLink {
-    /// 账户类型标识
-    pub discriminator: u64,
     
-    /// 邀请人
-    pub referrer: Pubkey,
+    /// 添加清算收入
+    pub fn add_liquidation_income(&mut self, amount_e6: i64) {
+        self.total_liquidation_income_e6 = self.total_liquidation_income_e6.saturating_add(amount_e6);
+    }
     
-    /// 邀请码 (唯一, 6-12 字符)
-    pub code: [u8; MAX_REFERRAL_CODE_LEN],
+    /// 添加 ADL 盈余
+    pub fn add_adl_profit(&mut self, amount_e6: i64) {
+        self.total_adl_profit_e6 = self.total_adl_profit_e6.saturating_add(amount_e6);
+    }
     
-    /// 创建时间
-    pub created_at: i64,
+    /// 添加交易手续费收入
+    /// 
+    /// V1 (未迁移): 手续费直接计入 total_liquidation_income_e6 统一管理
+    /// V2: 单独计入 total_trading_fee_e6
+    /// 
+    /// 迁移前保持 V1 记账，使 total_liquidation_income_e6 在迁移时恰为历史合计，
+    /// 迁移期间无需停机。
+    pub fn add_trading_fee(&mut self, fee_e6: i64) {
+        if self.is_v2() {
+            self.total_trading_fee_e6 = self.total_trading_fee_e6.saturating_add(fee_e6);
+        } else {
+            self.total_liquidation_income_e6 = self.total_liquidation_income_e6.saturating_add(fee_e6);
+        }
+    }
     
-    /// 是否激活
-    pub is_active: bool,
+    /// 是否已按 V2 分别记账清算收入与交易手续费
+    pub fn is_v2(&self) -> bool {
+        self.v2_migrated_at != 0
+    }
     
-    // === 自定义配置 (可选) ===
+    /// 一次性迁移到 V2: 按管理员回填值拆分历史 total_liquidation_income_e6
+    /// 
+    /// 两部分之和必须等于当前 total_liquidation_income_e6 (V1 合计)，
+    /// 合计收入与每日统计基线不变，历史分析口径保持可比。
+    pub fn migrate_v2(
+        &mut self,
+        liquidation_income_e6: i64,
+        trading_fee_e6: i64,
+        current_ts: i64,
+    ) -> Result<(), ProgramError> {
+        if self.is_v2() {
+            return Err(crate::error::FundError::InsuranceConfigAlreadyMigrated.into());
+        }
+        if liquidation_income_e6 < 0
+            || trading_fee_e6 < 0
+            || liquidation_income_e6.checked_add(trading_fee_e6) != Some(self.total_liquidation_income_e6)
+        {
+            return Err(crate::error::FundError::InsuranceMigrationMismatch.into());
+        }
+        self.total_liquidation_income_e6 = liquidation_income_e6;
+        self.total_trading_fee_e6 = trading_fee_e6;
+        self.v2_migrated_at = current_ts.max(1);
+        Ok(())
+    }
     
-    /// 自定义邀请人分成 (0 = 使用全局配置)
-    pub custom_referrer_share_bps: u16,
+    /// 清算收入 + 交易手续费 (V1 口径的 total_liquidation_income_e6)
+    pub fn liquidation_and_fee_income_e6(&self) -> i64 {
+        self.total_liquidation_income_e6.saturating_add(self.total_trading_fee_e6)
+    }
     
-    /// 自定义被邀请人折扣 (0 = 使用全局配置)
-    pub custom_referee_discount_bps: u16,
+    /// 更新1小时快照
+    pub fn update_hourly_snapshot(&mut self, current_balance_e6: i64, current_ts: i64) {
+        self.balance_1h_ago_e6 = current_balance_e6;
+        self.last_snapshot_ts = current_ts;
+    }
     
-    // === 统计 ===
+    /// 取出自上次写入每日统计以来的增量并推进基线
+    /// 
+    /// 返回: (清算收入, ADL 盈余, 穿仓支出)
+    /// 旧账户首次调用时只建立基线，返回全 0，避免把历史累计值计入当天。
+    pub fn take_daily_deltas(&mut self) -> (i64, i64, i64) {
+        let deltas = self.pending_daily_deltas();
+        
+        self.daily_baseline_liquidation_income_e6 = self.liquidation_and_fee_income_e6();
+        self.daily_baseline_adl_profit_e6 = self.total_adl_profit_e6;
+        self.daily_baseline_shortfall_payout_e6 = self.total_shortfall_payout_e6;
+        self.daily_baseline_set = true;
+        
+        deltas
+    }
     
-    /// 邀请人数
-    pub referred_count: u32,
+    /// 自上次写入每日统计以来的增量 (只读，不移动基线)
+    /// 
+    /// 返回: (清算收入, ADL 盈余, 穿仓支出)，基线未建立时返回全 0
+    /// 清算收入按 V1 口径包含交易手续费，迁移前后可比。
+    pub fn pending_daily_deltas(&self) -> (i64, i64, i64) {
+        if !self.daily_baseline_set {
+            return (0, 0, 0);
+        }
+        (
+            self.liquidation_and_fee_income_e6().saturating_sub(self.daily_baseline_liquidation_income_e6),
+            self.total_adl_profit_e6.saturating_sub(self.daily_baseline_adl_profit_e6),
+            self.total_shortfall_payout_e6.saturating_sub(self.daily_baseline_shortfall_payout_e6),
+        )
+    }
     
-    /// 累计交易量 (被邀请人产生)
-    pub total_volume_e6: i64,
+    /// 覆盖率 (bps): 余额 / 未平仓量
+    /// 
+    /// 未平仓量为 0 时返回 u64::MAX (无敞口，视为完全覆盖)
+    pub fn coverage_ratio_bps(balance_e6: i64, open_interest_e6: i64) -> u64 {
+        if open_interest_e6 <= 0 {
+            return u64::MAX;
+        }
+        let ratio = (balance_e6.max(0) as u128) * 10_000 / open_interest_e6 as u128;
+        ratio.min(u64::MAX as u128) as u64
+    }
     
-    /// 累计获得返佣
-    pub total_rewards_earned_e6: i64,
+    /// 记录 Ledger 上报的全平台未平仓量
+    pub fn record_open_interest(&mut self, open_interest_e6: i64, current_ts: i64) -> Result<(), ProgramError> {
+        if open_interest_e6 < 0 {
+            return Err(crate::error::FundError::InvalidAmount.into());
+        }
+        self.platform_open_interest_e6 = open_interest_e6;
+        self.open_interest_reported_at = current_ts;
+        Ok(())
+    }
     
-    /// 累计发放折扣
-    pub total_discounts_given_e6: i64,
+    /// 按金库余额与最近上报的未平仓量重算并发布覆盖率
+    pub fn update_coverage(&mut self, balance_e6: i64, current_ts: i64) -> Result<u64, ProgramError> {
+        if self.open_interest_reported_at == 0 {
+            return Err(crate::error::FundError::OpenInterestNotReported.into());
+        }
+        self.coverage_ratio_bps = Self::coverage_ratio_bps(balance_e6, self.platform_open_interest_e6);
+        self.coverage_updated_at = current_ts;
+        Ok(self.coverage_ratio_bps)
+    }
     
-    /// PDA bump
-    pub bump: u8,
+    /// Ledger 读取的覆盖率
+    /// 
+    /// 覆盖率或其依据的未平仓量超过 COVERAGE_MAX_AGE_SECS 未更新时返回 None，
+    /// Ledger 应按最严格的保证金档位处理
+    pub fn published_coverage_bps(&self, current_ts: i64) -> Option<u64> {
+        let fresh = |ts: i64| ts != 0 && current_ts.saturating_sub(ts) <= Self::COVERAGE_MAX_AGE_SECS;
+        (fresh(self.coverage_updated_at) && fresh(self.open_interest_reported_at))
+            .then_some(self.coverage_ratio_bps)
+    }
     
-    /// 预留字段
-    pub reserved: [u8; 32],
-}
-
-impl ReferralLink {
-    /// 账户大小
-    pub const SIZE: usize = 8   // discriminator
-        + 32  // referrer
-        + MAX_REFERRAL_CODE_LEN  // code
-        + 8   // created_at
-        + 1   // is_active
-        + 2   // custom_referrer_share_bps
-        + 2   // custom_referee_discount_bps
-        + 4   // referred_count
-        + 8   // total_volume_e6
-        + 8   // total_rewards_earned_e6
-        + 8   // total_discounts_given_e6
-        + 1   // bump
-        + 32; // reserved
+    /// 设置 ADL 进行中状态
+    /// 
+    /// 仅在从未进行切换到进行中时计为新一轮 ADL (episode = adl_trigger_count)，
+    /// 返回是否开始了新一轮
+    pub fn set_adl_in_progress(&mut self, in_progress: bool) -> bool {
+        let started = in_progress && !self.is_adl_in_progress;
+        self.is_adl_in_progress = in_progress;
+        if started {
+            self.adl_trigger_count = self.adl_trigger_count.saturating_add(1);
+        }
+        started
+    }
     
-    /// 创建新的邀请链接
-    pub fn new(
-        referrer: Pubkey,
-        code: &[u8],
-        bump: u8,
-        created_at: i64,
-    ) -> Self {
-        let mut code_bytes = [0u8; MAX_REFERRAL_CODE_LEN];
-        let len = code.len().min(MAX_REFERRAL_CODE_LEN);
-        code_bytes[..len].copy_from_slice(&code[..len]);
-        
-        Self {
-            discriminator: REFERRAL_LINK_DISCRIMINATOR,
-            referrer,
-            code: code_bytes,
-            created_at,
-            is_active: true,
-            custom_referrer_share_bps: 0,
-            custom_referee_discount_bps: 0,
-            referred_count: 0,
-            total_volume_e6: 0,
-            total_rewards_earned_e6: 0,
-            total_discounts_given_e6: 0,
-            bump,
-            reserved: [0u8; 32],
+    /// ADL 开始时的触发原因: 有未覆盖穿仓即为穿仓触发，否则按余额条件判断
+    pub fn adl_start_reason(&self, current_balance_e6: i64, twap_balance_e6: Option<i64>) -> ADLTriggerReason {
+        if self.pending_adl_shortfall_e6 > 0 {
+            return ADLTriggerReason::Bankruptcy;
         }
+        self.should_trigger_adl(current_balance_e6, 0, twap_balance_e6)
     }
     
-    /// PDA seeds
-    pub fn seeds(referrer: &Pubkey) -> Vec<Vec<u8>> {
-        vec![
-            REFERRAL_LINK_SEED.to_vec(),
-            referrer.to_bytes().to_vec(),
-        ]
+    /// 检查是否允许 LP 赎回
+    pub fn can_withdraw(&self) -> bool {
+        !self.is_adl_in_progress
     }
     
-    /// 获取邀请码字符串
-    pub fn code_str(&self) -> String {
-        let end = self.code.iter().position(|&b| b == 0).unwrap_or(self.code.len());
-        String::from_utf8_lossy(&self.code[..end]).to_string()
+    /// 检查是否允许 LP 存入 (ADL 期间 NAV 即将下调，暂停申购)
+    pub fn can_deposit(&self) -> bool {
+        !self.is_adl_in_progress
     }
     
-    /// 记录新邀请
-    pub fn record_referral(&mut self) {
-        self.referred_count = self.referred_count.saturating_add(1);
+    /// 验证调用方是否授权
+    pub fn is_authorized_caller(&self, caller: &Pubkey) -> bool {
+        caller == &self.authorized_caller
     }
     
-    /// 记录返佣
-    pub fn record_reward(&mut self, reward_e6: i64, discount_e6: i64, volume_e6: i64) {
-        self.total_rewards_earned_e6 = self.total_rewards_earned_e6.saturating_add(reward_e6);
-        self.total_discounts_given_e6 = self.total_discounts_given_e6.saturating_add(discount_e6);
-        self.total_volume_e6 = self.total_volume_e6.saturating_add(volume_e6);
+    /// 获取总收入
+    pub fn total_income_e6(&self) -> i64 {
+        self.liquidation_and_fee_income_e6().saturating_add(self.total_adl_profit_e6)
+    }
+    
+    /// 获取净收入 (收入 - 支出)
+    pub fn net_income_e6(&self) -> i64 {
+        self.total_income_e6().saturating_sub(self.total_shortfall_payout_e6)
     }
 }
 
-/// 邀请关系绑定
+// =============================================================================
+// Square Payment Record
+// =============================================================================
+
+/// Square 支付类型
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SquarePaymentType {
+    /// 知识付费买断
+    #[default]
+    KnowledgePurchase = 0,
+    /// 月度订阅
+    Subscription = 1,
+    /// 直播打赏
+    LiveDonation = 2,
+}
+
+/// Square 平台支付记录
 /// 
-/// PDA Seeds: ["referral_binding", referee]
+/// 记录 Square 平台上的所有支付交易，包括：
+/// - 知识付费买断
+/// - 月度订阅
+/// - 直播打赏
+/// 
+/// 资金分成: 一部分进入创作者 Vault，一部分进入平台 Square Fund
+/// 
+/// PDA Seeds: ["square_payment", payer, nonce]
+///
+/// nonce 来自付款人的 `SquarePayerNonce` 计数器，同一付款人同一区块内的多笔支付
+/// 不会再因时间戳相同而冲突。
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct ReferralBinding {
-    /// 账户类型标识
+pub struct SquarePaymentRecord {
+    /// 账户类型标识符
     pub discriminator: u64,
     
-    /// 被邀请人
-    pub referee: Pubkey,
+    /// 支付者地址 (用户)
+    pub payer: Pubkey,
     
-    /// 邀请人
-    pub referrer: Pubkey,
+    /// 创作者地址 (收款人)
+    pub creator: Pubkey,
     
-    /// 邀请链接
-    pub referral_link: Pubkey,
+    /// 内容 ID (唯一标识内容)
+    pub content_id: u64,
     
-    /// 绑定时间
-    pub bound_at: i64,
+    /// 支付类型
+    pub payment_type: SquarePaymentType,
     
-    // === 统计 ===
+    /// 总支付金额 (e6)
+    pub total_amount_e6: i64,
     
-    /// 被邀请人累计交易量 (e6)
-    pub referee_volume_e6: i64,
+    /// 创作者分成金额 (e6) - 进入创作者 Vault
+    pub creator_amount_e6: i64,
     
-    /// 邀请人从此用户获得的返佣 (e6)
-    pub referrer_rewards_e6: i64,
+    /// 平台分成金额 (e6) - 进入 Square Fund
+    pub platform_amount_e6: i64,
     
-    /// 被邀请人获得的折扣 (e6)
-    pub referee_discounts_e6: i64,
+    /// 创作者分成比例 (基点, 10000 = 100%)
+    pub creator_share_bps: u16,
     
-    /// 交易次数
-    pub trade_count: u64,
+    /// 支付时间戳
+    pub payment_ts: i64,
     
-    /// 最后交易时间
-    pub last_trade_ts: i64,
+    /// 订阅周期数 (仅用于订阅类型)
+    pub subscription_period: u8,
+    
+    /// 交易备注 (最多32字节)
+    pub memo: [u8; 32],
     
     /// PDA bump
     pub bump: u8,
     
-    /// 预留字段
-    pub reserved: [u8; 32],
+    /// 付款人 nonce (PDA seed; 旧记录为 0)
+    pub nonce: u64,
+    
+    /// 保留字段
+    pub reserved: [u8; 8],
 }
 
-impl ReferralBinding {
-    /// 账户大小
-    pub const SIZE: usize = 8   // discriminator
-        + 32  // referee
-        + 32  // referrer
-        + 32  // referral_link
-        + 8   // bound_at
-        + 8   // referee_volume_e6
-        + 8   // referrer_rewards_e6
-        + 8   // referee_discounts_e6
-        + 8   // trade_count
-        + 8   // last_trade_ts
+impl SquarePaymentRecord {
+    /// Account size in bytes
+    pub const SIZE: usize = 8    // discriminator
+        + 32  // payer
+        + 32  // creator
+        + 8   // content_id
+        + 1   // payment_type
+        + 8   // total_amount_e6
+        + 8   // creator_amount_e6
+        + 8   // platform_amount_e6
+        + 2   // creator_share_bps
+        + 8   // payment_ts
+        + 1   // subscription_period
+        + 32  // memo
         + 1   // bump
-        + 32; // reserved
+        + 8   // nonce
+        + 8;  // reserved
     
-    /// 创建新的邀请关系
+    /// 创建新的支付记录
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        referee: Pubkey,
-        referrer: Pubkey,
-        referral_link: Pubkey,
+        payer: Pubkey,
+        creator: Pubkey,
+        content_id: u64,
+        payment_type: SquarePaymentType,
+        total_amount_e6: i64,
+        creator_share_bps: u16,
+        payment_ts: i64,
+        subscription_period: u8,
+        memo: &[u8],
+        nonce: u64,
         bump: u8,
-        bound_at: i64,
     ) -> Self {
+        // 计算分成金额
+        let creator_amount_e6 = (total_amount_e6 as i128 * creator_share_bps as i128 / 10000) as i64;
+        let platform_amount_e6 = total_amount_e6.saturating_sub(creator_amount_e6);
+        
+        let mut memo_array = [0u8; 32];
+        let copy_len = memo.len().min(32);
+        memo_array[..copy_len].copy_from_slice(&memo[..copy_len]);
+        
         Self {
-            discriminator: REFERRAL_BINDING_DISCRIMINATOR,
-            referee,
-            referrer,
-            referral_link,
-            bound_at,
-            referee_volume_e6: 0,
-            referrer_rewards_e6: 0,
-            referee_discounts_e6: 0,
-            trade_count: 0,
-            last_trade_ts: 0,
+            discriminator: SQUARE_PAYMENT_RECORD_DISCRIMINATOR,
+            payer,
+            creator,
+            content_id,
+            payment_type,
+            total_amount_e6,
+            creator_amount_e6,
+            platform_amount_e6,
+            creator_share_bps,
+            payment_ts,
+            subscription_period,
+            memo: memo_array,
             bump,
-            reserved: [0u8; 32],
+            nonce,
+            reserved: [0u8; 8],
         }
     }
     
-    /// PDA seeds
-    pub fn seeds(referee: &Pubkey) -> Vec<Vec<u8>> {
+    /// PDA seeds for SquarePaymentRecord
+    pub fn seeds(payer: &Pubkey, nonce: u64) -> Vec<Vec<u8>> {
         vec![
-            REFERRAL_BINDING_SEED.to_vec(),
-            referee.to_bytes().to_vec(),
+            SQUARE_PAYMENT_RECORD_SEED.to_vec(),
+            payer.to_bytes().to_vec(),
+            nonce.to_le_bytes().to_vec(),
         ]
     }
     
-    /// 记录交易
-    pub fn record_trade(
-        &mut self,
-        volume_e6: i64,
-        referrer_reward_e6: i64,
-        referee_discount_e6: i64,
-        current_ts: i64,
-    ) {
-        self.referee_volume_e6 = self.referee_volume_e6.saturating_add(volume_e6);
-        self.referrer_rewards_e6 = self.referrer_rewards_e6.saturating_add(referrer_reward_e6);
-        self.referee_discounts_e6 = self.referee_discounts_e6.saturating_add(referee_discount_e6);
-        self.trade_count = self.trade_count.saturating_add(1);
-        self.last_trade_ts = current_ts;
+    /// 获取创作者分成金额
+    pub fn get_creator_amount(&self) -> i64 {
+        self.creator_amount_e6
+    }
+    
+    /// 获取平台分成金额
+    pub fn get_platform_amount(&self) -> i64 {
+        self.platform_amount_e6
+    }
+    
+    /// 检查是否为订阅类型
+    pub fn is_subscription(&self) -> bool {
+        self.payment_type == SquarePaymentType::Subscription
+    }
+    
+    /// 获取 memo 字符串
+    pub fn memo_str(&self) -> &str {
+        let end = self.memo.iter().position(|&b| b == 0).unwrap_or(32);
+        std::str::from_utf8(&self.memo[..end]).unwrap_or("")
     }
 }
 
 // =============================================================================
-// Prediction Market Fee Config
+// Referral System
 // =============================================================================
 
-/// 预测市场手续费配置
-/// 
-/// 管理预测市场的手续费收取和分配
+/// 最大邀请码长度
+pub const MAX_REFERRAL_CODE_LEN: usize = 12;
+
+/// VIP 等级数量
+pub const VIP_LEVELS: usize = 6;
+
+/// 默认邀请人分成 (2000 = 20%)
+pub const DEFAULT_REFERRER_SHARE_BPS: u16 = 2000;
+
+/// 默认被邀请人折扣 (1000 = 10%)
+pub const DEFAULT_REFEREE_DISCOUNT_BPS: u16 = 1000;
+
+/// 全局返佣配置
 /// 
-/// PDA Seeds: ["prediction_market_fee_config"]
+/// PDA Seeds: ["referral_config"]
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct PredictionMarketFeeConfig {
-    /// 账户类型标识符
+pub struct ReferralConfig {
+    /// 账户类型标识
     pub discriminator: u64,
     
-    /// 预测市场手续费资金池 (USDC Token Account)
-    pub prediction_market_fee_vault: Pubkey,
+    /// 管理员
+    pub authority: Pubkey,
     
-    /// PDA bump
-    pub bump: u8,
+    /// Vault Program ID (用于 CPI 转账)
+    pub vault_program: Pubkey,
     
-    // === 预测市场费率配置 (basis points, 10000 = 100%) ===
+    // === 基础分成比例 (basis points, 10000 = 100%) ===
     
-    /// 预测市场铸造费率 (默认 10 = 0.1%)
-    pub prediction_market_minting_fee_bps: u16,
+    /// 邀请人获得手续费的比例 (默认 2000 = 20%)
+    pub referrer_share_bps: u16,
     
-    /// 预测市场赎回费率 (默认 10 = 0.1%)
-    pub prediction_market_redemption_fee_bps: u16,
+    /// 被邀请人手续费折扣 (默认 1000 = 10%)
+    pub referee_discount_bps: u16,
     
-    /// 预测市场 Taker 交易费率 (默认 10 = 0.1%)
-    pub prediction_market_trading_fee_taker_bps: u16,
+    // === VIP 等级加成 ===
     
-    /// 预测市场 Maker 交易费率 (默认 0 = 0%)
-    pub prediction_market_trading_fee_maker_bps: u16,
+    /// 邀请人 VIP 等级加成 [VIP0, VIP1, ..., VIP5] bps
+    pub referrer_vip_bonus_bps: [u16; VIP_LEVELS],
     
-    /// 预测市场结算费率 (默认 0 = 0%)
-    pub prediction_market_settlement_fee_bps: u16,
+    /// 被邀请人 VIP 等级折扣加成 [VIP0, VIP1, ..., VIP5] bps
+    pub referee_vip_bonus_bps: [u16; VIP_LEVELS],
     
-    // === 预测市场费用分配比例 (basis points, 总计 10000) ===
+    // === 限制 ===
     
-    /// 预测市场协议收入占比 (默认 7000 = 70%)
-    pub prediction_market_protocol_share_bps: u16,
+    /// 最低结算金额 (e6) - 低于此金额累计
+    pub min_settlement_amount_e6: i64,
     
-    /// 预测市场做市商奖励占比 (默认 2000 = 20%)
-    pub prediction_market_maker_reward_share_bps: u16,
+    /// 返佣有效期 (秒, 从绑定时间起算) - 0 = 永久
+    pub reward_validity_secs: i64,
     
-    /// 预测市场创建者占比 (默认 1000 = 10%)
-    pub prediction_market_creator_share_bps: u16,
+    // === 统计 ===
     
-    // === 预测市场累计统计 (e6) ===
+    /// 总发放返佣金额 (e6)
+    pub total_rewards_paid_e6: i64,
     
-    /// 预测市场累计铸造费收入
-    pub prediction_market_total_minting_fee_e6: i64,
+    /// 总发放折扣金额 (e6)
+    pub total_discounts_given_e6: i64,
     
-    /// 预测市场累计赎回费收入
-    pub prediction_market_total_redemption_fee_e6: i64,
+    /// 总注册邀请链接数
+    pub total_referral_links: u64,
     
-    /// 预测市场累计交易费收入
-    pub prediction_market_total_trading_fee_e6: i64,
+    /// 总邀请用户数
+    pub total_referred_users: u64,
     
-    /// 预测市场累计做市商奖励发放
-    pub prediction_market_total_maker_rewards_e6: i64,
+    /// 总产生交易量 (e6)
+    pub total_referred_volume_e6: i64,
     
-    /// 预测市场累计创建者分成
-    pub prediction_market_total_creator_rewards_e6: i64,
+    // === 状态 ===
     
-    /// 预测市场累计协议收入
-    pub prediction_market_total_protocol_income_e6: i64,
+    /// 是否暂停
+    pub is_paused: bool,
     
-    // === 授权 ===
+    /// PDA bump
+    pub bump: u8,
     
-    /// 授权调用方 (Prediction Market Program)
-    pub prediction_market_authorized_caller: Pubkey,
+    /// 最后更新时间
+    pub last_update_ts: i64,
     
-    /// 管理员
-    pub authority: Pubkey,
+    /// 绑定过期后是否允许换绑
+    pub rebind_policy: ReferralRebindPolicy,
     
-    /// 是否暂停
-    pub is_paused: bool,
+    /// 创建时间 (旧账户为 0)
+    pub created_at: i64,
     
-    /// 最后更新时间戳
-    pub last_update_ts: i64,
+    /// 已从交易手续费划入返佣金库的邀请人奖励累计 (e6)
+    pub total_rewards_funded_e6: i64,
+    
+    /// 已被邀请人领取的奖励累计 (e6，含转为基金份额的部分)
+    pub total_rewards_claimed_e6: i64,
     
     /// 预留字段
-    pub reserved: [u8; 64],
+    pub reserved: [u8; 39],
 }
 
-impl PredictionMarketFeeConfig {
+impl ReferralConfig {
     /// 账户大小
     pub const SIZE: usize = 8   // discriminator
-        + 32  // prediction_market_fee_vault
-        + 1   // bump
-        + 2   // prediction_market_minting_fee_bps
-        + 2   // prediction_market_redemption_fee_bps
-        + 2   // prediction_market_trading_fee_taker_bps
-        + 2   // prediction_market_trading_fee_maker_bps
-        + 2   // prediction_market_settlement_fee_bps
-        + 2   // prediction_market_protocol_share_bps
-        + 2   // prediction_market_maker_reward_share_bps
-        + 2   // prediction_market_creator_share_bps
-        + 8   // prediction_market_total_minting_fee_e6
-        + 8   // prediction_market_total_redemption_fee_e6
-        + 8   // prediction_market_total_trading_fee_e6
-        + 8   // prediction_market_total_maker_rewards_e6
-        + 8   // prediction_market_total_creator_rewards_e6
-        + 8   // prediction_market_total_protocol_income_e6
-        + 32  // prediction_market_authorized_caller
         + 32  // authority
+        + 32  // vault_program
+        + 2   // referrer_share_bps
+        + 2   // referee_discount_bps
+        + 12  // referrer_vip_bonus_bps (6 * 2)
+        + 12  // referee_vip_bonus_bps (6 * 2)
+        + 8   // min_settlement_amount_e6
+        + 8   // reward_validity_secs
+        + 8   // total_rewards_paid_e6
+        + 8   // total_discounts_given_e6
+        + 8   // total_referral_links
+        + 8   // total_referred_users
+        + 8   // total_referred_volume_e6
         + 1   // is_paused
+        + 1   // bump
         + 8   // last_update_ts
-        + 64; // reserved
+        + 1   // rebind_policy
+        + 8   // created_at
+        + 8   // total_rewards_funded_e6
+        + 8   // total_rewards_claimed_e6
+        + 39; // reserved
     
-    /// 创建新的 PredictionMarketFeeConfig
+    /// 创建新的 ReferralConfig
     pub fn new(
-        prediction_market_fee_vault: Pubkey,
-        bump: u8,
-        prediction_market_authorized_caller: Pubkey,
         authority: Pubkey,
+        vault_program: Pubkey,
+        referrer_share_bps: u16,
+        referee_discount_bps: u16,
+        bump: u8,
         created_at: i64,
     ) -> Self {
         Self {
-            discriminator: PREDICTION_MARKET_FEE_CONFIG_DISCRIMINATOR,
-            prediction_market_fee_vault,
-            bump,
-            // 默认费率
-            prediction_market_minting_fee_bps: 10,      // 0.1%
-            prediction_market_redemption_fee_bps: 10,   // 0.1%
-            prediction_market_trading_fee_taker_bps: 10, // 0.1%
-            prediction_market_trading_fee_maker_bps: 0,  // 0%
-            prediction_market_settlement_fee_bps: 0,     // 0%
-            // 默认分配比例
-            prediction_market_protocol_share_bps: 7000,      // 70%
-            prediction_market_maker_reward_share_bps: 2000,  // 20%
-            prediction_market_creator_share_bps: 1000,       // 10%
-            // 统计初始化
-            prediction_market_total_minting_fee_e6: 0,
-            prediction_market_total_redemption_fee_e6: 0,
-            prediction_market_total_trading_fee_e6: 0,
-            prediction_market_total_maker_rewards_e6: 0,
-            prediction_market_total_creator_rewards_e6: 0,
-            prediction_market_total_protocol_income_e6: 0,
-            prediction_market_authorized_caller,
+            discriminator: REFERRAL_CONFIG_DISCRIMINATOR,
             authority,
+            vault_program,
+            referrer_share_bps,
+            referee_discount_bps,
+            // 默认 VIP 加成: [0%, 2%, 5%, 10%, 15%, 20%]
+            referrer_vip_bonus_bps: [0, 200, 500, 1000, 1500, 2000],
+            referee_vip_bonus_bps: [0, 200, 500, 1000, 1500, 2000],
+            min_settlement_amount_e6: 10_000_000, // $10 最低结算
+            reward_validity_secs: 0, // 永久有效
+            total_rewards_paid_e6: 0,
+            total_discounts_given_e6: 0,
+            total_referral_links: 0,
+            total_referred_users: 0,
+            total_referred_volume_e6: 0,
             is_paused: false,
+            bump,
             last_update_ts: created_at,
-            reserved: [0u8; 64],
+            rebind_policy: ReferralRebindPolicy::Never,
+            created_at,
+            total_rewards_funded_e6: 0,
+            total_rewards_claimed_e6: 0,
+            reserved: [0u8; 39],
         }
     }
     
     /// PDA seeds
     pub fn seeds() -> Vec<Vec<u8>> {
-        vec![PREDICTION_MARKET_FEE_CONFIG_SEED.to_vec()]
+        vec![REFERRAL_CONFIG_SEED.to_vec()]
     }
     
-    /// 验证调用方是否授权
-    pub fn is_prediction_market_authorized_caller(&self, caller: &Pubkey) -> bool {
-        caller == &self.prediction_market_authorized_caller
+    /// 返佣金库 PDA seeds
+    pub fn reward_vault_seeds() -> Vec<Vec<u8>> {
+        vec![REFERRAL_REWARD_VAULT_SEED.to_vec()]
     }
     
-    /// 计算预测市场铸造费
-    pub fn calculate_prediction_market_minting_fee(&self, amount_e6: i64) -> i64 {
-        (amount_e6 as i128 * self.prediction_market_minting_fee_bps as i128 / 10000) as i64
+    /// 从已收取 (已扣除被邀请人折扣) 的手续费中划出邀请人份额
+    /// 
+    /// link 设置了自定义比例时优先使用，否则用基础比例
+    pub fn referrer_share_of_fee(&self, collected_fee_e6: i64, custom_share_bps: u16) -> i64 {
+        let share_bps = if custom_share_bps > 0 {
+            custom_share_bps
+        } else {
+            self.referrer_share_bps
+        };
+        (collected_fee_e6.max(0) as i128 * share_bps as i128 / 10000) as i64
     }
     
-    /// 计算预测市场赎回费
-    pub fn calculate_prediction_market_redemption_fee(&self, amount_e6: i64) -> i64 {
-        (amount_e6 as i128 * self.prediction_market_redemption_fee_bps as i128 / 10000) as i64
-    }
-    
-    /// 计算预测市场交易费 (Taker)
-    pub fn calculate_prediction_market_taker_fee(&self, volume_e6: i64) -> i64 {
-        (volume_e6 as i128 * self.prediction_market_trading_fee_taker_bps as i128 / 10000) as i64
-    }
-    
-    /// 计算预测市场交易费 (Maker)
-    pub fn calculate_prediction_market_maker_fee(&self, volume_e6: i64) -> i64 {
-        (volume_e6 as i128 * self.prediction_market_trading_fee_maker_bps as i128 / 10000) as i64
-    }
-    
-    /// 分配预测市场手续费
-    /// 返回 (protocol_amount, maker_reward, creator_reward)
-    pub fn distribute_prediction_market_fee(&self, fee_e6: i64) -> (i64, i64, i64) {
-        let protocol = (fee_e6 as i128 * self.prediction_market_protocol_share_bps as i128 / 10000) as i64;
-        let maker = (fee_e6 as i128 * self.prediction_market_maker_reward_share_bps as i128 / 10000) as i64;
-        let creator = (fee_e6 as i128 * self.prediction_market_creator_share_bps as i128 / 10000) as i64;
-        (protocol, maker, creator)
-    }
-    
-    /// 记录预测市场铸造费收入
-    pub fn record_prediction_market_minting_fee(&mut self, fee_e6: i64, current_ts: i64) {
-        self.prediction_market_total_minting_fee_e6 = self.prediction_market_total_minting_fee_e6.saturating_add(fee_e6);
-        let (protocol, _maker, _creator) = self.distribute_prediction_market_fee(fee_e6);
-        self.prediction_market_total_protocol_income_e6 = self.prediction_market_total_protocol_income_e6.saturating_add(protocol);
-        self.last_update_ts = current_ts;
+    /// 记录划入返佣金库的奖励 (同时计入已产生的返佣)
+    pub fn record_funded_reward(&mut self, reward_e6: i64, current_ts: i64) {
+        self.record_reward(reward_e6, 0, 0, current_ts);
+        self.total_rewards_funded_e6 = self.total_rewards_funded_e6.saturating_add(reward_e6);
     }
     
-    /// 记录预测市场赎回费收入
-    pub fn record_prediction_market_redemption_fee(&mut self, fee_e6: i64, current_ts: i64) {
-        self.prediction_market_total_redemption_fee_e6 = self.prediction_market_total_redemption_fee_e6.saturating_add(fee_e6);
-        let (protocol, _maker, _creator) = self.distribute_prediction_market_fee(fee_e6);
-        self.prediction_market_total_protocol_income_e6 = self.prediction_market_total_protocol_income_e6.saturating_add(protocol);
+    /// 记录邀请人从返佣金库领取的奖励
+    pub fn record_claimed_reward(&mut self, amount_e6: i64, current_ts: i64) {
+        self.total_rewards_claimed_e6 = self.total_rewards_claimed_e6.saturating_add(amount_e6);
         self.last_update_ts = current_ts;
     }
     
-    /// 记录预测市场交易费收入
-    pub fn record_prediction_market_trading_fee(&mut self, fee_e6: i64, current_ts: i64) {
-        self.prediction_market_total_trading_fee_e6 = self.prediction_market_total_trading_fee_e6.saturating_add(fee_e6);
-        let (protocol, _maker, _creator) = self.distribute_prediction_market_fee(fee_e6);
-        self.prediction_market_total_protocol_income_e6 = self.prediction_market_total_protocol_income_e6.saturating_add(protocol);
-        self.last_update_ts = current_ts;
+    /// 该绑定是否允许换绑到新的邀请人
+    pub fn can_rebind(&self, binding: &ReferralBinding, current_ts: i64) -> bool {
+        self.rebind_policy == ReferralRebindPolicy::AfterExpiry
+            && binding.is_expired(self.reward_validity_secs, current_ts)
     }
     
-    /// 记录预测市场做市商奖励发放
-    pub fn record_prediction_market_maker_reward(&mut self, reward_e6: i64, current_ts: i64) {
-        self.prediction_market_total_maker_rewards_e6 = self.prediction_market_total_maker_rewards_e6.saturating_add(reward_e6);
-        self.last_update_ts = current_ts;
+    /// 获取邀请人总分成比例 (基础 + VIP 加成)
+    pub fn get_referrer_share(&self, vip_level: u8) -> u16 {
+        let level = (vip_level as usize).min(VIP_LEVELS - 1);
+        self.referrer_share_bps.saturating_add(self.referrer_vip_bonus_bps[level])
     }
     
-    /// 记录预测市场创建者分成发放
-    pub fn record_prediction_market_creator_reward(&mut self, reward_e6: i64, current_ts: i64) {
-        self.prediction_market_total_creator_rewards_e6 = self.prediction_market_total_creator_rewards_e6.saturating_add(reward_e6);
-        self.last_update_ts = current_ts;
+    /// 获取被邀请人总折扣比例 (基础 + VIP 加成)
+    pub fn get_referee_discount(&self, vip_level: u8) -> u16 {
+        let level = (vip_level as usize).min(VIP_LEVELS - 1);
+        self.referee_discount_bps.saturating_add(self.referee_vip_bonus_bps[level])
     }
     
-    /// 获取预测市场总手续费收入
-    pub fn prediction_market_total_fee_income_e6(&self) -> i64 {
-        self.prediction_market_total_minting_fee_e6
-            .saturating_add(self.prediction_market_total_redemption_fee_e6)
-            .saturating_add(self.prediction_market_total_trading_fee_e6)
+    /// 计算返佣金额
+    /// 
+    /// 返回: (referrer_reward, referee_discount, platform_income)
+    pub fn calculate_rewards(
+        &self,
+        trade_fee_e6: i64,
+        referrer_vip: u8,
+        referee_vip: u8,
+    ) -> (i64, i64, i64) {
+        // 取较高的 VIP 等级
+        let effective_vip = referrer_vip.max(referee_vip);
+        
+        // 计算被邀请人折扣
+        let discount_bps = self.get_referee_discount(effective_vip);
+        let referee_discount = (trade_fee_e6 as i128 * discount_bps as i128 / 10000) as i64;
+        
+        // 实际收取的手续费
+        let actual_fee = trade_fee_e6.saturating_sub(referee_discount);
+        
+        // 计算邀请人返佣 (基于实际收取的手续费)
+        let referrer_share_bps = self.get_referrer_share(effective_vip);
+        let referrer_reward = (actual_fee as i128 * referrer_share_bps as i128 / 10000) as i64;
+        
+        // 平台收入
+        let platform_income = actual_fee.saturating_sub(referrer_reward);
+        
+        (referrer_reward, referee_discount, platform_income)
     }
     
-    /// 获取预测市场总奖励发放
-    pub fn prediction_market_total_rewards_distributed_e6(&self) -> i64 {
-        self.prediction_market_total_maker_rewards_e6.saturating_add(self.prediction_market_total_creator_rewards_e6)
+    /// 更新统计
+    pub fn record_reward(
+        &mut self,
+        referrer_reward_e6: i64,
+        referee_discount_e6: i64,
+        volume_e6: i64,
+        current_ts: i64,
+    ) {
+        self.total_rewards_paid_e6 = self.total_rewards_paid_e6.saturating_add(referrer_reward_e6);
+        self.total_discounts_given_e6 = self.total_discounts_given_e6.saturating_add(referee_discount_e6);
+        self.total_referred_volume_e6 = self.total_referred_volume_e6.saturating_add(volume_e6);
+        self.last_update_ts = current_ts;
     }
 }
 
-// =============================================================================
-// Spot Trading Fee Config (Phase 2/3)
-// =============================================================================
-
-/// Discriminator for SpotTradingFeeConfig account
-pub const SPOT_TRADING_FEE_CONFIG_DISCRIMINATOR: u64 = 0x53505F4645455F43; // "SP_FEE_C"
-
-/// Seed prefix for SpotTradingFeeConfig PDA
-pub const SPOT_TRADING_FEE_CONFIG_SEED: &[u8] = b"spot_trading_fee_config";
-
-/// Seed prefix for Spot Fee Vault PDA
-pub const SPOT_FEE_VAULT_SEED: &[u8] = b"spot_fee_vault";
+/// 绑定过期后的换绑策略
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferralRebindPolicy {
+    /// 永不换绑 (过期后仅停止返佣)
+    #[default]
+    Never = 0,
+    /// 过期后可通过 BindReferral 绑定新的邀请人
+    AfterExpiry = 1,
+}
 
-/// Spot 交易手续费配置账户
-/// 
-/// 管理 Spot 交易的手续费收取和分配
+/// 邀请链接
 /// 
-/// PDA Seeds: ["spot_trading_fee_config"]
+/// PDA Seeds: ["referral_link", referrer]
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct SpotTradingFeeConfig {
-    /// 账户类型标识符
+pub struct ReferralLink {
+    /// 账户类型标识
     pub discriminator: u64,
     
-    /// Spot 手续费资金池 (Token Account，按 quote token 收取)
-    pub spot_fee_vault: Pubkey,
-    
-    /// PDA bump
-    pub bump: u8,
-    
-    // === Spot 费率配置 (basis points, 10000 = 100%) ===
-    
-    /// Taker 交易费率 (默认 20 = 0.2%)
-    pub taker_fee_bps: u16,
-    
-    /// Maker 交易费率 (默认 5 = 0.05%)
-    pub maker_fee_bps: u16,
-    
-    // === 费用分配比例 (basis points, 总计 10000) ===
-    
-    /// 协议收入占比 (默认 6000 = 60%)
-    pub protocol_share_bps: u16,
+    /// 邀请人
+    pub referrer: Pubkey,
     
-    /// 保险基金占比 (默认 2000 = 20%)
-    pub insurance_share_bps: u16,
+    /// 邀请码 (唯一, 6-12 字符)
+    pub code: [u8; MAX_REFERRAL_CODE_LEN],
     
-    /// 返佣池占比 (默认 1500 = 15%)
-    pub referral_share_bps: u16,
+    /// 创建时间
+    pub created_at: i64,
     
-    /// 做市商激励占比 (默认 500 = 5%)
-    pub maker_reward_share_bps: u16,
+    /// 是否激活
+    pub is_active: bool,
     
-    // === 累计统计 (e6) ===
+    // === 自定义配置 (可选) ===
     
-    /// 累计 Taker 手续费
-    pub total_taker_fee_e6: i64,
+    /// 自定义邀请人分成 (0 = 使用全局配置)
+    pub custom_referrer_share_bps: u16,
     
-    /// 累计 Maker 手续费
-    pub total_maker_fee_e6: i64,
+    /// 自定义被邀请人折扣 (0 = 使用全局配置)
+    pub custom_referee_discount_bps: u16,
     
-    /// 累计协议收入
-    pub total_protocol_income_e6: i64,
+    // === 统计 ===
     
-    /// 累计保险基金转入
-    pub total_insurance_income_e6: i64,
+    /// 邀请人数
+    pub referred_count: u32,
     
-    /// 累计返佣发放
-    pub total_referral_paid_e6: i64,
+    /// 累计交易量 (被邀请人产生)
+    pub total_volume_e6: i64,
     
-    /// 累计做市商奖励
-    pub total_maker_rewards_e6: i64,
+    /// 累计获得返佣
+    pub total_rewards_earned_e6: i64,
     
-    // === 管理 ===
+    /// 累计发放折扣
+    pub total_discounts_given_e6: i64,
     
-    /// 授权调用方 (通常是 Vault Program 或 Ledger Program)
-    pub authorized_caller: Pubkey,
+    /// PDA bump
+    pub bump: u8,
     
-    /// 管理员
-    pub authority: Pubkey,
+    /// 最后更新时间 (旧账户为 0)
+    pub last_update_ts: i64,
     
-    /// 是否暂停
-    pub is_paused: bool,
+    /// 停用时间 (0 = 未停用)
+    pub deactivated_at: i64,
     
-    /// 最后更新时间
-    pub last_update_ts: i64,
+    /// 已划入返佣金库、尚未领取的奖励 (e6)
+    pub unclaimed_rewards_e6: i64,
     
     /// 预留字段
-    pub reserved: [u8; 64],
+    pub reserved: [u8; 8],
 }
 
-impl SpotTradingFeeConfig {
+impl ReferralLink {
     /// 账户大小
     pub const SIZE: usize = 8   // discriminator
-        + 32  // spot_fee_vault
+        + 32  // referrer
+        + MAX_REFERRAL_CODE_LEN  // code
+        + 8   // created_at
+        + 1   // is_active
+        + 2   // custom_referrer_share_bps
+        + 2   // custom_referee_discount_bps
+        + 4   // referred_count
+        + 8   // total_volume_e6
+        + 8   // total_rewards_earned_e6
+        + 8   // total_discounts_given_e6
         + 1   // bump
-        + 2   // taker_fee_bps
-        + 2   // maker_fee_bps
-        + 2   // protocol_share_bps
-        + 2   // insurance_share_bps
-        + 2   // referral_share_bps
-        + 2   // maker_reward_share_bps
-        + 8   // total_taker_fee_e6
-        + 8   // total_maker_fee_e6
-        + 8   // total_protocol_income_e6
-        + 8   // total_insurance_income_e6
-        + 8   // total_referral_paid_e6
-        + 8   // total_maker_rewards_e6
-        + 32  // authorized_caller
-        + 32  // authority
-        + 1   // is_paused
         + 8   // last_update_ts
-        + 64; // reserved
-
-    /// 创建新的 SpotTradingFeeConfig
+        + 8   // deactivated_at
+        + 8   // unclaimed_rewards_e6
+        + 8;  // reserved
+    
+    /// 创建新的邀请链接
     pub fn new(
-        spot_fee_vault: Pubkey,
+        referrer: Pubkey,
+        code: &[u8],
         bump: u8,
-        authorized_caller: Pubkey,
-        authority: Pubkey,
         created_at: i64,
     ) -> Self {
+        let mut code_bytes = [0u8; MAX_REFERRAL_CODE_LEN];
+        let len = code.len().min(MAX_REFERRAL_CODE_LEN);
+        code_bytes[..len].copy_from_slice(&code[..len]);
+        
         Self {
-            discriminator: SPOT_TRADING_FEE_CONFIG_DISCRIMINATOR,
-            spot_fee_vault,
+            discriminator: REFERRAL_LINK_DISCRIMINATOR,
+            referrer,
+            code: code_bytes,
+            created_at,
+            is_active: true,
+            custom_referrer_share_bps: 0,
+            custom_referee_discount_bps: 0,
+            referred_count: 0,
+            total_volume_e6: 0,
+            total_rewards_earned_e6: 0,
+            total_discounts_given_e6: 0,
             bump,
-            // 默认费率
-            taker_fee_bps: 20,      // 0.2%
-            maker_fee_bps: 5,       // 0.05%
-            // 默认分配比例
-            protocol_share_bps: 6000,     // 60%
-            insurance_share_bps: 2000,    // 20%
+            last_update_ts: created_at,
+            deactivated_at: 0,
+            unclaimed_rewards_e6: 0,
+            reserved: [0u8; 8],
+        }
+    }
+    
+    /// PDA seeds
+    pub fn seeds(referrer: &Pubkey) -> Vec<Vec<u8>> {
+        vec![
+            REFERRAL_LINK_SEED.to_vec(),
+            referrer.to_bytes().to_vec(),
+        ]
+    }
+    
+    /// 获取邀请码字符串
+    pub fn code_str(&self) -> String {
+        let end = self.code.iter().position(|&b| b == 0).unwrap_or(self.code.len());
+        String::from_utf8_lossy(&self.code[..end]).to_string()
+    }
+    
+    /// 记录新邀请
+    pub fn record_referral(&mut self, current_ts: i64) {
+        self.referred_count = self.referred_count.saturating_add(1);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 记录返佣
+    pub fn record_reward(&mut self, reward_e6: i64, discount_e6: i64, volume_e6: i64, current_ts: i64) {
+        self.total_rewards_earned_e6 = self.total_rewards_earned_e6.saturating_add(reward_e6);
+        self.total_discounts_given_e6 = self.total_discounts_given_e6.saturating_add(discount_e6);
+        self.total_volume_e6 = self.total_volume_e6.saturating_add(volume_e6);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 记录划入返佣金库的奖励 (同时计入累计返佣，可领取)
+    pub fn record_funded_reward(&mut self, reward_e6: i64, current_ts: i64) {
+        self.record_reward(reward_e6, 0, 0, current_ts);
+        self.unclaimed_rewards_e6 = self.unclaimed_rewards_e6.saturating_add(reward_e6);
+    }
+    
+    /// 领取全部未领取奖励，返回领取金额
+    pub fn claim_rewards(&mut self, current_ts: i64) -> i64 {
+        let amount_e6 = self.unclaimed_rewards_e6.max(0);
+        self.unclaimed_rewards_e6 = 0;
+        self.last_update_ts = current_ts;
+        amount_e6
+    }
+    
+    /// 停用邀请链接
+    pub fn deactivate(&mut self, current_ts: i64) {
+        self.is_active = false;
+        self.deactivated_at = current_ts;
+        self.last_update_ts = current_ts;
+    }
+}
+
+/// 邀请关系绑定
+/// 
+/// PDA Seeds: ["referral_binding", referee]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ReferralBinding {
+    /// 账户类型标识
+    pub discriminator: u64,
+    
+    /// 被邀请人
+    pub referee: Pubkey,
+    
+    /// 邀请人
+    pub referrer: Pubkey,
+    
+    /// 邀请链接
+    pub referral_link: Pubkey,
+    
+    /// 绑定时间
+    pub bound_at: i64,
+    
+    // === 统计 ===
+    
+    /// 被邀请人累计交易量 (e6)
+    pub referee_volume_e6: i64,
+    
+    /// 邀请人从此用户获得的返佣 (e6)
+    pub referrer_rewards_e6: i64,
+    
+    /// 被邀请人获得的折扣 (e6)
+    pub referee_discounts_e6: i64,
+    
+    /// 交易次数
+    pub trade_count: u64,
+    
+    /// 最后交易时间
+    pub last_trade_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 已通过 ExpireReferralBinding 标记过期
+    pub expired: bool,
+    
+    /// 标记过期的时间 (0 = 未标记)
+    pub expired_at: i64,
+    
+    /// 预留字段
+    pub reserved: [u8; 23],
+}
+
+impl ReferralBinding {
+    /// 账户大小
+    pub const SIZE: usize = 8   // discriminator
+        + 32  // referee
+        + 32  // referrer
+        + 32  // referral_link
+        + 8   // bound_at
+        + 8   // referee_volume_e6
+        + 8   // referrer_rewards_e6
+        + 8   // referee_discounts_e6
+        + 8   // trade_count
+        + 8   // last_trade_ts
+        + 1   // bump
+        + 1   // expired
+        + 8   // expired_at
+        + 23; // reserved
+    
+    /// 创建新的邀请关系
+    pub fn new(
+        referee: Pubkey,
+        referrer: Pubkey,
+        referral_link: Pubkey,
+        bump: u8,
+        bound_at: i64,
+    ) -> Self {
+        Self {
+            discriminator: REFERRAL_BINDING_DISCRIMINATOR,
+            referee,
+            referrer,
+            referral_link,
+            bound_at,
+            referee_volume_e6: 0,
+            referrer_rewards_e6: 0,
+            referee_discounts_e6: 0,
+            trade_count: 0,
+            last_trade_ts: 0,
+            bump,
+            expired: false,
+            expired_at: 0,
+            reserved: [0u8; 23],
+        }
+    }
+    
+    /// 记录手续费划转时产生 (并已入库) 的邀请人奖励
+    pub fn record_funded_reward(&mut self, reward_e6: i64, current_ts: i64) {
+        self.referrer_rewards_e6 = self.referrer_rewards_e6.saturating_add(reward_e6);
+        self.last_trade_ts = current_ts;
+    }
+    
+    /// 标记为已过期
+    pub fn mark_expired(&mut self, current_ts: i64) {
+        self.expired = true;
+        self.expired_at = current_ts;
+    }
+    
+    /// 绑定是否已过期 (validity_secs = 0 表示永久)
+    pub fn is_expired(&self, validity_secs: i64, current_ts: i64) -> bool {
+        self.expired
+            || (validity_secs > 0 && current_ts >= self.bound_at.saturating_add(validity_secs))
+    }
+    
+    /// PDA seeds
+    pub fn seeds(referee: &Pubkey) -> Vec<Vec<u8>> {
+        vec![
+            REFERRAL_BINDING_SEED.to_vec(),
+            referee.to_bytes().to_vec(),
+        ]
+    }
+    
+    /// 记录交易
+    pub fn record_trade(
+        &mut self,
+        volume_e6: i64,
+        referrer_reward_e6: i64,
+        referee_discount_e6: i64,
+        current_ts: i64,
+    ) {
+        self.referee_volume_e6 = self.referee_volume_e6.saturating_add(volume_e6);
+        self.referrer_rewards_e6 = self.referrer_rewards_e6.saturating_add(referrer_reward_e6);
+        self.referee_discounts_e6 = self.referee_discounts_e6.saturating_add(referee_discount_e6);
+        self.trade_count = self.trade_count.saturating_add(1);
+        self.last_trade_ts = current_ts;
+    }
+}
+
+// =============================================================================
+// Prediction Market Fee Config
+// =============================================================================
+
+/// 预测市场手续费配置
+/// 
+/// 管理预测市场的手续费收取和分配
+/// 
+/// PDA Seeds: ["prediction_market_fee_config"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PredictionMarketFeeConfig {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// 预测市场手续费资金池 (USDC Token Account)
+    pub prediction_market_fee_vault: Pubkey,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    // === 预测市场费率配置 (basis points, 10000 = 100%) ===
+    
+    /// 预测市场铸造费率 (默认 10 = 0.1%)
+    pub prediction_market_minting_fee_bps: u16,
+    
+    /// 预测市场赎回费率 (默认 10 = 0.1%)
+    pub prediction_market_redemption_fee_bps: u16,
+    
+    /// 预测市场 Taker 交易费率 (默认 10 = 0.1%)
+    pub prediction_market_trading_fee_taker_bps: u16,
+    
+    /// 预测市场 Maker 交易费率 (默认 0 = 0%)
+    pub prediction_market_trading_fee_maker_bps: u16,
+    
+    /// 预测市场结算费率 (默认 0 = 0%)
+    pub prediction_market_settlement_fee_bps: u16,
+    
+    // === 预测市场费用分配比例 (basis points, 总计 10000) ===
+    
+    /// 预测市场协议收入占比 (默认 7000 = 70%)
+    pub prediction_market_protocol_share_bps: u16,
+    
+    /// 预测市场做市商奖励占比 (默认 2000 = 20%)
+    pub prediction_market_maker_reward_share_bps: u16,
+    
+    /// 预测市场创建者占比 (默认 1000 = 10%)
+    pub prediction_market_creator_share_bps: u16,
+    
+    // === 预测市场累计统计 (e6) ===
+    
+    /// 预测市场累计铸造费收入
+    pub prediction_market_total_minting_fee_e6: i64,
+    
+    /// 预测市场累计赎回费收入
+    pub prediction_market_total_redemption_fee_e6: i64,
+    
+    /// 预测市场累计交易费收入
+    pub prediction_market_total_trading_fee_e6: i64,
+    
+    /// 预测市场累计做市商奖励发放
+    pub prediction_market_total_maker_rewards_e6: i64,
+    
+    /// 预测市场累计创建者分成
+    pub prediction_market_total_creator_rewards_e6: i64,
+    
+    /// 预测市场累计协议收入
+    pub prediction_market_total_protocol_income_e6: i64,
+    
+    // === 授权 ===
+    
+    /// 授权调用方 (Prediction Market Program)
+    pub prediction_market_authorized_caller: Pubkey,
+    
+    /// 管理员
+    pub authority: Pubkey,
+    
+    /// 是否暂停
+    pub is_paused: bool,
+    
+    /// 最后更新时间戳
+    pub last_update_ts: i64,
+    
+    // === 分类费率 ===
+    
+    /// 已设置覆盖费率的分类 (bit i = 分类 i)
+    pub category_override_mask: u8,
+    
+    /// 分类覆盖费率 (未设置的分类使用默认费率)
+    pub category_fees: [PMCategoryFees; MAX_PM_CATEGORIES],
+    
+    /// 已划转至 Treasury 的协议收入
+    pub prediction_market_total_protocol_swept_e6: i64,
+    
+    /// 预留字段
+    pub reserved: [u8; 7],
+}
+
+/// 预测市场分类数量上限 (体育 / 加密 / 政治 ...)
+pub const MAX_PM_CATEGORIES: usize = 8;
+
+/// 单个分类的费率覆盖 (basis points)
+///
+/// Maker 交易费不分分类, 始终使用全局配置
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PMCategoryFees {
+    /// 铸造费率
+    pub minting_fee_bps: u16,
+    
+    /// 赎回费率
+    pub redemption_fee_bps: u16,
+    
+    /// Taker 交易费率
+    pub trading_fee_taker_bps: u16,
+}
+
+impl PMCategoryFees {
+    /// Size in bytes
+    pub const SIZE: usize = 2  // minting_fee_bps
+        + 2  // redemption_fee_bps
+        + 2; // trading_fee_taker_bps
+}
+
+impl PredictionMarketFeeConfig {
+    /// 账户大小
+    pub const SIZE: usize = 8   // discriminator
+        + 32  // prediction_market_fee_vault
+        + 1   // bump
+        + 2   // prediction_market_minting_fee_bps
+        + 2   // prediction_market_redemption_fee_bps
+        + 2   // prediction_market_trading_fee_taker_bps
+        + 2   // prediction_market_trading_fee_maker_bps
+        + 2   // prediction_market_settlement_fee_bps
+        + 2   // prediction_market_protocol_share_bps
+        + 2   // prediction_market_maker_reward_share_bps
+        + 2   // prediction_market_creator_share_bps
+        + 8   // prediction_market_total_minting_fee_e6
+        + 8   // prediction_market_total_redemption_fee_e6
+        + 8   // prediction_market_total_trading_fee_e6
+        + 8   // prediction_market_total_maker_rewards_e6
+        + 8   // prediction_market_total_creator_rewards_e6
+        + 8   // prediction_market_total_protocol_income_e6
+        + 32  // prediction_market_authorized_caller
+        + 32  // authority
+        + 1   // is_paused
+        + 8   // last_update_ts
+        + 1   // category_override_mask
+        + PMCategoryFees::SIZE * MAX_PM_CATEGORIES  // category_fees
+        + 8   // prediction_market_total_protocol_swept_e6
+        + 7;  // reserved
+    
+    /// 创建新的 PredictionMarketFeeConfig
+    pub fn new(
+        prediction_market_fee_vault: Pubkey,
+        bump: u8,
+        prediction_market_authorized_caller: Pubkey,
+        authority: Pubkey,
+        created_at: i64,
+    ) -> Self {
+        Self {
+            discriminator: PREDICTION_MARKET_FEE_CONFIG_DISCRIMINATOR,
+            prediction_market_fee_vault,
+            bump,
+            // 默认费率
+            prediction_market_minting_fee_bps: 10,      // 0.1%
+            prediction_market_redemption_fee_bps: 10,   // 0.1%
+            prediction_market_trading_fee_taker_bps: 10, // 0.1%
+            prediction_market_trading_fee_maker_bps: 0,  // 0%
+            prediction_market_settlement_fee_bps: 0,     // 0%
+            // 默认分配比例
+            prediction_market_protocol_share_bps: 7000,      // 70%
+            prediction_market_maker_reward_share_bps: 2000,  // 20%
+            prediction_market_creator_share_bps: 1000,       // 10%
+            // 统计初始化
+            prediction_market_total_minting_fee_e6: 0,
+            prediction_market_total_redemption_fee_e6: 0,
+            prediction_market_total_trading_fee_e6: 0,
+            prediction_market_total_maker_rewards_e6: 0,
+            prediction_market_total_creator_rewards_e6: 0,
+            prediction_market_total_protocol_income_e6: 0,
+            prediction_market_authorized_caller,
+            authority,
+            is_paused: false,
+            last_update_ts: created_at,
+            category_override_mask: 0,
+            category_fees: [PMCategoryFees::default(); MAX_PM_CATEGORIES],
+            prediction_market_total_protocol_swept_e6: 0,
+            reserved: [0u8; 7],
+        }
+    }
+    
+    /// PDA seeds
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![PREDICTION_MARKET_FEE_CONFIG_SEED.to_vec()]
+    }
+    
+    /// 获取分类费率 (None 或未覆盖的分类返回默认费率)
+    pub fn prediction_market_category_fees(&self, category: Option<u8>) -> Result<PMCategoryFees, ProgramError> {
+        let defaults = PMCategoryFees {
+            minting_fee_bps: self.prediction_market_minting_fee_bps,
+            redemption_fee_bps: self.prediction_market_redemption_fee_bps,
+            trading_fee_taker_bps: self.prediction_market_trading_fee_taker_bps,
+        };
+        match category {
+            None => Ok(defaults),
+            Some(c) if (c as usize) >= MAX_PM_CATEGORIES => Err(crate::error::FundError::InvalidPMCategory.into()),
+            Some(c) if self.category_override_mask & (1 << c) != 0 => Ok(self.category_fees[c as usize]),
+            Some(_) => Ok(defaults),
+        }
+    }
+    
+    /// 设置 (Some) 或清除 (None) 分类覆盖费率
+    pub fn set_category_fees(&mut self, category: u8, fees: Option<PMCategoryFees>) -> Result<(), ProgramError> {
+        if (category as usize) >= MAX_PM_CATEGORIES {
+            return Err(crate::error::FundError::InvalidPMCategory.into());
+        }
+        match fees {
+            Some(fees) => {
+                self.category_fees[category as usize] = fees;
+                self.category_override_mask |= 1 << category;
+            }
+            None => {
+                self.category_fees[category as usize] = PMCategoryFees::default();
+                self.category_override_mask &= !(1 << category);
+            }
+        }
+        Ok(())
+    }
+    
+    /// 验证调用方是否授权
+    pub fn is_prediction_market_authorized_caller(&self, caller: &Pubkey) -> bool {
+        caller == &self.prediction_market_authorized_caller
+    }
+    
+    /// 计算预测市场铸造费
+    pub fn calculate_prediction_market_minting_fee(&self, amount_e6: i64) -> i64 {
+        (amount_e6 as i128 * self.prediction_market_minting_fee_bps as i128 / 10000) as i64
+    }
+    
+    /// 计算预测市场赎回费
+    pub fn calculate_prediction_market_redemption_fee(&self, amount_e6: i64) -> i64 {
+        (amount_e6 as i128 * self.prediction_market_redemption_fee_bps as i128 / 10000) as i64
+    }
+    
+    /// 计算预测市场交易费 (Taker)
+    pub fn calculate_prediction_market_taker_fee(&self, volume_e6: i64) -> i64 {
+        (volume_e6 as i128 * self.prediction_market_trading_fee_taker_bps as i128 / 10000) as i64
+    }
+    
+    /// 计算预测市场交易费 (Maker)
+    pub fn calculate_prediction_market_maker_fee(&self, volume_e6: i64) -> i64 {
+        (volume_e6 as i128 * self.prediction_market_trading_fee_maker_bps as i128 / 10000) as i64
+    }
+    
+    /// 分配预测市场手续费
+    /// 返回 (protocol_amount, maker_reward, creator_reward)
+    pub fn distribute_prediction_market_fee(&self, fee_e6: i64) -> (i64, i64, i64) {
+        let protocol = (fee_e6 as i128 * self.prediction_market_protocol_share_bps as i128 / 10000) as i64;
+        let maker = (fee_e6 as i128 * self.prediction_market_maker_reward_share_bps as i128 / 10000) as i64;
+        let creator = (fee_e6 as i128 * self.prediction_market_creator_share_bps as i128 / 10000) as i64;
+        (protocol, maker, creator)
+    }
+    
+    /// 记录预测市场铸造费收入
+    pub fn record_prediction_market_minting_fee(&mut self, fee_e6: i64, current_ts: i64) {
+        self.prediction_market_total_minting_fee_e6 = self.prediction_market_total_minting_fee_e6.saturating_add(fee_e6);
+        let (protocol, _maker, _creator) = self.distribute_prediction_market_fee(fee_e6);
+        self.prediction_market_total_protocol_income_e6 = self.prediction_market_total_protocol_income_e6.saturating_add(protocol);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 记录预测市场赎回费收入
+    pub fn record_prediction_market_redemption_fee(&mut self, fee_e6: i64, current_ts: i64) {
+        self.prediction_market_total_redemption_fee_e6 = self.prediction_market_total_redemption_fee_e6.saturating_add(fee_e6);
+        let (protocol, _maker, _creator) = self.distribute_prediction_market_fee(fee_e6);
+        self.prediction_market_total_protocol_income_e6 = self.prediction_market_total_protocol_income_e6.saturating_add(protocol);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 记录预测市场交易费收入
+    pub fn record_prediction_market_trading_fee(&mut self, fee_e6: i64, current_ts: i64) {
+        self.prediction_market_total_trading_fee_e6 = self.prediction_market_total_trading_fee_e6.saturating_add(fee_e6);
+        let (protocol, _maker, _creator) = self.distribute_prediction_market_fee(fee_e6);
+        self.prediction_market_total_protocol_income_e6 = self.prediction_market_total_protocol_income_e6.saturating_add(protocol);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 记录预测市场做市商奖励发放
+    pub fn record_prediction_market_maker_reward(&mut self, reward_e6: i64, current_ts: i64) {
+        self.prediction_market_total_maker_rewards_e6 = self.prediction_market_total_maker_rewards_e6.saturating_add(reward_e6);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 记录预测市场创建者分成发放
+    pub fn record_prediction_market_creator_reward(&mut self, reward_e6: i64, current_ts: i64) {
+        self.prediction_market_total_creator_rewards_e6 = self.prediction_market_total_creator_rewards_e6.saturating_add(reward_e6);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 获取预测市场总手续费收入
+    pub fn prediction_market_total_fee_income_e6(&self) -> i64 {
+        self.prediction_market_total_minting_fee_e6
+            .saturating_add(self.prediction_market_total_redemption_fee_e6)
+            .saturating_add(self.prediction_market_total_trading_fee_e6)
+    }
+    
+    /// 尚未划转至 Treasury 的协议收入
+    pub fn prediction_market_unswept_protocol_income_e6(&self) -> i64 {
+        self.prediction_market_total_protocol_income_e6
+            .saturating_sub(self.prediction_market_total_protocol_swept_e6)
+            .max(0)
+    }
+    
+    /// 记录协议收入划转
+    pub fn record_prediction_market_protocol_swept(&mut self, amount_e6: i64, current_ts: i64) {
+        self.prediction_market_total_protocol_swept_e6 = self.prediction_market_total_protocol_swept_e6.saturating_add(amount_e6);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 获取预测市场总奖励发放
+    pub fn prediction_market_total_rewards_distributed_e6(&self) -> i64 {
+        self.prediction_market_total_maker_rewards_e6.saturating_add(self.prediction_market_total_creator_rewards_e6)
+    }
+}
+
+// =============================================================================
+// Spot Trading Fee Config (Phase 2/3)
+// =============================================================================
+
+/// Discriminator for SpotTradingFeeConfig account
+pub const SPOT_TRADING_FEE_CONFIG_DISCRIMINATOR: u64 = 0x53505F4645455F43; // "SP_FEE_C"
+
+/// Seed prefix for SpotTradingFeeConfig PDA
+pub const SPOT_TRADING_FEE_CONFIG_SEED: &[u8] = b"spot_trading_fee_config";
+
+/// Seed prefix for Spot Fee Vault PDA
+pub const SPOT_FEE_VAULT_SEED: &[u8] = b"spot_fee_vault";
+
+/// Spot 交易手续费配置账户
+/// 
+/// 管理 Spot 交易的手续费收取和分配
+/// 
+/// PDA Seeds: ["spot_trading_fee_config"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SpotTradingFeeConfig {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// Spot 手续费资金池 (Token Account，按 quote token 收取)
+    pub spot_fee_vault: Pubkey,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    // === Spot 费率配置 (basis points, 10000 = 100%) ===
+    
+    /// Taker 交易费率 (默认 20 = 0.2%)
+    pub taker_fee_bps: u16,
+    
+    /// Maker 交易费率 (默认 5 = 0.05%)
+    pub maker_fee_bps: u16,
+    
+    // === 费用分配比例 (basis points, 总计 10000) ===
+    
+    /// 协议收入占比 (默认 6000 = 60%)
+    pub protocol_share_bps: u16,
+    
+    /// 保险基金占比 (默认 2000 = 20%)
+    pub insurance_share_bps: u16,
+    
+    /// 返佣池占比 (默认 1500 = 15%)
+    pub referral_share_bps: u16,
+    
+    /// 做市商激励占比 (默认 500 = 5%)
+    pub maker_reward_share_bps: u16,
+    
+    // === 累计统计 (e6) ===
+    
+    /// 累计 Taker 手续费
+    pub total_taker_fee_e6: i64,
+    
+    /// 累计 Maker 手续费
+    pub total_maker_fee_e6: i64,
+    
+    /// 累计协议收入
+    pub total_protocol_income_e6: i64,
+    
+    /// 累计保险基金转入
+    pub total_insurance_income_e6: i64,
+    
+    /// 累计返佣发放
+    pub total_referral_paid_e6: i64,
+    
+    /// 累计做市商奖励
+    pub total_maker_rewards_e6: i64,
+    
+    // === 管理 ===
+    
+    /// 授权调用方 (通常是 Vault Program 或 Ledger Program)
+    pub authorized_caller: Pubkey,
+    
+    /// 管理员
+    pub authority: Pubkey,
+    
+    /// 是否暂停
+    pub is_paused: bool,
+    
+    /// 最后更新时间
+    pub last_update_ts: i64,
+    
+    /// 创建时间 (旧账户为 0)
+    pub created_at: i64,
+    
+    /// 预留字段
+    pub reserved: [u8; 56],
+}
+
+impl SpotTradingFeeConfig {
+    /// 账户大小
+    pub const SIZE: usize = 8   // discriminator
+        + 32  // spot_fee_vault
+        + 1   // bump
+        + 2   // taker_fee_bps
+        + 2   // maker_fee_bps
+        + 2   // protocol_share_bps
+        + 2   // insurance_share_bps
+        + 2   // referral_share_bps
+        + 2   // maker_reward_share_bps
+        + 8   // total_taker_fee_e6
+        + 8   // total_maker_fee_e6
+        + 8   // total_protocol_income_e6
+        + 8   // total_insurance_income_e6
+        + 8   // total_referral_paid_e6
+        + 8   // total_maker_rewards_e6
+        + 32  // authorized_caller
+        + 32  // authority
+        + 1   // is_paused
+        + 8   // last_update_ts
+        + 8   // created_at
+        + 56; // reserved
+
+    /// 创建新的 SpotTradingFeeConfig
+    pub fn new(
+        spot_fee_vault: Pubkey,
+        bump: u8,
+        authorized_caller: Pubkey,
+        authority: Pubkey,
+        created_at: i64,
+    ) -> Self {
+        Self {
+            discriminator: SPOT_TRADING_FEE_CONFIG_DISCRIMINATOR,
+            spot_fee_vault,
+            bump,
+            // 默认费率
+            taker_fee_bps: 20,      // 0.2%
+            maker_fee_bps: 5,       // 0.05%
+            // 默认分配比例
+            protocol_share_bps: 6000,     // 60%
+            insurance_share_bps: 2000,    // 20%
             referral_share_bps: 1500,     // 15%
             maker_reward_share_bps: 500,  // 5%
             // 统计初始化
@@ -2034,656 +3900,8636 @@ impl SpotTradingFeeConfig {
             total_maker_rewards_e6: 0,
             authorized_caller,
             authority,
-            is_paused: false,
-            last_update_ts: created_at,
-            reserved: [0u8; 64],
+            is_paused: false,
+            last_update_ts: created_at,
+            created_at,
+            reserved: [0u8; 56],
+        }
+    }
+
+    /// PDA seeds
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![SPOT_TRADING_FEE_CONFIG_SEED.to_vec()]
+    }
+
+    /// 验证调用方是否授权
+    pub fn is_authorized_caller(&self, caller: &Pubkey) -> bool {
+        caller == &self.authorized_caller
+    }
+
+    /// 计算 Taker 手续费
+    pub fn calculate_taker_fee(&self, volume_e6: i64) -> i64 {
+        (volume_e6 as i128 * self.taker_fee_bps as i128 / 10000) as i64
+    }
+
+    /// 计算 Maker 手续费
+    pub fn calculate_maker_fee(&self, volume_e6: i64) -> i64 {
+        (volume_e6 as i128 * self.maker_fee_bps as i128 / 10000) as i64
+    }
+
+    /// 分配手续费
+    /// 返回 (protocol, insurance, referral, maker_reward)
+    pub fn distribute_fee(&self, fee_e6: i64) -> (i64, i64, i64, i64) {
+        let protocol = (fee_e6 as i128 * self.protocol_share_bps as i128 / 10000) as i64;
+        let insurance = (fee_e6 as i128 * self.insurance_share_bps as i128 / 10000) as i64;
+        let referral = (fee_e6 as i128 * self.referral_share_bps as i128 / 10000) as i64;
+        let maker = (fee_e6 as i128 * self.maker_reward_share_bps as i128 / 10000) as i64;
+        (protocol, insurance, referral, maker)
+    }
+
+    /// 记录 Taker 手续费
+    pub fn record_taker_fee(&mut self, fee_e6: i64, current_ts: i64) {
+        self.total_taker_fee_e6 = self.total_taker_fee_e6.saturating_add(fee_e6);
+        let (protocol, insurance, _referral, _maker) = self.distribute_fee(fee_e6);
+        self.total_protocol_income_e6 = self.total_protocol_income_e6.saturating_add(protocol);
+        self.total_insurance_income_e6 = self.total_insurance_income_e6.saturating_add(insurance);
+        self.last_update_ts = current_ts;
+    }
+
+    /// 记录 Maker 手续费
+    pub fn record_maker_fee(&mut self, fee_e6: i64, current_ts: i64) {
+        self.total_maker_fee_e6 = self.total_maker_fee_e6.saturating_add(fee_e6);
+        let (protocol, insurance, _referral, _maker) = self.distribute_fee(fee_e6);
+        self.total_protocol_income_e6 = self.total_protocol_income_e6.saturating_add(protocol);
+        self.total_insurance_income_e6 = self.total_insurance_income_e6.saturating_add(insurance);
+        self.last_update_ts = current_ts;
+    }
+
+    /// 记录返佣发放
+    pub fn record_referral_paid(&mut self, amount_e6: i64, current_ts: i64) {
+        self.total_referral_paid_e6 = self.total_referral_paid_e6.saturating_add(amount_e6);
+        self.last_update_ts = current_ts;
+    }
+
+    /// 记录做市商奖励
+    pub fn record_maker_reward(&mut self, reward_e6: i64, current_ts: i64) {
+        self.total_maker_rewards_e6 = self.total_maker_rewards_e6.saturating_add(reward_e6);
+        self.last_update_ts = current_ts;
+    }
+
+    /// 获取总手续费收入
+    pub fn total_fee_income_e6(&self) -> i64 {
+        self.total_taker_fee_e6.saturating_add(self.total_maker_fee_e6)
+    }
+}
+
+// =============================================================================
+// Deposit Receipts
+// =============================================================================
+
+/// Discriminator for DepositReceipt account
+pub const DEPOSIT_RECEIPT_DISCRIMINATOR: u64 = 0x4445505F52435054; // "DEP_RCPT"
+
+/// Seed prefix for DepositReceipt PDA
+pub const DEPOSIT_RECEIPT_SEED: &[u8] = b"deposit_receipt";
+
+/// Seed prefix for the receipt NFT mint PDA
+pub const RECEIPT_MINT_SEED: &[u8] = b"receipt_mint";
+
+/// Seed prefix for the receipt NFT token account PDA
+pub const RECEIPT_TOKEN_SEED: &[u8] = b"receipt_token";
+
+/// Proof-of-investment receipt for an LP position
+///
+/// Backed by a 0-decimal, supply-1 SPL mint (a standard NFT) whose mint
+/// authority is the Fund PDA. The NFT is held in a program-derived token
+/// account owned by the investor and burned on full redemption.
+///
+/// PDA Seeds: ["deposit_receipt", lp_position]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DepositReceipt {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund the deposit was made into
+    pub fund: Pubkey,
+    
+    /// Investor wallet
+    pub investor: Pubkey,
+    
+    /// Receipt NFT mint
+    pub receipt_mint: Pubkey,
+    
+    /// Amount of the latest deposit (e6)
+    pub amount_e6: i64,
+    
+    /// NAV at the latest deposit (e6)
+    pub nav_e6: i64,
+    
+    /// Timestamp of the latest deposit
+    pub deposited_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl DepositReceipt {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // investor
+        + 32  // receipt_mint
+        + 8   // amount_e6
+        + 8   // nav_e6
+        + 8   // deposited_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new DepositReceipt
+    pub fn new(
+        fund: Pubkey,
+        investor: Pubkey,
+        receipt_mint: Pubkey,
+        amount_e6: i64,
+        nav_e6: i64,
+        deposited_at: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: DEPOSIT_RECEIPT_DISCRIMINATOR,
+            fund,
+            investor,
+            receipt_mint,
+            amount_e6,
+            nav_e6,
+            deposited_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for DepositReceipt
+    pub fn seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![DEPOSIT_RECEIPT_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    /// PDA seeds for the receipt NFT mint
+    pub fn mint_seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![RECEIPT_MINT_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    /// PDA seeds for the receipt NFT token account
+    pub fn token_seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![RECEIPT_TOKEN_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    /// Record the latest deposit on the receipt
+    pub fn record_deposit(&mut self, amount_e6: i64, nav_e6: i64, deposited_at: i64) {
+        self.amount_e6 = amount_e6;
+        self.nav_e6 = nav_e6;
+        self.deposited_at = deposited_at;
+    }
+}
+
+// =============================================================================
+// Manager Profile
+// =============================================================================
+
+/// Discriminator for ManagerProfile account
+pub const MANAGER_PROFILE_DISCRIMINATOR: u64 = 0x4D47525F50524F46; // "MGR_PROF"
+
+/// Seed prefix for ManagerProfile PDA
+pub const MANAGER_PROFILE_SEED: &[u8] = b"manager_profile";
+
+/// Per-manager bookkeeping used to rate-limit fund creation
+///
+/// PDA Seeds: ["manager_profile", manager]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ManagerProfile {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Manager wallet
+    pub manager: Pubkey,
+    
+    /// Funds ever created by this manager
+    pub funds_created: u64,
+    
+    /// Funds currently active (created - closed)
+    pub active_funds: u64,
+    
+    /// Total creation fees paid (USDC e6)
+    pub total_creation_fees_paid: u64,
+    
+    /// First fund creation timestamp
+    pub created_at: i64,
+    
+    /// Last update timestamp
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl ManagerProfile {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // manager
+        + 8   // funds_created
+        + 8   // active_funds
+        + 8   // total_creation_fees_paid
+        + 8   // created_at
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new ManagerProfile
+    pub fn new(manager: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: MANAGER_PROFILE_DISCRIMINATOR,
+            manager,
+            funds_created: 0,
+            active_funds: 0,
+            total_creation_fees_paid: 0,
+            created_at,
+            last_update_ts: created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for ManagerProfile
+    pub fn seeds(manager: &Pubkey) -> Vec<Vec<u8>> {
+        vec![MANAGER_PROFILE_SEED.to_vec(), manager.to_bytes().to_vec()]
+    }
+    
+    /// Check whether another fund may be created under `max_funds` (0 = unlimited)
+    pub fn can_create_fund(&self, max_funds: u16) -> bool {
+        max_funds == 0 || self.active_funds < max_funds as u64
+    }
+    
+    /// Record a fund creation
+    pub fn record_fund_created(&mut self, fee_e6: u64, current_ts: i64) {
+        self.funds_created = self.funds_created.saturating_add(1);
+        self.active_funds = self.active_funds.saturating_add(1);
+        self.total_creation_fees_paid = self.total_creation_fees_paid.saturating_add(fee_e6);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// Record a fund closure
+    pub fn record_fund_closed(&mut self, current_ts: i64) {
+        self.active_funds = self.active_funds.saturating_sub(1);
+        self.last_update_ts = current_ts;
+    }
+}
+
+// =============================================================================
+// Rent Reserve
+// =============================================================================
+
+/// Discriminator for RentReserve account
+pub const RENT_RESERVE_DISCRIMINATOR: u64 = 0x52454E545F525356; // "RENT_RSV"
+
+/// Seed prefix for RentReserve PDA
+pub const RENT_RESERVE_SEED: &[u8] = b"rent_reserve";
+
+/// Lamport reserve that sponsors rent for PDAs created in user flows
+/// (LPPosition, ReferralBinding), so gasless users need no SOL
+///
+/// The account's own lamports above its rent-exempt minimum are the spendable
+/// reserve; top it up with a plain SOL transfer.
+///
+/// PDA Seeds: ["rent_reserve"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RentReserve {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Sponsoring enabled?
+    pub is_enabled: bool,
+    
+    /// Total lamports paid out as rent
+    pub total_sponsored_lamports: u64,
+    
+    /// Total lamports reimbursed by users/relayers
+    pub total_reimbursed_lamports: u64,
+    
+    /// Number of accounts sponsored
+    pub accounts_sponsored: u64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Last update timestamp
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl RentReserve {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 1   // is_enabled
+        + 8   // total_sponsored_lamports
+        + 8   // total_reimbursed_lamports
+        + 8   // accounts_sponsored
+        + 8   // created_at
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new RentReserve (disabled until the admin turns it on)
+    pub fn new(bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: RENT_RESERVE_DISCRIMINATOR,
+            is_enabled: false,
+            total_sponsored_lamports: 0,
+            total_reimbursed_lamports: 0,
+            accounts_sponsored: 0,
+            created_at,
+            last_update_ts: created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for RentReserve
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![RENT_RESERVE_SEED.to_vec()]
+    }
+    
+    /// Lamports that can be spent without dropping below rent exemption
+    pub fn available_lamports(account_lamports: u64, rent_exempt_minimum: u64) -> u64 {
+        account_lamports.saturating_sub(rent_exempt_minimum)
+    }
+    
+    /// Sponsored rent not yet reimbursed
+    pub fn outstanding_lamports(&self) -> u64 {
+        self.total_sponsored_lamports.saturating_sub(self.total_reimbursed_lamports)
+    }
+    
+    /// Record rent paid for a new account
+    pub fn record_sponsorship(&mut self, lamports: u64, current_ts: i64) {
+        self.total_sponsored_lamports = self.total_sponsored_lamports.saturating_add(lamports);
+        self.accounts_sponsored = self.accounts_sponsored.saturating_add(1);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// Record a reimbursement paid back into the reserve
+    pub fn record_reimbursement(&mut self, lamports: u64, current_ts: i64) {
+        self.total_reimbursed_lamports = self.total_reimbursed_lamports.saturating_add(lamports);
+        self.last_update_ts = current_ts;
+    }
+}
+
+// =============================================================================
+// Pending Reinitialize
+// =============================================================================
+
+/// Discriminator for PendingReinitialize account
+pub const PENDING_REINIT_DISCRIMINATOR: u64 = 0x50454E445F52494E; // "PEND_RIN"
+
+/// Seed prefix for PendingReinitialize PDA
+pub const PENDING_REINIT_SEED: &[u8] = b"pending_reinit";
+
+/// A scheduled FundConfig reinitialization waiting out its timelock
+///
+/// PDA Seeds: ["pending_reinit"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PendingReinitialize {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Admin that scheduled (and must execute) the reinitialization
+    pub authority: Pubkey,
+    
+    /// Vault Program ID to write
+    pub vault_program: Pubkey,
+    
+    /// Ledger Program ID to write
+    pub ledger_program: Pubkey,
+    
+    /// Fund counter to restore if the old config cannot be decoded
+    pub total_funds: u64,
+    
+    /// Scheduling timestamp
+    pub scheduled_at: i64,
+    
+    /// Earliest execution timestamp
+    pub executable_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl PendingReinitialize {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // authority
+        + 32  // vault_program
+        + 32  // ledger_program
+        + 8   // total_funds
+        + 8   // scheduled_at
+        + 8   // executable_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Delay between scheduling and executing a reinitialization (48 hours)
+    pub const TIMELOCK_SECS: i64 = 48 * 60 * 60;
+    
+    /// Create a new PendingReinitialize
+    pub fn new(
+        authority: Pubkey,
+        vault_program: Pubkey,
+        ledger_program: Pubkey,
+        total_funds: u64,
+        bump: u8,
+        scheduled_at: i64,
+    ) -> Self {
+        Self {
+            discriminator: PENDING_REINIT_DISCRIMINATOR,
+            authority,
+            vault_program,
+            ledger_program,
+            total_funds,
+            scheduled_at,
+            executable_at: scheduled_at.saturating_add(Self::TIMELOCK_SECS),
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for PendingReinitialize
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![PENDING_REINIT_SEED.to_vec()]
+    }
+    
+    /// Has the timelock elapsed?
+    pub fn is_executable(&self, current_ts: i64) -> bool {
+        current_ts >= self.executable_at
+    }
+}
+
+// =============================================================================
+// Pending Insurance Config Update
+// =============================================================================
+
+/// Discriminator for PendingInsuranceConfigUpdate account
+pub const PENDING_INSURANCE_UPDATE_DISCRIMINATOR: u64 = 0x50454E445F494E53; // "PEND_INS"
+
+/// Seed prefix for PendingInsuranceConfigUpdate PDA
+pub const PENDING_INSURANCE_UPDATE_SEED: &[u8] = b"pending_insurance_update";
+
+/// `update_mask` bit: adl_trigger_threshold_e6
+pub const INSURANCE_UPDATE_ADL_THRESHOLD: u8 = 1 << 0;
+
+/// `update_mask` bit: withdrawal_delay_secs
+pub const INSURANCE_UPDATE_WITHDRAWAL_DELAY: u8 = 1 << 1;
+
+/// `update_mask` bit: authorized_caller
+pub const INSURANCE_UPDATE_AUTHORIZED_CALLER: u8 = 1 << 2;
+
+/// Maximum insurance LP withdrawal delay (30 days)
+pub const MAX_INSURANCE_WITHDRAWAL_DELAY_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// A scheduled InsuranceFundConfig update waiting out its timelock
+///
+/// PDA Seeds: ["pending_insurance_update"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PendingInsuranceConfigUpdate {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Admin that scheduled (and must execute) the update
+    pub authority: Pubkey,
+    
+    /// INSURANCE_UPDATE_* bits of the fields to write
+    pub update_mask: u8,
+    
+    /// New ADL balance threshold (e6)
+    pub adl_trigger_threshold_e6: i64,
+    
+    /// New LP withdrawal delay (seconds)
+    pub withdrawal_delay_secs: i64,
+    
+    /// New authorized caller for the ingress instructions
+    pub authorized_caller: Pubkey,
+    
+    /// Scheduling timestamp
+    pub scheduled_at: i64,
+    
+    /// Earliest execution timestamp
+    pub executable_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl PendingInsuranceConfigUpdate {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // authority
+        + 1   // update_mask
+        + 8   // adl_trigger_threshold_e6
+        + 8   // withdrawal_delay_secs
+        + 32  // authorized_caller
+        + 8   // scheduled_at
+        + 8   // executable_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Delay between scheduling and applying an update (48 hours)
+    pub const TIMELOCK_SECS: i64 = 48 * 60 * 60;
+    
+    /// Create a new PendingInsuranceConfigUpdate
+    ///
+    /// `None` fields are left unchanged on execution.
+    pub fn new(
+        authority: Pubkey,
+        adl_trigger_threshold_e6: Option<i64>,
+        withdrawal_delay_secs: Option<i64>,
+        authorized_caller: Option<Pubkey>,
+        bump: u8,
+        scheduled_at: i64,
+    ) -> Self {
+        let mut update_mask = 0;
+        if adl_trigger_threshold_e6.is_some() {
+            update_mask |= INSURANCE_UPDATE_ADL_THRESHOLD;
+        }
+        if withdrawal_delay_secs.is_some() {
+            update_mask |= INSURANCE_UPDATE_WITHDRAWAL_DELAY;
+        }
+        if authorized_caller.is_some() {
+            update_mask |= INSURANCE_UPDATE_AUTHORIZED_CALLER;
+        }
+        Self {
+            discriminator: PENDING_INSURANCE_UPDATE_DISCRIMINATOR,
+            authority,
+            update_mask,
+            adl_trigger_threshold_e6: adl_trigger_threshold_e6.unwrap_or_default(),
+            withdrawal_delay_secs: withdrawal_delay_secs.unwrap_or_default(),
+            authorized_caller: authorized_caller.unwrap_or_default(),
+            scheduled_at,
+            executable_at: scheduled_at.saturating_add(Self::TIMELOCK_SECS),
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for PendingInsuranceConfigUpdate
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![PENDING_INSURANCE_UPDATE_SEED.to_vec()]
+    }
+    
+    /// Has the timelock elapsed?
+    pub fn is_executable(&self, current_ts: i64) -> bool {
+        current_ts >= self.executable_at
+    }
+    
+    /// Write the scheduled fields into `config`
+    pub fn apply(&self, config: &mut InsuranceFundConfig) {
+        if self.update_mask & INSURANCE_UPDATE_ADL_THRESHOLD != 0 {
+            config.adl_trigger_threshold_e6 = self.adl_trigger_threshold_e6;
+        }
+        if self.update_mask & INSURANCE_UPDATE_WITHDRAWAL_DELAY != 0 {
+            config.withdrawal_delay_secs = self.withdrawal_delay_secs;
+        }
+        if self.update_mask & INSURANCE_UPDATE_AUTHORIZED_CALLER != 0 {
+            config.authorized_caller = self.authorized_caller;
+        }
+    }
+}
+
+// =============================================================================
+// Insurance Daily Stats
+// =============================================================================
+
+/// Discriminator for InsuranceDailyStats account
+pub const INSURANCE_DAILY_STATS_DISCRIMINATOR: u64 = 0x494E535F4441595F; // "INS_DAY_"
+
+/// Seed prefix for InsuranceDailyStats PDA
+pub const INSURANCE_DAILY_STATS_SEED: &[u8] = b"insurance_daily_stats";
+
+/// Insurance Fund 每日收支统计
+/// 
+/// 由小时快照 crank (UpdateHourlySnapshot) 写入，每个 UTC 日一个账户。
+/// 收入按来源拆分，增量取自 InsuranceFundConfig 累计值与其每日基线之差。
+/// 
+/// PDA Seeds: ["insurance_daily_stats", day.to_le_bytes()]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InsuranceDailyStats {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// 日序号 (unix_ts / 86400)
+    pub day: i64,
+    
+    // === 收入 (按来源) ===
+    
+    /// 当日清算收入 (e6, V1 含交易手续费)
+    pub liquidation_income_e6: i64,
+    
+    /// 当日 ADL 盈余 (e6)
+    pub adl_profit_e6: i64,
+    
+    // === 支出 ===
+    
+    /// 当日穿仓支出 (e6)
+    pub shortfall_payout_e6: i64,
+    
+    /// 当日净收入 (收入 - 支出, e6)
+    pub net_e6: i64,
+    
+    // === 余额 ===
+    
+    /// 当日首个快照前的余额 (e6)
+    pub opening_balance_e6: i64,
+    
+    /// 当日最近一次快照的余额 (e6)
+    pub ending_balance_e6: i64,
+    
+    /// 当日写入的快照次数
+    pub snapshot_count: u32,
+    
+    /// 最后更新时间戳
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段 (扩展用)
+    pub reserved: [u8; 32],
+}
+
+impl InsuranceDailyStats {
+    /// 一天的秒数
+    pub const SECONDS_PER_DAY: i64 = 86_400;
+    
+    /// 账户大小 (bytes)
+    pub const SIZE: usize = 8   // discriminator
+        + 8   // day
+        + 8   // liquidation_income_e6
+        + 8   // adl_profit_e6
+        + 8   // shortfall_payout_e6
+        + 8   // net_e6
+        + 8   // opening_balance_e6
+        + 8   // ending_balance_e6
+        + 4   // snapshot_count
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// 创建新的 InsuranceDailyStats
+    pub fn new(day: i64, opening_balance_e6: i64, bump: u8) -> Self {
+        Self {
+            discriminator: INSURANCE_DAILY_STATS_DISCRIMINATOR,
+            day,
+            liquidation_income_e6: 0,
+            adl_profit_e6: 0,
+            shortfall_payout_e6: 0,
+            net_e6: 0,
+            opening_balance_e6,
+            ending_balance_e6: opening_balance_e6,
+            snapshot_count: 0,
+            last_update_ts: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// 时间戳所在的日序号
+    pub fn day_index(ts: i64) -> i64 {
+        ts.div_euclid(Self::SECONDS_PER_DAY)
+    }
+    
+    /// PDA seeds for InsuranceDailyStats
+    pub fn seeds(day: i64) -> Vec<Vec<u8>> {
+        vec![
+            INSURANCE_DAILY_STATS_SEED.to_vec(),
+            day.to_le_bytes().to_vec(),
+        ]
+    }
+    
+    /// 记录一次快照的增量与余额
+    pub fn record_snapshot(
+        &mut self,
+        liquidation_income_e6: i64,
+        adl_profit_e6: i64,
+        shortfall_payout_e6: i64,
+        balance_e6: i64,
+        current_ts: i64,
+    ) {
+        self.liquidation_income_e6 = self.liquidation_income_e6.saturating_add(liquidation_income_e6);
+        self.adl_profit_e6 = self.adl_profit_e6.saturating_add(adl_profit_e6);
+        self.shortfall_payout_e6 = self.shortfall_payout_e6.saturating_add(shortfall_payout_e6);
+        self.net_e6 = self.total_income_e6().saturating_sub(self.shortfall_payout_e6);
+        self.ending_balance_e6 = balance_e6;
+        self.snapshot_count = self.snapshot_count.saturating_add(1);
+        self.last_update_ts = current_ts;
+    }
+    
+    /// 当日总收入
+    pub fn total_income_e6(&self) -> i64 {
+        self.liquidation_income_e6.saturating_add(self.adl_profit_e6)
+    }
+}
+
+// =============================================================================
+// Relayer Nonce
+// =============================================================================
+
+/// Discriminator for RelayerNonce account
+pub const RELAYER_NONCE_DISCRIMINATOR: u64 = 0x524C595F4E4F4E43; // "RLY_NONC"
+
+/// Seed prefix for RelayerNonce PDA
+pub const RELAYER_NONCE_SEED: &[u8] = b"relayer_nonce";
+
+/// Per-user replay protection for Relayer* instructions
+///
+/// Every relayer-submitted operation carries a nonce that must be strictly
+/// greater than the last one accepted for the same user, so a resubmitted
+/// deposit/redeem is rejected.
+///
+/// PDA Seeds: ["relayer_nonce", user]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayerNonce {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// User wallet the nonce belongs to
+    pub user: Pubkey,
+    
+    /// Last accepted nonce (0 = none yet)
+    pub last_nonce: u64,
+    
+    /// Number of operations accepted
+    pub operation_count: u64,
+    
+    /// Last update timestamp
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl RelayerNonce {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // user
+        + 8   // last_nonce
+        + 8   // operation_count
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new RelayerNonce
+    pub fn new(user: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: RELAYER_NONCE_DISCRIMINATOR,
+            user,
+            last_nonce: 0,
+            operation_count: 0,
+            last_update_ts: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for RelayerNonce
+    pub fn seeds(user: &Pubkey) -> Vec<Vec<u8>> {
+        vec![RELAYER_NONCE_SEED.to_vec(), user.to_bytes().to_vec()]
+    }
+    
+    /// Accept `nonce` if it is strictly greater than the last one
+    ///
+    /// Returns false (and leaves the state untouched) on a replay.
+    pub fn consume(&mut self, nonce: u64, current_ts: i64) -> bool {
+        if nonce <= self.last_nonce {
+            return false;
+        }
+        self.last_nonce = nonce;
+        self.operation_count = self.operation_count.saturating_add(1);
+        self.last_update_ts = current_ts;
+        true
+    }
+}
+
+// =============================================================================
+// Relayer Stats
+// =============================================================================
+
+/// Discriminator for RelayerStats account
+pub const RELAYER_STATS_DISCRIMINATOR: u64 = 0x524C595F53544154; // "RLY_STAT"
+
+/// Seed prefix for RelayerStats PDA
+pub const RELAYER_STATS_SEED: &[u8] = b"relayer_stats";
+
+/// Per-relayer activity counters for monitoring and comparing relayers
+///
+/// Updated by Relayer* instructions whenever the relayer's PDA is passed.
+///
+/// PDA Seeds: ["relayer_stats", relayer]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RelayerStats {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Relayer wallet
+    pub relayer: Pubkey,
+    
+    /// Relayer* operations accepted
+    pub tx_count: u64,
+    
+    /// Deposit and payment amounts relayed (e6)
+    pub total_volume_e6: u64,
+    
+    /// Requests refused by the relayer limits
+    pub rejected_over_limit_count: u64,
+    
+    /// Last accepted or rejected operation (0 = none yet)
+    pub last_activity_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl RelayerStats {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // relayer
+        + 8   // tx_count
+        + 8   // total_volume_e6
+        + 8   // rejected_over_limit_count
+        + 8   // last_activity_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new RelayerStats
+    pub fn new(relayer: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: RELAYER_STATS_DISCRIMINATOR,
+            relayer,
+            tx_count: 0,
+            total_volume_e6: 0,
+            rejected_over_limit_count: 0,
+            last_activity_ts: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for RelayerStats
+    pub fn seeds(relayer: &Pubkey) -> Vec<Vec<u8>> {
+        vec![RELAYER_STATS_SEED.to_vec(), relayer.to_bytes().to_vec()]
+    }
+    
+    /// Count an accepted operation moving `volume_e6`
+    pub fn record_operation(&mut self, volume_e6: u64, current_ts: i64) {
+        self.tx_count = self.tx_count.saturating_add(1);
+        self.total_volume_e6 = self.total_volume_e6.saturating_add(volume_e6);
+        self.last_activity_ts = current_ts;
+    }
+    
+    /// Count a request refused by the relayer limits
+    pub fn record_rejection(&mut self, current_ts: i64) {
+        self.rejected_over_limit_count = self.rejected_over_limit_count.saturating_add(1);
+        self.last_activity_ts = current_ts;
+    }
+    
+    /// Rejected share of all requests (bps)
+    pub fn rejection_rate_bps(&self) -> u64 {
+        let total = self.tx_count.saturating_add(self.rejected_over_limit_count);
+        if total == 0 {
+            return 0;
+        }
+        (self.rejected_over_limit_count as u128 * BPS_DENOMINATOR as u128 / total as u128) as u64
+    }
+}
+
+// =============================================================================
+// Payout Allow-list
+// =============================================================================
+
+/// Discriminator for PayoutAllowList account
+pub const PAYOUT_ALLOWLIST_DISCRIMINATOR: u64 = 0x5041594F55545F41; // "PAYOUT_A"
+
+/// Seed prefix for PayoutAllowList PDA
+pub const PAYOUT_ALLOWLIST_SEED: &[u8] = b"payout_allowlist";
+
+/// Maximum registered payout addresses per LP position
+pub const MAX_PAYOUT_ADDRESSES: usize = 4;
+
+/// Addresses an LP position may redeem to
+///
+/// Created by the first RegisterPayoutAddress, which also opts the position
+/// in (`LPPosition::payout_allowlist_enabled`). A newly registered address
+/// only becomes usable after `ACTIVATION_DELAY_SECS`, giving the LP time to
+/// react if their key is compromised. Removal takes effect immediately.
+///
+/// PDA Seeds: ["payout_allowlist", lp_position]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PayoutAllowList {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// LP position this list belongs to
+    pub lp_position: Pubkey,
+    
+    /// Investor wallet
+    pub investor: Pubkey,
+    
+    /// Registered payout addresses (wallet or token account)
+    pub addresses: [Pubkey; MAX_PAYOUT_ADDRESSES],
+    
+    /// Timestamp from which each address may receive redemptions
+    pub active_after: [i64; MAX_PAYOUT_ADDRESSES],
+    
+    /// Number of registered addresses
+    pub count: u8,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Last update timestamp
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl PayoutAllowList {
+    /// Delay before a newly registered address becomes active (24 hours)
+    pub const ACTIVATION_DELAY_SECS: i64 = 24 * 60 * 60;
+    
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // lp_position
+        + 32  // investor
+        + 32 * MAX_PAYOUT_ADDRESSES // addresses
+        + 8 * MAX_PAYOUT_ADDRESSES  // active_after
+        + 1   // count
+        + 8   // created_at
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an empty PayoutAllowList
+    pub fn new(lp_position: Pubkey, investor: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: PAYOUT_ALLOWLIST_DISCRIMINATOR,
+            lp_position,
+            investor,
+            addresses: [Pubkey::default(); MAX_PAYOUT_ADDRESSES],
+            active_after: [0; MAX_PAYOUT_ADDRESSES],
+            count: 0,
+            created_at,
+            last_update_ts: created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for PayoutAllowList
+    pub fn seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![PAYOUT_ALLOWLIST_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    fn position_of(&self, address: &Pubkey) -> Option<usize> {
+        self.addresses[..self.count as usize].iter().position(|a| a == address)
+    }
+    
+    /// Register an address; it becomes active after the activation delay
+    ///
+    /// Returns the activation timestamp. Re-registering an existing address
+    /// keeps its original activation time.
+    pub fn register(&mut self, address: Pubkey, current_ts: i64) -> Option<i64> {
+        if let Some(i) = self.position_of(&address) {
+            return Some(self.active_after[i]);
+        }
+        let i = self.count as usize;
+        if i >= MAX_PAYOUT_ADDRESSES {
+            return None;
+        }
+        let active_after = current_ts.saturating_add(Self::ACTIVATION_DELAY_SECS);
+        self.addresses[i] = address;
+        self.active_after[i] = active_after;
+        self.count += 1;
+        self.last_update_ts = current_ts;
+        Some(active_after)
+    }
+    
+    /// Remove an address (effective immediately)
+    pub fn remove(&mut self, address: &Pubkey, current_ts: i64) -> bool {
+        let Some(i) = self.position_of(address) else {
+            return false;
+        };
+        let last = self.count as usize - 1;
+        self.addresses[i] = self.addresses[last];
+        self.active_after[i] = self.active_after[last];
+        self.addresses[last] = Pubkey::default();
+        self.active_after[last] = 0;
+        self.count -= 1;
+        self.last_update_ts = current_ts;
+        true
+    }
+    
+    /// Whether `address` is registered and past its activation delay
+    pub fn is_allowed(&self, address: &Pubkey, current_ts: i64) -> bool {
+        self.position_of(address)
+            .map(|i| current_ts >= self.active_after[i])
+            .unwrap_or(false)
+    }
+}
+
+// =============================================================================
+// LP Index
+// =============================================================================
+
+/// Discriminator for LPIndexPage account
+pub const LP_INDEX_PAGE_DISCRIMINATOR: u64 = 0x4C505F494E444558; // "LP_INDEX"
+
+/// Seed prefix for LPIndexPage PDA
+pub const LP_INDEX_PAGE_SEED: &[u8] = b"lp_index";
+
+/// LP positions per LPIndexPage
+pub const LP_INDEX_PAGE_CAPACITY: usize = 64;
+
+/// One page of a fund's LP enumeration
+///
+/// New positions are appended to the fund's last page (a new page is
+/// created once it is full); positions are swap-removed when they become
+/// empty. Walking pages `0..Fund::lp_index_page_count` visits every active
+/// LP exactly once.
+///
+/// PDA Seeds: ["lp_index", fund, page.to_le_bytes()]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LPIndexPage {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund this page belongs to
+    pub fund: Pubkey,
+    
+    /// Page number
+    pub page: u16,
+    
+    /// Number of used entries
+    pub count: u16,
+    
+    /// LPPosition addresses (first `count` are valid)
+    pub entries: [Pubkey; LP_INDEX_PAGE_CAPACITY],
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl LPIndexPage {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 2   // page
+        + 2   // count
+        + 32 * LP_INDEX_PAGE_CAPACITY // entries
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create an empty page
+    pub fn new(fund: Pubkey, page: u16, bump: u8) -> Self {
+        Self {
+            discriminator: LP_INDEX_PAGE_DISCRIMINATOR,
+            fund,
+            page,
+            count: 0,
+            entries: [Pubkey::default(); LP_INDEX_PAGE_CAPACITY],
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for LPIndexPage
+    pub fn seeds(fund: &Pubkey, page: u16) -> Vec<Vec<u8>> {
+        vec![
+            LP_INDEX_PAGE_SEED.to_vec(),
+            fund.to_bytes().to_vec(),
+            page.to_le_bytes().to_vec(),
+        ]
+    }
+    
+    /// Valid entries
+    pub fn positions(&self) -> &[Pubkey] {
+        &self.entries[..self.count as usize]
+    }
+    
+    /// Check if the page has no free slot
+    pub fn is_full(&self) -> bool {
+        self.count as usize >= LP_INDEX_PAGE_CAPACITY
+    }
+    
+    /// Append a position; returns false if the page is full
+    pub fn push(&mut self, lp_position: Pubkey) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.entries[self.count as usize] = lp_position;
+        self.count += 1;
+        true
+    }
+    
+    /// Swap-remove a position; returns false if it is not on this page
+    pub fn remove(&mut self, lp_position: &Pubkey) -> bool {
+        let Some(i) = self.positions().iter().position(|p| p == lp_position) else {
+            return false;
+        };
+        let last = self.count as usize - 1;
+        self.entries[i] = self.entries[last];
+        self.entries[last] = Pubkey::default();
+        self.count -= 1;
+        true
+    }
+}
+
+// =============================================================================
+// LP Distribution
+// =============================================================================
+
+/// Discriminator for LPDistribution account
+pub const LP_DISTRIBUTION_DISCRIMINATOR: u64 = 0x4C505F4449535452; // "LP_DISTR"
+
+/// Seed prefix for LPDistribution PDA
+pub const LP_DISTRIBUTION_SEED: &[u8] = b"lp_distribution";
+
+/// Progress of a fund's pro-rata USDC distribution to its LPs
+///
+/// One account per fund, reused across rounds. A round pays a fixed amount
+/// per share to every LP by walking the fund's LPIndexPage list; the cursor
+/// (`next_page`, `next_index`) lets a round span many transactions.
+///
+/// PDA Seeds: ["lp_distribution", fund]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LPDistribution {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund being distributed
+    pub fund: Pubkey,
+    
+    /// Number of rounds started so far
+    pub round: u32,
+    
+    /// USDC (e6) paid per whole share (1_000_000 share units)
+    pub amount_per_share_e6: u64,
+    
+    /// Fund total shares when the round started
+    pub total_shares_snapshot: u64,
+    
+    /// Maximum USDC (e6) the round can pay out
+    pub budget_e6: u64,
+    
+    /// USDC (e6) paid so far in this round
+    pub paid_e6: u64,
+    
+    /// LP positions paid so far in this round
+    pub paid_count: u32,
+    
+    /// Cursor: LPIndexPage to process next
+    pub next_page: u16,
+    
+    /// Cursor: entry within `next_page` to process next
+    pub next_index: u16,
+    
+    /// A round is in progress
+    pub is_active: bool,
+    
+    /// Round start timestamp
+    pub started_at: i64,
+    
+    /// Round completion timestamp (0 while active)
+    pub completed_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl LPDistribution {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 4   // round
+        + 8   // amount_per_share_e6
+        + 8   // total_shares_snapshot
+        + 8   // budget_e6
+        + 8   // paid_e6
+        + 4   // paid_count
+        + 2   // next_page
+        + 2   // next_index
+        + 1   // is_active
+        + 8   // started_at
+        + 8   // completed_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an idle distribution account
+    pub fn new(fund: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: LP_DISTRIBUTION_DISCRIMINATOR,
+            fund,
+            round: 0,
+            amount_per_share_e6: 0,
+            total_shares_snapshot: 0,
+            budget_e6: 0,
+            paid_e6: 0,
+            paid_count: 0,
+            next_page: 0,
+            next_index: 0,
+            is_active: false,
+            started_at: 0,
+            completed_at: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for LPDistribution
+    pub fn seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![LP_DISTRIBUTION_SEED.to_vec(), fund.to_bytes().to_vec()]
+    }
+    
+    /// USDC (e6) owed to a holder of `shares`
+    pub fn payout_for(amount_per_share_e6: u64, shares: u64) -> Result<u64, ProgramError> {
+        let amount = (shares as u128)
+            .checked_mul(amount_per_share_e6 as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / 1_000_000;
+        u64::try_from(amount).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+    
+    /// Start a new round; returns the budget
+    pub fn start(
+        &mut self,
+        amount_per_share_e6: u64,
+        total_shares: u64,
+        current_ts: i64,
+    ) -> Result<u64, ProgramError> {
+        self.budget_e6 = Self::payout_for(amount_per_share_e6, total_shares)?;
+        self.round = self.round.saturating_add(1);
+        self.amount_per_share_e6 = amount_per_share_e6;
+        self.total_shares_snapshot = total_shares;
+        self.paid_e6 = 0;
+        self.paid_count = 0;
+        self.next_page = 0;
+        self.next_index = 0;
+        self.is_active = true;
+        self.started_at = current_ts;
+        self.completed_at = 0;
+        Ok(self.budget_e6)
+    }
+    
+    /// Record a payout to one LP; never exceeds the round budget
+    pub fn record_payout(&mut self, amount_e6: u64) -> Result<(), ProgramError> {
+        let paid = self.paid_e6.checked_add(amount_e6).ok_or(ProgramError::ArithmeticOverflow)?;
+        if paid > self.budget_e6 {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        self.paid_e6 = paid;
+        self.paid_count = self.paid_count.saturating_add(1);
+        Ok(())
+    }
+    
+    /// Close the round
+    pub fn finish(&mut self, current_ts: i64) {
+        self.is_active = false;
+        self.completed_at = current_ts;
+    }
+}
+
+// =============================================================================
+// Governance
+// =============================================================================
+
+/// Discriminator for Proposal account
+pub const PROPOSAL_DISCRIMINATOR: u64 = 0x50524F504F53414C; // "PROPOSAL"
+
+/// Discriminator for VoteRecord account
+pub const VOTE_RECORD_DISCRIMINATOR: u64 = 0x564F54455F524543; // "VOTE_REC"
+
+/// Seed prefix for Proposal PDA
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Seed prefix for VoteRecord PDA
+pub const VOTE_RECORD_SEED: &[u8] = b"vote_record";
+
+/// Share of total shares an LP needs to open a proposal (5%)
+pub const PROPOSAL_LP_THRESHOLD_BPS: u64 = 500;
+
+/// Share of total shares that must vote for a result to count (20%)
+pub const PROPOSAL_QUORUM_BPS: u64 = 2_000;
+
+/// Minimum voting period (1 day)
+pub const MIN_VOTING_PERIOD_SECS: i64 = 24 * 3600;
+
+/// Maximum voting period (14 days)
+pub const MAX_VOTING_PERIOD_SECS: i64 = 14 * 24 * 3600;
+
+/// What a proposal asks LPs to approve
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProposalKind {
+    /// Change management/performance fees (applied when passed)
+    #[default]
+    FeeChange = 0,
+    /// Wind the fund down: closed to new deposits (applied when passed)
+    WindDown = 1,
+    /// Approve a new manager (recorded only; the Fund PDA is derived from the manager)
+    ReplaceManager = 2,
+}
+
+/// Lifecycle of a proposal
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProposalStatus {
+    /// Voting open (or ended but not finalized)
+    #[default]
+    Active = 0,
+    /// Quorum reached and yes > no
+    Passed = 1,
+    /// Quorum missed or yes <= no
+    Rejected = 2,
+}
+
+/// A share-weighted governance proposal for one fund
+///
+/// Voting weight is `LPPosition.shares` as of `snapshot_ts`: only positions
+/// not modified since the snapshot may vote, so shares bought (or moved)
+/// after the proposal was created carry no weight.
+///
+/// PDA Seeds: ["proposal", fund, proposal_id.to_le_bytes()]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Proposal {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund the proposal applies to
+    pub fund: Pubkey,
+    
+    /// Proposer-chosen id (unique per fund)
+    pub proposal_id: u64,
+    
+    /// Manager or LP that opened the proposal
+    pub proposer: Pubkey,
+    
+    /// Proposal type
+    pub kind: ProposalKind,
+    
+    /// FeeChange: new management fee (bps)
+    pub new_management_fee_bps: u32,
+    
+    /// FeeChange: new performance fee (bps)
+    pub new_performance_fee_bps: u32,
+    
+    /// ReplaceManager: proposed manager
+    pub new_manager: Pubkey,
+    
+    /// Share snapshot timestamp (creation time)
+    pub snapshot_ts: i64,
+    
+    /// Voting closes at this timestamp
+    pub voting_ends_ts: i64,
+    
+    /// Fund total shares at the snapshot
+    pub total_shares_snapshot: u64,
+    
+    /// Shares voting yes
+    pub yes_weight: u64,
+    
+    /// Shares voting no
+    pub no_weight: u64,
+    
+    /// Number of votes cast
+    pub voter_count: u32,
+    
+    /// Current status
+    pub status: ProposalStatus,
+    
+    /// Finalization timestamp (0 while active)
+    pub finalized_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl Proposal {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 8   // proposal_id
+        + 32  // proposer
+        + 1   // kind
+        + 4   // new_management_fee_bps
+        + 4   // new_performance_fee_bps
+        + 32  // new_manager
+        + 8   // snapshot_ts
+        + 8   // voting_ends_ts
+        + 8   // total_shares_snapshot
+        + 8   // yes_weight
+        + 8   // no_weight
+        + 4   // voter_count
+        + 1   // status
+        + 8   // finalized_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new active proposal
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        fund: Pubkey,
+        proposal_id: u64,
+        proposer: Pubkey,
+        kind: ProposalKind,
+        total_shares_snapshot: u64,
+        snapshot_ts: i64,
+        voting_period_secs: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: PROPOSAL_DISCRIMINATOR,
+            fund,
+            proposal_id,
+            proposer,
+            kind,
+            new_management_fee_bps: 0,
+            new_performance_fee_bps: 0,
+            new_manager: Pubkey::default(),
+            snapshot_ts,
+            voting_ends_ts: snapshot_ts.saturating_add(voting_period_secs),
+            total_shares_snapshot,
+            yes_weight: 0,
+            no_weight: 0,
+            voter_count: 0,
+            status: ProposalStatus::Active,
+            finalized_at: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for Proposal
+    pub fn seeds(fund: &Pubkey, proposal_id: u64) -> Vec<Vec<u8>> {
+        vec![
+            PROPOSAL_SEED.to_vec(),
+            fund.to_bytes().to_vec(),
+            proposal_id.to_le_bytes().to_vec(),
+        ]
+    }
+    
+    /// Check if an LP holding `shares` may open a proposal
+    pub fn meets_lp_threshold(shares: u64, total_shares: u64) -> bool {
+        total_shares > 0
+            && (shares as u128) * 10_000 >= (total_shares as u128) * PROPOSAL_LP_THRESHOLD_BPS as u128
+    }
+    
+    /// Check if votes are still accepted
+    pub fn is_voting_open(&self, current_ts: i64) -> bool {
+        self.status == ProposalStatus::Active && current_ts < self.voting_ends_ts
+    }
+    
+    /// Snapshot voting weight of a position (0 if it changed after the snapshot)
+    pub fn vote_weight(&self, position: &LPPosition) -> u64 {
+        if position.fund != self.fund || position.last_update_ts > self.snapshot_ts {
+            return 0;
+        }
+        position.shares
+    }
+    
+    /// Tally a vote
+    pub fn record_vote(&mut self, support: bool, weight: u64) {
+        if support {
+            self.yes_weight = self.yes_weight.saturating_add(weight);
+        } else {
+            self.no_weight = self.no_weight.saturating_add(weight);
+        }
+        self.voter_count = self.voter_count.saturating_add(1);
+    }
+    
+    /// Check if turnout reached quorum
+    pub fn quorum_reached(&self) -> bool {
+        let turnout = self.yes_weight as u128 + self.no_weight as u128;
+        turnout * 10_000 >= (self.total_shares_snapshot as u128) * PROPOSAL_QUORUM_BPS as u128
+    }
+    
+    /// Close voting and settle the outcome
+    pub fn finalize(&mut self, current_ts: i64) -> ProposalStatus {
+        self.status = if self.quorum_reached() && self.yes_weight > self.no_weight {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        };
+        self.finalized_at = current_ts;
+        self.status
+    }
+}
+
+/// One LP position's vote on a proposal (prevents double voting)
+///
+/// PDA Seeds: ["vote_record", proposal, lp_position]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VoteRecord {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Proposal voted on
+    pub proposal: Pubkey,
+    
+    /// LP position that voted
+    pub lp_position: Pubkey,
+    
+    /// true = yes, false = no
+    pub support: bool,
+    
+    /// Shares counted
+    pub weight: u64,
+    
+    /// Vote timestamp
+    pub voted_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl VoteRecord {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // proposal
+        + 32  // lp_position
+        + 1   // support
+        + 8   // weight
+        + 8   // voted_at
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create a new vote record
+    pub fn new(proposal: Pubkey, lp_position: Pubkey, support: bool, weight: u64, voted_at: i64, bump: u8) -> Self {
+        Self {
+            discriminator: VOTE_RECORD_DISCRIMINATOR,
+            proposal,
+            lp_position,
+            support,
+            weight,
+            voted_at,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for VoteRecord
+    pub fn seeds(proposal: &Pubkey, lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![
+            VOTE_RECORD_SEED.to_vec(),
+            proposal.to_bytes().to_vec(),
+            lp_position.to_bytes().to_vec(),
+        ]
+    }
+}
+
+// =============================================================================
+// Share Price Feed
+// =============================================================================
+
+/// Discriminator for SharePriceFeed account
+pub const SHARE_PRICE_FEED_DISCRIMINATOR: u64 = 0x50524943455F4644; // "PRICE_FD"
+
+/// Seed prefix for SharePriceFeed PDA
+pub const SHARE_PRICE_FEED_SEED: &[u8] = b"share_price_feed";
+
+/// Price exponent (NAV is published in e6)
+pub const SHARE_PRICE_FEED_EXPONENT: i32 = -6;
+
+/// Oracle-style feed of a fund's share price (NAV per share)
+///
+/// Laid out like common push oracles (price / conf / expo / publish_time)
+/// so lending protocols can take fund shares as collateral. `conf` is the
+/// absolute NAV move since the previous publish.
+///
+/// PDA Seeds: ["share_price_feed", fund]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SharePriceFeed {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund being priced
+    pub fund: Pubkey,
+    
+    /// Share token mint the price applies to
+    pub share_mint: Pubkey,
+    
+    /// Price of one share in USDC, scaled by 10^expo
+    pub price: i64,
+    
+    /// Confidence interval (same scale as price)
+    pub conf: u64,
+    
+    /// Price exponent
+    pub expo: i32,
+    
+    /// Publish timestamp
+    pub publish_ts: i64,
+    
+    /// Previous price
+    pub prev_price: i64,
+    
+    /// Previous publish timestamp
+    pub prev_publish_ts: i64,
+    
+    /// Fund AUM (e6) at publish
+    pub aum_e6: i64,
+    
+    /// Fund total shares at publish
+    pub total_shares: u64,
+    
+    /// Number of publishes
+    pub publish_count: u64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl SharePriceFeed {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // share_mint
+        + 8   // price
+        + 8   // conf
+        + 4   // expo
+        + 8   // publish_ts
+        + 8   // prev_price
+        + 8   // prev_publish_ts
+        + 8   // aum_e6
+        + 8   // total_shares
+        + 8   // publish_count
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an unpublished feed
+    pub fn new(fund: Pubkey, share_mint: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: SHARE_PRICE_FEED_DISCRIMINATOR,
+            fund,
+            share_mint,
+            price: 0,
+            conf: 0,
+            expo: SHARE_PRICE_FEED_EXPONENT,
+            publish_ts: 0,
+            prev_price: 0,
+            prev_publish_ts: 0,
+            aum_e6: 0,
+            total_shares: 0,
+            publish_count: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for SharePriceFeed
+    pub fn seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SHARE_PRICE_FEED_SEED.to_vec(), fund.to_bytes().to_vec()]
+    }
+    
+    /// Publish a new NAV
+    pub fn publish(&mut self, nav_e6: i64, aum_e6: i64, total_shares: u64, current_ts: i64) {
+        if self.publish_count > 0 {
+            self.prev_price = self.price;
+            self.prev_publish_ts = self.publish_ts;
+            self.conf = nav_e6.abs_diff(self.price);
+        }
+        self.price = nav_e6;
+        self.publish_ts = current_ts;
+        self.aum_e6 = aum_e6;
+        self.total_shares = total_shares;
+        self.publish_count = self.publish_count.saturating_add(1);
+    }
+    
+    /// (price, conf) if published within `max_age_secs` of `current_ts`
+    pub fn get_price_no_older_than(&self, current_ts: i64, max_age_secs: i64) -> Option<(i64, u64)> {
+        if self.publish_count == 0 || current_ts.saturating_sub(self.publish_ts) > max_age_secs {
+            return None;
+        }
+        Some((self.price, self.conf))
+    }
+}
+
+// =============================================================================
+// Trade Intent
+// =============================================================================
+
+/// Discriminator for TradeIntent account
+pub const TRADE_INTENT_DISCRIMINATOR: u64 = 0x54525F494E54454E; // "TR_INTEN"
+
+/// Seed prefix for TradeIntent PDA
+pub const TRADE_INTENT_SEED: &[u8] = b"trade_intent";
+
+/// A manager-signed limit order executed later by a whitelisted keeper
+///
+/// Executable once, before `expiry_ts`, at a price no worse than
+/// `limit_price_e6` (≤ for longs, ≥ for shorts).
+///
+/// PDA Seeds: ["trade_intent", fund, intent_id.to_le_bytes()]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct TradeIntent {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund trading
+    pub fund: Pubkey,
+    
+    /// Manager-chosen id (unique per fund)
+    pub intent_id: u64,
+    
+    /// Market index
+    pub market_index: u8,
+    
+    /// Side (0 = Long, 1 = Short)
+    pub side: u8,
+    
+    /// Position size (e6)
+    pub size_e6: u64,
+    
+    /// Worst acceptable execution price (e6)
+    pub limit_price_e6: u64,
+    
+    /// Leverage (1-100)
+    pub leverage: u8,
+    
+    /// Intent expires at this timestamp
+    pub expiry_ts: i64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Already executed
+    pub is_executed: bool,
+    
+    /// Execution price (e6, 0 until executed)
+    pub executed_price_e6: u64,
+    
+    /// Execution timestamp (0 until executed)
+    pub executed_at: i64,
+    
+    /// Keeper that executed the intent
+    pub executed_by: Pubkey,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl TradeIntent {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 8   // intent_id
+        + 1   // market_index
+        + 1   // side
+        + 8   // size_e6
+        + 8   // limit_price_e6
+        + 1   // leverage
+        + 8   // expiry_ts
+        + 8   // created_at
+        + 1   // is_executed
+        + 8   // executed_price_e6
+        + 8   // executed_at
+        + 32  // executed_by
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new pending intent
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        fund: Pubkey,
+        intent_id: u64,
+        market_index: u8,
+        side: u8,
+        size_e6: u64,
+        limit_price_e6: u64,
+        leverage: u8,
+        expiry_ts: i64,
+        created_at: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: TRADE_INTENT_DISCRIMINATOR,
+            fund,
+            intent_id,
+            market_index,
+            side,
+            size_e6,
+            limit_price_e6,
+            leverage,
+            expiry_ts,
+            created_at,
+            is_executed: false,
+            executed_price_e6: 0,
+            executed_at: 0,
+            executed_by: Pubkey::default(),
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for TradeIntent
+    pub fn seeds(fund: &Pubkey, intent_id: u64) -> Vec<Vec<u8>> {
+        vec![
+            TRADE_INTENT_SEED.to_vec(),
+            fund.to_bytes().to_vec(),
+            intent_id.to_le_bytes().to_vec(),
+        ]
+    }
+    
+    /// Check if the intent has expired
+    pub fn is_expired(&self, current_ts: i64) -> bool {
+        current_ts >= self.expiry_ts
+    }
+    
+    /// Check if `price_e6` is at or better than the limit
+    pub fn price_satisfies_limit(&self, price_e6: u64) -> bool {
+        match self.side {
+            0 => price_e6 <= self.limit_price_e6,
+            _ => price_e6 >= self.limit_price_e6,
+        }
+    }
+    
+    /// Mark the intent executed
+    pub fn mark_executed(&mut self, keeper: Pubkey, price_e6: u64, current_ts: i64) {
+        self.is_executed = true;
+        self.executed_price_e6 = price_e6;
+        self.executed_at = current_ts;
+        self.executed_by = keeper;
+    }
+    
+    /// Can ExpireStaleEntries close it? (expired or executed, plus a grace period for indexers)
+    pub fn is_stale(&self, current_ts: i64) -> bool {
+        let settled_at = if self.is_executed { self.executed_at } else { self.expiry_ts };
+        current_ts >= settled_at.saturating_add(STALE_ENTRY_GRACE_SECS)
+    }
+}
+
+/// Time an expired / executed entry stays readable before anyone may close it
+pub const STALE_ENTRY_GRACE_SECS: i64 = 7 * 86400;
+
+/// Share of a closed entry's rent paid to the ExpireStaleEntries keeper (10%)
+pub const STALE_ENTRY_KEEPER_TIP_BPS: u64 = 1_000;
+
+/// Keeper tip out of a stale entry's reclaimed rent (the rest goes to its creator)
+pub fn stale_entry_keeper_tip(lamports: u64) -> u64 {
+    (lamports as u128 * STALE_ENTRY_KEEPER_TIP_BPS as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+// =============================================================================
+// Deposit Attribution
+// =============================================================================
+
+/// Discriminator for CampaignStats account
+pub const CAMPAIGN_STATS_DISCRIMINATOR: u64 = 0x43414D505F535441; // "CAMP_STA"
+
+/// Discriminator for DepositAttribution account
+pub const DEPOSIT_ATTRIBUTION_DISCRIMINATOR: u64 = 0x4445505F41545452; // "DEP_ATTR"
+
+/// Seed prefix for CampaignStats PDA
+pub const CAMPAIGN_STATS_SEED: &[u8] = b"campaign_stats";
+
+/// Seed prefix for DepositAttribution PDA
+pub const DEPOSIT_ATTRIBUTION_SEED: &[u8] = b"deposit_attribution";
+
+/// Per-campaign aggregate of attributed deposits (across all funds)
+///
+/// PDA Seeds: ["campaign_stats", campaign_id]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CampaignStats {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Marketing campaign identifier
+    pub campaign_id: u64,
+    
+    /// Number of attributed deposits (also the next DepositAttribution sequence)
+    pub deposit_count: u64,
+    
+    /// Total attributed deposits (e6)
+    pub total_deposits_e6: i64,
+    
+    /// Total shares minted by attributed deposits
+    pub total_shares: u64,
+    
+    /// First attributed deposit timestamp
+    pub first_deposit_ts: i64,
+    
+    /// Last attributed deposit timestamp
+    pub last_deposit_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl CampaignStats {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 8   // campaign_id
+        + 8   // deposit_count
+        + 8   // total_deposits_e6
+        + 8   // total_shares
+        + 8   // first_deposit_ts
+        + 8   // last_deposit_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create empty campaign stats
+    pub fn new(campaign_id: u64, bump: u8) -> Self {
+        Self {
+            discriminator: CAMPAIGN_STATS_DISCRIMINATOR,
+            campaign_id,
+            deposit_count: 0,
+            total_deposits_e6: 0,
+            total_shares: 0,
+            first_deposit_ts: 0,
+            last_deposit_ts: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for CampaignStats
+    pub fn seeds(campaign_id: u64) -> Vec<Vec<u8>> {
+        vec![CAMPAIGN_STATS_SEED.to_vec(), campaign_id.to_le_bytes().to_vec()]
+    }
+    
+    /// Aggregate a deposit, returning its attribution sequence number
+    pub fn record_deposit(&mut self, amount_e6: i64, shares: u64, current_ts: i64) -> Result<u64, ProgramError> {
+        let seq = self.deposit_count;
+        self.total_deposits_e6 = safe_add_i64(self.total_deposits_e6, amount_e6)?;
+        self.total_shares = self.total_shares.saturating_add(shares);
+        if seq == 0 {
+            self.first_deposit_ts = current_ts;
+        }
+        self.last_deposit_ts = current_ts;
+        self.deposit_count = seq.saturating_add(1);
+        Ok(seq)
+    }
+}
+
+/// Append-only record of one deposit attributed to a campaign
+///
+/// Written once and never modified or closed.
+///
+/// PDA Seeds: ["deposit_attribution", campaign_id, seq]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DepositAttribution {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Marketing campaign identifier
+    pub campaign_id: u64,
+    
+    /// Sequence number within the campaign
+    pub seq: u64,
+    
+    /// Fund deposited into
+    pub fund: Pubkey,
+    
+    /// Depositing LP
+    pub investor: Pubkey,
+    
+    /// Deposit amount (e6)
+    pub amount_e6: i64,
+    
+    /// Shares minted
+    pub shares: u64,
+    
+    /// NAV at deposit (e6)
+    pub nav_e6: i64,
+    
+    /// Deposit timestamp
+    pub deposited_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl DepositAttribution {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 8   // campaign_id
+        + 8   // seq
+        + 32  // fund
+        + 32  // investor
+        + 8   // amount_e6
+        + 8   // shares
+        + 8   // nav_e6
+        + 8   // deposited_at
+        + 1;  // bump
+    
+    /// PDA seeds for DepositAttribution
+    pub fn seeds(campaign_id: u64, seq: u64) -> Vec<Vec<u8>> {
+        vec![
+            DEPOSIT_ATTRIBUTION_SEED.to_vec(),
+            campaign_id.to_le_bytes().to_vec(),
+            seq.to_le_bytes().to_vec(),
+        ]
+    }
+}
+
+// =============================================================================
+// Prediction Market Maker Volume
+// =============================================================================
+
+/// Discriminator for PMMakerEpoch account
+pub const PM_MAKER_EPOCH_DISCRIMINATOR: u64 = 0x504D5F4D4B5F4550; // "PM_MK_EP"
+
+/// Discriminator for PMMakerVolume account
+pub const PM_MAKER_VOLUME_DISCRIMINATOR: u64 = 0x504D5F4D4B5F564F; // "PM_MK_VO"
+
+/// Seed prefix for PMMakerEpoch PDA
+pub const PM_MAKER_EPOCH_SEED: &[u8] = b"pm_maker_epoch";
+
+/// Seed prefix for PMMakerVolume PDA
+pub const PM_MAKER_VOLUME_SEED: &[u8] = b"pm_maker_volume";
+
+/// Maker reward epoch length (7 days)
+pub const PM_MAKER_EPOCH_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// 做市商奖励周期: 记录周期内的做市总量与奖励池
+///
+/// 奖励池 = 周期内交易费的做市商分成 (prediction_market_maker_reward_share_bps)
+///
+/// PDA Seeds: ["pm_maker_epoch", epoch]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PMMakerEpoch {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// 周期编号 (timestamp / PM_MAKER_EPOCH_SECS)
+    pub epoch: u64,
+    
+    /// 周期内做市总量 (e6)
+    pub total_maker_volume_e6: i64,
+    
+    /// 周期内累计的做市商奖励池 (e6)
+    pub reward_pool_e6: i64,
+    
+    /// 已发放奖励 (e6)
+    pub distributed_e6: i64,
+    
+    /// 做市商数量
+    pub maker_count: u32,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段
+    pub reserved: [u8; 32],
+}
+
+impl PMMakerEpoch {
+    /// 账户大小
+    pub const SIZE: usize = 8  // discriminator
+        + 8   // epoch
+        + 8   // total_maker_volume_e6
+        + 8   // reward_pool_e6
+        + 8   // distributed_e6
+        + 4   // maker_count
+        + 1   // bump
+        + 32; // reserved
+    
+    /// 创建新的周期
+    pub fn new(epoch: u64, bump: u8) -> Self {
+        Self {
+            discriminator: PM_MAKER_EPOCH_DISCRIMINATOR,
+            epoch,
+            total_maker_volume_e6: 0,
+            reward_pool_e6: 0,
+            distributed_e6: 0,
+            maker_count: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// 时间戳所在周期
+    pub fn epoch_at(ts: i64) -> u64 {
+        (ts.max(0) / PM_MAKER_EPOCH_SECS) as u64
+    }
+    
+    /// PDA seeds
+    pub fn seeds(epoch: u64) -> Vec<Vec<u8>> {
+        vec![PM_MAKER_EPOCH_SEED.to_vec(), epoch.to_le_bytes().to_vec()]
+    }
+    
+    /// 周期是否已结束
+    pub fn is_ended(&self, current_ts: i64) -> bool {
+        Self::epoch_at(current_ts) > self.epoch
+    }
+    
+    /// 按做市量比例计算奖励
+    pub fn reward_for(&self, maker_volume_e6: i64) -> i64 {
+        if self.total_maker_volume_e6 <= 0 || maker_volume_e6 <= 0 {
+            return 0;
+        }
+        (self.reward_pool_e6.max(0) as i128 * maker_volume_e6 as i128 / self.total_maker_volume_e6 as i128) as i64
+    }
+}
+
+/// 单个做市商在一个周期内的做市量
+///
+/// PDA Seeds: ["pm_maker_volume", maker, epoch]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PMMakerVolume {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// 做市商
+    pub maker: Pubkey,
+    
+    /// 周期编号
+    pub epoch: u64,
+    
+    /// 做市量 (e6)
+    pub volume_e6: i64,
+    
+    /// 已发放奖励 (e6)
+    pub reward_paid_e6: i64,
+    
+    /// 奖励是否已发放
+    pub is_rewarded: bool,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段
+    pub reserved: [u8; 16],
+}
+
+impl PMMakerVolume {
+    /// 账户大小
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // maker
+        + 8   // epoch
+        + 8   // volume_e6
+        + 8   // reward_paid_e6
+        + 1   // is_rewarded
+        + 1   // bump
+        + 16; // reserved
+    
+    /// 创建新的做市量记录
+    pub fn new(maker: Pubkey, epoch: u64, bump: u8) -> Self {
+        Self {
+            discriminator: PM_MAKER_VOLUME_DISCRIMINATOR,
+            maker,
+            epoch,
+            volume_e6: 0,
+            reward_paid_e6: 0,
+            is_rewarded: false,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds
+    pub fn seeds(maker: &Pubkey, epoch: u64) -> Vec<Vec<u8>> {
+        vec![
+            PM_MAKER_VOLUME_SEED.to_vec(),
+            maker.to_bytes().to_vec(),
+            epoch.to_le_bytes().to_vec(),
+        ]
+    }
+}
+
+// =============================================================================
+// Treasury
+// =============================================================================
+
+/// Discriminator for Treasury account
+pub const TREASURY_DISCRIMINATOR: u64 = 0x5452454153555259; // "TREASURY"
+
+/// Seed prefix for Treasury PDA
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Number of tracked treasury revenue sources
+pub const TREASURY_SOURCE_COUNT: usize = 5;
+
+/// 协议收入来源
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreasurySource {
+    /// 预测市场协议分成
+    PredictionMarketProtocol,
+    /// Square 平台分成
+    SquarePlatform,
+    /// 业绩费抽成 (预留)
+    PerformanceFee,
+    /// 保险基金盈余 (预留)
+    InsuranceSurplus,
+    /// 基金创建费
+    FundCreationFee,
+}
+
+/// 协议金库: 所有协议收入的统一归集账户
+///
+/// 金库 vault 的 token authority 为 Treasury PDA, 提取需经过 timelock
+///
+/// PDA Seeds: ["treasury"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Treasury {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// 金库 Token 账户 (owner = Treasury PDA)
+    pub vault: Pubkey,
+    
+    /// 各来源累计流入 (e6), 按 TreasurySource 索引
+    pub total_routed_e6: [i64; TREASURY_SOURCE_COUNT],
+    
+    /// 累计提取 (e6)
+    pub total_withdrawn_e6: i64,
+    
+    /// 待执行提取金额 (e6, 0 = 无)
+    pub pending_withdrawal_e6: i64,
+    
+    /// 待执行提取的目标 Token 账户
+    pub pending_destination: Pubkey,
+    
+    /// 待执行提取的最早执行时间
+    pub withdrawal_executable_at: i64,
+    
+    /// 创建时间
+    pub created_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段
+    pub reserved: [u8; 32],
+}
+
+impl Treasury {
+    /// 账户大小
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // vault
+        + 8 * TREASURY_SOURCE_COUNT  // total_routed_e6
+        + 8   // total_withdrawn_e6
+        + 8   // pending_withdrawal_e6
+        + 32  // pending_destination
+        + 8   // withdrawal_executable_at
+        + 8   // created_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// 提取 timelock (48 小时)
+    pub const WITHDRAWAL_TIMELOCK_SECS: i64 = 48 * 60 * 60;
+    
+    /// 创建新的金库
+    pub fn new(vault: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: TREASURY_DISCRIMINATOR,
+            vault,
+            total_routed_e6: [0; TREASURY_SOURCE_COUNT],
+            total_withdrawn_e6: 0,
+            pending_withdrawal_e6: 0,
+            pending_destination: Pubkey::default(),
+            withdrawal_executable_at: 0,
+            created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![TREASURY_SEED.to_vec()]
+    }
+    
+    /// 记录一笔协议收入
+    pub fn record_inflow(&mut self, source: TreasurySource, amount_e6: i64) {
+        let total = &mut self.total_routed_e6[source as usize];
+        *total = total.saturating_add(amount_e6);
+    }
+    
+    /// 所有来源累计流入
+    pub fn total_inflow_e6(&self) -> i64 {
+        self.total_routed_e6.iter().fold(0i64, |acc, v| acc.saturating_add(*v))
+    }
+    
+    /// 是否有待执行的提取
+    pub fn has_pending_withdrawal(&self) -> bool {
+        self.pending_withdrawal_e6 > 0
+    }
+    
+    /// 安排一笔提取; 重新安排会重置 timelock
+    pub fn schedule_withdrawal(&mut self, amount_e6: i64, destination: Pubkey, current_ts: i64) {
+        self.pending_withdrawal_e6 = amount_e6;
+        self.pending_destination = destination;
+        self.withdrawal_executable_at = current_ts.saturating_add(Self::WITHDRAWAL_TIMELOCK_SECS);
+    }
+    
+    /// 取消待执行的提取
+    pub fn clear_pending_withdrawal(&mut self) {
+        self.pending_withdrawal_e6 = 0;
+        self.pending_destination = Pubkey::default();
+        self.withdrawal_executable_at = 0;
+    }
+    
+    /// timelock 是否已过
+    pub fn is_withdrawal_executable(&self, current_ts: i64) -> bool {
+        self.has_pending_withdrawal() && current_ts >= self.withdrawal_executable_at
+    }
+    
+    /// 完成待执行的提取, 返回提取金额
+    pub fn complete_withdrawal(&mut self) -> i64 {
+        let amount = self.pending_withdrawal_e6;
+        self.total_withdrawn_e6 = self.total_withdrawn_e6.saturating_add(amount);
+        self.clear_pending_withdrawal();
+        amount
+    }
+}
+
+// =============================================================================
+// Balances Checkpoint
+// =============================================================================
+
+/// Discriminator for BalancesCheckpoint account
+pub const BALANCES_CHECKPOINT_DISCRIMINATOR: u64 = 0x42414C5F524F4F54; // "BAL_ROOT"
+
+/// Seed prefix for BalancesCheckpoint PDA
+pub const BALANCES_CHECKPOINT_SEED: &[u8] = b"balances_checkpoint";
+
+/// Latest committed merkle root of a fund's (investor, shares) balances
+///
+/// Leaves are `balance_leaf_hash(investor, shares)`; see `verify_merkle_proof`.
+///
+/// PDA Seeds: ["balances_checkpoint", fund]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BalancesCheckpoint {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund the snapshot belongs to
+    pub fund: Pubkey,
+    
+    /// Merkle root of (investor, shares) leaves
+    pub root: [u8; 32],
+    
+    /// Snapshot timestamp the balances were taken at
+    pub snapshot_ts: i64,
+    
+    /// Number of leaves in the tree
+    pub leaf_count: u32,
+    
+    /// Sum of shares across all leaves
+    pub total_shares: u64,
+    
+    /// Operator that posted the root
+    pub committed_by: Pubkey,
+    
+    /// Commit timestamp
+    pub committed_at: i64,
+    
+    /// Number of roots committed so far
+    pub checkpoint_count: u64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl BalancesCheckpoint {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // root
+        + 8   // snapshot_ts
+        + 4   // leaf_count
+        + 8   // total_shares
+        + 32  // committed_by
+        + 8   // committed_at
+        + 8   // checkpoint_count
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an empty checkpoint
+    pub fn new(fund: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: BALANCES_CHECKPOINT_DISCRIMINATOR,
+            fund,
+            root: [0u8; 32],
+            snapshot_ts: 0,
+            leaf_count: 0,
+            total_shares: 0,
+            committed_by: Pubkey::default(),
+            committed_at: 0,
+            checkpoint_count: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for BalancesCheckpoint
+    pub fn seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![BALANCES_CHECKPOINT_SEED.to_vec(), fund.to_bytes().to_vec()]
+    }
+    
+    /// Has a root been committed?
+    pub fn is_committed(&self) -> bool {
+        self.checkpoint_count > 0
+    }
+    
+    /// Replace the root; snapshots must move forward in time
+    pub fn commit(
+        &mut self,
+        root: [u8; 32],
+        snapshot_ts: i64,
+        leaf_count: u32,
+        total_shares: u64,
+        committed_by: Pubkey,
+        current_ts: i64,
+    ) -> Result<(), ProgramError> {
+        if snapshot_ts > current_ts || (self.is_committed() && snapshot_ts <= self.snapshot_ts) {
+            return Err(crate::error::FundError::InvalidSnapshotTimestamp.into());
+        }
+        self.root = root;
+        self.snapshot_ts = snapshot_ts;
+        self.leaf_count = leaf_count;
+        self.total_shares = total_shares;
+        self.committed_by = committed_by;
+        self.committed_at = current_ts;
+        self.checkpoint_count = self.checkpoint_count.saturating_add(1);
+        Ok(())
+    }
+    
+    /// Check an (investor, shares) inclusion proof against the committed root
+    pub fn verify(&self, investor: &Pubkey, shares: u64, proof: &[[u8; 32]]) -> bool {
+        self.is_committed() && verify_merkle_proof(balance_leaf_hash(investor, shares), proof, &self.root)
+    }
+}
+
+// =============================================================================
+// Manager Risk
+// =============================================================================
+
+/// Discriminator for ManagerRiskLimits account
+pub const MANAGER_RISK_LIMITS_DISCRIMINATOR: u64 = 0x4D47525F4C494D54; // "MGR_LIMT"
+
+/// Seed for ManagerRiskLimits PDA
+pub const MANAGER_RISK_LIMITS_SEED: &[u8] = b"manager_risk_limits";
+
+/// Discriminator for ManagerRiskState account
+pub const MANAGER_RISK_STATE_DISCRIMINATOR: u64 = 0x4D47525F5249534B; // "MGR_RISK"
+
+/// Seed prefix for ManagerRiskState PDA
+pub const MANAGER_RISK_STATE_SEED: &[u8] = b"manager_risk";
+
+/// How long a risk limit breach may persist before EnforceRiskLimits can reduce positions (1 hour)
+pub const RISK_BREACH_GRACE_SECS: i64 = 3600;
+
+/// Max age of the Ledger-signed Fund exposure EnforceRiskLimits acts on (5 minutes)
+pub const RISK_EXPOSURE_MAX_AGE_SECS: i64 = 300;
+
+/// Keeper incentive for an enforced reduction, in bps of the notional closed (0.1%)
+pub const RISK_ENFORCEMENT_KEEPER_FEE_BPS: u64 = 10;
+
+/// Cap on the keeper incentive per enforcement (100 USDC)
+pub const MAX_RISK_ENFORCEMENT_KEEPER_FEE_E6: u64 = 100_000_000;
+
+/// Admin-set caps applied to every manager across all of their funds
+///
+/// Enforced by TradeFund / ExecuteTradeIntent while
+/// `FundConfig.manager_risk_limits_enabled` is set.
+///
+/// PDA Seeds: ["manager_risk_limits"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ManagerRiskLimits {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Max open notional per manager, summed over their funds (e6, 0 = no cap)
+    pub max_notional_e6: u64,
+    
+    /// Max leverage of any single trade by the manager (0 = no cap)
+    pub max_leverage: u8,
+    
+    /// Last update timestamp
+    pub updated_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl ManagerRiskLimits {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 8   // max_notional_e6
+        + 1   // max_leverage
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create limits with no caps
+    pub fn new(bump: u8) -> Self {
+        Self {
+            discriminator: MANAGER_RISK_LIMITS_DISCRIMINATOR,
+            max_notional_e6: 0,
+            max_leverage: 0,
+            updated_at: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for ManagerRiskLimits
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![MANAGER_RISK_LIMITS_SEED.to_vec()]
+    }
+    
+    /// Is any cap set?
+    pub fn has_caps(&self) -> bool {
+        self.max_notional_e6 > 0 || self.max_leverage > 0
+    }
+    
+    /// Check a new trade against the caps given the manager's current open notional
+    pub fn check(&self, open_notional_e6: u64, notional_e6: u64, leverage: u8) -> Result<(), ProgramError> {
+        if self.max_leverage > 0 && leverage > self.max_leverage {
+            return Err(crate::error::FundError::ManagerLeverageCapExceeded.into());
+        }
+        if self.max_notional_e6 > 0
+            && open_notional_e6.saturating_add(notional_e6) > self.max_notional_e6
+        {
+            return Err(crate::error::FundError::ManagerNotionalCapExceeded.into());
+        }
+        Ok(())
+    }
+    
+    /// One fund's share of the notional above the caps (e6, 0 = within limits)
+    /// 
+    /// The manager-wide notional cap excess is attributed to the fund pro rata
+    /// to its own open notional. Besides that, the fund's book may not exceed
+    /// `max_leverage` times its own `fund_equity_e6`, so losses that shrink
+    /// equity can push a fund over the limit without any new trade. Never more
+    /// than the fund has open, so no fund is reduced for another fund's exposure.
+    pub fn fund_excess_notional_e6(
+        &self,
+        manager_open_notional_e6: u64,
+        fund_open_notional_e6: u64,
+        fund_equity_e6: i64,
+    ) -> u64 {
+        let mut excess = 0u64;
+        if self.max_notional_e6 > 0 && manager_open_notional_e6 > 0 {
+            let manager_excess = manager_open_notional_e6.saturating_sub(self.max_notional_e6);
+            excess = (manager_excess as u128 * fund_open_notional_e6 as u128
+                / manager_open_notional_e6 as u128) as u64;
+        }
+        if self.max_leverage > 0 {
+            let max_book = (fund_equity_e6.max(0) as u128 * self.max_leverage as u128).min(u64::MAX as u128) as u64;
+            excess = excess.max(fund_open_notional_e6.saturating_sub(max_book));
+        }
+        excess.min(fund_open_notional_e6)
+    }
+}
+
+/// Aggregate exposure of one manager across all of their funds
+///
+/// Opened notional is added by TradeFund / ExecuteTradeIntent and removed
+/// by CloseFundPosition at the close price, floored at zero.
+///
+/// PDA Seeds: ["manager_risk", manager]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ManagerRiskState {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund manager wallet
+    pub manager: Pubkey,
+    
+    /// Open notional across the manager's funds (e6)
+    pub open_notional_e6: u64,
+    
+    /// Highest open notional seen (e6)
+    pub peak_notional_e6: u64,
+    
+    /// Number of trades recorded
+    pub trade_count: u64,
+    
+    /// Last trade timestamp
+    pub last_trade_ts: i64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use (breach tracking moved to Fund.risk_breach_since)
+    pub reserved: [u8; 32],
+}
+
+impl ManagerRiskState {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // manager
+        + 8   // open_notional_e6
+        + 8   // peak_notional_e6
+        + 8   // trade_count
+        + 8   // last_trade_ts
+        + 8   // created_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an empty risk state
+    pub fn new(manager: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: MANAGER_RISK_STATE_DISCRIMINATOR,
+            manager,
+            open_notional_e6: 0,
+            peak_notional_e6: 0,
+            trade_count: 0,
+            last_trade_ts: 0,
+            created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for ManagerRiskState
+    pub fn seeds(manager: &Pubkey) -> Vec<Vec<u8>> {
+        vec![MANAGER_RISK_STATE_SEED.to_vec(), manager.to_bytes().to_vec()]
+    }
+    
+    /// Record an opened position
+    pub fn record_open(&mut self, notional_e6: u64, current_ts: i64) {
+        self.open_notional_e6 = self.open_notional_e6.saturating_add(notional_e6);
+        self.peak_notional_e6 = self.peak_notional_e6.max(self.open_notional_e6);
+        self.trade_count = self.trade_count.saturating_add(1);
+        self.last_trade_ts = current_ts;
+    }
+    
+    /// Record a closed position
+    pub fn record_close(&mut self, notional_e6: u64, current_ts: i64) {
+        self.open_notional_e6 = self.open_notional_e6.saturating_sub(notional_e6);
+        self.trade_count = self.trade_count.saturating_add(1);
+        self.last_trade_ts = current_ts;
+    }
+}
+
+/// Keeper incentive for closing `notional_e6` under EnforceRiskLimits (e6)
+pub fn risk_enforcement_keeper_fee_e6(notional_e6: u64) -> u64 {
+    ((notional_e6 as u128 * RISK_ENFORCEMENT_KEEPER_FEE_BPS as u128 / BPS_DENOMINATOR as u128) as u64)
+        .min(MAX_RISK_ENFORCEMENT_KEEPER_FEE_E6)
+}
+
+// =============================================================================
+// Mark Price
+// =============================================================================
+
+/// Discriminator for MarkPrice account
+pub const MARK_PRICE_DISCRIMINATOR: u64 = 0x4D41524B5F505243; // "MARK_PRC"
+
+/// Seed prefix for MarkPrice PDA
+pub const MARK_PRICE_SEED: &[u8] = b"mark_price";
+
+/// Mark prices older than this cannot back a risk enforcement (60 seconds)
+pub const MARK_PRICE_MAX_AGE_SECS: i64 = 60;
+
+/// Max distance of a keeper's EnforceRiskLimits exit price from the mark price (0.5%)
+pub const RISK_ENFORCEMENT_MAX_PRICE_DEVIATION_BPS: u64 = 50;
+
+/// Latest mark price of a Ledger market, pushed by an authorized relayer
+///
+/// PDA Seeds: ["mark_price", market_index]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct MarkPrice {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Ledger market index
+    pub market_index: u8,
+    
+    /// Mark price (e6)
+    pub price_e6: u64,
+    
+    /// Publish timestamp
+    pub updated_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl MarkPrice {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 1   // market_index
+        + 8   // price_e6
+        + 8   // updated_at
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create an unpublished mark price
+    pub fn new(market_index: u8, bump: u8) -> Self {
+        Self {
+            discriminator: MARK_PRICE_DISCRIMINATOR,
+            market_index,
+            price_e6: 0,
+            updated_at: 0,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for MarkPrice
+    pub fn seeds(market_index: u8) -> Vec<Vec<u8>> {
+        vec![MARK_PRICE_SEED.to_vec(), vec![market_index]]
+    }
+    
+    /// Publish a new mark price
+    pub fn update(&mut self, price_e6: u64, current_ts: i64) -> Result<(), ProgramError> {
+        if price_e6 == 0 {
+            return Err(crate::error::FundError::InvalidAmount.into());
+        }
+        self.price_e6 = price_e6;
+        self.updated_at = current_ts;
+        Ok(())
+    }
+    
+    /// Mark price if published within MARK_PRICE_MAX_AGE_SECS
+    pub fn fresh_price_e6(&self, current_ts: i64) -> Option<u64> {
+        (self.price_e6 > 0 && current_ts.saturating_sub(self.updated_at) <= MARK_PRICE_MAX_AGE_SECS)
+            .then_some(self.price_e6)
+    }
+    
+    /// Is `price_e6` within `max_deviation_bps` of `mark_e6`?
+    pub fn within_band(mark_e6: u64, price_e6: u64, max_deviation_bps: u64) -> bool {
+        let diff = mark_e6.abs_diff(price_e6) as u128;
+        diff * BPS_DENOMINATOR as u128 <= mark_e6 as u128 * max_deviation_bps as u128
+    }
+}
+
+// =============================================================================
+// NAV Blackout
+// =============================================================================
+
+/// Discriminator for BlackoutPolicy account
+pub const BLACKOUT_POLICY_DISCRIMINATOR: u64 = 0x424C4B5F504F4C59; // "BLK_POLY"
+
+/// Seed for BlackoutPolicy PDA
+pub const BLACKOUT_POLICY_SEED: &[u8] = b"blackout_policy";
+
+/// Admin bounds for per-fund deposit/redemption blackouts
+///
+/// Managers choose a fund's window and PnL threshold via SetFundBlackout;
+/// the window may not exceed `max_window_secs` and the threshold may not be
+/// below `min_threshold_bps`.
+///
+/// PDA Seeds: ["blackout_policy"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BlackoutPolicy {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Longest blackout a manager may configure (seconds)
+    pub max_window_secs: u32,
+    
+    /// Smallest PnL threshold a manager may configure (bps of fund value)
+    pub min_threshold_bps: u16,
+    
+    /// Last update timestamp
+    pub updated_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl BlackoutPolicy {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 4   // max_window_secs
+        + 2   // min_threshold_bps
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a policy that allows no blackouts
+    pub fn new(bump: u8) -> Self {
+        Self {
+            discriminator: BLACKOUT_POLICY_DISCRIMINATOR,
+            max_window_secs: 0,
+            min_threshold_bps: 0,
+            updated_at: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for BlackoutPolicy
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![BLACKOUT_POLICY_SEED.to_vec()]
+    }
+    
+    /// Is a fund's (window, threshold) within the policy?
+    pub fn allows(&self, window_secs: u32, threshold_bps: u16) -> bool {
+        window_secs <= self.max_window_secs
+            && threshold_bps >= self.min_threshold_bps
+            && threshold_bps > 0
+            && threshold_bps as u64 <= BPS_DENOMINATOR
+    }
+}
+
+// =============================================================================
+// Insurance Yield
+// =============================================================================
+
+/// Discriminator for InsuranceYieldVenue account
+pub const INSURANCE_YIELD_VENUE_DISCRIMINATOR: u64 = 0x494E535F59454C44; // "INS_YELD"
+
+/// Seed prefix for InsuranceYieldVenue PDA
+pub const INSURANCE_YIELD_VENUE_SEED: &[u8] = b"insurance_yield_venue";
+
+/// Whitelisted yield venue for idle insurance fund capital
+///
+/// The venue deposit account is held by the insurance Fund PDA, so deployed
+/// capital can be recalled instantly (CoverShortfall does so before it
+/// reports an uncovered remainder). Outstanding principal is tracked on the
+/// Fund as `deployed_capital_e6`; anything recalled beyond it is yield.
+///
+/// PDA Seeds: ["insurance_yield_venue", insurance_fund]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InsuranceYieldVenue {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Insurance Fund PDA
+    pub fund: Pubkey,
+    
+    /// Venue deposit token account (authority = insurance Fund PDA)
+    pub venue_vault: Pubkey,
+    
+    /// Max share of insurance capital (vault + deployed) that may be deployed
+    pub max_deploy_bps: u16,
+    
+    /// Cumulative capital deployed (e6)
+    pub total_deployed_e6: i64,
+    
+    /// Cumulative capital recalled, including yield (e6)
+    pub total_recalled_e6: i64,
+    
+    /// Cumulative yield realized on recall (e6)
+    pub total_yield_e6: i64,
+    
+    /// Last update timestamp
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl InsuranceYieldVenue {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // venue_vault
+        + 2   // max_deploy_bps
+        + 8   // total_deployed_e6
+        + 8   // total_recalled_e6
+        + 8   // total_yield_e6
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new venue record
+    pub fn new(fund: Pubkey, venue_vault: Pubkey, max_deploy_bps: u16, bump: u8) -> Self {
+        Self {
+            discriminator: INSURANCE_YIELD_VENUE_DISCRIMINATOR,
+            fund,
+            venue_vault,
+            max_deploy_bps,
+            total_deployed_e6: 0,
+            total_recalled_e6: 0,
+            total_yield_e6: 0,
+            last_update_ts: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for InsuranceYieldVenue
+    pub fn seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![INSURANCE_YIELD_VENUE_SEED.to_vec(), fund.as_ref().to_vec()]
+    }
+    
+    /// Room left to deploy given the vault balance and outstanding principal
+    pub fn deploy_capacity_e6(&self, vault_balance_e6: i64, deployed_e6: i64) -> i64 {
+        let total = (vault_balance_e6.max(0) as i128) + (deployed_e6.max(0) as i128);
+        let cap = total * (self.max_deploy_bps as i128) / (BPS_DENOMINATOR as i128);
+        (cap - deployed_e6.max(0) as i128).clamp(0, i64::MAX as i128) as i64
+    }
+    
+    /// Book a deployment; returns the new outstanding principal
+    pub fn record_deploy(&mut self, amount_e6: i64, deployed_e6: i64) -> Result<i64, ProgramError> {
+        self.total_deployed_e6 = safe_add_i64(self.total_deployed_e6, amount_e6)?;
+        safe_add_i64(deployed_e6, amount_e6)
+    }
+    
+    /// Book a recall; returns (new outstanding principal, yield realized)
+    ///
+    /// Principal is repaid first; the excess is yield.
+    pub fn record_recall(&mut self, amount_e6: i64, deployed_e6: i64) -> Result<(i64, i64), ProgramError> {
+        let principal = amount_e6.min(deployed_e6.max(0));
+        let yield_e6 = amount_e6 - principal;
+        self.total_recalled_e6 = safe_add_i64(self.total_recalled_e6, amount_e6)?;
+        self.total_yield_e6 = safe_add_i64(self.total_yield_e6, yield_e6)?;
+        Ok((deployed_e6 - principal, yield_e6))
+    }
+}
+
+// =============================================================================
+// ADL Event Ledger
+// =============================================================================
+
+/// Discriminator for ADLEvent account
+pub const ADL_EVENT_DISCRIMINATOR: u64 = 0x41444C5F45564E54; // "ADL_EVNT"
+
+/// Seed prefix for ADLEvent PDA
+pub const ADL_EVENT_SEED: &[u8] = b"adl_event";
+
+/// ADL 事件记录 (每轮 ADL 一个，只追加)
+/// 
+/// SetADLInProgress(true) 创建并记录触发原因与触发时指标；ADL 期间
+/// CoverShortfall 未覆盖的部分累加到 `amount_socialized_e6`；
+/// SetADLInProgress(false) 写入结束时间，此后记录不再变更。
+/// 
+/// PDA Seeds: ["adl_event", episode (u64 LE)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ADLEvent {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// ADL 轮次 (= InsuranceFundConfig.adl_trigger_count)
+    pub episode: u64,
+    
+    /// 触发原因
+    pub trigger_reason: ADLTriggerReason,
+    
+    /// 触发时保险基金余额 (e6)
+    pub balance_at_trigger_e6: i64,
+    
+    /// 触发时 1 小时前快照余额 (e6)
+    pub balance_1h_ago_e6: i64,
+    
+    /// 触发时 ADL 余额阈值 (e6)
+    pub adl_threshold_e6: i64,
+    
+    /// 通过 ADL 分摊的穿仓金额 (e6)
+    pub amount_socialized_e6: i64,
+    
+    /// 计入的穿仓次数
+    pub shortfall_count: u32,
+    
+    /// 开始时间
+    pub started_at: i64,
+    
+    /// 结束时间 (0 = 进行中)
+    pub ended_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段
+    pub reserved: [u8; 32],
+}
+
+impl ADLEvent {
+    /// 账户大小 (bytes)
+    pub const SIZE: usize = 8  // discriminator
+        + 8   // episode
+        + 1   // trigger_reason
+        + 8   // balance_at_trigger_e6
+        + 8   // balance_1h_ago_e6
+        + 8   // adl_threshold_e6
+        + 8   // amount_socialized_e6
+        + 4   // shortfall_count
+        + 8   // started_at
+        + 8   // ended_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// 从 InsuranceFundConfig 的当前状态开启一轮 ADL 记录
+    /// 
+    /// `twap_balance_e6` 为触发时保险基金的 TWAP 价值 (与触发判断一致)
+    pub fn open(
+        config: &InsuranceFundConfig,
+        balance_e6: i64,
+        twap_balance_e6: Option<i64>,
+        started_at: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: ADL_EVENT_DISCRIMINATOR,
+            episode: config.adl_trigger_count,
+            trigger_reason: config.adl_start_reason(balance_e6, twap_balance_e6),
+            balance_at_trigger_e6: balance_e6,
+            balance_1h_ago_e6: config.balance_1h_ago_e6,
+            adl_threshold_e6: config.adl_trigger_threshold_e6,
+            amount_socialized_e6: config.pending_adl_shortfall_e6.max(0),
+            shortfall_count: (config.pending_adl_shortfall_e6 > 0) as u32,
+            started_at,
+            ended_at: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for ADLEvent
+    pub fn seeds(episode: u64) -> Vec<Vec<u8>> {
+        vec![ADL_EVENT_SEED.to_vec(), episode.to_le_bytes().to_vec()]
+    }
+    
+    /// 是否已结束
+    pub fn is_closed(&self) -> bool {
+        self.ended_at != 0
+    }
+    
+    /// 记录一笔 ADL 分摊的穿仓
+    pub fn record_socialized(&mut self, amount_e6: i64) -> Result<(), ProgramError> {
+        if self.is_closed() {
+            return Err(crate::error::FundError::ADLEventClosed.into());
+        }
+        self.amount_socialized_e6 = safe_add_i64(self.amount_socialized_e6, amount_e6)?;
+        self.shortfall_count = self.shortfall_count.saturating_add(1);
+        Ok(())
+    }
+    
+    /// 结束本轮 ADL
+    pub fn close(&mut self, ended_at: i64) -> Result<(), ProgramError> {
+        if self.is_closed() {
+            return Err(crate::error::FundError::ADLEventClosed.into());
+        }
+        self.ended_at = ended_at.max(self.started_at).max(1);
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Square Fund Stats
+// =============================================================================
+
+/// Discriminator for SquareFundStats account
+pub const SQUARE_FUND_STATS_DISCRIMINATOR: u64 = 0x5351525F53544154; // "SQR_STAT"
+
+/// Discriminator for SquareCreatorStats account
+pub const SQUARE_CREATOR_STATS_DISCRIMINATOR: u64 = 0x5351525F43525452; // "SQR_CRTR"
+
+/// Seed for SquareFundStats PDA
+pub const SQUARE_FUND_STATS_SEED: &[u8] = b"square_fund_stats";
+
+/// Seed for the program-owned square fund vault
+pub const SQUARE_FUND_VAULT_SEED: &[u8] = b"square_fund_vault";
+
+/// Seed prefix for SquareCreatorStats PDA
+pub const SQUARE_CREATOR_STATS_SEED: &[u8] = b"square_creator_stats";
+
+/// Square 单个支付类型的累计
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SquareTypeTotals {
+    /// 支付笔数
+    pub payment_count: u64,
+    
+    /// 支付总额 (e6)
+    pub volume_e6: i64,
+    
+    /// 创作者分成 (e6)
+    pub creator_share_e6: i64,
+    
+    /// 平台分成 (e6)
+    pub platform_share_e6: i64,
+}
+
+impl SquareTypeTotals {
+    pub const SIZE: usize = 8 + 8 + 8 + 8;
+}
+
+/// Square Fund 账本
+/// 
+/// 与程序持有的 square fund vault 双向记账：SquarePayment 转入 vault 的
+/// 平台分成记为流入，WithdrawSquareFund 记为流出，
+/// `accounted_balance_e6()` 应与 vault 余额一致。
+/// 
+/// PDA Seeds: ["square_fund_stats"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquareFundStats {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// Square fund vault (USDC Token Account，owner = 本 PDA)
+    pub square_fund_vault: Pubkey,
+    
+    /// 按支付类型的累计 (下标 = SquarePaymentType)
+    pub totals: [SquareTypeTotals; 3],
+    
+    /// 累计转入 vault 的平台分成 (e6)
+    pub total_vault_inflow_e6: i64,
+    
+    /// 累计提取 (e6)
+    pub total_withdrawn_e6: i64,
+    
+    /// 提取次数
+    pub withdrawal_count: u64,
+    
+    /// 最后更新时间
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段
+    pub reserved: [u8; 32],
+}
+
+impl SquareFundStats {
+    /// 账户大小 (bytes)
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // square_fund_vault
+        + SquareTypeTotals::SIZE * 3  // totals
+        + 8   // total_vault_inflow_e6
+        + 8   // total_withdrawn_e6
+        + 8   // withdrawal_count
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    pub fn new(square_fund_vault: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: SQUARE_FUND_STATS_DISCRIMINATOR,
+            square_fund_vault,
+            totals: [SquareTypeTotals::default(); 3],
+            total_vault_inflow_e6: 0,
+            total_withdrawn_e6: 0,
+            withdrawal_count: 0,
+            last_update_ts: created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for SquareFundStats
+    pub fn seeds() -> Vec<Vec<u8>> {
+        vec![SQUARE_FUND_STATS_SEED.to_vec()]
+    }
+    
+    /// PDA seeds for the square fund vault
+    pub fn vault_seeds() -> Vec<Vec<u8>> {
+        vec![SQUARE_FUND_VAULT_SEED.to_vec()]
+    }
+    
+    /// 记录一笔支付；`to_vault` = 平台分成进入 square fund vault (而非 Treasury)
+    pub fn record_payment(
+        &mut self,
+        payment_type: SquarePaymentType,
+        amount_e6: i64,
+        creator_share_e6: i64,
+        platform_share_e6: i64,
+        to_vault: bool,
+        current_ts: i64,
+    ) -> Result<(), ProgramError> {
+        let totals = &mut self.totals[payment_type as usize];
+        totals.payment_count = totals.payment_count.saturating_add(1);
+        totals.volume_e6 = safe_add_i64(totals.volume_e6, amount_e6)?;
+        totals.creator_share_e6 = safe_add_i64(totals.creator_share_e6, creator_share_e6)?;
+        totals.platform_share_e6 = safe_add_i64(totals.platform_share_e6, platform_share_e6)?;
+        if to_vault {
+            self.total_vault_inflow_e6 = safe_add_i64(self.total_vault_inflow_e6, platform_share_e6)?;
+        }
+        self.last_update_ts = current_ts;
+        Ok(())
+    }
+    
+    /// 账面余额 = 流入 - 提取
+    pub fn accounted_balance_e6(&self) -> i64 {
+        self.total_vault_inflow_e6.saturating_sub(self.total_withdrawn_e6)
+    }
+    
+    /// 记录一笔提取 (不得超过账面余额)
+    pub fn record_withdrawal(&mut self, amount_e6: i64, current_ts: i64) -> Result<(), ProgramError> {
+        if amount_e6 <= 0 || amount_e6 > self.accounted_balance_e6() {
+            return Err(crate::error::FundError::SquareWithdrawalExceedsBalance.into());
+        }
+        self.total_withdrawn_e6 = safe_add_i64(self.total_withdrawn_e6, amount_e6)?;
+        self.withdrawal_count = self.withdrawal_count.saturating_add(1);
+        self.last_update_ts = current_ts;
+        Ok(())
+    }
+}
+
+/// Square 创作者累计收入
+/// 
+/// PDA Seeds: ["square_creator_stats", creator]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquareCreatorStats {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// 创作者
+    pub creator: Pubkey,
+    
+    /// 收到的支付总额 (e6)
+    pub total_volume_e6: i64,
+    
+    /// 创作者分成总额 (e6)
+    pub total_earned_e6: i64,
+    
+    /// 支付笔数
+    pub payment_count: u64,
+    
+    /// 首笔支付时间
+    pub first_payment_ts: i64,
+    
+    /// 最近支付时间
+    pub last_payment_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段
+    pub reserved: [u8; 16],
+}
+
+impl SquareCreatorStats {
+    /// 账户大小 (bytes)
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // creator
+        + 8   // total_volume_e6
+        + 8   // total_earned_e6
+        + 8   // payment_count
+        + 8   // first_payment_ts
+        + 8   // last_payment_ts
+        + 1   // bump
+        + 16; // reserved
+    
+    pub fn new(creator: Pubkey, bump: u8, current_ts: i64) -> Self {
+        Self {
+            discriminator: SQUARE_CREATOR_STATS_DISCRIMINATOR,
+            creator,
+            total_volume_e6: 0,
+            total_earned_e6: 0,
+            payment_count: 0,
+            first_payment_ts: current_ts,
+            last_payment_ts: current_ts,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for SquareCreatorStats
+    pub fn seeds(creator: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SQUARE_CREATOR_STATS_SEED.to_vec(), creator.as_ref().to_vec()]
+    }
+    
+    /// 记录一笔支付
+    pub fn record_payment(&mut self, amount_e6: i64, creator_share_e6: i64, current_ts: i64) -> Result<(), ProgramError> {
+        self.total_volume_e6 = safe_add_i64(self.total_volume_e6, amount_e6)?;
+        self.total_earned_e6 = safe_add_i64(self.total_earned_e6, creator_share_e6)?;
+        self.payment_count = self.payment_count.saturating_add(1);
+        self.last_payment_ts = current_ts;
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Revenue Split
+// =============================================================================
+
+/// Discriminator for RevenueSplit account
+pub const REVENUE_SPLIT_DISCRIMINATOR: u64 = 0x5245565F53504C54; // "REV_SPLT"
+
+/// Seed prefix for RevenueSplit PDA
+pub const REVENUE_SPLIT_SEED: &[u8] = b"revenue_split";
+
+/// 分账最多接收方数量
+pub const MAX_SPLIT_RECIPIENTS: usize = 5;
+
+/// 分账接收方
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RevenueSplitRecipient {
+    /// 接收方 USDC Token Account
+    pub recipient_vault: Pubkey,
+    
+    /// 分成比例 (bps，全部接收方合计 10000)
+    pub share_bps: u16,
+}
+
+impl RevenueSplitRecipient {
+    pub const SIZE: usize = 32 + 2;
+}
+
+/// 创作者收入分账 (合作内容 / 机构)
+/// 
+/// 生效时 SquarePayment 将创作者分成按比例付给各接收方，而不是创作者 vault。
+/// 
+/// PDA Seeds: ["revenue_split", creator]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RevenueSplit {
+    /// 账户类型标识符
+    pub discriminator: u64,
+    
+    /// 创作者
+    pub creator: Pubkey,
+    
+    /// 有效接收方数量 (0 = 未启用)
+    pub recipient_count: u8,
+    
+    /// 接收方 (前 recipient_count 个有效)
+    pub recipients: [RevenueSplitRecipient; MAX_SPLIT_RECIPIENTS],
+    
+    /// 最后更新时间
+    pub updated_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留字段
+    pub reserved: [u8; 16],
+}
+
+impl RevenueSplit {
+    /// 账户大小 (bytes)
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // creator
+        + 1   // recipient_count
+        + RevenueSplitRecipient::SIZE * MAX_SPLIT_RECIPIENTS  // recipients
+        + 8   // updated_at
+        + 1   // bump
+        + 16; // reserved
+    
+    pub fn new(creator: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: REVENUE_SPLIT_DISCRIMINATOR,
+            creator,
+            recipient_count: 0,
+            recipients: [RevenueSplitRecipient::default(); MAX_SPLIT_RECIPIENTS],
+            updated_at: 0,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for RevenueSplit
+    pub fn seeds(creator: &Pubkey) -> Vec<Vec<u8>> {
+        vec![REVENUE_SPLIT_SEED.to_vec(), creator.as_ref().to_vec()]
+    }
+    
+    /// 是否启用
+    pub fn is_active(&self) -> bool {
+        self.recipient_count > 0
+    }
+    
+    /// 有效接收方
+    pub fn active_recipients(&self) -> &[RevenueSplitRecipient] {
+        &self.recipients[..self.recipient_count as usize]
+    }
+    
+    /// 设置接收方 (空列表 = 停用分账)
+    pub fn set_recipients(&mut self, recipients: &[RevenueSplitRecipient], current_ts: i64) -> Result<(), ProgramError> {
+        let invalid = || crate::error::FundError::InvalidRevenueSplit.into();
+        if recipients.len() > MAX_SPLIT_RECIPIENTS {
+            return Err(invalid());
+        }
+        if !recipients.is_empty() {
+            let total: u64 = recipients.iter().map(|r| r.share_bps as u64).sum();
+            if total != BPS_DENOMINATOR || recipients.iter().any(|r| r.share_bps == 0) {
+                return Err(invalid());
+            }
+            for (i, r) in recipients.iter().enumerate() {
+                if recipients[..i].iter().any(|o| o.recipient_vault == r.recipient_vault) {
+                    return Err(invalid());
+                }
+            }
+        }
+        
+        self.recipients = [RevenueSplitRecipient::default(); MAX_SPLIT_RECIPIENTS];
+        self.recipients[..recipients.len()].copy_from_slice(recipients);
+        self.recipient_count = recipients.len() as u8;
+        self.updated_at = current_ts;
+        Ok(())
+    }
+    
+    /// 按比例拆分金额；舍入余数归最后一个接收方，合计恒等于 amount
+    pub fn allocate(&self, amount_e6: i64) -> Vec<(Pubkey, i64)> {
+        let recipients = self.active_recipients();
+        let mut remaining = amount_e6;
+        recipients
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let share = if i + 1 == recipients.len() {
+                    remaining
+                } else {
+                    ((amount_e6 as i128) * (r.share_bps as i128) / (BPS_DENOMINATOR as i128)) as i64
+                };
+                remaining -= share;
+                (r.recipient_vault, share)
+            })
+            .collect()
+    }
+}
+
+// =============================================================================
+// Square Payer Nonce
+// =============================================================================
+
+/// Discriminator for SquarePayerNonce account
+pub const SQUARE_PAYER_NONCE_DISCRIMINATOR: u64 = 0x5351525F4E4F4E43; // "SQR_NONC"
+
+/// Seed prefix for SquarePayerNonce PDA
+pub const SQUARE_PAYER_NONCE_SEED: &[u8] = b"square_payer_nonce";
+
+/// 付款人的 SquarePayment 计数器
+///
+/// 每笔支付携带的 nonce 必须严格大于上一笔，支付记录 PDA 以 (payer, nonce)
+/// 派生，不再依赖时间戳去重。
+///
+/// PDA Seeds: ["square_payer_nonce", payer]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquarePayerNonce {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// 付款人地址
+    pub payer: Pubkey,
+    
+    /// 上一笔已接受的 nonce (0 = 尚无支付)
+    pub last_nonce: u64,
+    
+    /// 已记录的支付笔数
+    pub payment_count: u64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl SquarePayerNonce {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // payer
+        + 8   // last_nonce
+        + 8   // payment_count
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create a new SquarePayerNonce
+    pub fn new(payer: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: SQUARE_PAYER_NONCE_DISCRIMINATOR,
+            payer,
+            last_nonce: 0,
+            payment_count: 0,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for SquarePayerNonce
+    pub fn seeds(payer: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SQUARE_PAYER_NONCE_SEED.to_vec(), payer.to_bytes().to_vec()]
+    }
+    
+    /// 客户端下一笔支付可用的最小 nonce
+    pub fn next_nonce(&self) -> u64 {
+        self.last_nonce.saturating_add(1)
+    }
+    
+    /// Accept `nonce` if it is strictly greater than the last one
+    ///
+    /// Returns false (and leaves the state untouched) otherwise.
+    pub fn consume(&mut self, nonce: u64) -> bool {
+        if nonce <= self.last_nonce {
+            return false;
+        }
+        self.last_nonce = nonce;
+        self.payment_count = self.payment_count.saturating_add(1);
+        true
+    }
+}
+
+// =============================================================================
+// Square Payment History
+// =============================================================================
+
+/// Discriminator for SquarePaymentHistory account
+pub const SQUARE_PAYMENT_HISTORY_DISCRIMINATOR: u64 = 0x5351525F48495354; // "SQR_HIST"
+
+/// Discriminator for SquarePaymentHistoryPage account
+pub const SQUARE_PAYMENT_HISTORY_PAGE_DISCRIMINATOR: u64 = 0x5351525F48504147; // "SQR_HPAG"
+
+/// Seed prefix for SquarePaymentHistory PDA
+pub const SQUARE_PAYMENT_HISTORY_SEED: &[u8] = b"square_payment_history";
+
+/// Seed prefix for SquarePaymentHistoryPage PDA
+pub const SQUARE_PAYMENT_HISTORY_PAGE_SEED: &[u8] = b"square_payment_history_page";
+
+/// Payment nonces per SquarePaymentHistoryPage
+pub const SQUARE_PAYMENT_HISTORY_PAGE_CAPACITY: usize = 64;
+
+/// 付款人的支付记录索引
+///
+/// 每笔 SquarePayment 把 nonce 追加到 `head_page` 页，满页后开新页。钱包从
+/// `head_page` 往前读各页，即可按时间倒序列出该付款人的全部支付记录
+/// (`SquarePaymentRecord::seeds(payer, nonce)`)，无需 memcmp 扫描。
+///
+/// PDA Seeds: ["square_payment_history", payer]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquarePaymentHistory {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// 付款人地址
+    pub payer: Pubkey,
+    
+    /// 已索引的支付笔数
+    pub payment_count: u64,
+    
+    /// 当前追加的页号 (页 0..=head_page 均已创建)
+    pub head_page: u32,
+    
+    /// 创建时间
+    pub created_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl SquarePaymentHistory {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // payer
+        + 8   // payment_count
+        + 4   // head_page
+        + 8   // created_at
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create an empty history (page 0 is opened with the first payment)
+    pub fn new(payer: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: SQUARE_PAYMENT_HISTORY_DISCRIMINATOR,
+            payer,
+            payment_count: 0,
+            head_page: 0,
+            created_at,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for SquarePaymentHistory
+    pub fn seeds(payer: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SQUARE_PAYMENT_HISTORY_SEED.to_vec(), payer.to_bytes().to_vec()]
+    }
+    
+    /// Number of pages created
+    pub fn page_count(&self) -> u32 {
+        if self.payment_count == 0 {
+            0
+        } else {
+            self.head_page.saturating_add(1)
+        }
+    }
+}
+
+/// 支付记录索引的一页 (按支付顺序保存 nonce)
+///
+/// PDA Seeds: ["square_payment_history_page", payer, page.to_le_bytes()]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquarePaymentHistoryPage {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// 付款人地址
+    pub payer: Pubkey,
+    
+    /// Page number
+    pub page: u32,
+    
+    /// Number of used entries
+    pub count: u16,
+    
+    /// 支付 nonce (first `count` are valid)
+    pub nonces: [u64; SQUARE_PAYMENT_HISTORY_PAGE_CAPACITY],
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl SquarePaymentHistoryPage {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // payer
+        + 4   // page
+        + 2   // count
+        + 8 * SQUARE_PAYMENT_HISTORY_PAGE_CAPACITY // nonces
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create an empty page
+    pub fn new(payer: Pubkey, page: u32, bump: u8) -> Self {
+        Self {
+            discriminator: SQUARE_PAYMENT_HISTORY_PAGE_DISCRIMINATOR,
+            payer,
+            page,
+            count: 0,
+            nonces: [0u64; SQUARE_PAYMENT_HISTORY_PAGE_CAPACITY],
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for SquarePaymentHistoryPage
+    pub fn seeds(payer: &Pubkey, page: u32) -> Vec<Vec<u8>> {
+        vec![
+            SQUARE_PAYMENT_HISTORY_PAGE_SEED.to_vec(),
+            payer.to_bytes().to_vec(),
+            page.to_le_bytes().to_vec(),
+        ]
+    }
+    
+    /// Valid entries, oldest first
+    pub fn entries(&self) -> &[u64] {
+        &self.nonces[..self.count as usize]
+    }
+    
+    /// Check if the page has no free slot
+    pub fn is_full(&self) -> bool {
+        self.count as usize >= SQUARE_PAYMENT_HISTORY_PAGE_CAPACITY
+    }
+    
+    /// Append a payment nonce; returns false if the page is full
+    pub fn push(&mut self, nonce: u64) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.nonces[self.count as usize] = nonce;
+        self.count += 1;
+        true
+    }
+}
+
+// =============================================================================
+// Square Escrow
+// =============================================================================
+
+/// Discriminator for SquareEscrow account
+pub const SQUARE_ESCROW_DISCRIMINATOR: u64 = 0x5351525F45534352; // "SQR_ESCR"
+
+/// Seed prefix for SquareEscrow PDA
+pub const SQUARE_ESCROW_SEED: &[u8] = b"square_escrow";
+
+/// 托管超时 (7 天): 买家未确认也未发起争议时，任何人可放款给创作者
+pub const SQUARE_ESCROW_TIMEOUT_SECS: i64 = 7 * 24 * 3600;
+
+/// 托管状态
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SquareEscrowStatus {
+    /// 资金托管中
+    #[default]
+    Held = 0,
+    /// 买家已发起争议，等待 Admin 裁决
+    Disputed = 1,
+    /// 已放款给创作者/平台
+    Released = 2,
+    /// 已退款给买家
+    Refunded = 3,
+}
+
+/// 知识付费托管
+///
+/// `EscrowSquarePayment` 将买断款全额转入托管 token account (owner 为本 PDA)，
+/// 买家确认交付 (或超时) 后按支付记录的分成放款；买家在超时前可发起争议，
+/// 由 Admin 裁决退款或放款。
+///
+/// PDA Seeds: ["square_escrow", payment_record]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquareEscrow {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// 对应的 SquarePaymentRecord
+    pub payment_record: Pubkey,
+    
+    /// 买家
+    pub payer: Pubkey,
+    
+    /// 创作者
+    pub creator: Pubkey,
+    
+    /// 内容 ID
+    pub content_id: u64,
+    
+    /// 托管 token account
+    pub escrow_vault: Pubkey,
+    
+    /// 退款目标 (买家付款的 token account)
+    pub payer_vault: Pubkey,
+    
+    /// 创作者分成目标
+    pub creator_vault: Pubkey,
+    
+    /// 平台分成目标 (square fund vault 或 Treasury vault)
+    pub platform_vault: Pubkey,
+    
+    /// 托管总额 (e6)
+    pub amount_e6: i64,
+    
+    /// 创作者分成 (e6)
+    pub creator_amount_e6: i64,
+    
+    /// 平台分成 (e6)
+    pub platform_amount_e6: i64,
+    
+    /// 当前状态
+    pub status: SquareEscrowStatus,
+    
+    /// 托管时间
+    pub created_at: i64,
+    
+    /// 超时放款时间 (也是争议截止时间)
+    pub release_after: i64,
+    
+    /// 发起争议时间 (0 = 无)
+    pub disputed_at: i64,
+    
+    /// 放款/退款时间 (0 = 未结算)
+    pub settled_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl SquareEscrow {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // payment_record
+        + 32  // payer
+        + 32  // creator
+        + 8   // content_id
+        + 32  // escrow_vault
+        + 32  // payer_vault
+        + 32  // creator_vault
+        + 32  // platform_vault
+        + 8   // amount_e6
+        + 8   // creator_amount_e6
+        + 8   // platform_amount_e6
+        + 1   // status
+        + 8   // created_at
+        + 8   // release_after
+        + 8   // disputed_at
+        + 8   // settled_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// 由支付记录创建托管
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        payment_record: Pubkey,
+        record: &SquarePaymentRecord,
+        escrow_vault: Pubkey,
+        payer_vault: Pubkey,
+        creator_vault: Pubkey,
+        platform_vault: Pubkey,
+        current_ts: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            discriminator: SQUARE_ESCROW_DISCRIMINATOR,
+            payment_record,
+            payer: record.payer,
+            creator: record.creator,
+            content_id: record.content_id,
+            escrow_vault,
+            payer_vault,
+            creator_vault,
+            platform_vault,
+            amount_e6: record.total_amount_e6,
+            creator_amount_e6: record.creator_amount_e6,
+            platform_amount_e6: record.platform_amount_e6,
+            status: SquareEscrowStatus::Held,
+            created_at: current_ts,
+            release_after: current_ts.saturating_add(SQUARE_ESCROW_TIMEOUT_SECS),
+            disputed_at: 0,
+            settled_at: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for SquareEscrow
+    pub fn seeds(payment_record: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SQUARE_ESCROW_SEED.to_vec(), payment_record.to_bytes().to_vec()]
+    }
+    
+    /// 是否可放款: 买家随时可确认，其他人须等到超时；争议中只能由 Admin 裁决
+    pub fn can_release(&self, caller: &Pubkey, current_ts: i64) -> bool {
+        self.status == SquareEscrowStatus::Held
+            && (*caller == self.payer || current_ts >= self.release_after)
+    }
+    
+    /// 买家发起争议 (仅限托管中且未超时)
+    pub fn raise_dispute(&mut self, current_ts: i64) -> Result<(), ProgramError> {
+        if self.status != SquareEscrowStatus::Held {
+            return Err(crate::error::FundError::SquareEscrowNotHeld.into());
+        }
+        if current_ts >= self.release_after {
+            return Err(crate::error::FundError::SquareEscrowDisputeWindowClosed.into());
+        }
+        self.status = SquareEscrowStatus::Disputed;
+        self.disputed_at = current_ts;
+        Ok(())
+    }
+    
+    /// 标记结算完成
+    pub fn settle(&mut self, refunded: bool, current_ts: i64) {
+        self.status = if refunded {
+            SquareEscrowStatus::Refunded
+        } else {
+            SquareEscrowStatus::Released
+        };
+        self.settled_at = current_ts;
+    }
+}
+
+// =============================================================================
+// Square Tip Token
+// =============================================================================
+
+/// Discriminator for SquareTipToken account
+pub const SQUARE_TIP_TOKEN_DISCRIMINATOR: u64 = 0x5351525F54495054; // "SQR_TIPT"
+
+/// Seed prefix for SquareTipToken PDA
+pub const SQUARE_TIP_TOKEN_SEED: &[u8] = b"square_tip_token";
+
+/// 打赏代币价格默认最长有效期 (1 小时)
+pub const DEFAULT_TIP_PRICE_MAX_AGE_SECS: i64 = 3600;
+
+/// 白名单打赏代币
+///
+/// 直播打赏可使用白名单内的 SPL 代币；代币按 bps 直接分给创作者与平台
+/// (`platform_vault`)，支付记录中的金额为按 `price_e6` 折算的 USD e6 价值，
+/// 用于统计和排行榜。价格由 `price_authority` 推送，超过 `max_price_age_secs`
+/// 视为过期。
+///
+/// PDA Seeds: ["square_tip_token", mint]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SquareTipToken {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// 代币 mint
+    pub mint: Pubkey,
+    
+    /// 代币精度
+    pub decimals: u8,
+    
+    /// 平台分成接收 token account
+    pub platform_vault: Pubkey,
+    
+    /// 价格推送者
+    pub price_authority: Pubkey,
+    
+    /// 1 个完整代币的 USD 价格 (e6)
+    pub price_e6: i64,
+    
+    /// 价格更新时间 (0 = 尚未报价)
+    pub price_updated_at: i64,
+    
+    /// 价格最长有效期 (秒)
+    pub max_price_age_secs: i64,
+    
+    /// 是否启用
+    pub enabled: bool,
+    
+    /// 累计打赏笔数
+    pub total_tips: u64,
+    
+    /// 累计打赏代币数量 (最小单位)
+    pub total_token_amount: u64,
+    
+    /// 累计打赏 USD 价值 (e6)
+    pub total_value_e6: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl SquareTipToken {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // mint
+        + 1   // decimals
+        + 32  // platform_vault
+        + 32  // price_authority
+        + 8   // price_e6
+        + 8   // price_updated_at
+        + 8   // max_price_age_secs
+        + 1   // enabled
+        + 8   // total_tips
+        + 8   // total_token_amount
+        + 8   // total_value_e6
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new (unpriced) SquareTipToken
+    pub fn new(mint: Pubkey, decimals: u8, bump: u8) -> Self {
+        Self {
+            discriminator: SQUARE_TIP_TOKEN_DISCRIMINATOR,
+            mint,
+            decimals,
+            platform_vault: Pubkey::default(),
+            price_authority: Pubkey::default(),
+            price_e6: 0,
+            price_updated_at: 0,
+            max_price_age_secs: DEFAULT_TIP_PRICE_MAX_AGE_SECS,
+            enabled: false,
+            total_tips: 0,
+            total_token_amount: 0,
+            total_value_e6: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for SquareTipToken
+    pub fn seeds(mint: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SQUARE_TIP_TOKEN_SEED.to_vec(), mint.to_bytes().to_vec()]
+    }
+    
+    /// 推送价格
+    pub fn set_price(&mut self, price_e6: i64, current_ts: i64) -> Result<(), ProgramError> {
+        if price_e6 <= 0 {
+            return Err(crate::error::FundError::InvalidAmount.into());
+        }
+        self.price_e6 = price_e6;
+        self.price_updated_at = current_ts;
+        Ok(())
+    }
+    
+    /// 将代币数量折算为 USD e6 (价格须未过期)
+    pub fn value_e6(&self, amount: u64, current_ts: i64) -> Result<i64, ProgramError> {
+        if self.price_updated_at == 0
+            || current_ts.saturating_sub(self.price_updated_at) > self.max_price_age_secs
+        {
+            return Err(crate::error::FundError::SquareTipPriceStale.into());
+        }
+        let value = amount as i128 * self.price_e6 as i128 / 10i128.pow(self.decimals as u32);
+        i64::try_from(value).map_err(|_| crate::error::FundError::Overflow.into())
+    }
+    
+    /// 按 bps 拆分代币数量，返回 (创作者, 平台)
+    pub fn split_amount(amount: u64, creator_share_bps: u16) -> (u64, u64) {
+        let creator = (amount as u128 * creator_share_bps as u128 / 10000) as u64;
+        (creator, amount - creator)
+    }
+    
+    /// 累计一笔打赏
+    pub fn record_tip(&mut self, amount: u64, value_e6: i64) -> Result<(), ProgramError> {
+        self.total_tips = self.total_tips.saturating_add(1);
+        self.total_token_amount = self.total_token_amount.saturating_add(amount);
+        self.total_value_e6 = safe_add_i64(self.total_value_e6, value_e6)?;
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Fund LP Referral
+// =============================================================================
+
+/// Discriminator for FundLPReferralProgram account
+pub const FUND_LP_REFERRAL_DISCRIMINATOR: u64 = 0x464C505F52454650; // "FLP_REFP"
+
+/// Discriminator for FundLPReferralBinding account
+pub const FUND_LP_REFERRAL_BINDING_DISCRIMINATOR: u64 = 0x464C505F52454642; // "FLP_REFB"
+
+/// Seed prefix for FundLPReferralProgram PDA
+pub const FUND_LP_REFERRAL_SEED: &[u8] = b"fund_lp_referral";
+
+/// Seed prefix for FundLPReferralBinding PDA
+pub const FUND_LP_REFERRAL_BINDING_SEED: &[u8] = b"fund_lp_referral_binding";
+
+/// LP 引荐奖励上限 (5% of referred deposits)
+pub const MAX_FUND_LP_REFERRAL_BOUNTY_BPS: u16 = 500;
+
+/// 基金级 LP 引荐计划
+///
+/// 与交易返佣独立：基金经理为引入 LP 资金的引荐人设置奖励 (被引荐存款的 bps)。
+/// 奖励在存款时按 binding 累计 (`total_accrued_e6`)，资金来自经理的费用流 ——
+/// CollectFees 优先从应付费用中扣留未覆盖的奖励留在金库 (`total_funded_e6`)，
+/// 引荐人再通过 ClaimFundLPReferralBounty 从已覆盖部分领取。
+///
+/// PDA Seeds: ["fund_lp_referral", fund]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct FundLPReferralProgram {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// 所属基金
+    pub fund: Pubkey,
+    
+    /// 奖励比例 (被引荐存款的 bps)
+    pub bounty_bps: u16,
+    
+    /// 是否接受新绑定与累计奖励
+    pub is_active: bool,
+    
+    /// 被引荐 LP 数量
+    pub referee_count: u32,
+    
+    /// 累计被引荐存款 (e6)
+    pub total_referred_deposits_e6: i64,
+    
+    /// 累计应付奖励 (e6)
+    pub total_accrued_e6: i64,
+    
+    /// 已从费用中扣留、留存在金库的奖励 (e6)
+    pub total_funded_e6: i64,
+    
+    /// 已领取奖励 (e6)
+    pub total_claimed_e6: i64,
+    
+    /// 最后配置时间
+    pub updated_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl FundLPReferralProgram {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 2   // bounty_bps
+        + 1   // is_active
+        + 4   // referee_count
+        + 8   // total_referred_deposits_e6
+        + 8   // total_accrued_e6
+        + 8   // total_funded_e6
+        + 8   // total_claimed_e6
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an inactive program for a fund
+    pub fn new(fund: Pubkey, bump: u8, current_ts: i64) -> Self {
+        Self {
+            discriminator: FUND_LP_REFERRAL_DISCRIMINATOR,
+            fund,
+            bounty_bps: 0,
+            is_active: false,
+            referee_count: 0,
+            total_referred_deposits_e6: 0,
+            total_accrued_e6: 0,
+            total_funded_e6: 0,
+            total_claimed_e6: 0,
+            updated_at: current_ts,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for FundLPReferralProgram
+    pub fn seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![FUND_LP_REFERRAL_SEED.to_vec(), fund.to_bytes().to_vec()]
+    }
+    
+    /// 计算一笔存款的引荐奖励
+    pub fn bounty_for(&self, deposit_e6: i64) -> i64 {
+        (deposit_e6.max(0) as i128 * self.bounty_bps as i128 / 10000) as i64
+    }
+    
+    /// 尚未被费用覆盖的应付奖励
+    pub fn unfunded_e6(&self) -> i64 {
+        self.total_accrued_e6.saturating_sub(self.total_funded_e6).max(0)
+    }
+    
+    /// 金库中可供领取的奖励
+    pub fn claimable_pool_e6(&self) -> i64 {
+        self.total_funded_e6.saturating_sub(self.total_claimed_e6).max(0)
+    }
+    
+    /// 累计一笔被引荐存款，返回本笔奖励
+    pub fn record_deposit(&mut self, deposit_e6: i64) -> Result<i64, ProgramError> {
+        let bounty = self.bounty_for(deposit_e6);
+        self.total_referred_deposits_e6 = safe_add_i64(self.total_referred_deposits_e6, deposit_e6)?;
+        self.total_accrued_e6 = safe_add_i64(self.total_accrued_e6, bounty)?;
+        Ok(bounty)
+    }
+    
+    /// 从本次费用中扣留未覆盖的奖励，返回扣留金额
+    pub fn fund_from_fees(&mut self, fee_e6: i64) -> Result<i64, ProgramError> {
+        let withheld = self.unfunded_e6().min(fee_e6.max(0));
+        self.total_funded_e6 = safe_add_i64(self.total_funded_e6, withheld)?;
+        Ok(withheld)
+    }
+}
+
+/// LP 引荐绑定 (按 (fund, referee) 唯一)
+///
+/// 被引荐人在首次存入该基金前绑定；此后每笔存款按计划 bps 为引荐人累计奖励。
+///
+/// PDA Seeds: ["fund_lp_referral_binding", fund, referee]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct FundLPReferralBinding {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// 所属基金
+    pub fund: Pubkey,
+    
+    /// 被引荐 LP
+    pub referee: Pubkey,
+    
+    /// 引荐人
+    pub referrer: Pubkey,
+    
+    /// 绑定时间
+    pub bound_at: i64,
+    
+    /// 被引荐人累计存款 (e6)
+    pub referred_deposits_e6: i64,
+    
+    /// 累计奖励 (e6)
+    pub bounty_accrued_e6: i64,
+    
+    /// 已领取奖励 (e6)
+    pub bounty_claimed_e6: i64,
+    
+    /// 存款次数
+    pub deposit_count: u32,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl FundLPReferralBinding {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // referee
+        + 32  // referrer
+        + 8   // bound_at
+        + 8   // referred_deposits_e6
+        + 8   // bounty_accrued_e6
+        + 8   // bounty_claimed_e6
+        + 4   // deposit_count
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create a new binding
+    pub fn new(fund: Pubkey, referee: Pubkey, referrer: Pubkey, bump: u8, current_ts: i64) -> Self {
+        Self {
+            discriminator: FUND_LP_REFERRAL_BINDING_DISCRIMINATOR,
+            fund,
+            referee,
+            referrer,
+            bound_at: current_ts,
+            referred_deposits_e6: 0,
+            bounty_accrued_e6: 0,
+            bounty_claimed_e6: 0,
+            deposit_count: 0,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for FundLPReferralBinding
+    pub fn seeds(fund: &Pubkey, referee: &Pubkey) -> Vec<Vec<u8>> {
+        vec![
+            FUND_LP_REFERRAL_BINDING_SEED.to_vec(),
+            fund.to_bytes().to_vec(),
+            referee.to_bytes().to_vec(),
+        ]
+    }
+    
+    /// 累计一笔存款及其奖励
+    pub fn record_deposit(&mut self, deposit_e6: i64, bounty_e6: i64) -> Result<(), ProgramError> {
+        self.referred_deposits_e6 = safe_add_i64(self.referred_deposits_e6, deposit_e6)?;
+        self.bounty_accrued_e6 = safe_add_i64(self.bounty_accrued_e6, bounty_e6)?;
+        self.deposit_count = self.deposit_count.saturating_add(1);
+        Ok(())
+    }
+    
+    /// 未领取奖励
+    pub fn unclaimed_e6(&self) -> i64 {
+        self.bounty_accrued_e6.saturating_sub(self.bounty_claimed_e6).max(0)
+    }
+    
+    /// 领取：不超过未领取部分与计划中已覆盖的奖励池，返回实际领取金额
+    pub fn claim(&mut self, program: &mut FundLPReferralProgram) -> Result<i64, ProgramError> {
+        let amount = self.unclaimed_e6().min(program.claimable_pool_e6());
+        self.bounty_claimed_e6 = safe_add_i64(self.bounty_claimed_e6, amount)?;
+        program.total_claimed_e6 = safe_add_i64(program.total_claimed_e6, amount)?;
+        Ok(amount)
+    }
+}
+
+// =============================================================================
+// LP Fee Statement
+// =============================================================================
+
+/// Discriminator for LPFeeStatement account
+pub const LP_FEE_STATEMENT_DISCRIMINATOR: u64 = 0x4C505F4653544D54; // "LP_FSTMT"
+
+/// Seed prefix for LPFeeStatement PDA
+pub const LP_FEE_STATEMENT_SEED: &[u8] = b"lp_fee_statement";
+
+/// Entries kept per LPFeeStatement (ring buffer; lifetime totals are kept separately)
+pub const LP_FEE_STATEMENT_ENTRIES: usize = 16;
+
+/// One crystallization's fees attributed to an LP
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LPFeeStatementEntry {
+    /// Fund fee epoch
+    pub epoch: u32,
+    /// LP shares at crystallization
+    pub shares: u64,
+    /// LP share of the management fee (e6)
+    pub mgmt_fee_e6: i64,
+    /// LP share of the performance fee (e6)
+    pub perf_fee_e6: i64,
+    /// Crystallization timestamp
+    pub crystallized_at: i64,
+}
+
+impl LPFeeStatementEntry {
+    /// Serialized size in bytes
+    pub const SIZE: usize = 4 + 8 + 8 + 8 + 8;
+}
+
+/// Per-LP ledger of crystallized fees paid
+///
+/// One entry per fee epoch, appended by RecordLPFeeStatement while the
+/// position's shares are unchanged since the crystallization.
+///
+/// PDA Seeds: ["lp_fee_statement", lp_position]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LPFeeStatement {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund the position belongs to
+    pub fund: Pubkey,
+    
+    /// LPPosition this statement covers
+    pub lp_position: Pubkey,
+    
+    /// Investor wallet
+    pub investor: Pubkey,
+    
+    /// Latest fee epoch recorded (0 = none)
+    pub last_epoch: u32,
+    
+    /// Entries recorded so far (next slot = entry_count % LP_FEE_STATEMENT_ENTRIES)
+    pub entry_count: u32,
+    
+    /// Lifetime management fees attributed (e6)
+    pub total_mgmt_fee_e6: i64,
+    
+    /// Lifetime performance fees attributed (e6)
+    pub total_perf_fee_e6: i64,
+    
+    /// Most recent entries
+    pub entries: [LPFeeStatementEntry; LP_FEE_STATEMENT_ENTRIES],
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl LPFeeStatement {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // lp_position
+        + 32  // investor
+        + 4   // last_epoch
+        + 4   // entry_count
+        + 8   // total_mgmt_fee_e6
+        + 8   // total_perf_fee_e6
+        + LPFeeStatementEntry::SIZE * LP_FEE_STATEMENT_ENTRIES // entries
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create an empty statement
+    pub fn new(fund: Pubkey, lp_position: Pubkey, investor: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: LP_FEE_STATEMENT_DISCRIMINATOR,
+            fund,
+            lp_position,
+            investor,
+            last_epoch: 0,
+            entry_count: 0,
+            total_mgmt_fee_e6: 0,
+            total_perf_fee_e6: 0,
+            entries: [LPFeeStatementEntry::default(); LP_FEE_STATEMENT_ENTRIES],
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for LPFeeStatement
+    pub fn seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![LP_FEE_STATEMENT_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    /// LP share of the fund's latest crystallization
+    ///
+    /// Fees come out of NAV, so each share bears `fee / fee_epoch_shares`.
+    pub fn entry_for(fund: &Fund, shares: u64) -> LPFeeStatementEntry {
+        let pro_rata = |fee_e6: i64| -> i64 {
+            if fund.fee_epoch_shares == 0 {
+                return 0;
+            }
+            (fee_e6 as i128 * shares as i128 / fund.fee_epoch_shares as i128) as i64
+        };
+        LPFeeStatementEntry {
+            epoch: fund.fee_epoch,
+            shares,
+            mgmt_fee_e6: pro_rata(fund.fee_epoch_mgmt_fee_e6),
+            perf_fee_e6: pro_rata(fund.fee_epoch_perf_fee_e6),
+            crystallized_at: fund.fee_epoch_at,
+        }
+    }
+    
+    /// Append an entry for a newer epoch
+    pub fn append(&mut self, entry: LPFeeStatementEntry) -> Result<(), ProgramError> {
+        if entry.epoch <= self.last_epoch {
+            return Err(crate::error::FundError::LPFeeStatementUpToDate.into());
+        }
+        self.total_mgmt_fee_e6 = safe_add_i64(self.total_mgmt_fee_e6, entry.mgmt_fee_e6)?;
+        self.total_perf_fee_e6 = safe_add_i64(self.total_perf_fee_e6, entry.perf_fee_e6)?;
+        self.entries[self.entry_count as usize % LP_FEE_STATEMENT_ENTRIES] = entry;
+        self.entry_count = self.entry_count.saturating_add(1);
+        self.last_epoch = entry.epoch;
+        Ok(())
+    }
+    
+    /// Entries oldest → newest
+    pub fn recent_entries(&self) -> Vec<LPFeeStatementEntry> {
+        let len = (self.entry_count as usize).min(LP_FEE_STATEMENT_ENTRIES);
+        let start = self.entry_count as usize - len;
+        (start..self.entry_count as usize)
+            .map(|i| self.entries[i % LP_FEE_STATEMENT_ENTRIES])
+            .collect()
+    }
+}
+
+// =============================================================================
+// Stable Swap Route
+// =============================================================================
+
+/// Discriminator for StableSwapRoute account
+pub const STABLE_SWAP_ROUTE_DISCRIMINATOR: u64 = 0x5354425F53574150; // "STB_SWAP"
+
+/// Seed prefix for StableSwapRoute PDA
+pub const STABLE_SWAP_ROUTE_SEED: &[u8] = b"stable_swap_route";
+
+/// Upper bound on a route's max slippage (1%)
+pub const MAX_STABLE_SWAP_SLIPPAGE_BPS: u16 = 100;
+
+/// Whitelisted stable-swap route for depositing an alternate stablecoin
+///
+/// DepositViaStableSwap swaps `input_mint` into the fund's vault mint through
+/// `swap_program` and deposits the proceeds; the fund stays single-currency.
+///
+/// PDA Seeds: ["stable_swap_route", input_mint]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct StableSwapRoute {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Stablecoin accepted from the LP (e.g. USDT)
+    pub input_mint: Pubkey,
+    
+    /// Stablecoin received (the fund vault mint, e.g. USDC)
+    pub output_mint: Pubkey,
+    
+    /// Decimals of `input_mint`
+    pub input_decimals: u8,
+    
+    /// Decimals of `output_mint`
+    pub output_decimals: u8,
+    
+    /// Whitelisted swap program
+    pub swap_program: Pubkey,
+    
+    /// Max shortfall of output vs input at par (bps, ≤ MAX_STABLE_SWAP_SLIPPAGE_BPS)
+    pub max_slippage_bps: u16,
+    
+    /// Accepting deposits
+    pub is_active: bool,
+    
+    /// Cumulative input swapped (input token units)
+    pub total_input: u64,
+    
+    /// Cumulative output deposited (output token units)
+    pub total_output: u64,
+    
+    /// Number of swapped deposits
+    pub swap_count: u64,
+    
+    /// Last update timestamp
+    pub last_update_ts: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl StableSwapRoute {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // input_mint
+        + 32  // output_mint
+        + 1   // input_decimals
+        + 1   // output_decimals
+        + 32  // swap_program
+        + 2   // max_slippage_bps
+        + 1   // is_active
+        + 8   // total_input
+        + 8   // total_output
+        + 8   // swap_count
+        + 8   // last_update_ts
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a route
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        input_decimals: u8,
+        output_decimals: u8,
+        swap_program: Pubkey,
+        max_slippage_bps: u16,
+        bump: u8,
+        current_ts: i64,
+    ) -> Self {
+        Self {
+            discriminator: STABLE_SWAP_ROUTE_DISCRIMINATOR,
+            input_mint,
+            output_mint,
+            input_decimals,
+            output_decimals,
+            swap_program,
+            max_slippage_bps,
+            is_active: true,
+            total_input: 0,
+            total_output: 0,
+            swap_count: 0,
+            last_update_ts: current_ts,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for StableSwapRoute
+    pub fn seeds(input_mint: &Pubkey) -> Vec<Vec<u8>> {
+        vec![STABLE_SWAP_ROUTE_SEED.to_vec(), input_mint.to_bytes().to_vec()]
+    }
+    
+    /// Minimum output for `input` at par less the route's max slippage
+    pub fn min_output(&self, input: u64) -> u64 {
+        let at_par = if self.output_decimals >= self.input_decimals {
+            (input as u128).saturating_mul(10u128.pow((self.output_decimals - self.input_decimals) as u32))
+        } else {
+            input as u128 / 10u128.pow((self.input_decimals - self.output_decimals) as u32)
+        };
+        let min = at_par * (10_000 - self.max_slippage_bps.min(10_000) as u128) / 10_000;
+        u64::try_from(min).unwrap_or(u64::MAX)
+    }
+    
+    /// Record a completed swap deposit
+    pub fn record_swap(&mut self, input: u64, output: u64, current_ts: i64) {
+        self.total_input = self.total_input.saturating_add(input);
+        self.total_output = self.total_output.saturating_add(output);
+        self.swap_count = self.swap_count.saturating_add(1);
+        self.last_update_ts = current_ts;
+    }
+}
+
+// =============================================================================
+// Notification Preferences
+// =============================================================================
+
+/// Discriminator for NotificationPrefs account
+pub const NOTIFICATION_PREFS_DISCRIMINATOR: u64 = 0x4E54465F50524546; // "NTF_PREF"
+
+/// Seed prefix for NotificationPrefs PDA
+pub const NOTIFICATION_PREFS_SEED: &[u8] = b"notification_prefs";
+
+/// Notify on NAV drops (FUND_NAV_BELOW_FLOOR, FUND_WIND_DOWN_REVIEW)
+pub const NOTIFY_NAV_DROP: u32 = 1 << 0;
+
+/// Notify on fee collection (LP_FEE_STATEMENT_RECORDED)
+pub const NOTIFY_FEE_COLLECTED: u32 = 1 << 1;
+
+/// Notify on wind-down (WIND_DOWN_REVIEW_RESOLVED)
+pub const NOTIFY_WIND_DOWN: u32 = 1 << 2;
+
+/// All defined notification bits
+pub const NOTIFY_EVENT_MASK: u32 = NOTIFY_NAV_DROP | NOTIFY_FEE_COLLECTED | NOTIFY_WIND_DOWN;
+
+/// An investor's opt-in notification routing
+///
+/// Stores only a hash commitment to the off-chain endpoint (e.g. a webhook
+/// URL plus salt), never the endpoint itself. Investor-specific events carry
+/// the investor's mask when this PDA is passed; indexers resolve the
+/// commitment off-chain.
+///
+/// PDA Seeds: ["notification_prefs", investor]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct NotificationPrefs {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Investor wallet
+    pub investor: Pubkey,
+    
+    /// Hash commitment to the off-chain endpoint
+    pub endpoint_hash: [u8; 32],
+    
+    /// Subscribed NOTIFY_* bits
+    pub event_mask: u32,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Last update timestamp
+    pub updated_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl NotificationPrefs {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // investor
+        + 32  // endpoint_hash
+        + 4   // event_mask
+        + 8   // created_at
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create empty prefs (no subscriptions)
+    pub fn new(investor: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: NOTIFICATION_PREFS_DISCRIMINATOR,
+            investor,
+            endpoint_hash: [0u8; 32],
+            event_mask: 0,
+            created_at,
+            updated_at: created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for NotificationPrefs
+    pub fn seeds(investor: &Pubkey) -> Vec<Vec<u8>> {
+        vec![NOTIFICATION_PREFS_SEED.to_vec(), investor.to_bytes().to_vec()]
+    }
+    
+    /// Replace the endpoint commitment and subscriptions
+    pub fn update(&mut self, endpoint_hash: [u8; 32], event_mask: u32, current_ts: i64) -> Result<(), ProgramError> {
+        if event_mask & !NOTIFY_EVENT_MASK != 0 || endpoint_hash == [0u8; 32] {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.endpoint_hash = endpoint_hash;
+        self.event_mask = event_mask;
+        self.updated_at = current_ts;
+        Ok(())
+    }
+    
+    /// Subscribed to `event`?
+    pub fn wants(&self, event: u32) -> bool {
+        self.event_mask & event != 0
+    }
+}
+
+// =============================================================================
+// Share Supply Checkpoints
+// =============================================================================
+
+/// Discriminator for ShareCheckpoints account
+pub const SHARE_CHECKPOINTS_DISCRIMINATOR: u64 = 0x5348525F434B5054; // "SHR_CKPT"
+
+/// Seed prefix for ShareCheckpoints PDA
+pub const SHARE_CHECKPOINTS_SEED: &[u8] = b"share_checkpoints";
+
+/// Checkpoints kept per fund (ring buffer)
+pub const SHARE_CHECKPOINT_ENTRIES: usize = 64;
+
+/// Default supply change that triggers a checkpoint (1%)
+pub const DEFAULT_SHARE_CHECKPOINT_THRESHOLD_BPS: u16 = 100;
+
+/// Fund share supply at a point in time
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShareCheckpoint {
+    /// Checkpoint timestamp
+    pub timestamp: i64,
+    /// Total shares outstanding
+    pub total_shares: u64,
+    /// NAV per share (e6)
+    pub nav_e6: i64,
+}
+
+impl ShareCheckpoint {
+    /// Serialized size in bytes
+    pub const SIZE: usize = 8 + 8 + 8;
+}
+
+/// Ring buffer of a fund's share supply history
+///
+/// A checkpoint is appended whenever total_shares has moved more than
+/// `threshold_bps` from the latest one, so pro-rata programs (airdrops,
+/// rebates, governance snapshots) can look up historical supply directly.
+///
+/// PDA Seeds: ["share_checkpoints", fund]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ShareCheckpoints {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund the checkpoints belong to
+    pub fund: Pubkey,
+    
+    /// Supply change that triggers a checkpoint (bps of the latest checkpoint)
+    pub threshold_bps: u16,
+    
+    /// Checkpoints recorded so far (next slot = checkpoint_count % SHARE_CHECKPOINT_ENTRIES)
+    pub checkpoint_count: u32,
+    
+    /// Most recent checkpoints
+    pub checkpoints: [ShareCheckpoint; SHARE_CHECKPOINT_ENTRIES],
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl ShareCheckpoints {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 2   // threshold_bps
+        + 4   // checkpoint_count
+        + ShareCheckpoint::SIZE * SHARE_CHECKPOINT_ENTRIES // checkpoints
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create an empty history
+    pub fn new(fund: Pubkey, threshold_bps: u16, bump: u8) -> Self {
+        Self {
+            discriminator: SHARE_CHECKPOINTS_DISCRIMINATOR,
+            fund,
+            threshold_bps,
+            checkpoint_count: 0,
+            checkpoints: [ShareCheckpoint::default(); SHARE_CHECKPOINT_ENTRIES],
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for ShareCheckpoints
+    pub fn seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SHARE_CHECKPOINTS_SEED.to_vec(), fund.to_bytes().to_vec()]
+    }
+    
+    /// Most recent checkpoint
+    pub fn latest(&self) -> Option<ShareCheckpoint> {
+        self.checkpoint_count
+            .checked_sub(1)
+            .map(|i| self.checkpoints[i as usize % SHARE_CHECKPOINT_ENTRIES])
+    }
+    
+    /// Has the supply moved more than `threshold_bps` since the latest checkpoint?
+    pub fn should_record(&self, total_shares: u64) -> bool {
+        let Some(latest) = self.latest() else {
+            return true;
+        };
+        let change = latest.total_shares.abs_diff(total_shares) as u128;
+        change * BPS_DENOMINATOR as u128 > latest.total_shares as u128 * self.threshold_bps as u128
+            || (latest.total_shares == 0 && change > 0)
+    }
+    
+    /// Append a checkpoint if the supply moved past the threshold; returns whether one was recorded
+    pub fn maybe_record(&mut self, timestamp: i64, total_shares: u64, nav_e6: i64) -> bool {
+        if !self.should_record(total_shares) {
+            return false;
+        }
+        self.checkpoints[self.checkpoint_count as usize % SHARE_CHECKPOINT_ENTRIES] =
+            ShareCheckpoint { timestamp, total_shares, nav_e6 };
+        self.checkpoint_count = self.checkpoint_count.saturating_add(1);
+        true
+    }
+    
+    /// Checkpoints oldest → newest
+    pub fn recent_checkpoints(&self) -> Vec<ShareCheckpoint> {
+        let len = (self.checkpoint_count as usize).min(SHARE_CHECKPOINT_ENTRIES);
+        let start = self.checkpoint_count as usize - len;
+        (start..self.checkpoint_count as usize)
+            .map(|i| self.checkpoints[i % SHARE_CHECKPOINT_ENTRIES])
+            .collect()
+    }
+    
+    /// Latest retained checkpoint at or before `timestamp`
+    pub fn checkpoint_at(&self, timestamp: i64) -> Option<ShareCheckpoint> {
+        self.recent_checkpoints()
+            .into_iter()
+            .rev()
+            .find(|c| c.timestamp <= timestamp)
+    }
+}
+
+// =============================================================================
+// Position NFTs
+// =============================================================================
+
+/// Discriminator for PositionNFT account
+pub const POSITION_NFT_DISCRIMINATOR: u64 = 0x504F535F4E46545F; // "POS_NFT_"
+
+/// Seed prefix for PositionNFT PDA
+pub const POSITION_NFT_SEED: &[u8] = b"position_nft";
+
+/// Seed prefix for the position NFT mint PDA
+pub const POSITION_NFT_MINT_SEED: &[u8] = b"position_nft_mint";
+
+/// Seed prefix for the escrowed share token account PDA
+pub const POSITION_NFT_ESCROW_SEED: &[u8] = b"position_nft_escrow";
+
+/// Transferable NFT representing a whole LP position
+///
+/// The position's shares sit in a Fund-owned escrow token account and are
+/// locked on the LPPosition with this PDA as lienholder, so they cannot be
+/// redeemed until unwrapped. Whoever holds the 0-decimal, supply-1 NFT can
+/// take over the LPPosition via TransferPositionNFT. This account is the
+/// on-chain metadata (fund + share amount) for marketplaces.
+///
+/// PDA Seeds: ["position_nft", lp_position]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PositionNFT {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund the position belongs to
+    pub fund: Pubkey,
+    
+    /// Wrapped LP position
+    pub lp_position: Pubkey,
+    
+    /// Position NFT mint
+    pub nft_mint: Pubkey,
+    
+    /// Shares held in escrow (0 = unwrapped)
+    pub shares: u64,
+    
+    /// Investor the LPPosition is assigned to
+    pub holder: Pubkey,
+    
+    /// Last wrap timestamp
+    pub wrapped_at: i64,
+    
+    /// TransferPositionNFT calls so far
+    pub transfer_count: u32,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl PositionNFT {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // lp_position
+        + 32  // nft_mint
+        + 8   // shares
+        + 32  // holder
+        + 8   // wrapped_at
+        + 4   // transfer_count
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create a new PositionNFT
+    pub fn new(fund: Pubkey, lp_position: Pubkey, nft_mint: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: POSITION_NFT_DISCRIMINATOR,
+            fund,
+            lp_position,
+            nft_mint,
+            shares: 0,
+            holder: Pubkey::default(),
+            wrapped_at: 0,
+            transfer_count: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for PositionNFT
+    pub fn seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![POSITION_NFT_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    /// PDA seeds for the position NFT mint
+    pub fn mint_seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![POSITION_NFT_MINT_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    /// PDA seeds for the escrowed share token account
+    pub fn escrow_seeds(lp_position: &Pubkey) -> Vec<Vec<u8>> {
+        vec![POSITION_NFT_ESCROW_SEED.to_vec(), lp_position.to_bytes().to_vec()]
+    }
+    
+    /// Shares currently wrapped?
+    pub fn is_wrapped(&self) -> bool {
+        self.shares > 0
+    }
+    
+    /// Lock a position's shares into the NFT
+    pub fn wrap(&mut self, position: &mut LPPosition, self_key: Pubkey, current_ts: i64) -> Result<(), ProgramError> {
+        if self.is_wrapped() || position.nft_wrapped {
+            return Err(crate::error::FundError::PositionNFTWrapped.into());
+        }
+        if position.locked_shares > 0 {
+            return Err(crate::error::FundError::SharesLocked.into());
+        }
+        position.lock_shares(self_key, position.shares)?;
+        position.nft_wrapped = true;
+        self.shares = position.shares;
+        self.holder = position.investor;
+        self.wrapped_at = current_ts;
+        Ok(())
+    }
+    
+    /// Move the position to the NFT's new holder
+    pub fn transfer(&mut self, position: &mut LPPosition, new_holder: Pubkey, current_ts: i64) -> Result<(), ProgramError> {
+        if !self.is_wrapped() || !position.nft_wrapped {
+            return Err(crate::error::FundError::PositionNFTNotWrapped.into());
+        }
+        if position.investor == new_holder {
+            return Err(ProgramError::InvalidArgument);
+        }
+        position.reassign(new_holder, current_ts);
+        self.holder = new_holder;
+        self.transfer_count = self.transfer_count.saturating_add(1);
+        Ok(())
+    }
+    
+    /// Release the escrowed shares; returns the shares unlocked
+    pub fn unwrap(&mut self, position: &mut LPPosition, self_key: &Pubkey) -> Result<u64, ProgramError> {
+        if !self.is_wrapped() || !position.nft_wrapped {
+            return Err(crate::error::FundError::PositionNFTNotWrapped.into());
+        }
+        let shares = self.shares;
+        position.unlock_shares(self_key, shares)?;
+        position.nft_wrapped = false;
+        self.shares = 0;
+        Ok(shares)
+    }
+}
+
+// =============================================================================
+// Share Freeze
+// =============================================================================
+
+/// Discriminator for ShareFreezeRecord account
+pub const SHARE_FREEZE_RECORD_DISCRIMINATOR: u64 = 0x5348525F46525A4E; // "SHR_FRZN"
+
+/// Seed prefix for ShareFreezeRecord PDA
+pub const SHARE_FREEZE_RECORD_SEED: &[u8] = b"share_freeze";
+
+/// Why a share token account was frozen
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShareFreezeReason {
+    /// Not frozen / never frozen
+    #[default]
+    None = 0,
+    /// Holder matched a sanctions list
+    Sanctions = 1,
+    /// Court or regulator order
+    LegalOrder = 2,
+    /// Suspected fraud or stolen funds
+    Fraud = 3,
+    /// Any other compliance reason
+    Other = 4,
+}
+
+/// Compliance record for a frozen share token account
+///
+/// Written by FreezeInvestorShares / ThawInvestorShares; kept after a thaw
+/// so the history stays on-chain.
+///
+/// PDA Seeds: ["share_freeze", share token account]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ShareFreezeRecord {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Fund whose shares are frozen
+    pub fund: Pubkey,
+    
+    /// Share token account
+    pub token_account: Pubkey,
+    
+    /// Owner of the token account when frozen
+    pub owner: Pubkey,
+    
+    /// Reason code of the current / last freeze
+    pub reason: ShareFreezeReason,
+    
+    /// Currently frozen?
+    pub is_frozen: bool,
+    
+    /// Signer of the last freeze (fund manager or admin)
+    pub frozen_by: Pubkey,
+    
+    /// Last freeze timestamp
+    pub frozen_at: i64,
+    
+    /// Signer of the last thaw
+    pub thawed_by: Pubkey,
+    
+    /// Last thaw timestamp (0 = never)
+    pub thawed_at: i64,
+    
+    /// Number of times frozen
+    pub freeze_count: u32,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl ShareFreezeRecord {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 32  // token_account
+        + 32  // owner
+        + 1   // reason
+        + 1   // is_frozen
+        + 32  // frozen_by
+        + 8   // frozen_at
+        + 32  // thawed_by
+        + 8   // thawed_at
+        + 4   // freeze_count
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an empty record
+    pub fn new(fund: Pubkey, token_account: Pubkey, owner: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: SHARE_FREEZE_RECORD_DISCRIMINATOR,
+            fund,
+            token_account,
+            owner,
+            reason: ShareFreezeReason::None,
+            is_frozen: false,
+            frozen_by: Pubkey::default(),
+            frozen_at: 0,
+            thawed_by: Pubkey::default(),
+            thawed_at: 0,
+            freeze_count: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for ShareFreezeRecord
+    pub fn seeds(token_account: &Pubkey) -> Vec<Vec<u8>> {
+        vec![SHARE_FREEZE_RECORD_SEED.to_vec(), token_account.to_bytes().to_vec()]
+    }
+    
+    /// Record a freeze
+    pub fn record_freeze(&mut self, reason: ShareFreezeReason, frozen_by: Pubkey, current_ts: i64) {
+        self.reason = reason;
+        self.is_frozen = true;
+        self.frozen_by = frozen_by;
+        self.frozen_at = current_ts;
+        self.freeze_count = self.freeze_count.saturating_add(1);
+    }
+    
+    /// Record a thaw (the reason of the last freeze is kept)
+    pub fn record_thaw(&mut self, thawed_by: Pubkey, current_ts: i64) {
+        self.is_frozen = false;
+        self.thawed_by = thawed_by;
+        self.thawed_at = current_ts;
+    }
+}
+
+// =============================================================================
+// Investor Portfolio
+// =============================================================================
+
+/// Discriminator for InvestorPortfolio account
+pub const INVESTOR_PORTFOLIO_DISCRIMINATOR: u64 = 0x494E565F504F5254; // "INV_PORT"
+
+/// Seed prefix for InvestorPortfolio PDA
+pub const INVESTOR_PORTFOLIO_SEED: &[u8] = b"investor_portfolio";
+
+/// Funds tracked per portfolio
+pub const MAX_PORTFOLIO_FUNDS: usize = 16;
+
+/// An investor's holding in one fund, as of its last update
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PortfolioEntry {
+    /// Fund PDA
+    pub fund: Pubkey,
+    /// LP position shares
+    pub shares: u64,
+    /// Net invested: deposited - withdrawn (e6)
+    pub invested_e6: i64,
+    /// Fund NAV per share at the last update (e6)
+    pub nav_e6: i64,
+    /// Last update timestamp
+    pub updated_at: i64,
+}
+
+impl PortfolioEntry {
+    /// Serialized size in bytes
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 8;
+
+    /// Value at the recorded NAV (e6)
+    pub fn value_e6(&self) -> i64 {
+        (self.shares as i128 * self.nav_e6 as i128 / 1_000_000).min(i64::MAX as i128) as i64
+    }
+}
+
+/// Consolidated view of a wallet's LP positions across funds
+///
+/// Optional: once created, passing it to DepositToFund / RedeemFromFund keeps
+/// the fund's entry current, so wallets can show one "1024 funds" balance
+/// from a single read. Values use each fund's NAV at the entry's last update.
+///
+/// PDA Seeds: ["investor_portfolio", investor]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InvestorPortfolio {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Wallet the portfolio belongs to
+    pub investor: Pubkey,
+    
+    /// Sum of the entries' net invested (e6)
+    pub total_invested_e6: i64,
+    
+    /// Sum of the entries' value at their last NAV (e6)
+    pub total_value_e6: i64,
+    
+    /// Entries in use (entries[..fund_count])
+    pub fund_count: u8,
+    
+    /// Per-fund holdings
+    pub entries: [PortfolioEntry; MAX_PORTFOLIO_FUNDS],
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Last update timestamp
+    pub updated_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl InvestorPortfolio {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // investor
+        + 8   // total_invested_e6
+        + 8   // total_value_e6
+        + 1   // fund_count
+        + PortfolioEntry::SIZE * MAX_PORTFOLIO_FUNDS // entries
+        + 8   // created_at
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Create an empty portfolio
+    pub fn new(investor: Pubkey, bump: u8, created_at: i64) -> Self {
+        Self {
+            discriminator: INVESTOR_PORTFOLIO_DISCRIMINATOR,
+            investor,
+            total_invested_e6: 0,
+            total_value_e6: 0,
+            fund_count: 0,
+            entries: [PortfolioEntry::default(); MAX_PORTFOLIO_FUNDS],
+            created_at,
+            updated_at: created_at,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for InvestorPortfolio
+    pub fn seeds(investor: &Pubkey) -> Vec<Vec<u8>> {
+        vec![INVESTOR_PORTFOLIO_SEED.to_vec(), investor.to_bytes().to_vec()]
+    }
+    
+    /// Entries in use
+    pub fn active_entries(&self) -> &[PortfolioEntry] {
+        &self.entries[..self.fund_count as usize]
+    }
+    
+    /// Entry for `fund`, if tracked
+    pub fn entry(&self, fund: &Pubkey) -> Option<&PortfolioEntry> {
+        self.active_entries().iter().find(|e| e.fund == *fund)
+    }
+    
+    /// Mirror an LP position; an emptied position drops its fund from the list
+    pub fn sync_position(&mut self, position: &LPPosition, nav_e6: i64, current_ts: i64) -> Result<(), ProgramError> {
+        let count = self.fund_count as usize;
+        let index = self.entries[..count].iter().position(|e| e.fund == position.fund);
+        
+        if position.shares == 0 {
+            if let Some(i) = index {
+                // Swap-remove keeps the list dense
+                self.entries[i] = self.entries[count - 1];
+                self.entries[count - 1] = PortfolioEntry::default();
+                self.fund_count -= 1;
+            }
+        } else {
+            let i = match index {
+                Some(i) => i,
+                None if count < MAX_PORTFOLIO_FUNDS => {
+                    self.fund_count += 1;
+                    count
+                }
+                None => return Err(crate::error::FundError::InvestorPortfolioFull.into()),
+            };
+            self.entries[i] = PortfolioEntry {
+                fund: position.fund,
+                shares: position.shares,
+                invested_e6: position.total_deposited_e6.saturating_sub(position.total_withdrawn_e6),
+                nav_e6,
+                updated_at: current_ts,
+            };
+        }
+        
+        let active = &self.entries[..self.fund_count as usize];
+        self.total_invested_e6 = active.iter().fold(0i64, |acc, e| acc.saturating_add(e.invested_e6));
+        self.total_value_e6 = active.iter().fold(0i64, |acc, e| acc.saturating_add(e.value_e6()));
+        self.updated_at = current_ts;
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Fund Coverage
+// =============================================================================
+
+/// Discriminator for FundCoveragePolicy account
+pub const FUND_COVERAGE_POLICY_DISCRIMINATOR: u64 = 0x4644435F504F4C59; // "FDC_POLY"
+
+/// Discriminator for FundCoverageClaim account
+pub const FUND_COVERAGE_CLAIM_DISCRIMINATOR: u64 = 0x4644435F434C4D5F; // "FDC_CLM_"
+
+/// Seed prefix for FundCoveragePolicy PDA
+pub const FUND_COVERAGE_POLICY_SEED: &[u8] = b"fund_coverage";
+
+/// Seed prefix for FundCoverageClaim PDA
+pub const FUND_COVERAGE_CLAIM_SEED: &[u8] = b"fund_coverage_claim";
+
+/// Maximum annual premium (10% of fund value)
+pub const MAX_COVERAGE_PREMIUM_BPS: u16 = 1_000;
+
+/// Maximum share of a qualifying loss compensated (coverage is partial)
+pub const MAX_COVERAGE_BPS: u16 = 5_000;
+
+/// Maximum drawdown LPs absorb before a loss qualifies
+pub const MAX_COVERAGE_DEDUCTIBLE_BPS: u16 = 5_000;
+
+/// Claims stay open this long after the premium was last paid
+pub const COVERAGE_PREMIUM_GRACE_SECS: i64 = 7 * 86400;
+
+/// Drawdown insurance bought by a fund for its LPs
+///
+/// The manager pays `premium_bps` of fund value per year from the vault
+/// into the Insurance Fund. When NAV falls more than `deductible_bps` below
+/// the inception NAV and RecordPnL has booked net trading losses since
+/// inception, LPs claim `coverage_bps` of the further drop per share from
+/// the Insurance Fund, up to `coverage_limit_e6` in total.
+///
+/// PDA Seeds: ["fund_coverage", fund]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct FundCoveragePolicy {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Covered fund
+    pub fund: Pubkey,
+    
+    /// Annual premium (bps of fund value)
+    pub premium_bps: u16,
+    
+    /// Share of the qualifying loss compensated (bps)
+    pub coverage_bps: u16,
+    
+    /// Drawdown from inception NAV absorbed by LPs (bps)
+    pub deductible_bps: u16,
+    
+    /// Maximum total compensation (e6)
+    pub coverage_limit_e6: i64,
+    
+    /// Fund NAV when the policy was registered (e6)
+    pub inception_nav_e6: i64,
+    
+    /// Fund trading PnL when the policy was registered (e6)
+    pub inception_trading_pnl_e6: i64,
+    
+    /// Premiums are settled up to this timestamp
+    pub premium_paid_through: i64,
+    
+    /// Premiums paid into the Insurance Fund (e6)
+    pub total_premiums_e6: i64,
+    
+    /// Compensation paid to LPs (e6)
+    pub total_claims_e6: i64,
+    
+    /// Policy in force (false once cancelled)
+    pub is_active: bool,
+    
+    /// Registration timestamp
+    pub created_at: i64,
+    
+    /// Cancellation timestamp (0 = active)
+    pub cancelled_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 32],
+}
+
+impl FundCoveragePolicy {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // fund
+        + 2   // premium_bps
+        + 2   // coverage_bps
+        + 2   // deductible_bps
+        + 8   // coverage_limit_e6
+        + 8   // inception_nav_e6
+        + 8   // inception_trading_pnl_e6
+        + 8   // premium_paid_through
+        + 8   // total_premiums_e6
+        + 8   // total_claims_e6
+        + 1   // is_active
+        + 8   // created_at
+        + 8   // cancelled_at
+        + 1   // bump
+        + 32; // reserved
+    
+    /// Register a policy at the fund's current NAV / trading PnL
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        fund: Pubkey,
+        premium_bps: u16,
+        coverage_bps: u16,
+        deductible_bps: u16,
+        coverage_limit_e6: i64,
+        inception_nav_e6: i64,
+        inception_trading_pnl_e6: i64,
+        bump: u8,
+        created_at: i64,
+    ) -> Self {
+        Self {
+            discriminator: FUND_COVERAGE_POLICY_DISCRIMINATOR,
+            fund,
+            premium_bps,
+            coverage_bps,
+            deductible_bps,
+            coverage_limit_e6,
+            inception_nav_e6,
+            inception_trading_pnl_e6,
+            premium_paid_through: created_at,
+            total_premiums_e6: 0,
+            total_claims_e6: 0,
+            is_active: true,
+            created_at,
+            cancelled_at: 0,
+            bump,
+            reserved: [0u8; 32],
+        }
+    }
+    
+    /// PDA seeds for FundCoveragePolicy
+    pub fn seeds(fund: &Pubkey) -> Vec<Vec<u8>> {
+        vec![FUND_COVERAGE_POLICY_SEED.to_vec(), fund.to_bytes().to_vec()]
+    }
+    
+    /// Validate policy terms
+    pub fn validate_terms(
+        premium_bps: u16,
+        coverage_bps: u16,
+        deductible_bps: u16,
+        coverage_limit_e6: i64,
+    ) -> Result<(), ProgramError> {
+        if premium_bps == 0
+            || premium_bps > MAX_COVERAGE_PREMIUM_BPS
+            || coverage_bps == 0
+            || coverage_bps > MAX_COVERAGE_BPS
+            || deductible_bps > MAX_COVERAGE_DEDUCTIBLE_BPS
+            || coverage_limit_e6 <= 0
+        {
+            return Err(crate::error::FundError::InvalidCoverageTerms.into());
+        }
+        Ok(())
+    }
+    
+    /// Premium accrued since `premium_paid_through` on `fund_value_e6`
+    pub fn premium_due_e6(&self, fund_value_e6: i64, current_ts: i64) -> i64 {
+        let elapsed = current_ts.saturating_sub(self.premium_paid_through).max(0) as u128;
+        (fund_value_e6.max(0) as u128 * self.premium_bps as u128 * elapsed
+            / BPS_DENOMINATOR as u128
+            / SECONDS_PER_YEAR as u128)
+            .min(i64::MAX as u128) as i64
+    }
+    
+    /// Settle the premium up to `current_ts`
+    pub fn record_premium(&mut self, premium_e6: i64, current_ts: i64) {
+        self.total_premiums_e6 = self.total_premiums_e6.saturating_add(premium_e6);
+        self.premium_paid_through = current_ts;
+    }
+    
+    /// Active with premiums paid recently enough to claim?
+    pub fn is_in_force(&self, current_ts: i64) -> bool {
+        self.is_active && current_ts <= self.premium_paid_through.saturating_add(COVERAGE_PREMIUM_GRACE_SECS)
+    }
+    
+    /// NAV below which losses qualify (e6)
+    pub fn trigger_nav_e6(&self) -> i64 {
+        let retained = BPS_DENOMINATOR.saturating_sub(self.deductible_bps as u64) as i128;
+        (self.inception_nav_e6 as i128 * retained / BPS_DENOMINATOR as i128) as i64
+    }
+    
+    /// Compensation owed for `shares` whose loss was last covered down to `reference_nav_e6`
+    ///
+    /// Only the drop below both the trigger NAV and the reference counts, and
+    /// only while the fund shows net trading losses since inception, so fees
+    /// or expenses alone never trigger a payout. Capped by the remaining limit.
+    pub fn compensation_e6(
+        &self,
+        shares: u64,
+        reference_nav_e6: i64,
+        current_nav_e6: i64,
+        trading_pnl_e6: i64,
+    ) -> i64 {
+        if trading_pnl_e6 >= self.inception_trading_pnl_e6 {
+            return 0;
+        }
+        let drop = reference_nav_e6.min(self.trigger_nav_e6()).saturating_sub(current_nav_e6);
+        if drop <= 0 {
+            return 0;
+        }
+        let loss = shares as u128 * drop as u128 / 1_000_000;
+        let payout = (loss * self.coverage_bps as u128 / BPS_DENOMINATOR as u128).min(i64::MAX as u128) as i64;
+        payout.min(self.coverage_limit_e6.saturating_sub(self.total_claims_e6).max(0))
+    }
+    
+    /// Record a paid claim
+    pub fn record_claim(&mut self, amount_e6: i64) {
+        self.total_claims_e6 = self.total_claims_e6.saturating_add(amount_e6);
+    }
+    
+    /// Stop the policy; no further premiums or claims
+    pub fn cancel(&mut self, current_ts: i64) {
+        self.is_active = false;
+        self.cancelled_at = current_ts;
+    }
+}
+
+/// An LP's claims against a FundCoveragePolicy
+///
+/// PDA Seeds: ["fund_coverage_claim", policy, investor]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct FundCoverageClaim {
+    /// Discriminator for account type
+    pub discriminator: u64,
+    
+    /// Policy claimed against
+    pub policy: Pubkey,
+    
+    /// Claiming LP
+    pub investor: Pubkey,
+    
+    /// NAV the LP's loss has been compensated down to (e6)
+    pub compensated_to_nav_e6: i64,
+    
+    /// Compensation received (e6)
+    pub total_claimed_e6: i64,
+    
+    /// Last claim timestamp
+    pub last_claim_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// Reserved for future use
+    pub reserved: [u8; 16],
+}
+
+impl FundCoverageClaim {
+    /// Account size in bytes
+    pub const SIZE: usize = 8  // discriminator
+        + 32  // policy
+        + 32  // investor
+        + 8   // compensated_to_nav_e6
+        + 8   // total_claimed_e6
+        + 8   // last_claim_at
+        + 1   // bump
+        + 16; // reserved
+    
+    /// Create an empty claim record
+    pub fn new(policy: Pubkey, investor: Pubkey, bump: u8) -> Self {
+        Self {
+            discriminator: FUND_COVERAGE_CLAIM_DISCRIMINATOR,
+            policy,
+            investor,
+            compensated_to_nav_e6: 0,
+            total_claimed_e6: 0,
+            last_claim_at: 0,
+            bump,
+            reserved: [0u8; 16],
+        }
+    }
+    
+    /// PDA seeds for FundCoverageClaim
+    pub fn seeds(policy: &Pubkey, investor: &Pubkey) -> Vec<Vec<u8>> {
+        vec![
+            FUND_COVERAGE_CLAIM_SEED.to_vec(),
+            policy.to_bytes().to_vec(),
+            investor.to_bytes().to_vec(),
+        ]
+    }
+    
+    /// NAV the next claim is measured from: the LP's entry NAV, or where the last claim stopped
+    pub fn reference_nav_e6(&self, position_nav_e6: i64) -> i64 {
+        if self.last_claim_at == 0 {
+            position_nav_e6
+        } else {
+            self.compensated_to_nav_e6.min(position_nav_e6)
+        }
+    }
+    
+    /// Record a paid claim at `nav_e6`
+    pub fn record_claim(&mut self, amount_e6: i64, nav_e6: i64, current_ts: i64) {
+        self.compensated_to_nav_e6 = nav_e6;
+        self.total_claimed_e6 = self.total_claimed_e6.saturating_add(amount_e6);
+        self.last_claim_at = current_ts;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+    use crate::utils::SECONDS_PER_YEAR;
+
+    #[test]
+    fn test_fund_config_size() {
+        assert!(FundConfig::SIZE > 0);
+        println!("FundConfig SIZE: {}", FundConfig::SIZE);
+    }
+
+    #[test]
+    fn test_fund_config_feature_flags() {
+        let mut config = FundConfig::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            255,
+        );
+        assert_eq!(config.try_to_vec().unwrap().len(), FundConfig::SIZE);
+
+        // Everything enabled by default
+        assert!(config.is_feature_enabled(ALL_FEATURES));
+
+        config.set_features_enabled(FEATURE_REFERRALS | FEATURE_RELAYER, false);
+        assert!(!config.is_feature_enabled(FEATURE_REFERRALS));
+        assert!(!config.is_feature_enabled(FEATURE_SQUARE_PAYMENTS | FEATURE_RELAYER));
+        assert!(config.is_feature_enabled(FEATURE_SQUARE_PAYMENTS | FEATURE_PM_FEES));
+
+        config.set_features_enabled(FEATURE_RELAYER, true);
+        assert!(config.is_feature_enabled(FEATURE_RELAYER));
+        assert!(!config.is_feature_enabled(FEATURE_REFERRALS));
+        
+        // The relayer pause is separate from the feature bits and the program pause
+        config.relayer_paused = true;
+        assert!(config.is_feature_enabled(FEATURE_RELAYER));
+        assert!(!config.is_paused);
+    }
+
+    #[test]
+    fn test_fund_size() {
+        assert!(Fund::SIZE > 0);
+        println!("Fund SIZE: {}", Fund::SIZE);
+    }
+
+    #[test]
+    fn test_lp_position_size() {
+        assert!(LPPosition::SIZE > 0);
+        println!("LPPosition SIZE: {}", LPPosition::SIZE);
+    }
+
+    #[test]
+    fn test_fund_creation() {
+        let manager = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let fee_config = FeeConfig::new(200, 2000);
+        
+        let fund = Fund::new(
+            manager,
+            "Test Fund",
+            254,
+            vault,
+            mint,
+            fee_config,
+            1,
+            1000000,
+        );
+        
+        assert_eq!(fund.manager, manager);
+        assert_eq!(fund.name_str(), "Test Fund");
+        assert!(fund.is_open);
+        assert!(!fund.is_paused);
+        assert_eq!(fund.stats.current_nav_e6, INITIAL_NAV_E6);
+    }
+
+    #[test]
+    fn test_fund_deposit_withdrawal() {
+        let manager = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let fee_config = FeeConfig::new(200, 2000);
+        
+        let mut fund = Fund::new(
+            manager,
+            "Test Fund",
+            254,
+            vault,
+            mint,
+            fee_config,
+            1,
+            1000000,
+        );
+        
+        // Record deposit
+        fund.record_deposit(100_000_000, 100_000_000).unwrap();
+        assert_eq!(fund.stats.total_deposits_e6, 100_000_000);
+        assert_eq!(fund.stats.total_shares, 100_000_000);
+        
+        // Record withdrawal
+        fund.record_withdrawal(50_000_000, 50_000_000).unwrap();
+        assert_eq!(fund.stats.total_withdrawals_e6, 50_000_000);
+        assert_eq!(fund.stats.total_shares, 50_000_000);
+    }
+
+    #[test]
+    fn test_lp_position() {
+        let fund = Pubkey::new_unique();
+        let investor = Pubkey::new_unique();
+        
+        let mut position = LPPosition::new(
+            fund,
+            investor,
+            100_000_000, // 100 shares
+            1_000_000,   // NAV = 1.0
+            100_000_000, // 100 USDC
+            1000000,
+            254,
+        );
+        
+        // Check current value at NAV = 1.0
+        assert_eq!(position.current_value(1_000_000), 100_000_000);
+        
+        // Check current value at NAV = 1.5
+        assert_eq!(position.current_value(1_500_000), 150_000_000);
+        
+        // Check unrealized PnL at NAV = 1.5
+        assert_eq!(position.unrealized_pnl(1_500_000), 50_000_000);
+        
+        // Add more shares
+        position.add_shares(50_000_000, 50_000_000, 1_000_000, 2000000).unwrap();
+        assert_eq!(position.shares, 150_000_000);
+        assert_eq!(position.total_deposited_e6, 150_000_000);
+        
+        // Remove shares
+        position.remove_shares(25_000_000, 25_000_000, 3000000).unwrap();
+        assert_eq!(position.shares, 125_000_000);
+        assert_eq!(position.total_withdrawn_e6, 25_000_000);
+    }
+
+    #[test]
+    fn test_fund_stats() {
+        let mut stats = FundStats::new(1000000);
+        
+        assert_eq!(stats.current_nav_e6, INITIAL_NAV_E6);
+        assert_eq!(stats.high_water_mark_e6, INITIAL_NAV_E6);
+        assert_eq!(stats.total_shares, 0);
+        
+        // Simulate deposits
+        stats.total_deposits_e6 = 100_000_000;
+        stats.total_shares = 100_000_000;
+        stats.update_nav().unwrap();
+        
+        assert_eq!(stats.current_nav_e6, 1_000_000); // NAV = 1.0
+        
+        // Simulate profit
+        stats.total_realized_pnl_e6 = 20_000_000;
+        stats.update_nav().unwrap();
+        stats.update_hwm();
+        
+        // NAV should increase: (100 - 0 + 20 - 0 - 0) / 100 = 1.2
+        assert_eq!(stats.current_nav_e6, 1_200_000);
+        assert_eq!(stats.high_water_mark_e6, 1_200_000);
+    }
+
+    // === Insurance Fund Config Tests ===
+
+    #[test]
+    fn test_insurance_fund_config_size() {
+        assert!(InsuranceFundConfig::SIZE > 0);
+        println!("InsuranceFundConfig SIZE: {}", InsuranceFundConfig::SIZE);
+    }
+
+    #[test]
+    fn test_insurance_fund_config_creation() {
+        let fund = Pubkey::new_unique();
+        let caller = Pubkey::new_unique();
+        
+        let mut config = InsuranceFundConfig::new(
+            fund,
+            254,
+            100_000_000,      // 100 USDC threshold
+            3600,             // 1 hour delay
+            caller,
+            1000000,
+        );
+        
+        assert_eq!(config.fund, fund);
+        assert_eq!(config.adl_trigger_threshold_e6, 100_000_000);
+        assert_eq!(config.withdrawal_delay_secs, 3600);
+        assert_eq!(config.total_liquidation_income_e6, 0);
+        assert!(!config.is_adl_in_progress);
+        assert!(config.can_deposit() && config.can_withdraw());
+        
+        // ADL pauses both deposits and redemptions
+        config.set_adl_in_progress(true);
+        assert!(!config.can_deposit() && !config.can_withdraw());
+    }
+
+    #[test]
+    fn test_pending_insurance_config_update() {
+        let caller = Pubkey::new_unique();
+        let mut config = InsuranceFundConfig::new(
+            Pubkey::new_unique(),
+            254,
+            100_000_000,
+            3600,
+            caller,
+            1000000,
+        );
+        
+        let pending = PendingInsuranceConfigUpdate::new(
+            Pubkey::new_unique(),
+            None,
+            Some(7200),
+            None,
+            253,
+            1700000000,
+        );
+        assert_eq!(pending.try_to_vec().unwrap().len(), PendingInsuranceConfigUpdate::SIZE);
+        assert_eq!(pending.update_mask, INSURANCE_UPDATE_WITHDRAWAL_DELAY);
+        assert!(!pending.is_executable(1700000000 + PendingInsuranceConfigUpdate::TIMELOCK_SECS - 1));
+        assert!(pending.is_executable(1700000000 + PendingInsuranceConfigUpdate::TIMELOCK_SECS));
+        
+        // Only the scheduled field changes
+        pending.apply(&mut config);
+        assert_eq!(config.withdrawal_delay_secs, 7200);
+        assert_eq!(config.adl_trigger_threshold_e6, 100_000_000);
+        assert_eq!(config.authorized_caller, caller);
+        
+        let new_caller = Pubkey::new_unique();
+        let pending = PendingInsuranceConfigUpdate::new(
+            Pubkey::new_unique(),
+            Some(0),
+            None,
+            Some(new_caller),
+            253,
+            1700000000,
+        );
+        pending.apply(&mut config);
+        assert_eq!(config.adl_trigger_threshold_e6, 0);
+        assert_eq!(config.withdrawal_delay_secs, 7200);
+        assert_eq!(config.authorized_caller, new_caller);
+    }
+
+    #[test]
+    fn test_insurance_fund_adl_trigger_conditions() {
+        let fund = Pubkey::new_unique();
+        let caller = Pubkey::new_unique();
+        
+        let mut config = InsuranceFundConfig::new(
+            fund,
+            254,
+            100_000_000,      // 100 USDC threshold
+            3600,
+            caller,
+            1000000,
+        );
+        
+        // 设置1小时前余额
+        config.balance_1h_ago_e6 = 1_000_000_000; // 1000 USDC
+        
+        // 测试条件1: 穿仓触发
+        assert_eq!(
+            config.should_trigger_adl(50_000_000, 100_000_000, None), // 余额50, 穿仓100
+            ADLTriggerReason::Bankruptcy
+        );
+        
+        // 测试条件2: 余额不足触发
+        assert_eq!(
+            config.should_trigger_adl(50_000_000, 0, None), // 余额50 < 阈值100
+            ADLTriggerReason::InsufficientBalance
+        );
+        
+        // 测试条件3: 1小时下降30%触发
+        assert_eq!(
+            config.should_trigger_adl(600_000_000, 0, None), // 余额600 < 1000*0.7=700
+            ADLTriggerReason::RapidDecline
+        );
+        
+        // 瞬时下跌但 TWAP 价值仍高于 70%: 不触发
+        assert_eq!(
+            config.should_trigger_adl(600_000_000, 0, Some(900_000_000)),
+            ADLTriggerReason::None
+        );
+        assert_eq!(
+            config.should_trigger_adl(600_000_000, 0, Some(650_000_000)),
+            ADLTriggerReason::RapidDecline
+        );
+        
+        // 测试正常情况: 不触发
+        assert_eq!(
+            config.should_trigger_adl(800_000_000, 0, None), // 余额800 > 阈值100, > 700
+            ADLTriggerReason::None
+        );
+    }
+
+    #[test]
+    fn test_insurance_fund_cover_shortfall() {
+        let fund = Pubkey::new_unique();
+        let caller = Pubkey::new_unique();
+        
+        let mut config = InsuranceFundConfig::new(
+            fund,
+            254,
+            100_000_000,
+            3600,
+            caller,
+            1000000,
+        );
+        
+        // 情况1: 完全覆盖
+        let (covered, remaining) = config.cover_shortfall(500_000_000, 1_000_000_000);
+        assert_eq!(covered, 500_000_000);
+        assert_eq!(remaining, 0);
+        assert_eq!(config.total_shortfall_payout_e6, 500_000_000);
+        
+        // 情况2: 部分覆盖
+        let (covered, remaining) = config.cover_shortfall(600_000_000, 400_000_000);
+        assert_eq!(covered, 400_000_000);
+        assert_eq!(remaining, 200_000_000);
+        assert_eq!(config.total_shortfall_payout_e6, 900_000_000);
+    }
+
+    #[test]
+    fn test_insurance_fund_income_tracking() {
+        let fund = Pubkey::new_unique();
+        let caller = Pubkey::new_unique();
+        
+        let mut config = InsuranceFundConfig::new(
+            fund,
+            254,
+            100_000_000,
+            3600,
+            caller,
+            1000000,
+        );
+        
+        // 添加清算收入
+        config.add_liquidation_income(100_000_000);
+        assert_eq!(config.total_liquidation_income_e6, 100_000_000);
+        
+        // 添加ADL盈余
+        config.add_adl_profit(50_000_000);
+        assert_eq!(config.total_adl_profit_e6, 50_000_000);
+        
+        // 检查总收入
+        assert_eq!(config.total_income_e6(), 150_000_000);
+        
+        // 模拟支出
+        config.cover_shortfall(30_000_000, 1_000_000_000);
+        
+        // 检查净收入
+        assert_eq!(config.net_income_e6(), 120_000_000); // 150 - 30
+    }
+
+    // === Square Payment Record Tests ===
+
+    #[test]
+    fn test_square_payment_record_size() {
+        assert!(SquarePaymentRecord::SIZE > 0);
+        println!("SquarePaymentRecord SIZE: {}", SquarePaymentRecord::SIZE);
+    }
+
+    #[test]
+    fn test_square_payment_record_creation() {
+        let payer = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let content_id = 12345u64;
+        let payment_type = SquarePaymentType::KnowledgePurchase;
+        let total_amount = 100_000_000i64; // 100 USDC
+        let creator_share_bps = 9000u16; // 90%
+        let timestamp = 1700000000i64;
+        
+        let record = SquarePaymentRecord::new(
+            payer,
+            creator,
+            content_id,
+            payment_type,
+            total_amount,
+            creator_share_bps,
+            timestamp,
+            0, // no subscription period
+            b"Test payment",
+            1,
+            254,
+        );
+        
+        assert_eq!(record.payer, payer);
+        assert_eq!(record.creator, creator);
+        assert_eq!(record.content_id, content_id);
+        assert_eq!(record.payment_type, SquarePaymentType::KnowledgePurchase);
+        assert_eq!(record.total_amount_e6, 100_000_000);
+        assert_eq!(record.creator_amount_e6, 90_000_000); // 90%
+        assert_eq!(record.platform_amount_e6, 10_000_000); // 10%
+        assert_eq!(record.creator_share_bps, 9000);
+        assert_eq!(record.payment_ts, timestamp);
+        assert!(!record.is_subscription());
+    }
+
+    #[test]
+    fn test_square_payment_subscription() {
+        let payer = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        
+        let record = SquarePaymentRecord::new(
+            payer,
+            creator,
+            99999,
+            SquarePaymentType::Subscription,
+            50_000_000, // 50 USDC
+            8500, // 85%
+            1700000000,
+            12, // 12 months
+            b"Monthly sub",
+            1,
+            254,
+        );
+        
+        assert!(record.is_subscription());
+        assert_eq!(record.subscription_period, 12);
+        assert_eq!(record.creator_amount_e6, 42_500_000); // 85%
+        assert_eq!(record.platform_amount_e6, 7_500_000); // 15%
+    }
+
+    #[test]
+    fn test_square_payment_live_donation() {
+        let payer = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        
+        let record = SquarePaymentRecord::new(
+            payer,
+            creator,
+            1,
+            SquarePaymentType::LiveDonation,
+            10_000_000, // 10 USDC
+            7000, // 70%
+            1700000000,
+            0,
+            b"Great stream!",
+            1,
+            254,
+        );
+        
+        assert_eq!(record.payment_type, SquarePaymentType::LiveDonation);
+        assert_eq!(record.creator_amount_e6, 7_000_000); // 70%
+        assert_eq!(record.platform_amount_e6, 3_000_000); // 30%
+        assert_eq!(record.memo_str(), "Great stream!");
+    }
+
+    #[test]
+    fn test_square_payment_memo_truncation() {
+        let payer = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        
+        // Test with a long memo that should be truncated
+        let long_memo = b"This is a very long memo that exceeds 32 bytes and should be truncated";
+        let record = SquarePaymentRecord::new(
+            payer,
+            creator,
+            1,
+            SquarePaymentType::KnowledgePurchase,
+            10_000_000,
+            9000,
+            1700000000,
+            0,
+            long_memo,
+            1,
+            254,
+        );
+        
+        // Memo should be truncated to 32 bytes
+        assert_eq!(record.memo.len(), 32);
+        // First 32 bytes should match
+        assert_eq!(&record.memo[..], &long_memo[..32]);
+    }
+
+    #[test]
+    fn test_square_payment_seeds() {
+        let payer = Pubkey::new_unique();
+        let nonce = 7u64;
+        
+        let seeds = SquarePaymentRecord::seeds(&payer, nonce);
+        
+        assert_eq!(seeds.len(), 3);
+        assert_eq!(seeds[0], SQUARE_PAYMENT_RECORD_SEED.to_vec());
+        assert_eq!(seeds[1], payer.to_bytes().to_vec());
+        assert_eq!(seeds[2], nonce.to_le_bytes().to_vec());
+    }
+
+    // === Referral Config Tests ===
+
+    #[test]
+    fn test_referral_config_size() {
+        assert!(ReferralConfig::SIZE > 0);
+        println!("ReferralConfig SIZE: {}", ReferralConfig::SIZE);
+    }
+
+    #[test]
+    fn test_referral_config_creation() {
+        let authority = Pubkey::new_unique();
+        let vault_program = Pubkey::new_unique();
+        
+        let config = ReferralConfig::new(
+            authority,
+            vault_program,
+            DEFAULT_REFERRER_SHARE_BPS,  // 20%
+            DEFAULT_REFEREE_DISCOUNT_BPS, // 10%
+            254,
+            1700000000,
+        );
+        
+        assert_eq!(config.authority, authority);
+        assert_eq!(config.referrer_share_bps, 2000);
+        assert_eq!(config.referee_discount_bps, 1000);
+        assert!(!config.is_paused);
+        assert_eq!(config.total_referral_links, 0);
+    }
+
+    #[test]
+    fn test_referral_config_vip_bonus() {
+        let authority = Pubkey::new_unique();
+        let vault_program = Pubkey::new_unique();
+        
+        let config = ReferralConfig::new(
+            authority,
+            vault_program,
+            2000, // 20%
+            1000, // 10%
+            254,
+            1700000000,
+        );
+        
+        // VIP 0: 20% base + 0% bonus = 20%
+        assert_eq!(config.get_referrer_share(0), 2000);
+        assert_eq!(config.get_referee_discount(0), 1000);
+        
+        // VIP 3: 20% base + 10% bonus = 30%
+        assert_eq!(config.get_referrer_share(3), 3000);
+        assert_eq!(config.get_referee_discount(3), 2000);
+        
+        // VIP 5: 20% base + 20% bonus = 40%
+        assert_eq!(config.get_referrer_share(5), 4000);
+        assert_eq!(config.get_referee_discount(5), 3000);
+    }
+
+    #[test]
+    fn test_referral_reward_calculation() {
+        let authority = Pubkey::new_unique();
+        let vault_program = Pubkey::new_unique();
+        
+        let config = ReferralConfig::new(
+            authority,
+            vault_program,
+            2000, // 20%
+            1000, // 10%
+            254,
+            1700000000,
+        );
+        
+        // 测试: $100 手续费, VIP 0
+        let (referrer_reward, referee_discount, platform_income) = 
+            config.calculate_rewards(100_000_000, 0, 0);
+        
+        // 被邀请人折扣: $100 * 10% = $10
+        assert_eq!(referee_discount, 10_000_000);
+        // 实际收费: $100 - $10 = $90
+        // 邀请人返佣: $90 * 20% = $18
+        assert_eq!(referrer_reward, 18_000_000);
+        // 平台收入: $90 - $18 = $72
+        assert_eq!(platform_income, 72_000_000);
+        
+        // 测试: $100 手续费, VIP 3 (取较高)
+        let (referrer_reward, referee_discount, platform_income) = 
+            config.calculate_rewards(100_000_000, 3, 1);
+        
+        // VIP 3 折扣: 10% + 10% = 20%
+        // 被邀请人折扣: $100 * 20% = $20
+        assert_eq!(referee_discount, 20_000_000);
+        // 实际收费: $100 - $20 = $80
+        // VIP 3 分成: 20% + 10% = 30%
+        // 邀请人返佣: $80 * 30% = $24
+        assert_eq!(referrer_reward, 24_000_000);
+        // 平台收入: $80 - $24 = $56
+        assert_eq!(platform_income, 56_000_000);
+    }
+
+    #[test]
+    fn test_referral_funded_reward() {
+        let mut config = ReferralConfig::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            2000, // 20%
+            1000,
+            254,
+            1700000000,
+        );
+        
+        // $90 已收手续费: 基础 20% → $18, 自定义 30% → $27
+        assert_eq!(config.referrer_share_of_fee(90_000_000, 0), 18_000_000);
+        assert_eq!(config.referrer_share_of_fee(90_000_000, 3000), 27_000_000);
+        assert_eq!(config.referrer_share_of_fee(-1, 0), 0);
+        
+        // 划入金库的奖励同时计入累计返佣, 二者保持一致
+        config.record_funded_reward(18_000_000, 1700000100);
+        assert_eq!(config.total_rewards_paid_e6, 18_000_000);
+        assert_eq!(config.total_rewards_funded_e6, 18_000_000);
+        assert_eq!(config.last_update_ts, 1700000100);
+        assert_eq!(config.try_to_vec().unwrap().len(), ReferralConfig::SIZE);
+        
+        // 邀请人领取: link 只累计已入库的奖励, 领取后清零
+        let mut link = ReferralLink::new(Pubkey::new_unique(), b"TEST123", 254, 1700000000);
+        link.record_reward(5_000_000, 0, 0, 1700000050);
+        link.record_funded_reward(18_000_000, 1700000100);
+        assert_eq!(link.total_rewards_earned_e6, 23_000_000);
+        assert_eq!(link.unclaimed_rewards_e6, 18_000_000);
+        assert_eq!(link.claim_rewards(1700000200), 18_000_000);
+        assert_eq!(link.claim_rewards(1700000300), 0);
+        config.record_claimed_reward(18_000_000, 1700000200);
+        assert_eq!(config.total_rewards_claimed_e6, 18_000_000);
+        assert_eq!(link.try_to_vec().unwrap().len(), ReferralLink::SIZE);
+    }
+
+    // === Referral Link Tests ===
+
+    #[test]
+    fn test_referral_link_size() {
+        assert!(ReferralLink::SIZE > 0);
+        println!("ReferralLink SIZE: {}", ReferralLink::SIZE);
+    }
+
+    #[test]
+    fn test_referral_link_creation() {
+        let referrer = Pubkey::new_unique();
+        let code = b"ALICE2024";
+        
+        let link = ReferralLink::new(referrer, code, 254, 1700000000);
+        
+        assert_eq!(link.referrer, referrer);
+        assert_eq!(link.code_str(), "ALICE2024");
+        assert!(link.is_active);
+        assert_eq!(link.referred_count, 0);
+        assert_eq!(link.total_rewards_earned_e6, 0);
+    }
+
+    #[test]
+    fn test_referral_link_statistics() {
+        let referrer = Pubkey::new_unique();
+        let mut link = ReferralLink::new(referrer, b"TEST123", 254, 1700000000);
+        
+        assert_eq!(link.try_to_vec().unwrap().len(), ReferralLink::SIZE);
+        assert_eq!(link.last_update_ts, 1700000000);
+        
+        // 记录新邀请
+        link.record_referral(1700000100);
+        assert_eq!(link.referred_count, 1);
+        assert_eq!(link.last_update_ts, 1700000100);
+        
+        // 记录返佣
+        link.record_reward(18_000_000, 10_000_000, 1_000_000_000, 1700000200);
+        assert_eq!(link.total_rewards_earned_e6, 18_000_000);
+        assert_eq!(link.total_discounts_given_e6, 10_000_000);
+        assert_eq!(link.total_volume_e6, 1_000_000_000);
+        assert_eq!(link.last_update_ts, 1700000200);
+        
+        // 停用
+        link.deactivate(1700000300);
+        assert!(!link.is_active);
+        assert_eq!((link.deactivated_at, link.last_update_ts), (1700000300, 1700000300));
+    }
+
+    // === Referral Binding Tests ===
+
+    #[test]
+    fn test_referral_binding_size() {
+        assert!(ReferralBinding::SIZE > 0);
+        println!("ReferralBinding SIZE: {}", ReferralBinding::SIZE);
+    }
+
+    #[test]
+    fn test_referral_binding_creation() {
+        let referee = Pubkey::new_unique();
+        let referrer = Pubkey::new_unique();
+        let link = Pubkey::new_unique();
+        
+        let binding = ReferralBinding::new(referee, referrer, link, 254, 1700000000);
+        
+        assert_eq!(binding.referee, referee);
+        assert_eq!(binding.referrer, referrer);
+        assert_eq!(binding.referral_link, link);
+        assert_eq!(binding.trade_count, 0);
+    }
+
+    #[test]
+    fn test_referral_binding_expiry_and_rebind() {
+        let mut config = ReferralConfig::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            DEFAULT_REFERRER_SHARE_BPS,
+            DEFAULT_REFEREE_DISCOUNT_BPS,
+            254,
+            1700000000,
+        );
+        assert_eq!(config.try_to_vec().unwrap().len(), ReferralConfig::SIZE);
+        
+        let mut binding = ReferralBinding::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            254,
+            1700000000,
+        );
+        assert_eq!(binding.try_to_vec().unwrap().len(), ReferralBinding::SIZE);
+        
+        // validity 0 = permanent
+        assert!(!binding.is_expired(0, i64::MAX));
+        
+        config.reward_validity_secs = 86400;
+        assert!(!binding.is_expired(config.reward_validity_secs, 1700000000 + 86399));
+        assert!(binding.is_expired(config.reward_validity_secs, 1700000000 + 86400));
+        
+        // Default policy never rebinds
+        assert!(!config.can_rebind(&binding, 1700000000 + 86400));
+        config.rebind_policy = ReferralRebindPolicy::AfterExpiry;
+        assert!(!config.can_rebind(&binding, 1700000000 + 86399));
+        assert!(config.can_rebind(&binding, 1700000000 + 86400));
+        
+        // An explicitly expired binding stays expired even if validity is lifted
+        binding.mark_expired(1700000000 + 86400);
+        assert_eq!(binding.expired_at, 1700000000 + 86400);
+        assert!(binding.is_expired(0, 1700000000));
+    }
+
+    #[test]
+    fn test_referral_binding_trade_recording() {
+        let referee = Pubkey::new_unique();
+        let referrer = Pubkey::new_unique();
+        let link = Pubkey::new_unique();
+        
+        let mut binding = ReferralBinding::new(referee, referrer, link, 254, 1700000000);
+        
+        // 记录第一笔交易
+        binding.record_trade(1_000_000_000, 18_000_000, 10_000_000, 1700001000);
+        assert_eq!(binding.trade_count, 1);
+        assert_eq!(binding.referee_volume_e6, 1_000_000_000);
+        assert_eq!(binding.referrer_rewards_e6, 18_000_000);
+        assert_eq!(binding.referee_discounts_e6, 10_000_000);
+        assert_eq!(binding.last_trade_ts, 1700001000);
+        
+        // 记录第二笔交易
+        binding.record_trade(500_000_000, 9_000_000, 5_000_000, 1700002000);
+        assert_eq!(binding.trade_count, 2);
+        assert_eq!(binding.referee_volume_e6, 1_500_000_000);
+        assert_eq!(binding.referrer_rewards_e6, 27_000_000);
+        assert_eq!(binding.referee_discounts_e6, 15_000_000);
+    }
+
+    #[test]
+    fn test_fund_serialized_size() {
+        let fund = Fund::new(
+            Pubkey::new_unique(),
+            "Test Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+    }
+
+    #[test]
+    fn test_deposit_receipt() {
+        let fund = Pubkey::new_unique();
+        let investor = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        
+        let mut receipt = DepositReceipt::new(fund, investor, mint, 100_000_000, 1_000_000, 1700000000, 254);
+        assert_eq!(receipt.try_to_vec().unwrap().len(), DepositReceipt::SIZE);
+        
+        receipt.record_deposit(50_000_000, 1_200_000, 1700001000);
+        assert_eq!(receipt.amount_e6, 50_000_000);
+        assert_eq!(receipt.nav_e6, 1_200_000);
+        assert_eq!(receipt.deposited_at, 1700001000);
+        assert_eq!(receipt.receipt_mint, mint);
+    }
+
+    #[test]
+    fn test_lp_position_beneficiary() {
+        let mut position = LPPosition::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            100_000_000,
+            1_000_000,
+            100_000_000,
+            1700000000,
+            254,
+        );
+        assert_eq!(position.try_to_vec().unwrap().len(), LPPosition::SIZE);
+        assert!(!position.can_beneficiary_claim(0, 1800000000));
+        
+        position.beneficiary = Pubkey::new_unique();
+        let period = FundConfig::DEFAULT_BENEFICIARY_INACTIVITY_SECS;
+        assert!(!position.can_beneficiary_claim(period, 1700000000 + period - 1));
+        assert!(position.can_beneficiary_claim(period, 1700000000 + period));
+        
+        let (shares, net_invested) = position.transfer_out(1800000000);
+        assert_eq!(shares, 100_000_000);
+        assert_eq!(net_invested, 100_000_000);
+        assert!(position.is_empty());
+        assert!(!position.has_beneficiary());
+    }
+
+    #[test]
+    fn test_lp_position_move_to() {
+        let mut position = LPPosition::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            100_000_000,
+            1_200_000,
+            120_000_000,
+            1700000000,
+            254,
+        );
+        position.remove_shares(20_000_000, 25_000_000, 1700100000).unwrap();
+        position.beneficiary = Pubkey::new_unique();
+        assert_eq!(position.coverage_reference_nav_e6(), 1_200_000);
+        position.record_coverage_claim(900_000, 1700200000);
+        
+        let new_owner = Pubkey::new_unique();
+        let moved = position.move_to(new_owner, 253, 1800000000);
+        assert_eq!(moved.investor, new_owner);
+        assert_eq!(moved.fund, position.fund);
+        assert_eq!(moved.bump, 253);
+        assert_eq!(moved.shares, 80_000_000);
+        // Cost basis and deposit clock carry over
+        assert_eq!(moved.deposit_nav_e6, 1_200_000);
+        assert_eq!(moved.total_deposited_e6, 120_000_000);
+        assert_eq!(moved.total_withdrawn_e6, 25_000_000);
+        assert_eq!(moved.deposited_at, 1700000000);
+        assert_eq!(moved.last_update_ts, 1800000000);
+        assert!(!moved.has_beneficiary() && !moved.is_indexed);
+        // Coverage already paid down to 0.9 cannot be claimed again by the new wallet
+        assert_eq!(moved.coverage_reference_nav_e6(), 900_000);
+        
+        assert!(position.is_empty());
+        assert!(!position.has_beneficiary());
+    }
+
+    #[test]
+    fn test_manager_profile_limits() {
+        let mut profile = ManagerProfile::new(Pubkey::new_unique(), 254, 1700000000);
+        assert_eq!(profile.try_to_vec().unwrap().len(), ManagerProfile::SIZE);
+        
+        // Unlimited
+        assert!(profile.can_create_fund(0));
+        
+        profile.record_fund_created(1_000_000, 1700000100);
+        profile.record_fund_created(1_000_000, 1700000200);
+        assert_eq!(profile.funds_created, 2);
+        assert_eq!(profile.total_creation_fees_paid, 2_000_000);
+        assert!(!profile.can_create_fund(2));
+        assert!(profile.can_create_fund(3));
+        
+        profile.record_fund_closed(1700000300);
+        assert_eq!(profile.active_funds, 1);
+        assert_eq!(profile.funds_created, 2);
+        assert!(profile.can_create_fund(2));
+    }
+
+    #[test]
+    fn test_fund_listing_status_gates_deposits() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Curated Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        assert!(fund.can_deposit());
+        
+        fund.listing_status = FundListingStatus::PendingApproval;
+        assert!(!fund.can_deposit());
+        assert!(fund.can_withdraw(1700000000));
+        // The manager's CreateFund seed deposit does not wait for approval
+        assert!(fund.can_seed_deposit());
+        
+        fund.listing_status = FundListingStatus::Rejected;
+        assert!(!fund.can_deposit());
+        assert!(!fund.can_seed_deposit());
+        
+        fund.listing_status = FundListingStatus::Listed;
+        assert!(fund.can_deposit());
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+    }
+
+
+    #[test]
+    fn test_fund_time_weighted_management_fee() {
+        let start = 1700000000;
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "TWA Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 0),
+            1,
+            start,
+        );
+        
+        // 100k USDC for the first half-year, 300k for the second half
+        let half_year = SECONDS_PER_YEAR / 2;
+        fund.accrue_aum(start);
+        fund.record_deposit(100_000_000_000, 100_000_000_000).unwrap();
+        fund.accrue_aum(start + half_year);
+        fund.record_deposit(200_000_000_000, 200_000_000_000).unwrap();
+        
+        let (mgmt_fee, _) = fund.calculate_fees(start + SECONDS_PER_YEAR).unwrap();
+        // 2% on a time-weighted 200k AUM = 4,000 USDC (spot AUM would give 6,000)
+        assert_eq!(mgmt_fee, 4_000_000_000);
+        
+        fund.accrue_aum(start + SECONDS_PER_YEAR);
+        fund.collect_fees(mgmt_fee, 0, start + SECONDS_PER_YEAR).unwrap();
+        assert_eq!(fund.aum_seconds_e6, 0);
+        assert_eq!(fund.last_aum_accrual_ts, start + SECONDS_PER_YEAR);
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+    }
+
+
+    #[test]
+    fn test_fund_fee_crank_permissions() {
+        let manager = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let mut fund = Fund::new(
+            manager,
+            "Crank Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        let mut config = FundConfig::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            255,
+        );
+        config.add_relayer(relayer).unwrap();
+        
+        assert!(fund.can_collect_fees(&manager, None));
+        assert!(!fund.can_collect_fees(&relayer, Some(&config)));
+        
+        fund.fee_crank_enabled = true;
+        assert!(fund.can_collect_fees(&relayer, Some(&config)));
+        assert!(!fund.can_collect_fees(&relayer, None));
+        assert!(!fund.can_collect_fees(&Pubkey::new_unique(), Some(&config)));
+    }
+
+
+    #[test]
+    fn test_fund_fee_destination() {
+        let manager = Pubkey::new_unique();
+        let mut fund = Fund::new(
+            manager,
+            "Fee Dest Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        let manager_usdc = Pubkey::new_unique();
+        let treasury_usdc = Pubkey::new_unique();
+        
+        // Unset: any manager-owned account
+        assert!(fund.is_valid_fee_destination(&manager_usdc, &manager));
+        assert!(!fund.is_valid_fee_destination(&manager_usdc, &Pubkey::new_unique()));
+        
+        // Set: only the configured account
+        fund.fee_destination = treasury_usdc;
+        assert!(fund.is_valid_fee_destination(&treasury_usdc, &Pubkey::new_unique()));
+        assert!(!fund.is_valid_fee_destination(&manager_usdc, &manager));
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+    }
+
+
+    #[test]
+    fn test_rent_reserve_accounting() {
+        let mut reserve = RentReserve::new(253, 1700000000);
+        assert!(!reserve.is_enabled);
+        assert_eq!(reserve.try_to_vec().unwrap().len(), RentReserve::SIZE);
+        
+        assert_eq!(RentReserve::available_lamports(5_000_000, 1_500_000), 3_500_000);
+        assert_eq!(RentReserve::available_lamports(1_000_000, 1_500_000), 0);
+        
+        reserve.record_sponsorship(2_000_000, 1700000100);
+        reserve.record_sponsorship(1_000_000, 1700000200);
+        assert_eq!(reserve.accounts_sponsored, 2);
+        assert_eq!(reserve.outstanding_lamports(), 3_000_000);
+        
+        reserve.record_reimbursement(2_500_000, 1700000300);
+        assert_eq!(reserve.outstanding_lamports(), 500_000);
+        assert_eq!(reserve.last_update_ts, 1700000300);
+    }
+
+
+    #[test]
+    fn test_pending_reinitialize_timelock() {
+        let authority = Pubkey::new_unique();
+        let pending = PendingReinitialize::new(
+            authority,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            7,
+            254,
+            1700000000,
+        );
+        assert_eq!(pending.try_to_vec().unwrap().len(), PendingReinitialize::SIZE);
+        assert!(!pending.is_executable(1700000000));
+        assert!(!pending.is_executable(1700000000 + PendingReinitialize::TIMELOCK_SECS - 1));
+        assert!(pending.is_executable(1700000000 + PendingReinitialize::TIMELOCK_SECS));
+        
+        // Authority can be read from the header even if the tail is unreadable
+        let config = FundConfig::new(authority, Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        let mut data = config.try_to_vec().unwrap();
+        data.truncate(64);
+        assert_eq!(FundConfig::authority_from_header(&data), Some(authority));
+        assert_eq!(FundConfig::authority_from_header(&[0u8; 64]), None);
+        assert_eq!(FundConfig::authority_from_header(&data[..16]), None);
+    }
+
+
+    #[test]
+    fn test_versioned_account_unpack() {
+        let fund = Fund::new(
+            Pubkey::new_unique(),
+            "Versioned",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            3,
+            1700000000,
+        );
+        let data = fund.try_to_vec().unwrap();
+        
+        // Exact size
+        let decoded = Fund::unpack_versioned(&data).unwrap();
+        assert_eq!(decoded.fund_index, 3);
+        
+        // Reallocated account with a zeroed tail
+        let mut grown = data.clone();
+        grown.resize(Fund::SIZE + 128, 0);
+        assert!(Fund::try_from_slice(&grown).is_err());
+        let decoded = Fund::unpack_versioned(&grown).unwrap();
+        assert_eq!(decoded.manager, fund.manager);
+        
+        // Older, shorter account: missing tail reads as zero
+        let shrunk = &data[..Fund::SIZE - 5];
+        let decoded = Fund::unpack_versioned(shrunk).unwrap();
+        assert_eq!(decoded.fee_destination, fund.fee_destination);
+        assert!(!decoded.is_insurance_fund && !decoded.is_system_fund);
+        assert_eq!(decoded.lp_index_page_count, 0);
+        assert!(!decoded.distribution_in_progress);
+        assert_eq!(decoded.total_redemption_fees_e6, 0);
+        
+        let config = FundConfig::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        let mut config_data = config.try_to_vec().unwrap();
+        config_data.resize(FundConfig::SIZE + 64, 0);
+        assert_eq!(FundConfig::unpack_versioned(&config_data).unwrap().authority, config.authority);
+    }
+
+    #[test]
+    fn test_insurance_daily_stats() {
+        let mut config = InsuranceFundConfig::new(
+            Pubkey::new_unique(),
+            254,
+            100_000_000,
+            3600,
+            Pubkey::new_unique(),
+            1000000,
+        );
+        assert_eq!(config.try_to_vec().unwrap().len(), InsuranceFundConfig::SIZE);
+        
+        config.add_liquidation_income(100_000_000);
+        config.add_adl_profit(50_000_000);
+        config.cover_shortfall(30_000_000, 1_000_000_000);
+        assert_eq!(config.take_daily_deltas(), (100_000_000, 50_000_000, 30_000_000));
+        assert_eq!(config.take_daily_deltas(), (0, 0, 0));
+        
+        // 旧账户: 首次调用只建立基线
+        config.daily_baseline_set = false;
+        config.add_trading_fee(5_000_000);
+        assert_eq!(config.take_daily_deltas(), (0, 0, 0));
+        config.add_trading_fee(5_000_000);
+        assert_eq!(config.take_daily_deltas(), (5_000_000, 0, 0));
+        
+        let day = InsuranceDailyStats::day_index(1700000000);
+        assert_eq!(day, 19675);
+        assert_eq!(InsuranceDailyStats::day_index(-1), -1);
+        
+        let mut stats = InsuranceDailyStats::new(day, 900_000_000, 253);
+        assert_eq!(stats.try_to_vec().unwrap().len(), InsuranceDailyStats::SIZE);
+        
+        stats.record_snapshot(100_000_000, 50_000_000, 30_000_000, 1_020_000_000, 1700000000);
+        stats.record_snapshot(10_000_000, 0, 0, 1_030_000_000, 1700003600);
+        assert_eq!(stats.total_income_e6(), 160_000_000);
+        assert_eq!(stats.net_e6, 130_000_000);
+        assert_eq!(stats.opening_balance_e6, 900_000_000);
+        assert_eq!(stats.ending_balance_e6, 1_030_000_000);
+        assert_eq!(stats.snapshot_count, 2);
+    }
+
+    #[test]
+    fn test_insurance_config_migrate_v2() {
+        let mut config = InsuranceFundConfig::new(
+            Pubkey::new_unique(),
+            254,
+            100_000_000,
+            3600,
+            Pubkey::new_unique(),
+            1700000000,
+        );
+        assert!(config.is_v2());
+        
+        // 旧账户: reserved 全 0，按 V1 记账
+        config.v2_migrated_at = 0;
+        config.add_liquidation_income(70_000_000);
+        config.add_trading_fee(30_000_000);
+        assert_eq!(config.total_liquidation_income_e6, 100_000_000);
+        assert_eq!(config.take_daily_deltas(), (100_000_000, 0, 0));
+        
+        // 回填值之和必须等于 V1 合计
+        assert!(config.migrate_v2(70_000_000, 20_000_000, 1700086400).is_err());
+        assert!(config.migrate_v2(110_000_000, -10_000_000, 1700086400).is_err());
+        config.migrate_v2(70_000_000, 30_000_000, 1700086400).unwrap();
+        assert_eq!((config.total_liquidation_income_e6, config.total_trading_fee_e6), (70_000_000, 30_000_000));
+        assert_eq!(config.v2_migrated_at, 1700086400);
+        assert!(config.migrate_v2(70_000_000, 30_000_000, 1700090000).is_err());
+        
+        // 合计收入与每日统计不受迁移影响
+        assert_eq!(config.total_income_e6(), 100_000_000);
+        assert_eq!(config.take_daily_deltas(), (0, 0, 0));
+        config.add_trading_fee(5_000_000);
+        assert_eq!(config.total_trading_fee_e6, 35_000_000);
+        assert_eq!(config.take_daily_deltas(), (5_000_000, 0, 0));
+    }
+
+    #[test]
+    fn test_relayer_nonce_rejects_replay() {
+        let user = Pubkey::new_unique();
+        let mut nonce = RelayerNonce::new(user, 252);
+        assert_eq!(nonce.try_to_vec().unwrap().len(), RelayerNonce::SIZE);
+        
+        // 0 is never a valid nonce
+        assert!(!nonce.consume(0, 1700000000));
+        
+        assert!(nonce.consume(1, 1700000000));
+        assert!(!nonce.consume(1, 1700000001));
+        
+        // Gaps are allowed, going backwards is not
+        assert!(nonce.consume(10, 1700000002));
+        assert!(!nonce.consume(5, 1700000003));
+        assert_eq!(nonce.last_nonce, 10);
+        assert_eq!(nonce.operation_count, 2);
+        assert_eq!(nonce.last_update_ts, 1700000002);
+    }
+
+    #[test]
+    fn test_relayer_stats() {
+        let mut stats = RelayerStats::new(Pubkey::new_unique(), 251);
+        assert_eq!(stats.try_to_vec().unwrap().len(), RelayerStats::SIZE);
+        assert_eq!(stats.rejection_rate_bps(), 0);
+        
+        stats.record_operation(5_000_000, 1700000000);
+        stats.record_operation(0, 1700000010);
+        stats.record_rejection(1700000020);
+        assert_eq!(stats.tx_count, 2);
+        assert_eq!(stats.total_volume_e6, 5_000_000);
+        assert_eq!(stats.rejected_over_limit_count, 1);
+        assert_eq!(stats.last_activity_ts, 1700000020);
+        assert_eq!(stats.rejection_rate_bps(), 3_333);
+    }
+
+    #[test]
+    fn test_lp_position_versioned_pack() {
+        let mut position = LPPosition::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000_000,
+            INITIAL_NAV_E6,
+            1_000_000,
+            1700000000,
+            255,
+        );
+        assert_eq!(position.try_to_vec().unwrap().len(), LPPosition::SIZE);
+        
+        // Legacy-sized accounts round-trip while the new fields are unset
+        let mut legacy = vec![0u8; LPPosition::LEGACY_SIZE];
+        position.pack_versioned(&mut legacy).unwrap();
+        let decoded = LPPosition::unpack_versioned(&legacy).unwrap();
+        assert_eq!(decoded.shares, 1_000_000);
+        assert!(!decoded.payout_allowlist_enabled);
+        
+        // ...but must be grown before the allow-list flag can be stored
+        position.payout_allowlist_enabled = true;
+        assert_eq!(
+            position.pack_versioned(&mut legacy),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        let mut current = vec![0u8; LPPosition::SIZE];
+        position.pack_versioned(&mut current).unwrap();
+        assert!(LPPosition::unpack_versioned(&current).unwrap().payout_allowlist_enabled);
+    }
+
+    #[test]
+    fn test_payout_allowlist_activation_delay() {
+        let mut list = PayoutAllowList::new(Pubkey::new_unique(), Pubkey::new_unique(), 254, 1700000000);
+        assert_eq!(list.try_to_vec().unwrap().len(), PayoutAllowList::SIZE);
+        
+        let cold = Pubkey::new_unique();
+        let active_after = list.register(cold, 1700000000).unwrap();
+        assert_eq!(active_after, 1700000000 + PayoutAllowList::ACTIVATION_DELAY_SECS);
+        assert!(!list.is_allowed(&cold, 1700000000));
+        assert!(list.is_allowed(&cold, active_after));
+        
+        // Re-registering does not reset the delay
+        assert_eq!(list.register(cold, 1700050000), Some(active_after));
+        assert_eq!(list.count, 1);
+        
+        for _ in 1..MAX_PAYOUT_ADDRESSES {
+            assert!(list.register(Pubkey::new_unique(), 1700000000).is_some());
         }
+        assert!(list.register(Pubkey::new_unique(), 1700000000).is_none());
+        
+        assert!(list.remove(&cold, 1700100000));
+        assert!(!list.is_allowed(&cold, 1800000000));
+        assert!(!list.remove(&cold, 1700100000));
+        assert_eq!(list.count as usize, MAX_PAYOUT_ADDRESSES - 1);
+    }
+
+    #[test]
+    fn test_fund_system_flags() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "System Fund",
+            255,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(0, 0),
+            0,
+            1700000000,
+        );
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+        assert!(!fund.is_protected());
+        
+        fund.is_insurance_fund = true;
+        assert!(fund.is_protected());
+        
+        fund.is_insurance_fund = false;
+        fund.is_system_fund = true;
+        assert!(fund.is_protected());
+    }
+
+    #[test]
+    fn test_partial_fee_collection() {
+        // No cap: everything requested is taken
+        assert_eq!(Fund::plan_fee_collection(1_000, 5_000, true, true, 0), (1_000, 5_000));
+        // Management fee only
+        assert_eq!(Fund::plan_fee_collection(1_000, 5_000, true, false, 0), (1_000, 0));
+        // Cap applies to management first; performance is all-or-nothing
+        assert_eq!(Fund::plan_fee_collection(1_000, 5_000, true, true, 600), (600, 0));
+        assert_eq!(Fund::plan_fee_collection(1_000, 5_000, true, true, 5_999), (1_000, 0));
+        assert_eq!(Fund::plan_fee_collection(1_000, 5_000, true, true, 6_000), (1_000, 5_000));
+        
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Fee Fund",
+            255,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            0,
+            0,
+        );
+        fund.stats.total_deposits_e6 = 100_000_000_000;
+        fund.stats.total_shares = 100_000_000_000;
+        fund.stats.update_nav().unwrap();
+        
+        // One year at 2% on 100,000 USDC = 2,000 USDC management fee
+        fund.accrue_aum(SECONDS_PER_YEAR);
+        let (mgmt_accrued, _) = fund.calculate_fees(SECONDS_PER_YEAR).unwrap();
+        assert_eq!(mgmt_accrued, 2_000_000_000);
+        
+        // Take a quarter now; the rest stays accrued
+        fund.collect_fees_partial(500_000_000, mgmt_accrued, false, 0, SECONDS_PER_YEAR).unwrap();
+        let (deferred, _) = fund.calculate_fees(SECONDS_PER_YEAR + 1).unwrap();
+        assert!((1_499_000_000..=1_500_100_000).contains(&deferred), "deferred = {}", deferred);
+    }
+
+    #[test]
+    fn test_fund_benchmark_relative_fee() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Benchmarked Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(0, 2000),
+            0,
+            0,
+        );
+        fund.stats.total_deposits_e6 = 100_000_000;
+        fund.stats.total_shares = 100_000_000;
+        fund.stats.total_realized_pnl_e6 = 20_000_000;
+        fund.stats.update_nav().unwrap();
+        assert_eq!(fund.stats.current_nav_e6, 1_200_000);
+        assert!(!fund.has_benchmark());
+        
+        // Absolute: 20% of the $20 gain over the HWM
+        let (_, perf) = fund.calculate_fees_with_benchmark(100, Some(1_100_000_000)).unwrap();
+        assert_eq!(perf, 4_000_000);
+        
+        // Benchmark up 10% → hurdle 1.10, fee only on the $10 outperformance
+        fund.benchmark_feed = Pubkey::new_unique();
+        fund.benchmark_base_price_e6 = 1_000_000_000;
+        fund.benchmark_relative_fee = true;
+        assert!(fund.has_benchmark());
+        assert_eq!(fund.benchmark_hurdle_nav_e6(1_100_000_000), 1_100_000);
+        let (_, perf) = fund.calculate_fees_with_benchmark(100, Some(1_100_000_000)).unwrap();
+        assert_eq!(perf, 2_000_000);
+        
+        // Benchmark beat the fund → no fee
+        let (_, perf) = fund.calculate_fees_with_benchmark(100, Some(1_300_000_000)).unwrap();
+        assert_eq!(perf, 0);
+        
+        // Benchmark down: hurdle never drops below the HWM
+        assert_eq!(fund.benchmark_hurdle_nav_e6(900_000_000), fund.stats.high_water_mark_e6);
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+    }
+
+    #[test]
+    fn test_fund_pnl_blackout() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Blackout Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            0,
+            0,
+        );
+        // Disabled by default
+        assert!(!fund.check_pnl_blackout(-50_000_000, 100_000_000, 1000));
+        assert!(!fund.is_in_blackout(1000));
+        
+        // 10 min window on |PnL| >= 5% of value
+        fund.blackout_secs = 600;
+        fund.blackout_pnl_threshold_bps = 500;
+        assert!(!fund.check_pnl_blackout(4_999_999, 100_000_000, 1000));
+        assert!(fund.check_pnl_blackout(-5_000_000, 100_000_000, 1000));
+        assert!(fund.is_in_blackout(1599));
+        assert!(!fund.is_in_blackout(1600));
+        
+        // A second large PnL extends, never shortens
+        assert!(fund.check_pnl_blackout(10_000_000, 100_000_000, 1300));
+        assert_eq!(fund.blackout_until, 1900);
+        fund.blackout_secs = 60;
+        assert!(fund.check_pnl_blackout(10_000_000, 100_000_000, 1310));
+        assert_eq!(fund.blackout_until, 1900);
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+    }
+
+    #[test]
+    fn test_sol_denominated_fund_units() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "SOL Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            0,
+            0,
+        );
+        // USDC funds move e6 amounts 1:1
+        assert_eq!(fund.token_amount_to_e6(1_500_000).unwrap(), 1_500_000);
+        assert_eq!(fund.e6_to_token_amount(1_500_000).unwrap(), 1_500_000);
+        assert_eq!(fund.min_deposit_e6(), MIN_DEPOSIT_AMOUNT_E6);
+        
+        // wSOL: 1.5 SOL in lamports = 1.5 in micro-SOL, sub-micro dust floors
+        fund.sol_denominated = true;
+        assert_eq!(fund.token_amount_to_e6(1_500_000_999).unwrap(), 1_500_000);
+        assert_eq!(fund.e6_to_token_amount(1_500_000).unwrap(), 1_500_000_000);
+        assert_eq!(fund.min_deposit_e6(), MIN_SOL_DEPOSIT_E6);
+        assert!(fund.token_amount_to_e6(9_999_999).unwrap() < fund.min_deposit_e6());
+        assert!(fund.e6_to_token_amount(-1).is_err());
+        assert!(fund.e6_to_token_amount(i64::MAX).is_err());
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
+    }
+
+    #[test]
+    fn test_insurance_yield_venue() {
+        let mut venue = InsuranceYieldVenue::new(Pubkey::new_unique(), Pubkey::new_unique(), 2_000, 255);
+        assert_eq!(venue.try_to_vec().unwrap().len(), InsuranceYieldVenue::SIZE);
+        
+        // 20% of (vault + deployed)
+        assert_eq!(venue.deploy_capacity_e6(1_000_000_000, 0), 200_000_000);
+        let deployed = venue.record_deploy(150_000_000, 0).unwrap();
+        assert_eq!(deployed, 150_000_000);
+        assert_eq!(venue.deploy_capacity_e6(850_000_000, deployed), 50_000_000);
+        assert_eq!(venue.deploy_capacity_e6(100_000_000, deployed), 0);
+        
+        // Principal first, the excess is yield
+        let (deployed, yield_e6) = venue.record_recall(100_000_000, deployed).unwrap();
+        assert_eq!((deployed, yield_e6), (50_000_000, 0));
+        let (deployed, yield_e6) = venue.record_recall(53_000_000, deployed).unwrap();
+        assert_eq!((deployed, yield_e6), (0, 3_000_000));
+        assert_eq!(venue.total_deployed_e6, 150_000_000);
+        assert_eq!(venue.total_recalled_e6, 153_000_000);
+        assert_eq!(venue.total_yield_e6, 3_000_000);
+    }
+
+    #[test]
+    fn test_adl_event_ledger() {
+        let mut config = InsuranceFundConfig::new(
+            Pubkey::new_unique(),
+            254,
+            100_000_000,
+            3600,
+            Pubkey::new_unique(),
+            1000,
+        );
+        assert_eq!(config.try_to_vec().unwrap().len(), InsuranceFundConfig::SIZE);
+        
+        // Re-asserting an ongoing ADL is not a new episode
+        assert!(config.set_adl_in_progress(true));
+        assert!(!config.set_adl_in_progress(true));
+        assert_eq!(config.adl_trigger_count, 1);
+        assert!(!config.set_adl_in_progress(false));
+        
+        // Uncovered shortfall before the episode starts makes it a bankruptcy
+        config.pending_adl_shortfall_e6 = 7_000_000;
+        assert!(config.set_adl_in_progress(true));
+        let mut event = ADLEvent::open(&config, 500_000_000, None, 2000, 255);
+        assert_eq!(event.try_to_vec().unwrap().len(), ADLEvent::SIZE);
+        assert_eq!(event.episode, 2);
+        assert_eq!(event.trigger_reason, ADLTriggerReason::Bankruptcy);
+        assert_eq!((event.amount_socialized_e6, event.shortfall_count), (7_000_000, 1));
+        
+        event.record_socialized(3_000_000).unwrap();
+        assert_eq!((event.amount_socialized_e6, event.shortfall_count), (10_000_000, 2));
+        
+        // Closed records are immutable
+        event.close(2600).unwrap();
+        assert_eq!(event.ended_at, 2600);
+        assert!(event.record_socialized(1).is_err());
+        assert!(event.close(2700).is_err());
+        
+        // Without a pending shortfall the balance conditions decide
+        config.pending_adl_shortfall_e6 = 0;
+        assert_eq!(config.adl_start_reason(50_000_000, None), ADLTriggerReason::InsufficientBalance);
+        
+        // A rapid decline the TWAP value does not confirm is not recorded as one
+        config.balance_1h_ago_e6 = 1_000_000_000;
+        let event = ADLEvent::open(&config, 600_000_000, Some(800_000_000), 3000, 255);
+        assert_eq!(event.trigger_reason, ADLTriggerReason::None);
+        let event = ADLEvent::open(&config, 600_000_000, Some(650_000_000), 3000, 255);
+        assert_eq!(event.trigger_reason, ADLTriggerReason::RapidDecline);
     }
 
-    /// PDA seeds
-    pub fn seeds() -> Vec<Vec<u8>> {
-        vec![SPOT_TRADING_FEE_CONFIG_SEED.to_vec()]
+    #[test]
+    fn test_square_fund_stats() {
+        let mut stats = SquareFundStats::new(Pubkey::new_unique(), 255, 1000);
+        assert_eq!(stats.try_to_vec().unwrap().len(), SquareFundStats::SIZE);
+        
+        stats.record_payment(SquarePaymentType::Subscription, 10_000_000, 9_000_000, 1_000_000, true, 1100).unwrap();
+        stats.record_payment(SquarePaymentType::Subscription, 20_000_000, 18_000_000, 2_000_000, true, 1200).unwrap();
+        // Platform share routed to the Treasury: top-line only, no vault inflow
+        stats.record_payment(SquarePaymentType::LiveDonation, 5_000_000, 4_000_000, 1_000_000, false, 1300).unwrap();
+        
+        let subs = stats.totals[SquarePaymentType::Subscription as usize];
+        assert_eq!((subs.payment_count, subs.volume_e6, subs.platform_share_e6), (2, 30_000_000, 3_000_000));
+        assert_eq!(stats.totals[SquarePaymentType::LiveDonation as usize].creator_share_e6, 4_000_000);
+        assert_eq!(stats.totals[SquarePaymentType::KnowledgePurchase as usize], SquareTypeTotals::default());
+        assert_eq!(stats.accounted_balance_e6(), 3_000_000);
+        
+        assert!(stats.record_withdrawal(3_000_001, 1400).is_err());
+        assert!(stats.record_withdrawal(0, 1400).is_err());
+        stats.record_withdrawal(2_500_000, 1400).unwrap();
+        assert_eq!(stats.accounted_balance_e6(), 500_000);
+        assert_eq!(stats.withdrawal_count, 1);
+        
+        let mut creator = SquareCreatorStats::new(Pubkey::new_unique(), 254, 1100);
+        assert_eq!(creator.try_to_vec().unwrap().len(), SquareCreatorStats::SIZE);
+        creator.record_payment(10_000_000, 9_000_000, 1100).unwrap();
+        creator.record_payment(5_000_000, 4_000_000, 1300).unwrap();
+        assert_eq!((creator.total_volume_e6, creator.total_earned_e6, creator.payment_count), (15_000_000, 13_000_000, 2));
+        assert_eq!((creator.first_payment_ts, creator.last_payment_ts), (1100, 1300));
     }
 
-    /// 验证调用方是否授权
-    pub fn is_authorized_caller(&self, caller: &Pubkey) -> bool {
-        caller == &self.authorized_caller
+    #[test]
+    fn test_revenue_split() {
+        let mut split = RevenueSplit::new(Pubkey::new_unique(), 253);
+        assert_eq!(split.try_to_vec().unwrap().len(), RevenueSplit::SIZE);
+        assert!(!split.is_active());
+        
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let r = |recipient_vault, share_bps| RevenueSplitRecipient { recipient_vault, share_bps };
+        
+        // Must sum to 10000, no zero shares, no duplicates, at most 5
+        assert!(split.set_recipients(&[r(a, 5000), r(b, 4999)], 1).is_err());
+        assert!(split.set_recipients(&[r(a, 10_000), r(b, 0)], 1).is_err());
+        assert!(split.set_recipients(&[r(a, 5000), r(a, 5000)], 1).is_err());
+        let six: Vec<_> = (0..6).map(|_| r(Pubkey::new_unique(), 1)).collect();
+        assert!(split.set_recipients(&six, 1).is_err());
+        assert!(!split.is_active());
+        
+        split.set_recipients(&[r(a, 3333), r(b, 3333), r(c, 3334)], 100).unwrap();
+        assert_eq!(split.recipient_count, 3);
+        
+        // Rounding dust lands on the last recipient; payouts always sum up
+        let payouts = split.allocate(1_000_001);
+        assert_eq!(payouts, vec![(a, 333_300), (b, 333_300), (c, 333_401)]);
+        assert_eq!(payouts.iter().map(|(_, v)| v).sum::<i64>(), 1_000_001);
+        
+        // Empty list switches the split off
+        split.set_recipients(&[], 200).unwrap();
+        assert!(!split.is_active());
+        assert!(split.allocate(1_000_000).is_empty());
     }
 
-    /// 计算 Taker 手续费
-    pub fn calculate_taker_fee(&self, volume_e6: i64) -> i64 {
-        (volume_e6 as i128 * self.taker_fee_bps as i128 / 10000) as i64
+    #[test]
+    fn test_square_payer_nonce_monotonic() {
+        let payer = Pubkey::new_unique();
+        let mut nonce = SquarePayerNonce::new(payer, 250);
+        assert_eq!(nonce.try_to_vec().unwrap().len(), SquarePayerNonce::SIZE);
+        assert_eq!(nonce.next_nonce(), 1);
+        
+        assert!(!nonce.consume(0));
+        assert!(nonce.consume(1));
+        assert!(!nonce.consume(1));
+        assert_eq!(nonce.next_nonce(), 2);
+        
+        // Two payments in the same slot just use consecutive nonces
+        assert!(nonce.consume(2));
+        assert!(nonce.consume(5));
+        assert!(!nonce.consume(4));
+        assert_eq!(nonce.last_nonce, 5);
+        assert_eq!(nonce.payment_count, 3);
+        
+        // Distinct nonces give distinct record PDAs
+        assert_ne!(SquarePaymentRecord::seeds(&payer, 1), SquarePaymentRecord::seeds(&payer, 2));
     }
 
-    /// 计算 Maker 手续费
-    pub fn calculate_maker_fee(&self, volume_e6: i64) -> i64 {
-        (volume_e6 as i128 * self.maker_fee_bps as i128 / 10000) as i64
+    #[test]
+    fn test_square_escrow_lifecycle() {
+        let payer = Pubkey::new_unique();
+        let record = SquarePaymentRecord::new(
+            payer,
+            Pubkey::new_unique(),
+            42,
+            SquarePaymentType::KnowledgePurchase,
+            500_000_000,
+            9000,
+            1700000000,
+            0,
+            b"",
+            1,
+            254,
+        );
+        let mut escrow = SquareEscrow::new(
+            Pubkey::new_unique(),
+            &record,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1700000000,
+            253,
+        );
+        assert_eq!(escrow.try_to_vec().unwrap().len(), SquareEscrow::SIZE);
+        assert_eq!(escrow.amount_e6, 500_000_000);
+        assert_eq!(escrow.creator_amount_e6, 450_000_000);
+        assert_eq!(escrow.platform_amount_e6, 50_000_000);
+        
+        // Buyer may confirm at once, anyone else only after the timeout
+        let keeper = Pubkey::new_unique();
+        assert!(escrow.can_release(&payer, 1700000001));
+        assert!(!escrow.can_release(&keeper, 1700000001));
+        assert!(escrow.can_release(&keeper, escrow.release_after));
+        
+        // A dispute blocks both until the admin resolves it
+        assert!(escrow.raise_dispute(1700000100).is_ok());
+        assert_eq!(escrow.status, SquareEscrowStatus::Disputed);
+        assert!(!escrow.can_release(&payer, 1700000200));
+        assert!(!escrow.can_release(&keeper, escrow.release_after));
+        assert!(escrow.raise_dispute(1700000200).is_err());
+        
+        escrow.settle(true, 1700000300);
+        assert_eq!(escrow.status, SquareEscrowStatus::Refunded);
+        assert_eq!(escrow.settled_at, 1700000300);
     }
 
-    /// 分配手续费
-    /// 返回 (protocol, insurance, referral, maker_reward)
-    pub fn distribute_fee(&self, fee_e6: i64) -> (i64, i64, i64, i64) {
-        let protocol = (fee_e6 as i128 * self.protocol_share_bps as i128 / 10000) as i64;
-        let insurance = (fee_e6 as i128 * self.insurance_share_bps as i128 / 10000) as i64;
-        let referral = (fee_e6 as i128 * self.referral_share_bps as i128 / 10000) as i64;
-        let maker = (fee_e6 as i128 * self.maker_reward_share_bps as i128 / 10000) as i64;
-        (protocol, insurance, referral, maker)
+    #[test]
+    fn test_square_escrow_dispute_window() {
+        let record = SquarePaymentRecord::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1,
+            SquarePaymentType::KnowledgePurchase,
+            10_000_000,
+            9000,
+            1700000000,
+            0,
+            b"",
+            1,
+            254,
+        );
+        let mut escrow = SquareEscrow::new(
+            Pubkey::new_unique(),
+            &record,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1700000000,
+            253,
+        );
+        assert!(escrow.raise_dispute(escrow.release_after).is_err());
+        assert_eq!(escrow.status, SquareEscrowStatus::Held);
+        
+        escrow.settle(false, escrow.release_after);
+        assert_eq!(escrow.status, SquareEscrowStatus::Released);
+        assert!(escrow.raise_dispute(escrow.release_after - 1).is_err());
     }
 
-    /// 记录 Taker 手续费
-    pub fn record_taker_fee(&mut self, fee_e6: i64, current_ts: i64) {
-        self.total_taker_fee_e6 = self.total_taker_fee_e6.saturating_add(fee_e6);
-        let (protocol, insurance, _referral, _maker) = self.distribute_fee(fee_e6);
-        self.total_protocol_income_e6 = self.total_protocol_income_e6.saturating_add(protocol);
-        self.total_insurance_income_e6 = self.total_insurance_income_e6.saturating_add(insurance);
-        self.last_update_ts = current_ts;
+    #[test]
+    fn test_square_tip_token_value() {
+        let mut token = SquareTipToken::new(Pubkey::new_unique(), 9, 255);
+        assert_eq!(token.try_to_vec().unwrap().len(), SquareTipToken::SIZE);
+        
+        // Unpriced tokens cannot be valued
+        assert!(token.value_e6(1_000_000_000, 1700000000).is_err());
+        assert!(token.set_price(0, 1700000000).is_err());
+        
+        // 1 token = $150
+        token.set_price(150_000_000, 1700000000).unwrap();
+        assert_eq!(token.value_e6(2_500_000_000, 1700000000).unwrap(), 375_000_000);
+        assert_eq!(
+            token.value_e6(1_000_000_000, 1700000000 + DEFAULT_TIP_PRICE_MAX_AGE_SECS).unwrap(),
+            150_000_000
+        );
+        assert!(token.value_e6(1_000_000_000, 1700000001 + DEFAULT_TIP_PRICE_MAX_AGE_SECS).is_err());
+        
+        // Token split keeps the dust with the platform
+        assert_eq!(SquareTipToken::split_amount(999, 7000), (699, 300));
+        assert_eq!(SquareTipToken::split_amount(1_000, 10000), (1_000, 0));
+        
+        token.record_tip(2_500_000_000, 375_000_000).unwrap();
+        assert_eq!(token.total_tips, 1);
+        assert_eq!(token.total_token_amount, 2_500_000_000);
+        assert_eq!(token.total_value_e6, 375_000_000);
     }
 
-    /// 记录 Maker 手续费
-    pub fn record_maker_fee(&mut self, fee_e6: i64, current_ts: i64) {
-        self.total_maker_fee_e6 = self.total_maker_fee_e6.saturating_add(fee_e6);
-        let (protocol, insurance, _referral, _maker) = self.distribute_fee(fee_e6);
-        self.total_protocol_income_e6 = self.total_protocol_income_e6.saturating_add(protocol);
-        self.total_insurance_income_e6 = self.total_insurance_income_e6.saturating_add(insurance);
-        self.last_update_ts = current_ts;
+    #[test]
+    fn test_fund_hook_program() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Hooked Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        assert!(!fund.has_hook());
+        // As decoded from an account that predates the NAV TWAP
+        fund.nav_cumulative_ts = 0;
+        fund.twap_last_ts = 0;
+        // Funds created before the hook field still fit until one is set
+        assert!(fund.fits_in(Fund::SIZE - 319));
+        
+        fund.hook_program = Pubkey::new_unique();
+        assert!(fund.has_hook());
+        assert!(!fund.fits_in(Fund::SIZE - 319));
+        
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        assert_ne!(Fund::hook_authority_seeds(&a), Fund::hook_authority_seeds(&b));
+        assert_ne!(Fund::hook_authority_seeds(&a), Fund::vault_seeds(&a));
     }
 
-    /// 记录返佣发放
-    pub fn record_referral_paid(&mut self, amount_e6: i64, current_ts: i64) {
-        self.total_referral_paid_e6 = self.total_referral_paid_e6.saturating_add(amount_e6);
-        self.last_update_ts = current_ts;
+    #[test]
+    fn test_share_authority_seeds() {
+        let fund = Pubkey::new_unique();
+        let v1 = Fund::share_authority_seeds(&fund, 1);
+        let v2 = Fund::share_authority_seeds(&fund, 2);
+        assert_eq!(v1.len(), 3);
+        assert_eq!(v1[0], SHARE_MINT_AUTHORITY_SEED.to_vec());
+        assert_eq!(v1[2], vec![1u8]);
+        // Each rotation derives a fresh authority
+        assert_ne!(v1, v2);
+        assert_ne!(v1, Fund::share_authority_seeds(&Pubkey::new_unique(), 1));
     }
 
-    /// 记录做市商奖励
-    pub fn record_maker_reward(&mut self, reward_e6: i64, current_ts: i64) {
-        self.total_maker_rewards_e6 = self.total_maker_rewards_e6.saturating_add(reward_e6);
-        self.last_update_ts = current_ts;
+    #[test]
+    fn test_fund_lp_referral_bounty() {
+        let fund = Pubkey::new_unique();
+        let mut program = FundLPReferralProgram::new(fund, 255, 1700000000);
+        assert_eq!(program.try_to_vec().unwrap().len(), FundLPReferralProgram::SIZE);
+        program.bounty_bps = 50; // 0.5%
+        program.is_active = true;
+        
+        let referee = Pubkey::new_unique();
+        let mut binding = FundLPReferralBinding::new(fund, referee, Pubkey::new_unique(), 254, 1700000000);
+        assert_eq!(binding.try_to_vec().unwrap().len(), FundLPReferralBinding::SIZE);
+        
+        // 10,000 USDC deposit → 50 USDC bounty
+        let bounty = program.record_deposit(10_000_000_000).unwrap();
+        assert_eq!(bounty, 50_000_000);
+        binding.record_deposit(10_000_000_000, bounty).unwrap();
+        assert_eq!(program.unfunded_e6(), 50_000_000);
+        
+        // Nothing funded yet → nothing to claim
+        assert_eq!(binding.claim(&mut program).unwrap(), 0);
+        
+        // A 30 USDC fee collection is fully withheld, then the rest from the next one
+        assert_eq!(program.fund_from_fees(30_000_000).unwrap(), 30_000_000);
+        assert_eq!(binding.claim(&mut program).unwrap(), 30_000_000);
+        assert_eq!(program.fund_from_fees(100_000_000).unwrap(), 20_000_000);
+        assert_eq!(program.fund_from_fees(100_000_000).unwrap(), 0);
+        assert_eq!(binding.claim(&mut program).unwrap(), 20_000_000);
+        assert_eq!(binding.unclaimed_e6(), 0);
+        assert_eq!(program.claimable_pool_e6(), 0);
+        assert_eq!(program.total_claimed_e6, 50_000_000);
+        
+        assert_ne!(
+            FundLPReferralBinding::seeds(&fund, &referee),
+            FundLPReferralBinding::seeds(&Pubkey::new_unique(), &referee)
+        );
     }
 
-    /// 获取总手续费收入
-    pub fn total_fee_income_e6(&self) -> i64 {
-        self.total_taker_fee_e6.saturating_add(self.total_maker_fee_e6)
+    #[test]
+    fn test_share_freeze_record() {
+        let manager = Pubkey::new_unique();
+        let mut record = ShareFreezeRecord::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            255,
+        );
+        assert_eq!(record.try_to_vec().unwrap().len(), ShareFreezeRecord::SIZE);
+        assert!(!record.is_frozen);
+        
+        record.record_freeze(ShareFreezeReason::Sanctions, manager, 1700000000);
+        assert!(record.is_frozen);
+        assert_eq!(record.reason, ShareFreezeReason::Sanctions);
+        assert_eq!(record.freeze_count, 1);
+        
+        record.record_thaw(manager, 1700086400);
+        assert!(!record.is_frozen);
+        assert_eq!(record.reason, ShareFreezeReason::Sanctions);
+        assert_eq!(record.thawed_at, 1700086400);
+        assert_eq!(record.try_to_vec().unwrap().len(), ShareFreezeRecord::SIZE);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use solana_program::pubkey::Pubkey;
+    #[test]
+    fn test_blackout_policy() {
+        let mut policy = BlackoutPolicy::new(255);
+        assert_eq!(policy.try_to_vec().unwrap().len(), BlackoutPolicy::SIZE);
+        assert!(!policy.allows(1, 500));
+        
+        policy.max_window_secs = 3600;
+        policy.min_threshold_bps = 100;
+        assert!(policy.allows(3600, 100));
+        assert!(!policy.allows(3601, 100));
+        assert!(!policy.allows(600, 99));
+        assert!(!policy.allows(600, 10_001));
+    }
 
     #[test]
-    fn test_fund_config_size() {
-        assert!(FundConfig::SIZE > 0);
-        println!("FundConfig SIZE: {}", FundConfig::SIZE);
+    fn test_lp_index_page() {
+        let fund = Pubkey::new_unique();
+        let mut page = LPIndexPage::new(fund, 0, 250);
+        assert_eq!(page.try_to_vec().unwrap().len(), LPIndexPage::SIZE);
+        
+        let positions: Vec<Pubkey> = (0..LP_INDEX_PAGE_CAPACITY).map(|_| Pubkey::new_unique()).collect();
+        for p in &positions {
+            assert!(page.push(*p));
+        }
+        assert!(page.is_full());
+        assert!(!page.push(Pubkey::new_unique()));
+        
+        // Swap-remove keeps the valid entries contiguous
+        assert!(page.remove(&positions[0]));
+        assert!(!page.remove(&positions[0]));
+        assert_eq!(page.count as usize, LP_INDEX_PAGE_CAPACITY - 1);
+        assert_eq!(page.positions()[0], positions[LP_INDEX_PAGE_CAPACITY - 1]);
+        assert!(!page.positions().contains(&positions[0]));
+        assert!(page.push(positions[0]));
     }
 
+
     #[test]
-    fn test_fund_size() {
-        assert!(Fund::SIZE > 0);
-        println!("Fund SIZE: {}", Fund::SIZE);
+    fn test_lp_distribution() {
+        let mut dist = LPDistribution::new(Pubkey::new_unique(), 255);
+        assert_eq!(dist.try_to_vec().unwrap().len(), LPDistribution::SIZE);
+        
+        // 0.05 USDC per share on 1,000 shares
+        let budget = dist.start(50_000, 1_000_000_000, 1000).unwrap();
+        assert_eq!(budget, 50_000_000);
+        assert!(dist.is_active);
+        assert_eq!(dist.round, 1);
+        
+        let owed = LPDistribution::payout_for(dist.amount_per_share_e6, 600_000_000).unwrap();
+        assert_eq!(owed, 30_000_000);
+        dist.record_payout(owed).unwrap();
+        dist.record_payout(20_000_000).unwrap();
+        assert!(dist.record_payout(1).is_err());
+        assert_eq!(dist.paid_count, 2);
+        
+        dist.finish(2000);
+        assert!(!dist.is_active);
+        
+        // Next round resets progress
+        dist.next_page = 3;
+        dist.start(1, 1_000_000, 3000).unwrap();
+        assert_eq!(dist.round, 2);
+        assert_eq!((dist.next_page, dist.next_index, dist.paid_e6), (0, 0, 0));
     }
 
+
     #[test]
-    fn test_lp_position_size() {
-        assert!(LPPosition::SIZE > 0);
-        println!("LPPosition SIZE: {}", LPPosition::SIZE);
+    fn test_proposal_voting() {
+        let fund = Pubkey::new_unique();
+        let mut proposal = Proposal::new(
+            fund, 1, Pubkey::new_unique(), ProposalKind::WindDown,
+            1_000_000_000, 1000, MIN_VOTING_PERIOD_SECS, 255,
+        );
+        assert_eq!(proposal.try_to_vec().unwrap().len(), Proposal::SIZE);
+        assert_eq!(
+            VoteRecord::new(Pubkey::new_unique(), Pubkey::new_unique(), true, 1, 0, 255).try_to_vec().unwrap().len(),
+            VoteRecord::SIZE
+        );
+        
+        assert!(Proposal::meets_lp_threshold(50_000_000, 1_000_000_000));
+        assert!(!Proposal::meets_lp_threshold(49_999_999, 1_000_000_000));
+        assert!(!Proposal::meets_lp_threshold(1, 0));
+        
+        // Positions touched after the snapshot carry no weight
+        let mut position = LPPosition::new(fund, Pubkey::new_unique(), 150_000_000, 1_000_000, 150_000_000, 900, 255);
+        assert_eq!(proposal.vote_weight(&position), 150_000_000);
+        position.last_update_ts = 1001;
+        assert_eq!(proposal.vote_weight(&position), 0);
+        
+        assert!(proposal.is_voting_open(1000 + MIN_VOTING_PERIOD_SECS - 1));
+        assert!(!proposal.is_voting_open(1000 + MIN_VOTING_PERIOD_SECS));
+        
+        // 15% turnout: below quorum
+        proposal.record_vote(true, 150_000_000);
+        assert!(!proposal.quorum_reached());
+        
+        proposal.record_vote(false, 100_000_000);
+        assert!(proposal.quorum_reached());
+        assert_eq!(proposal.finalize(2000), ProposalStatus::Passed);
+        assert!(!proposal.is_voting_open(1001));
+    }
+
+
+    #[test]
+    fn test_share_price_feed() {
+        let mut feed = SharePriceFeed::new(Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        assert_eq!(feed.try_to_vec().unwrap().len(), SharePriceFeed::SIZE);
+        assert_eq!(feed.get_price_no_older_than(0, 60), None);
+        
+        feed.publish(1_000_000, 100_000_000_000, 100_000_000_000, 1000);
+        assert_eq!(feed.get_price_no_older_than(1060, 60), Some((1_000_000, 0)));
+        assert_eq!(feed.get_price_no_older_than(1061, 60), None);
+        
+        feed.publish(1_020_000, 102_000_000_000, 100_000_000_000, 2000);
+        assert_eq!(feed.conf, 20_000);
+        assert_eq!(feed.prev_price, 1_000_000);
+        assert_eq!(feed.prev_publish_ts, 1000);
+        assert_eq!(feed.publish_count, 2);
+    }
+
+
+    #[test]
+    fn test_lp_share_lock() {
+        let mut position = LPPosition::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 1_000_000, 1_000, 0, 255);
+        assert_eq!(position.try_to_vec().unwrap().len(), LPPosition::SIZE);
+        let lender = Pubkey::new_unique();
+        
+        position.lock_shares(lender, 600).unwrap();
+        assert_eq!(position.available_shares(), 400);
+        assert!(position.lock_shares(Pubkey::new_unique(), 1).is_err());
+        assert!(position.lock_shares(lender, 401).is_err());
+        
+        // Locked shares cannot be redeemed
+        assert_eq!(
+            position.remove_shares(401, 401, 1),
+            Err(crate::error::FundError::SharesLocked.into())
+        );
+        position.remove_shares(400, 400, 1).unwrap();
+        
+        assert!(position.unlock_shares(&Pubkey::new_unique(), 600).is_err());
+        position.unlock_shares(&lender, 600).unwrap();
+        assert_eq!(position.lienholder, Pubkey::default());
+        assert_eq!(position.available_shares(), 600);
+    }
+
+    #[test]
+    fn test_position_nft_wrap_transfer() {
+        let seller = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+        let lp_position = Pubkey::new_unique();
+        let record_key = Pubkey::new_unique();
+        let mut position = LPPosition::new(Pubkey::new_unique(), seller, 1_000, 1_000_000, 1_000, 0, 255);
+        position.beneficiary = Pubkey::new_unique();
+        let mut record = PositionNFT::new(position.fund, lp_position, Pubkey::new_unique(), 254);
+        assert_eq!(record.try_to_vec().unwrap().len(), PositionNFT::SIZE);
+        
+        // Not wrapped yet
+        assert!(record.transfer(&mut position, buyer, 10).is_err());
+        
+        record.wrap(&mut position, record_key, 10).unwrap();
+        assert!(record.is_wrapped() && position.nft_wrapped);
+        assert_eq!((record.shares, record.holder), (1_000, seller));
+        assert_eq!(position.available_shares(), 0);
+        assert_eq!(
+            record.wrap(&mut position, record_key, 11),
+            Err(crate::error::FundError::PositionNFTWrapped.into())
+        );
+        
+        record.transfer(&mut position, buyer, 20).unwrap();
+        assert_eq!(position.investor, buyer);
+        assert_eq!(position.beneficiary, Pubkey::default());
+        assert_eq!(position.shares, 1_000);
+        assert_eq!((record.holder, record.transfer_count), (buyer, 1));
+        assert!(record.transfer(&mut position, buyer, 21).is_err());
+        
+        assert_eq!(record.unwrap(&mut position, &record_key).unwrap(), 1_000);
+        assert!(!record.is_wrapped() && !position.nft_wrapped);
+        assert_eq!(position.available_shares(), 1_000);
+        assert!(record.unwrap(&mut position, &record_key).is_err());
+        
+        // An existing lien blocks wrapping
+        position.lock_shares(Pubkey::new_unique(), 1).unwrap();
+        assert_eq!(
+            record.wrap(&mut position, record_key, 30),
+            Err(crate::error::FundError::SharesLocked.into())
+        );
+    }
+
+
+    #[test]
+    fn test_trade_intent() {
+        let mut long = TradeIntent::new(Pubkey::new_unique(), 7, 0, 0, 1_000_000, 50_000_000_000, 5, 2000, 1000, 255);
+        assert_eq!(long.try_to_vec().unwrap().len(), TradeIntent::SIZE);
+        
+        // Buy limit: fill at or below the limit
+        assert!(long.price_satisfies_limit(49_000_000_000));
+        assert!(long.price_satisfies_limit(50_000_000_000));
+        assert!(!long.price_satisfies_limit(50_000_000_001));
+        
+        // Sell limit: fill at or above the limit
+        let short = TradeIntent { side: 1, ..long.clone() };
+        assert!(short.price_satisfies_limit(51_000_000_000));
+        assert!(!short.price_satisfies_limit(49_999_999_999));
+        
+        assert!(!long.is_expired(1999));
+        assert!(long.is_expired(2000));
+        
+        // Stale once the grace period after expiry has passed
+        assert!(!long.is_stale(2000 + STALE_ENTRY_GRACE_SECS - 1));
+        assert!(long.is_stale(2000 + STALE_ENTRY_GRACE_SECS));
+        
+        long.mark_executed(Pubkey::new_unique(), 49_000_000_000, 1500);
+        assert!(long.is_executed);
+        assert_eq!(long.executed_price_e6, 49_000_000_000);
+        // ...or after execution
+        assert!(long.is_stale(1500 + STALE_ENTRY_GRACE_SECS));
+        
+        assert_eq!(stale_entry_keeper_tip(2_000_000), 200_000);
+        assert_eq!(stale_entry_keeper_tip(9), 0);
+    }
+
+    #[test]
+    fn test_redemption_fee_curve() {
+        let curve = RedemptionFeeCurve { target_cash_ratio_bps: 2_000, max_fee_bps: 200 };
+        
+        // Disabled curve never charges
+        assert_eq!(RedemptionFeeCurve::default().fee_bps(0, 1_000_000_000), 0);
+        
+        // At or above the 20% target: no fee
+        assert_eq!(curve.fee_bps(200_000_000, 1_000_000_000), 0);
+        assert_eq!(curve.fee_bps(900_000_000, 1_000_000_000), 0);
+        
+        // 10% cash: half of the max fee
+        assert_eq!(curve.fee_bps(100_000_000, 1_000_000_000), 100);
+        assert_eq!(curve.fee_for(50_000_000, 100_000_000, 1_000_000_000), 500_000);
+        
+        // No cash left: max fee
+        assert_eq!(curve.fee_bps(0, 1_000_000_000), 200);
+        
+        // Last LP out (nothing left to compensate): no fee
+        assert_eq!(curve.fee_bps(0, 0), 0);
+    }
+
+    #[test]
+    fn test_campaign_stats_record_deposit() {
+        let mut stats = CampaignStats::new(42, 255);
+        assert_eq!(stats.try_to_vec().unwrap().len(), CampaignStats::SIZE);
+        
+        assert_eq!(stats.record_deposit(1_000_000_000, 1_000_000_000, 1000).unwrap(), 0);
+        assert_eq!(stats.record_deposit(500_000_000, 400_000_000, 2000).unwrap(), 1);
+        assert_eq!(stats.deposit_count, 2);
+        assert_eq!(stats.total_deposits_e6, 1_500_000_000);
+        assert_eq!(stats.total_shares, 1_400_000_000);
+        assert_eq!(stats.first_deposit_ts, 1000);
+        assert_eq!(stats.last_deposit_ts, 2000);
+        
+        let attribution = DepositAttribution {
+            discriminator: DEPOSIT_ATTRIBUTION_DISCRIMINATOR,
+            campaign_id: 42,
+            seq: 1,
+            fund: Pubkey::new_unique(),
+            investor: Pubkey::new_unique(),
+            amount_e6: 500_000_000,
+            shares: 400_000_000,
+            nav_e6: 1_250_000,
+            deposited_at: 2000,
+            bump: 255,
+        };
+        assert_eq!(attribution.try_to_vec().unwrap().len(), DepositAttribution::SIZE);
+    }
+
+    #[test]
+    fn test_pm_maker_epoch_reward() {
+        let mut epoch = PMMakerEpoch::new(PMMakerEpoch::epoch_at(PM_MAKER_EPOCH_SECS * 3 + 5), 255);
+        assert_eq!(epoch.epoch, 3);
+        assert_eq!(epoch.try_to_vec().unwrap().len(), PMMakerEpoch::SIZE);
+        assert_eq!(
+            PMMakerVolume::new(Pubkey::new_unique(), 3, 255).try_to_vec().unwrap().len(),
+            PMMakerVolume::SIZE
+        );
+        
+        assert!(!epoch.is_ended(PM_MAKER_EPOCH_SECS * 4 - 1));
+        assert!(epoch.is_ended(PM_MAKER_EPOCH_SECS * 4));
+        
+        // No volume recorded: nothing to distribute
+        assert_eq!(epoch.reward_for(1_000_000), 0);
+        
+        epoch.total_maker_volume_e6 = 4_000_000_000;
+        epoch.reward_pool_e6 = 100_000_000;
+        assert_eq!(epoch.reward_for(1_000_000_000), 25_000_000);
+        assert_eq!(epoch.reward_for(3_000_000_000), 75_000_000);
+        assert_eq!(epoch.reward_for(0), 0);
+    }
+
+    #[test]
+    fn test_pm_category_fees() {
+        let mut config = PredictionMarketFeeConfig::new(
+            Pubkey::new_unique(),
+            255,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1700000000,
+        );
+        assert_eq!(config.try_to_vec().unwrap().len(), PredictionMarketFeeConfig::SIZE);
+        
+        let defaults = config.prediction_market_category_fees(None).unwrap();
+        assert_eq!(defaults.trading_fee_taker_bps, 10);
+        
+        // Unset category falls back to defaults
+        assert_eq!(config.prediction_market_category_fees(Some(2)).unwrap(), defaults);
+        
+        let sports = PMCategoryFees { minting_fee_bps: 5, redemption_fee_bps: 5, trading_fee_taker_bps: 25 };
+        config.set_category_fees(2, Some(sports)).unwrap();
+        assert_eq!(config.prediction_market_category_fees(Some(2)).unwrap(), sports);
+        assert_eq!(config.prediction_market_category_fees(Some(3)).unwrap(), defaults);
+        
+        config.set_category_fees(2, None).unwrap();
+        assert_eq!(config.prediction_market_category_fees(Some(2)).unwrap(), defaults);
+        
+        // Out-of-range index is rejected
+        assert!(config.prediction_market_category_fees(Some(MAX_PM_CATEGORIES as u8)).is_err());
+        assert!(config.set_category_fees(MAX_PM_CATEGORIES as u8, Some(sports)).is_err());
+    }
+
+    #[test]
+    fn test_treasury() {
+        let vault = Pubkey::new_unique();
+        let mut treasury = Treasury::new(vault, 254, 1700000000);
+        assert_eq!(treasury.try_to_vec().unwrap().len(), Treasury::SIZE);
+        
+        treasury.record_inflow(TreasurySource::PredictionMarketProtocol, 700_000);
+        treasury.record_inflow(TreasurySource::SquarePlatform, 300_000);
+        treasury.record_inflow(TreasurySource::SquarePlatform, 100_000);
+        assert_eq!(treasury.total_routed_e6[TreasurySource::SquarePlatform as usize], 400_000);
+        assert_eq!(treasury.total_inflow_e6(), 1_100_000);
+        
+        assert!(!treasury.is_withdrawal_executable(i64::MAX));
+        let destination = Pubkey::new_unique();
+        treasury.schedule_withdrawal(500_000, destination, 1700000000);
+        assert!(treasury.has_pending_withdrawal());
+        assert!(!treasury.is_withdrawal_executable(1700000000 + Treasury::WITHDRAWAL_TIMELOCK_SECS - 1));
+        assert!(treasury.is_withdrawal_executable(1700000000 + Treasury::WITHDRAWAL_TIMELOCK_SECS));
+        
+        assert_eq!(treasury.complete_withdrawal(), 500_000);
+        assert_eq!(treasury.total_withdrawn_e6, 500_000);
+        assert!(!treasury.has_pending_withdrawal());
+        assert_eq!(treasury.pending_destination, Pubkey::default());
+    }
+
+    #[test]
+    fn test_pm_unswept_protocol_income() {
+        let mut config = PredictionMarketFeeConfig::new(
+            Pubkey::new_unique(),
+            255,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1700000000,
+        );
+        config.record_prediction_market_trading_fee(1_000_000, 1700000001);
+        assert_eq!(config.prediction_market_unswept_protocol_income_e6(), 700_000);
+        
+        config.record_prediction_market_protocol_swept(700_000, 1700000002);
+        assert_eq!(config.prediction_market_unswept_protocol_income_e6(), 0);
+    }
+
+    #[test]
+    fn test_balances_checkpoint() {
+        let fund = Pubkey::new_unique();
+        let mut checkpoint = BalancesCheckpoint::new(fund, 253);
+        assert_eq!(checkpoint.try_to_vec().unwrap().len(), BalancesCheckpoint::SIZE);
+        
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let a = balance_leaf_hash(&alice, 600);
+        let b = balance_leaf_hash(&bob, 400);
+        let (l, r) = if a <= b { (a, b) } else { (b, a) };
+        let root = solana_program::hash::hashv(&[&[1u8], &l, &r]).to_bytes();
+        
+        // Nothing committed yet
+        assert!(!checkpoint.verify(&alice, 600, &[b]));
+        
+        let operator = Pubkey::new_unique();
+        checkpoint.commit(root, 1700000000, 2, 1000, operator, 1700000100).unwrap();
+        assert!(checkpoint.verify(&alice, 600, &[b]));
+        assert!(checkpoint.verify(&bob, 400, &[a]));
+        assert!(!checkpoint.verify(&alice, 601, &[b]));
+        
+        // Snapshots must be in the past and strictly newer than the last one
+        assert!(checkpoint.commit(root, 1700000000, 2, 1000, operator, 1700000200).is_err());
+        assert!(checkpoint.commit(root, 1700000300, 2, 1000, operator, 1700000200).is_err());
+        checkpoint.commit(root, 1700000150, 2, 1000, operator, 1700000200).unwrap();
+        assert_eq!(checkpoint.checkpoint_count, 2);
     }
 
     #[test]
-    fn test_fund_creation() {
-        let manager = Pubkey::new_unique();
-        let vault = Pubkey::new_unique();
-        let mint = Pubkey::new_unique();
-        let fee_config = FeeConfig::new(200, 2000);
+    fn test_fund_pause_auto_expiry() {
+        let mut config = FundConfig::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        assert_eq!(config.try_to_vec().unwrap().len(), FundConfig::SIZE);
+        assert_eq!(config.max_pause_for(0), FundConfig::DEFAULT_MAX_PAUSE_SECS);
+        // Overrides are clamped to the admin policy
+        assert_eq!(config.max_pause_for(7 * 86400), 7 * 86400);
+        assert_eq!(config.max_pause_for(u32::MAX), FundConfig::DEFAULT_MAX_PAUSE_OVERRIDE_SECS);
+        config.default_max_pause_secs = 86400;
+        assert_eq!(config.max_pause_for(0), 86400);
         
-        let fund = Fund::new(
-            manager,
-            "Test Fund",
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Paused Fund",
             254,
-            vault,
-            mint,
-            fee_config,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
             1,
-            1000000,
+            1700000000,
         );
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
         
-        assert_eq!(fund.manager, manager);
-        assert_eq!(fund.name_str(), "Test Fund");
-        assert!(fund.is_open);
-        assert!(!fund.is_paused);
-        assert_eq!(fund.stats.current_nav_e6, INITIAL_NAV_E6);
+        fund.set_paused(true, config.max_pause_for(fund.max_pause_secs), 1700000000);
+        assert!(!fund.can_withdraw(1700000000 + 86399));
+        assert!(!fund.is_pause_expired(1700000000 + 86399));
+        assert!(fund.can_withdraw(1700000000 + 86400));
+        assert!(fund.is_pause_expired(1700000000 + 86400));
+        // Still paused for deposits
+        assert!(!fund.can_deposit());
+        
+        fund.set_paused(false, 0, 1700100000);
+        assert_eq!((fund.paused_at, fund.pause_expires_at), (0, 0));
+        assert!(fund.can_withdraw(1700100000));
+        
+        // Protected funds never auto-expire
+        fund.is_system_fund = true;
+        fund.set_paused(true, 86400, 1700200000);
+        assert_eq!(fund.pause_expires_at, 0);
+        assert!(!fund.can_withdraw(i64::MAX));
     }
 
     #[test]
-    fn test_fund_deposit_withdrawal() {
-        let manager = Pubkey::new_unique();
-        let vault = Pubkey::new_unique();
-        let mint = Pubkey::new_unique();
-        let fee_config = FeeConfig::new(200, 2000);
-        
+    fn test_fund_pause_toggle_limits() {
         let mut fund = Fund::new(
-            manager,
-            "Test Fund",
+            Pubkey::new_unique(),
+            "Flicker Fund",
             254,
-            vault,
-            mint,
-            fee_config,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
             1,
-            1000000,
+            1700000000,
         );
+        let day_start = 19_676 * 86400; // 2023-11-15 00:00 UTC
+        let cooldown = Fund::PAUSE_TOGGLE_COOLDOWN_SECS;
         
-        // Record deposit
-        fund.record_deposit(100_000_000, 100_000_000).unwrap();
-        assert_eq!(fund.stats.total_deposits_e6, 100_000_000);
-        assert_eq!(fund.stats.total_shares, 100_000_000);
+        assert!(fund.check_pause_toggle(day_start).is_ok());
+        fund.record_pause_toggle(day_start);
+        // Cooldown between toggles
+        assert!(fund.check_pause_toggle(day_start + cooldown - 1).is_err());
+        assert!(fund.check_pause_toggle(day_start + cooldown).is_ok());
         
-        // Record withdrawal
-        fund.record_withdrawal(50_000_000, 50_000_000).unwrap();
-        assert_eq!(fund.stats.total_withdrawals_e6, 50_000_000);
-        assert_eq!(fund.stats.total_shares, 50_000_000);
+        for i in 1..Fund::MAX_PAUSE_TOGGLES_PER_DAY as i64 {
+            fund.record_pause_toggle(day_start + i * cooldown);
+        }
+        assert_eq!(fund.pause_toggles_today, Fund::MAX_PAUSE_TOGGLES_PER_DAY);
+        // Daily cap, reset on the next UTC day
+        assert!(fund.check_pause_toggle(day_start + 20 * cooldown).is_err());
+        assert!(fund.check_pause_toggle(day_start + 86400).is_ok());
+        fund.record_pause_toggle(day_start + 86400);
+        assert_eq!(fund.pause_toggles_today, 1);
     }
 
     #[test]
-    fn test_lp_position() {
-        let fund = Pubkey::new_unique();
-        let investor = Pubkey::new_unique();
-        
-        let mut position = LPPosition::new(
-            fund,
-            investor,
-            100_000_000, // 100 shares
-            1_000_000,   // NAV = 1.0
-            100_000_000, // 100 USDC
-            1000000,
+    fn test_fund_state_transitions() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Lifecycle Fund",
             254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
         );
+        assert_eq!(fund.lifecycle_state(), FundLifecycleState::Open);
+        assert!(fund.state_transition(FundLifecycleState::Open).is_err());
+        assert_eq!(fund.state_transition(FundLifecycleState::DepositsClosed).unwrap(), (true, false));
+        assert_eq!(fund.state_transition(FundLifecycleState::Paused).unwrap(), (false, true));
         
-        // Check current value at NAV = 1.0
-        assert_eq!(position.current_value(1_000_000), 100_000_000);
+        // Pausing keeps is_open; leaving the pause sets it from the target
+        fund.is_open = false;
+        fund.set_paused(true, 0, 1700000100);
+        assert_eq!(fund.lifecycle_state(), FundLifecycleState::Paused);
+        assert!(fund.state_transition(FundLifecycleState::Paused).is_err());
+        assert_eq!(fund.state_transition(FundLifecycleState::Open).unwrap(), (true, true));
+        assert_eq!(fund.state_transition(FundLifecycleState::DepositsClosed).unwrap(), (false, true));
         
-        // Check current value at NAV = 1.5
-        assert_eq!(position.current_value(1_500_000), 150_000_000);
+        // Closed funds are final
+        fund.closed_at = 1700000200;
+        assert!(fund.state_transition(FundLifecycleState::Open).is_err());
+    }
+
+    #[test]
+    fn test_lp_fee_statement() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Statement Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        fund.record_deposit(1_000_000_000, 1_000_000_000).unwrap();
+        // Nothing crystallized: no epoch
+        fund.collect_fees_partial(0, 0, false, 0, 1700001000).unwrap();
+        assert_eq!(fund.fee_epoch, 0);
         
-        // Check unrealized PnL at NAV = 1.5
-        assert_eq!(position.unrealized_pnl(1_500_000), 50_000_000);
+        fund.collect_fees(4_000_000, 6_000_000, 1700002000).unwrap();
+        assert_eq!((fund.fee_epoch, fund.fee_epoch_at, fund.fee_epoch_shares), (1, 1700002000, 1_000_000_000));
         
-        // Add more shares
-        position.add_shares(50_000_000, 50_000_000, 1_000_000, 2000000).unwrap();
-        assert_eq!(position.shares, 150_000_000);
-        assert_eq!(position.total_deposited_e6, 150_000_000);
+        let mut statement = LPFeeStatement::new(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        assert_eq!(statement.try_to_vec().unwrap().len(), LPFeeStatement::SIZE);
         
-        // Remove shares
-        position.remove_shares(25_000_000, 25_000_000, 3000000).unwrap();
-        assert_eq!(position.shares, 125_000_000);
-        assert_eq!(position.total_withdrawn_e6, 25_000_000);
+        // 25% of the shares bears 25% of each fee
+        let entry = LPFeeStatement::entry_for(&fund, 250_000_000);
+        assert_eq!((entry.mgmt_fee_e6, entry.perf_fee_e6), (1_000_000, 1_500_000));
+        statement.append(entry).unwrap();
+        assert!(statement.append(entry).is_err());
+        
+        for epoch in 2..=(LP_FEE_STATEMENT_ENTRIES as u32 + 2) {
+            fund.collect_fees(1_000_000, 0, 1700002000 + epoch as i64).unwrap();
+            statement.append(LPFeeStatement::entry_for(&fund, 500_000_000)).unwrap();
+        }
+        assert_eq!(statement.entry_count, LP_FEE_STATEMENT_ENTRIES as u32 + 2);
+        assert_eq!(statement.total_mgmt_fee_e6, 1_000_000 + 500_000 * (LP_FEE_STATEMENT_ENTRIES as i64 + 1));
+        assert_eq!(statement.total_perf_fee_e6, 1_500_000);
+        let recent = statement.recent_entries();
+        assert_eq!(recent.len(), LP_FEE_STATEMENT_ENTRIES);
+        assert_eq!((recent[0].epoch, recent[LP_FEE_STATEMENT_ENTRIES - 1].epoch), (3, 18));
     }
 
     #[test]
-    fn test_fund_stats() {
-        let mut stats = FundStats::new(1000000);
+    fn test_stable_swap_route_min_output() {
+        let mut route = StableSwapRoute::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            6,
+            6,
+            Pubkey::new_unique(),
+            30,
+            255,
+            1700000000,
+        );
+        assert_eq!(route.try_to_vec().unwrap().len(), StableSwapRoute::SIZE);
+        // 1000 USDT at par less 0.3%
+        assert_eq!(route.min_output(1_000_000_000), 997_000_000);
         
-        assert_eq!(stats.current_nav_e6, INITIAL_NAV_E6);
-        assert_eq!(stats.high_water_mark_e6, INITIAL_NAV_E6);
-        assert_eq!(stats.total_shares, 0);
+        // Decimals are normalized to the output mint
+        route.input_decimals = 8;
+        assert_eq!(route.min_output(100_000_000_000), 997_000_000);
+        route.input_decimals = 6;
+        route.output_decimals = 9;
+        assert_eq!(route.min_output(1_000_000), 997_000_000);
         
-        // Simulate deposits
-        stats.total_deposits_e6 = 100_000_000;
-        stats.total_shares = 100_000_000;
-        stats.update_nav().unwrap();
+        route.record_swap(1_000_000, 999_000_000, 1700000100);
+        assert_eq!((route.total_input, route.total_output, route.swap_count), (1_000_000, 999_000_000, 1));
+    }
+
+    #[test]
+    fn test_share_checkpoints() {
+        let mut history = ShareCheckpoints::new(Pubkey::new_unique(), DEFAULT_SHARE_CHECKPOINT_THRESHOLD_BPS, 255);
+        assert_eq!(history.try_to_vec().unwrap().len(), ShareCheckpoints::SIZE);
+        assert!(history.latest().is_none());
         
-        assert_eq!(stats.current_nav_e6, 1_000_000); // NAV = 1.0
+        assert!(history.maybe_record(1700000000, 1_000_000_000, 1_000_000));
+        // 1% exactly is not "more than" the threshold
+        assert!(!history.maybe_record(1700000100, 1_010_000_000, 1_000_000));
+        assert!(history.maybe_record(1700000200, 1_010_000_001, 1_001_000));
+        assert!(history.maybe_record(1700000300, 900_000_000, 1_002_000));
+        assert_eq!(history.checkpoint_count, 3);
         
-        // Simulate profit
-        stats.total_realized_pnl_e6 = 20_000_000;
-        stats.update_nav().unwrap();
-        stats.update_hwm();
+        assert_eq!(history.checkpoint_at(1700000250).unwrap().total_shares, 1_010_000_001);
+        assert_eq!(history.checkpoint_at(1700000300).unwrap().nav_e6, 1_002_000);
+        assert!(history.checkpoint_at(1699999999).is_none());
         
-        // NAV should increase: (100 - 0 + 20 - 0 - 0) / 100 = 1.2
-        assert_eq!(stats.current_nav_e6, 1_200_000);
-        assert_eq!(stats.high_water_mark_e6, 1_200_000);
+        // Fully redeemed and refilled
+        assert!(history.maybe_record(1700000400, 0, 1_002_000));
+        assert!(history.maybe_record(1700000500, 1, 1_002_000));
+        
+        for i in 0..SHARE_CHECKPOINT_ENTRIES as u64 {
+            assert!(history.maybe_record(1700001000 + i as i64, (i + 1) * 1_000_000_000, 1_000_000));
+        }
+        let recent = history.recent_checkpoints();
+        assert_eq!(recent.len(), SHARE_CHECKPOINT_ENTRIES);
+        assert_eq!(recent.last().copied(), history.latest());
     }
 
-    // === Insurance Fund Config Tests ===
-
     #[test]
-    fn test_insurance_fund_config_size() {
-        assert!(InsuranceFundConfig::SIZE > 0);
-        println!("InsuranceFundConfig SIZE: {}", InsuranceFundConfig::SIZE);
+    fn test_notification_prefs() {
+        let mut prefs = NotificationPrefs::new(Pubkey::new_unique(), 255, 1700000000);
+        assert_eq!(prefs.try_to_vec().unwrap().len(), NotificationPrefs::SIZE);
+        assert!(!prefs.wants(NOTIFY_NAV_DROP));
+        
+        assert!(prefs.update([7u8; 32], NOTIFY_NAV_DROP | NOTIFY_WIND_DOWN, 1700000100).is_ok());
+        assert!(prefs.wants(NOTIFY_NAV_DROP));
+        assert!(!prefs.wants(NOTIFY_FEE_COLLECTED));
+        assert_eq!(prefs.updated_at, 1700000100);
+        
+        // Unknown bits and an empty commitment are rejected
+        assert!(prefs.update([7u8; 32], 1 << 3, 1700000200).is_err());
+        assert!(prefs.update([0u8; 32], NOTIFY_FEE_COLLECTED, 1700000200).is_err());
+        assert_eq!(prefs.event_mask, NOTIFY_NAV_DROP | NOTIFY_WIND_DOWN);
     }
 
     #[test]
-    fn test_insurance_fund_config_creation() {
-        let fund = Pubkey::new_unique();
-        let caller = Pubkey::new_unique();
-        
-        let config = InsuranceFundConfig::new(
-            fund,
+    fn test_fund_fee_rebate() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Rebate Fund",
             254,
-            100_000_000,      // 100 USDC threshold
-            3600,             // 1 hour delay
-            caller,
-            1000000,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
         );
+        fund.record_deposit(1_000_000_000, 1_000_000_000).unwrap();
+        fund.record_pnl(-20_000_000).unwrap();
         
-        assert_eq!(config.fund, fund);
-        assert_eq!(config.adl_trigger_threshold_e6, 100_000_000);
-        assert_eq!(config.withdrawal_delay_secs, 3600);
-        assert_eq!(config.total_liquidation_income_e6, 0);
-        assert!(!config.is_adl_in_progress);
+        fund.record_fee_rebate(5_000_000).unwrap();
+        assert!(fund.record_fee_rebate(0).is_err());
+        assert!(fund.record_fee_rebate(-1).is_err());
+        
+        // Rebates lift total value / NAV but stay out of trading PnL
+        assert_eq!(fund.total_fee_rebates_e6, 5_000_000);
+        assert_eq!(fund.stats.total_value_e6(), 985_000_000);
+        assert_eq!(fund.stats.current_nav_e6, 985_000);
+        assert_eq!(fund.trading_pnl_e6(), -20_000_000);
     }
 
     #[test]
-    fn test_insurance_fund_adl_trigger_conditions() {
-        let fund = Pubkey::new_unique();
-        let caller = Pubkey::new_unique();
-        
-        let mut config = InsuranceFundConfig::new(
-            fund,
+    fn test_fund_exposure_mirror() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Levered Fund",
             254,
-            100_000_000,      // 100 USDC threshold
-            3600,
-            caller,
-            1000000,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
         );
+        assert_eq!(fund.margin_utilization_bps(), 0);
+        assert!(!fund.has_fresh_exposure(1700000000));
         
-        // 设置1小时前余额
-        config.balance_1h_ago_e6 = 1000_000_000; // 1000 USDC
+        fund.record_exposure(3_000_000_000, 300_000_000, 700_000_000, 1700000100);
+        assert_eq!(fund.open_notional_e6, 3_000_000_000);
+        assert_eq!(fund.exposure_updated_at, 1700000100);
+        assert!(fund.has_fresh_exposure(1700000100 + RISK_EXPOSURE_MAX_AGE_SECS));
+        assert!(!fund.has_fresh_exposure(1700000101 + RISK_EXPOSURE_MAX_AGE_SECS));
+        assert_eq!(fund.margin_utilization_bps(), 3_000);
         
-        // 测试条件1: 穿仓触发
+        // Underwater: no free collateral left
+        fund.record_exposure(3_000_000_000, 300_000_000, -50_000_000, 1700000200);
+        assert_eq!(fund.margin_utilization_bps(), 10_000);
+    }
+
+    #[test]
+    fn test_fund_reconcile_share_supply() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Drifted Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        fund.record_deposit(1_000_000_000, 1_000_000_000).unwrap();
         assert_eq!(
-            config.should_trigger_adl(50_000_000, 100_000_000), // 余额50, 穿仓100
-            ADLTriggerReason::Bankruptcy
+            fund.reconcile_share_supply(1_000_000_000, true, 1700000100).unwrap(),
+            ShareSupplyReconciliation::Consistent
         );
         
-        // 测试条件2: 余额不足触发
+        // Small drift on a controlled mint: stats follow the mint
         assert_eq!(
-            config.should_trigger_adl(50_000_000, 0), // 余额50 < 阈值100
-            ADLTriggerReason::InsufficientBalance
+            fund.reconcile_share_supply(995_000_000, true, 1700000100).unwrap(),
+            ShareSupplyReconciliation::Fixed { previous_shares: 1_000_000_000 }
         );
+        assert_eq!(fund.stats.total_shares, 995_000_000);
+        assert!(fund.stats.current_nav_e6 > 1_000_000);
+        assert!(fund.can_deposit());
         
-        // 测试条件3: 1小时下降30%触发
+        // Any burn on a controlled mint is adopted, however large
         assert_eq!(
-            config.should_trigger_adl(600_000_000, 0), // 余额600 < 1000*0.7=700
-            ADLTriggerReason::RapidDecline
+            fund.reconcile_share_supply(800_000_000, true, 1700000150).unwrap(),
+            ShareSupplyReconciliation::Fixed { previous_shares: 995_000_000 }
         );
+        assert!(fund.can_deposit());
+        fund.stats.total_shares = 995_000_000;
         
-        // 测试正常情况: 不触发
+        // Growth past the cap, or a mint the fund does not control, is flagged
         assert_eq!(
-            config.should_trigger_adl(800_000_000, 0), // 余额800 > 阈值100, > 700
-            ADLTriggerReason::None
+            fund.reconcile_share_supply(1_200_000_000, true, 1700000200).unwrap(),
+            ShareSupplyReconciliation::Inconsistent
         );
-    }
-
-    #[test]
-    fn test_insurance_fund_cover_shortfall() {
-        let fund = Pubkey::new_unique();
-        let caller = Pubkey::new_unique();
-        
-        let mut config = InsuranceFundConfig::new(
-            fund,
-            254,
-            100_000_000,
-            3600,
-            caller,
-            1000000,
+        assert_eq!(fund.share_supply_inconsistent_since, 1700000200);
+        assert!(!fund.can_deposit());
+        assert_eq!(
+            fund.reconcile_share_supply(995_000_000, false, 1700000300).unwrap(),
+            ShareSupplyReconciliation::Inconsistent
         );
+        assert_eq!(fund.share_supply_inconsistent_since, 1700000200);
+        assert_eq!(fund.stats.total_shares, 995_000_000);
         
-        // 情况1: 完全覆盖
-        let (covered, remaining) = config.cover_shortfall(500_000_000, 1000_000_000);
-        assert_eq!(covered, 500_000_000);
-        assert_eq!(remaining, 0);
-        assert_eq!(config.total_shortfall_payout_e6, 500_000_000);
-        
-        // 情况2: 部分覆盖
-        let (covered, remaining) = config.cover_shortfall(600_000_000, 400_000_000);
-        assert_eq!(covered, 400_000_000);
-        assert_eq!(remaining, 200_000_000);
-        assert_eq!(config.total_shortfall_payout_e6, 900_000_000);
-    }
-
-    #[test]
-    fn test_insurance_fund_income_tracking() {
-        let fund = Pubkey::new_unique();
-        let caller = Pubkey::new_unique();
-        
-        let mut config = InsuranceFundConfig::new(
-            fund,
-            254,
-            100_000_000,
-            3600,
-            caller,
-            1000000,
+        // Once the supply is back in line the flag clears
+        assert_eq!(
+            fund.reconcile_share_supply(995_000_000, true, 1700000400).unwrap(),
+            ShareSupplyReconciliation::Consistent
         );
+        assert!(!fund.is_share_supply_inconsistent());
         
-        // 添加清算收入
-        config.add_liquidation_income(100_000_000);
-        assert_eq!(config.total_liquidation_income_e6, 100_000_000);
-        
-        // 添加ADL盈余
-        config.add_adl_profit(50_000_000);
-        assert_eq!(config.total_adl_profit_e6, 50_000_000);
-        
-        // 检查总收入
-        assert_eq!(config.total_income_e6(), 150_000_000);
-        
-        // 模拟支出
-        config.cover_shortfall(30_000_000, 1000_000_000);
-        
-        // 检查净收入
-        assert_eq!(config.net_income_e6(), 120_000_000); // 150 - 30
-    }
-
-    // === Square Payment Record Tests ===
-
-    #[test]
-    fn test_square_payment_record_size() {
-        assert!(SquarePaymentRecord::SIZE > 0);
-        println!("SquarePaymentRecord SIZE: {}", SquarePaymentRecord::SIZE);
+        // Admin clear
+        assert!(fund.clear_share_supply_flag().is_err());
+        fund.reconcile_share_supply(995_000_000, false, 1700000500).unwrap();
+        assert_eq!(fund.clear_share_supply_flag().unwrap(), 1700000500);
+        assert!(fund.can_deposit());
     }
 
     #[test]
-    fn test_square_payment_record_creation() {
-        let payer = Pubkey::new_unique();
-        let creator = Pubkey::new_unique();
-        let content_id = 12345u64;
-        let payment_type = SquarePaymentType::KnowledgePurchase;
-        let total_amount = 100_000_000i64; // 100 USDC
-        let creator_share_bps = 9000u16; // 90%
-        let timestamp = 1700000000i64;
-        
-        let record = SquarePaymentRecord::new(
-            payer,
-            creator,
-            content_id,
-            payment_type,
-            total_amount,
-            creator_share_bps,
-            timestamp,
-            0, // no subscription period
-            b"Test payment",
+    fn test_fund_record_expense() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Expense Fund",
             254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
         );
+        fund.record_deposit(1_000_000_000_000, 1_000_000_000_000).unwrap();
+        assert!(fund.record_expense(1_000_000, 1700000000).is_err());
         
-        assert_eq!(record.payer, payer);
-        assert_eq!(record.creator, creator);
-        assert_eq!(record.content_id, content_id);
-        assert_eq!(record.payment_type, SquarePaymentType::KnowledgePurchase);
-        assert_eq!(record.total_amount_e6, 100_000_000);
-        assert_eq!(record.creator_amount_e6, 90_000_000); // 90%
-        assert_eq!(record.platform_amount_e6, 10_000_000); // 10%
-        assert_eq!(record.creator_share_bps, 9000);
-        assert_eq!(record.payment_ts, timestamp);
-        assert!(!record.is_subscription());
+        // 0.5% a year, at most 3,000 USDC per payment
+        fund.expense_cap_bps = 50;
+        fund.max_expense_payment_e6 = 3_000_000_000;
+        assert_eq!(fund.expense_cap_e6(), 5_000_000_000);
+        assert!(fund.record_expense(0, 1700000000).is_err());
+        assert!(fund.record_expense(3_000_000_001, 1700000000).is_err());
+        
+        fund.record_expense(3_000_000_000, 1700000100).unwrap();
+        assert_eq!(fund.expense_period_start, 1700000100);
+        assert_eq!(fund.total_expenses_e6, 3_000_000_000);
+        assert_eq!(fund.stats.current_nav_e6, 997_000);
+        assert_eq!(fund.trading_pnl_e6(), 0);
+        
+        // Cap is on the current value: 0.5% of 997,000 USDC
+        assert!(fund.record_expense(2_000_000_000, 1700000200).is_err());
+        fund.record_expense(1_985_000_000, 1700000200).unwrap();
+        assert_eq!(fund.expenses_paid_in_period_e6(1700000200), 4_985_000_000);
+        
+        // A new period starts a year after the first payment
+        let next = 1700000100 + Fund::EXPENSE_PERIOD_SECS;
+        assert_eq!(fund.expenses_paid_in_period_e6(next), 0);
+        fund.record_expense(1_000_000_000, next).unwrap();
+        assert_eq!(fund.expense_period_start, next);
+        assert_eq!(fund.expense_period_paid_e6, 1_000_000_000);
+        assert_eq!(fund.total_expenses_e6, 5_985_000_000);
     }
 
     #[test]
-    fn test_square_payment_subscription() {
-        let payer = Pubkey::new_unique();
-        let creator = Pubkey::new_unique();
-        
-        let record = SquarePaymentRecord::new(
-            payer,
-            creator,
-            99999,
-            SquarePaymentType::Subscription,
-            50_000_000, // 50 USDC
-            8500, // 85%
-            1700000000,
-            12, // 12 months
-            b"Monthly sub",
+    fn test_fund_check_vault_mint() {
+        let share_mint = Pubkey::new_unique();
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Mint Check Fund",
             254,
+            Pubkey::new_unique(),
+            share_mint,
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
         );
+        let usdc = Pubkey::new_unique();
+        let native = spl_token::native_mint::id();
         
-        assert!(record.is_subscription());
-        assert_eq!(record.subscription_period, 12);
-        assert_eq!(record.creator_amount_e6, 42_500_000); // 85%
-        assert_eq!(record.platform_amount_e6, 7_500_000); // 15%
+        // Self-referential vault and denomination mismatches
+        assert!(fund.check_vault_mint(&share_mint).is_err());
+        assert!(fund.check_vault_mint(&native).is_err());
+        assert!(fund.check_vault_mint(&usdc).is_ok());
+        fund.sol_denominated = true;
+        assert!(fund.check_vault_mint(&usdc).is_err());
+        assert!(fund.check_vault_mint(&native).is_ok());
+        fund.sol_denominated = false;
+        
+        // Once validated, only the recorded mint is accepted
+        assert!(!fund.is_setup_validated());
+        fund.denomination_mint = usdc;
+        assert!(fund.is_setup_validated());
+        assert!(fund.check_vault_mint(&usdc).is_ok());
+        assert!(fund.check_vault_mint(&Pubkey::new_unique()).is_err());
     }
 
     #[test]
-    fn test_square_payment_live_donation() {
-        let payer = Pubkey::new_unique();
-        let creator = Pubkey::new_unique();
-        
-        let record = SquarePaymentRecord::new(
-            payer,
-            creator,
+    fn test_fund_nav_twap() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Twap Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
             1,
-            SquarePaymentType::LiveDonation,
-            10_000_000, // 10 USDC
-            7000, // 70%
             1700000000,
-            0,
-            b"Great stream!",
-            254,
         );
+        let t0 = 1700000000;
+        let window = Fund::NAV_TWAP_WINDOW_SECS;
+        fund.stats.current_nav_e6 = 1_000_000;
+        // No history yet: spot NAV
+        assert_eq!(fund.nav_twap_e6(t0), 1_000_000);
         
-        assert_eq!(record.payment_type, SquarePaymentType::LiveDonation);
-        assert_eq!(record.creator_amount_e6, 7_000_000); // 70%
-        assert_eq!(record.platform_amount_e6, 3_000_000); // 30%
-        assert_eq!(record.memo_str(), "Great stream!");
+        // Not started (legacy account): accrual is a no-op and the TWAP is spot
+        let mut legacy = fund.clone();
+        legacy.nav_cumulative_ts = 0;
+        legacy.twap_last_ts = 0;
+        legacy.accrue_aum(t0 + window);
+        assert_eq!((legacy.nav_cumulative_e6, legacy.twap_last_ts), (0, 0));
+        assert_eq!(legacy.nav_twap_e6(t0 + window), 1_000_000);
+        legacy.start_nav_twap(t0 + window);
+        assert_eq!(legacy.nav_cumulative_ts, t0 + window);
+        
+        fund.accrue_aum(t0);
+        fund.accrue_aum(t0 + window);
+        assert_eq!((fund.twap_prev_ts, fund.twap_last_ts), (t0, t0 + window));
+        
+        // A spike right before collection barely moves the TWAP
+        fund.stats.current_nav_e6 = 2_000_000;
+        let twap = fund.nav_twap_e6(t0 + window + 60);
+        assert_eq!(twap, (3600 * 1_000_000 + 60 * 2_000_000) / 3660);
+        assert_eq!(fund.fee_nav_e6(t0 + window + 60), twap);
+        
+        // Sustained for a full window the TWAP catches up
+        fund.accrue_aum(t0 + 2 * window);
+        assert_eq!(fund.nav_twap_e6(t0 + 3 * window), 2_000_000);
+        // Spot below TWAP: fees use spot
+        fund.stats.current_nav_e6 = 1_500_000;
+        assert_eq!(fund.fee_nav_e6(t0 + 2 * window), 1_500_000);
     }
 
     #[test]
-    fn test_square_payment_memo_truncation() {
-        let payer = Pubkey::new_unique();
-        let creator = Pubkey::new_unique();
-        
-        // Test with a long memo that should be truncated
-        let long_memo = b"This is a very long memo that exceeds 32 bytes and should be truncated";
-        let record = SquarePaymentRecord::new(
-            payer,
-            creator,
+    fn test_fund_full_exit_value() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Exit Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
             1,
-            SquarePaymentType::KnowledgePurchase,
-            10_000_000,
-            9000,
             1700000000,
-            0,
-            long_memo,
-            254,
         );
+        // 3 shares worth 10 e6 total: NAV truncates to 3_333_333
+        fund.record_deposit(10, 3).unwrap();
+        assert_eq!(fund.full_exit_value_e6(1).unwrap(), calculate_redemption_value(1, fund.stats.current_nav_e6).unwrap());
         
-        // Memo should be truncated to 32 bytes
-        assert_eq!(record.memo.len(), 32);
-        // First 32 bytes should match
-        assert_eq!(&record.memo[..], &long_memo[..32]);
+        // The last LP out takes everything, no dust left
+        assert_eq!(fund.full_exit_value_e6(3).unwrap(), 10);
     }
 
     #[test]
-    fn test_square_payment_seeds() {
-        let payer = Pubkey::new_unique();
-        let content_id = 12345u64;
-        let timestamp = 1700000000i64;
+    fn test_insurance_health_helpers() {
+        let mut config = InsuranceFundConfig::new(
+            Pubkey::new_unique(),
+            254,
+            100_000_000,
+            3600,
+            Pubkey::new_unique(),
+            1000000,
+        );
+        config.take_daily_deltas();
+        config.add_liquidation_income(10_000_000);
+        config.cover_shortfall(4_000_000, 1_000_000_000);
         
-        let seeds = SquarePaymentRecord::seeds(&payer, content_id, timestamp);
+        // 只读: 不移动基线
+        assert_eq!(config.pending_daily_deltas(), (10_000_000, 0, 4_000_000));
+        assert_eq!(config.pending_daily_deltas(), (10_000_000, 0, 4_000_000));
+        assert_eq!(config.take_daily_deltas(), (10_000_000, 0, 4_000_000));
+        assert_eq!(config.pending_daily_deltas(), (0, 0, 0));
         
-        assert_eq!(seeds.len(), 4);
-        assert_eq!(seeds[0], SQUARE_PAYMENT_RECORD_SEED.to_vec());
-        assert_eq!(seeds[1], payer.to_bytes().to_vec());
-        assert_eq!(seeds[2], content_id.to_le_bytes().to_vec());
-        assert_eq!(seeds[3], timestamp.to_le_bytes().to_vec());
+        assert_eq!(InsuranceFundConfig::coverage_ratio_bps(50_000_000, 1_000_000_000), 500);
+        
+        // Published coverage: needs a reported OI and goes stale
+        assert!(config.update_coverage(50_000_000, 1000100).is_err());
+        assert!(config.record_open_interest(-1, 1000100).is_err());
+        config.record_open_interest(1_000_000_000, 1000100).unwrap();
+        assert_eq!(config.update_coverage(50_000_000, 1000200).unwrap(), 500);
+        let max_age = InsuranceFundConfig::COVERAGE_MAX_AGE_SECS;
+        assert_eq!(config.published_coverage_bps(1000100 + max_age), Some(500));
+        assert_eq!(config.published_coverage_bps(1000101 + max_age), None);
+        
+        // Ledger-side offset of the coverage fields
+        let data = config.try_to_vec().unwrap();
+        assert_eq!(data.len(), InsuranceFundConfig::SIZE);
+        let offset = InsuranceFundConfig::COVERAGE_OFFSET;
+        assert_eq!(data[offset..offset + 8], 1_000_000_000i64.to_le_bytes());
+        assert_eq!(data[offset + 16..offset + 24], 500u64.to_le_bytes());
+        
+        // Configs created before the coverage fields still decode
+        let legacy = &data[..offset];
+        let decoded = InsuranceFundConfig::unpack_versioned(legacy).unwrap();
+        assert_eq!(decoded.coverage_updated_at, 0);
+        assert_eq!(InsuranceFundConfig::coverage_ratio_bps(2_000_000_000, 1_000_000_000), 20_000);
+        assert_eq!(InsuranceFundConfig::coverage_ratio_bps(-1, 1_000_000_000), 0);
+        assert_eq!(InsuranceFundConfig::coverage_ratio_bps(1, 0), u64::MAX);
     }
 
-    // === Referral Config Tests ===
-
     #[test]
-    fn test_referral_config_size() {
-        assert!(ReferralConfig::SIZE > 0);
-        println!("ReferralConfig SIZE: {}", ReferralConfig::SIZE);
+    fn test_manager_risk() {
+        let mut limits = ManagerRiskLimits::new(255);
+        assert_eq!(limits.try_to_vec().unwrap().len(), ManagerRiskLimits::SIZE);
+        assert!(!limits.has_caps());
+        assert!(limits.check(u64::MAX, 1, 100).is_ok());
+        
+        limits.max_notional_e6 = 1_000_000_000;
+        limits.max_leverage = 10;
+        assert!(limits.has_caps());
+        assert!(limits.check(600_000_000, 400_000_000, 10).is_ok());
+        assert_eq!(
+            limits.check(600_000_000, 400_000_001, 10),
+            Err(crate::error::FundError::ManagerNotionalCapExceeded.into())
+        );
+        assert_eq!(
+            limits.check(0, 1, 11),
+            Err(crate::error::FundError::ManagerLeverageCapExceeded.into())
+        );
+        
+        let mut state = ManagerRiskState::new(Pubkey::new_unique(), 254, 1700000000);
+        assert_eq!(state.try_to_vec().unwrap().len(), ManagerRiskState::SIZE);
+        state.record_open(600_000_000, 1700000100);
+        state.record_open(300_000_000, 1700000200);
+        state.record_close(500_000_000, 1700000300);
+        assert_eq!(state.open_notional_e6, 400_000_000);
+        assert_eq!(state.peak_notional_e6, 900_000_000);
+        assert_eq!(state.trade_count, 3);
+        // Closing at a higher price than opened floors at zero
+        state.record_close(1_000_000_000, 1700000400);
+        assert_eq!(state.open_notional_e6, 0);
     }
 
     #[test]
-    fn test_referral_config_creation() {
-        let authority = Pubkey::new_unique();
-        let vault_program = Pubkey::new_unique();
+    fn test_manager_risk_breach() {
+        let mut limits = ManagerRiskLimits::new(255);
+        assert_eq!(limits.fund_excess_notional_e6(u64::MAX, u64::MAX, 0), 0);
         
-        let config = ReferralConfig::new(
-            authority,
-            vault_program,
-            DEFAULT_REFERRER_SHARE_BPS,  // 20%
-            DEFAULT_REFEREE_DISCOUNT_BPS, // 10%
+        limits.max_notional_e6 = 1_000_000_000;
+        limits.max_leverage = 5;
+        // 900 open on 200 equity: within both caps
+        assert_eq!(limits.fund_excess_notional_e6(900_000_000, 900_000_000, 200_000_000), 0);
+        // A loss shrinks equity to 150: the book may only be 750
+        assert_eq!(limits.fund_excess_notional_e6(900_000_000, 900_000_000, 150_000_000), 150_000_000);
+        // The larger of the two excesses wins
+        assert_eq!(limits.fund_excess_notional_e6(1_200_000_000, 1_200_000_000, 1_000_000_000), 200_000_000);
+        assert_eq!(limits.fund_excess_notional_e6(100, 100, -1), 100);
+        // Cap excess split by each fund's share of the book: a small fund with
+        // no leverage breach only carries its 1/4 of the 200 over the cap
+        assert_eq!(limits.fund_excess_notional_e6(1_200_000_000, 300_000_000, 1_000_000_000), 50_000_000);
+        // A fund with nothing open cannot be charged for the others
+        assert_eq!(limits.fund_excess_notional_e6(1_200_000_000, 0, 0), 0);
+        
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Risk Fund",
             254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
             1700000000,
         );
+        assert!(!fund.is_risk_breach_enforceable(1700000000));
+        assert!(fund.observe_risk_breach(150_000_000, 1700000100));
+        assert!(!fund.observe_risk_breach(120_000_000, 1700000200));
+        assert_eq!(fund.risk_breach_since, 1700000100);
+        assert!(!fund.is_risk_breach_enforceable(1700000100 + RISK_BREACH_GRACE_SECS - 1));
+        assert!(fund.is_risk_breach_enforceable(1700000100 + RISK_BREACH_GRACE_SECS));
+        assert!(!fund.observe_risk_breach(0, 1700000300));
+        assert_eq!(fund.risk_breach_since, 0);
         
-        assert_eq!(config.authority, authority);
-        assert_eq!(config.referrer_share_bps, 2000);
-        assert_eq!(config.referee_discount_bps, 1000);
-        assert!(!config.is_paused);
-        assert_eq!(config.total_referral_links, 0);
+        fund.record_exposure(300_000_000, 60_000_000, 0, 1700000300);
+        assert_eq!(fund.record_enforced_close(100_000_000), 100_000_000);
+        assert_eq!(fund.record_enforced_close(500_000_000), 200_000_000);
+        assert_eq!(fund.open_notional_e6, 0);
+        
+        assert_eq!(risk_enforcement_keeper_fee_e6(150_000_000), 150_000);
+        assert_eq!(risk_enforcement_keeper_fee_e6(u64::MAX), MAX_RISK_ENFORCEMENT_KEEPER_FEE_E6);
     }
 
     #[test]
-    fn test_referral_config_vip_bonus() {
-        let authority = Pubkey::new_unique();
-        let vault_program = Pubkey::new_unique();
+    fn test_mark_price() {
+        let mut mark = MarkPrice::new(3, 254);
+        assert_eq!(mark.try_to_vec().unwrap().len(), MarkPrice::SIZE);
+        assert_eq!(mark.fresh_price_e6(1700000000), None);
+        assert!(mark.update(0, 1700000000).is_err());
         
-        let config = ReferralConfig::new(
-            authority,
-            vault_program,
-            2000, // 20%
-            1000, // 10%
+        mark.update(50_000_000_000, 1700000000).unwrap();
+        assert_eq!(mark.fresh_price_e6(1700000000 + MARK_PRICE_MAX_AGE_SECS), Some(50_000_000_000));
+        assert_eq!(mark.fresh_price_e6(1700000001 + MARK_PRICE_MAX_AGE_SECS), None);
+        
+        // 0.5% band around 50,000
+        assert!(MarkPrice::within_band(50_000_000_000, 49_750_000_000, RISK_ENFORCEMENT_MAX_PRICE_DEVIATION_BPS));
+        assert!(MarkPrice::within_band(50_000_000_000, 50_250_000_000, RISK_ENFORCEMENT_MAX_PRICE_DEVIATION_BPS));
+        assert!(!MarkPrice::within_band(50_000_000_000, 49_749_999_999, RISK_ENFORCEMENT_MAX_PRICE_DEVIATION_BPS));
+        assert_ne!(MarkPrice::seeds(3), MarkPrice::seeds(4));
+    }
+
+    #[test]
+    fn test_nav_floor_wind_down_review() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Collapsed Fund",
             254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
             1700000000,
         );
+        fund.record_deposit(100_000_000, 100_000_000).unwrap();
+        assert!(!fund.check_nav_floor(1700000100));
         
-        // VIP 0: 20% base + 0% bonus = 20%
-        assert_eq!(config.get_referrer_share(0), 2000);
-        assert_eq!(config.get_referee_discount(0), 1000);
-        
-        // VIP 3: 20% base + 10% bonus = 30%
-        assert_eq!(config.get_referrer_share(3), 3000);
-        assert_eq!(config.get_referee_discount(3), 2000);
+        // Lose 99.995% of the fund: NAV 0.00005 < 0.01
+        fund.record_pnl(-99_995_000).unwrap();
+        assert!(fund.is_below_nav_floor());
+        assert!(fund.check_nav_floor(1700000200));
+        assert!(!fund.check_nav_floor(1700000300));
+        assert_eq!(fund.wind_down_review_since, 1700000200);
+        assert!(!fund.can_deposit());
+        assert!(fund.can_withdraw(1700000300));
         
-        // VIP 5: 20% base + 20% bonus = 40%
-        assert_eq!(config.get_referrer_share(5), 4000);
-        assert_eq!(config.get_referee_discount(5), 3000);
+        // Review stays until resolved, even once NAV recovers
+        fund.record_pnl(99_995_000).unwrap();
+        assert!(!fund.is_below_nav_floor());
+        assert!(!fund.can_deposit());
+        fund.wind_down_review_since = 0;
+        assert!(fund.can_deposit());
     }
 
     #[test]
-    fn test_referral_reward_calculation() {
-        let authority = Pubkey::new_unique();
-        let vault_program = Pubkey::new_unique();
+    fn test_update_nav_wiped_out_fund() {
+        // Total value ≤ 0 with shares outstanding: NAV floors at 0
+        let mut stats = FundStats::new(1700000000);
+        stats.total_deposits_e6 = 100_000_000;
+        stats.total_shares = 100_000_000;
+        stats.total_realized_pnl_e6 = -150_000_000;
+        assert!(stats.update_nav().is_ok());
+        assert_eq!(stats.current_nav_e6, 0);
         
-        let config = ReferralConfig::new(
-            authority,
-            vault_program,
-            2000, // 20%
-            1000, // 10%
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Wiped Fund",
             254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
             1700000000,
         );
+        fund.record_deposit(100_000_000, 100_000_000).unwrap();
+        fund.record_pnl(-150_000_000).unwrap();
+        assert_eq!(fund.stats.current_nav_e6, 0);
         
-        // 测试: $100 手续费, VIP 0
-        let (referrer_reward, referee_discount, platform_income) = 
-            config.calculate_rewards(100_000_000, 0, 0);
-        
-        // 被邀请人折扣: $100 * 10% = $10
-        assert_eq!(referee_discount, 10_000_000);
-        // 实际收费: $100 - $10 = $90
-        // 邀请人返佣: $90 * 20% = $18
-        assert_eq!(referrer_reward, 18_000_000);
-        // 平台收入: $90 - $18 = $72
-        assert_eq!(platform_income, 72_000_000);
+        // Redemptions pay 0, including the last LP out
+        assert_eq!(calculate_redemption_value(10_000_000, fund.stats.current_nav_e6).unwrap(), 0);
+        assert_eq!(fund.full_exit_value_e6(10_000_000).unwrap(), 0);
+        assert_eq!(fund.full_exit_value_e6(100_000_000).unwrap(), 0);
         
-        // 测试: $100 手续费, VIP 3 (取较高)
-        let (referrer_reward, referee_discount, platform_income) = 
-            config.calculate_rewards(100_000_000, 3, 1);
+        // Deposits are blocked by the NAV floor
+        assert_eq!(
+            crate::utils::calculate_shares_to_mint(10_000_000, fund.stats.current_nav_e6),
+            Err(crate::error::FundError::NAVBelowFloor.into())
+        );
+        assert!(fund.check_nav_floor(1700000100));
+        assert!(!fund.can_deposit());
         
-        // VIP 3 折扣: 10% + 10% = 20%
-        // 被邀请人折扣: $100 * 20% = $20
-        assert_eq!(referee_discount, 20_000_000);
-        // 实际收费: $100 - $20 = $80
-        // VIP 3 分成: 20% + 10% = 30%
-        // 邀请人返佣: $80 * 30% = $24
-        assert_eq!(referrer_reward, 24_000_000);
-        // 平台收入: $80 - $24 = $56
-        assert_eq!(platform_income, 56_000_000);
+        // Value back above 0: NAV recovers (50 USDC over 100 shares)
+        fund.record_pnl(100_000_000).unwrap();
+        assert_eq!(fund.stats.current_nav_e6, 500_000);
+        assert_eq!(calculate_redemption_value(10_000_000, fund.stats.current_nav_e6).unwrap(), 5_000_000);
+        assert_eq!(fund.full_exit_value_e6(100_000_000).unwrap(), 50_000_000);
     }
 
-    // === Referral Link Tests ===
-
     #[test]
-    fn test_referral_link_size() {
-        assert!(ReferralLink::SIZE > 0);
-        println!("ReferralLink SIZE: {}", ReferralLink::SIZE);
+    fn test_fund_closed_at_and_legacy_fit() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Closing Fund",
+            254,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            1,
+            1700000000,
+        );
+        // Trailing zero fields fit an account that predates them
+        fund.nav_cumulative_ts = 0;
+        fund.twap_last_ts = 0;
+        let pre_closed_at_len = Fund::SIZE - 392;
+        assert!(fund.fits_in(pre_closed_at_len));
+        
+        fund.is_open = false;
+        fund.closed_at = 1700086400;
+        assert!(fund.is_closed());
+        assert!(!fund.can_deposit());
+        assert!(fund.fits_in(pre_closed_at_len + 8));
+        assert!(!fund.fits_in(pre_closed_at_len));
+        
+        let mut data = vec![0u8; pre_closed_at_len];
+        assert_eq!(fund.pack_versioned(&mut data), Err(ProgramError::AccountDataTooSmall));
     }
 
     #[test]
-    fn test_referral_link_creation() {
-        let referrer = Pubkey::new_unique();
-        let code = b"ALICE2024";
+    fn test_fund_setup_pending_blocks_deposits() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "Split Setup Fund",
+            253,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            2,
+            1700000000,
+        );
+        assert!(fund.can_deposit());
         
-        let link = ReferralLink::new(referrer, code, 254, 1700000000);
+        fund.setup_pending = true;
+        assert!(!fund.can_deposit());
+        assert_eq!(fund.try_to_vec().unwrap().len(), Fund::SIZE);
         
-        assert_eq!(link.referrer, referrer);
-        assert_eq!(link.code_str(), "ALICE2024");
-        assert!(link.is_active);
-        assert_eq!(link.referred_count, 0);
-        assert_eq!(link.total_rewards_earned_e6, 0);
+        fund.setup_pending = false;
+        assert!(fund.can_deposit());
     }
 
     #[test]
-    fn test_referral_link_statistics() {
-        let referrer = Pubkey::new_unique();
-        let mut link = ReferralLink::new(referrer, b"TEST123", 254, 1700000000);
+    fn test_fund_lookup_table() {
+        let mut fund = Fund::new(
+            Pubkey::new_unique(),
+            "ALT Fund",
+            253,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            FeeConfig::new(200, 2000),
+            3,
+            1700000000,
+        );
+        assert!(!fund.has_lookup_table());
+        // Funds without a table still fit accounts that predate the field
+        assert!(fund.fits_in(Fund::SIZE - 40));
         
-        // 记录新邀请
-        link.record_referral();
-        assert_eq!(link.referred_count, 1);
+        fund.lookup_table = Pubkey::new_unique();
+        assert!(fund.has_lookup_table());
+        assert!(!fund.fits_in(Fund::SIZE - 40));
         
-        // 记录返佣
-        link.record_reward(18_000_000, 10_000_000, 1000_000_000);
-        assert_eq!(link.total_rewards_earned_e6, 18_000_000);
-        assert_eq!(link.total_discounts_given_e6, 10_000_000);
-        assert_eq!(link.total_volume_e6, 1000_000_000);
+        let mut data = vec![0u8; Fund::SIZE];
+        fund.pack_versioned(&mut data).unwrap();
+        assert_eq!(Fund::unpack_versioned(&data).unwrap().lookup_table, fund.lookup_table);
     }
 
-    // === Referral Binding Tests ===
-
     #[test]
-    fn test_referral_binding_size() {
-        assert!(ReferralBinding::SIZE > 0);
-        println!("ReferralBinding SIZE: {}", ReferralBinding::SIZE);
+    fn test_investor_portfolio_sync() {
+        let investor = Pubkey::new_unique();
+        let mut portfolio = InvestorPortfolio::new(investor, 255, 1700000000);
+        assert_eq!(portfolio.try_to_vec().unwrap().len(), InvestorPortfolio::SIZE);
+        
+        let fund_a = Pubkey::new_unique();
+        let fund_b = Pubkey::new_unique();
+        let mut pos_a = LPPosition::new(fund_a, investor, 100_000_000, 1_000_000, 100_000_000, 1700000000, 254);
+        let pos_b = LPPosition::new(fund_b, investor, 50_000_000, 2_000_000, 100_000_000, 1700000000, 254);
+        
+        portfolio.sync_position(&pos_a, 1_100_000, 1700000100).unwrap();
+        portfolio.sync_position(&pos_b, 2_000_000, 1700000100).unwrap();
+        assert_eq!(portfolio.fund_count, 2);
+        assert_eq!(portfolio.total_invested_e6, 200_000_000);
+        assert_eq!(portfolio.total_value_e6, 210_000_000);
+        
+        // Partial redeem updates the entry in place
+        pos_a.remove_shares(40_000_000, 44_000_000, 1700000200).unwrap();
+        portfolio.sync_position(&pos_a, 1_100_000, 1700000200).unwrap();
+        assert_eq!(portfolio.entry(&fund_a).unwrap().shares, 60_000_000);
+        assert_eq!(portfolio.total_invested_e6, 156_000_000);
+        assert_eq!(portfolio.total_value_e6, 166_000_000);
+        
+        // Emptied position drops out; the list stays dense
+        pos_a.remove_shares(60_000_000, 66_000_000, 1700000300).unwrap();
+        portfolio.sync_position(&pos_a, 1_100_000, 1700000300).unwrap();
+        assert_eq!(portfolio.fund_count, 1);
+        assert!(portfolio.entry(&fund_a).is_none());
+        assert_eq!(portfolio.active_entries()[0].fund, fund_b);
+        assert_eq!(portfolio.total_value_e6, 100_000_000);
+        
+        // Full portfolio rejects new funds
+        for _ in 1..MAX_PORTFOLIO_FUNDS {
+            let pos = LPPosition::new(Pubkey::new_unique(), investor, 1, 1_000_000, 1, 1700000000, 254);
+            portfolio.sync_position(&pos, 1_000_000, 1700000400).unwrap();
+        }
+        let extra = LPPosition::new(Pubkey::new_unique(), investor, 1, 1_000_000, 1, 1700000000, 254);
+        assert!(portfolio.sync_position(&extra, 1_000_000, 1700000500).is_err());
+        assert_eq!(portfolio.try_to_vec().unwrap().len(), InvestorPortfolio::SIZE);
     }
 
     #[test]
-    fn test_referral_binding_creation() {
-        let referee = Pubkey::new_unique();
-        let referrer = Pubkey::new_unique();
-        let link = Pubkey::new_unique();
+    fn test_fund_coverage_compensation() {
+        let mut policy = FundCoveragePolicy::new(
+            Pubkey::new_unique(),
+            200,              // 2%/yr premium
+            5000,             // 50% of the qualifying loss
+            1000,             // 10% deductible
+            100_000_000,      // $100 limit
+            1_000_000,        // inception NAV 1.0
+            0,
+            255,
+            1700000000,
+        );
+        assert_eq!(policy.try_to_vec().unwrap().len(), FundCoveragePolicy::SIZE);
+        assert!(FundCoveragePolicy::validate_terms(200, 5000, 1000, 1).is_ok());
+        assert!(FundCoveragePolicy::validate_terms(0, 5000, 1000, 1).is_err());
+        assert!(FundCoveragePolicy::validate_terms(200, MAX_COVERAGE_BPS + 1, 1000, 1).is_err());
         
-        let binding = ReferralBinding::new(referee, referrer, link, 254, 1700000000);
+        // One year on $1000 at 2%
+        let now = 1700000000 + SECONDS_PER_YEAR;
+        assert_eq!(policy.premium_due_e6(1_000_000_000, now), 20_000_000);
+        policy.record_premium(20_000_000, now);
+        assert_eq!(policy.premium_due_e6(1_000_000_000, now), 0);
+        assert!(policy.is_in_force(now + COVERAGE_PREMIUM_GRACE_SECS));
+        assert!(!policy.is_in_force(now + COVERAGE_PREMIUM_GRACE_SECS + 1));
         
-        assert_eq!(binding.referee, referee);
-        assert_eq!(binding.referrer, referrer);
-        assert_eq!(binding.referral_link, link);
-        assert_eq!(binding.trade_count, 0);
+        assert_eq!(policy.trigger_nav_e6(), 900_000);
+        // NAV 0.8 without trading losses (fees/expenses only) → nothing
+        assert_eq!(policy.compensation_e6(100_000_000, 1_000_000, 800_000, 0), 0);
+        // With trading losses: (0.9 - 0.8) × 100 shares × 50% = $5
+        assert_eq!(policy.compensation_e6(100_000_000, 1_000_000, 800_000, -1), 5_000_000);
+        // Entered below the trigger → only the drop since entry counts
+        assert_eq!(policy.compensation_e6(100_000_000, 850_000, 800_000, -1), 2_500_000);
+        // Above the trigger → nothing
+        assert_eq!(policy.compensation_e6(100_000_000, 1_000_000, 950_000, -1), 0);
+        
+        // Capped by the remaining limit
+        policy.record_claim(98_000_000);
+        assert_eq!(policy.compensation_e6(100_000_000, 1_000_000, 800_000, -1), 2_000_000);
+        
+        let mut claim = FundCoverageClaim::new(Pubkey::new_unique(), Pubkey::new_unique(), 254);
+        assert_eq!(claim.try_to_vec().unwrap().len(), FundCoverageClaim::SIZE);
+        assert_eq!(claim.reference_nav_e6(1_000_000), 1_000_000);
+        claim.record_claim(5_000_000, 800_000, now);
+        // Later claims only cover further drops
+        assert_eq!(claim.reference_nav_e6(1_000_000), 800_000);
+        
+        policy.cancel(now);
+        assert!(!policy.is_in_force(now));
     }
 
     #[test]
-    fn test_referral_binding_trade_recording() {
-        let referee = Pubkey::new_unique();
-        let referrer = Pubkey::new_unique();
-        let link = Pubkey::new_unique();
-        
-        let mut binding = ReferralBinding::new(referee, referrer, link, 254, 1700000000);
-        
-        // 记录第一笔交易
-        binding.record_trade(1000_000_000, 18_000_000, 10_000_000, 1700001000);
-        assert_eq!(binding.trade_count, 1);
-        assert_eq!(binding.referee_volume_e6, 1000_000_000);
-        assert_eq!(binding.referrer_rewards_e6, 18_000_000);
-        assert_eq!(binding.referee_discounts_e6, 10_000_000);
-        assert_eq!(binding.last_trade_ts, 1700001000);
+    fn test_square_payment_history_page() {
+        let payer = Pubkey::new_unique();
+        let history = SquarePaymentHistory::new(payer, 253, 1700000000);
+        assert_eq!(history.try_to_vec().unwrap().len(), SquarePaymentHistory::SIZE);
+        assert_eq!(history.page_count(), 0);
         
-        // 记录第二笔交易
-        binding.record_trade(500_000_000, 9_000_000, 5_000_000, 1700002000);
-        assert_eq!(binding.trade_count, 2);
-        assert_eq!(binding.referee_volume_e6, 1500_000_000);
-        assert_eq!(binding.referrer_rewards_e6, 27_000_000);
-        assert_eq!(binding.referee_discounts_e6, 15_000_000);
+        let mut page = SquarePaymentHistoryPage::new(payer, 0, 252);
+        assert_eq!(page.try_to_vec().unwrap().len(), SquarePaymentHistoryPage::SIZE);
+        for nonce in 1..=SQUARE_PAYMENT_HISTORY_PAGE_CAPACITY as u64 {
+            assert!(page.push(nonce * 10));
+        }
+        assert!(page.is_full());
+        assert!(!page.push(1));
+        assert_eq!(page.entries().len(), SQUARE_PAYMENT_HISTORY_PAGE_CAPACITY);
+        assert_eq!(page.entries()[0], 10);
+        assert_ne!(SquarePaymentHistoryPage::seeds(&payer, 0), SquarePaymentHistoryPage::seeds(&payer, 1));
     }
 }
-
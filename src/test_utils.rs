@@ -0,0 +1,661 @@
+//! Scenario builders for integration tests (`test-utils` feature)
+//!
+//! Seeds a ready-to-use fund directly into a `solana-program-test` bank
+//! (FundConfig, Fund, vault, share mint, USDC accounts for the manager and N
+//! depositors) and wraps the common LP flows behind async helpers, with a
+//! deterministically warped clock:
+//!
+//! ```ignore
+//! let mut s = FundScenarioBuilder::new().depositors(2, 10_000_000_000).build().await;
+//! s.deposit(0, 1_000_000_000).await?;
+//! s.apply_pnl_sequence(&[(86_400, 50_000_000), (86_400, -20_000_000)]).await?;
+//! s.collect_fees().await?;
+//! ```
+//!
+//! Positive PnL is settled by minting the same amount of USDC into the fund
+//! vault, so the vault keeps covering liabilities under `strict-invariants`
+//! (enabled by this feature).
+//!
+//! FundConfig.ledger_program points at a mock Ledger (`mock_ledger_process_instruction`)
+//! that relays Ledger-only instructions signed by its fund caller PDA.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    instruction::{AccountMeta, Instruction},
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::{
+    instruction::{
+        CollectFeesArgs, DepositToFundArgs, FundInstruction, RecordFeeRebateArgs, RecordFundExposureArgs,
+        RecordPnLArgs, RedeemAllArgs, RedeemFromFundArgs,
+    },
+    ledger_interface::{fund_caller_address, FUND_CALLER_SEED},
+    state::{FeeConfig, Fund, FundConfig, LPIndexPage, LPPosition, VersionedAccount},
+};
+
+/// Default scenario start time (2023-11-14 22:13:20 UTC)
+pub const DEFAULT_START_TS: i64 = 1_700_000_000;
+
+/// Lamports given to every seeded account and wallet
+const SEED_LAMPORTS: u64 = 10_000_000_000;
+
+/// First byte of a mock Ledger relay (outside the real Ledger discriminators)
+pub const MOCK_LEDGER_RELAY: u8 = 0xFF;
+
+/// Mock Ledger Program registered as FundConfig.ledger_program
+///
+/// `[MOCK_LEDGER_RELAY, fund instruction..]` invokes the Fund Program (account 0)
+/// with the remaining accounts, signing for the fund caller PDA. Anything else
+/// (Ledger OpenPosition / ClosePosition CPIs) is accepted as a no-op.
+pub fn mock_ledger_process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let Some((&MOCK_LEDGER_RELAY, fund_ix)) = data.split_first() else {
+        return Ok(());
+    };
+    let (fund_program, ix_accounts) = accounts.split_first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let (caller, bump) = fund_caller_address(program_id);
+    let metas = ix_accounts
+        .iter()
+        .map(|a| AccountMeta {
+            pubkey: *a.key,
+            is_signer: a.is_signer || *a.key == caller,
+            is_writable: a.is_writable,
+        })
+        .collect();
+    invoke_signed(
+        &Instruction::new_with_bytes(*fund_program.key, fund_ix, metas),
+        ix_accounts,
+        &[&[FUND_CALLER_SEED, &[bump]]],
+    )
+}
+
+fn find_pda(seeds: &[Vec<u8>], program_id: &Pubkey) -> (Pubkey, u8) {
+    let refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    Pubkey::find_program_address(&refs, program_id)
+}
+
+fn mint_account(authority: Pubkey) -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(authority),
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::Some(authority),
+    }
+    .pack_into_slice(&mut data);
+    Account { lamports: SEED_LAMPORTS, data, owner: spl_token::id(), executable: false, rent_epoch: 0 }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account { lamports: SEED_LAMPORTS, data, owner: spl_token::id(), executable: false, rent_epoch: 0 }
+}
+
+fn wallet_account() -> Account {
+    Account { lamports: SEED_LAMPORTS, owner: system_program::id(), ..Account::default() }
+}
+
+/// Sign with the context payer plus `signers` and process on a fresh blockhash
+async fn signed_transaction(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<Transaction, BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await?;
+    let mut all_signers: Vec<&Keypair> = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    Ok(Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    ))
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let tx = signed_transaction(context, instructions, signers).await?;
+    context.banks_client.process_transaction(tx).await
+}
+
+/// LP wallet seeded by the builder
+pub struct TestInvestor {
+    pub keypair: Keypair,
+    /// USDC token account (owner = investor)
+    pub usdc: Pubkey,
+    /// Share token account (owner = investor)
+    pub shares: Pubkey,
+    /// LPPosition PDA (created by the first deposit)
+    pub lp_position: Pubkey,
+}
+
+/// Builder for a listed fund with N funded depositors
+pub struct FundScenarioBuilder {
+    program_id: Pubkey,
+    fund_name: String,
+    fee_config: FeeConfig,
+    depositor_balances: Vec<u64>,
+    start_ts: i64,
+}
+
+impl Default for FundScenarioBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FundScenarioBuilder {
+    /// 2% management / 20% performance, no depositors, clock at DEFAULT_START_TS
+    pub fn new() -> Self {
+        Self {
+            program_id: crate::id(),
+            fund_name: "Scenario Fund".to_string(),
+            fee_config: FeeConfig::new(200, 2000),
+            depositor_balances: Vec::new(),
+            start_ts: DEFAULT_START_TS,
+        }
+    }
+
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    pub fn fund_name(mut self, name: &str) -> Self {
+        self.fund_name = name.to_string();
+        self
+    }
+
+    pub fn fees(mut self, management_fee_bps: u32, performance_fee_bps: u32) -> Self {
+        self.fee_config = FeeConfig::new(management_fee_bps, performance_fee_bps);
+        self
+    }
+
+    /// Add `count` depositors, each holding `usdc_each` (e6) in their USDC account
+    pub fn depositors(mut self, count: usize, usdc_each: u64) -> Self {
+        self.depositor_balances.extend(std::iter::repeat_n(usdc_each, count));
+        self
+    }
+
+    pub fn start_ts(mut self, ts: i64) -> Self {
+        self.start_ts = ts;
+        self
+    }
+
+    /// Seed all accounts, start the bank and warp the clock to `start_ts`
+    pub async fn build(self) -> FundScenario {
+        let program_id = self.program_id;
+        let mut program_test = ProgramTest::new(
+            "fund_program",
+            program_id,
+            processor!(crate::process_instruction),
+        );
+
+        let ledger_program = Pubkey::new_unique();
+        program_test.add_program(
+            "mock_ledger",
+            ledger_program,
+            processor!(mock_ledger_process_instruction),
+        );
+
+        let admin = Keypair::new();
+        let manager = Keypair::new();
+        let usdc_mint_authority = Keypair::new();
+        let usdc_mint = Pubkey::new_unique();
+        let manager_usdc = Pubkey::new_unique();
+
+        let (fund_config, config_bump) = find_pda(&[crate::state::FUND_CONFIG_SEED.to_vec()], &program_id);
+        let (fund, fund_bump) = find_pda(&Fund::seeds(&manager.pubkey(), 0), &program_id);
+        let (fund_vault, _) = find_pda(&Fund::vault_seeds(&fund), &program_id);
+        let (share_mint, _) = find_pda(&Fund::share_mint_seeds(&fund), &program_id);
+
+        let vault_program = Pubkey::new_unique();
+        let config = FundConfig::new(admin.pubkey(), vault_program, ledger_program, config_bump);
+        program_test.add_account(
+            fund_config,
+            Account {
+                lamports: SEED_LAMPORTS,
+                data: config.try_to_vec().unwrap(),
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        let fund_state = Fund::new(
+            manager.pubkey(),
+            &self.fund_name,
+            fund_bump,
+            fund_vault,
+            share_mint,
+            self.fee_config,
+            0,
+            self.start_ts,
+        );
+        program_test.add_account(
+            fund,
+            Account {
+                lamports: SEED_LAMPORTS,
+                data: fund_state.try_to_vec().unwrap(),
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        program_test.add_account(usdc_mint, mint_account(usdc_mint_authority.pubkey()));
+        program_test.add_account(share_mint, mint_account(fund));
+        program_test.add_account(fund_vault, token_account(usdc_mint, fund, 0));
+        program_test.add_account(manager.pubkey(), wallet_account());
+        program_test.add_account(manager_usdc, token_account(usdc_mint, manager.pubkey(), 0));
+
+        let mut investors = Vec::with_capacity(self.depositor_balances.len());
+        for &balance in &self.depositor_balances {
+            let keypair = Keypair::new();
+            let usdc = Pubkey::new_unique();
+            let shares = Pubkey::new_unique();
+            let (lp_position, _) = find_pda(&LPPosition::seeds(&fund, &keypair.pubkey()), &program_id);
+            program_test.add_account(keypair.pubkey(), wallet_account());
+            program_test.add_account(usdc, token_account(usdc_mint, keypair.pubkey(), balance));
+            program_test.add_account(shares, token_account(share_mint, keypair.pubkey(), 0));
+            investors.push(TestInvestor { keypair, usdc, shares, lp_position });
+        }
+
+        let context = program_test.start_with_context().await;
+        let mut scenario = FundScenario {
+            context,
+            program_id,
+            admin,
+            manager,
+            ledger_program,
+            ledger_caller: fund_caller_address(&ledger_program).0,
+            vault_program,
+            usdc_mint,
+            usdc_mint_authority,
+            manager_usdc,
+            fund_config,
+            fund,
+            fund_vault,
+            share_mint,
+            investors,
+        };
+        scenario.warp_to_timestamp(self.start_ts).await;
+        scenario
+    }
+}
+
+/// Running scenario: bank context plus every seeded key
+pub struct FundScenario {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    /// FundConfig authority
+    pub admin: Keypair,
+    pub manager: Keypair,
+    /// Mock Ledger registered as FundConfig.ledger_program (caller for RecordPnL)
+    pub ledger_program: Pubkey,
+    /// The mock Ledger's fund caller PDA
+    pub ledger_caller: Pubkey,
+    /// FundConfig.vault_program (no program deployed)
+    pub vault_program: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub usdc_mint_authority: Keypair,
+    /// Manager-owned USDC account (default fee destination)
+    pub manager_usdc: Pubkey,
+    pub fund_config: Pubkey,
+    pub fund: Pubkey,
+    pub fund_vault: Pubkey,
+    pub share_mint: Pubkey,
+    pub investors: Vec<TestInvestor>,
+}
+
+impl FundScenario {
+    // === Clock ===
+
+    pub async fn now(&mut self) -> i64 {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp
+    }
+
+    /// Set Clock.unix_timestamp on the working bank
+    pub async fn warp_to_timestamp(&mut self, ts: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = ts;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn advance_clock(&mut self, secs: i64) {
+        let now = self.now().await;
+        self.warp_to_timestamp(now + secs).await;
+    }
+
+    // === Accounts ===
+
+    /// Create or overwrite an account in the working bank
+    pub fn set_account(&mut self, key: &Pubkey, owner: &Pubkey, data: Vec<u8>) {
+        let account = Account { lamports: SEED_LAMPORTS, data, owner: *owner, executable: false, rent_epoch: 0 };
+        self.context.set_account(key, &AccountSharedData::from(account));
+    }
+
+    /// New token account of `mint` owned by `owner`
+    pub fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.set_token_account(&key, mint, owner, amount);
+        key
+    }
+
+    /// Create or overwrite a token account of `mint` owned by `owner` at `key` (e.g. a PDA vault)
+    pub fn set_token_account(&mut self, key: &Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+        let account = token_account(*mint, *owner, amount);
+        self.context.set_account(key, &AccountSharedData::from(account));
+    }
+
+    /// New USDC token account owned by `owner`
+    pub fn create_usdc_account(&mut self, owner: &Pubkey, amount: u64) -> Pubkey {
+        let mint = self.usdc_mint;
+        self.create_token_account(&mint, owner, amount)
+    }
+
+    /// Overwrite the FundConfig account
+    pub fn write_fund_config(&mut self, config: &FundConfig) {
+        let (key, program_id) = (self.fund_config, self.program_id);
+        self.set_account(&key, &program_id, config.try_to_vec().unwrap());
+    }
+
+    /// Overwrite the scenario fund's state, keeping its account size
+    pub async fn write_fund(&mut self, fund: &Fund) {
+        let (key, program_id) = (self.fund, self.program_id);
+        let mut data = self.context.banks_client.get_account(key).await.unwrap().unwrap().data;
+        fund.pack_versioned(&mut data).unwrap();
+        self.set_account(&key, &program_id, data);
+    }
+
+    // === Transactions ===
+
+    /// Process instructions signed by the payer plus `signers`
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        send(&mut self.context, instructions, signers).await
+    }
+
+    /// Like `process`, returning the transaction's return data (empty if none)
+    pub async fn process_with_return_data(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Vec<u8>, BanksClientError> {
+        let tx = signed_transaction(&mut self.context, instructions, signers).await?;
+        let outcome = self.context.banks_client.process_transaction_with_metadata(tx).await?;
+        outcome.result.map_err(BanksClientError::TransactionError)?;
+        Ok(outcome.metadata.and_then(|metadata| metadata.return_data).map(|data| data.data).unwrap_or_default())
+    }
+
+    /// Fund Program instruction
+    pub fn instruction(&self, ix: FundInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction::new_with_bytes(self.program_id, &ix.try_to_vec().unwrap(), accounts)
+    }
+
+    /// Relay a Fund Program instruction through the mock Ledger, which signs for
+    /// `ledger_caller` wherever it appears in `accounts`
+    pub fn ledger_instruction(&self, ix: FundInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+        let mut data = vec![MOCK_LEDGER_RELAY];
+        data.extend_from_slice(&ix.try_to_vec().unwrap());
+        let mut relay_accounts = vec![AccountMeta::new_readonly(self.program_id, false)];
+        relay_accounts.extend(accounts.into_iter().map(|meta| AccountMeta { is_signer: false, ..meta }));
+        Instruction::new_with_bytes(self.ledger_program, &data, relay_accounts)
+    }
+
+    /// DepositToFund from investor `index`
+    pub async fn deposit(&mut self, index: usize, amount: u64) -> Result<(), BanksClientError> {
+        self.deposit_with(index, amount, Vec::new()).await
+    }
+
+    /// DepositToFund from investor `index`, appending `extra` (e.g. ShareCheckpoints) to the accounts
+    pub async fn deposit_with(
+        &mut self,
+        index: usize,
+        amount: u64,
+        extra: Vec<AccountMeta>,
+    ) -> Result<(), BanksClientError> {
+        let mut ix = self.deposit_instruction(index, amount).await;
+        ix.accounts.extend(extra);
+        send(&mut self.context, &[ix], &[&self.investors[index].keypair]).await
+    }
+
+    /// DepositToFund instruction of investor `index`, for tests that alter its accounts
+    pub async fn deposit_instruction(&mut self, index: usize, amount: u64) -> Instruction {
+        // Pass both the current last LP index page and the next one; the processor
+        // picks whichever has room
+        let page_count = self.fund_state().await.lp_index_page_count;
+        let last_page = find_pda(&LPIndexPage::seeds(&self.fund, page_count.saturating_sub(1)), &self.program_id).0;
+        let next_page = find_pda(&LPIndexPage::seeds(&self.fund, page_count), &self.program_id).0;
+
+        let investor = &self.investors[index];
+        let mut accounts = vec![
+            AccountMeta::new(investor.keypair.pubkey(), true),
+            AccountMeta::new(self.fund, false),
+            AccountMeta::new(self.fund_vault, false),
+            AccountMeta::new(investor.usdc, false),
+            AccountMeta::new(investor.lp_position, false),
+            AccountMeta::new(investor.shares, false),
+            AccountMeta::new(self.share_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(last_page, false),
+        ];
+        if next_page != last_page {
+            accounts.push(AccountMeta::new(next_page, false));
+        }
+        self.instruction(
+            FundInstruction::DepositToFund(DepositToFundArgs { amount, campaign_id: None, memo: None }),
+            accounts,
+        )
+    }
+
+    /// RedeemFromFund from investor `index`
+    pub async fn redeem(&mut self, index: usize, shares: u64) -> Result<(), BanksClientError> {
+        let accounts = self.redeem_accounts(index).await;
+        let ix = self.instruction(FundInstruction::RedeemFromFund(RedeemFromFundArgs { shares, memo: None }), accounts);
+        send(&mut self.context, &[ix], &[&self.investors[index].keypair]).await
+    }
+
+    /// RedeemAll from investor `index`
+    pub async fn redeem_all(&mut self, index: usize) -> Result<(), BanksClientError> {
+        self.redeem_all_with(index, Vec::new()).await
+    }
+
+    /// RedeemAll with extra accounts appended (allow-list, hook, RentReserve, ...)
+    pub async fn redeem_all_with(&mut self, index: usize, extra: Vec<AccountMeta>) -> Result<(), BanksClientError> {
+        let mut accounts = self.redeem_accounts(index).await;
+        accounts.extend(extra);
+        let ix = self.instruction(FundInstruction::RedeemAll(RedeemAllArgs { memo: None }), accounts);
+        send(&mut self.context, &[ix], &[&self.investors[index].keypair]).await
+    }
+
+    /// Redemption accounts 0-7 of investor `index`, plus its LP index page if indexed
+    async fn redeem_accounts(&mut self, index: usize) -> Vec<AccountMeta> {
+        // A full redemption unlists the position from its LP index page
+        let index_page = match self.lp_position(index).await {
+            Some(position) if position.is_indexed => Some(position.lp_index_page),
+            _ => None,
+        };
+
+        let investor = &self.investors[index];
+        let mut accounts = vec![
+            AccountMeta::new(investor.keypair.pubkey(), true),
+            AccountMeta::new(self.fund, false),
+            AccountMeta::new(self.fund_vault, false),
+            AccountMeta::new(investor.usdc, false),
+            AccountMeta::new(investor.lp_position, false),
+            AccountMeta::new(investor.shares, false),
+            AccountMeta::new(self.share_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+        if let Some(page) = index_page {
+            let page = find_pda(&LPIndexPage::seeds(&self.fund, page), &self.program_id).0;
+            accounts.push(AccountMeta::new(page, false));
+        }
+        accounts
+    }
+
+    /// RecordPnL as the Ledger Program; positive PnL is also minted into the vault
+    pub async fn record_pnl(&mut self, pnl_e6: i64) -> Result<(), BanksClientError> {
+        let mut instructions = Vec::with_capacity(2);
+        if pnl_e6 > 0 {
+            instructions.push(
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &self.usdc_mint,
+                    &self.fund_vault,
+                    &self.usdc_mint_authority.pubkey(),
+                    &[],
+                    pnl_e6 as u64,
+                )
+                .unwrap(),
+            );
+        }
+        instructions.push(self.instruction(
+            FundInstruction::RecordPnL(RecordPnLArgs { pnl_e6 }),
+            vec![
+                AccountMeta::new_readonly(self.ledger_program, false),
+                AccountMeta::new(self.fund, false),
+                AccountMeta::new_readonly(self.fund_config, false),
+            ],
+        ));
+        if pnl_e6 > 0 {
+            send(&mut self.context, &instructions, &[&self.usdc_mint_authority]).await
+        } else {
+            send(&mut self.context, &instructions, &[]).await
+        }
+    }
+
+    /// RecordFeeRebate relayed by the mock Ledger; the rebate is also minted into the vault
+    pub async fn record_fee_rebate(&mut self, rebate_e6: i64) -> Result<(), BanksClientError> {
+        let mint = spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &self.usdc_mint,
+            &self.fund_vault,
+            &self.usdc_mint_authority.pubkey(),
+            &[],
+            rebate_e6.max(0) as u64,
+        )
+        .unwrap();
+        let ix = self.ledger_instruction(
+            FundInstruction::RecordFeeRebate(RecordFeeRebateArgs { rebate_e6 }),
+            vec![
+                AccountMeta::new_readonly(self.ledger_caller, false),
+                AccountMeta::new(self.fund, false),
+                AccountMeta::new_readonly(self.fund_config, false),
+            ],
+        );
+        send(&mut self.context, &[mint, ix], &[&self.usdc_mint_authority]).await
+    }
+
+    /// RecordFundExposure relayed by the mock Ledger
+    pub async fn record_exposure(
+        &mut self,
+        open_notional_e6: u64,
+        margin_used_e6: u64,
+        free_collateral_e6: i64,
+    ) -> Result<(), BanksClientError> {
+        let ix = self.ledger_instruction(
+            FundInstruction::RecordFundExposure(RecordFundExposureArgs {
+                open_notional_e6,
+                margin_used_e6,
+                free_collateral_e6,
+            }),
+            vec![
+                AccountMeta::new_readonly(self.ledger_caller, false),
+                AccountMeta::new(self.fund, false),
+                AccountMeta::new_readonly(self.fund_config, false),
+            ],
+        );
+        send(&mut self.context, &[ix], &[]).await
+    }
+
+    /// Apply `(advance_secs, pnl_e6)` steps in order: warp the clock, then RecordPnL
+    pub async fn apply_pnl_sequence(&mut self, steps: &[(i64, i64)]) -> Result<(), BanksClientError> {
+        for &(advance_secs, pnl_e6) in steps {
+            self.advance_clock(advance_secs).await;
+            self.record_pnl(pnl_e6).await?;
+        }
+        Ok(())
+    }
+
+    /// CollectFees (management + performance) into the manager's USDC account
+    pub async fn collect_fees(&mut self) -> Result<(), BanksClientError> {
+        let ix = self.instruction(
+            FundInstruction::CollectFees(CollectFeesArgs::all()),
+            vec![
+                AccountMeta::new(self.manager.pubkey(), true),
+                AccountMeta::new(self.fund, false),
+                AccountMeta::new(self.fund_vault, false),
+                AccountMeta::new(self.manager_usdc, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        );
+        send(&mut self.context, &[ix], &[&self.manager]).await
+    }
+
+    // === Queries ===
+
+    pub async fn fund_state(&mut self) -> Fund {
+        let fund = self.fund;
+        self.fund_state_of(fund).await
+    }
+
+    /// Any Fund account (e.g. one created during the scenario)
+    pub async fn fund_state_of(&mut self, fund: Pubkey) -> Fund {
+        let account = self.context.banks_client.get_account(fund).await.unwrap().unwrap();
+        Fund::unpack_versioned(&account.data).unwrap()
+    }
+
+    pub async fn fund_config_state(&mut self) -> FundConfig {
+        let account = self.context.banks_client.get_account(self.fund_config).await.unwrap().unwrap();
+        FundConfig::unpack_versioned(&account.data).unwrap()
+    }
+
+    /// LP position of investor `index`, None before the first deposit
+    pub async fn lp_position(&mut self, index: usize) -> Option<LPPosition> {
+        let key = self.investors[index].lp_position;
+        let account = self.context.banks_client.get_account(key).await.unwrap()?;
+        Some(LPPosition::unpack_versioned(&account.data).unwrap())
+    }
+
+    pub async fn token_balance(&mut self, token_account: Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(token_account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+}
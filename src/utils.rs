@@ -4,6 +4,7 @@
 
 use solana_program::{
     account_info::AccountInfo,
+    hash::hashv,
     program_error::ProgramError,
     pubkey::Pubkey,
     sysvar::{clock::Clock, Sysvar},
@@ -22,9 +23,21 @@ pub const MAX_MANAGEMENT_FEE_BPS: u32 = 1_000;
 /// Maximum performance fee (50% = 5000 bps)
 pub const MAX_PERFORMANCE_FEE_BPS: u32 = 5_000;
 
+/// Maximum annual fund expense allowance (1% = 100 bps of fund value)
+pub const MAX_FUND_EXPENSE_BPS: u16 = 100;
+
+/// Maximum dynamic redemption fee (3% = 300 bps)
+pub const MAX_REDEMPTION_FEE_BPS: u16 = 300;
+
 /// Minimum deposit amount (1 USDC = 1_000_000 e6)
 pub const MIN_DEPOSIT_AMOUNT_E6: i64 = 1_000_000;
 
+/// Lamports per micro-SOL (wSOL has 9 decimals, fund accounting uses e6)
+pub const LAMPORTS_PER_SOL_E6: u64 = 1_000;
+
+/// Minimum deposit for wSOL funds (0.01 SOL = 10_000 e6)
+pub const MIN_SOL_DEPOSIT_E6: i64 = 10_000;
+
 /// Seconds per year (for management fee calculation)
 pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
 
@@ -34,6 +47,42 @@ pub const MAX_FUND_NAME_LEN: usize = 32;
 /// Initial NAV (1.0 in e6 format)
 pub const INITIAL_NAV_E6: i64 = 1_000_000;
 
+/// NAV floor for deposits (0.01 in e6 format)
+///
+/// Below it a deposit would mint 100x+ shares per USDC; the fund enters
+/// wind-down review instead.
+pub const MIN_DEPOSIT_NAV_E6: i64 = 10_000;
+
+/// Share mint decimals (same as USDC)
+pub const SHARE_MINT_DECIMALS: u8 = 6;
+
+/// Vault mint decimals of non-SOL funds (token amounts are e6 units)
+pub const VAULT_MINT_DECIMALS: u8 = 6;
+
+/// Largest share supply drift ReconcileShareSupply corrects in FundStats (1%)
+///
+/// A bigger gap, or any gap on a mint the fund no longer controls, flags the
+/// fund as inconsistent instead.
+pub const MAX_SHARE_SUPPLY_AUTO_FIX_BPS: u64 = 100;
+
+/// Maximum age of a benchmark price used for performance fees (seconds)
+pub const BENCHMARK_MAX_AGE_SECS: i64 = 3600;
+
+/// Maximum share of insurance capital deployable to a yield venue (50%)
+pub const MAX_INSURANCE_DEPLOY_BPS: u16 = 5_000;
+
+/// Maximum leverage for fund trades
+pub const MAX_TRADE_LEVERAGE: u8 = 100;
+
+/// Maximum deposit / redemption memo length (bytes)
+pub const MAX_MEMO_LEN: usize = 128;
+
+/// Memo bytes kept in the emitted event
+pub const MEMO_EVENT_LEN: usize = 64;
+
+/// Addresses per ExtendFundLookupTable call (keeps the transaction under the size limit)
+pub const MAX_LOOKUP_TABLE_EXTEND_ADDRESSES: usize = 30;
+
 // === Validation Functions ===
 
 /// Assert that an account is a signer
@@ -60,6 +109,105 @@ pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> Result<(), Prog
     Ok(())
 }
 
+/// Assert that an account's address equals the expected key
+pub fn assert_key(account: &AccountInfo, expected: &Pubkey) -> Result<(), ProgramError> {
+    if account.key != expected {
+        return Err(FundError::AccountKeyMismatch.into());
+    }
+    Ok(())
+}
+
+/// Assert that an account is the PDA derived from `seeds`, returning the bump
+pub fn assert_pda(
+    account: &AccountInfo,
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+    if account.key != &pda {
+        return Err(FundError::PDAMismatch.into());
+    }
+    Ok(bump)
+}
+
+/// Declarative account constraints, checked in the order written.
+///
+/// ```ignore
+/// validate_accounts! {
+///     investor: signer;
+///     fund_account: writable, owner = program_id;
+///     lp_position: writable, pda = (&lp_seeds_refs, program_id);
+///     token_program: key = &spl_token::id();
+/// }
+/// ```
+///
+/// Supported constraints: `signer`, `writable`, `owner = <&Pubkey>`,
+/// `key = <&Pubkey>`, `pda = (<&[&[u8]]>, <&Pubkey>)`.
+/// Must be used inside a function returning `Result<_, ProgramError>`.
+#[macro_export]
+macro_rules! validate_accounts {
+    (@check $account:ident, signer) => {
+        $crate::utils::assert_signer($account)?
+    };
+    (@check $account:ident, writable) => {
+        $crate::utils::assert_writable($account)?
+    };
+    (@check $account:ident, owner, $owner:expr) => {
+        $crate::utils::assert_owned_by($account, $owner)?
+    };
+    (@check $account:ident, key, $key:expr) => {
+        $crate::utils::assert_key($account, $key)?
+    };
+    (@check $account:ident, pda, $pda:expr) => {{
+        let (seeds, program_id) = $pda;
+        $crate::utils::assert_pda($account, seeds, program_id)?;
+    }};
+    ($($account:ident : $($constraint:ident $(= $value:expr)?),+ ;)+) => {
+        $($(
+            $crate::validate_accounts!(@check $account, $constraint $(, $value)?);
+        )+)+
+    };
+}
+
+/// Log remaining compute units at a checkpoint (no-op without the `measure-cu` feature)
+#[inline(always)]
+pub fn log_compute_units(_label: &str) {
+    #[cfg(feature = "measure-cu")]
+    solana_program::msg!(
+        "CU {}: {} remaining",
+        _label,
+        solana_program::compute_units::sol_remaining_compute_units()
+    );
+}
+
+/// Remaining compute units of the transaction
+///
+/// Off-chain (native processor tests) the syscall is a stub returning 0, so the
+/// budget is treated as unlimited there.
+#[inline(always)]
+pub fn remaining_compute_units() -> u64 {
+    #[cfg(target_os = "solana")]
+    {
+        solana_program::compute_units::sol_remaining_compute_units()
+    }
+    #[cfg(not(target_os = "solana"))]
+    {
+        u64::MAX
+    }
+}
+
+/// Close a program-owned account: move all lamports to `destination` and zero its data
+pub fn close_program_account(account: &AccountInfo, destination: &AccountInfo) -> Result<(), ProgramError> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(FundError::Overflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
 /// Validate fee configuration
 pub fn validate_fee_config(
     management_fee_bps: u32,
@@ -74,6 +222,20 @@ pub fn validate_fee_config(
     Ok(())
 }
 
+/// Validate a fund expense allowance (cap 0 = expenses disabled)
+pub fn validate_expense_policy(
+    expense_cap_bps: u16,
+    max_expense_payment_e6: u64,
+) -> Result<(), ProgramError> {
+    if expense_cap_bps > MAX_FUND_EXPENSE_BPS
+        || (expense_cap_bps > 0 && max_expense_payment_e6 == 0)
+        || i64::try_from(max_expense_payment_e6).is_err()
+    {
+        return Err(FundError::InvalidExpensePolicy.into());
+    }
+    Ok(())
+}
+
 /// Validate fund name
 pub fn validate_fund_name(name: &str) -> Result<(), ProgramError> {
     if name.len() > MAX_FUND_NAME_LEN || name.is_empty() {
@@ -82,6 +244,26 @@ pub fn validate_fund_name(name: &str) -> Result<(), ProgramError> {
     Ok(())
 }
 
+/// Validate an optional deposit / redemption memo
+pub fn validate_memo(memo: Option<&str>) -> Result<(), ProgramError> {
+    if memo.is_some_and(|m| m.is_empty() || m.len() > MAX_MEMO_LEN) {
+        return Err(FundError::InvalidMemo.into());
+    }
+    Ok(())
+}
+
+/// Truncate a memo to at most `max_len` bytes on a char boundary
+pub fn truncate_memo(memo: &str, max_len: usize) -> &str {
+    if memo.len() <= max_len {
+        return memo;
+    }
+    let mut end = max_len;
+    while !memo.is_char_boundary(end) {
+        end -= 1;
+    }
+    &memo[..end]
+}
+
 // === Math Functions ===
 
 /// Safe addition for i64
@@ -134,6 +316,9 @@ pub fn safe_div_u64(a: u64, b: u64) -> Result<u64, ProgramError> {
 
 /// Calculate NAV (Net Asset Value) per share
 /// NAV = total_value_e6 / total_shares (in e6 format)
+///
+/// A wiped-out fund (total value ≤ 0 with shares outstanding) has NAV 0:
+/// redemptions pay nothing and deposits are refused by the NAV floor.
 pub fn calculate_nav_e6(total_value_e6: i64, total_shares: u64) -> Result<i64, ProgramError> {
     if total_shares == 0 {
         // Initial NAV is 1.0
@@ -141,26 +326,27 @@ pub fn calculate_nav_e6(total_value_e6: i64, total_shares: u64) -> Result<i64, P
     }
     
     if total_value_e6 <= 0 {
-        return Err(FundError::NAVCalculationError.into());
+        return Ok(0);
     }
     
     // NAV = total_value * 1e6 / total_shares
-    let nav = ((total_value_e6 as i128) * 1_000_000 / (total_shares as i128)) as i64;
-    Ok(nav)
+    let nav = (total_value_e6 as i128) * 1_000_000 / (total_shares as i128);
+    i64::try_from(nav).map_err(|_| FundError::Overflow.into())
 }
 
 /// Calculate shares to mint for a deposit
 /// shares = deposit_amount_e6 * 1e6 / nav_e6
 pub fn calculate_shares_to_mint(deposit_amount_e6: i64, nav_e6: i64) -> Result<u64, ProgramError> {
-    if nav_e6 <= 0 {
-        return Err(FundError::NAVCalculationError.into());
+    if nav_e6 < MIN_DEPOSIT_NAV_E6 {
+        return Err(FundError::NAVBelowFloor.into());
     }
     if deposit_amount_e6 <= 0 {
         return Err(FundError::InvalidAmount.into());
     }
     
     // shares = deposit * 1e6 / nav
-    let shares = ((deposit_amount_e6 as i128) * 1_000_000 / (nav_e6 as i128)) as u64;
+    let shares = (deposit_amount_e6 as i128) * 1_000_000 / (nav_e6 as i128);
+    let shares = u64::try_from(shares).map_err(|_| ProgramError::from(FundError::Overflow))?;
     
     if shares == 0 {
         return Err(FundError::ShareCalculationError.into());
@@ -170,18 +356,18 @@ pub fn calculate_shares_to_mint(deposit_amount_e6: i64, nav_e6: i64) -> Result<u
 }
 
 /// Calculate USDC value for share redemption
-/// value = shares * nav_e6 / 1e6
+/// value = shares * nav_e6 / 1e6 (0 once NAV has hit 0)
 pub fn calculate_redemption_value(shares: u64, nav_e6: i64) -> Result<i64, ProgramError> {
-    if nav_e6 <= 0 {
-        return Err(FundError::NAVCalculationError.into());
-    }
     if shares == 0 {
         return Err(FundError::InvalidAmount.into());
     }
+    if nav_e6 <= 0 {
+        return Ok(0);
+    }
     
     // value = shares * nav / 1e6
-    let value = ((shares as i128) * (nav_e6 as i128) / 1_000_000) as i64;
-    Ok(value)
+    let value = (shares as i128) * (nav_e6 as i128) / 1_000_000;
+    i64::try_from(value).map_err(|_| FundError::Overflow.into())
 }
 
 /// Calculate management fee for a period
@@ -203,6 +389,25 @@ pub fn calculate_management_fee(
     Ok(fee)
 }
 
+/// Calculate management fee from a time-weighted AUM integral
+/// fee = Σ(aum * dt) * fee_bps / BPS_DENOMINATOR / SECONDS_PER_YEAR
+pub fn calculate_management_fee_from_integral(
+    aum_seconds_e6: i128,
+    fee_bps: u32,
+) -> Result<i64, ProgramError> {
+    if aum_seconds_e6 <= 0 || fee_bps == 0 {
+        return Ok(0);
+    }
+    
+    let fee = aum_seconds_e6
+        .checked_mul(fee_bps as i128)
+        .ok_or(FundError::Overflow)?
+        / (BPS_DENOMINATOR as i128)
+        / (SECONDS_PER_YEAR as i128);
+    
+    i64::try_from(fee).map_err(|_| FundError::Overflow.into())
+}
+
 /// Calculate performance fee (only on profit above HWM)
 /// fee = (nav - hwm) * total_value * fee_bps / BPS_DENOMINATOR / nav
 pub fn calculate_performance_fee(
@@ -228,6 +433,50 @@ pub fn calculate_performance_fee(
     Ok(fee)
 }
 
+// === Trade Calculations ===
+
+/// Calculate (notional, initial margin) of a fund trade
+/// notional = size * price / 1e6, margin = notional / leverage (rounded up)
+pub fn calculate_trade_margin(size_e6: u64, price_e6: u64, leverage: u8) -> Result<(u64, u64), ProgramError> {
+    if size_e6 == 0 || price_e6 == 0 {
+        return Err(FundError::InvalidAmount.into());
+    }
+    if leverage == 0 || leverage > MAX_TRADE_LEVERAGE {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let notional = (size_e6 as u128) * (price_e6 as u128) / 1_000_000;
+    let margin = notional.div_ceil(leverage as u128);
+    
+    let notional = u64::try_from(notional).map_err(|_| ProgramError::from(FundError::Overflow))?;
+    let margin = u64::try_from(margin).map_err(|_| ProgramError::from(FundError::Overflow))?;
+    Ok((notional, margin))
+}
+
+// === Merkle Proofs ===
+
+/// Maximum inclusion proof depth (supports 2^32 leaves)
+pub const MAX_MERKLE_PROOF_DEPTH: usize = 32;
+
+/// Leaf hash of an (investor, shares) balance entry
+///
+/// Leaves and nodes use distinct prefixes so an inner node cannot be passed off as a leaf.
+pub fn balance_leaf_hash(investor: &Pubkey, shares: u64) -> [u8; 32] {
+    hashv(&[&[0u8], investor.as_ref(), &shares.to_le_bytes()]).to_bytes()
+}
+
+/// Verify a sorted-pair merkle inclusion proof against `root`
+pub fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    if proof.len() > MAX_MERKLE_PROOF_DEPTH {
+        return false;
+    }
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[&[1u8], &left, &right]).to_bytes()
+    });
+    computed == *root
+}
+
 // === Time Functions ===
 
 /// Get current timestamp from Clock sysvar
@@ -259,6 +508,13 @@ mod tests {
         
         // NAV = 0.5 when value = 0.5 * shares
         assert_eq!(calculate_nav_e6(5_000_000, 10_000_000).unwrap(), 500_000);
+        
+        // Wiped out: NAV floors at 0 instead of erroring
+        assert_eq!(calculate_nav_e6(0, 10_000_000).unwrap(), 0);
+        assert_eq!(calculate_nav_e6(-5_000_000, 10_000_000).unwrap(), 0);
+        
+        // NAV beyond i64 is an error, not a truncation
+        assert_eq!(calculate_nav_e6(i64::MAX, 1), Err(FundError::Overflow.into()));
     }
 
     #[test]
@@ -274,6 +530,20 @@ mod tests {
         // At NAV = 0.5, 100 USDC = 200 shares
         let shares = calculate_shares_to_mint(100_000_000, 500_000).unwrap();
         assert_eq!(shares, 200_000_000);
+        
+        // At the NAV floor, 100 USDC = 10,000 shares; below it deposits are refused
+        let shares = calculate_shares_to_mint(100_000_000, MIN_DEPOSIT_NAV_E6).unwrap();
+        assert_eq!(shares, 10_000_000_000);
+        assert_eq!(
+            calculate_shares_to_mint(100_000_000, MIN_DEPOSIT_NAV_E6 - 1),
+            Err(FundError::NAVBelowFloor.into())
+        );
+        
+        // Share count beyond u64 is an error, not a truncation
+        assert_eq!(
+            calculate_shares_to_mint(i64::MAX, MIN_DEPOSIT_NAV_E6),
+            Err(FundError::Overflow.into())
+        );
     }
 
     #[test]
@@ -285,6 +555,10 @@ mod tests {
         // At NAV = 1.5, 100 shares = 150 USDC
         let value = calculate_redemption_value(100_000_000, 1_500_000).unwrap();
         assert_eq!(value, 150_000_000);
+        
+        // At NAV = 0, shares redeem for nothing
+        assert_eq!(calculate_redemption_value(100_000_000, 0).unwrap(), 0);
+        assert!(calculate_redemption_value(0, 0).is_err());
     }
 
     #[test]
@@ -372,5 +646,111 @@ mod tests {
         assert_eq!(safe_div_i64(100, 10).unwrap(), 10);
         assert!(safe_div_i64(100, 0).is_err());
     }
-}
 
+    #[test]
+    fn test_validate_accounts() {
+        fn check(
+            account: &AccountInfo,
+            program_id: &Pubkey,
+            seeds: &[&[u8]],
+        ) -> Result<(), ProgramError> {
+            validate_accounts! {
+                account: signer, writable, owner = program_id, pda = (seeds, program_id);
+            }
+            Ok(())
+        }
+
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[b"test"], &program_id);
+        let mut lamports = 0u64;
+        let mut data = [0u8; 0];
+        let account = AccountInfo::new(&pda, true, true, &mut lamports, &mut data, &program_id, false, 0);
+
+        assert!(check(&account, &program_id, &[b"test"]).is_ok());
+        assert_eq!(
+            check(&account, &program_id, &[b"other"]).unwrap_err(),
+            FundError::PDAMismatch.into()
+        );
+        assert_eq!(
+            check(&account, &Pubkey::new_unique(), &[b"test"]).unwrap_err(),
+            FundError::InvalidAccountOwner.into()
+        );
+        assert_eq!(assert_key(&account, &pda), Ok(()));
+        assert_eq!(
+            assert_key(&account, &program_id).unwrap_err(),
+            FundError::AccountKeyMismatch.into()
+        );
+    }
+
+
+    #[test]
+    fn test_calculate_management_fee_from_integral() {
+        // 100,000 USDC held for a full year at 2% = 2,000 USDC
+        let integral = 100_000_000_000i128 * SECONDS_PER_YEAR as i128;
+        let fee = calculate_management_fee_from_integral(integral, 200).unwrap();
+        assert_eq!(fee, 2_000_000_000);
+        
+        // Matches the spot formula when AUM is constant
+        let spot = calculate_management_fee(100_000_000_000, 200, SECONDS_PER_YEAR).unwrap();
+        assert_eq!(fee, spot);
+        
+        assert_eq!(calculate_management_fee_from_integral(0, 200).unwrap(), 0);
+        assert_eq!(calculate_management_fee_from_integral(integral, 0).unwrap(), 0);
+    }
+
+
+    #[test]
+    fn test_calculate_trade_margin() {
+        // 2 BTC at 50,000 with 10x = 100,000 notional, 10,000 margin
+        let (notional, margin) = calculate_trade_margin(2_000_000, 50_000_000_000, 10).unwrap();
+        assert_eq!(notional, 100_000_000_000);
+        assert_eq!(margin, 10_000_000_000);
+        
+        // Margin rounds up
+        assert_eq!(calculate_trade_margin(1, 1_000_000, 3).unwrap(), (1, 1));
+        
+        assert!(calculate_trade_margin(0, 1_000_000, 1).is_err());
+        assert!(calculate_trade_margin(1_000_000, 1_000_000, 0).is_err());
+        assert!(calculate_trade_margin(1_000_000, 1_000_000, MAX_TRADE_LEVERAGE + 1).is_err());
+    }
+
+    #[test]
+    fn test_memo() {
+        assert!(validate_memo(None).is_ok());
+        assert!(validate_memo(Some("INV-2024-0042")).is_ok());
+        assert!(validate_memo(Some("")).is_err());
+        assert!(validate_memo(Some(&"x".repeat(MAX_MEMO_LEN))).is_ok());
+        assert!(validate_memo(Some(&"x".repeat(MAX_MEMO_LEN + 1))).is_err());
+        
+        assert_eq!(truncate_memo("short", MEMO_EVENT_LEN), "short");
+        assert_eq!(truncate_memo(&"a".repeat(100), MEMO_EVENT_LEN).len(), MEMO_EVENT_LEN);
+        // Never splits a multi-byte char ("资" is 3 bytes)
+        assert_eq!(truncate_memo("资金资金", 4), "资");
+    }
+
+    #[test]
+    fn test_verify_merkle_proof() {
+        let leaves: Vec<[u8; 32]> = (0..4u64)
+            .map(|i| balance_leaf_hash(&Pubkey::new_unique(), 1_000 * (i + 1)))
+            .collect();
+        let node = |a: [u8; 32], b: [u8; 32]| {
+            let (l, r) = if a <= b { (a, b) } else { (b, a) };
+            hashv(&[&[1u8], &l, &r]).to_bytes()
+        };
+        let n01 = node(leaves[0], leaves[1]);
+        let n23 = node(leaves[2], leaves[3]);
+        let root = node(n01, n23);
+        
+        assert!(verify_merkle_proof(leaves[0], &[leaves[1], n23], &root));
+        assert!(verify_merkle_proof(leaves[3], &[leaves[2], n01], &root));
+        
+        // Wrong sibling, wrong leaf
+        assert!(!verify_merkle_proof(leaves[0], &[leaves[2], n23], &root));
+        assert!(!verify_merkle_proof(leaves[1], &[leaves[1], n23], &root));
+        
+        // Single-leaf tree: the leaf is the root
+        assert!(verify_merkle_proof(leaves[0], &[], &leaves[0]));
+        assert!(!verify_merkle_proof(leaves[0], &[[0u8; 32]; MAX_MERKLE_PROOF_DEPTH + 1], &root));
+    }
+
+}
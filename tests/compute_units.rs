@@ -0,0 +1,213 @@
+//! Compute-unit regression checks for hot handlers
+//!
+//! Enabled with the `measure-cu` feature and run against the SBF build, since
+//! native builtins are not metered. The test is `#[ignore]`d so a native
+//! `cargo test --all-features` skips it; run it explicitly with:
+//!
+//! ```text
+//! cargo build-sbf && cargo test-sbf --features measure-cu -- --ignored
+//! ```
+
+#![cfg(feature = "measure-cu")]
+
+use borsh::BorshSerialize;
+use fund_program::{
+    instruction::{DepositToFundArgs, FundInstruction, RedeemAllArgs, RedeemFromFundArgs},
+    state::{FeeConfig, Fund, LPIndexPage, LPPosition},
+};
+use solana_program::{program_option::COption, program_pack::Pack};
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+
+/// CU budget for DepositToFund (first deposit, creates the LP position and LP index page)
+const DEPOSIT_CU_BUDGET: u64 = 75_000;
+
+/// CU budget for a partial RedeemFromFund
+const REDEEM_CU_BUDGET: u64 = 40_000;
+
+/// CU budget for RedeemAll (burns the rest, unindexes and closes the LP position)
+const REDEEM_ALL_CU_BUDGET: u64 = 45_000;
+
+const DEPOSIT_AMOUNT: u64 = 1_000_000_000; // 1,000 USDC
+
+fn seeds_refs(seeds: &[Vec<u8>]) -> Vec<&[u8]> {
+    seeds.iter().map(|s| s.as_slice()).collect()
+}
+
+fn mint_account(authority: Pubkey) -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(authority),
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account { lamports: 1_000_000_000, data, owner: spl_token::id(), executable: false, rent_epoch: 0 }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account { lamports: 1_000_000_000, data, owner: spl_token::id(), executable: false, rent_epoch: 0 }
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build: cargo test-sbf --features measure-cu -- --ignored"]
+async fn test_deposit_and_redeem_within_cu_budget() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("fund_program", program_id, None);
+    program_test.prefer_bpf(true);
+
+    let manager = Pubkey::new_unique();
+    let investor = Keypair::new();
+    let usdc_mint = Pubkey::new_unique();
+
+    let (fund_pda, fund_bump) =
+        Pubkey::find_program_address(&seeds_refs(&Fund::seeds(&manager, 0)), &program_id);
+    let (fund_vault, _) =
+        Pubkey::find_program_address(&seeds_refs(&Fund::vault_seeds(&fund_pda)), &program_id);
+    let (share_mint, _) =
+        Pubkey::find_program_address(&seeds_refs(&Fund::share_mint_seeds(&fund_pda)), &program_id);
+    let (lp_position, _) = Pubkey::find_program_address(
+        &seeds_refs(&LPPosition::seeds(&fund_pda, &investor.pubkey())),
+        &program_id,
+    );
+    let (lp_index_page, _) =
+        Pubkey::find_program_address(&seeds_refs(&LPIndexPage::seeds(&fund_pda, 0)), &program_id);
+    let investor_usdc = Pubkey::new_unique();
+    let investor_shares = Pubkey::new_unique();
+
+    let fund = Fund::new(
+        manager,
+        "CU Fund",
+        fund_bump,
+        fund_vault,
+        share_mint,
+        FeeConfig::new(200, 2000),
+        0,
+        0,
+    );
+    program_test.add_account(
+        fund_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data: fund.try_to_vec().unwrap(),
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(usdc_mint, mint_account(Pubkey::new_unique()));
+    program_test.add_account(share_mint, mint_account(fund_pda));
+    program_test.add_account(fund_vault, token_account(usdc_mint, fund_pda, 0));
+    program_test.add_account(investor_usdc, token_account(usdc_mint, investor.pubkey(), DEPOSIT_AMOUNT));
+    program_test.add_account(investor_shares, token_account(share_mint, investor.pubkey(), 0));
+    program_test.add_account(
+        investor.pubkey(),
+        Account { lamports: 10_000_000_000, owner: system_program::id(), ..Account::default() },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // Deposit
+    let deposit_ix = Instruction::new_with_bytes(
+        program_id,
+        &FundInstruction::DepositToFund(DepositToFundArgs { amount: DEPOSIT_AMOUNT, campaign_id: None, memo: None })
+            .try_to_vec()
+            .unwrap(),
+        vec![
+            AccountMeta::new(investor.pubkey(), true),
+            AccountMeta::new(fund_pda, false),
+            AccountMeta::new(fund_vault, false),
+            AccountMeta::new(investor_usdc, false),
+            AccountMeta::new(lp_position, false),
+            AccountMeta::new(investor_shares, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(lp_index_page, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &investor],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok(), "{:?}", result.result);
+    let units = result.metadata.unwrap().compute_units_consumed;
+    assert!(units <= DEPOSIT_CU_BUDGET, "DepositToFund used {} CU (budget {})", units, DEPOSIT_CU_BUDGET);
+
+    // Partial redeem
+    let redeem_ix = Instruction::new_with_bytes(
+        program_id,
+        &FundInstruction::RedeemFromFund(RedeemFromFundArgs { shares: DEPOSIT_AMOUNT / 2, memo: None })
+            .try_to_vec()
+            .unwrap(),
+        vec![
+            AccountMeta::new(investor.pubkey(), true),
+            AccountMeta::new(fund_pda, false),
+            AccountMeta::new(fund_vault, false),
+            AccountMeta::new(investor_usdc, false),
+            AccountMeta::new(lp_position, false),
+            AccountMeta::new(investor_shares, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[redeem_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &investor],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok(), "{:?}", result.result);
+    let units = result.metadata.unwrap().compute_units_consumed;
+    assert!(units <= REDEEM_CU_BUDGET, "RedeemFromFund used {} CU (budget {})", units, REDEEM_CU_BUDGET);
+
+    // Full exit of the remaining shares
+    let redeem_all_ix = Instruction::new_with_bytes(
+        program_id,
+        &FundInstruction::RedeemAll(RedeemAllArgs { memo: None }).try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(investor.pubkey(), true),
+            AccountMeta::new(fund_pda, false),
+            AccountMeta::new(fund_vault, false),
+            AccountMeta::new(investor_usdc, false),
+            AccountMeta::new(lp_position, false),
+            AccountMeta::new(investor_shares, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(lp_index_page, false),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[redeem_all_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &investor],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(result.result.is_ok(), "{:?}", result.result);
+    let units = result.metadata.unwrap().compute_units_consumed;
+    assert!(units <= REDEEM_ALL_CU_BUDGET, "RedeemAll used {} CU (budget {})", units, REDEEM_ALL_CU_BUDGET);
+    assert!(banks_client.get_account(lp_position).await.unwrap().is_none());
+}
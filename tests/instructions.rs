@@ -0,0 +1,1705 @@
+//! Instruction-level checks (authorization and failure paths) on the `test-utils` builders
+//!
+//! ```text
+//! cargo test --features test-utils --test instructions
+//! ```
+
+#![cfg(feature = "test-utils")]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use fund_program::{
+    error::FundError,
+    instruction::{
+        AddTradingFeeArgs, AdminSetFundOpenArgs, AdminSetFundPausedArgs, ClaimReferralRewardsArgs, CreateFundArgs, DepositToFundArgs, EnforceRiskLimitsArgs, FundInstruction, InitializeShareCheckpointsArgs,
+        RecordFeeRebateArgs, RecordFundExposureArgs, RelayerDepositToFundArgs, RelayerRedeemFromFundArgs, ReportPlatformOpenInterestArgs, SetFundStateArgs, SetLPBeneficiaryArgs, SetRelayerSystemPausedArgs, SettleReferralRewardsBatchResult, TransferLPPositionArgs,
+    },
+    ledger_interface::LedgerPositionHeader,
+    return_data::decode_return_data,
+    state::{
+        DepositReceipt, FeeConfig, Fund, FundCoveragePolicy, FundCoverageClaim, FundLifecycleState, FundListingStatus, FundStateReason, InsuranceDailyStats, InsuranceFundConfig, InvestorPortfolio,
+        FundConfig, LPIndexPage, LPPosition, ManagerProfile, ManagerRiskLimits, ManagerRiskState, MarkPrice, PendingReinitialize, RelayerNonce, RelayerStats, RISK_BREACH_GRACE_SECS, RISK_EXPOSURE_MAX_AGE_SECS,
+        ReferralConfig, ReferralLink, ShareCheckpoints, TradeIntent, Treasury, TreasurySource, VersionedAccount,
+        STALE_ENTRY_GRACE_SECS,
+    },
+    test_utils::{FundScenario, FundScenarioBuilder},
+};
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey, system_program, sysvar};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+const USDC: u64 = 1_000_000;
+
+fn instruction_error(result: Result<(), BanksClientError>) -> InstructionError {
+    match result.expect_err("transaction should fail").unwrap() {
+        TransactionError::InstructionError(_, err) => err,
+        err => panic!("unexpected transaction error: {err:?}"),
+    }
+}
+
+fn assert_fund_error(result: Result<(), BanksClientError>, expected: FundError) {
+    assert_eq!(instruction_error(result), InstructionError::Custom(expected as u32));
+}
+
+fn find_pda(seeds: Vec<Vec<u8>>, program_id: &Pubkey) -> (Pubkey, u8) {
+    let refs: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+    Pubkey::find_program_address(&refs, program_id)
+}
+
+async fn lamports(s: &mut FundScenario, key: Pubkey) -> u64 {
+    s.context.banks_client.get_balance(key).await.unwrap()
+}
+
+async fn account_data(s: &mut FundScenario, key: Pubkey) -> Vec<u8> {
+    s.context.banks_client.get_account(key).await.unwrap().unwrap().data
+}
+
+async fn funded_scenario() -> FundScenario {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    s
+}
+
+/// Caller, Fund, FundConfig: the layout of the Ledger-only instructions
+fn ledger_call_accounts(s: &FundScenario, caller: AccountMeta) -> Vec<AccountMeta> {
+    vec![
+        caller,
+        AccountMeta::new(s.fund, false),
+        AccountMeta::new_readonly(s.fund_config, false),
+    ]
+}
+
+// === DepositToFund ===
+
+#[tokio::test]
+async fn test_deposit_account_constraints() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let investor = s.investors[0].keypair.insecure_clone();
+    let (usdc, shares) = (s.investors[0].usdc, s.investors[0].shares);
+
+    let mut ix = s.deposit_instruction(0, 1_000 * USDC).await;
+    ix.accounts[0].is_signer = false;
+    assert_eq!(instruction_error(s.process(&[ix], &[]).await), InstructionError::MissingRequiredSignature);
+
+    let mut ix = s.deposit_instruction(0, 1_000 * USDC).await;
+    ix.accounts[1].is_writable = false;
+    assert_eq!(instruction_error(s.process(&[ix], &[&investor]).await), InstructionError::InvalidAccountData);
+
+    // A token account posing as the fund
+    let mut ix = s.deposit_instruction(0, 1_000 * USDC).await;
+    ix.accounts[1].pubkey = usdc;
+    assert_fund_error(s.process(&[ix], &[&investor]).await, FundError::InvalidAccountOwner);
+
+    // Vault and share mint must be the fund's own
+    let fund = s.fund;
+    let mut ix = s.deposit_instruction(0, 1_000 * USDC).await;
+    ix.accounts[2].pubkey = s.create_usdc_account(&fund, 0);
+    assert_fund_error(s.process(&[ix], &[&investor]).await, FundError::AccountKeyMismatch);
+    let mut ix = s.deposit_instruction(0, 1_000 * USDC).await;
+    ix.accounts[6].pubkey = shares;
+    assert_fund_error(s.process(&[ix], &[&investor]).await, FundError::AccountKeyMismatch);
+
+    let mut ix = s.deposit_instruction(0, 1_000 * USDC).await;
+    ix.accounts[7].pubkey = system_program::id();
+    assert_fund_error(s.process(&[ix], &[&investor]).await, FundError::AccountKeyMismatch);
+
+    assert!(s.lp_position(0).await.is_none());
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+}
+
+/// DepositReceipt record, mint and token account PDAs of investor `index`
+fn receipt_accounts(s: &FundScenario, index: usize) -> Vec<AccountMeta> {
+    let lp_position = s.investors[index].lp_position;
+    [DepositReceipt::seeds(&lp_position), DepositReceipt::mint_seeds(&lp_position), DepositReceipt::token_seeds(&lp_position)]
+        .into_iter()
+        .map(|seeds| AccountMeta::new(find_pda(seeds, &s.program_id).0, false))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_full_redemption_requires_and_burns_deposit_receipt() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let mut fund = s.fund_state().await;
+    fund.deposit_receipts_enabled = true;
+    s.write_fund(&fund).await;
+
+    // Receipt accounts follow the System Program
+    let receipts = receipt_accounts(&s, 0);
+    let mut ix = s.deposit_instruction(0, 1_000 * USDC).await;
+    ix.accounts.splice(9..9, receipts.clone());
+    let investor = s.investors[0].keypair.insecure_clone();
+    s.process(&[ix], &[&investor]).await.unwrap();
+    assert!(s.lp_position(0).await.unwrap().has_receipt);
+    assert_eq!(s.token_balance(receipts[2].pubkey).await, 1);
+
+    // Partial redemptions leave the receipt alone; emptying the position needs it
+    s.redeem(0, 400 * USDC).await.unwrap();
+    assert_fund_error(s.redeem(0, 600 * USDC).await, FundError::DepositReceiptMissing);
+    assert_fund_error(s.redeem_all(0).await, FundError::DepositReceiptMissing);
+
+    s.redeem_all_with(0, receipts.clone()).await.unwrap();
+    // Record and token account are closed; the mint cannot be, so its supply drops to 0
+    assert!(s.context.banks_client.get_account(receipts[0].pubkey).await.unwrap().is_none());
+    assert!(s.context.banks_client.get_account(receipts[2].pubkey).await.unwrap().is_none());
+    let mint = spl_token::state::Mint::unpack(&account_data(&mut s, receipts[1].pubkey).await).unwrap();
+    assert_eq!(mint.supply, 0);
+    assert!(s.lp_position(0).await.is_none());
+}
+
+#[tokio::test]
+async fn test_insurance_fund_deposits_blocked_during_adl() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let mut fund = s.fund_state().await;
+    fund.is_insurance_fund = true;
+    s.write_fund(&fund).await;
+
+    let program_id = s.program_id;
+    let (insurance_config, bump) = find_pda(InsuranceFundConfig::seeds(), &program_id);
+    let mut config = InsuranceFundConfig::new(s.fund, bump, 0, 0, Pubkey::new_unique(), s.now().await);
+    config.set_adl_in_progress(true);
+    s.set_account(&insurance_config, &program_id, config.try_to_vec().unwrap());
+
+    assert_fund_error(s.deposit(0, 1_000 * USDC).await, FundError::InvalidInsuranceFundConfig);
+    let config_meta = AccountMeta::new_readonly(insurance_config, false);
+    assert_fund_error(s.deposit_with(0, 1_000 * USDC, vec![config_meta.clone()]).await, FundError::ADLInProgress);
+    assert!(s.lp_position(0).await.is_none());
+
+    config.set_adl_in_progress(false);
+    s.set_account(&insurance_config, &program_id, config.try_to_vec().unwrap());
+    s.deposit_with(0, 1_000 * USDC, vec![config_meta]).await.unwrap();
+    assert_eq!(s.fund_state().await.stats.total_deposits_e6, 1_000 * USDC as i64);
+}
+
+// === Reinitialize ===
+
+#[tokio::test]
+async fn test_reinitialize_grows_but_never_shrinks_fund_config() {
+    let mut s = FundScenarioBuilder::new().build().await;
+    let (program_id, admin) = (s.program_id, s.admin.insecure_clone());
+    s.set_account(&admin.pubkey(), &system_program::id(), Vec::new());
+    let (pending, bump) = find_pda(PendingReinitialize::seeds(), &program_id);
+    let fund_config = s.fund_config;
+    let (vault_program, ledger_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut config = s.fund_config_state().await;
+    config.total_funds = 7;
+    let encoded = config.try_to_vec().unwrap();
+    assert_eq!(encoded.len(), FundConfig::SIZE);
+
+    let reinitialize = |s: &mut FundScenario, config_data: Vec<u8>, scheduled_at: i64| {
+        s.set_account(&fund_config, &program_id, config_data);
+        let schedule = PendingReinitialize::new(admin.pubkey(), vault_program, ledger_program, 0, bump, scheduled_at);
+        s.set_account(&pending, &program_id, schedule.try_to_vec().unwrap());
+        s.instruction(
+            FundInstruction::Reinitialize,
+            vec![
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new(fund_config, false),
+                AccountMeta::new(pending, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let now = s.now().await;
+    let ready = now - PendingReinitialize::TIMELOCK_SECS;
+
+    let ix = reinitialize(&mut s, encoded.clone(), now);
+    assert_fund_error(s.process(&[ix], &[&admin]).await, FundError::TimelockNotElapsed);
+
+    // An account from an older, shorter layout grows, keeping its counters
+    let ix = reinitialize(&mut s, encoded[..FundConfig::SIZE - 16].to_vec(), ready);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    assert_eq!(account_data(&mut s, fund_config).await.len(), FundConfig::SIZE);
+    let config = s.fund_config_state().await;
+    assert_eq!((config.total_funds, config.vault_program, config.ledger_program), (7, vault_program, ledger_program));
+    assert!(s.context.banks_client.get_account(pending).await.unwrap().is_none());
+
+    // A larger account keeps its size; only the bytes past the layout are cleared
+    let mut oversized = encoded.clone();
+    oversized.extend([0xAA; 64]);
+    let ix = reinitialize(&mut s, oversized, ready);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    let data = account_data(&mut s, fund_config).await;
+    assert_eq!(data.len(), FundConfig::SIZE + 64);
+    assert!(data[FundConfig::SIZE..].iter().all(|b| *b == 0));
+    assert_eq!(s.fund_config_state().await.total_funds, 7);
+}
+
+// === RecordFeeRebate ===
+
+#[tokio::test]
+async fn test_fee_rebate_from_ledger_credits_nav() {
+    let mut s = funded_scenario().await;
+    let nav_before = s.fund_state().await.stats.current_nav_e6;
+
+    s.record_fee_rebate(10 * USDC as i64).await.unwrap();
+
+    let fund = s.fund_state().await;
+    assert_eq!(fund.total_fee_rebates_e6, 10 * USDC as i64);
+    assert!(fund.stats.current_nav_e6 > nav_before);
+}
+
+#[tokio::test]
+async fn test_fee_rebate_rejects_unsigned_ledger_caller() {
+    let mut s = funded_scenario().await;
+    let args = || FundInstruction::RecordFeeRebate(RecordFeeRebateArgs { rebate_e6: 10 * USDC as i64 });
+
+    // The right key, but nothing signed for it
+    let caller = AccountMeta::new_readonly(s.ledger_caller, false);
+    let ix = s.instruction(args(), ledger_call_accounts(&s, caller));
+    assert_eq!(instruction_error(s.process(&[ix], &[]).await), InstructionError::MissingRequiredSignature);
+
+    // The Ledger program id itself can never sign
+    let caller = AccountMeta::new_readonly(s.ledger_program, false);
+    let ix = s.instruction(args(), ledger_call_accounts(&s, caller));
+    assert_fund_error(s.process(&[ix], &[]).await, FundError::UnauthorizedCaller);
+
+    // Any other signer
+    let outsider = Keypair::new();
+    let caller = AccountMeta::new_readonly(outsider.pubkey(), true);
+    let ix = s.instruction(args(), ledger_call_accounts(&s, caller));
+    assert_fund_error(s.process(&[ix], &[&outsider]).await, FundError::UnauthorizedCaller);
+
+    assert_eq!(s.fund_state().await.total_fee_rebates_e6, 0);
+}
+
+// === RecordFundExposure ===
+
+#[tokio::test]
+async fn test_fund_exposure_from_ledger() {
+    let mut s = funded_scenario().await;
+    s.advance_clock(60).await;
+
+    s.record_exposure(2_000 * USDC, 400 * USDC, 600 * USDC as i64).await.unwrap();
+
+    let now = s.now().await;
+    let fund = s.fund_state().await;
+    assert_eq!((fund.open_notional_e6, fund.margin_used_e6), (2_000 * USDC, 400 * USDC));
+    assert_eq!(fund.free_collateral_e6, 600 * USDC as i64);
+    assert_eq!((fund.exposure_updated_at, fund.last_update_ts), (now, now));
+}
+
+#[tokio::test]
+async fn test_fund_exposure_rejects_unsigned_ledger_caller() {
+    let mut s = funded_scenario().await;
+    let args = || {
+        FundInstruction::RecordFundExposure(RecordFundExposureArgs {
+            open_notional_e6: u64::MAX,
+            margin_used_e6: 0,
+            free_collateral_e6: 0,
+        })
+    };
+
+    let caller = AccountMeta::new_readonly(s.ledger_caller, false);
+    let ix = s.instruction(args(), ledger_call_accounts(&s, caller));
+    assert_eq!(instruction_error(s.process(&[ix], &[]).await), InstructionError::MissingRequiredSignature);
+
+    let caller = AccountMeta::new_readonly(s.ledger_program, false);
+    let ix = s.instruction(args(), ledger_call_accounts(&s, caller));
+    assert_fund_error(s.process(&[ix], &[]).await, FundError::UnauthorizedCaller);
+
+    assert_eq!(s.fund_state().await.open_notional_e6, 0);
+}
+
+// === EnforceRiskLimits ===
+
+const MARKET: u8 = 0;
+const MARK_E6: u64 = 100 * USDC;
+
+/// Keeper and risk accounts around a fund with 1,000 USDC of equity and a 2x leverage cap
+struct RiskSetup {
+    keeper: Keypair,
+    keeper_usdc: Pubkey,
+    limits: Pubkey,
+    state: Pubkey,
+    mark_price: Pubkey,
+    position: Pubkey,
+}
+
+async fn risk_scenario(position_size_e6: u64) -> (FundScenario, RiskSetup) {
+    let mut s = funded_scenario().await;
+    let program_id = s.program_id;
+    let now = s.now().await;
+
+    let (limits, bump) = find_pda(ManagerRiskLimits::seeds(), &program_id);
+    let mut limits_state = ManagerRiskLimits::new(bump);
+    limits_state.max_leverage = 2;
+    s.set_account(&limits, &program_id, limits_state.try_to_vec().unwrap());
+
+    let manager = s.manager.pubkey();
+    let (state, bump) = find_pda(ManagerRiskState::seeds(&manager), &program_id);
+    let mut risk_state = ManagerRiskState::new(manager, bump, now);
+    risk_state.record_open(5_000 * USDC, now);
+    s.set_account(&state, &program_id, risk_state.try_to_vec().unwrap());
+
+    let (mark_price, _) = find_pda(MarkPrice::seeds(MARKET), &program_id);
+    let position = Pubkey::new_unique();
+    let header = LedgerPositionHeader {
+        discriminator: 1,
+        user: s.fund,
+        market_index: MARKET,
+        side: 0,
+        size_e6: position_size_e6,
+    };
+    let ledger_program = s.ledger_program;
+    s.set_account(&position, &ledger_program, header.try_to_vec().unwrap());
+
+    let keeper = Keypair::new();
+    let keeper_usdc = s.create_usdc_account(&keeper.pubkey(), 0);
+    let setup = RiskSetup { keeper, keeper_usdc, limits, state, mark_price, position };
+    publish_mark(&mut s, &setup).await;
+    (s, setup)
+}
+
+/// Write a fresh MarkPrice for MARKET
+async fn publish_mark(s: &mut FundScenario, r: &RiskSetup) {
+    let (_, bump) = find_pda(MarkPrice::seeds(MARKET), &s.program_id);
+    let mut mark = MarkPrice::new(MARKET, bump);
+    mark.price_e6 = MARK_E6;
+    mark.updated_at = s.now().await;
+    let program_id = s.program_id;
+    s.set_account(&r.mark_price, &program_id, mark.try_to_vec().unwrap());
+}
+
+fn enforce_ix(s: &FundScenario, r: &RiskSetup, size_e6: u64) -> Instruction {
+    s.instruction(
+        FundInstruction::EnforceRiskLimits(EnforceRiskLimitsArgs {
+            market_index: MARKET,
+            size_e6,
+            price_e6: MARK_E6,
+        }),
+        vec![
+            AccountMeta::new_readonly(r.keeper.pubkey(), true),
+            AccountMeta::new(s.fund, false),
+            AccountMeta::new_readonly(s.fund_config, false),
+            AccountMeta::new_readonly(r.limits, false),
+            AccountMeta::new(r.state, false),
+            AccountMeta::new(s.fund_vault, false),
+            AccountMeta::new(r.keeper_usdc, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(s.ledger_program, false),
+            AccountMeta::new(r.position, false),
+            // Ledger user account, vault config, insurance fund, ledger config, user stats
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(s.vault_program, false),
+            AccountMeta::new_readonly(r.mark_price, false),
+        ],
+    )
+}
+
+async fn enforce(s: &mut FundScenario, r: &RiskSetup, size_e6: u64) -> Result<(), BanksClientError> {
+    let ix = enforce_ix(s, r, size_e6);
+    s.process(&[ix], &[&r.keeper]).await
+}
+
+#[tokio::test]
+async fn test_enforce_risk_limits_grace_period_and_keeper_payout() {
+    let (mut s, r) = risk_scenario(50 * USDC).await;
+    // 5,000 open against 2x of 1,000 equity: 3,000 over
+    s.record_exposure(5_000 * USDC, 0, 0).await.unwrap();
+
+    // First call only records the breach
+    let breached_at = s.now().await;
+    enforce(&mut s, &r, 100 * USDC).await.unwrap();
+    let fund = s.fund_state().await;
+    assert_eq!(fund.risk_breach_since, breached_at);
+    assert_eq!(fund.open_notional_e6, 5_000 * USDC);
+    assert_eq!(s.token_balance(r.keeper_usdc).await, 0);
+
+    // Still inside the grace period
+    s.advance_clock(RISK_BREACH_GRACE_SECS / 2).await;
+    s.record_exposure(5_000 * USDC, 0, 0).await.unwrap();
+    publish_mark(&mut s, &r).await;
+    assert_fund_error(enforce(&mut s, &r, 100 * USDC).await, FundError::RiskBreachGracePeriodActive);
+
+    // Past the grace period, but the Ledger has not reported since
+    s.advance_clock(RISK_BREACH_GRACE_SECS / 2).await;
+    publish_mark(&mut s, &r).await;
+    assert_fund_error(enforce(&mut s, &r, 100 * USDC).await, FundError::FundExposureStale);
+
+    // Fresh exposure: the close is clamped to the 3,000 excess (30 @ 100) and the keeper earns 0.1%
+    s.record_exposure(5_000 * USDC, 0, 0).await.unwrap();
+    let vault_before = s.token_balance(s.fund_vault).await;
+    enforce(&mut s, &r, 100 * USDC).await.unwrap();
+
+    assert_eq!(s.token_balance(r.keeper_usdc).await, 3 * USDC);
+    assert_eq!(s.token_balance(s.fund_vault).await, vault_before - 3 * USDC);
+    let fund = s.fund_state().await;
+    assert_eq!(fund.open_notional_e6, 2_000 * USDC);
+    assert_eq!(fund.stats.total_realized_pnl_e6, -3 * USDC as i64);
+    assert_eq!(fund.risk_breach_since, 0);
+}
+
+#[tokio::test]
+async fn test_enforce_risk_limits_bounded_by_ledger_position() {
+    let (mut s, r) = risk_scenario(10 * USDC).await;
+    s.record_exposure(5_000 * USDC, 0, 0).await.unwrap();
+    enforce(&mut s, &r, 100 * USDC).await.unwrap();
+    let breached_at = s.fund_state().await.risk_breach_since;
+
+    s.advance_clock(RISK_BREACH_GRACE_SECS).await;
+    s.record_exposure(5_000 * USDC, 0, 0).await.unwrap();
+    publish_mark(&mut s, &r).await;
+
+    // A position account not owned by the Ledger is rejected
+    let ledger_program = s.ledger_program;
+    let spoofed = LedgerPositionHeader {
+        discriminator: 1,
+        user: s.fund,
+        market_index: MARKET,
+        side: 0,
+        size_e6: 100 * USDC,
+    };
+    let program_id = s.program_id;
+    s.set_account(&r.position, &program_id, spoofed.try_to_vec().unwrap());
+    assert_fund_error(enforce(&mut s, &r, 100 * USDC).await, FundError::LedgerPositionMismatch);
+
+    // Only the 10 the fund holds on the Ledger can be closed, so the breach stays open
+    let held = LedgerPositionHeader { size_e6: 10 * USDC, ..spoofed };
+    s.set_account(&r.position, &ledger_program, held.try_to_vec().unwrap());
+    enforce(&mut s, &r, 100 * USDC).await.unwrap();
+
+    assert_eq!(s.token_balance(r.keeper_usdc).await, USDC);
+    let fund = s.fund_state().await;
+    assert_eq!(fund.open_notional_e6, 4_000 * USDC);
+    assert_eq!(fund.risk_breach_since, breached_at);
+    assert!(s.now().await - fund.exposure_updated_at <= RISK_EXPOSURE_MAX_AGE_SECS);
+}
+
+// === ClaimFundCoverage / TransferLPPosition ===
+
+/// Insurance Fund holding 1,000 USDC and a 50% / 10% deductible policy on the scenario fund
+struct CoverageSetup {
+    insurance_config: Pubkey,
+    insurance_fund: Pubkey,
+    insurance_vault: Pubkey,
+    policy: Pubkey,
+}
+
+async fn coverage_scenario() -> (FundScenario, CoverageSetup) {
+    let mut s = FundScenarioBuilder::new().depositors(2, 10_000 * USDC).build().await;
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    let program_id = s.program_id;
+    let now = s.now().await;
+
+    let insurance_manager = Pubkey::new_unique();
+    let (insurance_fund, bump) = find_pda(Fund::seeds(&insurance_manager, 0), &program_id);
+    let insurance_vault = s.create_usdc_account(&insurance_fund, 1_000 * USDC);
+    let mut insurance = Fund::new(
+        insurance_manager,
+        "Insurance Fund",
+        bump,
+        insurance_vault,
+        Pubkey::new_unique(),
+        FeeConfig::new(0, 0),
+        0,
+        now,
+    );
+    insurance.record_deposit(1_000 * USDC as i64, 1_000 * USDC).unwrap();
+    s.set_account(&insurance_fund, &program_id, insurance.try_to_vec().unwrap());
+
+    let (insurance_config, bump) = find_pda(InsuranceFundConfig::seeds(), &program_id);
+    let config = InsuranceFundConfig::new(insurance_fund, bump, 0, 0, Pubkey::new_unique(), now);
+    s.set_account(&insurance_config, &program_id, config.try_to_vec().unwrap());
+
+    let (policy, bump) = find_pda(FundCoveragePolicy::seeds(&s.fund), &program_id);
+    let terms = FundCoveragePolicy::new(s.fund, 200, 5000, 1000, 500 * USDC as i64, 1_000_000, 0, bump, now);
+    s.set_account(&policy, &program_id, terms.try_to_vec().unwrap());
+
+    (s, CoverageSetup { insurance_config, insurance_fund, insurance_vault, policy })
+}
+
+fn claim_coverage_ix(s: &FundScenario, c: &CoverageSetup, index: usize) -> Instruction {
+    let investor = &s.investors[index];
+    let (claim, _) = find_pda(FundCoverageClaim::seeds(&c.policy, &investor.keypair.pubkey()), &s.program_id);
+    s.instruction(
+        FundInstruction::ClaimFundCoverage,
+        vec![
+            AccountMeta::new(investor.keypair.pubkey(), true),
+            AccountMeta::new_readonly(s.fund, false),
+            AccountMeta::new(c.policy, false),
+            AccountMeta::new(investor.lp_position, false),
+            AccountMeta::new(claim, false),
+            AccountMeta::new_readonly(c.insurance_config, false),
+            AccountMeta::new(c.insurance_fund, false),
+            AccountMeta::new(c.insurance_vault, false),
+            AccountMeta::new(investor.usdc, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+async fn claim_coverage(s: &mut FundScenario, c: &CoverageSetup, index: usize) -> Result<(), BanksClientError> {
+    let ix = claim_coverage_ix(s, c, index);
+    let payer = s.investors[index].keypair.insecure_clone();
+    s.process(&[ix], &[&payer]).await
+}
+
+/// TransferLPPosition from investor `from` to investor `to`'s wallet
+fn transfer_position_ix(s: &FundScenario, from: usize, to: usize) -> Instruction {
+    let page = find_pda(LPIndexPage::seeds(&s.fund, 0), &s.program_id).0;
+    let next_page = find_pda(LPIndexPage::seeds(&s.fund, 1), &s.program_id).0;
+    let (owner, new_owner) = (&s.investors[from], &s.investors[to]);
+    s.instruction(
+        FundInstruction::TransferLPPosition(TransferLPPositionArgs { new_owner: new_owner.keypair.pubkey() }),
+        vec![
+            AccountMeta::new_readonly(owner.keypair.pubkey(), true),
+            AccountMeta::new(new_owner.keypair.pubkey(), true),
+            AccountMeta::new(s.fund, false),
+            AccountMeta::new(owner.lp_position, false),
+            AccountMeta::new(new_owner.lp_position, false),
+            AccountMeta::new(owner.shares, false),
+            AccountMeta::new(new_owner.shares, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(page, false),
+            AccountMeta::new(next_page, false),
+        ],
+    )
+}
+
+async fn transfer_position(s: &mut FundScenario, from: usize, to: usize) -> Result<(), BanksClientError> {
+    let ix = transfer_position_ix(s, from, to);
+    let signers = [s.investors[from].keypair.insecure_clone(), s.investors[to].keypair.insecure_clone()];
+    s.process(&[ix], &[&signers[0], &signers[1]]).await
+}
+
+#[tokio::test]
+async fn test_coverage_claim_not_repeated_after_position_transfer() {
+    let (mut s, c) = coverage_scenario().await;
+    // Trading loss to NAV 0.8, below the 0.9 trigger
+    s.record_pnl(-200 * USDC as i64).await.unwrap();
+
+    // 1,000 shares × (0.9 - 0.8) × 50%
+    let usdc_before = s.token_balance(s.investors[0].usdc).await;
+    claim_coverage(&mut s, &c, 0).await.unwrap();
+    assert_eq!(s.token_balance(s.investors[0].usdc).await - usdc_before, 50 * USDC);
+    assert_eq!(s.lp_position(0).await.unwrap().coverage_nav_e6, 800_000);
+    assert_fund_error(claim_coverage(&mut s, &c, 0).await, FundError::NoQualifyingLoss);
+
+    // The compensated-to NAV moves with the position to the new wallet
+    transfer_position(&mut s, 0, 1).await.unwrap();
+    let moved = s.lp_position(1).await.unwrap();
+    assert_eq!((moved.shares, moved.coverage_nav_e6), (1_000 * USDC, 800_000));
+    assert_fund_error(claim_coverage(&mut s, &c, 1).await, FundError::NoQualifyingLoss);
+    assert_eq!(s.token_balance(c.insurance_vault).await, 950 * USDC);
+
+    // A further drop is still covered, from where the last claim stopped
+    s.record_pnl(-100 * USDC as i64).await.unwrap();
+    let usdc_before = s.token_balance(s.investors[1].usdc).await;
+    claim_coverage(&mut s, &c, 1).await.unwrap();
+    assert_eq!(s.token_balance(s.investors[1].usdc).await - usdc_before, 50 * USDC);
+}
+
+#[tokio::test]
+async fn test_transfer_lp_position_failure_paths() {
+    let mut s = FundScenarioBuilder::new().depositors(3, 10_000 * USDC).build().await;
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    s.deposit(1, 500 * USDC).await.unwrap();
+    let deposited_at = s.lp_position(0).await.unwrap().deposited_at;
+
+    // Both wallets must sign
+    let mut ix = transfer_position_ix(&s, 0, 2);
+    ix.accounts[1].is_signer = false;
+    let owner = s.investors[0].keypair.insecure_clone();
+    assert_eq!(instruction_error(s.process(&[ix], &[&owner]).await), InstructionError::MissingRequiredSignature);
+
+    // Moving a position onto the same wallet is not a transfer
+    assert_eq!(instruction_error(transfer_position(&mut s, 0, 0).await), InstructionError::InvalidArgument);
+
+    // Only the investor recorded on the position can move it
+    let mut ix = transfer_position_ix(&s, 1, 2);
+    ix.accounts[3].pubkey = s.investors[0].lp_position;
+    ix.accounts[5].pubkey = s.investors[0].shares;
+    let signers = [s.investors[1].keypair.insecure_clone(), s.investors[2].keypair.insecure_clone()];
+    assert_fund_error(s.process(&[ix], &[&signers[0], &signers[1]]).await, FundError::LPPositionNotFound);
+
+    // A live position is never merged into
+    assert_fund_error(transfer_position(&mut s, 0, 1).await, FundError::LPPositionTransferTargetInUse);
+    assert_eq!(s.lp_position(1).await.unwrap().shares, 500 * USDC);
+
+    // The emptied source cannot be transferred a second time
+    transfer_position(&mut s, 0, 2).await.unwrap();
+    let moved = s.lp_position(2).await.unwrap();
+    assert_eq!((moved.shares, moved.deposited_at), (1_000 * USDC, deposited_at));
+    assert_eq!(s.token_balance(s.investors[2].shares).await, 1_000 * USDC);
+    assert_fund_error(transfer_position(&mut s, 0, 1).await, FundError::InsufficientShares);
+}
+
+// === LP Beneficiary ===
+
+fn set_beneficiary_ix(s: &FundScenario, index: usize, beneficiary: Option<Pubkey>, shares: Pubkey) -> Instruction {
+    let investor = &s.investors[index];
+    s.instruction(
+        FundInstruction::SetLPBeneficiary(SetLPBeneficiaryArgs { beneficiary }),
+        vec![
+            AccountMeta::new_readonly(investor.keypair.pubkey(), true),
+            AccountMeta::new_readonly(s.fund, false),
+            AccountMeta::new(investor.lp_position, false),
+            AccountMeta::new(shares, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+fn claim_as_beneficiary_ix(s: &FundScenario, from: usize, to: usize, source: Pubkey) -> Instruction {
+    let page = find_pda(LPIndexPage::seeds(&s.fund, 0), &s.program_id).0;
+    let (investor, beneficiary) = (&s.investors[from], &s.investors[to]);
+    s.instruction(
+        FundInstruction::ClaimAsBeneficiary,
+        vec![
+            AccountMeta::new(beneficiary.keypair.pubkey(), true),
+            AccountMeta::new_readonly(s.fund_config, false),
+            AccountMeta::new(s.fund, false),
+            AccountMeta::new(investor.lp_position, false),
+            AccountMeta::new(beneficiary.lp_position, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(beneficiary.shares, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(page, false),
+        ],
+    )
+}
+
+async fn share_account_state(s: &mut FundScenario, key: Pubkey) -> spl_token::state::Account {
+    spl_token::state::Account::unpack(&account_data(s, key).await).unwrap()
+}
+
+#[tokio::test]
+async fn test_beneficiary_claim_bound_to_investor_share_approval() {
+    let mut s = FundScenarioBuilder::new().depositors(2, 10_000 * USDC).build().await;
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    s.deposit(1, 500 * USDC).await.unwrap();
+    let (investor, beneficiary) = (s.investors[0].keypair.insecure_clone(), s.investors[1].keypair.insecure_clone());
+    let (investor_shares, beneficiary_shares) = (s.investors[0].shares, s.investors[1].shares);
+    let inactivity = FundConfig::DEFAULT_BENEFICIARY_INACTIVITY_SECS;
+
+    // Only the investor's own share account can carry the approval
+    let ix = set_beneficiary_ix(&s, 0, Some(beneficiary.pubkey()), beneficiary_shares);
+    assert_fund_error(s.process(&[ix], &[&investor]).await, FundError::InvalidAccountOwner);
+
+    // The approval covers exactly the position's shares at the time
+    let ix = set_beneficiary_ix(&s, 0, Some(beneficiary.pubkey()), investor_shares);
+    s.process(&[ix], &[&investor]).await.unwrap();
+    let approved = share_account_state(&mut s, investor_shares).await;
+    assert_eq!(approved.delegate, COption::Some(s.fund));
+    assert_eq!(approved.delegated_amount, s.lp_position(0).await.unwrap().shares);
+
+    // Shares added afterwards outgrow the approval until it is refreshed
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    s.advance_clock(inactivity).await;
+    let ix = claim_as_beneficiary_ix(&s, 0, 1, investor_shares);
+    assert_fund_error(s.process(&[ix], &[&beneficiary]).await, FundError::BeneficiaryApprovalMissing);
+
+    let ix = set_beneficiary_ix(&s, 0, Some(beneficiary.pubkey()), investor_shares);
+    s.process(&[ix], &[&investor]).await.unwrap();
+    s.advance_clock(inactivity).await;
+
+    // The source must be the investor's share account, not one the beneficiary controls
+    let ix = claim_as_beneficiary_ix(&s, 0, 1, beneficiary_shares);
+    assert_fund_error(s.process(&[ix], &[&beneficiary]).await, FundError::InvalidAccountOwner);
+
+    let claimed = s.lp_position(0).await.unwrap().shares;
+    let before = share_account_state(&mut s, beneficiary_shares).await.amount;
+    let ix = claim_as_beneficiary_ix(&s, 0, 1, investor_shares);
+    s.process(&[ix], &[&beneficiary]).await.unwrap();
+    assert_eq!(share_account_state(&mut s, investor_shares).await.amount, 0);
+    assert_eq!(share_account_state(&mut s, beneficiary_shares).await.amount, before + claimed);
+    assert_eq!(s.lp_position(0).await.unwrap().shares, 0);
+}
+
+// === AddTradingFee ===
+
+#[tokio::test]
+async fn test_add_trading_fee_checks_destination_and_mint() {
+    let (mut s, c) = coverage_scenario().await;
+    let program_id = s.program_id;
+    let ledger = Keypair::new();
+    let config = InsuranceFundConfig::new(c.insurance_fund, 255, 0, 0, ledger.pubkey(), s.now().await);
+    s.set_account(&c.insurance_config, &program_id, config.try_to_vec().unwrap());
+    let fee_source = s.create_usdc_account(&ledger.pubkey(), 100 * USDC);
+
+    let add_fee = |s: &FundScenario, source: Pubkey, destination: Pubkey| {
+        s.instruction(
+            FundInstruction::AddTradingFee(AddTradingFeeArgs { fee_e6: 10 * USDC as i64 }),
+            vec![
+                AccountMeta::new_readonly(ledger.pubkey(), true),
+                AccountMeta::new(c.insurance_fund, false),
+                AccountMeta::new(c.insurance_config, false),
+                AccountMeta::new(source, false),
+                AccountMeta::new(destination, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        )
+    };
+
+    // Any other USDC account, even one the Ledger controls, is not the insurance vault
+    let elsewhere = s.create_usdc_account(&ledger.pubkey(), 0);
+    let ix = add_fee(&s, fee_source, elsewhere);
+    assert_fund_error(s.process(&[ix], &[&ledger]).await, FundError::FeeDestinationMismatch);
+
+    let other_mint_source = s.create_token_account(&Pubkey::new_unique(), &ledger.pubkey(), 100 * USDC);
+    let ix = add_fee(&s, other_mint_source, c.insurance_vault);
+    assert_fund_error(s.process(&[ix], &[&ledger]).await, FundError::FeeMintMismatch);
+    assert_eq!(s.token_balance(c.insurance_vault).await, 1_000 * USDC);
+
+    let ix = add_fee(&s, fee_source, c.insurance_vault);
+    s.process(&[ix], &[&ledger]).await.unwrap();
+    assert_eq!(s.token_balance(c.insurance_vault).await, 1_010 * USDC);
+    assert_eq!(s.token_balance(fee_source).await, 90 * USDC);
+}
+
+// === Insurance Coverage ===
+
+async fn insurance_config_state(s: &mut FundScenario, c: &CoverageSetup) -> InsuranceFundConfig {
+    let account = s.context.banks_client.get_account(c.insurance_config).await.unwrap().unwrap();
+    InsuranceFundConfig::unpack_versioned(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_insurance_coverage_report_and_crank() {
+    let (mut s, c) = coverage_scenario().await;
+    let program_id = s.program_id;
+    let ledger = Keypair::new();
+    let bump = find_pda(InsuranceFundConfig::seeds(), &program_id).1;
+    let config = InsuranceFundConfig::new(c.insurance_fund, bump, 0, 0, ledger.pubkey(), s.now().await);
+    s.set_account(&c.insurance_config, &program_id, config.try_to_vec().unwrap());
+
+    let report = |s: &FundScenario, caller: &Pubkey, fund: Pubkey, vault: Pubkey, open_interest_e6: i64| {
+        s.instruction(
+            FundInstruction::ReportPlatformOpenInterest(ReportPlatformOpenInterestArgs { open_interest_e6 }),
+            vec![
+                AccountMeta::new_readonly(*caller, true),
+                AccountMeta::new(c.insurance_config, false),
+                AccountMeta::new_readonly(fund, false),
+                AccountMeta::new_readonly(vault, false),
+            ],
+        )
+    };
+    let crank = |s: &FundScenario| {
+        s.instruction(
+            FundInstruction::UpdateInsuranceCoverage,
+            vec![
+                AccountMeta::new(c.insurance_config, false),
+                AccountMeta::new_readonly(c.insurance_fund, false),
+                AccountMeta::new_readonly(c.insurance_vault, false),
+            ],
+        )
+    };
+
+    // Nothing to publish before the Ledger's first report
+    assert_fund_error(s.process(&[crank(&s)], &[]).await, FundError::OpenInterestNotReported);
+
+    let outsider = Keypair::new();
+    let ix = report(&s, &outsider.pubkey(), c.insurance_fund, c.insurance_vault, 10_000 * USDC as i64);
+    assert_fund_error(s.process(&[ix], &[&outsider]).await, FundError::UnauthorizedCaller);
+    let ix = report(&s, &ledger.pubkey(), c.insurance_fund, c.insurance_vault, -1);
+    assert_fund_error(s.process(&[ix], &[&ledger]).await, FundError::InvalidAmount);
+    // Coverage is measured on this insurance fund's own vault only
+    let (fund, vault) = (s.fund, s.fund_vault);
+    let ix = report(&s, &ledger.pubkey(), fund, vault, 10_000 * USDC as i64);
+    assert_fund_error(s.process(&[ix], &[&ledger]).await, FundError::AccountKeyMismatch);
+    let ix = report(&s, &ledger.pubkey(), c.insurance_fund, vault, 10_000 * USDC as i64);
+    assert_fund_error(s.process(&[ix], &[&ledger]).await, FundError::AccountKeyMismatch);
+
+    // 1,000 USDC against 10,000 USDC of open interest
+    let ix = report(&s, &ledger.pubkey(), c.insurance_fund, c.insurance_vault, 10_000 * USDC as i64);
+    s.process(&[ix], &[&ledger]).await.unwrap();
+    let now = s.now().await;
+    assert_eq!(insurance_config_state(&mut s, &c).await.published_coverage_bps(now), Some(1_000));
+
+    // The crank follows the vault balance at the last reported open interest
+    let (vault, mint, fund) = (c.insurance_vault, s.usdc_mint, c.insurance_fund);
+    s.set_token_account(&vault, &mint, &fund, 2_000 * USDC);
+    s.advance_clock(InsuranceFundConfig::COVERAGE_MAX_AGE_SECS + 1).await;
+    s.process(&[crank(&s)], &[]).await.unwrap();
+    let config = insurance_config_state(&mut s, &c).await;
+    assert_eq!(config.coverage_ratio_bps, 2_000);
+    // ...but a stale open interest report keeps it unpublished
+    assert_eq!(config.published_coverage_bps(s.now().await), None);
+}
+
+// === UpdateHourlySnapshot ===
+
+#[tokio::test]
+async fn test_hourly_snapshot_requires_daily_stats_once_enabled() {
+    let (mut s, c) = coverage_scenario().await;
+    let program_id = s.program_id;
+    let relayer = Keypair::new();
+    s.set_account(&relayer.pubkey(), &system_program::id(), Vec::new());
+
+    let snapshot = |s: &FundScenario, daily_stats: Option<Pubkey>| {
+        let mut accounts = vec![
+            AccountMeta::new(relayer.pubkey(), true),
+            AccountMeta::new_readonly(c.insurance_fund, false),
+            AccountMeta::new(c.insurance_config, false),
+            AccountMeta::new_readonly(c.insurance_vault, false),
+        ];
+        if let Some(daily_stats) = daily_stats {
+            accounts.extend([AccountMeta::new(daily_stats, false), AccountMeta::new_readonly(system_program::id(), false)]);
+        }
+        s.instruction(FundInstruction::UpdateHourlySnapshot, accounts)
+    };
+    let day_stats = |now: i64| find_pda(InsuranceDailyStats::seeds(InsuranceDailyStats::day_index(now)), &program_id).0;
+
+    // A config that predates daily stats may still snapshot without them
+    let mut config = InsuranceFundConfig::new(c.insurance_fund, 255, 0, 0, Pubkey::new_unique(), s.now().await);
+    config.daily_baseline_set = false;
+    s.set_account(&c.insurance_config, &program_id, config.try_to_vec().unwrap());
+    s.advance_clock(3_600).await;
+    s.process(&[snapshot(&s, None)], &[&relayer]).await.unwrap();
+
+    // Its first snapshot with the day's stats turns them on for good
+    s.advance_clock(3_600).await;
+    let stats = day_stats(s.now().await);
+    s.process(&[snapshot(&s, Some(stats))], &[&relayer]).await.unwrap();
+    let recorded = InsuranceDailyStats::try_from_slice(&account_data(&mut s, stats).await).unwrap();
+    assert_eq!(recorded.ending_balance_e6, 1_000 * USDC as i64);
+
+    s.advance_clock(3_600).await;
+    assert_fund_error(s.process(&[snapshot(&s, None)], &[&relayer]).await, FundError::InsuranceDailyStatsRequired);
+    let stats = day_stats(s.now().await);
+    s.process(&[snapshot(&s, Some(stats))], &[&relayer]).await.unwrap();
+}
+
+// === CreateFund ===
+
+/// Fund PDAs of a new fund created by `manager` as FundConfig's next fund
+struct NewFund {
+    fund: Pubkey,
+    vault: Pubkey,
+    share_mint: Pubkey,
+    profile: Pubkey,
+}
+
+async fn new_fund_keys(s: &mut FundScenario, manager: &Pubkey) -> NewFund {
+    let index = s.fund_config_state().await.total_funds;
+    let fund = find_pda(Fund::seeds(manager, index), &s.program_id).0;
+    NewFund {
+        fund,
+        vault: find_pda(Fund::vault_seeds(&fund), &s.program_id).0,
+        share_mint: find_pda(Fund::share_mint_seeds(&fund), &s.program_id).0,
+        profile: find_pda(ManagerProfile::seeds(manager), &s.program_id).0,
+    }
+}
+
+fn create_fund_args(initial_deposit_amount: Option<u64>) -> CreateFundArgs {
+    CreateFundArgs {
+        name: "Seeded Fund".to_string(),
+        management_fee_bps: 200,
+        performance_fee_bps: 2000,
+        use_high_water_mark: true,
+        fee_collection_interval: 0,
+        initial_deposit_amount,
+        expense_cap_bps: 0,
+        max_expense_payment_e6: 0,
+    }
+}
+
+/// CreateFund accounts 0-9, then `fee_accounts` (Treasury, Treasury vault, manager's USDC)
+fn create_fund_accounts(s: &FundScenario, manager: &Pubkey, f: &NewFund, fee_accounts: &[Pubkey]) -> Vec<AccountMeta> {
+    let mut accounts = vec![
+        AccountMeta::new(*manager, true),
+        AccountMeta::new(f.fund, false),
+        AccountMeta::new(f.vault, false),
+        AccountMeta::new(f.share_mint, false),
+        AccountMeta::new(s.fund_config, false),
+        AccountMeta::new_readonly(s.usdc_mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(f.profile, false),
+    ];
+    accounts.extend(fee_accounts.iter().map(|key| AccountMeta::new(*key, false)));
+    accounts
+}
+
+/// InitializeTreasury with an empty USDC vault; returns (Treasury PDA, vault)
+async fn init_treasury(s: &mut FundScenario) -> (Pubkey, Pubkey) {
+    let treasury = find_pda(Treasury::seeds(), &s.program_id).0;
+    let vault = s.create_token_account(&s.usdc_mint.clone(), &treasury, 0);
+    let admin = s.admin.insecure_clone();
+    // The admin pays the Treasury rent
+    s.set_account(&admin.pubkey(), &system_program::id(), Vec::new());
+    let ix = s.instruction(
+        FundInstruction::InitializeTreasury,
+        vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(s.fund_config, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    s.process(&[ix], &[&admin]).await.unwrap();
+    (treasury, vault)
+}
+
+#[tokio::test]
+async fn test_create_fund_seed_deposit_while_pending_approval() {
+    let mut s = FundScenarioBuilder::new().depositors(2, 10_000 * USDC).build().await;
+    let mut config = s.fund_config_state().await;
+    config.requires_listing_approval = true;
+    s.write_fund_config(&config);
+
+    // Investor 1 creates a fund and seeds it with 500 USDC
+    let manager = s.investors[1].keypair.insecure_clone();
+    let manager_usdc = s.investors[1].usdc;
+    let f = new_fund_keys(&mut s, &manager.pubkey()).await;
+    let manager_position = find_pda(LPPosition::seeds(&f.fund, &manager.pubkey()), &s.program_id).0;
+    let manager_shares = Keypair::new();
+    let page = find_pda(LPIndexPage::seeds(&f.fund, 0), &s.program_id).0;
+    // No creation fee configured: the treasury slots are not read
+    let placeholders = [s.admin.pubkey(), s.admin.pubkey(), manager_usdc];
+    let mut accounts = create_fund_accounts(&s, &manager.pubkey(), &f, &placeholders);
+    accounts.extend([
+        AccountMeta::new(manager_position, false),
+        AccountMeta::new(manager_shares.pubkey(), true),
+        AccountMeta::new(page, false),
+    ]);
+    let ix = s.instruction(FundInstruction::CreateFund(create_fund_args(Some(500 * USDC))), accounts);
+    s.process(&[ix], &[&manager, &manager_shares]).await.unwrap();
+
+    let fund = s.fund_state_of(f.fund).await;
+    assert_eq!(fund.listing_status, FundListingStatus::PendingApproval);
+    assert_eq!(fund.stats.total_shares, 500 * USDC);
+    assert_eq!(s.token_balance(f.vault).await, 500 * USDC);
+    assert_eq!(s.token_balance(manager_shares.pubkey()).await, 500 * USDC);
+
+    // Other LPs still wait for the listing approval
+    let investor = s.investors[0].keypair.insecure_clone();
+    let investor_usdc = s.investors[0].usdc;
+    let investor_shares = s.create_token_account(&f.share_mint, &investor.pubkey(), 0);
+    let investor_position = find_pda(LPPosition::seeds(&f.fund, &investor.pubkey()), &s.program_id).0;
+    let ix = s.instruction(
+        FundInstruction::DepositToFund(DepositToFundArgs { amount: 100 * USDC, campaign_id: None, memo: None }),
+        vec![
+            AccountMeta::new(investor.pubkey(), true),
+            AccountMeta::new(f.fund, false),
+            AccountMeta::new(f.vault, false),
+            AccountMeta::new(investor_usdc, false),
+            AccountMeta::new(investor_position, false),
+            AccountMeta::new(investor_shares, false),
+            AccountMeta::new(f.share_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(page, false),
+        ],
+    );
+    assert_fund_error(s.process(&[ix], &[&investor]).await, FundError::FundListingNotApproved);
+}
+
+#[tokio::test]
+async fn test_create_fund_fee_routed_to_treasury() {
+    const FEE: u64 = 25 * USDC;
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let (treasury, treasury_vault) = init_treasury(&mut s).await;
+    let mut config = s.fund_config_state().await;
+    config.fund_creation_fee_e6 = FEE;
+    s.write_fund_config(&config);
+
+    let manager = s.investors[0].keypair.insecure_clone();
+    let manager_usdc = s.investors[0].usdc;
+    let f = new_fund_keys(&mut s, &manager.pubkey()).await;
+    let create = |s: &FundScenario, fee_accounts: &[Pubkey]| {
+        let accounts = create_fund_accounts(s, &manager.pubkey(), &f, fee_accounts);
+        s.instruction(FundInstruction::CreateFund(create_fund_args(None)), accounts)
+    };
+
+    // The admin wallet is no longer a valid fee destination
+    let ix = create(&s, &[s.admin.pubkey(), treasury_vault, manager_usdc]);
+    assert_fund_error(s.process(&[ix], &[&manager]).await, FundError::InvalidAccountOwner);
+
+    // Nor is any token account other than the Treasury's vault
+    let other_vault = s.create_token_account(&s.usdc_mint.clone(), &manager.pubkey(), 0);
+    let ix = create(&s, &[treasury, other_vault, manager_usdc]);
+    assert_fund_error(s.process(&[ix], &[&manager]).await, FundError::TreasuryVaultMismatch);
+
+    let ix = create(&s, &[treasury]);
+    assert_eq!(instruction_error(s.process(&[ix], &[&manager]).await), InstructionError::NotEnoughAccountKeys);
+
+    let ix = create(&s, &[treasury, treasury_vault, manager_usdc]);
+    s.process(&[ix], &[&manager]).await.unwrap();
+
+    assert_eq!(s.token_balance(treasury_vault).await, FEE);
+    assert_eq!(s.token_balance(manager_usdc).await, 10_000 * USDC - FEE);
+    let account = s.context.banks_client.get_account(treasury).await.unwrap().unwrap();
+    let state = Treasury::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.total_routed_e6[TreasurySource::FundCreationFee as usize], FEE as i64);
+    let account = s.context.banks_client.get_account(f.profile).await.unwrap().unwrap();
+    assert_eq!(ManagerProfile::try_from_slice(&account.data).unwrap().total_creation_fees_paid, FEE);
+}
+
+// === Admin Intervention ===
+
+fn intervention_accounts(s: &FundScenario, signer: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*signer, true),
+        AccountMeta::new_readonly(s.fund_config, false),
+        AccountMeta::new(s.fund, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]
+}
+
+fn admin_set_paused_ix(s: &FundScenario, signer: &Pubkey, is_paused: bool, lock_manager: bool) -> Instruction {
+    let args = AdminSetFundPausedArgs { is_paused, lock_manager, reason: Some("incident".to_string()) };
+    s.instruction(FundInstruction::AdminSetFundPaused(args), intervention_accounts(s, signer))
+}
+
+fn admin_set_open_ix(s: &FundScenario, signer: &Pubkey, is_open: bool, lock_manager: bool) -> Instruction {
+    let args = AdminSetFundOpenArgs { is_open, lock_manager, reason: None };
+    s.instruction(FundInstruction::AdminSetFundOpen(args), intervention_accounts(s, signer))
+}
+
+fn manager_set_state_ix(s: &FundScenario, state: FundLifecycleState) -> Instruction {
+    s.instruction(
+        FundInstruction::SetFundState(SetFundStateArgs { state, reason: FundStateReason::Unspecified }),
+        vec![
+            AccountMeta::new(s.manager.pubkey(), true),
+            AccountMeta::new(s.fund, false),
+            AccountMeta::new_readonly(s.fund_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn test_admin_pause_overrides_and_locks_manager() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let (admin, manager) = (s.admin.insecure_clone(), s.manager.insecure_clone());
+    s.set_account(&admin.pubkey(), &system_program::id(), Vec::new());
+
+    // The fund's own manager is not the admin
+    let ix = admin_set_paused_ix(&s, &manager.pubkey(), true, true);
+    assert_fund_error(s.process(&[ix], &[&manager]).await, FundError::AdminRequired);
+
+    let ix = admin_set_paused_ix(&s, &admin.pubkey(), true, true);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    let fund = s.fund_state().await;
+    assert!(fund.is_paused && fund.admin_pause_locked);
+    assert_fund_error(s.deposit(0, 1_000 * USDC).await, FundError::FundClosed);
+
+    // The lock keeps the manager from lifting the pause
+    let ix = manager_set_state_ix(&s, FundLifecycleState::Open);
+    assert_fund_error(s.process(&[ix], &[&manager]).await, FundError::FundAdminLocked);
+
+    let ix = admin_set_paused_ix(&s, &admin.pubkey(), false, false);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    assert!(!s.fund_state().await.is_paused);
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_admin_close_deposits_locks_manager() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let (admin, manager) = (s.admin.insecure_clone(), s.manager.insecure_clone());
+    s.set_account(&admin.pubkey(), &system_program::id(), Vec::new());
+
+    let ix = admin_set_open_ix(&s, &manager.pubkey(), false, true);
+    assert_fund_error(s.process(&[ix], &[&manager]).await, FundError::AdminRequired);
+
+    let ix = admin_set_open_ix(&s, &admin.pubkey(), false, true);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    assert!(!s.fund_state().await.is_open);
+    assert_fund_error(s.deposit(0, 1_000 * USDC).await, FundError::FundClosed);
+
+    let ix = manager_set_state_ix(&s, FundLifecycleState::Open);
+    assert_fund_error(s.process(&[ix], &[&manager]).await, FundError::FundAdminLocked);
+
+    // Reopened without the lock, the manager is back in control
+    let ix = admin_set_open_ix(&s, &admin.pubkey(), true, false);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    let ix = manager_set_state_ix(&s, FundLifecycleState::DepositsClosed);
+    s.process(&[ix], &[&manager]).await.unwrap();
+    assert!(!s.fund_state().await.is_open);
+}
+
+// === RedeemAll ===
+
+#[tokio::test]
+async fn test_redeem_all_drops_fund_from_investor_portfolio() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let program_id = s.program_id;
+    let investor = s.investors[0].keypair.pubkey();
+    let (portfolio, bump) = find_pda(InvestorPortfolio::seeds(&investor), &program_id);
+    let state = InvestorPortfolio::new(investor, bump, s.now().await);
+    s.set_account(&portfolio, &program_id, state.try_to_vec().unwrap());
+    let portfolio_state = |data: Vec<u8>| InvestorPortfolio::try_from_slice(&data).unwrap();
+
+    s.deposit_with(0, 1_000 * USDC, vec![AccountMeta::new(portfolio, false)]).await.unwrap();
+    let state = portfolio_state(account_data(&mut s, portfolio).await);
+    assert_eq!((state.fund_count, state.total_invested_e6), (1, 1_000 * USDC as i64));
+    assert!(state.entry(&s.fund).is_some());
+
+    s.redeem_all_with(0, vec![AccountMeta::new(portfolio, false)]).await.unwrap();
+    let state = portfolio_state(account_data(&mut s, portfolio).await);
+    assert_eq!((state.fund_count, state.total_invested_e6, state.total_value_e6), (0, 0, 0));
+    assert!(state.entry(&s.fund).is_none());
+    assert!(s.lp_position(0).await.is_none());
+}
+
+#[tokio::test]
+async fn test_redeem_all_invokes_fund_hook() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    // The mock Ledger accepts any non-relay instruction, so it stands in as the hook
+    let mut fund = s.fund_state().await;
+    fund.hook_program = s.ledger_program;
+    s.write_fund(&fund).await;
+    let (hook_authority, _) = find_pda(Fund::hook_authority_seeds(&s.fund), &s.program_id);
+    let hook_accounts = vec![
+        AccountMeta::new_readonly(s.ledger_program, false),
+        AccountMeta::new_readonly(hook_authority, false),
+    ];
+
+    s.deposit_with(0, 1_000 * USDC, hook_accounts.clone()).await.unwrap();
+
+    // Without the hook accounts the redemption is rejected and nothing moves
+    assert_fund_error(s.redeem_all(0).await, FundError::FundHookAccountsMissing);
+    assert!(s.lp_position(0).await.is_some());
+    let usdc = s.investors[0].usdc;
+    assert_eq!(s.token_balance(usdc).await, 9_000 * USDC);
+
+    s.redeem_all_with(0, hook_accounts).await.unwrap();
+    assert!(s.lp_position(0).await.is_none());
+    assert_eq!(s.token_balance(usdc).await, 10_000 * USDC);
+}
+
+// === AuditFundVault ===
+
+/// Rewrite the scenario vault's owner, delegate and close authority, keeping its balance
+async fn set_vault_authorities(s: &mut FundScenario, owner: Pubkey, delegate: Option<Pubkey>, close_authority: Option<Pubkey>) {
+    let amount = s.token_balance(s.fund_vault).await;
+    let vault = spl_token::state::Account {
+        mint: s.usdc_mint,
+        owner,
+        amount,
+        delegate: delegate.map_or(COption::None, COption::Some),
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: if delegate.is_some() { amount } else { 0 },
+        close_authority: close_authority.map_or(COption::None, COption::Some),
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    vault.pack_into_slice(&mut data);
+    let fund_vault = s.fund_vault;
+    s.set_account(&fund_vault, &spl_token::id(), data);
+}
+
+async fn audit_vault(s: &mut FundScenario) -> Result<(), BanksClientError> {
+    let ix = s.instruction(
+        FundInstruction::AuditFundVault,
+        vec![
+            AccountMeta::new_readonly(s.context.payer.pubkey(), true),
+            AccountMeta::new_readonly(s.fund, false),
+            AccountMeta::new(s.fund_vault, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    );
+    s.process(&[ix], &[]).await
+}
+
+#[tokio::test]
+async fn test_audit_fund_vault_revokes_authorities_that_block_lp_flows() {
+    let mut s = FundScenarioBuilder::new().depositors(2, 10_000 * USDC).build().await;
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    s.deposit(1, 1_000 * USDC).await.unwrap();
+    let fund = s.fund;
+
+    // A delegate could drain the vault: every LP flow stops until it is revoked
+    set_vault_authorities(&mut s, fund, Some(Pubkey::new_unique()), None).await;
+    assert_fund_error(s.deposit(0, 100 * USDC).await, FundError::FundVaultDelegateSet);
+    assert_fund_error(s.redeem(0, 100 * USDC).await, FundError::FundVaultDelegateSet);
+    assert_fund_error(s.redeem_all(1).await, FundError::FundVaultDelegateSet);
+    audit_vault(&mut s).await.unwrap();
+    s.redeem(0, 100 * USDC).await.unwrap();
+
+    // A close authority held by the fund is cleared by the audit
+    set_vault_authorities(&mut s, fund, None, Some(fund)).await;
+    assert_fund_error(s.redeem_all(1).await, FundError::FundVaultCloseAuthoritySet);
+    audit_vault(&mut s).await.unwrap();
+    let vault = s.context.banks_client.get_account(s.fund_vault).await.unwrap().unwrap();
+    let vault = spl_token::state::Account::unpack(&vault.data).unwrap();
+    assert!(vault.delegate.is_none() && vault.close_authority.is_none());
+    s.redeem_all(1).await.unwrap();
+    assert!(s.lp_position(1).await.is_none());
+
+    // Authorities the fund does not hold cannot be cleared, so the audit fails
+    set_vault_authorities(&mut s, fund, None, Some(Pubkey::new_unique())).await;
+    assert_fund_error(audit_vault(&mut s).await, FundError::FundVaultCloseAuthoritySet);
+    set_vault_authorities(&mut s, Pubkey::new_unique(), None, None).await;
+    assert_fund_error(audit_vault(&mut s).await, FundError::FundVaultOwnerMismatch);
+    assert_fund_error(s.deposit(0, 100 * USDC).await, FundError::FundVaultOwnerMismatch);
+}
+
+// === CloseFund ===
+
+#[tokio::test]
+async fn test_close_fund_requires_manager_profile() {
+    let mut s = FundScenarioBuilder::new().depositors(2, 10_000 * USDC).build().await;
+    let manager = s.investors[0].keypair.insecure_clone();
+    let manager_usdc = s.investors[0].usdc;
+    let f = new_fund_keys(&mut s, &manager.pubkey()).await;
+    let accounts = create_fund_accounts(&s, &manager.pubkey(), &f, &[]);
+    let ix = s.instruction(FundInstruction::CreateFund(create_fund_args(None)), accounts);
+    s.process(&[ix], &[&manager]).await.unwrap();
+    let active_funds = s.fund_config_state().await.active_funds;
+
+    let close = |s: &FundScenario, profile: Option<Pubkey>| {
+        let mut accounts = vec![
+            AccountMeta::new(manager.pubkey(), true),
+            AccountMeta::new(f.fund, false),
+            AccountMeta::new(f.vault, false),
+            AccountMeta::new(f.share_mint, false),
+            AccountMeta::new(s.fund_config, false),
+            AccountMeta::new(manager_usdc, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+        accounts.extend(profile.map(|profile| AccountMeta::new(profile, false)));
+        s.instruction(FundInstruction::CloseFund, accounts)
+    };
+
+    // Without the profile the manager's fund slot would never be freed
+    let ix = close(&s, None);
+    assert_eq!(instruction_error(s.process(&[ix], &[&manager]).await), InstructionError::NotEnoughAccountKeys);
+
+    let other_profile = find_pda(ManagerProfile::seeds(&s.investors[1].keypair.pubkey()), &s.program_id).0;
+    let ix = close(&s, Some(other_profile));
+    assert_fund_error(s.process(&[ix], &[&manager]).await, FundError::PDAMismatch);
+
+    let ix = close(&s, Some(f.profile));
+    s.process(&[ix], &[&manager]).await.unwrap();
+
+    assert!(s.fund_state_of(f.fund).await.is_closed());
+    assert_eq!(s.fund_config_state().await.active_funds, active_funds - 1);
+    let account = s.context.banks_client.get_account(f.profile).await.unwrap().unwrap();
+    assert_eq!(ManagerProfile::try_from_slice(&account.data).unwrap().active_funds, 0);
+}
+
+// === ExpireStaleEntries ===
+
+#[tokio::test]
+async fn test_expire_stale_entries_closes_only_stale_intents() {
+    let mut s = FundScenarioBuilder::new().build().await;
+    let program_id = s.program_id;
+    let now = s.now().await;
+    let keeper = Keypair::new();
+    s.set_account(&keeper.pubkey(), &system_program::id(), Vec::new());
+
+    // 0: expired past the grace period, 1: still live, 2: executed past the grace period
+    let mut intents = Vec::new();
+    for id in 0..3u64 {
+        let (key, bump) = find_pda(TradeIntent::seeds(&s.fund, id), &program_id);
+        let expiry = if id == 1 { now + 86_400 } else { now - STALE_ENTRY_GRACE_SECS };
+        let mut intent = TradeIntent::new(s.fund, id, 0, 0, 1_000 * USDC, 50_000 * USDC, 2, expiry, now - 30 * 86_400, bump);
+        if id == 2 {
+            intent.mark_executed(Pubkey::new_unique(), 50_000 * USDC, now - STALE_ENTRY_GRACE_SECS);
+        }
+        s.set_account(&key, &program_id, intent.try_to_vec().unwrap());
+        intents.push(key);
+    }
+    // An intent of another fund
+    let other_fund = Pubkey::new_unique();
+    let (foreign, bump) = find_pda(TradeIntent::seeds(&other_fund, 0), &program_id);
+    let intent = TradeIntent::new(other_fund, 0, 0, 0, USDC, USDC, 1, now - STALE_ENTRY_GRACE_SECS, 0, bump);
+    s.set_account(&foreign, &program_id, intent.try_to_vec().unwrap());
+
+    let expire = |s: &FundScenario, manager: Pubkey, entries: &[Pubkey]| {
+        let mut accounts = vec![
+            AccountMeta::new(keeper.pubkey(), true),
+            AccountMeta::new_readonly(s.fund, false),
+            AccountMeta::new(manager, false),
+        ];
+        accounts.extend(entries.iter().map(|e| AccountMeta::new(*e, false)));
+        s.instruction(FundInstruction::ExpireStaleEntries, accounts)
+    };
+
+    // Refunds go to the fund's manager only
+    let ix = expire(&s, keeper.pubkey(), &intents);
+    assert_fund_error(s.process(&[ix], &[&keeper]).await, FundError::AccountKeyMismatch);
+    let manager = s.manager.pubkey();
+    let ix = expire(&s, manager, &[intents[0], foreign]);
+    assert_fund_error(s.process(&[ix], &[&keeper]).await, FundError::UnsupportedStaleEntry);
+
+    let rent = lamports(&mut s, intents[0]).await;
+    let keeper_before = lamports(&mut s, keeper.pubkey()).await;
+    let manager_before = lamports(&mut s, manager).await;
+    let ix = expire(&s, manager, &intents);
+    s.process(&[ix], &[&keeper]).await.unwrap();
+
+    // Two closed, each tipping the keeper 10% of its rent
+    let tip = rent / 10;
+    assert_eq!(lamports(&mut s, keeper.pubkey()).await - keeper_before, 2 * tip);
+    assert_eq!(lamports(&mut s, manager).await - manager_before, 2 * (rent - tip));
+    assert!(s.context.banks_client.get_account(intents[0]).await.unwrap().is_none());
+    assert!(s.context.banks_client.get_account(intents[1]).await.unwrap().is_some());
+    assert!(s.context.banks_client.get_account(intents[2]).await.unwrap().is_none());
+}
+
+// === ShareCheckpoints ===
+
+#[tokio::test]
+async fn test_share_checkpoints_required_once_initialized() {
+    let mut s = funded_scenario().await;
+    let history = find_pda(ShareCheckpoints::seeds(&s.fund), &s.program_id).0;
+
+    let manager = s.manager.insecure_clone();
+    let ix = s.instruction(
+        FundInstruction::InitializeShareCheckpoints(InitializeShareCheckpointsArgs { threshold_bps: 0 }),
+        vec![
+            AccountMeta::new(manager.pubkey(), true),
+            AccountMeta::new(s.fund, false),
+            AccountMeta::new(history, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    s.process(&[ix], &[&manager]).await.unwrap();
+    assert!(s.fund_state().await.share_checkpoints_enabled);
+
+    // Skipping the PDA would leave the supply change unrecorded
+    assert_fund_error(s.deposit(0, 100 * USDC).await, FundError::ShareCheckpointsRequired);
+
+    s.deposit_with(0, 100 * USDC, vec![AccountMeta::new(history, false)]).await.unwrap();
+    let account = s.context.banks_client.get_account(history).await.unwrap().unwrap();
+    let checkpoints = ShareCheckpoints::try_from_slice(&account.data).unwrap();
+    assert_eq!(checkpoints.checkpoint_count, 2);
+}
+
+// === Relayer ===
+
+/// Scenario with a relayer registered on FundConfig and funded to pay nonce rent
+async fn relayer_scenario() -> (FundScenario, Keypair) {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let relayer = Keypair::new();
+    s.set_account(&relayer.pubkey(), &system_program::id(), Vec::new());
+    let mut fund_config = s.fund_config_state().await;
+    fund_config.add_relayer(relayer.pubkey()).unwrap();
+    fund_config.relayer_limits.single_tx_limit_e6 = 1_000 * USDC as i64;
+    fund_config.relayer_limits.daily_limit_e6 = 1_500 * USDC as i64;
+    s.write_fund_config(&fund_config);
+    (s, relayer)
+}
+
+fn relayer_deposit_ix(s: &FundScenario, relayer: &Pubkey, amount: u64, nonce: u64, stats: Option<Pubkey>) -> Instruction {
+    let investor = &s.investors[0];
+    let user = investor.keypair.pubkey();
+    let mut accounts = vec![
+        AccountMeta::new(*relayer, true),
+        AccountMeta::new(s.fund_config, false),
+        AccountMeta::new(s.fund, false),
+        AccountMeta::new(s.fund_vault, false),
+        AccountMeta::new(investor.usdc, false),
+        AccountMeta::new(investor.lp_position, false),
+        AccountMeta::new(investor.shares, false),
+        AccountMeta::new(s.share_mint, false),
+        AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        AccountMeta::new_readonly(s.vault_program, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_pda(RelayerNonce::seeds(&user), &s.program_id).0, false),
+    ];
+    accounts.extend(stats.map(|stats| AccountMeta::new(stats, false)));
+    let args = RelayerDepositToFundArgs { user_wallet: user, amount, nonce, campaign_id: None, memo: None };
+    s.instruction(FundInstruction::RelayerDepositToFund(args), accounts)
+}
+
+/// RelayerStats PDA of `relayer`, created empty
+fn init_relayer_stats(s: &mut FundScenario, relayer: &Pubkey) -> Pubkey {
+    let program_id = s.program_id;
+    let (key, bump) = find_pda(RelayerStats::seeds(relayer), &program_id);
+    s.set_account(&key, &program_id, RelayerStats::new(*relayer, bump).try_to_vec().unwrap());
+    key
+}
+
+async fn relayer_stats_state(s: &mut FundScenario, key: Pubkey) -> RelayerStats {
+    RelayerStats::try_from_slice(&account_data(s, key).await).unwrap()
+}
+
+#[tokio::test]
+async fn test_relayer_deposit_requires_registered_relayer() {
+    let (mut s, relayer) = relayer_scenario().await;
+    let outsider = Keypair::new();
+    s.set_account(&outsider.pubkey(), &system_program::id(), Vec::new());
+    let ix = relayer_deposit_ix(&s, &outsider.pubkey(), 100 * USDC, 1, None);
+    assert_fund_error(s.process(&[ix], &[&outsider]).await, FundError::Unauthorized);
+
+    let mut ix = relayer_deposit_ix(&s, &relayer.pubkey(), 100 * USDC, 1, None);
+    ix.accounts[0].is_signer = false;
+    assert_eq!(instruction_error(s.process(&[ix], &[]).await), InstructionError::MissingRequiredSignature);
+
+    let ix = relayer_deposit_ix(&s, &relayer.pubkey(), 100 * USDC, 1, None);
+    s.process(&[ix], &[&relayer]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_relayer_deposit_limits_counted_in_relayer_stats() {
+    let (mut s, relayer) = relayer_scenario().await;
+
+    // Over the single-transaction limit without RelayerStats: a plain failure
+    let ix = relayer_deposit_ix(&s, &relayer.pubkey(), 2_000 * USDC, 1, None);
+    assert_fund_error(s.process(&[ix], &[&relayer]).await, FundError::RelayerLimitExceeded);
+
+    // With RelayerStats the rejection is recorded and the request ends without effect
+    let stats = init_relayer_stats(&mut s, &relayer.pubkey());
+    let ix = relayer_deposit_ix(&s, &relayer.pubkey(), 2_000 * USDC, 1, Some(stats));
+    s.process(&[ix], &[&relayer]).await.unwrap();
+    let recorded = relayer_stats_state(&mut s, stats).await;
+    assert_eq!((recorded.rejected_over_limit_count, recorded.tx_count), (1, 0));
+    assert_eq!(s.fund_config_state().await.relayer_limits.daily_used_e6, 0);
+    let nonce = find_pda(RelayerNonce::seeds(&s.investors[0].keypair.pubkey()), &s.program_id).0;
+    assert!(s.context.banks_client.get_account(nonce).await.unwrap().is_none());
+
+    let ix = relayer_deposit_ix(&s, &relayer.pubkey(), 1_000 * USDC, 1, Some(stats));
+    s.process(&[ix], &[&relayer]).await.unwrap();
+    assert_eq!(s.fund_config_state().await.relayer_limits.daily_used_e6, 1_000 * USDC as i64);
+
+    // 1,000 + 600 is over the 1,500 daily limit
+    let ix = relayer_deposit_ix(&s, &relayer.pubkey(), 600 * USDC, 2, Some(stats));
+    s.process(&[ix], &[&relayer]).await.unwrap();
+    let recorded = relayer_stats_state(&mut s, stats).await;
+    assert_eq!((recorded.rejected_over_limit_count, recorded.tx_count), (2, 1));
+    assert_eq!(recorded.total_volume_e6, 1_000 * USDC);
+}
+
+#[tokio::test]
+async fn test_relayer_nonce_shared_across_relayer_instructions() {
+    let (mut s, relayer) = relayer_scenario().await;
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    let user = s.investors[0].keypair.pubkey();
+    let nonce_account = find_pda(RelayerNonce::seeds(&user), &s.program_id).0;
+
+    let redeem = |s: &FundScenario, nonce: u64| {
+        s.instruction(
+            FundInstruction::RelayerRedeemFromFund(RelayerRedeemFromFundArgs { user_wallet: user, shares: USDC, nonce, memo: None }),
+            vec![
+                AccountMeta::new(relayer.pubkey(), true),
+                AccountMeta::new_readonly(s.fund_config, false),
+                AccountMeta::new(nonce_account, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(s.investors[0].lp_position, false),
+                AccountMeta::new_readonly(s.investors[0].usdc, false),
+            ],
+        )
+    };
+
+    let ix = relayer_deposit_ix(&s, &relayer.pubkey(), 100 * USDC, 5, None);
+    s.process(&[ix], &[&relayer]).await.unwrap();
+
+    // A redeem cannot reuse the deposit's nonce, nor arrive late with a lower one
+    assert_fund_error(s.process(&[redeem(&s, 5)], &[&relayer]).await, FundError::RelayerNonceReplay);
+    assert_fund_error(s.process(&[redeem(&s, 3)], &[&relayer]).await, FundError::RelayerNonceReplay);
+    s.process(&[redeem(&s, 6)], &[&relayer]).await.unwrap();
+
+    let ix = relayer_deposit_ix(&s, &relayer.pubkey(), 100 * USDC, 6, None);
+    assert_fund_error(s.process(&[ix], &[&relayer]).await, FundError::RelayerNonceReplay);
+    let state = RelayerNonce::try_from_slice(&account_data(&mut s, nonce_account).await).unwrap();
+    assert_eq!(state.last_nonce, 6);
+}
+
+// === ClaimReferralRewards / SettleReferralRewardsBatch ===
+
+/// ReferralConfig PDA and its reward vault holding `vault_amount`
+async fn init_referral_rewards(s: &mut FundScenario, vault_amount: u64) -> (Pubkey, Pubkey) {
+    let program_id = s.program_id;
+    let (referral_config, bump) = find_pda(ReferralConfig::seeds(), &program_id);
+    let config = ReferralConfig::new(s.admin.pubkey(), s.vault_program, 3000, 1000, bump, 0);
+    assert_eq!(config.min_settlement_amount_e6, 10 * USDC as i64);
+    s.set_account(&referral_config, &program_id, config.try_to_vec().unwrap());
+    let reward_vault = find_pda(ReferralConfig::reward_vault_seeds(), &program_id).0;
+    let usdc_mint = s.usdc_mint;
+    s.set_token_account(&reward_vault, &usdc_mint, &referral_config, vault_amount);
+    (referral_config, reward_vault)
+}
+
+/// Write `referrer`'s ReferralLink with `unclaimed` USDC of funded rewards
+fn set_referral_link(s: &mut FundScenario, referrer: &Pubkey, code: &str, unclaimed: u64) -> Pubkey {
+    let program_id = s.program_id;
+    let (link_key, link_bump) = find_pda(ReferralLink::seeds(referrer), &program_id);
+    let mut link = ReferralLink::new(*referrer, code.as_bytes(), link_bump, 0);
+    link.unclaimed_rewards_e6 = unclaimed as i64;
+    s.set_account(&link_key, &program_id, link.try_to_vec().unwrap());
+    link_key
+}
+
+async fn referral_link_state(s: &mut FundScenario, link: Pubkey) -> ReferralLink {
+    let account = s.context.banks_client.get_account(link).await.unwrap().unwrap();
+    ReferralLink::try_from_slice(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_claim_referral_rewards_as_usdc_or_fund_shares() {
+    let mut s = FundScenarioBuilder::new().depositors(2, 1_000 * USDC).build().await;
+    let (referral_config, reward_vault) = init_referral_rewards(&mut s, 100 * USDC).await;
+    let referrer = s.investors[0].keypair.insecure_clone();
+    let link = set_referral_link(&mut s, &referrer.pubkey(), "CLAIM0", 40 * USDC);
+
+    let claim_ix = |s: &FundScenario, signer: &Pubkey, into_fund_shares: bool| {
+        let investor = &s.investors[0];
+        let mut accounts = vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new(referral_config, false),
+            AccountMeta::new(link, false),
+            AccountMeta::new(reward_vault, false),
+            AccountMeta::new(investor.usdc, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+        if into_fund_shares {
+            accounts.extend([
+                AccountMeta::new(s.fund, false),
+                AccountMeta::new(s.fund_vault, false),
+                AccountMeta::new(investor.lp_position, false),
+                AccountMeta::new(investor.shares, false),
+                AccountMeta::new(s.share_mint, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(find_pda(LPIndexPage::seeds(&s.fund, 0), &s.program_id).0, false),
+            ]);
+        }
+        s.instruction(FundInstruction::ClaimReferralRewards(ClaimReferralRewardsArgs { into_fund_shares }), accounts)
+    };
+
+    // The link is derived from the signer, so another wallet cannot claim it
+    let outsider = s.investors[1].keypair.insecure_clone();
+    let ix = claim_ix(&s, &outsider.pubkey(), false);
+    assert_fund_error(s.process(&[ix], &[&outsider]).await, FundError::PDAMismatch);
+
+    let usdc_before = s.token_balance(s.investors[0].usdc).await;
+    let ix = claim_ix(&s, &referrer.pubkey(), false);
+    s.process(&[ix], &[&referrer]).await.unwrap();
+    assert_eq!(s.token_balance(s.investors[0].usdc).await - usdc_before, 40 * USDC);
+    assert_eq!(s.token_balance(reward_vault).await, 60 * USDC);
+    assert_eq!(referral_link_state(&mut s, link).await.unclaimed_rewards_e6, 0);
+
+    let ix = claim_ix(&s, &referrer.pubkey(), false);
+    assert_fund_error(s.process(&[ix], &[&referrer]).await, FundError::NoReferralRewardsToClaim);
+
+    // Paying out in shares without the DepositToFund accounts fails as a whole
+    set_referral_link(&mut s, &referrer.pubkey(), "CLAIM0", 30 * USDC);
+    let mut ix = claim_ix(&s, &referrer.pubkey(), true);
+    ix.accounts.truncate(8);
+    assert_eq!(instruction_error(s.process(&[ix], &[&referrer]).await), InstructionError::NotEnoughAccountKeys);
+    assert_eq!(referral_link_state(&mut s, link).await.unclaimed_rewards_e6, 30 * USDC as i64);
+
+    // The claimed USDC passes through the referrer's account into the fund
+    let usdc_before = s.token_balance(s.investors[0].usdc).await;
+    let vault_before = s.token_balance(s.fund_vault).await;
+    let ix = claim_ix(&s, &referrer.pubkey(), true);
+    s.process(&[ix], &[&referrer]).await.unwrap();
+    assert_eq!(s.token_balance(s.investors[0].usdc).await, usdc_before);
+    assert_eq!(s.token_balance(s.fund_vault).await - vault_before, 30 * USDC);
+    assert_eq!(s.token_balance(reward_vault).await, 30 * USDC);
+    let position = s.lp_position(0).await.unwrap();
+    assert!(position.shares > 0);
+    assert_eq!(s.token_balance(s.investors[0].shares).await, position.shares);
+    assert_eq!(referral_link_state(&mut s, link).await.unclaimed_rewards_e6, 0);
+}
+
+/// SettleReferralRewardsBatch over (ReferralLink, payout account) pairs
+fn settle_referral_batch_ix(
+    s: &FundScenario,
+    relayer: &Pubkey,
+    referral_config: Pubkey,
+    reward_vault: Pubkey,
+    items: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*relayer, true),
+        AccountMeta::new_readonly(s.fund_config, false),
+        AccountMeta::new(referral_config, false),
+        AccountMeta::new(reward_vault, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    for (link, payout) in items {
+        accounts.extend([AccountMeta::new(*link, false), AccountMeta::new(*payout, false)]);
+    }
+    s.instruction(FundInstruction::SettleReferralRewardsBatch, accounts)
+}
+
+#[tokio::test]
+async fn test_relayer_system_pause_stops_relayer_instructions_only() {
+    let mut s = FundScenarioBuilder::new().depositors(1, 10_000 * USDC).build().await;
+    let relayer = Keypair::new();
+    let mut fund_config = s.fund_config_state().await;
+    fund_config.add_relayer(relayer.pubkey()).unwrap();
+    s.write_fund_config(&fund_config);
+    let (referral_config, reward_vault) = init_referral_rewards(&mut s, 100 * USDC).await;
+    let referrer = Pubkey::new_unique();
+    let link = set_referral_link(&mut s, &referrer, "PAUSED", 20 * USDC);
+    let items = [(link, s.create_usdc_account(&referrer, 0))];
+
+    let set_paused = |s: &FundScenario, authority: &Pubkey, is_paused: bool| {
+        s.instruction(
+            FundInstruction::SetRelayerSystemPaused(SetRelayerSystemPausedArgs { is_paused }),
+            vec![AccountMeta::new_readonly(*authority, true), AccountMeta::new(s.fund_config, false)],
+        )
+    };
+    let admin = s.admin.insecure_clone();
+
+    let ix = set_paused(&s, &relayer.pubkey(), true);
+    assert_fund_error(s.process(&[ix], &[&relayer]).await, FundError::AdminRequired);
+    let ix = set_paused(&s, &admin.pubkey(), true);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    assert!(s.fund_config_state().await.relayer_paused);
+
+    let ix = settle_referral_batch_ix(&s, &relayer.pubkey(), referral_config, reward_vault, &items);
+    assert_fund_error(s.process(&[ix], &[&relayer]).await, FundError::RelayerSystemPaused);
+    // Direct user flows are unaffected
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+
+    let ix = set_paused(&s, &admin.pubkey(), false);
+    s.process(&[ix], &[&admin]).await.unwrap();
+    let ix = settle_referral_batch_ix(&s, &relayer.pubkey(), referral_config, reward_vault, &items);
+    s.process(&[ix], &[&relayer]).await.unwrap();
+    assert_eq!(s.token_balance(items[0].1).await, 20 * USDC);
+}
+
+#[tokio::test]
+async fn test_settle_referral_rewards_batch_per_item_results() {
+    let mut s = FundScenarioBuilder::new().build().await;
+    let relayer = Keypair::new();
+    let mut fund_config = s.fund_config_state().await;
+    fund_config.add_relayer(relayer.pubkey()).unwrap();
+    s.write_fund_config(&fund_config);
+    let (referral_config, reward_vault) = init_referral_rewards(&mut s, 100 * USDC).await;
+
+    // 0: settled, 1: below the minimum, 2: payout account of someone else, 3: settled
+    let mut items = Vec::new();
+    for (i, unclaimed) in [25, 5, 15, 20].into_iter().enumerate() {
+        let referrer = Pubkey::new_unique();
+        let link_key = set_referral_link(&mut s, &referrer, &format!("CODE{i}"), unclaimed * USDC);
+        let payout_owner = if i == 2 { Pubkey::new_unique() } else { referrer };
+        items.push((link_key, s.create_usdc_account(&payout_owner, 0)));
+    }
+
+    let settle = |s: &FundScenario, relayer: &Pubkey| settle_referral_batch_ix(s, relayer, referral_config, reward_vault, &items);
+
+    let outsider = Keypair::new();
+    let ix = settle(&s, &outsider.pubkey());
+    assert_fund_error(s.process(&[ix], &[&outsider]).await, FundError::Unauthorized);
+
+    let ix = settle(&s, &relayer.pubkey());
+    let data = s.process_with_return_data(&[ix], &[&relayer]).await.unwrap();
+    let result = decode_return_data::<SettleReferralRewardsBatchResult>(&data).unwrap();
+    assert_eq!(
+        result,
+        SettleReferralRewardsBatchResult { processed: 4, settled_bitmap: 0b1001, total_settled_e6: 45 * USDC as i64 }
+    );
+
+    assert_eq!(s.token_balance(reward_vault).await, 55 * USDC);
+    assert_eq!(s.token_balance(items[0].1).await, 25 * USDC);
+    assert_eq!(s.token_balance(items[2].1).await, 0);
+    assert_eq!(s.token_balance(items[3].1).await, 20 * USDC);
+    for ((link, _), unclaimed) in items.iter().zip([0, 5, 15, 0]) {
+        assert_eq!(referral_link_state(&mut s, *link).await.unclaimed_rewards_e6, unclaimed * USDC as i64);
+    }
+    let account = s.context.banks_client.get_account(referral_config).await.unwrap().unwrap();
+    assert_eq!(ReferralConfig::try_from_slice(&account.data).unwrap().total_rewards_claimed_e6, 45 * USDC as i64);
+}
@@ -0,0 +1,85 @@
+//! End-to-end LP scenarios on the `test-utils` builders
+//!
+//! ```text
+//! cargo test --features test-utils --test scenarios
+//! ```
+
+#![cfg(feature = "test-utils")]
+
+use fund_program::test_utils::{FundScenarioBuilder, DEFAULT_START_TS};
+use solana_program::pubkey::Pubkey;
+
+const USDC: u64 = 1_000_000;
+
+#[tokio::test]
+async fn test_deposit_pnl_and_fee_collection() {
+    let mut s = FundScenarioBuilder::new().depositors(2, 10_000 * USDC).build().await;
+    assert_eq!(s.now().await, DEFAULT_START_TS);
+
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    s.deposit(1, 3_000 * USDC).await.unwrap();
+
+    let fund = s.fund_state().await;
+    assert_eq!(fund.stats.lp_count, 2);
+    assert_eq!(fund.stats.total_shares, 4_000 * USDC);
+    assert_eq!(s.lp_position(1).await.unwrap().shares, 3_000 * USDC);
+    assert_eq!(s.token_balance(s.fund_vault).await, 4_000 * USDC);
+
+    // +400 after a day, -100 after another
+    s.apply_pnl_sequence(&[(86_400, 400 * USDC as i64), (86_400, -100 * USDC as i64)])
+        .await
+        .unwrap();
+    assert_eq!(s.now().await, DEFAULT_START_TS + 2 * 86_400);
+    let fund = s.fund_state().await;
+    assert_eq!(fund.stats.total_realized_pnl_e6, 300 * USDC as i64);
+    assert!(fund.stats.current_nav_e6 > 1_000_000);
+
+    s.collect_fees().await.unwrap();
+    let collected = s.token_balance(s.manager_usdc).await;
+    assert!(collected > 0);
+    let fund = s.fund_state().await;
+    assert_eq!(
+        (fund.stats.total_management_fee_e6 + fund.stats.total_performance_fee_e6) as u64,
+        collected
+    );
+
+    // LP exits half at the post-fee NAV
+    let usdc_before = s.token_balance(s.investors[0].usdc).await;
+    s.redeem(0, 500 * USDC).await.unwrap();
+    assert!(s.token_balance(s.investors[0].usdc).await > usdc_before);
+    assert_eq!(s.lp_position(0).await.unwrap().shares, 500 * USDC);
+}
+
+#[tokio::test]
+async fn test_builder_options_and_fee_free_round_trip() {
+    let program_id = Pubkey::new_unique();
+    let start_ts = DEFAULT_START_TS + 30 * 86_400;
+    let mut s = FundScenarioBuilder::new()
+        .program_id(program_id)
+        .fund_name("No Fee Fund")
+        .fees(0, 0)
+        .start_ts(start_ts)
+        .depositors(1, 2_000 * USDC)
+        .build()
+        .await;
+    assert_eq!(s.program_id, program_id);
+    assert_eq!(s.now().await, start_ts);
+
+    let fund = s.fund_state().await;
+    assert_eq!(fund.name_str(), "No Fee Fund");
+    assert_eq!((fund.fee_config.management_fee_bps, fund.fee_config.performance_fee_bps), (0, 0));
+    assert_eq!(s.fund_config_state().await.ledger_program, s.ledger_program);
+
+    // Without fees or PnL the LP leaves with exactly what it put in
+    s.deposit(0, 1_000 * USDC).await.unwrap();
+    s.advance_clock(86_400).await;
+    s.redeem(0, 1_000 * USDC).await.unwrap();
+    assert_eq!(s.token_balance(s.investors[0].usdc).await, 2_000 * USDC);
+    assert_eq!(s.token_balance(s.fund_vault).await, 0);
+    let fund = s.fund_state().await;
+    assert_eq!((fund.stats.total_shares, fund.stats.lp_count), (0, 0));
+    // The emptied position stays open but leaves the LP index
+    let position = s.lp_position(0).await.unwrap();
+    assert_eq!(position.shares, 0);
+    assert!(!position.is_indexed);
+}